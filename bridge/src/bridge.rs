@@ -29,7 +29,8 @@ use tokio::sync::{broadcast, mpsc, Notify};
 pub use player::{BoxError, PreparedRequest, RequestKind};
 
 use player::{
-    AbrStrategy, LicenseResolver, Player, PlayerEvent, RequestInterceptor, Tracks,
+    AbrStrategy, LicenseResolver, LoudnessParams, Player, PlayerEvent, RequestInterceptor,
+    SubtitleFormat, Tracks,
 };
 
 /// Implemented by each platform shell. The bridge core calls these to (a) push
@@ -95,6 +96,14 @@ enum Cmd {
     Audio { adapt: usize, repr: usize },
     Subtitle { adapt: usize, repr: usize },
     ClearSubs,
+    SecondarySubtitle { adapt: usize, repr: usize },
+    ClearSecondarySubs,
+    ExternalSubtitle {
+        url: String,
+        lang: String,
+        label: String,
+        format: SubtitleFormat,
+    },
 }
 
 /// Pre-`play()` configuration for [`start`]. `Default` reproduces the
@@ -216,10 +225,58 @@ impl BridgeHandle {
         self.player
             .seek(Duration::from_millis(position_ms.max(0) as u64));
     }
+    /// Step `n` frames (negative = back) while paused; each landing emits a
+    /// `frame_stepped` event with the exact position.
+    pub fn step_frame(&self, n: i32) {
+        self.player.step_frame(n);
+    }
+    /// Keyframe-only fast-forward / rewind at `speed` × (±2..=32, clamped
+    /// by the player; 0 = back to normal playback). Confirmed by a
+    /// `trick_play_changed` event.
+    pub fn set_trick_play(&self, speed: i32) {
+        self.player.set_trick_play(speed);
+    }
+    pub fn trick_play(&self) -> i32 {
+        self.player.trick_play()
+    }
     /// Absolute volume, 0.0..=1.0.
     pub fn set_volume(&self, volume: f32) {
         self.player.set_volume(volume);
     }
+    /// Playback speed, 0.5..=2.0 (clamped by the player), pitch-preserving.
+    /// Confirmed by a `playback_rate_changed` event.
+    pub fn set_playback_rate(&self, rate: f32) {
+        self.player.set_playback_rate(rate);
+    }
+    pub fn playback_rate(&self) -> f32 {
+        self.player.playback_rate()
+    }
+    /// Lip-sync offset in ms (+ = video later than audio), ±2000, live. Not
+    /// persisted — the shells keep it per output device.
+    pub fn set_av_offset_ms(&self, offset_ms: i32) {
+        self.player.set_av_offset_ms(offset_ms);
+    }
+    pub fn av_offset_ms(&self) -> i32 {
+        self.player.av_offset_ms()
+    }
+    /// Detach (`false`) / re-attach video for audio-only background
+    /// playback; re-attaching resumes the picture at the current position.
+    pub fn set_video_enabled(&self, enabled: bool) {
+        self.player.set_video_enabled(enabled);
+    }
+    pub fn video_enabled(&self) -> bool {
+        self.player.video_enabled()
+    }
+    /// Loudness normalisation to `target_lufs` (e.g. -23 EBU R128, -16
+    /// mobile) and night-mode compression; both off by default. Live.
+    pub fn set_loudness(&self, normalize: bool, target_lufs: f32, night_mode: bool) {
+        self.player.set_loudness(LoudnessParams {
+            normalize,
+            target_lufs,
+            night_mode,
+            ..self.player.loudness()
+        });
+    }
     pub fn position_ms(&self) -> i64 {
         self.player.position().as_millis() as i64
     }
@@ -257,6 +314,46 @@ impl BridgeHandle {
     pub fn clear_subtitles(&self) {
         let _ = self.cmd_tx.send(Cmd::ClearSubs);
     }
+    /// Dual subtitles: a second text track (same `text` indices) drawn at
+    /// the top of the picture alongside the primary one.
+    pub fn set_secondary_subtitle_track(&self, adapt: usize, repr: usize) {
+        let _ = self.cmd_tx.send(Cmd::SecondarySubtitle { adapt, repr });
+    }
+    pub fn clear_secondary_subtitles(&self) {
+        let _ = self.cmd_tx.send(Cmd::ClearSecondarySubs);
+    }
+    /// Add a sidecar subtitle file (`Player::add_external_subtitle`). It is
+    /// appended to the `text` array of [`tracks_json`](Self::tracks_json)
+    /// and announced with `text_tracks_changed`; a failed download or parse
+    /// is reported as `external_subtitle_failed` (non-fatal).
+    pub fn add_external_subtitle(
+        &self,
+        url: String,
+        lang: String,
+        label: String,
+        format: SubtitleFormat,
+    ) {
+        let _ = self.cmd_tx.send(Cmd::ExternalSubtitle {
+            url,
+            lang,
+            label,
+            format,
+        });
+    }
+    /// Subtitle timing offset in ms (+ = cues later), ±600000, live, both
+    /// subtitle tracks.
+    pub fn set_subtitle_offset_ms(&self, offset_ms: i64) {
+        self.player.set_subtitle_offset_ms(offset_ms);
+    }
+    pub fn subtitle_offset_ms(&self) -> i64 {
+        self.player.subtitle_offset_ms()
+    }
+    /// Host-rendered subtitles: `true` turns the built-in overlay off and
+    /// delivers cues as `subtitle_cue` / `subtitle_cue_cleared` events for
+    /// the shell to draw natively. Default off.
+    pub fn set_host_subtitles(&self, enabled: bool) {
+        self.player.set_host_subtitles(enabled);
+    }
 
     /// Forward a surface size change to the renderer.
     pub fn resize(&self, width: u32, height: u32) {
@@ -295,7 +392,8 @@ async fn orchestrate(
         host.on_event(error_json("other", &format!("prepare: {e}")));
         return;
     }
-    let tracks = match player.get_tracks() {
+    let mut events = player.events();
+    let mut tracks = match player.get_tracks() {
         Ok(t) => t,
         Err(e) => {
             host.on_event(error_json("other", &format!("get_tracks: {e}")));
@@ -347,13 +445,28 @@ async fn orchestrate(
         let _ = handle.await;
     });
 
+    let mut events_open = true;
     loop {
         tokio::select! {
             _ = shutdown.notified() => break,
             cmd = cmd_rx.recv() => match cmd {
                 None => break,
-                Some(c) => apply_cmd(&player, &tracks, c),
+                Some(c) => apply_cmd(&player, &tracks, &host, c),
             },
+            // Closed-caption and external tracks join the list mid-playback:
+            // refresh the snapshot commands index into, then tell the host
+            // to re-read.
+            changed = text_tracks_changed(&mut events), if events_open => {
+                if !changed {
+                    events_open = false;
+                    continue;
+                }
+                if let Ok(t) = player.get_tracks() {
+                    tracks = t;
+                }
+                *tracks_json.lock().unwrap() = tracks_to_json(&tracks);
+                host.on_event(obj("text_tracks_changed"));
+            }
         }
     }
 
@@ -362,7 +475,23 @@ async fn orchestrate(
     let _ = (&mut play_task).await;
 }
 
-fn apply_cmd(player: &Player, tracks: &Tracks, cmd: Cmd) {
+/// Wait until the text track list may have changed: a `TextTracksChanged`,
+/// or a lag that could have swallowed one. Every other event is skipped.
+/// `false` once the player's event channel has closed. Cancel-safe, like
+/// the `recv` it loops over.
+async fn text_tracks_changed(events: &mut broadcast::Receiver<PlayerEvent>) -> bool {
+    loop {
+        match events.recv().await {
+            Ok(PlayerEvent::TextTracksChanged) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                return true
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Closed) => return false,
+        }
+    }
+}
+
+fn apply_cmd(player: &Player, tracks: &Tracks, host: &Arc<dyn BridgeHost>, cmd: Cmd) {
     match cmd {
         Cmd::Video { adapt, repr, soft } => {
             if let Some(r) = tracks
@@ -403,9 +532,55 @@ fn apply_cmd(player: &Player, tracks: &Tracks, cmd: Cmd) {
             }
         }
         Cmd::ClearSubs => player.clear_subtitle_track(),
+        Cmd::SecondarySubtitle { adapt, repr } => {
+            if let Some(r) = tracks
+                .text
+                .get(adapt)
+                .and_then(|a| a.representations.get(repr))
+            {
+                player.set_secondary_subtitle_track(r);
+            } else {
+                log::warn!("set_secondary_subtitle_track: no rep at adapt={adapt} repr={repr}");
+            }
+        }
+        Cmd::ClearSecondarySubs => player.clear_secondary_subtitle_track(),
+        Cmd::ExternalSubtitle {
+            url,
+            lang,
+            label,
+            format,
+        } => spawn_external_subtitle(player.clone(), host.clone(), url, lang, label, format),
     }
 }
 
+/// Fetch and register an external subtitle off the command loop. Success
+/// surfaces through `TextTracksChanged` like a caption track; a failure
+/// leaves playback alone, so it gets its own event rather than `error`.
+fn spawn_external_subtitle(
+    player: Player,
+    host: Arc<dyn BridgeHost>,
+    url: String,
+    lang: String,
+    label: String,
+    format: SubtitleFormat,
+) {
+    tokio::spawn(async move {
+        // Stringify the (non-Send) error before the task returns.
+        let result = player
+            .add_external_subtitle(&url, &lang, &label, format)
+            .await
+            .map_err(|e| e.to_string());
+        if let Err(detail) = result {
+            log::warn!("add_external_subtitle: {detail}");
+            host.on_event(format!(
+                r#"{{"type":"external_subtitle_failed","url":{},"detail":{}}}"#,
+                jstr(&url),
+                jstr(&detail)
+            ));
+        }
+    });
+}
+
 fn spawn_event_pump(
     mut rx: broadcast::Receiver<PlayerEvent>,
     host: Arc<dyn BridgeHost>,
@@ -436,6 +611,9 @@ fn spawn_event_pump(
                                 w, h
                             ));
                         }
+                        // The orchestrator reports it once `tracks_json` is
+                        // refreshed.
+                        PlayerEvent::TextTracksChanged => continue,
                         _ => {}
                     }
                     host.on_event(event_to_json(&ev));
@@ -452,10 +630,15 @@ fn spawn_event_pump(
 /// Serialize one [`PlayerEvent`] to the unified event JSON. Schema:
 /// `{"type": "...", <fields>}` where `type` is one of `idle`,
 /// `manifest_loaded`, `prepared`, `buffering`, `playing`, `paused`,
-/// `position`, `track_changed`, `glitch_recovered`, `stats`, `end_of_stream`,
-/// `error`. (The pump additionally synthesizes a `video_size` event —
+/// `playback_rate_changed`, `position`, `track_changed`, `glitch_recovered`,
+/// `frame_stepped`, `trick_play_changed`, `audio_device_changed`, `stats`,
+/// `subtitle_cue`, `subtitle_cue_cleared`, `text_tracks_changed`,
+/// `end_of_stream`, `error`. (The pump additionally synthesizes a `video_size` event —
 /// `{"type":"video_size","width","height"}` — when the rendered resolution
-/// first appears / changes; it is not produced here.)
+/// first appears / changes; it is not produced here. `text_tracks_changed`
+/// reaches the host from the orchestrator, once `tracks_json` is refreshed, and
+/// a failed [`BridgeHandle::add_external_subtitle`] reports
+/// `{"type":"external_subtitle_failed","url","detail"}`.)
 pub fn event_to_json(ev: &PlayerEvent) -> String {
     match ev {
         PlayerEvent::Idle => obj("idle"),
@@ -478,6 +661,9 @@ pub fn event_to_json(ev: &PlayerEvent) -> String {
         ),
         PlayerEvent::Playing => obj("playing"),
         PlayerEvent::Paused => obj("paused"),
+        PlayerEvent::PlaybackRateChanged { rate } => {
+            format!(r#"{{"type":"playback_rate_changed","rate":{:.3}}}"#, rate)
+        }
         PlayerEvent::Position {
             position,
             duration,
@@ -499,6 +685,28 @@ pub fn event_to_json(ev: &PlayerEvent) -> String {
         PlayerEvent::GlitchRecovered { detail } => {
             format!(r#"{{"type":"glitch_recovered","detail":{}}}"#, jstr(detail))
         }
+        PlayerEvent::FrameStepped { position } => format!(
+            r#"{{"type":"frame_stepped","position_ms":{},"position_us":{}}}"#,
+            position.as_millis(),
+            position.as_micros()
+        ),
+        PlayerEvent::TrickPlayChanged { speed } => {
+            format!(r#"{{"type":"trick_play_changed","speed":{}}}"#, speed)
+        }
+        PlayerEvent::AudioDeviceChanged {
+            device,
+            sample_rate,
+            channels,
+        } => {
+            let (id, name) = device
+                .as_ref()
+                .map(|d| (jstr(&d.id), jstr(&d.name)))
+                .unwrap_or_else(|| ("null".into(), "null".into()));
+            format!(
+                r#"{{"type":"audio_device_changed","id":{},"name":{},"sample_rate":{},"channels":{}}}"#,
+                id, name, sample_rate, channels
+            )
+        }
         PlayerEvent::Stats {
             video_frames_decoded,
             video_frames_dropped,
@@ -516,11 +724,23 @@ pub fn event_to_json(ev: &PlayerEvent) -> String {
             judder_frames,
             interval_hist,
             bandwidth_bps,
+            audio_loudness_lufs,
+            audio_peak_db_channels,
             ..
         } => {
             let (w, h) = current_resolution.unwrap_or((0, 0));
+            let loudness = audio_loudness_lufs
+                .map(|l| format!("{:.1}", l))
+                .unwrap_or_else(|| "null".into());
+            let peaks = audio_peak_db_channels
+                .as_ref()
+                .map(|p| {
+                    let db: Vec<String> = p.iter().map(|v| format!("{:.1}", v)).collect();
+                    format!("[{}]", db.join(","))
+                })
+                .unwrap_or_else(|| "null".into());
             format!(
-                r#"{{"type":"stats","frames_decoded":{},"frames_dropped":{},"audio_underruns":{},"net_stall_ms":{},"decoder":{},"width":{},"height":{},"av_drift_ms":{},"video_buffer_ahead_ms":{},"audio_buffer_ahead_ms":{},"video_segment":{},"stall_events":{},"pipeline_retries":{},"render_gap_max_ms":{},"judder_frames":{},"int_lt25":{},"int_25_41":{},"int_42_58":{},"int_gt58":{},"bandwidth_bps":{}}}"#,
+                r#"{{"type":"stats","frames_decoded":{},"frames_dropped":{},"audio_underruns":{},"net_stall_ms":{},"decoder":{},"width":{},"height":{},"av_drift_ms":{},"video_buffer_ahead_ms":{},"audio_buffer_ahead_ms":{},"video_segment":{},"stall_events":{},"pipeline_retries":{},"render_gap_max_ms":{},"judder_frames":{},"int_lt25":{},"int_25_41":{},"int_42_58":{},"int_gt58":{},"bandwidth_bps":{},"loudness_lufs":{},"audio_peak_db":{}}}"#,
                 video_frames_decoded,
                 video_frames_dropped,
                 audio_underruns,
@@ -540,9 +760,44 @@ pub fn event_to_json(ev: &PlayerEvent) -> String {
                 interval_hist[1],
                 interval_hist[2],
                 interval_hist[3],
-                bandwidth_bps
+                bandwidth_bps,
+                loudness,
+                peaks
+            )
+        }
+        PlayerEvent::SubtitleCue {
+            start,
+            end,
+            text,
+            styling,
+            settings,
+        } => {
+            let spans: Vec<String> = styling
+                .iter()
+                .map(|s| {
+                    format!(
+                        r#"{{"text":{},"color":{},"background":{},"bold":{},"italic":{},"underline":{},"ruby":{}}}"#,
+                        jstr(&s.text),
+                        jcolor(s.color),
+                        jcolor(s.background),
+                        s.bold,
+                        s.italic,
+                        s.underline,
+                        s.ruby.as_deref().map(jstr).unwrap_or_else(|| "null".into())
+                    )
+                })
+                .collect();
+            format!(
+                r#"{{"type":"subtitle_cue","start_ms":{},"end_ms":{},"text":{},"settings":{},"styling":[{}]}}"#,
+                start.as_millis(),
+                end.as_millis(),
+                jstr(text),
+                jstr(settings),
+                spans.join(",")
             )
         }
+        PlayerEvent::SubtitleCueCleared => obj("subtitle_cue_cleared"),
+        PlayerEvent::TextTracksChanged => obj("text_tracks_changed"),
         PlayerEvent::EndOfStream => obj("end_of_stream"),
         PlayerEvent::Error { kind, detail } => format!(
             r#"{{"type":"error","kind":{},"detail":{}}}"#,
@@ -600,13 +855,18 @@ pub fn tracks_to_json(t: &Tracks) -> String {
         .flat_map(|(ai, a)| {
             let lang = a.language().unwrap_or("").to_string();
             let forced = a.is_forced();
+            let external = a.is_external();
+            let label = a.label.clone();
             a.representations
                 .iter()
                 .enumerate()
                 .map(move |(ri, r)| {
+                    // External subtitles carry the host's label; manifest
+                    // tracks describe their codec and bitrate.
+                    let label = label.clone().unwrap_or_else(|| r.label());
                     format!(
-                        r#"{{"adapt":{},"repr":{},"id":{},"lang":{},"forced":{},"codecs":{},"bandwidth":{},"label":{}}}"#,
-                        ai, ri, r.id, jstr(&lang), forced, jstr(&r.codecs), r.bandwidth, jstr(&r.label())
+                        r#"{{"adapt":{},"repr":{},"id":{},"lang":{},"forced":{},"external":{},"codecs":{},"bandwidth":{},"label":{}}}"#,
+                        ai, ri, r.id, jstr(&lang), forced, external, jstr(&r.codecs), r.bandwidth, jstr(&label)
                     )
                 })
                 .collect::<Vec<_>>()
@@ -681,3 +941,36 @@ fn jstr(s: &str) -> String {
     out.push('"');
     out
 }
+
+/// RGBA as a `"#rrggbbaa"` string, `null` when unset.
+fn jcolor(c: Option<[u8; 4]>) -> String {
+    c.map(|[r, g, b, a]| format!(r##""#{:02x}{:02x}{:02x}{:02x}""##, r, g, b, a))
+        .unwrap_or_else(|| "null".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn text_tracks_changed_skips_other_events() {
+        let (tx, mut rx) = broadcast::channel(8);
+        tx.send(PlayerEvent::Position {
+            position: Duration::from_secs(1),
+            duration: Duration::from_secs(60),
+            buffered_ahead_secs: 4.0,
+            bandwidth_bps: 0,
+        })
+        .unwrap();
+        tx.send(PlayerEvent::TextTracksChanged).unwrap();
+        assert!(text_tracks_changed(&mut rx).await);
+
+        // A later change still gets through after unrelated events.
+        tx.send(PlayerEvent::Playing).unwrap();
+        tx.send(PlayerEvent::TextTracksChanged).unwrap();
+        assert!(text_tracks_changed(&mut rx).await);
+
+        drop(tx);
+        assert!(!text_tracks_changed(&mut rx).await);
+    }
+}
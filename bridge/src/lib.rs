@@ -201,21 +201,14 @@ fn lang_matches(track_lang: Option<&str>, want: &str) -> bool {
     !want.is_empty() && primary(have) == primary(want)
 }
 
-pub(crate) fn apply_default_tracks(
-    player: &Player,
-    tracks: &player::Tracks,
-    passthrough_override: Option<bool>,
-    auto_select_subtitle: bool,
-    preferred_audio_language: Option<&str>,
-    preferred_subtitle_language: Option<&str>,
-) {
+fn apply_default_video(player: &Player, tracks: &player::Tracks) {
     // Video: representation picked by `video_pref()` (default: first
     // adaptation, index 5 = 720p HEVC in the preclikos.cz fixture,
     // matching what both shells used to hardcode). The hdr/dv preferences
     // scan EVERY video adaptation set — DV reps commonly live in their
     // own set, not the first one.
     if tracks.video.is_empty() {
-        log::error!("no video adaptations in manifest");
+        log::info!("no video adaptations in manifest; playing audio-only");
         return;
     }
     for (ai, a) in tracks.video.iter().enumerate() {
@@ -278,6 +271,17 @@ pub(crate) fn apply_default_tracks(
         video_repr.width, video_repr.height, video_repr.codecs, pref,
         video_repr.hdr10, video_repr.dolby_vision
     );
+}
+
+pub(crate) fn apply_default_tracks(
+    player: &Player,
+    tracks: &player::Tracks,
+    passthrough_override: Option<bool>,
+    auto_select_subtitle: bool,
+    preferred_audio_language: Option<&str>,
+    preferred_subtitle_language: Option<&str>,
+) {
+    apply_default_video(player, tracks);
 
     // Audio passthrough opt-in (Android, bitstream to HDMI/AVR). Same dual
     // mechanism as direct.txt / video_pref.txt: an env var on desktop, or a
@@ -292,7 +296,7 @@ pub(crate) fn apply_default_tracks(
     // Audio: prefer an AAC (mp4a*) representation since the Android
     // MediaCodec backend can't configure EC-3 / AC-3 without an esds box.
     // Desktop accepts either, so this is a safe lowest-common-denominator.
-    // Override via `RUST_PLAYER_AUDIO=ec-3` (or `ac-3`, `mp4a`) for runtime
+    // Override via `RUST_PLAYER_AUDIO=ec-3` (or `ac-3`, `mp4a`, `opus`, `flac`) for runtime
     // testing of the AudioToolbox / FFmpeg / MediaCodec AC-3 paths; on Android
     // the same value can come from an `audio_pref.txt` file. Passthrough forces
     // `ec-3` (the bitstream sink only handles E-AC-3 / AC-3).
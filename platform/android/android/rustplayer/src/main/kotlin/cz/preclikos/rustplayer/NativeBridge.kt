@@ -36,21 +36,53 @@ object NativeBridge {
     external fun nativePause(handle: Long)
     external fun nativeIsPaused(handle: Long): Boolean
     external fun nativeSeekMs(handle: Long, positionMs: Long)
+    external fun nativeStepFrame(handle: Long, frames: Int)
+    external fun nativeSetTrickPlay(handle: Long, speed: Int)
     external fun nativePositionMs(handle: Long): Long
     external fun nativeDurationMs(handle: Long): Long
     external fun nativeSetVolume(handle: Long, volume: Float)
+    external fun nativeSetPlaybackRate(handle: Long, rate: Float)
+    external fun nativeSetAvOffsetMs(handle: Long, offsetMs: Int)
+    external fun nativeSetLoudness(handle: Long, normalize: Boolean, targetLufs: Float, nightMode: Boolean)
     external fun nativeGetTracksJson(handle: Long): String
     external fun nativeSetVideoTrack(handle: Long, adapt: Int, repr: Int)
     external fun nativeSetVideoAuto(handle: Long)
     external fun nativeSetAudioTrack(handle: Long, adapt: Int, repr: Int)
     external fun nativeSetSubtitleTrack(handle: Long, adapt: Int, repr: Int)
     external fun nativeClearSubtitles(handle: Long)
+    external fun nativeSetHostSubtitles(handle: Long, enabled: Boolean)
+    external fun nativeSetSecondarySubtitleTrack(handle: Long, adapt: Int, repr: Int)
+    external fun nativeClearSecondarySubtitles(handle: Long)
+    external fun nativeSetSubtitleOffsetMs(handle: Long, offsetMs: Long)
+    external fun nativeAddExternalSubtitle(handle: Long, url: String, lang: String, label: String, format: Int)
     external fun nativeDestroy(handle: Long)
 
     // Generic player knobs.
     external fun nativeSetVideoOutputWindow(handle: Long, surface: Surface?)
     external fun nativeSetSubtitleSafeInsetBottom(handle: Long, bottomPx: Int)
     external fun nativeSetAdaptiveFrameRate(handle: Long, enabled: Boolean)
-    external fun nativeSetSubtitleStyle(handle: Long, textArgb: Int, outlineArgb: Int, sizeScale: Float)
+    external fun nativeSetVideoEnabled(handle: Long, enabled: Boolean)
+    external fun nativeSetSubtitleStyle(
+        handle: Long,
+        textArgb: Int,
+        outlineArgb: Int,
+        sizeScale: Float,
+        backgroundArgb: Int,
+        windowArgb: Int,
+        edgeType: Int,
+        fontFamily: Int,
+    )
+    external fun nativeSetSecondarySubtitleStyle(
+        handle: Long,
+        textArgb: Int,
+        outlineArgb: Int,
+        sizeScale: Float,
+        backgroundArgb: Int,
+        windowArgb: Int,
+        edgeType: Int,
+        fontFamily: Int,
+        stacked: Boolean,
+    )
+    external fun nativeSetSubtitleFamilyFont(handle: Long, fontFamily: Int, font: ByteArray): Boolean
     external fun nativeSetVerboseLogging(enabled: Boolean)
 }
@@ -1,6 +1,11 @@
 package cz.preclikos.rustplayer
 
 import android.content.Context
+import android.media.AudioAttributes
+import android.media.AudioDeviceCallback
+import android.media.AudioDeviceInfo
+import android.media.AudioManager
+import android.os.Build
 import android.os.Handler
 import android.os.Looper
 import android.view.Surface
@@ -17,20 +22,38 @@ class RustPlayer(private val context: Context) {
     /** Player events, delivered on the main thread. All methods are optional. */
     interface Listener {
         fun onPrepared() {}
+        /** Track list; called again when closed captions are found mid-playback. */
         fun onTracks(json: String) {}
         fun onPlaying() {}
         fun onPaused() {}
+        fun onPlaybackRate(rate: Float) {}
         fun onBuffering() {}
+        fun onFrameStepped(positionUs: Long) {}
+        fun onTrickPlay(speed: Int) {}
         fun onPosition(positionMs: Long, durationMs: Long) {}
         fun onVideoSize(width: Int, height: Int) {}
         fun onEnded() {}
         fun onError(kind: String, detail: String) {}
 
+        /** [addExternalSubtitle] could not fetch or parse [url]; playback goes on. */
+        fun onExternalSubtitleFailed(url: String, detail: String) {}
+
+        /**
+         * Host-rendered subtitles only: raw `subtitle_cue` JSON (start_ms,
+         * end_ms, text, settings, styling[]) for a cue now on screen.
+         */
+        fun onSubtitleCue(json: String) {}
+
+        /** Host-rendered subtitles only: remove every cue shown so far. */
+        fun onSubtitleCueCleared() {}
+
         /**
          * Raw 1 Hz stats JSON (debug-HUD food): decoder, frames
          * decoded/dropped, av_drift_ms, video/audio_buffer_ahead_ms,
          * video_segment, stall_events, pipeline_retries, render_gap_max_ms,
-         * net_stall_ms, width/height. Fields are additive across versions -
+         * net_stall_ms, width/height, loudness_lufs (null until measured),
+         * audio_peak_db (dB per output channel, null before audio).
+         * Fields are additive across versions -
          * parse with opt*().
          */
         fun onStats(json: String) {}
@@ -41,6 +64,12 @@ class RustPlayer(private val context: Context) {
     private var handle: Long = 0L
     private val main = Handler(Looper.getMainLooper())
     private var bridge: PlayerBridge? = null
+    private val audioManager = context.getSystemService(Context.AUDIO_SERVICE) as AudioManager
+    private val prefs = context.getSharedPreferences("rustplayer", Context.MODE_PRIVATE)
+    private val routeCallback = object : AudioDeviceCallback() {
+        override fun onAudioDevicesAdded(added: Array<out AudioDeviceInfo>) = applyStoredAvOffset()
+        override fun onAudioDevicesRemoved(removed: Array<out AudioDeviceInfo>) = applyStoredAvOffset()
+    }
 
     val isStarted: Boolean get() = handle != 0L
 
@@ -90,6 +119,8 @@ class RustPlayer(private val context: Context) {
             preferredAudioLang,
             preferredSubtitleLang,
         )
+        applyStoredAvOffset()
+        audioManager.registerAudioDeviceCallback(routeCallback, main)
     }
 
     fun setSize(width: Int, height: Int) {
@@ -115,6 +146,19 @@ class RustPlayer(private val context: Context) {
         if (handle != 0L) NativeBridge.nativeSeekMs(handle, positionMs)
     }
 
+    /** Step [frames] while paused (negative = back); lands via [Listener.onFrameStepped]. */
+    fun stepFrame(frames: Int) {
+        if (handle != 0L) NativeBridge.nativeStepFrame(handle, frames)
+    }
+
+    /**
+     * Keyframe-only fast-forward (positive) / rewind (negative) at 2..32x, clamped;
+     * 0 returns to normal playback. Confirmed via [Listener.onTrickPlay].
+     */
+    fun setTrickPlay(speed: Int) {
+        if (handle != 0L) NativeBridge.nativeSetTrickPlay(handle, speed)
+    }
+
     val positionMs: Long get() = if (handle != 0L) NativeBridge.nativePositionMs(handle) else 0L
     val durationMs: Long get() = if (handle != 0L) NativeBridge.nativeDurationMs(handle) else 0L
 
@@ -122,6 +166,63 @@ class RustPlayer(private val context: Context) {
         if (handle != 0L) NativeBridge.nativeSetVolume(handle, volume)
     }
 
+    /** 0.5..2.0, pitch-preserving; confirmed via [Listener.onPlaybackRate]. */
+    fun setPlaybackRate(rate: Float) {
+        if (handle != 0L) NativeBridge.nativeSetPlaybackRate(handle, rate)
+    }
+
+    /**
+     * Loudness normalisation to [targetLufs] (-23 EBU R128, -16 typical for
+     * mobile) and night-mode compression. Live; measured loudness is in the
+     * stats JSON as `loudness_lufs`.
+     */
+    fun setLoudness(normalize: Boolean, targetLufs: Float = -23f, nightMode: Boolean = false) {
+        if (handle != 0L) NativeBridge.nativeSetLoudness(handle, normalize, targetLufs, nightMode)
+    }
+
+    /**
+     * Lip-sync offset in ms (+ = video later than audio), ±2000, for output
+     * latency the OS doesn't report (Bluetooth headsets, some AVRs). Live.
+     * Remembered (clamped) per output device and re-applied when the route
+     * changes.
+     */
+    var avOffsetMs: Int
+        get() = prefs.getInt(avOffsetKey(), 0)
+        set(value) {
+            val clamped = value.coerceIn(-MAX_AV_OFFSET_MS, MAX_AV_OFFSET_MS)
+            prefs.edit().putInt(avOffsetKey(), clamped).apply()
+            if (handle != 0L) NativeBridge.nativeSetAvOffsetMs(handle, clamped)
+        }
+
+    private fun applyStoredAvOffset() {
+        if (handle != 0L) NativeBridge.nativeSetAvOffsetMs(handle, avOffsetMs)
+    }
+
+    /** Key for the device media is routed to (see [routedOutput]). */
+    private fun avOffsetKey(): String {
+        val device = routedOutput()
+        return "avOffsetMs.${device?.type ?: 0}.${device?.productName ?: ""}"
+    }
+
+    /**
+     * The output media audio is routed to. API 33+ asks the audio policy for
+     * the media route; older releases have no such query, so the attached
+     * output Android prefers is assumed (Bluetooth, then wired, USB, HDMI,
+     * then the built-in speaker).
+     */
+    private fun routedOutput(): AudioDeviceInfo? {
+        val outputs = audioManager.getDevices(AudioManager.GET_DEVICES_OUTPUTS)
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.TIRAMISU) {
+            val media = AudioAttributes.Builder().setUsage(AudioAttributes.USAGE_MEDIA).build()
+            val routed = audioManager.getAudioDevicesForAttributes(media).firstOrNull()
+            if (routed != null) {
+                outputs.firstOrNull { it.type == routed.type && it.address == routed.address }
+                    ?.let { return it }
+            }
+        }
+        return ROUTE_PRIORITY.firstNotNullOfOrNull { type -> outputs.firstOrNull { it.type == type } }
+    }
+
     fun tracksJson(): String = if (handle != 0L) NativeBridge.nativeGetTracksJson(handle) else "{}"
 
     fun selectVideo(adapt: Int, repr: Int) {
@@ -144,6 +245,39 @@ class RustPlayer(private val context: Context) {
         if (handle != 0L) NativeBridge.nativeClearSubtitles(handle)
     }
 
+    /**
+     * Draw subtitles in the app's own UI (TalkBack, custom fonts): turns the
+     * built-in overlay off and delivers cues to [Listener.onSubtitleCue] /
+     * [Listener.onSubtitleCueCleared]. Default off.
+     */
+    fun setHostSubtitles(enabled: Boolean) {
+        if (handle != 0L) NativeBridge.nativeSetHostSubtitles(handle, enabled)
+    }
+
+    /** Dual subtitles: a second text track shown with the primary one. */
+    fun selectSecondarySubtitle(adapt: Int, repr: Int) {
+        if (handle != 0L) NativeBridge.nativeSetSecondarySubtitleTrack(handle, adapt, repr)
+    }
+
+    fun clearSecondarySubtitles() {
+        if (handle != 0L) NativeBridge.nativeClearSecondarySubtitles(handle)
+    }
+
+    /**
+     * Add a sidecar subtitle file ([SUBTITLE_FORMAT_SRT] / [SUBTITLE_FORMAT_VTT]
+     * / [SUBTITLE_FORMAT_TTML]). Once fetched it is appended to the text
+     * tracks and [Listener.onTracks] fires; select it like any other track.
+     * A failure goes to [Listener.onExternalSubtitleFailed].
+     */
+    fun addExternalSubtitle(url: String, language: String, label: String, format: Int) {
+        if (handle != 0L) NativeBridge.nativeAddExternalSubtitle(handle, url, language, label, format)
+    }
+
+    /** Subtitle timing offset in ms (+ = cues later), both tracks. Live. */
+    fun setSubtitleOffsetMs(offsetMs: Long) {
+        if (handle != 0L) NativeBridge.nativeSetSubtitleOffsetMs(handle, offsetMs)
+    }
+
     // --- generic knobs (ExoPlayer-style) ---
 
     /** Re-attach on a surface swap, or detach (null) on background. */
@@ -151,6 +285,16 @@ class RustPlayer(private val context: Context) {
         if (handle != 0L) NativeBridge.nativeSetVideoOutputWindow(handle, surface)
     }
 
+    /**
+     * Audio-only (background) playback. `false` stops video download and
+     * decode while audio, position and stats carry on — pair it with
+     * `setVideoSurface(null)`. `true` resumes the picture at the current
+     * position; hand the surface back with [setVideoSurface] first.
+     */
+    fun setVideoEnabled(enabled: Boolean) {
+        if (handle != 0L) NativeBridge.nativeSetVideoEnabled(handle, enabled)
+    }
+
     fun setSubtitleSafeInsetBottom(px: Int) {
         if (handle != 0L) NativeBridge.nativeSetSubtitleSafeInsetBottom(handle, px)
     }
@@ -159,11 +303,60 @@ class RustPlayer(private val context: Context) {
         if (handle != 0L) NativeBridge.nativeSetAdaptiveFrameRate(handle, enabled)
     }
 
-    /** ARGB ints (Android `Color`), like ExoPlayer `CaptionStyleCompat`. */
-    fun setSubtitleStyle(textArgb: Int, outlineArgb: Int, sizeScale: Float) {
-        if (handle != 0L) NativeBridge.nativeSetSubtitleStyle(handle, textArgb, outlineArgb, sizeScale)
+    /**
+     * ARGB ints (Android `Color`) and [edgeType], like ExoPlayer
+     * `CaptionStyleCompat` — a `CaptioningManager.CaptionStyle` passes straight
+     * through. [backgroundArgb] boxes each line, [windowArgb] the whole cue;
+     * alpha is the opacity (0 = no box). [fontFamily]: 0 = regular font, 1–7 =
+     * monospaced serif, proportional serif, monospaced sans, proportional
+     * sans, casual, cursive, small capitals (see [setSubtitleFamilyFont]).
+     */
+    fun setSubtitleStyle(
+        textArgb: Int,
+        outlineArgb: Int,
+        sizeScale: Float,
+        backgroundArgb: Int = 0,
+        windowArgb: Int = 0,
+        edgeType: Int = EDGE_TYPE_DROP_SHADOW,
+        fontFamily: Int = 0,
+    ) {
+        if (handle != 0L) {
+            NativeBridge.nativeSetSubtitleStyle(
+                handle, textArgb, outlineArgb, sizeScale, backgroundArgb, windowArgb, edgeType, fontFamily,
+            )
+        }
     }
 
+    /**
+     * Secondary track style; [stacked] = right above the primary cues instead
+     * of at the top of the picture.
+     */
+    fun setSecondarySubtitleStyle(
+        textArgb: Int,
+        outlineArgb: Int,
+        sizeScale: Float,
+        stacked: Boolean,
+        backgroundArgb: Int = 0,
+        windowArgb: Int = 0,
+        edgeType: Int = EDGE_TYPE_DROP_SHADOW,
+        fontFamily: Int = 0,
+    ) {
+        if (handle != 0L) {
+            NativeBridge.nativeSetSecondarySubtitleStyle(
+                handle, textArgb, outlineArgb, sizeScale, backgroundArgb, windowArgb, edgeType, fontFamily,
+                stacked,
+            )
+        }
+    }
+
+    /**
+     * Font for caption family [fontFamily] (1–7, see [setSubtitleStyle]) —
+     * e.g. the bytes of `/system/fonts/CutiveMono.ttf` for monospaced serif.
+     * False if the font is invalid.
+     */
+    fun setSubtitleFamilyFont(fontFamily: Int, font: ByteArray): Boolean =
+        handle != 0L && NativeBridge.nativeSetSubtitleFamilyFont(handle, fontFamily, font)
+
     /** Verbose logging (default off; gates per-frame vsync/HEALTH spam). */
     fun setVerboseLogging(enabled: Boolean) {
         NativeBridge.nativeSetVerboseLogging(enabled)
@@ -171,6 +364,7 @@ class RustPlayer(private val context: Context) {
 
     fun release() {
         if (handle != 0L) {
+            audioManager.unregisterAudioDeviceCallback(routeCallback)
             NativeBridge.nativeDestroy(handle)
             handle = 0L
             bridge = null
@@ -186,10 +380,13 @@ class RustPlayer(private val context: Context) {
         }
         when (o.optString("type")) {
             "prepared" -> l.onPrepared()
-            "tracks_ready" -> l.onTracks(tracksJson())
+            "tracks_ready", "text_tracks_changed" -> l.onTracks(tracksJson())
             "playing" -> l.onPlaying()
             "paused" -> l.onPaused()
+            "playback_rate_changed" -> l.onPlaybackRate(o.optDouble("rate", 1.0).toFloat())
             "buffering" -> l.onBuffering()
+            "frame_stepped" -> l.onFrameStepped(o.optLong("position_us"))
+            "trick_play_changed" -> l.onTrickPlay(o.optInt("speed"))
             "position" -> l.onPosition(o.optLong("position_ms"), o.optLong("duration_ms"))
             "video_size" -> {
                 val w = o.optInt("width")
@@ -202,8 +399,39 @@ class RustPlayer(private val context: Context) {
                 if (w > 0 && h > 0) l.onVideoSize(w, h)
                 l.onStats(json)
             }
+            "subtitle_cue" -> l.onSubtitleCue(json)
+            "subtitle_cue_cleared" -> l.onSubtitleCueCleared()
             "end_of_stream" -> l.onEnded()
             "error" -> l.onError(o.optString("kind"), o.optString("detail"))
+            "external_subtitle_failed" -> l.onExternalSubtitleFailed(o.optString("url"), o.optString("detail"))
         }
     }
+
+    companion object {
+        /** `CaptionStyleCompat.EDGE_TYPE_*` values [setSubtitleStyle] takes. */
+        const val EDGE_TYPE_NONE = 0
+        const val EDGE_TYPE_OUTLINE = 1
+        const val EDGE_TYPE_DROP_SHADOW = 2
+        const val EDGE_TYPE_RAISED = 3
+        const val EDGE_TYPE_DEPRESSED = 4
+
+        /** Formats [addExternalSubtitle] takes. */
+        const val SUBTITLE_FORMAT_SRT = 0
+        const val SUBTITLE_FORMAT_VTT = 1
+        const val SUBTITLE_FORMAT_TTML = 2
+
+        /** Bound of [avOffsetMs], as the native `MAX_AV_OFFSET_MS`. */
+        const val MAX_AV_OFFSET_MS = 2000
+
+        private val ROUTE_PRIORITY = listOf(
+            AudioDeviceInfo.TYPE_BLUETOOTH_A2DP,
+            AudioDeviceInfo.TYPE_WIRED_HEADPHONES,
+            AudioDeviceInfo.TYPE_WIRED_HEADSET,
+            AudioDeviceInfo.TYPE_USB_HEADSET,
+            AudioDeviceInfo.TYPE_USB_DEVICE,
+            AudioDeviceInfo.TYPE_HDMI,
+            AudioDeviceInfo.TYPE_HDMI_ARC,
+            AudioDeviceInfo.TYPE_BUILTIN_SPEAKER,
+        )
+    }
 }
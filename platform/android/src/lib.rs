@@ -22,7 +22,10 @@ use async_trait::async_trait;
 use jni::objects::{GlobalRef, JByteArray, JClass, JObject, JObjectArray, JString, JValue};
 use jni::sys::{jboolean, jfloat, jint, jlong, jstring};
 use jni::{JNIEnv, JavaVM};
-use player::{Player, SubtitleStyle};
+use player::{
+    Player, SecondarySubtitlePosition, SubtitleEdgeStyle, SubtitleFontFamily, SubtitleFormat,
+    SubtitleStyle,
+};
 
 /// Player bridge + the `ANativeWindow` refs it renders into.
 struct Handle {
@@ -431,6 +434,32 @@ pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSeekMs(
     }
 }
 
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeStepFrame(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    frames: jint,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        let _guard = runtime().enter();
+        h.bridge.step_frame(frames);
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetTrickPlay(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    speed: jint,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        let _guard = runtime().enter();
+        h.bridge.set_trick_play(speed);
+    }
+}
+
 #[no_mangle]
 pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativePositionMs(
     _env: JNIEnv,
@@ -466,6 +495,45 @@ pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetVolume
     }
 }
 
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetPlaybackRate(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    rate: jfloat,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        let _guard = runtime().enter();
+        h.bridge.set_playback_rate(rate);
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetAvOffsetMs(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    offset_ms: jint,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_av_offset_ms(offset_ms);
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetLoudness(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    normalize: jboolean,
+    target_lufs: jfloat,
+    night_mode: jboolean,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_loudness(normalize != 0, target_lufs, night_mode != 0);
+    }
+}
+
 #[no_mangle]
 pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeGetTracksJson<'local>(
     env: JNIEnv<'local>,
@@ -542,6 +610,87 @@ pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeClearSubt
     }
 }
 
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetHostSubtitles(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    enabled: jboolean,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_host_subtitles(enabled != 0);
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetSecondarySubtitleTrack(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    adapt: jint,
+    repr: jint,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge
+            .set_secondary_subtitle_track(adapt.max(0) as usize, repr.max(0) as usize);
+    }
+}
+
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeClearSecondarySubtitles(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.clear_secondary_subtitles();
+    }
+}
+
+/// Sidecar subtitle file; `format` 0 SubRip, 1 WebVTT, 2 TTML. Reported
+/// back as `text_tracks_changed` or `external_subtitle_failed`.
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeAddExternalSubtitle(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    url: JString,
+    lang: JString,
+    label: JString,
+    format: jint,
+) {
+    let Some(h) = (unsafe { handle_ref(handle) }) else {
+        return;
+    };
+    let Some(format) = SubtitleFormat::from_index(format) else {
+        log::warn!("nativeAddExternalSubtitle: unknown format {format}");
+        return;
+    };
+    let mut string = |s: &JString| -> String {
+        env.get_string(s).map(Into::into).unwrap_or_default()
+    };
+    let url = string(&url);
+    if url.is_empty() {
+        log::warn!("nativeAddExternalSubtitle: url missing");
+        return;
+    }
+    let lang = string(&lang);
+    let label = string(&label);
+    h.bridge.add_external_subtitle(url, lang, label, format);
+}
+
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetSubtitleOffsetMs(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    offset_ms: jlong,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_subtitle_offset_ms(offset_ms);
+    }
+}
+
 // --- generic player knobs (parity with ExoPlayer surface/track/format API) ---
 
 /// Re-point (or detach with a null surface) the MediaCodec video plane. Use on
@@ -598,8 +747,25 @@ pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetAdapti
     }
 }
 
-/// ARGB ints (Android `Color`), like ExoPlayer `CaptionStyleCompat`.
+/// Audio-only mode: `false` stops video download/decode (background
+/// listening), `true` resumes video at the current position.
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetVideoEnabled(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    enabled: jboolean,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_video_enabled(enabled != 0);
+    }
+}
+
+/// ARGB ints (Android `Color`) and edge type, like ExoPlayer
+/// `CaptionStyleCompat` (`CaptioningManager.CaptionStyle` maps 1:1);
+/// `font_family` 0 = regular font, 1–7 the FCC generic families.
 #[no_mangle]
+#[allow(clippy::too_many_arguments)]
 pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetSubtitleStyle(
     _env: JNIEnv,
     _class: JClass,
@@ -607,26 +773,122 @@ pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetSubtit
     text_argb: jint,
     outline_argb: jint,
     size_scale: jfloat,
+    background_argb: jint,
+    window_argb: jint,
+    edge_type: jint,
+    font_family: jint,
 ) {
     let Some(h) = (unsafe { handle_ref(handle) }) else {
         return;
     };
-    fn argb_to_rgba(c: jint) -> [u8; 4] {
-        let c = c as u32;
-        [
-            ((c >> 16) & 0xff) as u8, // R
-            ((c >> 8) & 0xff) as u8,  // G
-            (c & 0xff) as u8,         // B
-            ((c >> 24) & 0xff) as u8, // A
-        ]
-    }
-    let style = SubtitleStyle {
+    let style = subtitle_style(
+        text_argb,
+        outline_argb,
+        size_scale,
+        background_argb,
+        window_argb,
+        edge_type,
+        font_family,
+    )
+    .sanitised();
+    h.bridge.player().set_subtitle_style(style);
+}
+
+/// Secondary (dual) subtitle track style; `stacked` puts it right above
+/// the primary cues instead of at the top of the picture.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetSecondarySubtitleStyle(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    text_argb: jint,
+    outline_argb: jint,
+    size_scale: jfloat,
+    background_argb: jint,
+    window_argb: jint,
+    edge_type: jint,
+    font_family: jint,
+    stacked: jboolean,
+) {
+    let Some(h) = (unsafe { handle_ref(handle) }) else {
+        return;
+    };
+    let style = subtitle_style(
+        text_argb,
+        outline_argb,
+        size_scale,
+        background_argb,
+        window_argb,
+        edge_type,
+        font_family,
+    );
+    let position = if stacked != 0 {
+        SecondarySubtitlePosition::Stacked
+    } else {
+        SecondarySubtitlePosition::Top
+    };
+    h.bridge
+        .player()
+        .set_secondary_subtitle_style(style, position);
+}
+
+/// Font for one generic caption family (`font_family` 1–7 of the style
+/// setters), e.g. `/system/fonts/CutiveMono.ttf` read by the app. False
+/// on unreadable or invalid bytes, or family 0 (the regular font).
+#[no_mangle]
+pub extern "system" fn Java_cz_preclikos_rustplayer_NativeBridge_nativeSetSubtitleFamilyFont(
+    env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    font_family: jint,
+    font: JByteArray,
+) -> jboolean {
+    let Some(h) = (unsafe { handle_ref(handle) }) else {
+        return 0;
+    };
+    let Ok(bytes) = env.convert_byte_array(&font) else {
+        return 0;
+    };
+    let family = SubtitleFontFamily::from_index(font_family);
+    match h.bridge.player().set_subtitle_family_font(family, bytes) {
+        Ok(()) => 1,
+        Err(e) => {
+            log::warn!("subtitle family font rejected: {}", e);
+            0
+        }
+    }
+}
+
+/// The style setters' arguments as a [`SubtitleStyle`].
+fn subtitle_style(
+    text_argb: jint,
+    outline_argb: jint,
+    size_scale: jfloat,
+    background_argb: jint,
+    window_argb: jint,
+    edge_type: jint,
+    font_family: jint,
+) -> SubtitleStyle {
+    SubtitleStyle {
         text_color: argb_to_rgba(text_argb),
         outline_color: argb_to_rgba(outline_argb),
         size_scale,
+        background_color: argb_to_rgba(background_argb),
+        window_color: argb_to_rgba(window_argb),
+        edge_style: SubtitleEdgeStyle::from_edge_type(edge_type),
+        font_family: SubtitleFontFamily::from_index(font_family),
     }
-    .sanitised();
-    h.bridge.player().set_subtitle_style(style);
+}
+
+fn argb_to_rgba(c: jint) -> [u8; 4] {
+    let c = c as u32;
+    [
+        ((c >> 16) & 0xff) as u8, // R
+        ((c >> 8) & 0xff) as u8,  // G
+        (c & 0xff) as u8,         // B
+        ((c >> 24) & 0xff) as u8, // A
+    ]
 }
 
 /// Verbose logging toggle (default off → per-frame vsync/HEALTH spam gated).
@@ -2,6 +2,9 @@ import Foundation
 import CoreGraphics
 import QuartzCore
 import RustPlayerFFI
+#if os(iOS) || os(tvOS)
+import AVFoundation
+#endif
 
 /// Provider policy supplied by the host: URL/auth rewriting and DRM key
 /// resolution. `intercept` defaults to passthrough; `resolveKey` is required.
@@ -53,11 +56,21 @@ public protocol RustPlayerDelegate: AnyObject {
     func rustPlayer(_ player: RustPlayer, didLoadTracks json: String)
     func rustPlayerDidStartPlaying(_ player: RustPlayer)
     func rustPlayerDidPause(_ player: RustPlayer)
+    func rustPlayer(_ player: RustPlayer, playbackRate rate: Float)
     func rustPlayerDidBuffer(_ player: RustPlayer)
+    func rustPlayer(_ player: RustPlayer, frameSteppedToUs positionUs: Int64)
+    func rustPlayer(_ player: RustPlayer, trickPlaySpeed speed: Int)
     func rustPlayer(_ player: RustPlayer, position positionMs: Int64, duration durationMs: Int64)
     func rustPlayer(_ player: RustPlayer, videoSize size: CGSize)
     func rustPlayerDidEnd(_ player: RustPlayer)
     func rustPlayer(_ player: RustPlayer, didError kind: String, detail: String)
+    /// `addExternalSubtitle` could not fetch or parse `url`; playback goes on.
+    func rustPlayer(_ player: RustPlayer, externalSubtitleFailed url: String, detail: String)
+    /// Host-rendered subtitles only: raw `subtitle_cue` JSON (start_ms,
+    /// end_ms, text, settings, styling[]) for a cue now on screen.
+    func rustPlayer(_ player: RustPlayer, subtitleCue json: String)
+    /// Host-rendered subtitles only: remove every cue shown so far.
+    func rustPlayerDidClearSubtitleCues(_ player: RustPlayer)
 }
 
 public extension RustPlayerDelegate {
@@ -65,11 +78,17 @@ public extension RustPlayerDelegate {
     func rustPlayer(_ player: RustPlayer, didLoadTracks json: String) {}
     func rustPlayerDidStartPlaying(_ player: RustPlayer) {}
     func rustPlayerDidPause(_ player: RustPlayer) {}
+    func rustPlayer(_ player: RustPlayer, playbackRate rate: Float) {}
     func rustPlayerDidBuffer(_ player: RustPlayer) {}
+    func rustPlayer(_ player: RustPlayer, frameSteppedToUs positionUs: Int64) {}
+    func rustPlayer(_ player: RustPlayer, trickPlaySpeed speed: Int) {}
     func rustPlayer(_ player: RustPlayer, position positionMs: Int64, duration durationMs: Int64) {}
     func rustPlayer(_ player: RustPlayer, videoSize size: CGSize) {}
     func rustPlayerDidEnd(_ player: RustPlayer) {}
     func rustPlayer(_ player: RustPlayer, didError kind: String, detail: String) {}
+    func rustPlayer(_ player: RustPlayer, externalSubtitleFailed url: String, detail: String) {}
+    func rustPlayer(_ player: RustPlayer, subtitleCue json: String) {}
+    func rustPlayerDidClearSubtitleCues(_ player: RustPlayer) {}
 }
 
 /// Idiomatic Swift wrapper over the Rust player FFI. Create one per
@@ -81,6 +100,7 @@ public final class RustPlayer {
 
     private var handle: UnsafeMutableRawPointer?
     private var lastSize: CGSize = .zero
+    private var routeObserver: NSObjectProtocol?
 
     public init() {}
 
@@ -116,6 +136,12 @@ public final class RustPlayer {
                 user
             )
         }
+        applyStoredAvOffset()
+        #if os(iOS) || os(tvOS)
+        routeObserver = NotificationCenter.default.addObserver(
+            forName: AVAudioSession.routeChangeNotification, object: nil, queue: .main
+        ) { [weak self] _ in self?.applyStoredAvOffset() }
+        #endif
     }
 
     public func setSize(_ size: CGSize, scale: CGFloat) {
@@ -131,9 +157,51 @@ public final class RustPlayer {
     }
     public var isPaused: Bool { handle.map { rustplayer_player_is_paused($0) } ?? false }
     public func seek(toMs ms: Int64) { handle.map { rustplayer_player_seek_ms($0, ms) } }
+    /// Step `frames` while paused (negative = back); lands via `rustPlayer(_:frameSteppedToUs:)`.
+    public func stepFrame(_ frames: Int32) { handle.map { rustplayer_player_step_frame($0, frames) } }
+    /// Keyframe-only fast-forward (+) / rewind (-) at 2...32x; 0 = normal playback.
+    /// Confirmed via `rustPlayer(_:trickPlaySpeed:)`.
+    public func setTrickPlay(_ speed: Int32) { handle.map { rustplayer_player_set_trick_play($0, speed) } }
     public var positionMs: Int64 { handle.map { rustplayer_player_position_ms($0) } ?? 0 }
     public var durationMs: Int64 { handle.map { rustplayer_player_duration_ms($0) } ?? 0 }
     public func setVolume(_ v: Float) { handle.map { rustplayer_player_set_volume($0, v) } }
+    /// 0.5...2.0, pitch-preserving; confirmed via `rustPlayer(_:playbackRate:)`.
+    public func setPlaybackRate(_ rate: Float) { handle.map { rustplayer_player_set_playback_rate($0, rate) } }
+    /// Loudness normalisation (-23 LUFS EBU R128, -16 typical for mobile) and night-mode compression.
+    public func setLoudness(normalize: Bool, targetLufs: Float = -23, nightMode: Bool = false) {
+        handle.map { rustplayer_player_set_loudness($0, normalize, targetLufs, nightMode) }
+    }
+
+    /// Bound of `avOffsetMs`, as the native `MAX_AV_OFFSET_MS`.
+    public static let maxAvOffsetMs: Int32 = 2000
+
+    /// Lip-sync offset in ms (+ = video later than audio), ±`maxAvOffsetMs`,
+    /// for output latency the OS doesn't report (Bluetooth headsets, some
+    /// AVRs). Live. Remembered per audio route in `UserDefaults` and
+    /// re-applied when the route changes.
+    public var avOffsetMs: Int32 {
+        get { Int32(UserDefaults.standard.integer(forKey: avOffsetKey())) }
+        set {
+            // Stored as applied: the native side clamps to the same bound.
+            let clamped = min(max(newValue, -Self.maxAvOffsetMs), Self.maxAvOffsetMs)
+            UserDefaults.standard.set(Int(clamped), forKey: avOffsetKey())
+            handle.map { rustplayer_player_set_av_offset_ms($0, clamped) }
+        }
+    }
+
+    private func applyStoredAvOffset() {
+        let offset = avOffsetMs
+        handle.map { rustplayer_player_set_av_offset_ms($0, offset) }
+    }
+
+    private func avOffsetKey() -> String {
+        #if os(iOS) || os(tvOS)
+        let route = AVAudioSession.sharedInstance().currentRoute.outputs.first?.uid ?? "default"
+        #else
+        let route = "default"
+        #endif
+        return "rustplayer.avOffsetMs.\(route)"
+    }
 
     public func tracksJSON() -> String {
         guard let handle, let c = rustplayer_player_tracks_json(handle) else { return "{}" }
@@ -148,16 +216,75 @@ public final class RustPlayer {
     public func selectAudio(adapt: UInt32, repr: UInt32) { handle.map { rustplayer_player_select_audio($0, adapt, repr) } }
     public func selectSubtitle(adapt: UInt32, repr: UInt32) { handle.map { rustplayer_player_select_subtitle($0, adapt, repr) } }
     public func clearSubtitles() { handle.map { rustplayer_player_clear_subtitles($0) } }
+    /// Draw subtitles in the app's own UI: turns the built-in overlay off
+    /// and delivers cues to `rustPlayer(_:subtitleCue:)` /
+    /// `rustPlayerDidClearSubtitleCues(_:)`. Default off.
+    public func setHostSubtitles(_ enabled: Bool) {
+        handle.map { rustplayer_player_set_host_subtitles($0, enabled) }
+    }
+    /// Dual subtitles: a second text track shown with the primary one.
+    public func selectSecondarySubtitle(adapt: UInt32, repr: UInt32) {
+        handle.map { rustplayer_player_select_secondary_subtitle($0, adapt, repr) }
+    }
+    public func clearSecondarySubtitles() { handle.map { rustplayer_player_clear_secondary_subtitles($0) } }
+    /// Sidecar subtitle file; `format`: 0 SubRip, 1 WebVTT, 2 TTML. Once
+    /// fetched it joins the text tracks (`rustPlayer(_:didLoadTracks:)`);
+    /// a failure goes to `rustPlayer(_:externalSubtitleFailed:detail:)`.
+    public func addExternalSubtitle(url: String, language: String, label: String = "", format: Int32) {
+        handle.map { rustplayer_player_add_external_subtitle($0, url, language, label, format) }
+    }
+    /// Subtitle timing offset in ms (+ = cues later), both tracks. Live.
+    public func setSubtitleOffsetMs(_ offsetMs: Int64) {
+        handle.map { rustplayer_player_set_subtitle_offset_ms($0, offsetMs) }
+    }
 
     // --- generic knobs ---
 
-    /// ARGB ints (like Android `Color` / ExoPlayer `CaptionStyleCompat`).
-    public func setSubtitleStyle(textArgb: Int32, outlineArgb: Int32, sizeScale: Float) {
-        handle.map { rustplayer_player_set_subtitle_style($0, textArgb, outlineArgb, sizeScale) }
+    /// ARGB ints (like Android `Color`). `backgroundArgb` boxes each line,
+    /// `windowArgb` the whole cue; alpha is the opacity (0 = no box).
+    /// `edgeType`: a `MACaptionAppearanceTextEdgeStyle` raw value — 1 none,
+    /// 2 raised, 3 depressed, 4 uniform, 5 drop shadow. `fontFamily`: 0 =
+    /// regular font, 1–7 = `MACaptionAppearanceFontStyle` monospaced serif
+    /// … small capitals (see `setSubtitleFamilyFont`).
+    public func setSubtitleStyle(textArgb: Int32, outlineArgb: Int32, sizeScale: Float,
+                                 backgroundArgb: Int32 = 0, windowArgb: Int32 = 0,
+                                 edgeType: Int32 = 5, fontFamily: Int32 = 0) {
+        handle.map {
+            rustplayer_player_set_subtitle_style($0, textArgb, outlineArgb, sizeScale,
+                                                 backgroundArgb, windowArgb, edgeType, fontFamily)
+        }
+    }
+    /// Secondary track style; `stacked` = right above the primary cues
+    /// instead of at the top of the picture.
+    public func setSecondarySubtitleStyle(textArgb: Int32, outlineArgb: Int32, sizeScale: Float, stacked: Bool,
+                                          backgroundArgb: Int32 = 0, windowArgb: Int32 = 0,
+                                          edgeType: Int32 = 5, fontFamily: Int32 = 0) {
+        handle.map {
+            rustplayer_player_set_secondary_subtitle_style($0, textArgb, outlineArgb, sizeScale,
+                                                           backgroundArgb, windowArgb, edgeType, fontFamily,
+                                                           stacked)
+        }
+    }
+    /// Font for caption family `fontFamily` (1–7, see `setSubtitleStyle`),
+    /// e.g. a TTF bundled with the app. False if the font is invalid.
+    @discardableResult
+    public func setSubtitleFamilyFont(_ fontFamily: Int32, data: Data) -> Bool {
+        guard let handle else { return false }
+        return data.withUnsafeBytes { buf in
+            rustplayer_player_set_subtitle_family_font(
+                handle, fontFamily, buf.bindMemory(to: UInt8.self).baseAddress, buf.count)
+        }
     }
     public func setSubtitleSafeInsetBottom(_ px: UInt32) {
         handle.map { rustplayer_player_set_subtitle_safe_inset_bottom($0, px) }
     }
+    /// Audio-only (background) playback: `false` stops video download and
+    /// decode while audio, position and stats carry on — call it on
+    /// `didEnterBackground`; `true` on return resumes the picture at the
+    /// current position.
+    public func setVideoEnabled(_ enabled: Bool) {
+        handle.map { rustplayer_player_set_video_enabled($0, enabled) }
+    }
     /// Debug/compat: force HDR video to an 8-bit decode destination (the
     /// in-player tonemap still runs, at 8-bit precision). Applies from the
     /// next pipeline (re)build (play / retry / ABR swap).
@@ -169,6 +296,7 @@ public final class RustPlayer {
     }
 
     public func destroy() {
+        if let routeObserver { NotificationCenter.default.removeObserver(routeObserver); self.routeObserver = nil }
         if let handle { rustplayer_player_destroy(handle); self.handle = nil }
     }
 
@@ -181,10 +309,16 @@ public final class RustPlayer {
         let d = delegate
         switch type {
         case "prepared": d?.rustPlayerDidPrepare(self)
-        case "tracks_ready": d?.rustPlayer(self, didLoadTracks: tracksJSON())
+        case "tracks_ready", "text_tracks_changed": d?.rustPlayer(self, didLoadTracks: tracksJSON())
         case "playing": d?.rustPlayerDidStartPlaying(self)
         case "paused": d?.rustPlayerDidPause(self)
+        case "playback_rate_changed":
+            d?.rustPlayer(self, playbackRate: (obj["rate"] as? NSNumber)?.floatValue ?? 1)
         case "buffering": d?.rustPlayerDidBuffer(self)
+        case "frame_stepped":
+            d?.rustPlayer(self, frameSteppedToUs: (obj["position_us"] as? NSNumber)?.int64Value ?? 0)
+        case "trick_play_changed":
+            d?.rustPlayer(self, trickPlaySpeed: (obj["speed"] as? NSNumber)?.intValue ?? 0)
         case "position":
             d?.rustPlayer(self,
                           position: (obj["position_ms"] as? NSNumber)?.int64Value ?? 0,
@@ -196,11 +330,17 @@ public final class RustPlayer {
                 let s = CGSize(width: w, height: h)
                 if s != lastSize { lastSize = s; d?.rustPlayer(self, videoSize: s) }
             }
+        case "subtitle_cue": d?.rustPlayer(self, subtitleCue: json)
+        case "subtitle_cue_cleared": d?.rustPlayerDidClearSubtitleCues(self)
         case "end_of_stream": d?.rustPlayerDidEnd(self)
         case "error":
             d?.rustPlayer(self,
                           didError: obj["kind"] as? String ?? "",
                           detail: obj["detail"] as? String ?? "")
+        case "external_subtitle_failed":
+            d?.rustPlayer(self,
+                          externalSubtitleFailed: obj["url"] as? String ?? "",
+                          detail: obj["detail"] as? String ?? "")
         default: break
         }
     }
@@ -46,9 +46,21 @@ void rustplayer_player_play(void *handle);
 void rustplayer_player_pause(void *handle);
 bool rustplayer_player_is_paused(void *handle);
 void rustplayer_player_seek_ms(void *handle, int64_t position_ms);
+// Step frames while paused (negative = back); reported via "frame_stepped".
+void rustplayer_player_step_frame(void *handle, int32_t frames);
+// Keyframe-only fast-forward (+) / rewind (-), 2..32x (clamped); 0 = normal playback.
+// Confirmed by a "trick_play_changed" event.
+void rustplayer_player_set_trick_play(void *handle, int32_t speed);
 int64_t rustplayer_player_position_ms(void *handle);
 int64_t rustplayer_player_duration_ms(void *handle);
 void rustplayer_player_set_volume(void *handle, float volume);
+// 0.5..2.0, pitch-preserving; confirmed by a "playback_rate_changed" event.
+void rustplayer_player_set_playback_rate(void *handle, float rate);
+// Lip-sync offset in ms (+ = video later than audio), clamped to ±2000. Live.
+void rustplayer_player_set_av_offset_ms(void *handle, int32_t offset_ms);
+// EBU R128 normalisation to target_lufs (-23 broadcast, -16 mobile) and
+// night-mode compression. Measured loudness: "loudness_lufs" in stats.
+void rustplayer_player_set_loudness(void *handle, bool normalize, float target_lufs, bool night_mode);
 
 // Tracks. Returns a heap C string the caller MUST free with rustplayer_string_free.
 char *rustplayer_player_tracks_json(void *handle);
@@ -58,10 +70,38 @@ void rustplayer_player_select_video_auto(void *handle);
 void rustplayer_player_select_audio(void *handle, uint32_t adapt, uint32_t repr);
 void rustplayer_player_select_subtitle(void *handle, uint32_t adapt, uint32_t repr);
 void rustplayer_player_clear_subtitles(void *handle);
+// Host-rendered subtitles: no overlay; cues arrive as "subtitle_cue" /
+// "subtitle_cue_cleared" events for the app to draw. Default off.
+void rustplayer_player_set_host_subtitles(void *handle, bool enabled);
+// Dual subtitles: a second text track shown at the top (or stacked above the
+// primary cues, see rustplayer_player_set_secondary_subtitle_style).
+void rustplayer_player_select_secondary_subtitle(void *handle, uint32_t adapt, uint32_t repr);
+void rustplayer_player_clear_secondary_subtitles(void *handle);
+// Sidecar subtitle file, format 0 SubRip, 1 WebVTT, 2 TTML; strings are copied.
+// Listed via "text_tracks_changed" once fetched, else "external_subtitle_failed".
+void rustplayer_player_add_external_subtitle(void *handle, const char *url, const char *lang, const char *label,
+                                             int32_t format);
+// Subtitle timing offset in ms (+ = cues later), clamped to ±600000. Live.
+void rustplayer_player_set_subtitle_offset_ms(void *handle, int64_t offset_ms);
 
 // Generic knobs.
-void rustplayer_player_set_subtitle_style(void *handle, int32_t text_argb, int32_t outline_argb, float size_scale);
+// Subtitle style: ARGB colours; background = box per line, window = box behind
+// the whole cue (alpha 0 = none). edge_type as MACaptionAppearanceTextEdgeStyle:
+// 1 none, 2 raised, 3 depressed, 4 uniform, 5 (or 0) drop shadow. font_family:
+// 0 = regular font, 1-7 = MACaptionAppearanceFontStyle monospaced serif ... small
+// capitals, drawn with the font given to rustplayer_player_set_subtitle_family_font.
+void rustplayer_player_set_subtitle_style(void *handle, int32_t text_argb, int32_t outline_argb, float size_scale,
+                                          int32_t background_argb, int32_t window_argb, int32_t edge_type,
+                                          int32_t font_family);
+void rustplayer_player_set_secondary_subtitle_style(void *handle, int32_t text_argb, int32_t outline_argb,
+                                                    float size_scale, int32_t background_argb, int32_t window_argb,
+                                                    int32_t edge_type, int32_t font_family, bool stacked);
+// TTF/OTF bytes for caption font family 1-7; false if invalid. Copied.
+bool rustplayer_player_set_subtitle_family_font(void *handle, int32_t font_family, const uint8_t *data, size_t len);
 void rustplayer_player_set_subtitle_safe_inset_bottom(void *handle, uint32_t bottom_px);
+// Audio-only mode: false stops video download/decode (background listening),
+// true resumes video at the current position.
+void rustplayer_player_set_video_enabled(void *handle, bool enabled);
 // Debug/compat: force HDR video to an 8-bit decode destination (tonemap
 // still runs, at 8-bit precision). Applies from the next pipeline
 // (re)build (play / retry / ABR swap).
@@ -29,7 +29,10 @@ use bridge::{
 };
 use async_trait::async_trait;
 use bytes::Bytes;
-use player::{Player, SubtitleStyle};
+use player::{
+    Player, SecondarySubtitlePosition, SubtitleEdgeStyle, SubtitleFontFamily, SubtitleFormat,
+    SubtitleStyle,
+};
 use reqwest::Method;
 use tokio::sync::oneshot;
 
@@ -304,6 +307,22 @@ pub extern "C" fn rustplayer_player_seek_ms(handle: *mut c_void, position_ms: i6
     }
 }
 
+#[no_mangle]
+pub extern "C" fn rustplayer_player_step_frame(handle: *mut c_void, frames: i32) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        let _guard = runtime().enter();
+        h.bridge.step_frame(frames);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_trick_play(handle: *mut c_void, speed: i32) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        let _guard = runtime().enter();
+        h.bridge.set_trick_play(speed);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn rustplayer_player_position_ms(handle: *mut c_void) -> i64 {
     unsafe { handle_ref(handle) }
@@ -326,6 +345,33 @@ pub extern "C" fn rustplayer_player_set_volume(handle: *mut c_void, volume: f32)
     }
 }
 
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_playback_rate(handle: *mut c_void, rate: f32) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        let _guard = runtime().enter();
+        h.bridge.set_playback_rate(rate);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_av_offset_ms(handle: *mut c_void, offset_ms: i32) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_av_offset_ms(offset_ms);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_loudness(
+    handle: *mut c_void,
+    normalize: bool,
+    target_lufs: f32,
+    night_mode: bool,
+) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_loudness(normalize, target_lufs, night_mode);
+    }
+}
+
 /// Returns a heap C string the caller MUST free with [`rustplayer_string_free`].
 #[no_mangle]
 pub extern "C" fn rustplayer_player_tracks_json(handle: *mut c_void) -> *mut c_char {
@@ -386,35 +432,186 @@ pub extern "C" fn rustplayer_player_clear_subtitles(handle: *mut c_void) {
     }
 }
 
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_host_subtitles(handle: *mut c_void, enabled: bool) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_host_subtitles(enabled);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rustplayer_player_select_secondary_subtitle(handle: *mut c_void, adapt: u32, repr: u32) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_secondary_subtitle_track(adapt as usize, repr as usize);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rustplayer_player_clear_secondary_subtitles(handle: *mut c_void) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.clear_secondary_subtitles();
+    }
+}
+
+/// Sidecar subtitle file; `format` 0 SubRip, 1 WebVTT, 2 TTML. Reported
+/// back as `text_tracks_changed` or `external_subtitle_failed`.
+#[no_mangle]
+pub extern "C" fn rustplayer_player_add_external_subtitle(
+    handle: *mut c_void,
+    url: *const c_char,
+    lang: *const c_char,
+    label: *const c_char,
+    format: i32,
+) {
+    let Some(h) = (unsafe { handle_ref(handle) }) else {
+        return;
+    };
+    let Some(format) = SubtitleFormat::from_index(format) else {
+        log::warn!("add_external_subtitle: unknown format {format}");
+        return;
+    };
+    let url = unsafe { cstr(url) };
+    if url.is_empty() {
+        log::warn!("add_external_subtitle: url missing");
+        return;
+    }
+    let (lang, label) = unsafe { (cstr(lang), cstr(label)) };
+    h.bridge.add_external_subtitle(url, lang, label, format);
+}
+
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_subtitle_offset_ms(handle: *mut c_void, offset_ms: i64) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_subtitle_offset_ms(offset_ms);
+    }
+}
+
 // --- generic player knobs ---
 
-/// ARGB ints (like Android `Color` / ExoPlayer `CaptionStyleCompat`).
+/// ARGB ints (like Android `Color`), `edge_type` a
+/// `MACaptionAppearanceTextEdgeStyle`; `font_family` 0 = regular font, 1–7
+/// the FCC generic families in `MACaptionAppearanceFontStyle` order.
 #[no_mangle]
+#[allow(clippy::too_many_arguments)]
 pub extern "C" fn rustplayer_player_set_subtitle_style(
     handle: *mut c_void,
     text_argb: i32,
     outline_argb: i32,
     size_scale: f32,
+    background_argb: i32,
+    window_argb: i32,
+    edge_type: i32,
+    font_family: i32,
+) {
+    let Some(h) = (unsafe { handle_ref(handle) }) else {
+        return;
+    };
+    let style = subtitle_style(
+        text_argb,
+        outline_argb,
+        size_scale,
+        background_argb,
+        window_argb,
+        edge_type,
+        font_family,
+    )
+    .sanitised();
+    h.bridge.player().set_subtitle_style(style);
+}
+
+/// Style of the secondary (dual) subtitle track; `stacked` puts it right
+/// above the primary cues instead of at the top of the picture.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn rustplayer_player_set_secondary_subtitle_style(
+    handle: *mut c_void,
+    text_argb: i32,
+    outline_argb: i32,
+    size_scale: f32,
+    background_argb: i32,
+    window_argb: i32,
+    edge_type: i32,
+    font_family: i32,
+    stacked: bool,
 ) {
     let Some(h) = (unsafe { handle_ref(handle) }) else {
         return;
     };
-    fn argb_to_rgba(c: i32) -> [u8; 4] {
-        let c = c as u32;
-        [
-            ((c >> 16) & 0xff) as u8,
-            ((c >> 8) & 0xff) as u8,
-            (c & 0xff) as u8,
-            ((c >> 24) & 0xff) as u8,
-        ]
-    }
-    let style = SubtitleStyle {
+    let style = subtitle_style(
+        text_argb,
+        outline_argb,
+        size_scale,
+        background_argb,
+        window_argb,
+        edge_type,
+        font_family,
+    );
+    let position = if stacked {
+        SecondarySubtitlePosition::Stacked
+    } else {
+        SecondarySubtitlePosition::Top
+    };
+    h.bridge
+        .player()
+        .set_secondary_subtitle_style(style, position);
+}
+
+/// Font for one generic caption family (`font_family` 1–7 of the style
+/// setters) from `len` bytes at `data` (TTF/OTF, e.g. bundled with the
+/// app). False on invalid bytes or family 0 (the regular font).
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_subtitle_family_font(
+    handle: *mut c_void,
+    font_family: i32,
+    data: *const u8,
+    len: usize,
+) -> bool {
+    let Some(h) = (unsafe { handle_ref(handle) }) else {
+        return false;
+    };
+    if data.is_null() {
+        return false;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) }.to_vec();
+    let family = SubtitleFontFamily::from_index(font_family);
+    match h.bridge.player().set_subtitle_family_font(family, bytes) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("subtitle family font rejected: {}", e);
+            false
+        }
+    }
+}
+
+/// The style setters' arguments as a [`SubtitleStyle`].
+fn subtitle_style(
+    text_argb: i32,
+    outline_argb: i32,
+    size_scale: f32,
+    background_argb: i32,
+    window_argb: i32,
+    edge_type: i32,
+    font_family: i32,
+) -> SubtitleStyle {
+    SubtitleStyle {
         text_color: argb_to_rgba(text_argb),
         outline_color: argb_to_rgba(outline_argb),
         size_scale,
+        background_color: argb_to_rgba(background_argb),
+        window_color: argb_to_rgba(window_argb),
+        edge_style: SubtitleEdgeStyle::from_text_edge_style(edge_type),
+        font_family: SubtitleFontFamily::from_index(font_family),
     }
-    .sanitised();
-    h.bridge.player().set_subtitle_style(style);
+}
+
+fn argb_to_rgba(c: i32) -> [u8; 4] {
+    let c = c as u32;
+    [
+        ((c >> 16) & 0xff) as u8,
+        ((c >> 8) & 0xff) as u8,
+        (c & 0xff) as u8,
+        ((c >> 24) & 0xff) as u8,
+    ]
 }
 
 #[no_mangle]
@@ -424,6 +621,16 @@ pub extern "C" fn rustplayer_player_set_subtitle_safe_inset_bottom(handle: *mut
     }
 }
 
+/// Audio-only mode: `false` stops video download/decode (background
+/// listening — no GPU work while backgrounded), `true` resumes video at the
+/// current position.
+#[no_mangle]
+pub extern "C" fn rustplayer_player_set_video_enabled(handle: *mut c_void, enabled: bool) {
+    if let Some(h) = unsafe { handle_ref(handle) } {
+        h.bridge.set_video_enabled(enabled);
+    }
+}
+
 /// Debug/compat: force HDR (PQ/HLG) video to decode to an 8-bit
 /// destination. The in-player HDR→SDR tonemap still runs — colours stay
 /// correct, at 8-bit quantization of the PQ signal. Sampled at decoder
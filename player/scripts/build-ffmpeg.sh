@@ -5,7 +5,10 @@
 #
 # What the player actually uses (grep ffmpeg_audio.rs / ffmpeg_hw.rs):
 #   - libavcodec   decoders: h264, hevc (video — software fallback);
-#                            aac, ac3, eac3 (audio — Win/Linux full
+#                            av1 + libdav1d (Win/Linux — hwaccel, or
+#                            dav1d when the GPU has no AV1 block);
+#                            vp9 (Win/Linux — hwaccel only);
+#                            aac, ac3, eac3, opus, flac (audio — Win/Linux full
 #                            path, macOS audio-only since video is
 #                            VTDecompressionSession)
 #   - libavcodec   parsers:  h264, hevc, aac, ac3
@@ -15,8 +18,8 @@
 #   - libswresample audio resampling (AVCodec → cpal output)
 #   - libavfilter  linked, no filter graphs run
 # Disabled: postproc (GPL trigger), avdevice (no system I/O), all
-# external codec libs (x264/x265/vpx/aom/dav1d/…). No transitive deps,
-# bundle stays ~5–10 MB of shared objects per platform.
+# external codec libs except dav1d on Win/Linux (x264/x265/vpx/aom/…).
+# Bundle stays ~5–10 MB of shared objects per platform, plus libdav1d.
 #
 # Usage: build-ffmpeg.sh <linux | windows | macos-x64 | macos-arm64
 #                         | ios-arm64 | ios-sim-arm64 | ios-sim-x64>
@@ -29,8 +32,10 @@
 # builds stay shared + full (video software fallback + hwaccel).
 #
 # Runner pre-reqs:
-#   linux:       gcc, make, nasm, pkg-config, curl, xz-utils, libva-dev
+#   linux:       gcc, make, nasm, pkg-config, curl, xz-utils, libva-dev,
+#                libdav1d-dev
 #   windows:     msys64 mingw-w64-x86_64-toolchain + mingw-w64-x86_64-nasm
+#                + mingw-w64-x86_64-dav1d
 #                + pkg-config + make; run through msys64 bash:
 #                `C:\msys64\usr\bin\bash.exe -lc './scripts/build-ffmpeg.sh windows'`
 #   macos / ios: Xcode (full, for the iOS SDKs), brew install nasm pkg-config
@@ -44,7 +49,7 @@ ROOT="$(cd "$(dirname "$0")/.." && pwd)"
 # Defaults (desktop): shared libs, full decoder/parser set. iOS overrides these
 # to static + audio-only below.
 LINK_KIND=(--enable-shared --disable-static)
-DECODERS="h264,hevc,aac,ac3,eac3"
+DECODERS="h264,hevc,aac,ac3,eac3,opus,flac"
 PARSERS="h264,hevc,aac,ac3"
 
 case "$PLATFORM" in
@@ -55,9 +60,13 @@ case "$PLATFORM" in
     # requires libva-dev at build time + libva.so.2 at runtime
     # (usually pre-installed alongside any Intel/AMD GPU driver).
     # With --disable-autodetect we have to opt in explicitly.
+    # AV1: native decoder for av1_vaapi, libdav1d (libdav1d-dev) for
+    # GPUs without an AV1 decode block. VP9 is hwaccel-only.
+    DECODERS="$DECODERS,av1,libdav1d,vp9"
     EXTRA=(
       --enable-vaapi
-      --enable-hwaccel=h264_vaapi,hevc_vaapi
+      --enable-libdav1d
+      --enable-hwaccel=h264_vaapi,hevc_vaapi,av1_vaapi,vp9_vaapi
     )
     ;;
   windows)
@@ -74,6 +83,10 @@ case "$PLATFORM" in
     # Player crate uses D3D11VA via AV_HWDEVICE_TYPE_D3D11VA. DXVA2 is
     # bundled too because some FFmpeg internal helpers cross-reference
     # DXVA2 paths even when D3D11VA is the decode target.
+    # AV1: native decoder for the d3d11va hwaccels, libdav1d
+    # (mingw-w64-x86_64-dav1d) for GPUs without an AV1 decode block.
+    # VP9 is hwaccel-only.
+    DECODERS="$DECODERS,av1,libdav1d,vp9"
     EXTRA=(
       --target-os=mingw64
       --arch=x86_64
@@ -81,7 +94,8 @@ case "$PLATFORM" in
       --disable-pthreads
       --enable-d3d11va
       --enable-dxva2
-      --enable-hwaccel=h264_d3d11va,hevc_d3d11va,h264_d3d11va2,hevc_d3d11va2,h264_dxva2,hevc_dxva2
+      --enable-libdav1d
+      --enable-hwaccel=h264_d3d11va,hevc_d3d11va,h264_d3d11va2,hevc_d3d11va2,h264_dxva2,hevc_dxva2,av1_d3d11va,av1_d3d11va2,av1_dxva2,vp9_d3d11va,vp9_d3d11va2,vp9_dxva2
       --enable-bsf=h264_mp4toannexb,hevc_mp4toannexb
       "--extra-ldflags=-static-libgcc -static-libstdc++ -Wl,-Bstatic -lwinpthread -Wl,-Bdynamic"
     )
@@ -128,7 +142,7 @@ case "$PLATFORM" in
     # iOS: STATIC libs (an app can't load arbitrary dylibs without embedding +
     # signing a framework) and AUDIO-ONLY (video = native VideoToolbox).
     LINK_KIND=(--enable-static --disable-shared)
-    DECODERS="aac,ac3,eac3"
+    DECODERS="aac,ac3,eac3,opus,flac"
     PARSERS="aac,ac3"
     case "$PLATFORM" in
       ios-arm64)     SDK=iphoneos;        ARCH=arm64;  MINVER="-miphoneos-version-min=15.0" ;;
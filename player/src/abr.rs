@@ -269,6 +269,20 @@ mod tests {
         assert_eq!(idx, vec![1, 2]);
     }
 
+    #[test]
+    fn avc_ladder_is_eligible_and_depth_aware() {
+        let reps = vec![
+            make_rep(1, "avc1.42c01e", false, false),
+            make_rep(2, "avc1.4d401f", false, false),
+            make_rep(3, "avc1.640028", false, false),
+            make_rep(4, "avc1.6e0028", false, false),
+        ];
+        assert_eq!(AbrVideoProfile::Adaptive.filter_indices(&reps), vec![0, 1, 2, 3]);
+        // High 10 (profile_idc 0x6E) is the only 10-bit rung.
+        assert_eq!(AbrVideoProfile::LockedDepth(8).filter_indices(&reps), vec![0, 1, 2]);
+        assert_eq!(AbrVideoProfile::LockedDepth(10).filter_indices(&reps), vec![3]);
+    }
+
     #[test]
     fn dv_profile_5_never_eligible() {
         let reps = vec![
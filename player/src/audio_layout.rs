@@ -0,0 +1,341 @@
+//! Speaker layouts and the channel remix between decoded audio and the
+//! output device.
+//!
+//! Decoders hand PCM to the sink in the DEVICE's channel count and order.
+//! Each decoder first brings its output to the source layout for its
+//! channel count (FFmpeg via swresample, MediaCodec natively — both use the
+//! WAVE / SMPTE order), then a [`ChannelRemixer`] maps that onto the device
+//! layout: channels the device has are routed 1:1 (reordered where the
+//! platform's order differs, e.g. ALSA 5.1), channels it lacks are folded
+//! into the nearest speakers with the [`DownmixCoefficients`] the host
+//! configured via `Player::set_downmix`.
+
+/// Gains used when a source channel has no matching device speaker — most
+/// commonly 5.1 / 7.1 folded onto a stereo device. Linear amplitude, not
+/// dB. The player consumes these without persisting; the host owns the
+/// user's setting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DownmixCoefficients {
+    /// Centre channel into front left / right. ITU-R BS.775 uses -3 dB.
+    pub center: f32,
+    /// Surround (side / back) channels into the front of the same side.
+    /// ITU-R BS.775 uses -3 dB.
+    pub surround: f32,
+    /// LFE into the mains. 0 by default — ITU drops LFE on downmix, and
+    /// bass-managed content already carries its low end in the mains.
+    pub lfe: f32,
+}
+
+impl DownmixCoefficients {
+    /// ITU-R BS.775 stereo downmix (-3 dB centre and surrounds, no LFE).
+    pub const DEFAULT: Self = Self {
+        center: std::f32::consts::FRAC_1_SQRT_2,
+        surround: std::f32::consts::FRAC_1_SQRT_2,
+        lfe: 0.0,
+    };
+
+    /// Clamp into [0, 1]. Called by `Player::set_downmix`; gains above
+    /// unity only add clipping, since the folded sum is already clamped.
+    pub fn sanitised(self) -> Self {
+        let unit = |v: f32| if v.is_finite() { v.clamp(0.0, 1.0) } else { 0.0 };
+        Self {
+            center: unit(self.center),
+            surround: unit(self.surround),
+            lfe: unit(self.lfe),
+        }
+    }
+}
+
+impl Default for DownmixCoefficients {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Speaker {
+    FrontLeft,
+    FrontRight,
+    FrontCenter,
+    Lfe,
+    BackLeft,
+    BackRight,
+    BackCenter,
+    SideLeft,
+    SideRight,
+}
+
+use Speaker::*;
+
+/// Highest channel count the remix handles; decoders fold anything wider
+/// (e.g. 7.1.4) down to 7.1 before remixing.
+pub(crate) const MAX_CHANNELS: usize = 8;
+
+/// Channels the decoders produce for a `device_channels`-wide output. The
+/// remix tops out at [`MAX_CHANNELS`], so a wider device (a 10-channel
+/// interface, a 7.1.4 HDMI sink) is fed 7.1 in its first eight channels and
+/// silence in the rest — see [`fill_device_frames`].
+pub(crate) fn stream_channels(device_channels: u16) -> u16 {
+    device_channels.clamp(1, MAX_CHANNELS as u16)
+}
+
+/// Fill the interleaved `device_channels`-wide frames of `out` from
+/// `stream_channels`-wide source frames pulled sample by sample from `next`
+/// (`None` = underrun, played as silence), scaled by `gain`. Device
+/// channels past `stream_channels` stay silent, so the source stride and
+/// the device's consumption rate stay in step. Returns the source samples
+/// consumed.
+pub(crate) fn fill_device_frames(
+    out: &mut [f32],
+    device_channels: usize,
+    stream_channels: usize,
+    gain: f32,
+    mut next: impl FnMut() -> Option<f32>,
+) -> u64 {
+    let mut consumed = 0u64;
+    for frame in out.chunks_mut(device_channels.max(1)) {
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            let source = if ch < stream_channels { next() } else { None };
+            *sample = match source {
+                Some(s) => {
+                    consumed += 1;
+                    s * gain
+                }
+                None => 0.0,
+            };
+        }
+    }
+    consumed
+}
+
+/// Order of an N-channel decoded frame: WAVE / SMPTE, which is both the
+/// native order of the FFmpeg layouts the resampler targets and the order
+/// MediaCodec emits PCM in.
+pub(crate) fn source_layout(channels: usize) -> &'static [Speaker] {
+    match channels {
+        0 | 1 => &[FrontCenter],
+        2 => &[FrontLeft, FrontRight],
+        3 => &[FrontLeft, FrontRight, FrontCenter],
+        4 => &[FrontLeft, FrontRight, BackLeft, BackRight],
+        5 => &[FrontLeft, FrontRight, FrontCenter, BackLeft, BackRight],
+        6 => &[FrontLeft, FrontRight, FrontCenter, Lfe, BackLeft, BackRight],
+        7 => &[FrontLeft, FrontRight, FrontCenter, Lfe, BackCenter, SideLeft, SideRight],
+        _ => &[FrontLeft, FrontRight, FrontCenter, Lfe, BackLeft, BackRight, SideLeft, SideRight],
+    }
+}
+
+/// Order the output device expects for an N-channel stream. WASAPI and
+/// CoreAudio use WAVE order; ALSA puts the rears before centre / LFE.
+pub(crate) fn device_layout(channels: usize) -> &'static [Speaker] {
+    #[cfg(target_os = "linux")]
+    match channels {
+        5 => return &[FrontLeft, FrontRight, BackLeft, BackRight, FrontCenter],
+        6 => return &[FrontLeft, FrontRight, BackLeft, BackRight, FrontCenter, Lfe],
+        8 => {
+            return &[FrontLeft, FrontRight, BackLeft, BackRight, FrontCenter, Lfe, SideLeft, SideRight]
+        }
+        _ => {}
+    }
+    source_layout(channels)
+}
+
+/// Per-frame matrix from a source layout to a device layout. Built once per
+/// (input channels, output channels) pair; `process` is a plain
+/// multiply-accumulate over interleaved frames.
+pub(crate) struct ChannelRemixer {
+    in_ch: usize,
+    out_ch: usize,
+    /// Row-major `out_ch × in_ch`.
+    matrix: Vec<f32>,
+    /// True when the output is a pure reorder / copy (no folding), so no
+    /// clamp is needed.
+    no_fold: bool,
+}
+
+impl ChannelRemixer {
+    pub(crate) fn new(in_ch: usize, out_ch: usize, coeffs: DownmixCoefficients) -> Self {
+        let src = source_layout(in_ch.clamp(1, MAX_CHANNELS));
+        let dst = device_layout(out_ch.clamp(1, MAX_CHANNELS));
+        let (in_ch, out_ch) = (src.len(), dst.len());
+        let mut matrix = vec![0.0_f32; out_ch * in_ch];
+        let pos = |s: Speaker| dst.iter().position(|&d| d == s);
+        let mut no_fold = true;
+
+        for (i, &s) in src.iter().enumerate() {
+            if let Some(o) = pos(s) {
+                matrix[o * in_ch + i] = 1.0;
+                continue;
+            }
+            no_fold = false;
+            let mut add = |target: Speaker, gain: f32| {
+                if let Some(o) = pos(target) {
+                    matrix[o * in_ch + i] += gain;
+                }
+            };
+            // A mono source isn't a centre channel in a mix — play it at
+            // full level on both fronts rather than -3 dB.
+            let center = if in_ch == 1 { 1.0 } else { coeffs.center };
+            let fronts = pos(FrontLeft).is_some();
+            match s {
+                FrontCenter => {
+                    add(FrontLeft, center);
+                    add(FrontRight, center);
+                }
+                FrontLeft | FrontRight => add(FrontCenter, 0.5),
+                Lfe if fronts => {
+                    add(FrontLeft, coeffs.lfe);
+                    add(FrontRight, coeffs.lfe);
+                }
+                Lfe => add(FrontCenter, coeffs.lfe * 0.5),
+                BackLeft | SideLeft | BackRight | SideRight => {
+                    let left = matches!(s, BackLeft | SideLeft);
+                    let twin = match s {
+                        BackLeft => SideLeft,
+                        SideLeft => BackLeft,
+                        BackRight => SideRight,
+                        _ => BackRight,
+                    };
+                    let front = if left { FrontLeft } else { FrontRight };
+                    if pos(twin).is_some() {
+                        add(twin, 1.0);
+                    } else if fronts {
+                        add(front, coeffs.surround);
+                    } else {
+                        add(FrontCenter, coeffs.surround * 0.5);
+                    }
+                }
+                BackCenter => {
+                    let half = std::f32::consts::FRAC_1_SQRT_2;
+                    if pos(BackLeft).is_some() {
+                        add(BackLeft, half);
+                        add(BackRight, half);
+                    } else if pos(SideLeft).is_some() {
+                        add(SideLeft, half);
+                        add(SideRight, half);
+                    } else if fronts {
+                        add(FrontLeft, coeffs.surround * half);
+                        add(FrontRight, coeffs.surround * half);
+                    } else {
+                        add(FrontCenter, coeffs.surround * 0.5);
+                    }
+                }
+            }
+        }
+        Self { in_ch, out_ch, matrix, no_fold }
+    }
+
+    /// Remix interleaved `input` (in the source layout) to interleaved
+    /// device-layout samples.
+    pub(crate) fn process(&self, input: &[f32]) -> Vec<f32> {
+        let frames = input.len() / self.in_ch;
+        let mut out = Vec::with_capacity(frames * self.out_ch);
+        for frame in input.chunks_exact(self.in_ch) {
+            for row in self.matrix.chunks_exact(self.in_ch) {
+                let s: f32 = row.iter().zip(frame).map(|(g, x)| g * x).sum();
+                // Folding can exceed ±1.0 on hot surround sources.
+                out.push(if self.no_fold { s } else { s.clamp(-1.0, 1.0) });
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATT: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+    fn approx(a: &[f32], b: &[f32]) {
+        assert_eq!(a.len(), b.len(), "{a:?} vs {b:?}");
+        for (x, y) in a.iter().zip(b) {
+            assert!((x - y).abs() < 1e-5, "{a:?} vs {b:?}");
+        }
+    }
+
+    #[test]
+    fn stereo_to_stereo_is_identity() {
+        let r = ChannelRemixer::new(2, 2, DownmixCoefficients::DEFAULT);
+        approx(&r.process(&[0.1, -0.2, 0.3, 0.4]), &[0.1, -0.2, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn five_one_to_stereo_uses_coefficients() {
+        // FL FR FC LFE BL BR
+        let frame = [0.1, 0.2, 0.3, 0.9, 0.4, 0.5];
+        let r = ChannelRemixer::new(6, 2, DownmixCoefficients::DEFAULT);
+        approx(&r.process(&frame), &[0.1 + ATT * 0.3 + ATT * 0.4, 0.2 + ATT * 0.3 + ATT * 0.5]);
+
+        let custom = DownmixCoefficients { center: 1.0, surround: 0.5, lfe: 0.1 };
+        let r = ChannelRemixer::new(6, 2, custom);
+        approx(&r.process(&frame), &[0.1 + 0.3 + 0.09 + 0.2, 0.2 + 0.3 + 0.09 + 0.25]);
+    }
+
+    #[test]
+    fn fold_is_clamped() {
+        let r = ChannelRemixer::new(6, 2, DownmixCoefficients::DEFAULT);
+        let out = r.process(&[1.0; 6]);
+        approx(&out, &[1.0, 1.0]);
+    }
+
+    #[test]
+    fn mono_source_plays_full_level_on_both_fronts() {
+        let r = ChannelRemixer::new(1, 2, DownmixCoefficients::DEFAULT);
+        approx(&r.process(&[0.5]), &[0.5, 0.5]);
+    }
+
+    #[test]
+    fn stereo_to_mono_device_averages() {
+        let r = ChannelRemixer::new(2, 1, DownmixCoefficients::DEFAULT);
+        approx(&r.process(&[0.2, 0.6]), &[0.4]);
+    }
+
+    #[test]
+    fn seven_one_to_five_one_folds_sides_into_backs() {
+        // FL FR FC LFE BL BR SL SR
+        let r = ChannelRemixer::new(8, 6, DownmixCoefficients::DEFAULT);
+        let out = r.process(&[0.1, 0.2, 0.3, 0.4, 0.05, 0.06, 0.1, 0.2]);
+        let dst = device_layout(6);
+        let at = |s| out[dst.iter().position(|&d| d == s).unwrap()];
+        assert!((at(FrontCenter) - 0.3).abs() < 1e-6);
+        assert!((at(Lfe) - 0.4).abs() < 1e-6);
+        assert!((at(BackLeft) - 0.15).abs() < 1e-6);
+        assert!((at(BackRight) - 0.26).abs() < 1e-6);
+    }
+
+    #[test]
+    fn five_one_reorders_onto_device_layout() {
+        let r = ChannelRemixer::new(6, 6, DownmixCoefficients::DEFAULT);
+        let src = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0].map(|v| v / 10.0);
+        let out = r.process(&src);
+        for (o, s) in device_layout(6).iter().enumerate() {
+            let i = source_layout(6).iter().position(|x| x == s).unwrap();
+            assert_eq!(out[o], src[i]);
+        }
+    }
+
+    #[test]
+    fn ten_channel_device_gets_eight_channels_and_silence() {
+        assert_eq!(stream_channels(10), 8);
+        assert_eq!(stream_channels(0), 1);
+        let src: Vec<f32> = (0..16).map(|v| v as f32).collect();
+        let mut feed = src.iter().copied();
+        let mut out = [9.0_f32; 20];
+        let consumed = fill_device_frames(&mut out, 10, 8, 0.5, || feed.next());
+        assert_eq!(consumed, 16);
+        for (frame, src) in out.chunks(10).zip(src.chunks(8)) {
+            let scaled: Vec<f32> = src.iter().map(|v| v * 0.5).collect();
+            approx(&frame[..8], &scaled);
+            assert_eq!(frame[8..], [0.0, 0.0]);
+        }
+        // Underrun mid-buffer: silence, nothing counted.
+        let consumed = fill_device_frames(&mut out, 10, 8, 1.0, || None);
+        assert_eq!((consumed, out), (0, [0.0; 20]));
+    }
+
+    #[test]
+    fn sanitised_clamps_and_rejects_nan() {
+        let c = DownmixCoefficients { center: 2.0, surround: -1.0, lfe: f32::NAN }.sanitised();
+        assert_eq!(c, DownmixCoefficients { center: 1.0, surround: 0.0, lfe: 0.0 });
+    }
+}
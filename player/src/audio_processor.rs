@@ -0,0 +1,282 @@
+//! Host-supplied audio effects on the PCM path.
+//!
+//! Apps insert their own processing (EQ presets, dialogue enhancement,
+//! visualizer taps) via `Player::add_audio_processor`. The chain runs in
+//! `audio_sync_loop` on the final PCM — after the time stretch and the
+//! loudness stage, right before `AudioSink::put_samples` — so a tap sees
+//! exactly what the listener hears. Processors run in insertion order.
+//! Bitstream passthrough has no PCM to process, so the chain is skipped
+//! (and its latency dropped) while it is engaged.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+
+/// A host audio effect. Called from the audio sync task with interleaved
+/// `f32` frames in the device's channel count and order (see
+/// `audio_layout::device_layout`); keep `process` real-time friendly — no
+/// blocking, no unbounded allocation — or the output underruns.
+pub trait AudioProcessor: Send + 'static {
+    /// Called before the first `process` and whenever the output format
+    /// changes (a device switch). Size buffers / recompute filters here.
+    fn configure(&mut self, _sample_rate: u32, _channels: u16) {}
+
+    /// Process interleaved samples in place. `samples.len()` is a whole
+    /// number of frames; the block size varies from call to call.
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32, channels: u16);
+
+    /// Delay this processor adds, in frames at the configured rate
+    /// (lookahead limiters, linear-phase filters). Summed over the chain
+    /// and folded into `AudioSink::output_latency_ms`, so video stays in
+    /// sync with the delayed audio.
+    fn latency_frames(&self) -> u32 {
+        0
+    }
+
+    /// Drop internal state (filter memories, delay lines). Called on every
+    /// pipeline (re)build — seek, track switch — since the audio that
+    /// follows isn't continuous with what came before.
+    fn reset(&mut self) {}
+}
+
+/// Handle returned by `Player::add_audio_processor`, for removal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AudioProcessorId(u64);
+
+/// One processor plus the format it was last configured for (`None` until
+/// its first block). Locked only by the audio sync loop, so uncontended.
+struct Slot {
+    processor: Box<dyn AudioProcessor>,
+    format: Option<(u32, u16)>,
+}
+
+/// An immutable snapshot of the chain, swapped whole on add / remove.
+#[derive(Default)]
+struct Snapshot {
+    entries: Vec<(AudioProcessorId, Arc<Mutex<Slot>>)>,
+    /// Set for a fresh snapshot: the next `process` recomputes the latency.
+    latency_stale: AtomicBool,
+}
+
+/// The player's processor list. Shared by successive pipelines; the audio
+/// sync loop runs it on each block. The loop reads a snapshot, so a host
+/// adding or removing processors never waits for `process` callbacks, and
+/// the chain latency is only recomputed when the list or format changes.
+#[derive(Default)]
+pub(crate) struct AudioProcessorChain {
+    snapshot: ArcSwap<Snapshot>,
+    /// Next id; also serialises add / remove / clear.
+    next_id: Mutex<u64>,
+    latency_us: AtomicU64,
+}
+
+impl AudioProcessorChain {
+    pub(crate) fn add(&self, processor: Box<dyn AudioProcessor>) -> AudioProcessorId {
+        let mut next_id = self.next_id.lock().unwrap();
+        let id = AudioProcessorId(*next_id);
+        *next_id += 1;
+        let slot = Slot {
+            processor,
+            format: None,
+        };
+        let mut entries = self.snapshot.load().entries.clone();
+        entries.push((id, Arc::new(Mutex::new(slot))));
+        self.publish(entries);
+        id
+    }
+
+    pub(crate) fn remove(&self, id: AudioProcessorId) -> bool {
+        let _writer = self.next_id.lock().unwrap();
+        let mut entries = self.snapshot.load().entries.clone();
+        let before = entries.len();
+        entries.retain(|(e, _)| *e != id);
+        let removed = entries.len() != before;
+        if removed {
+            self.publish(entries);
+        }
+        removed
+    }
+
+    pub(crate) fn clear(&self) {
+        let _writer = self.next_id.lock().unwrap();
+        self.publish(Vec::new());
+    }
+
+    fn publish(&self, entries: Vec<(AudioProcessorId, Arc<Mutex<Slot>>)>) {
+        self.snapshot.store(Arc::new(Snapshot {
+            entries,
+            latency_stale: AtomicBool::new(true),
+        }));
+    }
+
+    /// Reset every processor for a discontinuity (new pipeline).
+    pub(crate) fn reset(&self) {
+        for (_, slot) in self.snapshot.load().entries.iter() {
+            slot.lock().unwrap().processor.reset();
+        }
+    }
+
+    /// Run the chain over `samples`. `None` = no processors, play the input
+    /// as-is. Also refreshes `latency_us` after a chain or format change.
+    pub(crate) fn process(
+        &self,
+        samples: &[f32],
+        sample_rate: u32,
+        channels: u16,
+    ) -> Option<Vec<f32>> {
+        let snapshot = self.snapshot.load();
+        let mut latency_stale = snapshot.latency_stale.swap(false, Ordering::Relaxed);
+        if snapshot.entries.is_empty() {
+            if latency_stale {
+                self.latency_us.store(0, Ordering::Relaxed);
+            }
+            return None;
+        }
+        let format = (sample_rate, channels);
+        let mut out = samples.to_vec();
+        for (_, slot) in snapshot.entries.iter() {
+            let mut slot = slot.lock().unwrap();
+            if slot.format != Some(format) {
+                slot.processor.configure(sample_rate, channels);
+                slot.format = Some(format);
+                latency_stale = true;
+            }
+            slot.processor.process(&mut out, sample_rate, channels);
+        }
+        if latency_stale {
+            let frames: u64 = snapshot
+                .entries
+                .iter()
+                .map(|(_, slot)| u64::from(slot.lock().unwrap().processor.latency_frames()))
+                .sum();
+            let us = frames * 1_000_000 / u64::from(sample_rate.max(1));
+            self.latency_us.store(us, Ordering::Relaxed);
+        }
+        Some(out)
+    }
+
+    /// Total delay of the chain as of the last `process`, in µs.
+    pub(crate) fn latency_us(&self) -> u64 {
+        self.latency_us.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    struct Gain {
+        gain: f32,
+        latency: u32,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl AudioProcessor for Gain {
+        fn configure(&mut self, sample_rate: u32, channels: u16) {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("configure {sample_rate} {channels}"));
+        }
+        fn process(&mut self, samples: &mut [f32], _sample_rate: u32, _channels: u16) {
+            samples.iter_mut().for_each(|s| *s *= self.gain);
+        }
+        fn latency_frames(&self) -> u32 {
+            self.latency
+        }
+        fn reset(&mut self) {
+            self.log.lock().unwrap().push("reset".into());
+        }
+    }
+
+    fn gain(gain: f32, latency: u32, log: &Arc<Mutex<Vec<String>>>) -> Box<dyn AudioProcessor> {
+        Box::new(Gain {
+            gain,
+            latency,
+            log: Arc::clone(log),
+        })
+    }
+
+    #[test]
+    fn empty_chain_is_bypassed() {
+        let chain = AudioProcessorChain::default();
+        assert_eq!(chain.process(&[0.5, 0.5], 48_000, 2), None);
+        assert_eq!(chain.latency_us(), 0);
+    }
+
+    #[test]
+    fn runs_in_order_and_sums_latency() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let chain = AudioProcessorChain::default();
+        chain.add(gain(0.5, 480, &log));
+        let second = chain.add(gain(3.0, 960, &log));
+        assert_eq!(
+            chain.process(&[0.2, -0.4], 48_000, 2),
+            Some(vec![0.3, -0.6])
+        );
+        assert_eq!(chain.latency_us(), 30_000);
+
+        assert!(chain.remove(second));
+        assert!(!chain.remove(second));
+        assert_eq!(
+            chain.process(&[0.2, -0.4], 48_000, 2),
+            Some(vec![0.1, -0.2])
+        );
+        assert_eq!(chain.latency_us(), 10_000);
+        chain.clear();
+        assert_eq!(chain.process(&[0.2, -0.4], 48_000, 2), None);
+        assert_eq!(chain.latency_us(), 0);
+    }
+
+    /// Adds a processor from inside its own `process`, which would deadlock
+    /// if the chain were locked around the callbacks.
+    struct AddsFromProcess {
+        chain: Arc<AudioProcessorChain>,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl AudioProcessor for AddsFromProcess {
+        fn process(&mut self, _samples: &mut [f32], _sample_rate: u32, _channels: u16) {
+            self.chain.add(gain(2.0, 0, &self.log));
+        }
+    }
+
+    #[test]
+    fn host_calls_do_not_wait_for_process() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let chain = Arc::new(AudioProcessorChain::default());
+        let id = chain.add(Box::new(AddsFromProcess {
+            chain: Arc::clone(&chain),
+            log: Arc::clone(&log),
+        }));
+        assert_eq!(chain.process(&[0.25], 48_000, 1), Some(vec![0.25]));
+        assert!(chain.remove(id));
+        assert_eq!(chain.process(&[0.25], 48_000, 1), Some(vec![0.5]));
+    }
+
+    #[test]
+    fn configures_on_add_and_format_change() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let chain = AudioProcessorChain::default();
+        chain.add(gain(1.0, 0, &log));
+        chain.process(&[0.0; 4], 48_000, 2);
+        chain.process(&[0.0; 4], 48_000, 2);
+        chain.add(gain(1.0, 0, &log));
+        chain.process(&[0.0; 4], 48_000, 2);
+        chain.reset();
+        chain.process(&[0.0; 6], 44_100, 6);
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "configure 48000 2",
+                "configure 48000 2",
+                "reset",
+                "reset",
+                "configure 44100 6",
+                "configure 44100 6",
+            ]
+        );
+    }
+}
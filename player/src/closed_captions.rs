@@ -0,0 +1,371 @@
+//! Closed captions embedded in the video stream: CEA-608 / CEA-708 data in
+//! the SEI of H.264 / HEVC access units (ATSC A/53 `cc_data`).
+//!
+//! Each video pipeline gets a `CaptionFeed` from `ClosedCaptions::restart`
+//! and `video_decoder_task` hands it every prepared segment on its blocking
+//! prepare thread, before the samples reach the codec. The caption decoders (`parsers::cea608`,
+//! `parsers::cea708`) turn the byte pairs into cue updates that land in a
+//! per-player log; `text_play` relays the selected service's updates to the
+//! overlay or the host like any downloaded track. A service seen for the
+//! first time joins the track list (`Tracks::add_caption_track`) and is
+//! announced with `PlayerEvent::TextTracksChanged`.
+
+use crate::events::PlayerEvent;
+use crate::parsers::cea608::Cea608Decoder;
+use crate::parsers::cea708::Cea708Decoder;
+use crate::parsers::vtt::VttCue;
+use crate::parsers::{avc, hevc};
+use crate::tracks::text::CaptionService;
+use crate::tracks::Tracks;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::futures::Notified;
+use tokio::sync::{broadcast, Notify};
+
+/// Cue updates kept for relays; one that falls further behind skips ahead.
+const MAX_LOG: usize = 5000;
+
+struct State {
+    field1: Cea608Decoder,
+    field2: Cea608Decoder,
+    dtvcc: Cea708Decoder,
+    /// Cue updates in arrival order; entry `i` has sequence number
+    /// `base + i`.
+    log: VecDeque<(CaptionService, VttCue)>,
+    base: u64,
+    /// Services that produced a cue since the last `clear`.
+    found: Vec<CaptionService>,
+    /// PTS of the last byte pair fed: where `restart` closes open cues.
+    last_pts_ms: i64,
+    /// Bumped by `restart`; only the newest `CaptionFeed` is decoded.
+    generation: u64,
+}
+
+impl State {
+    fn new(base: u64) -> Self {
+        State {
+            field1: Cea608Decoder::new(),
+            field2: Cea608Decoder::new(),
+            dtvcc: Cea708Decoder::new(),
+            log: VecDeque::new(),
+            base,
+            found: Vec::new(),
+            last_pts_ms: 0,
+            generation: 0,
+        }
+    }
+
+    /// Append decoder output; returns services never seen before.
+    fn push(&mut self, updates: Vec<(CaptionService, VttCue)>) -> Vec<CaptionService> {
+        let mut new = Vec::new();
+        for (service, cue) in updates {
+            if !self.found.contains(&service) {
+                self.found.push(service);
+                new.push(service);
+            }
+            self.log.push_back((service, cue));
+        }
+        while self.log.len() > MAX_LOG {
+            self.log.pop_front();
+            self.base += 1;
+        }
+        new
+    }
+
+    /// Decode one `cc_data` triplet at `pts_ms`.
+    fn feed(&mut self, pts_ms: i64, [head, b1, b2]: [u8; 3]) -> Vec<(CaptionService, VttCue)> {
+        self.last_pts_ms = pts_ms;
+        if head & 0x04 == 0 {
+            // cc_valid clear: padding.
+            return Vec::new();
+        }
+        match head & 0x03 {
+            cc_type @ (0 | 1) => {
+                let (decoder, first) = if cc_type == 0 {
+                    (&mut self.field1, 1)
+                } else {
+                    (&mut self.field2, 3)
+                };
+                let mut out = Vec::new();
+                decoder.feed(pts_ms, b1, b2, &mut out);
+                out.into_iter()
+                    .map(|(channel, cue)| (CaptionService::Cea608(first + channel as u8), cue))
+                    .collect()
+            }
+            cc_type => {
+                let mut out = Vec::new();
+                self.dtvcc.feed(pts_ms, cc_type, b1, b2, &mut out);
+                out.into_iter()
+                    .map(|(service, cue)| (CaptionService::Cea708(service), cue))
+                    .collect()
+            }
+        }
+    }
+
+    /// Close every open cue at the last fed PTS and reset the decoders.
+    fn flush(&mut self) -> Vec<(CaptionService, VttCue)> {
+        let pts_ms = self.last_pts_ms;
+        let (mut field1, mut field2, mut dtvcc) = (Vec::new(), Vec::new(), Vec::new());
+        self.field1.flush(pts_ms, &mut field1);
+        self.field2.flush(pts_ms, &mut field2);
+        self.dtvcc.flush(pts_ms, &mut dtvcc);
+        let cea608 = |first: u8| {
+            move |(channel, cue): (usize, VttCue)| {
+                (CaptionService::Cea608(first + channel as u8), cue)
+            }
+        };
+        field1
+            .into_iter()
+            .map(cea608(1))
+            .chain(field2.into_iter().map(cea608(3)))
+            .chain(
+                dtvcc
+                    .into_iter()
+                    .map(|(service, cue)| (CaptionService::Cea708(service), cue)),
+            )
+            .collect()
+    }
+}
+
+/// Caption decoders + cue log. One per player, shared by the video
+/// pipeline (producer) and `text_play` (relay).
+pub(crate) struct ClosedCaptions {
+    state: Mutex<State>,
+    updated: Notify,
+    tracks: Arc<Mutex<Option<Tracks>>>,
+    events: Arc<broadcast::Sender<PlayerEvent>>,
+}
+
+impl ClosedCaptions {
+    pub(crate) fn new(
+        tracks: Arc<Mutex<Option<Tracks>>>,
+        events: Arc<broadcast::Sender<PlayerEvent>>,
+    ) -> Self {
+        ClosedCaptions {
+            state: Mutex::new(State::new(0)),
+            updated: Notify::new(),
+            tracks,
+            events,
+        }
+    }
+
+    /// Forget every cue and service — a new presentation. Sequence numbers
+    /// keep counting, so relays never re-read an old entry.
+    pub(crate) fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        let base = state.base + state.log.len() as u64;
+        let generation = state.generation + 1;
+        *state = State::new(base);
+        state.generation = generation;
+    }
+
+    /// A video pipeline (re)started, e.g. after a seek or ABR swap: the
+    /// byte stream starts over, so close what was on screen where it was
+    /// last fed, and hand out the feed for the new pipeline. Segments of an
+    /// older pipeline still draining are ignored from now on.
+    pub(crate) fn restart(self: &Arc<Self>, hevc: bool) -> CaptionFeed {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        let generation = state.generation;
+        let updates = state.flush();
+        if !updates.is_empty() {
+            state.push(updates);
+            drop(state);
+            self.updated.notify_waiters();
+        }
+        CaptionFeed {
+            captions: Arc::clone(self),
+            hevc,
+            generation,
+        }
+    }
+
+    fn feed_segment(&self, feed: &CaptionFeed, data: &[u8], samples: &[(usize, usize, i64, u64)]) {
+        let hevc = feed.hevc;
+        let mut triplets: Vec<(i64, Vec<[u8; 3]>)> = samples
+            .iter()
+            .filter(|&&(offset, size, _, _)| offset + size <= data.len())
+            .map(|&(offset, size, ts, ts_scale)| {
+                let pts_ms = if ts_scale > 0 {
+                    ts * 1000 / ts_scale as i64
+                } else {
+                    0
+                };
+                (pts_ms, sei_cc_data(hevc, &data[offset..offset + size]))
+            })
+            .filter(|(_, cc)| !cc.is_empty())
+            .collect();
+        if triplets.is_empty() {
+            return;
+        }
+        // Caption bytes follow presentation order, not decode order.
+        triplets.sort_by_key(|(pts_ms, _)| *pts_ms);
+
+        let mut state = self.state.lock().unwrap();
+        if state.generation != feed.generation {
+            return;
+        }
+        let mut updates = Vec::new();
+        for (pts_ms, cc) in triplets {
+            for triplet in cc {
+                updates.extend(state.feed(pts_ms, triplet));
+            }
+        }
+        if updates.is_empty() {
+            return;
+        }
+        let new = state.push(updates);
+        drop(state);
+        self.updated.notify_waiters();
+        for service in new {
+            let added = self
+                .tracks
+                .lock()
+                .unwrap()
+                .as_mut()
+                .and_then(|t| t.add_caption_track(service, ""));
+            if added.is_some() {
+                log::info!("[cc] found {} in the video stream", service.name());
+                let _ = self.events.send(PlayerEvent::TextTracksChanged);
+            }
+        }
+    }
+
+    /// Cue updates for `service` from sequence number `from` on, and the
+    /// number to continue from.
+    pub(crate) fn updates(&self, service: CaptionService, from: u64) -> (Vec<VttCue>, u64) {
+        let state = self.state.lock().unwrap();
+        let skip = from.saturating_sub(state.base) as usize;
+        let cues = state
+            .log
+            .iter()
+            .skip(skip)
+            .filter(|(s, _)| *s == service)
+            .map(|(_, cue)| cue.clone())
+            .collect();
+        (cues, state.base + state.log.len() as u64)
+    }
+
+    /// Resolves on the next batch of updates. Create it before calling
+    /// `updates` so none slips in between.
+    pub(crate) fn notified(&self) -> Notified<'_> {
+        self.updated.notified()
+    }
+}
+
+/// One video pipeline's caption input (`ClosedCaptions::restart`).
+#[derive(Clone)]
+pub(crate) struct CaptionFeed {
+    captions: Arc<ClosedCaptions>,
+    /// HEVC, else H.264.
+    hevc: bool,
+    generation: u64,
+}
+
+impl CaptionFeed {
+    /// Decode the captions of one prepared segment: `data` holds 4-byte
+    /// length-prefixed NAL units, `samples` the `(offset, size, ts,
+    /// timescale)` of each access unit, in decode order.
+    pub(crate) fn feed_segment(&self, data: &[u8], samples: &[(usize, usize, i64, u64)]) {
+        self.captions.feed_segment(self, data, samples);
+    }
+}
+
+/// The `cc_data` triplets in the SEI NAL units of one access unit.
+fn sei_cc_data(hevc: bool, sample: &[u8]) -> Vec<[u8; 3]> {
+    let mut out = Vec::new();
+    let mut rest = sample;
+    while rest.len() >= 4 {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let Some(nalu) = rest.get(4..4 + len) else {
+            break;
+        };
+        if hevc {
+            if hevc::nal_unit_type(nalu) == Some(hevc::NAL_SEI_PREFIX) {
+                out.extend(hevc::parse_sei_cc_data(nalu));
+            }
+        } else if avc::nal_unit_type(nalu) == Some(avc::NAL_SEI) {
+            out.extend(avc::parse_sei_cc_data(nalu));
+        }
+        rest = &rest[4 + len..];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An H.264 access unit: one SEI NAL with a GA94 `cc_data` payload
+    /// carrying `pairs` as field-1 CEA-608 triplets, then a slice NAL.
+    fn access_unit(pairs: &[[u8; 2]]) -> Vec<u8> {
+        let mut t35 = vec![0xB5, 0x00, 0x31, b'G', b'A', b'9', b'4', 0x03];
+        t35.push(0x40 | pairs.len() as u8);
+        t35.push(0xFF);
+        for pair in pairs {
+            t35.extend([0xFC, pair[0], pair[1]]);
+        }
+        t35.push(0xFF);
+        let mut sei = vec![0x06, 0x04, t35.len() as u8];
+        sei.extend(t35);
+        sei.push(0x80);
+        let slice = [0x65, 0x88, 0x84];
+        let mut au = Vec::new();
+        for nalu in [&sei[..], &slice[..]] {
+            au.extend((nalu.len() as u32).to_be_bytes());
+            au.extend_from_slice(nalu);
+        }
+        au
+    }
+
+    #[test]
+    fn decodes_captions_in_presentation_order_and_announces_the_track() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let tracks = Arc::new(Mutex::new(Some(Tracks {
+            duration: std::time::Duration::ZERO,
+            video: Vec::new(),
+            audio: Vec::new(),
+            text: Vec::new(),
+        })));
+        let cc = Arc::new(ClosedCaptions::new(Arc::clone(&tracks), Arc::new(tx)));
+        let feed = cc.restart(false);
+
+        // Paint-on "HI" split over two access units, stored in decode
+        // order (the later one first).
+        let first = access_unit(&[[0x94, 0x29], [0x94, 0x29], [0x94, 0xF2], [0x94, 0xF2]]);
+        let second = access_unit(&[[0xC8, 0x49]]);
+        let mut data = second.clone();
+        data.extend(&first);
+        let samples = [
+            (0, second.len(), 2000, 1000),
+            (second.len(), first.len(), 1000, 1000),
+        ];
+        feed.feed_segment(&data, &samples);
+
+        let (cues, next) = cc.updates(CaptionService::Cea608(1), 0);
+        assert_eq!(cues.last().map(|c| c.text.as_str()), Some("HI"));
+        assert_eq!(cues.last().unwrap().start_ms, 2000);
+        assert!(cc.updates(CaptionService::Cea608(2), 0).0.is_empty());
+        assert!(matches!(rx.try_recv(), Ok(PlayerEvent::TextTracksChanged)));
+        let text = tracks.lock().unwrap().as_ref().unwrap().text.clone();
+        assert_eq!(text.len(), 1);
+        assert_eq!(text[0].embedded_captions(), Some(CaptionService::Cea608(1)));
+
+        // A restart closes the open cue where feeding stopped, and the old
+        // pipeline's feed goes quiet.
+        let feed2 = cc.restart(false);
+        let (cues, next) = cc.updates(CaptionService::Cea608(1), next);
+        assert_eq!(cues.len(), 1);
+        assert_eq!((cues[0].start_ms, cues[0].end_ms), (2000, 2000));
+        feed.feed_segment(&data, &samples);
+        assert!(cc.updates(CaptionService::Cea608(1), next).0.is_empty());
+
+        // Known services are not announced twice; clear forgets them.
+        feed2.feed_segment(&data, &samples);
+        assert_eq!(cc.updates(CaptionService::Cea608(1), next).0.len(), 1);
+        assert!(rx.try_recv().is_err());
+        cc.clear();
+        let (cues, _) = cc.updates(CaptionService::Cea608(1), 0);
+        assert!(cues.is_empty());
+    }
+}
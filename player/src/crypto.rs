@@ -397,6 +397,41 @@ pub fn parse_hvcc_nalus(init_data: &[u8]) -> Option<Vec<Vec<u8>>> {
     Some(out)
 }
 
+/// Extract the H.264 decoder configuration (`avcC` box): SPS/PPS NALUs plus
+/// profile/level and the High-profile bit depth.
+pub fn parse_avcc(init_data: &[u8]) -> Option<crate::parsers::avc::AvcDecoderConfig> {
+    let moov = find_top_box(init_data, b"moov")?;
+    let avcc = find_descendant(moov, b"avcC")?;
+    crate::parsers::avc::parse_avcc_record(avcc)
+}
+
+/// Extract the AV1 codec configuration (`av1C` box). The raw box body is
+/// returned alongside the parsed record — FFmpeg extradata and MediaCodec
+/// `csd-0` both take it verbatim.
+pub fn parse_av1c(init_data: &[u8]) -> Option<(crate::parsers::av1::Av1Config, Vec<u8>)> {
+    let moov = find_top_box(init_data, b"moov")?;
+    let av1c = find_descendant(moov, b"av1C")?;
+    Some((crate::parsers::av1::parse_av1c_record(av1c)?, av1c.to_vec()))
+}
+
+/// Extract the VP9 codec configuration (`vpcC` box).
+pub fn parse_vpcc(init_data: &[u8]) -> Option<crate::parsers::vp9::Vp9Config> {
+    let moov = find_top_box(init_data, b"moov")?;
+    crate::parsers::vp9::parse_vpcc_record(find_descendant(moov, b"vpcC")?)
+}
+
+/// Extract the Opus codec configuration (`dOps` box).
+pub fn parse_dops(init_data: &[u8]) -> Option<crate::parsers::opus::OpusConfig> {
+    let moov = find_top_box(init_data, b"moov")?;
+    crate::parsers::opus::parse_dops_record(find_descendant(moov, b"dOps")?)
+}
+
+/// Extract the FLAC codec configuration (`dfLa` box).
+pub fn parse_dfla(init_data: &[u8]) -> Option<crate::parsers::flac::FlacConfig> {
+    let moov = find_top_box(init_data, b"moov")?;
+    crate::parsers::flac::parse_dfla_record(find_descendant(moov, b"dfLa")?)
+}
+
 /// Extract AAC AudioSpecificConfig (profile, freq_index, channels) from `esds`.
 pub fn parse_aac_config(init_data: &[u8]) -> Option<AacConfig> {
     let moov = find_top_box(init_data, b"moov")?;
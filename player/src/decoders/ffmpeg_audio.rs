@@ -11,6 +11,7 @@ use ffmpeg_next::util::channel_layout::ChannelLayout;
 use ffmpeg_next::Packet;
 
 use super::{AudioCodec, AudioDecoder, AudioDecoderParams, DecodedAudioFrame, DecoderError};
+use crate::audio_layout::{ChannelRemixer, DownmixCoefficients, MAX_CHANNELS};
 
 pub struct FfmpegAudioDecoder {
     decoder: Option<ffmpeg_next::decoder::Audio>,
@@ -24,8 +25,17 @@ pub struct FfmpegAudioDecoder {
     /// no longer matches and trigger a rebuild.
     resampler_in_rate: u32,
     resampler_in_layout_bits: u64,
+    /// Channel count the resampler emits: the decoded count, capped at
+    /// 7.1, in the WAVE-order layout `audio_layout::source_layout` names.
+    resampler_out_channels: usize,
+    /// Maps the resampler's output onto the device layout. Rebuilt with
+    /// the resampler.
+    remixer: Option<ChannelRemixer>,
     /// Target output rate (cpal device rate) passed in at configure time.
     output_sample_rate: u32,
+    /// Device channel count and the fold gains for channels it lacks.
+    output_channels: u16,
+    downmix: DownmixCoefficients,
 }
 
 unsafe impl Send for FfmpegAudioDecoder {}
@@ -37,44 +47,76 @@ impl FfmpegAudioDecoder {
             resampler: None,
             resampler_in_rate: 0,
             resampler_in_layout_bits: 0,
+            resampler_out_channels: 0,
+            remixer: None,
             output_sample_rate: 0,
+            output_channels: 2,
+            downmix: DownmixCoefficients::DEFAULT,
         }
     }
 
     /// (Re)build the resampler so its input matches the decoded frame's
-    /// actual `rate` + `layout`, and its output is fixed stereo at the
-    /// device rate. Called lazily on first frame and whenever the input
+    /// actual `rate` + `layout`, and its output is packed f32 at the device
+    /// rate in the WAVE-order layout for the decoded channel count (so an
+    /// exotic layout like 5.1(side) or 7.1.4 arrives in the order the
+    /// remixer expects). The remixer then maps that onto the device's
+    /// channels. Called lazily on first frame and whenever the input
     /// format drifts.
     fn build_resampler(&mut self, in_rate: u32, in_layout: ChannelLayout) -> Result<(), DecoderError> {
+        let out_channels = (in_layout.channels().max(1) as usize).min(MAX_CHANNELS);
+        let out_layout = source_ffmpeg_layout(out_channels);
         let resampler = ResampleCtx::get(
             ffmpeg_next::util::format::sample::Sample::F32(Type::Planar),
             in_layout,
             in_rate,
             ffmpeg_next::util::format::sample::Sample::F32(Type::Packed),
-            ChannelLayout::STEREO,
+            out_layout,
             self.output_sample_rate,
         )
         .map_err(|e| -> DecoderError {
-            format!("resampler init ({}Hz {}ch -> {}Hz stereo): {}",
-                in_rate, in_layout.channels(), self.output_sample_rate, e).into()
+            format!("resampler init ({}Hz {}ch -> {}Hz {}ch): {}",
+                in_rate, in_layout.channels(), self.output_sample_rate, out_channels, e).into()
         })?;
         self.resampler_in_rate = in_rate;
         self.resampler_in_layout_bits = in_layout.bits();
+        self.resampler_out_channels = out_channels;
         self.resampler = Some(resampler);
+        self.remixer = Some(ChannelRemixer::new(
+            out_channels,
+            self.output_channels as usize,
+            self.downmix,
+        ));
         log::info!(
-            "FfmpegAudioDecoder: resampler {}Hz {}ch -> {}Hz stereo",
-            in_rate, in_layout.channels(), self.output_sample_rate
+            "FfmpegAudioDecoder: resampler {}Hz {}ch -> {}Hz {}ch, remix -> {}ch device",
+            in_rate, in_layout.channels(), self.output_sample_rate, out_channels, self.output_channels
         );
         Ok(())
     }
 }
 
+/// FFmpeg layout whose native order matches `audio_layout::source_layout`
+/// for `channels` (1..=8).
+fn source_ffmpeg_layout(channels: usize) -> ChannelLayout {
+    match channels {
+        1 => ChannelLayout::MONO,
+        2 => ChannelLayout::STEREO,
+        3 => ChannelLayout::SURROUND,
+        4 => ChannelLayout::QUAD,
+        5 => ChannelLayout::_5POINT0_BACK,
+        6 => ChannelLayout::_5POINT1_BACK,
+        7 => ChannelLayout::_6POINT1,
+        _ => ChannelLayout::_7POINT1,
+    }
+}
+
 impl AudioDecoder for FfmpegAudioDecoder {
     fn configure(&mut self, params: AudioDecoderParams) -> Result<(), DecoderError> {
         let codec_id = match params.codec {
             AudioCodec::Aac => ffmpeg_next::codec::Id::AAC,
             AudioCodec::Ac3 => ffmpeg_next::codec::Id::AC3,
             AudioCodec::Eac3 => ffmpeg_next::codec::Id::EAC3,
+            AudioCodec::Opus => ffmpeg_next::codec::Id::OPUS,
+            AudioCodec::Flac => ffmpeg_next::codec::Id::FLAC,
         };
         let codec = ffmpeg_next::decoder::find(codec_id).ok_or_else(|| -> DecoderError {
             format!("cannot find FFmpeg decoder for {:?}", params.codec).into()
@@ -92,7 +134,8 @@ impl AudioDecoder for FfmpegAudioDecoder {
             let ctx_ptr = ctx.as_mut_ptr();
 
             // Install codec-specific extradata for codecs that need it
-            // (AAC's 2-byte AudioSpecificConfig). AC-3 / EAC-3 don't —
+            // (AAC's 2-byte AudioSpecificConfig, Opus's OpusHead, FLAC's
+            // STREAMINFO behind the `fLaC` marker). AC-3 / EAC-3 don't —
             // their decoders read params from each frame's syncinfo.
             if !params.codec_specific_data.is_empty() {
                 let padding = ffmpeg_sys_next::AV_INPUT_BUFFER_PADDING_SIZE as usize;
@@ -128,15 +171,18 @@ impl AudioDecoder for FfmpegAudioDecoder {
         decoder.request_format(ffmpeg_next::util::format::sample::Sample::F32(Type::Planar));
 
         log::info!(
-            "FfmpegAudioDecoder: opened {:?} (manifest hint: {}Hz {}ch -> stereo {}Hz). \
+            "FfmpegAudioDecoder: opened {:?} (manifest hint: {}Hz {}ch -> {}ch {}Hz). \
              Resampler will be built lazily from the first decoded frame.",
             params.codec,
             params.input_sample_rate,
             params.input_channels,
+            params.output_channels,
             params.output_sample_rate,
         );
         self.decoder = Some(decoder);
         self.output_sample_rate = params.output_sample_rate;
+        self.output_channels = params.output_channels;
+        self.downmix = params.downmix;
         Ok(())
     }
 
@@ -208,8 +254,10 @@ impl AudioDecoder for FfmpegAudioDecoder {
                         return Ok(None);
                     }
                 }
-                let expected_bytes = dst.samples() * 2 * std::mem::size_of::<f32>();
-                let samples: Vec<f32> = bytemuck::cast_slice(&dst.data(0)[..expected_bytes]).to_vec();
+                let expected_bytes =
+                    dst.samples() * self.resampler_out_channels * std::mem::size_of::<f32>();
+                let pcm: &[f32] = bytemuck::cast_slice(&dst.data(0)[..expected_bytes]);
+                let samples = self.remixer.as_ref().unwrap().process(pcm);
                 Ok(Some(DecodedAudioFrame { pts_ms, samples }))
             }
             Err(ffmpeg_next::Error::Other { errno }) if errno == ffmpeg_sys_next::EAGAIN => {
@@ -225,7 +273,7 @@ mod tests {
     //! Platform smoke tests for the FFmpeg audio decoder build.
     //!
     //! Catches the easy regressions:
-    //!   - FFmpeg was rebuilt without AAC / AC-3 / E-AC-3 support
+    //!   - FFmpeg was rebuilt without AAC / AC-3 / E-AC-3 / Opus / FLAC support
     //!     (we'd hit the lookup failure at runtime when the user
     //!     opens an EC-3 audio adaptation — better to catch at CI).
     //!   - ChannelLayout construction for the channel counts the
@@ -264,6 +312,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ffmpeg_finds_opus_and_flac_decoders() {
+        // Music / low-bitrate profiles carry Opus (`dOps`) and FLAC (`dfLa`).
+        assert!(
+            ffmpeg_next::decoder::find(ffmpeg_next::codec::Id::OPUS).is_some(),
+            "FFmpeg build is missing the Opus decoder",
+        );
+        assert!(
+            ffmpeg_next::decoder::find(ffmpeg_next::codec::Id::FLAC).is_some(),
+            "FFmpeg build is missing the FLAC decoder",
+        );
+    }
+
     #[test]
     fn channel_layout_constants_have_expected_channels() {
         assert_eq!(ChannelLayout::MONO.channels(), 1);
@@ -281,6 +342,17 @@ mod tests {
         assert_eq!(ChannelLayout::default(8).channels(), 8);
     }
 
+    #[test]
+    fn source_layouts_match_remixer_channel_counts() {
+        // The remixer indexes the resampler's output by
+        // `audio_layout::source_layout(n)`; the FFmpeg layout must carry
+        // exactly n channels or the packed buffer is misread.
+        for n in 1..=crate::audio_layout::MAX_CHANNELS {
+            assert_eq!(source_ffmpeg_layout(n).channels() as usize, n);
+            assert_eq!(crate::audio_layout::source_layout(n).len(), n);
+        }
+    }
+
     #[test]
     fn resampler_can_be_built_for_typical_stream_params() {
         // The "real" resampler config the player builds in `build_resampler`:
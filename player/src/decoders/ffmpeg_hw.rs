@@ -1,11 +1,16 @@
 #![cfg(any(target_os = "windows", target_os = "linux"))]
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use ffmpeg_next::format::Pixel;
+use ffmpeg_next::software::scaling;
 use ffmpeg_next::Packet;
 use ffmpeg_sys_next::{
-    av_buffer_ref, av_buffer_unref, av_hwdevice_ctx_create, AVBufferRef, AVCodecContext,
-    AVHWDeviceType, AVPixelFormat,
+    av_buffer_ref, av_buffer_unref, av_hwdevice_ctx_create, av_hwframe_ctx_alloc,
+    av_hwframe_ctx_init, av_hwframe_get_buffer, av_hwframe_transfer_data, av_mallocz,
+    av_pix_fmt_desc_get, avcodec_get_hw_config, AVBufferRef, AVCodecContext, AVCodecID,
+    AVHWDeviceType, AVHWFramesContext, AVPixelFormat, AV_INPUT_BUFFER_PADDING_SIZE,
 };
 use crate::parsers::mp4::{append_hevc_header, parse_hevc_nalu};
 
@@ -22,10 +27,36 @@ pub struct FfmpegHwDecoder {
     shared_device: Option<Arc<SharedHwDevice>>,
     /// Stamped onto every decoded frame (from configure params).
     color: crate::decoders::VideoColorInfo,
+    /// Decides packet framing in `submit` (NAL re-framing vs whole sample).
+    codec: VideoCodec,
+    /// Software decode mode: never touch a hw-device (none exists, or the
+    /// API forced it). See [`FfmpegHwDecoder::new_software`].
+    software_only: bool,
+    output: FrameOutput,
+    /// Latched by `select_hw_format` when the native AV1 decoder was opened
+    /// but the GPU/driver has no AV1 decode profile (hwaccel init failed, so
+    /// libavcodec re-offered only software formats). Shared by every decoder
+    /// on the same device, so the supervisor's retry configures a fresh
+    /// decoder that goes straight to libdav1d / libaom; a new device (next
+    /// play() cycle, another GPU) starts unlatched.
+    av1_hw_unsupported: Arc<AtomicBool>,
 }
 
 unsafe impl Send for FfmpegHwDecoder {}
 
+/// Where decoded frames go before they reach the renderer.
+enum FrameOutput {
+    /// hwaccel decode — frames already are D3D11 textures / VAAPI surfaces.
+    Hw,
+    /// Software decoder on a GPU host (AV1 via libdav1d / libaom): frames
+    /// are uploaded into a hw frames context on the same device so the
+    /// renderer import path is unchanged.
+    Upload(HwUpload),
+    /// Software decode mode: frames stay in system memory as NV12 / P010
+    /// and the renderer uploads the planes itself.
+    System(SemiPlanar),
+}
+
 /// Create a fresh platform hw-device context (D3D11VA on Windows, VAAPI on
 /// Linux). The caller owns the returned ref and must `av_buffer_unref` it.
 fn create_hwdevice_ctx() -> Result<*mut AVBufferRef, DecoderError> {
@@ -66,6 +97,8 @@ fn create_hwdevice_ctx() -> Result<*mut AVBufferRef, DecoderError> {
 /// sharing it across threads is sound.
 pub struct SharedHwDevice {
     ctx: *mut AVBufferRef,
+    /// See `FfmpegHwDecoder::av1_hw_unsupported`.
+    av1_hw_unsupported: Arc<AtomicBool>,
 }
 
 unsafe impl Send for SharedHwDevice {}
@@ -77,6 +110,7 @@ impl SharedHwDevice {
     pub fn new() -> Result<Arc<Self>, DecoderError> {
         Ok(Arc::new(Self {
             ctx: create_hwdevice_ctx()?,
+            av1_hw_unsupported: Arc::default(),
         }))
     }
 
@@ -97,23 +131,51 @@ impl Drop for SharedHwDevice {
 
 impl FfmpegHwDecoder {
     /// Legacy / fallback: this decoder owns a freshly-created hw-device.
-    pub fn new() -> Self {
-        Self {
-            decoder: None,
-            hw_device_ctx: std::ptr::null_mut(),
-            shared_device: None,
-            color: Default::default(),
-        }
+    /// Pass the same `av1_hw_unsupported` to every decoder of a play() cycle
+    /// so an AV1 hwaccel failure isn't retried on each fresh device.
+    pub fn new(av1_hw_unsupported: Arc<AtomicBool>) -> Self {
+        Self::with_device(None, false, av1_hw_unsupported)
     }
 
     /// Preferred: reuse a [`SharedHwDevice`] created once per play() cycle so
     /// an ABR swap re-opens only the codec, never recreates the GPU device.
     pub fn new_shared(device: Arc<SharedHwDevice>) -> Self {
+        let av1_hw_unsupported = Arc::clone(&device.av1_hw_unsupported);
+        Self::with_device(Some(device), false, av1_hw_unsupported)
+    }
+
+    /// Software decode: libavcodec threads, frames in system memory (NV12 /
+    /// P010), uploaded by the renderer. For hosts where the hw-device can't
+    /// be created (GPU-less CI runners, headless servers, VMs) or when
+    /// forced via `Player::set_software_video_decode`.
+    pub fn new_software() -> Self {
+        Self::with_device(None, true, Arc::default())
+    }
+
+    /// [`pick_decoder`] for this decoder's mode and AV1 latch.
+    fn pick_decoder(&self, codec: VideoCodec) -> Result<(ffmpeg_next::Codec, bool), DecoderError> {
+        pick_decoder(
+            codec,
+            self.software_only,
+            self.av1_hw_unsupported.load(Ordering::Relaxed),
+        )
+    }
+
+    // Shared by the constructors (the type is Drop, so no `..Self::new()`).
+    fn with_device(
+        shared_device: Option<Arc<SharedHwDevice>>,
+        software_only: bool,
+        av1_hw_unsupported: Arc<AtomicBool>,
+    ) -> Self {
         Self {
             decoder: None,
             hw_device_ctx: std::ptr::null_mut(),
-            shared_device: Some(device),
+            shared_device,
             color: Default::default(),
+            codec: VideoCodec::Hevc,
+            software_only,
+            output: FrameOutput::Hw,
+            av1_hw_unsupported,
         }
     }
 
@@ -121,6 +183,205 @@ impl FfmpegHwDecoder {
         self.hw_device_ctx = create_hwdevice_ctx()?;
         Ok(())
     }
+
+    /// New owned reference to whichever hw-device this decoder uses, creating
+    /// the decoder-owned one on first use when no shared device was given.
+    fn device_ref(&mut self) -> Result<*mut AVBufferRef, DecoderError> {
+        if let Some(shared) = &self.shared_device {
+            return Ok(shared.new_ref());
+        }
+        if self.hw_device_ctx.is_null() {
+            self.create_hw_device()?;
+        }
+        Ok(unsafe { av_buffer_ref(self.hw_device_ctx) })
+    }
+}
+
+/// Does this FFmpeg decoder advertise a hwaccel producing `WANTED_HW`?
+/// Only says the hwaccel is compiled in — whether the GPU actually has the
+/// profile is found out at first-frame `get_format` time.
+fn codec_has_hwaccel(codec: &ffmpeg_next::Codec) -> bool {
+    let mut i = 0;
+    loop {
+        let cfg = unsafe { avcodec_get_hw_config(codec.as_ptr(), i) };
+        if cfg.is_null() {
+            return false;
+        }
+        if unsafe { (*cfg).pix_fmt } == WANTED_HW {
+            return true;
+        }
+        i += 1;
+    }
+}
+
+/// Pick the FFmpeg decoder for `codec`. Returns `(decoder, software)`;
+/// `software == true` means frames come back in system memory. With a
+/// hw-device only AV1 has a software route: GPUs without an AV1 decode
+/// block are still common, while HEVC/H.264/VP9 decode is universal. In
+/// `software_only` mode every codec decodes on the CPU (AV1 through
+/// libdav1d / libaom — the native `av1` decoder is hwaccel-only).
+fn pick_decoder(
+    codec: VideoCodec,
+    software_only: bool,
+    av1_hw_unsupported: bool,
+) -> Result<(ffmpeg_next::Codec, bool), DecoderError> {
+    let id = match codec {
+        VideoCodec::Hevc => ffmpeg_next::codec::Id::HEVC,
+        VideoCodec::H264 => ffmpeg_next::codec::Id::H264,
+        VideoCodec::Av1 => ffmpeg_next::codec::Id::AV1,
+        VideoCodec::Vp9 => ffmpeg_next::codec::Id::VP9,
+    };
+    if codec != VideoCodec::Av1 {
+        let dec = ffmpeg_next::decoder::find(id)
+            .ok_or_else(|| -> DecoderError { "cannot find FFmpeg decoder for codec".into() })?;
+        return Ok((dec, software_only));
+    }
+
+    // `decoder::find(AV1)` may return libdav1d ahead of the native decoder
+    // depending on registration order — ask for the native one by name.
+    if !software_only && !av1_hw_unsupported {
+        if let Some(native) = ffmpeg_next::decoder::find_by_name("av1") {
+            if codec_has_hwaccel(&native) {
+                return Ok((native, false));
+            }
+        }
+    }
+    for name in ["libdav1d", "libaom-av1"] {
+        if let Some(sw) = ffmpeg_next::decoder::find_by_name(name) {
+            log::info!("[ffmpeg_hw] AV1 via software decoder {}", name);
+            return Ok((sw, true));
+        }
+    }
+    Err("no usable AV1 decoder: no hardware AV1 and FFmpeg lacks libdav1d/libaom".into())
+}
+
+/// Planar YUV → NV12 (8-bit) / P010 (10/12-bit): the two layouts both the
+/// hw frames contexts and the renderer's Y + UV plane shaders take.
+#[derive(Default)]
+struct SemiPlanar {
+    /// Rebuilt when the source size or format changes.
+    scaler: Option<(scaling::Context, (u32, u32, Pixel))>,
+}
+
+impl SemiPlanar {
+    fn convert(
+        &mut self,
+        sw: ffmpeg_next::util::frame::Video,
+    ) -> Result<ffmpeg_next::util::frame::Video, DecoderError> {
+        let (w, h) = (sw.width(), sw.height());
+        let high_bit_depth = unsafe {
+            let desc = av_pix_fmt_desc_get(sw.format().into());
+            !desc.is_null() && (*desc).comp[0].depth > 8
+        };
+        let target = if high_bit_depth { Pixel::P010LE } else { Pixel::NV12 };
+        if sw.format() == target {
+            return Ok(sw);
+        }
+
+        let src_key = (w, h, sw.format());
+        if self.scaler.as_ref().map(|(_, k)| *k) != Some(src_key) {
+            let ctx = scaling::Context::get(sw.format(), w, h, target, w, h, scaling::Flags::POINT)
+                .map_err(|e| -> DecoderError { format!("swscale init: {}", e).into() })?;
+            self.scaler = Some((ctx, src_key));
+        }
+        let mut out = ffmpeg_next::util::frame::Video::new(target, w, h);
+        let (scaler, _) = self.scaler.as_mut().unwrap();
+        scaler
+            .run(&sw, &mut out)
+            .map_err(|e| -> DecoderError { format!("swscale: {}", e).into() })?;
+        out.set_pts(sw.pts());
+        Ok(out)
+    }
+}
+
+/// Uploads system-memory frames from a software decoder into a
+/// `WANTED_HW` frames context on the player's hw-device. The renderer's
+/// zero-copy import expects D3D11 textures / VAAPI surfaces, so software AV1
+/// output takes one CPU→GPU copy per frame to look exactly like hwaccel
+/// output.
+struct HwUpload {
+    /// Owned reference to the hw-device.
+    device: *mut AVBufferRef,
+    /// Owned frames context; rebuilt when size or bit depth changes.
+    frames: *mut AVBufferRef,
+    frames_key: (u32, u32, Pixel),
+    convert: SemiPlanar,
+}
+
+impl HwUpload {
+    fn new(device: *mut AVBufferRef) -> Self {
+        Self {
+            device,
+            frames: std::ptr::null_mut(),
+            frames_key: (0, 0, Pixel::None),
+            convert: SemiPlanar::default(),
+        }
+    }
+
+    fn upload(
+        &mut self,
+        sw: ffmpeg_next::util::frame::Video,
+    ) -> Result<ffmpeg_next::util::frame::Video, DecoderError> {
+        let staged = self.convert.convert(sw)?;
+        let (w, h) = (staged.width(), staged.height());
+        self.ensure_frames((w, h, staged.format()))?;
+        let mut hw = ffmpeg_next::util::frame::Video::empty();
+        unsafe {
+            let ret = av_hwframe_get_buffer(self.frames, hw.as_mut_ptr(), 0);
+            if ret < 0 {
+                return Err(format!("av_hwframe_get_buffer: {}", ret).into());
+            }
+            let ret = av_hwframe_transfer_data(hw.as_mut_ptr(), staged.as_ptr(), 0);
+            if ret < 0 {
+                return Err(format!("av_hwframe_transfer_data: {}", ret).into());
+            }
+        }
+        hw.set_pts(staged.pts());
+        Ok(hw)
+    }
+
+    fn ensure_frames(&mut self, key: (u32, u32, Pixel)) -> Result<(), DecoderError> {
+        if !self.frames.is_null() && self.frames_key == key {
+            return Ok(());
+        }
+        unsafe {
+            if !self.frames.is_null() {
+                av_buffer_unref(&mut self.frames);
+            }
+            let mut r = av_hwframe_ctx_alloc(self.device);
+            if r.is_null() {
+                return Err("av_hwframe_ctx_alloc failed".into());
+            }
+            // initial_pool_size stays 0: both D3D11VA and VAAPI then allocate
+            // surfaces on demand instead of a fixed decoder-style array.
+            let fc = (*r).data as *mut AVHWFramesContext;
+            (*fc).format = WANTED_HW;
+            (*fc).sw_format = key.2.into();
+            (*fc).width = key.0 as i32;
+            (*fc).height = key.1 as i32;
+            let ret = av_hwframe_ctx_init(r);
+            if ret < 0 {
+                av_buffer_unref(&mut r);
+                return Err(format!("av_hwframe_ctx_init: {}", ret).into());
+            }
+            self.frames = r;
+        }
+        self.frames_key = key;
+        Ok(())
+    }
+}
+
+impl Drop for HwUpload {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.frames.is_null() {
+                av_buffer_unref(&mut self.frames);
+            }
+            if !self.device.is_null() {
+                av_buffer_unref(&mut self.device);
+            }
+        }
+    }
 }
 
 impl Drop for FfmpegHwDecoder {
@@ -143,13 +404,14 @@ impl Drop for FfmpegHwDecoder {
 // We pick the platform's HW format if it's on offer and otherwise
 // return AV_PIX_FMT_NONE so libavcodec aborts decoder open — louder
 // than silently sliding into software and panicking downstream.
+// `ctx.opaque` points at the decoder's `av1_hw_unsupported` latch.
 #[cfg(target_os = "windows")]
 const WANTED_HW: AVPixelFormat = AVPixelFormat::AV_PIX_FMT_D3D11;
 #[cfg(target_os = "linux")]
 const WANTED_HW: AVPixelFormat = AVPixelFormat::AV_PIX_FMT_VAAPI;
 
 unsafe extern "C" fn select_hw_format(
-    _ctx: *mut AVCodecContext,
+    ctx: *mut AVCodecContext,
     fmts: *const AVPixelFormat,
 ) -> AVPixelFormat {
     // Snapshot the offered list so we can log it once. Critical for
@@ -170,12 +432,25 @@ unsafe extern "C" fn select_hw_format(
     if offered.contains(&WANTED_HW) {
         WANTED_HW
     } else {
+        if unsafe { (*ctx).codec_id } == AVCodecID::AV_CODEC_ID_AV1 {
+            log::warn!("[ffmpeg_hw] no hardware AV1 on this GPU; next configure uses software");
+            let latch = unsafe { (*ctx).opaque } as *const AtomicBool;
+            if !latch.is_null() {
+                unsafe { (*latch).store(true, Ordering::Relaxed) };
+            }
+        }
         AVPixelFormat::AV_PIX_FMT_NONE
     }
 }
 
 impl HwVideoDecoder for FfmpegHwDecoder {
     fn name(&self) -> &'static str {
+        if self.software_only {
+            return "Software (FFmpeg)";
+        }
+        if let FrameOutput::Upload(_) = self.output {
+            return "AV1 software (FFmpeg)";
+        }
         #[cfg(target_os = "windows")]
         {
             "D3D11VA (FFmpeg)"
@@ -187,12 +462,15 @@ impl HwVideoDecoder for FfmpegHwDecoder {
     }
 
     fn configure(&mut self, params: VideoDecoderParams) -> Result<(), DecoderError> {
-        let codec_id = match params.codec {
-            VideoCodec::Hevc => ffmpeg_next::codec::Id::HEVC,
-            VideoCodec::H264 => ffmpeg_next::codec::Id::H264,
-        };
-        let codec = ffmpeg_next::decoder::find(codec_id)
-            .ok_or_else(|| -> DecoderError { "cannot find FFmpeg decoder for codec".into() })?;
+        // Per-decoder device (the shared one failed at play() setup): if
+        // this one can't be created either, decode on the CPU.
+        if !self.software_only && self.shared_device.is_none() && self.hw_device_ctx.is_null() {
+            if let Err(e) = self.create_hw_device() {
+                log::warn!("[ffmpeg_hw] hw-device init failed ({e}); software decode");
+                self.software_only = true;
+            }
+        }
+        let (codec, software) = self.pick_decoder(params.codec)?;
 
         let mut ctx = ffmpeg_next::codec::Context::new_with_codec(codec);
 
@@ -200,47 +478,80 @@ impl HwVideoDecoder for FfmpegHwDecoder {
         // shared device (so an ABR swap never recreates the D3D11/VAAPI device
         // — see SharedHwDevice); otherwise lazily create a decoder-owned one.
         // The codec context takes ownership of the ref it is given and unrefs
-        // it on its own drop.
-        let device_ref = if let Some(shared) = &self.shared_device {
-            shared.new_ref()
+        // it on its own drop. A software decoder never sees the device; the
+        // ref goes to its HwUpload instead.
+        self.output = if self.software_only {
+            FrameOutput::System(SemiPlanar::default())
         } else {
-            if self.hw_device_ctx.is_null() {
-                self.create_hw_device()?;
+            let device_ref = self.device_ref()?;
+            if software {
+                FrameOutput::Upload(HwUpload::new(device_ref))
+            } else {
+                unsafe {
+                    (*ctx.as_mut_ptr()).hw_device_ctx = device_ref;
+                    (*ctx.as_mut_ptr()).get_format = Some(select_hw_format);
+                    // The Arc outlives the codec context: both live in self.
+                    (*ctx.as_mut_ptr()).opaque =
+                        Arc::as_ptr(&self.av1_hw_unsupported) as *mut std::ffi::c_void;
+                }
+                FrameOutput::Hw
             }
-            unsafe { av_buffer_ref(self.hw_device_ctx) }
         };
-        unsafe {
-            (*ctx.as_mut_ptr()).hw_device_ctx = device_ref;
-            (*ctx.as_mut_ptr()).get_format = Some(select_hw_format);
+        if software {
+            // 0 = one thread per core; software decode is hopeless at 4K on
+            // a single thread.
+            unsafe { (*ctx.as_mut_ptr()).thread_count = 0 };
+        }
+
+        // av1C goes in as extradata (the AV1 decoders parse the record and
+        // its configOBUs themselves). FFmpeg requires the padding tail.
+        if !params.config_record.is_empty() {
+            let len = params.config_record.len();
+            unsafe {
+                let buf = av_mallocz(len + AV_INPUT_BUFFER_PADDING_SIZE as usize) as *mut u8;
+                if buf.is_null() {
+                    return Err("extradata alloc failed".into());
+                }
+                std::ptr::copy_nonoverlapping(params.config_record.as_ptr(), buf, len);
+                (*ctx.as_mut_ptr()).extradata = buf;
+                (*ctx.as_mut_ptr()).extradata_size = len as i32;
+            }
         }
 
-        // No pre-allocated hw_frames_ctx: let hevc_d3d11va2 auto-create it
-        // inside its hwaccel->init() after get_format returns AV_PIX_FMT_D3D11.
+        // No pre-allocated hw_frames_ctx: let hevc_d3d11va2 (h264_d3d11va2 for
+        // AVC) auto-create it inside its hwaccel->init() after get_format
+        // returns AV_PIX_FMT_D3D11.
         // A manually pre-set context with format=AV_PIX_FMT_D3D11 caused the
         // hwaccel to log "Invalid pixfmt for hwaccel!" and abort because the
         // frames context format was evaluated before avctx->pix_fmt was set.
         // FFmpeg auto-derives sw_format from the SPS (NV12 for Main 8-bit,
         // P010 for Main 10), so the auto-allocated context is always correct.
+        // H.264 High 10 has no D3D11VA/VAAPI profile on mainstream GPUs —
+        // get_format then finds no HW format and the open fails loudly.
 
         let mut decoder = ctx
             .decoder()
             .video()
             .map_err(|e| -> DecoderError { format!("decoder open: {}", e).into() })?;
 
-        // Feed the hvcC NALUs (VPS/SPS/PPS) so the decoder can parse subsequent slices.
-        // Each element of `hvcc_nalus` is a raw NALU body (no prefix); append_hevc_header
-        // prepends the 00 00 00 01 start code.
-        for nalu_data in &params.hvcc_nalus {
+        // Feed the parameter sets (hvcC VPS/SPS/PPS or avcC SPS/PPS) so the
+        // decoder can parse subsequent slices. Each element of
+        // `parameter_sets` is a raw NALU body (no prefix); append_hevc_header
+        // prepends the 00 00 00 01 start code, which is codec-agnostic Annex-B.
+        for nalu_data in &params.parameter_sets {
             let nalu = append_hevc_header(nalu_data.clone());
             let mut packet = Packet::new(nalu.len());
             packet.data_mut().unwrap().clone_from_slice(&nalu);
             decoder
                 .send_packet(&packet)
-                .map_err(|e| -> DecoderError { format!("send hvcC NALU: {}", e).into() })?;
+                .map_err(|e| -> DecoderError {
+                    format!("send {:?} parameter set: {}", params.codec, e).into()
+                })?;
         }
 
         self.decoder = Some(decoder);
         self.color = params.color;
+        self.codec = params.codec;
         Ok(())
     }
 
@@ -250,8 +561,14 @@ impl HwVideoDecoder for FfmpegHwDecoder {
             .as_mut()
             .ok_or_else(|| -> DecoderError { "submit before configure".into() })?;
 
-        let nalus = parse_hevc_nalu(sample)
-            .map_err(|e| -> DecoderError { format!("sample NALU parse: {}", e).into() })?;
+        // AV1 samples are a temporal unit of OBUs, VP9 samples a frame or
+        // superframe (the decoder splits it) — one packet, as stored.
+        let nalus = if self.codec.is_nal_based() {
+            parse_hevc_nalu(sample)
+                .map_err(|e| -> DecoderError { format!("sample NALU parse: {}", e).into() })?
+        } else {
+            vec![sample.to_vec()]
+        };
 
         for nalu in nalus {
             let mut packet = Packet::new(nalu.len());
@@ -289,6 +606,11 @@ impl HwVideoDecoder for FfmpegHwDecoder {
         let mut frame = ffmpeg_next::util::frame::Video::empty();
         match decoder.receive_frame(&mut frame) {
             Ok(()) => {
+                frame = match &mut self.output {
+                    FrameOutput::Hw => frame,
+                    FrameOutput::Upload(upload) => upload.upload(frame)?,
+                    FrameOutput::System(convert) => convert.convert(frame)?,
+                };
                 // pts was stored in milliseconds; convert back to microseconds for the trait.
                 let pts_us = frame.pts().unwrap_or(0) * 1000;
                 let width = frame.width();
@@ -326,8 +648,9 @@ mod tests {
     //! cfg gate) and verify the assumptions the rest of the player
     //! makes about the local FFmpeg build:
     //!
-    //!   - HEVC + H.264 decoders are linked in (we'd fail later at
-    //!     `decoder::find` with a less obvious message).
+    //!   - HEVC + H.264 decoders and a software AV1 decoder are linked
+    //!     in (we'd fail later at `decoder::find` with a less obvious
+    //!     message).
     //!   - The HW pixel-format constant we look for matches the
     //!     platform's hwaccel framework.
     //!   - The HW device context can be created on this host.
@@ -336,14 +659,14 @@ mod tests {
     //!     because the test runner reports "ok" with a log warning.
     //!
     //! Anything past this — `decoder.open(codec)`, `send_packet`,
-    //! `receive_frame` — needs real HEVC NALUs (SPS/PPS/VPS plus
+    //! `receive_frame` — needs real HEVC/H.264 NALUs (parameter sets plus
     //! sample data) and isn't worth carrying as test fixtures here.
 
     use super::*;
 
     #[test]
     fn ffmpeg_finds_hevc_decoder() {
-        // This player only configures HEVC and H.264; if HEVC isn't
+        // HEVC is the primary ladder codec; if it isn't
         // in the local FFmpeg, the rest of the test suite is moot.
         assert!(
             ffmpeg_next::decoder::find(ffmpeg_next::codec::Id::HEVC).is_some(),
@@ -351,6 +674,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ffmpeg_finds_vp9_decoder() {
+        assert!(
+            ffmpeg_next::decoder::find(ffmpeg_next::codec::Id::VP9).is_some(),
+            "FFmpeg build is missing the VP9 decoder",
+        );
+    }
+
     #[test]
     fn ffmpeg_finds_h264_decoder() {
         assert!(
@@ -359,6 +690,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ffmpeg_has_an_av1_decoder() {
+        // Native `av1` needs a hwaccel; libdav1d / libaom cover GPUs
+        // without AV1 decode. At least one software route must exist.
+        assert!(
+            ffmpeg_next::decoder::find_by_name("libdav1d").is_some()
+                || ffmpeg_next::decoder::find_by_name("libaom-av1").is_some(),
+            "FFmpeg build has no software AV1 decoder (libdav1d / libaom)",
+        );
+    }
+
+    #[test]
+    fn hevc_h264_vp9_never_pick_software() {
+        for codec in [VideoCodec::Hevc, VideoCodec::H264, VideoCodec::Vp9] {
+            let (_, software) = pick_decoder(codec, false, false).unwrap();
+            assert!(!software, "{:?} must stay on the hwaccel path", codec);
+        }
+    }
+
+    #[test]
+    fn av1_latch_is_shared_per_device_and_routes_to_software() {
+        let (dec, software) = pick_decoder(VideoCodec::Av1, false, true).unwrap();
+        assert!(software);
+        assert_ne!(dec.name(), "av1");
+
+        // What select_hw_format does when the GPU offers no AV1 format:
+        // the retry on the same device must route AV1 to software.
+        let latch = Arc::new(AtomicBool::new(false));
+        let first = FfmpegHwDecoder::new(Arc::clone(&latch));
+        let retry = FfmpegHwDecoder::new(Arc::clone(&latch));
+        first.av1_hw_unsupported.store(true, Ordering::Relaxed);
+        let (dec, software) = retry.pick_decoder(VideoCodec::Av1).unwrap();
+        assert!(software, "latched AV1 must decode in software");
+        assert_ne!(dec.name(), "av1");
+        let (_, software) = retry.pick_decoder(VideoCodec::Hevc).unwrap();
+        assert!(!software, "the latch is AV1-only");
+
+        let next_device = FfmpegHwDecoder::new(Arc::default());
+        assert!(!next_device.av1_hw_unsupported.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn software_mode_decodes_every_codec_on_cpu() {
+        // No hw-device on a GPU-less host: every codec must resolve to a
+        // CPU decoder, AV1 included (native `av1` can't decode without a
+        // hwaccel, so it has to be libdav1d / libaom).
+        for codec in [VideoCodec::Hevc, VideoCodec::H264, VideoCodec::Vp9, VideoCodec::Av1] {
+            let (dec, software) = pick_decoder(codec, true, false).unwrap();
+            assert!(software, "{:?} must be flagged software", codec);
+            if codec == VideoCodec::Av1 {
+                assert_ne!(dec.name(), "av1");
+            }
+        }
+        assert_eq!(FfmpegHwDecoder::new_software().name(), "Software (FFmpeg)");
+    }
+
     #[test]
     fn wanted_hw_pixel_format_matches_platform() {
         // Catches accidental cfg flips during a refactor — the format
@@ -378,7 +765,7 @@ mod tests {
         // av_hwdevice_ctx_create returns < 0 (no D3D11 device / no
         // /dev/dri/renderD128). Skip-with-warning rather than fail so
         // we don't break unrelated PR builds.
-        let mut decoder = FfmpegHwDecoder::new();
+        let mut decoder = FfmpegHwDecoder::new(Arc::default());
         match decoder.create_hw_device() {
             Ok(()) => {
                 assert!(
@@ -29,12 +29,12 @@ use ndk::media::media_codec::{
 };
 use ndk::media::media_format::MediaFormat;
 
-use crate::parsers::hevc;
+use crate::parsers::{avc, hevc};
 use crate::parsers::mp4::parse_hevc_nalu;
 
 use super::{
     AndroidHardwareBufferFrame, DecodedVideoFrame, DecoderError, HdrFrameMeta, HwVideoDecoder,
-    PlatformFrame, SendableAhb, VideoCodec, VideoDecoderParams,
+    PlatformFrame, SendableAhb, TransferFunction, VideoCodec, VideoDecoderParams,
 };
 
 pub struct MediaCodecDecoder {
@@ -72,6 +72,10 @@ pub struct MediaCodecDecoder {
     /// decoder configured — it needs them). False = strip them (plain
     /// HEVC decoders may choke on unspecified NAL types).
     keep_dv_nalus: bool,
+    /// Codec from configure params. Gates the HEVC-only NAL inspection in
+    /// submit() — H.264 NAL headers are one byte with a different type
+    /// layout, so `hevc::nal_unit_type` would misread them.
+    video_codec: VideoCodec,
     /// Pipeline stop signal (direct mode). The `submit_direct` input-buffer
     /// spin checks it so a teardown (seek/track-switch) doesn't leave the
     /// decode task wedged in the spin when the codec is being torn down.
@@ -202,6 +206,7 @@ impl MediaCodecDecoder {
             static_hdr_meta: None,
             seen_max_cll_nits: None,
             keep_dv_nalus: false,
+            video_codec: VideoCodec::Hevc,
             stop_signal: None,
         }
     }
@@ -333,13 +338,12 @@ impl MediaCodecDecoder {
                 let key_profile = CString::new("profile").unwrap();
                 ndk_sys::AMediaFormat_setInt32(format, key_profile.as_ptr(), profile_const);
             }
-            if !params.hvcc_nalus.is_empty() {
-                let mut csd = Vec::new();
-                for n in &params.hvcc_nalus {
-                    csd.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]);
-                    csd.extend_from_slice(n);
-                }
-                let key_csd = CString::new("csd-0").unwrap();
+            for (key, value) in color_format_keys(params) {
+                let key_c = CString::new(key).unwrap();
+                ndk_sys::AMediaFormat_setInt32(format, key_c.as_ptr(), value);
+            }
+            for (key, csd) in codec_specific_data(params) {
+                let key_csd = CString::new(key).unwrap();
                 ndk_sys::AMediaFormat_setBuffer(
                     format,
                     key_csd.as_ptr(),
@@ -603,6 +607,58 @@ impl MediaCodecDecoder {
     }
 }
 
+/// Codec-specific data for the MediaFormat. HEVC takes every parameter set
+/// (VPS/SPS/PPS) concatenated Annex-B in `csd-0`; AVC wants the SPS in
+/// `csd-0` and the PPS in `csd-1` (MediaCodec's documented layout — some
+/// vendor AVC decoders ignore a PPS packed into csd-0). AV1 takes the
+/// verbatim `av1C` record as `csd-0`.
+fn codec_specific_data(params: &VideoDecoderParams) -> Vec<(&'static str, Vec<u8>)> {
+    fn annex_b<'a>(nalus: impl Iterator<Item = &'a Vec<u8>>) -> Vec<u8> {
+        let mut csd = Vec::new();
+        for n in nalus {
+            csd.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]);
+            csd.extend_from_slice(n);
+        }
+        csd
+    }
+    match params.codec {
+        VideoCodec::Vp9 => Vec::new(),
+        VideoCodec::Av1 => vec![("csd-0", params.config_record.clone())],
+        _ if params.parameter_sets.is_empty() => Vec::new(),
+        VideoCodec::Hevc => vec![("csd-0", annex_b(params.parameter_sets.iter()))],
+        VideoCodec::H264 => {
+            let is = |t: u8| move |n: &&Vec<u8>| avc::nal_unit_type(n) == Some(t);
+            let sps = annex_b(params.parameter_sets.iter().filter(is(avc::NAL_SPS)));
+            let pps = annex_b(params.parameter_sets.iter().filter(is(avc::NAL_PPS)));
+            vec![("csd-0", sps), ("csd-1", pps)]
+        }
+    }
+}
+
+/// MediaFormat colour keys for codecs whose bitstream can't say PQ/HLG.
+/// A VP9 frame header only knows "BT.2020" — the transfer lives in `vpcC`
+/// alone, so without these a Profile 2 HDR stream renders as SDR in direct
+/// mode. Values are MediaFormat COLOR_STANDARD_* / COLOR_TRANSFER_* /
+/// COLOR_RANGE_*.
+fn color_format_keys(params: &VideoDecoderParams) -> Vec<(&'static str, i32)> {
+    if params.codec != VideoCodec::Vp9 || !params.color.is_hdr() {
+        return Vec::new();
+    }
+    let transfer = match params.color.transfer {
+        TransferFunction::Pq => 6,  // COLOR_TRANSFER_ST2084
+        TransferFunction::Hlg => 7, // COLOR_TRANSFER_HLG
+        TransferFunction::Sdr => 3, // COLOR_TRANSFER_SDR_VIDEO
+    };
+    let mut keys = vec![
+        ("color-transfer", transfer),
+        ("color-range", if params.color.full_range { 1 } else { 2 }),
+    ];
+    if params.color.bt2020 {
+        keys.push(("color-standard", 6)); // COLOR_STANDARD_BT2020
+    }
+    keys
+}
+
 impl HwVideoDecoder for MediaCodecDecoder {
     fn name(&self) -> &'static str {
         "MediaCodec"
@@ -612,7 +668,10 @@ impl HwVideoDecoder for MediaCodecDecoder {
         let mime = match params.codec {
             VideoCodec::Hevc => "video/hevc",
             VideoCodec::H264 => "video/avc",
+            VideoCodec::Av1 => "video/av01",
+            VideoCodec::Vp9 => "video/x-vnd.on2.vp9",
         };
+        self.video_codec = params.codec;
 
         // Direct mode: the codec renders straight into the host's video
         // Surface (HW plane carries HDR/HDR10+/DV to the display) — no
@@ -740,14 +799,11 @@ impl HwVideoDecoder for MediaCodecDecoder {
         format.set_str("mime", mime);
         format.set_i32("width", params.width as i32);
         format.set_i32("height", params.height as i32);
-        if !params.hvcc_nalus.is_empty() {
-            // Build Annex-B csd-0: start-code prefix + raw NALU body for each NALU.
-            let mut csd = Vec::new();
-            for n in &params.hvcc_nalus {
-                csd.extend_from_slice(&[0x00, 0x00, 0x00, 0x01]);
-                csd.extend_from_slice(n);
-            }
-            format.set_buffer("csd-0", &csd);
+        for (key, value) in color_format_keys(&params) {
+            format.set_i32(key, value);
+        }
+        for (key, csd) in codec_specific_data(&params) {
+            format.set_buffer(key, &csd);
         }
 
         codec
@@ -778,8 +834,13 @@ impl HwVideoDecoder for MediaCodecDecoder {
 
         // `sample` is length-prefixed NALU (raw mdat). Convert to Annex-B
         // (start-code prefixed) — MediaCodec expects this for HEVC/H.264.
-        let nalus = parse_hevc_nalu(sample)
-            .map_err(|e| -> DecoderError { format!("NALU parse: {}", e).into() })?;
+        // AV1 temporal units / VP9 frames go in exactly as stored.
+        let nalus = if self.video_codec.is_nal_based() {
+            parse_hevc_nalu(sample)
+                .map_err(|e| -> DecoderError { format!("NALU parse: {}", e).into() })?
+        } else {
+            vec![sample.to_vec()]
+        };
         let mut annex_b = Vec::with_capacity(sample.len() + nalus.len() * 4);
         for n in nalus {
             // Harvest HDR metadata from prefix SEIs on the way in —
@@ -789,7 +850,12 @@ impl HwVideoDecoder for MediaCodecDecoder {
             // on the SDR ladder.
             // NALUs here carry the 4-byte start code prefix.
             let body = n.strip_prefix(&[0, 0, 0, 1][..]).unwrap_or(&n);
-            let nal_type = hevc::nal_unit_type(body);
+            // DV RPU/EL and the HDR SEI harvest are HEVC concepts here;
+            // H.264 access units pass through untouched.
+            let nal_type = match self.video_codec {
+                VideoCodec::Hevc => hevc::nal_unit_type(body),
+                VideoCodec::H264 | VideoCodec::Av1 | VideoCodec::Vp9 => None,
+            };
             // Dolby Vision RPU / enhancement-layer NALs: the platform
             // video/dolby-vision decoder NEEDS them (keep_dv_nalus), but a
             // plain video/hevc decoder doesn't know them — most ignore
@@ -1,10 +1,11 @@
-// MediaCodec-based AAC / AC-3 / EAC-3 audio decoder for Android.
+// MediaCodec-based AAC / AC-3 / EAC-3 / Opus / FLAC audio decoder for Android.
 //
-// Decodes raw access units (from DASH mdat) to interleaved stereo f32 PCM
-// at the stream's native sample rate. Multichannel input (typical for AC-3
-// and EAC-3 5.1) is downmixed to stereo here — the cpal AudioRenderer is
-// always stereo, so the decoder owns the downmix instead of relying on the
-// renderer to interpret arbitrary channel counts.
+// Decodes raw access units (from DASH mdat) to interleaved f32 PCM in the
+// output's channel count and layout. Multichannel input (typical for AC-3
+// and EAC-3 5.1) is remixed here via `audio_layout::ChannelRemixer` — the
+// AudioTrack PCM sink is stereo, so 5.1 is folded with the host's downmix
+// coefficients instead of leaving the renderer to interpret arbitrary
+// channel counts.
 //
 // The actual output channel count comes from MediaCodec's OutputFormatChanged
 // event, not the input hint — for EAC-3 streams the MPD often advertises
@@ -21,6 +22,10 @@ use ndk::media::media_codec::{
 use ndk::media::media_format::MediaFormat;
 
 use super::{AudioCodec, AudioDecoder, AudioDecoderParams, DecodedAudioFrame, DecoderError};
+use crate::audio_layout::{ChannelRemixer, DownmixCoefficients};
+use crate::parsers::opus::{OPUS_DECODE_RATE, OPUS_SEEK_PREROLL};
+
+const OPUS_SEEK_PREROLL_NS: u64 = OPUS_SEEK_PREROLL as u64 * 1_000_000_000 / OPUS_DECODE_RATE as u64;
 
 pub struct MediaCodecAudioDecoder {
     codec: Option<MediaCodec>,
@@ -30,91 +35,27 @@ pub struct MediaCodecAudioDecoder {
     /// the manifest hint; corrected by the OutputFormatChanged event before
     /// the first frame is drained.
     channels: usize,
+    /// Output channel count / fold gains from configure, and the remixer
+    /// built from them for the current `channels`.
+    output_channels: u16,
+    downmix: DownmixCoefficients,
+    remixer: ChannelRemixer,
 }
 
 unsafe impl Send for MediaCodecAudioDecoder {}
 
 impl MediaCodecAudioDecoder {
     pub fn new() -> Self {
-        Self { codec: None, input_rate: 44100, output_rate: 44100, channels: 2 }
-    }
-}
-
-/// Downmix interleaved multichannel f32 PCM to stereo. Channel order for
-/// MediaCodec PCM follows the AOSP convention: mono / L,R / L,R,C / L,R,C,LFE,BL,BR
-/// for 1/2/3/6 channels respectively. We use ITU-R BS.775 coefficients for
-/// 5.1 and a simple average for unusual counts so the result is always
-/// audible rather than mis-routed silence.
-fn downmix_to_stereo(input: &[f32], channels: usize) -> Vec<f32> {
-    if channels <= 1 {
-        let mut out = Vec::with_capacity(input.len() * 2);
-        for &s in input {
-            out.push(s);
-            out.push(s);
+        Self {
+            codec: None,
+            input_rate: 44100,
+            output_rate: 44100,
+            channels: 2,
+            output_channels: 2,
+            downmix: DownmixCoefficients::DEFAULT,
+            remixer: ChannelRemixer::new(2, 2, DownmixCoefficients::DEFAULT),
         }
-        return out;
-    }
-    if channels == 2 {
-        return input.to_vec();
     }
-    let frames = input.len() / channels;
-    let mut out = Vec::with_capacity(frames * 2);
-    const ATT: f32 = 0.707; // -3 dB
-    for f in 0..frames {
-        let base = f * channels;
-        let (l, r) = match channels {
-            // L,R,C — fold C equally into L/R.
-            3 => (input[base] + ATT * input[base + 2], input[base + 1] + ATT * input[base + 2]),
-            // L,R,BL,BR (quad).
-            4 => (
-                input[base] + ATT * input[base + 2],
-                input[base + 1] + ATT * input[base + 3],
-            ),
-            // L,R,C,BL,BR (5.0) — same as 5.1 minus LFE.
-            5 => (
-                input[base] + ATT * input[base + 2] + ATT * input[base + 3],
-                input[base + 1] + ATT * input[base + 2] + ATT * input[base + 4],
-            ),
-            // L,R,C,LFE,BL,BR (5.1) — LFE dropped, standard ITU downmix.
-            6 => (
-                input[base] + ATT * input[base + 2] + ATT * input[base + 4],
-                input[base + 1] + ATT * input[base + 2] + ATT * input[base + 5],
-            ),
-            // L,R,C,LFE,BL,BR,SL,SR (7.1).
-            8 => (
-                input[base]
-                    + ATT * input[base + 2]
-                    + ATT * input[base + 4]
-                    + ATT * input[base + 6],
-                input[base + 1]
-                    + ATT * input[base + 2]
-                    + ATT * input[base + 5]
-                    + ATT * input[base + 7],
-            ),
-            // Unknown layout — average even-indexed → L, odd → R as a
-            // best-effort fallback rather than producing silence.
-            _ => {
-                let mut sum_l = 0.0_f32;
-                let mut sum_r = 0.0_f32;
-                let mut cnt_l = 0_u32;
-                let mut cnt_r = 0_u32;
-                for c in 0..channels {
-                    if c % 2 == 0 {
-                        sum_l += input[base + c];
-                        cnt_l += 1;
-                    } else {
-                        sum_r += input[base + c];
-                        cnt_r += 1;
-                    }
-                }
-                (sum_l / cnt_l.max(1) as f32, sum_r / cnt_r.max(1) as f32)
-            }
-        };
-        // Clip — the additive downmix can exceed ±1.0 on hot 5.1 sources.
-        out.push(l.clamp(-1.0, 1.0));
-        out.push(r.clamp(-1.0, 1.0));
-    }
-    out
 }
 
 fn resample_linear(input: &[f32], channels: usize, from_rate: u32, to_rate: u32) -> Vec<f32> {
@@ -145,6 +86,8 @@ impl AudioDecoder for MediaCodecAudioDecoder {
             AudioCodec::Aac => "audio/mp4a-latm",
             AudioCodec::Ac3 => "audio/ac3",
             AudioCodec::Eac3 => "audio/eac3",
+            AudioCodec::Opus => "audio/opus",
+            AudioCodec::Flac => "audio/flac",
         };
 
         let codec =
@@ -159,6 +102,14 @@ impl AudioDecoder for MediaCodecAudioDecoder {
         if !params.codec_specific_data.is_empty() {
             format.set_buffer("csd-0", &params.codec_specific_data);
         }
+        if let AudioCodec::Opus = params.codec {
+            // The Opus decoder also wants csd-1 (codec delay) and csd-2
+            // (seek pre-roll), both u64 LE nanoseconds. Codec delay is 0:
+            // the player trims pre-skip itself so both backends behave
+            // alike (see `audio_play`).
+            format.set_buffer("csd-1", &0u64.to_le_bytes());
+            format.set_buffer("csd-2", &OPUS_SEEK_PREROLL_NS.to_le_bytes());
+        }
 
         codec
             .configure(&format, None, MediaCodecDirection::Decoder)
@@ -177,6 +128,9 @@ impl AudioDecoder for MediaCodecAudioDecoder {
         self.input_rate = params.input_sample_rate;
         self.output_rate = params.output_sample_rate;
         self.channels = params.input_channels as usize;
+        self.output_channels = params.output_channels;
+        self.downmix = params.downmix;
+        self.remixer = ChannelRemixer::new(self.channels, self.output_channels as usize, self.downmix);
         Ok(())
     }
 
@@ -234,7 +188,7 @@ impl AudioDecoder for MediaCodecAudioDecoder {
                 // Decode pipeline:
                 //   1. Read i16 PCM from the codec buffer
                 //   2. Convert to f32 normalised to ±1.0
-                //   3. Downmix to stereo (no-op when input is already stereo)
+                //   3. Remix to the output layout (reorder / fold as needed)
                 //   4. Linear resample to the output device rate
                 let samples = {
                     let buf: &[u8] = out.buffer();
@@ -242,9 +196,13 @@ impl AudioDecoder for MediaCodecAudioDecoder {
                     let pcm_i16: &[i16] = bytemuck::cast_slice(pcm);
                     let raw_f32: Vec<f32> =
                         pcm_i16.iter().map(|&s| s as f32 / 32768.0_f32).collect();
-                    let stereo = downmix_to_stereo(&raw_f32, self.channels);
-                    // After downmix we ALWAYS have 2 channels.
-                    resample_linear(&stereo, 2, self.input_rate, self.output_rate)
+                    let remixed = self.remixer.process(&raw_f32);
+                    resample_linear(
+                        &remixed,
+                        self.output_channels as usize,
+                        self.input_rate,
+                        self.output_rate,
+                    )
                 };
 
                 codec
@@ -270,6 +228,11 @@ impl AudioDecoder for MediaCodecAudioDecoder {
                     // a guess; the bitstream might decode to 6, 2 or 8).
                     // Trust what the codec just told us.
                     self.channels = ch as usize;
+                    self.remixer = ChannelRemixer::new(
+                        self.channels,
+                        self.output_channels as usize,
+                        self.downmix,
+                    );
                 }
                 log::info!(
                     "audio output format: {}Hz {}ch (decoder-reported)",
@@ -32,13 +32,25 @@ pub mod videotoolbox;
 // Video decoder types
 // ---------------------------------------------------------------------------
 
-#[derive(Clone, Copy, Debug)]
+/// Picked per representation from the init segment's decoder
+/// configuration box (`hvcC` → HEVC, `avcC` → H.264, `av1C` → AV1,
+/// `vpcC` → VP9), not the MPD codecs string, so an ABR swap always
+/// configures what the bitstream carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VideoCodec {
     Hevc,
-    // H.264 reserved for the planned ffmpeg/MediaCodec branch; currently only
-    // HEVC streams are exercised so the variant is never constructed.
-    #[allow(dead_code)]
     H264,
+    Av1,
+    Vp9,
+}
+
+impl VideoCodec {
+    /// Samples are length-prefixed NAL units (HEVC/H.264) that decoders
+    /// re-frame as Annex-B. AV1 samples (low-overhead OBUs) and VP9 samples
+    /// (a frame or superframe) go to the decoder as-is.
+    pub fn is_nal_based(&self) -> bool {
+        matches!(self, VideoCodec::Hevc | VideoCodec::H264)
+    }
 }
 
 pub struct VideoDecoderParams {
@@ -49,9 +61,14 @@ pub struct VideoDecoderParams {
     pub width: u32,
     #[allow(dead_code)]
     pub height: u32,
-    /// Raw NALU bytes (no length prefix, no start code) — VPS/SPS/PPS for HEVC,
-    /// extracted from the hvcC box in the init segment.
-    pub hvcc_nalus: Vec<Vec<u8>>,
+    /// Raw NALU bytes (no length prefix, no start code) — VPS/SPS/PPS for HEVC
+    /// (from the hvcC box), SPS/PPS for H.264 (from avcC), in that order.
+    pub parameter_sets: Vec<Vec<u8>>,
+    /// Verbatim body of the codec configuration box for codecs that are
+    /// not NAL-based (`av1C` for AV1). Backends hand it over as-is: FFmpeg
+    /// extradata, MediaCodec `csd-0`. Empty for HEVC/H.264, and for VP9,
+    /// whose frame headers carry everything in-band.
+    pub config_record: Vec<u8>,
     /// Colour information for the representation, parsed from the SPS VUI
     /// (authoritative — the MPD often mis-signals BT.709 on PQ content).
     /// Drives 10-bit surface allocation and the HDR tonemap path selection;
@@ -109,10 +126,11 @@ impl VideoColorInfo {
         !matches!(self.transfer, TransferFunction::Sdr)
     }
 
-    /// Build from a parsed SPS, with the hvcC bit depth as fallback.
+    /// Build from a parsed SPS (HEVC or H.264 — both parsers produce the
+    /// same shape), with the hvcC/avcC bit depth as fallback.
     pub fn from_sps(
         sps: Option<crate::parsers::hevc::SpsColorInfo>,
-        hvcc_bit_depth: Option<u8>,
+        config_bit_depth: Option<u8>,
     ) -> Self {
         match sps {
             Some(s) => Self {
@@ -126,7 +144,7 @@ impl VideoColorInfo {
                 full_range: s.full_range,
             },
             None => Self {
-                bit_depth: hvcc_bit_depth.unwrap_or(8),
+                bit_depth: config_bit_depth.unwrap_or(8),
                 ..Default::default()
             },
         }
@@ -299,6 +317,8 @@ pub enum AudioCodec {
     Aac,
     Ac3,
     Eac3,
+    Opus,
+    Flac,
 }
 
 pub struct AudioDecoderParams {
@@ -309,15 +329,23 @@ pub struct AudioDecoderParams {
     pub input_channels: u16,
     /// Target sample rate for the output device (from AudioRenderer::sample_rate()).
     pub output_sample_rate: u32,
+    /// Output device channel count (from `AudioSink::channels()`). Decoded
+    /// frames are remixed to this count in the device's channel order.
+    pub output_channels: u16,
+    /// Fold gains for source channels the device lacks (e.g. 5.1 on a
+    /// stereo device), from `Player::set_downmix`.
+    pub downmix: crate::audio_layout::DownmixCoefficients,
     /// Codec-specific extradata. For AAC this is the 2-byte AudioSpecificConfig
-    /// from `esds`. For AC-3 / EAC-3 it is empty — those formats are
-    /// self-describing (each frame carries its own syncinfo header), so neither
-    /// FFmpeg nor MediaCodec needs csd-0 to initialise.
+    /// from `esds`; for Opus an `OpusHead` built from `dOps`; for FLAC the
+    /// `fLaC` stream header built from `dfLa`. For AC-3 / EAC-3 it is empty —
+    /// those formats are self-describing (each frame carries its own syncinfo
+    /// header), so neither FFmpeg nor MediaCodec needs csd-0 to initialise.
     pub codec_specific_data: Vec<u8>,
 }
 
-/// A decoded audio buffer: interleaved stereo f32 PCM samples at
-/// `output_sample_rate`, timestamped in milliseconds.
+/// A decoded audio buffer: interleaved f32 PCM samples with
+/// `output_channels` channels in device order at `output_sample_rate`,
+/// timestamped in milliseconds.
 pub struct DecodedAudioFrame {
     pub pts_ms: i64,
     pub samples: Vec<f32>,
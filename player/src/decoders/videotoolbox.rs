@@ -1,8 +1,8 @@
-//! Apple (macOS + iOS) HEVC decoder backed by VTDecompressionSession.
+//! Apple (macOS + iOS) HEVC / H.264 decoder backed by VTDecompressionSession.
 //!
 //! Pipeline:
 //!   `configure(params)` builds a CMVideoFormatDescription from the hvcC
-//!   VPS/SPS/PPS NALUs and creates a VTDecompressionSession with a
+//!   VPS/SPS/PPS (or avcC SPS/PPS) NALUs and creates a VTDecompressionSession with a
 //!   destination-image-buffer attribute dict that requests NV12
 //!   (kCVPixelFormatType_420YpCbCr8BiPlanarVideoRange) on a
 //!   Metal-compatible IOSurface.
@@ -137,6 +137,15 @@ extern "C" {
         format_description_out: *mut CMVideoFormatDescriptionRef,
     ) -> OSStatus;
 
+    fn CMVideoFormatDescriptionCreateFromH264ParameterSets(
+        allocator: CFAllocatorRef,
+        parameter_set_count: usize,
+        parameter_set_pointers: *const *const u8,
+        parameter_set_sizes: *const usize,
+        nal_unit_header_length: i32,
+        format_description_out: *mut CMVideoFormatDescriptionRef,
+    ) -> OSStatus;
+
     fn CMBlockBufferCreateWithMemoryBlock(
         structure_allocator: CFAllocatorRef,
         memory_block: *mut c_void,
@@ -278,33 +287,67 @@ impl HwVideoDecoder for VideoToolboxDecoder {
     }
 
     fn configure(&mut self, params: VideoDecoderParams) -> Result<(), DecoderError> {
-        if !matches!(params.codec, VideoCodec::Hevc) {
-            return Err("VideoToolboxDecoder currently supports HEVC only".into());
-        }
-        if params.hvcc_nalus.is_empty() {
-            return Err("hvcc_nalus is empty — need VPS/SPS/PPS".into());
+        // VT's AV1 decoder only exists on M3 / A17 Pro and later and needs
+        // an av1C-built format description extension — not wired up. VP9
+        // in VT is private API (supplemental decoder registration).
+        let hevc = match params.codec {
+            VideoCodec::Hevc => true,
+            VideoCodec::H264 => false,
+            VideoCodec::Av1 | VideoCodec::Vp9 => {
+                return Err(format!(
+                    "{:?} is not supported by the VideoToolbox backend",
+                    params.codec
+                )
+                .into());
+            }
+        };
+        if params.parameter_sets.is_empty() {
+            return Err(if hevc {
+                "parameter_sets is empty — need VPS/SPS/PPS".into()
+            } else {
+                "parameter_sets is empty — need SPS/PPS".into()
+            });
         }
 
         // CMVideoFormatDescription wants C arrays of (ptr, size). Build
         // them from the Vec<Vec<u8>> input; the NALUs themselves are kept
         // alive by params for the duration of this call.
-        let ptrs: Vec<*const u8> = params.hvcc_nalus.iter().map(|n| n.as_ptr()).collect();
-        let sizes: Vec<usize> = params.hvcc_nalus.iter().map(|n| n.len()).collect();
+        let ptrs: Vec<*const u8> = params.parameter_sets.iter().map(|n| n.as_ptr()).collect();
+        let sizes: Vec<usize> = params.parameter_sets.iter().map(|n| n.len()).collect();
 
         let mut format_desc: CMVideoFormatDescriptionRef = ptr::null_mut();
-        let st = unsafe {
-            CMVideoFormatDescriptionCreateFromHEVCParameterSets(
-                ptr::null(),
-                ptrs.len(),
-                ptrs.as_ptr(),
-                sizes.as_ptr(),
-                4, // 4-byte length prefix (AVCC)
-                ptr::null(),
-                &mut format_desc,
-            )
+        // Samples are length-prefixed with 4 bytes (AVCC framing) for both
+        // codecs — the same assumption parse_hevc_nalu makes elsewhere.
+        let (st, what) = unsafe {
+            if hevc {
+                (
+                    CMVideoFormatDescriptionCreateFromHEVCParameterSets(
+                        ptr::null(),
+                        ptrs.len(),
+                        ptrs.as_ptr(),
+                        sizes.as_ptr(),
+                        4,
+                        ptr::null(),
+                        &mut format_desc,
+                    ),
+                    "CMVideoFormatDescriptionCreateFromHEVCParameterSets",
+                )
+            } else {
+                (
+                    CMVideoFormatDescriptionCreateFromH264ParameterSets(
+                        ptr::null(),
+                        ptrs.len(),
+                        ptrs.as_ptr(),
+                        sizes.as_ptr(),
+                        4,
+                        &mut format_desc,
+                    ),
+                    "CMVideoFormatDescriptionCreateFromH264ParameterSets",
+                )
+            }
         };
         if st != 0 || format_desc.is_null() {
-            return Err(format!("CMVideoFormatDescriptionCreateFromHEVCParameterSets: {}", st).into());
+            return Err(format!("{}: {}", what, st).into());
         }
         self.format_desc = format_desc;
 
@@ -572,6 +615,3 @@ impl HwVideoDecoder for VideoToolboxDecoder {
         }))
     }
 }
-
-// Suppress unused-import warning when VideoCodec::H264 isn't constructed.
-const _: VideoCodec = VideoCodec::Hevc;
@@ -30,6 +30,10 @@ pub enum PlayerEvent {
     Playing,
     /// Paused by the consumer via `pause()`.
     Paused,
+    /// `set_playback_rate` changed the speed (1.0 = normal). Emitted once
+    /// per actual change, after clamping; `Position` keeps reporting media
+    /// time, so it advances `rate` × faster than the wall clock.
+    PlaybackRateChanged { rate: f32 },
     /// Periodic — emitted at ≤ 4 Hz during playback.
     Position {
         position: Duration,
@@ -46,6 +50,21 @@ pub enum PlayerEvent {
     TrackChanged { kind: TrackKind, info: TrackInfo },
     /// Decoder hiccup the player recovered from. UI hint, not fatal.
     GlitchRecovered { detail: String },
+    /// The audio output moved to another device (host pick, system default
+    /// change, device unplugged) or reopened at another format. `device` is
+    /// `None` for outputs without a device identity. Not emitted for the
+    /// initial open.
+    AudioDeviceChanged {
+        device: Option<crate::AudioOutputDevice>,
+        sample_rate: u32,
+        channels: u16,
+    },
+    /// A `step_frame` landed: `position` is the exact (µs-precise, 0-based)
+    /// PTS of the frame now on screen.
+    FrameStepped { position: Duration },
+    /// Trick play (`set_trick_play`) changed speed: ±2..=32, 0 = back to
+    /// normal playback (also emitted when it runs off either end).
+    TrickPlayChanged { speed: i32 },
     /// Cumulative stats — emitted at ≤ 1 Hz.
     Stats {
         video_frames_decoded: u64,
@@ -60,9 +79,18 @@ pub enum PlayerEvent {
         /// What is actually being rendered post-ABR (matches the current
         /// representation). `None` before the first frame.
         current_resolution: Option<(u32, u32)>,
-        /// Last-frame L/R peak in dB (range typically -60..=0).
+        /// Last-frame L/R peak in dB (range typically -60..=0): the front
+        /// pair of a surround device, the one channel twice on a mono one.
         /// `None` until at least one audio frame has been mixed.
         audio_peak_db: Option<[f32; 2]>,
+        /// Last-frame peak in dB per output channel, in device order (up
+        /// to 7.1 — see `Player::set_downmix`). `None` like `audio_peak_db`.
+        audio_peak_db_channels: Option<Vec<f32>>,
+        /// Integrated loudness of the programme so far in LUFS (EBU R128 /
+        /// ITU-R BS.1770, gated), measured on the PCM path before any
+        /// normalisation gain. `None` until enough non-silent audio has
+        /// played; not updated under bitstream passthrough.
+        audio_loudness_lufs: Option<f32>,
         /// Measured A/V clock drift since pipeline start, in ms: video
         /// wall clock minus the audio device clock (negative = audio
         /// ahead). `None` while unmeasured (first second, or sinks that
@@ -95,6 +123,30 @@ pub enum PlayerEvent {
         /// number the ABR engine decides on).
         bandwidth_bps: u64,
     },
+    /// Host-rendered subtitles (`Player::set_host_subtitles`): a cue came
+    /// on screen at the current playback position. Several may be active
+    /// at once; the visible set is every `SubtitleCue` since the last
+    /// `SubtitleCueCleared`.
+    SubtitleCue {
+        start: Duration,
+        end: Duration,
+        /// Plain text, tags stripped, `\n` line breaks.
+        text: String,
+        /// `text` as styled runs, in order; at least one.
+        styling: Vec<SubtitleSpan>,
+        /// Raw WebVTT cue settings (`"line:0 align:left"`), empty when the
+        /// cue had none.
+        settings: String,
+    },
+    /// Host-rendered subtitles: the active set changed (or the track was
+    /// switched / cleared) — remove every cue shown so far.
+    SubtitleCueCleared,
+    /// The subtitle track list changed: closed captions turned up in the
+    /// video stream and are now listed in `Player::get_tracks` (as caption
+    /// tracks, `TextAdaptation::embedded_captions`), or an external
+    /// subtitle was added (`Player::add_external_subtitle`). Re-read the
+    /// list.
+    TextTracksChanged,
     /// End of media reached.
     EndOfStream,
     /// Fatal — playback cannot continue. `kind` lets the consumer
@@ -105,6 +157,22 @@ pub enum PlayerEvent {
     },
 }
 
+/// A run of a `SubtitleCue`'s text sharing one style. `None` colours mean
+/// the source left it to the renderer's default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SubtitleSpan {
+    pub text: String,
+    /// Text fill, RGBA.
+    pub color: Option<[u8; 4]>,
+    /// Box behind the run, RGBA.
+    pub background: Option<[u8; 4]>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    /// Ruby annotation to draw small above this run.
+    pub ruby: Option<String>,
+}
+
 /// Categorised player error so consumers can branch programmatically
 /// (refresh token on 401, abandon on Decoder, etc.) without parsing
 /// human-readable detail strings.
@@ -0,0 +1,275 @@
+//! Host-rendered subtitles: cues delivered as `PlayerEvent`s instead of
+//! drawn by the overlay.
+//!
+//! Apps that draw subtitles in their own UI layer (accessibility services,
+//! custom fonts, TalkBack) switch this on with `Player::set_host_subtitles`.
+//! `text_play` then queues cues here rather than into the `VideoSink`, and a
+//! clock task spawned per subtitle selection (`host_subtitle_clock`) steps
+//! the active set along the rendered position — the `position_ms` every
+//! sync loop publishes from `MediaClock` — emitting `SubtitleCueCleared`
+//! and `SubtitleCue` whenever it changes. The on-screen set is always the
+//! `SubtitleCue`s received since the last `SubtitleCueCleared`.
+
+use crate::events::{PlayerEvent, SubtitleSpan};
+use crate::parsers::vtt::VttCue;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Same bound as the overlay's cue store.
+const MAX_CUES: usize = 5000;
+
+#[derive(Default)]
+struct State {
+    /// Sorted by start_ms ascending, never evicted by time (backward seeks
+    /// must find old cues again — see `SubtitleOverlay::set_pts_ms`).
+    cues: Vec<VttCue>,
+    /// Identity of the cue set last announced; empty = nothing on screen.
+    shown: String,
+}
+
+/// Cue store + active-set tracker for host-rendered mode. One per player,
+/// shared by `text_play` (producer) and the clock task (consumer).
+pub(crate) struct HostSubtitles {
+    enabled: AtomicBool,
+    /// Bumped on every subtitle selection change, so a clock task spawned
+    /// for an older selection notices and exits.
+    session: AtomicU64,
+    /// `Player::set_subtitle_offset_ms`: cues show this much later.
+    offset_ms: AtomicI64,
+    state: Mutex<State>,
+    events: Arc<broadcast::Sender<PlayerEvent>>,
+}
+
+impl HostSubtitles {
+    pub(crate) fn new(events: Arc<broadcast::Sender<PlayerEvent>>) -> Self {
+        HostSubtitles {
+            enabled: AtomicBool::new(false),
+            session: AtomicU64::new(0),
+            offset_ms: AtomicI64::new(0),
+            state: Mutex::new(State::default()),
+            events,
+        }
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the previous value.
+    pub(crate) fn set_enabled(&self, enabled: bool) -> bool {
+        self.enabled.swap(enabled, Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_offset_ms(&self, offset_ms: i64) {
+        self.offset_ms.store(offset_ms, Ordering::Relaxed);
+    }
+
+    pub(crate) fn session(&self) -> u64 {
+        self.session.load(Ordering::Relaxed)
+    }
+
+    /// Drop every cue (emitting `SubtitleCueCleared` if any was on screen)
+    /// and start a new session. Returns the new session id.
+    pub(crate) fn reset(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.cues.clear();
+        if !std::mem::take(&mut state.shown).is_empty() {
+            let _ = self.events.send(PlayerEvent::SubtitleCueCleared);
+        }
+        self.session.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Add parsed cues; `text_play` sends a batch per segment. As in the
+    /// overlay, a cue with the start and text of a queued one replaces it.
+    pub(crate) fn queue(&self, cues: Vec<VttCue>) {
+        let mut state = self.state.lock().unwrap();
+        state.cues.retain(|c| {
+            !cues
+                .iter()
+                .any(|n| n.start_ms == c.start_ms && n.text == c.text)
+        });
+        state.cues.extend(cues);
+        state.cues.sort_by_key(|c| c.start_ms);
+        if state.cues.len() > MAX_CUES {
+            let excess = state.cues.len() - MAX_CUES;
+            state.cues.drain(0..excess);
+        }
+    }
+
+    /// Re-evaluate the active set at `pts_ms` and announce it if it changed.
+    /// Returns the next cue boundary after `pts_ms` (offset applied, so on
+    /// the same timeline as `pts_ms`), if any, so the caller can sleep until
+    /// then.
+    pub(crate) fn tick(&self, pts_ms: i64) -> Option<i64> {
+        let offset_ms = self.offset_ms.load(Ordering::Relaxed);
+        let pts_ms = pts_ms - offset_ms;
+        let mut state = self.state.lock().unwrap();
+        let active: Vec<&VttCue> = state.cues.iter().filter(|c| c.is_active(pts_ms)).collect();
+        let key = active
+            .iter()
+            .map(|c| format!("{}:{}:{}", c.start_ms, c.end_ms, c.text))
+            .collect::<Vec<_>>()
+            .join("\u{1}");
+        if key != state.shown {
+            if !state.shown.is_empty() {
+                let _ = self.events.send(PlayerEvent::SubtitleCueCleared);
+            }
+            for cue in &active {
+                let _ = self.events.send(cue_event(cue));
+            }
+            state.shown = key;
+        }
+        state
+            .cues
+            .iter()
+            .flat_map(|c| [c.start_ms, c.end_ms])
+            .filter(|&t| t > pts_ms)
+            .min()
+            .map(|t| t + offset_ms)
+    }
+}
+
+/// The `SubtitleCue` event for one cue. Unstyled cues get a single span
+/// covering the whole text, so hosts always have `styling` to walk.
+fn cue_event(cue: &VttCue) -> PlayerEvent {
+    let styling = if cue.spans.is_empty() {
+        vec![SubtitleSpan {
+            text: cue.text.clone(),
+            ..SubtitleSpan::default()
+        }]
+    } else {
+        cue.spans
+            .iter()
+            .map(|s| SubtitleSpan {
+                text: s.text.clone(),
+                color: s.style.color,
+                background: s.style.background,
+                bold: s.style.bold,
+                italic: s.style.italic,
+                underline: s.style.underline,
+                ruby: s.ruby.clone(),
+            })
+            .collect()
+    };
+    PlayerEvent::SubtitleCue {
+        start: Duration::from_millis(cue.start_ms.max(0) as u64),
+        end: Duration::from_millis(cue.end_ms.max(0) as u64),
+        text: cue.text.clone(),
+        styling,
+        settings: cue.settings.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::vtt::{CueSpan, SpanStyle};
+
+    fn cue(start_ms: i64, end_ms: i64, text: &str) -> VttCue {
+        VttCue {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            settings: String::new(),
+            spans: Vec::new(),
+            layout: None,
+        }
+    }
+
+    fn drain(rx: &mut broadcast::Receiver<PlayerEvent>) -> Vec<String> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|ev| match ev {
+                PlayerEvent::SubtitleCue { text, .. } => text,
+                PlayerEvent::SubtitleCueCleared => "-".to_string(),
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn announces_only_changes_of_the_active_set() {
+        let (tx, mut rx) = broadcast::channel(64);
+        let host = HostSubtitles::new(Arc::new(tx));
+        host.queue(vec![cue(2000, 3000, "two"), cue(1000, 2500, "one")]);
+
+        assert_eq!(host.tick(0), Some(1000));
+        assert!(drain(&mut rx).is_empty());
+        assert_eq!(host.tick(1000), Some(2000));
+        assert_eq!(host.tick(1500), Some(2000));
+        assert_eq!(drain(&mut rx), ["one"]);
+        host.tick(2200);
+        assert_eq!(drain(&mut rx), ["-", "one", "two"]);
+        host.tick(2600);
+        assert_eq!(drain(&mut rx), ["-", "two"]);
+        assert_eq!(host.tick(3000), None);
+        assert_eq!(drain(&mut rx), ["-"]);
+        // Seeking back finds the cue again.
+        host.tick(1200);
+        assert_eq!(drain(&mut rx), ["one"]);
+    }
+
+    #[test]
+    fn reset_clears_the_screen_and_starts_a_session() {
+        let (tx, mut rx) = broadcast::channel(64);
+        let host = HostSubtitles::new(Arc::new(tx));
+        let mut styled = cue(0, 1000, "hi");
+        styled.spans = vec![CueSpan {
+            text: "hi".to_string(),
+            style: SpanStyle {
+                bold: true,
+                ..SpanStyle::default()
+            },
+            ruby: None,
+        }];
+        host.queue(vec![styled]);
+        host.tick(500);
+        match rx.try_recv().unwrap() {
+            PlayerEvent::SubtitleCue { end, styling, .. } => {
+                assert_eq!(end, Duration::from_secs(1));
+                assert!(styling[0].bold);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let session = host.session();
+        assert_eq!(host.reset(), session + 1);
+        assert_eq!(drain(&mut rx), ["-"]);
+        host.tick(500);
+        assert!(drain(&mut rx).is_empty());
+        // Nothing on screen: no second clear.
+        host.reset();
+        assert!(drain(&mut rx).is_empty());
+    }
+
+    #[test]
+    fn offset_delays_cues_and_boundaries() {
+        let (tx, mut rx) = broadcast::channel(64);
+        let host = HostSubtitles::new(Arc::new(tx));
+        host.queue(vec![cue(1000, 2000, "one")]);
+        host.set_offset_ms(500);
+
+        assert_eq!(host.tick(1200), Some(1500));
+        assert!(drain(&mut rx).is_empty());
+        assert_eq!(host.tick(1500), Some(2500));
+        assert_eq!(drain(&mut rx), ["one"]);
+        host.set_offset_ms(-1000);
+        assert_eq!(host.tick(1500), None);
+        assert_eq!(drain(&mut rx), ["-"]);
+    }
+
+    #[test]
+    fn requeued_cue_replaces_its_open_ended_self() {
+        let (tx, mut rx) = broadcast::channel(64);
+        let host = HostSubtitles::new(Arc::new(tx));
+        host.queue(vec![cue(1000, 17_000, "cc")]);
+        host.tick(1500);
+        assert_eq!(drain(&mut rx), ["cc"]);
+        host.queue(vec![cue(1000, 2000, "cc")]);
+        assert_eq!(host.tick(1600), Some(2000));
+        assert_eq!(drain(&mut rx), ["-", "cc"]);
+        assert_eq!(host.tick(2000), None);
+        assert_eq!(drain(&mut rx), ["-"]);
+    }
+}
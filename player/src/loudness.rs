@@ -0,0 +1,536 @@
+//! Loudness normalisation and night-mode dynamic range compression — the
+//! optional DSP stage `audio_sync_loop` runs on PCM just before
+//! `AudioSink::put_samples`.
+//!
+//! Measurement follows ITU-R BS.1770-4 / EBU R128: each channel is
+//! K-weighted, mean-square energy is taken over 400 ms blocks every 100 ms,
+//! and blocks are gated at -70 LUFS (absolute) and 10 LU below the
+//! ungated mean (relative). The meter always runs so `Stats` can report the
+//! programme's integrated loudness; the gain stages only engage when the
+//! host enables them via `Player::set_loudness`.
+//!
+//! Normalisation steers towards the target from a sliding ~10 s gated
+//! window rather than the session integral, so an ad break at a different
+//! level is corrected within seconds, and the gain is slew-limited so the
+//! correction is heard as a slow level ride rather than a jump. Night mode
+//! adds a stereo-linked compressor with makeup gain, and a brickwall
+//! limiter keeps any boost from clipping.
+
+use std::collections::VecDeque;
+
+use crate::audio_layout::{device_layout, Speaker};
+
+/// What the loudness stage does to the PCM path. The player consumes these
+/// without persisting; the host owns the user's setting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LoudnessParams {
+    /// Apply gain so the programme plays at `target_lufs`.
+    pub normalize: bool,
+    /// Normalisation target in LUFS. EBU R128 broadcast uses -23; mobile
+    /// and streaming services commonly use -16.
+    pub target_lufs: f32,
+    /// Upper bound on the normalisation boost, dB, so a quiet programme
+    /// isn't pumped up to the point of raising its noise floor.
+    pub max_gain_db: f32,
+    /// Compress loud passages and lift quiet ones so dialogue and effects
+    /// sit closer together, for late-night listening.
+    pub night_mode: bool,
+}
+
+impl LoudnessParams {
+    /// Everything off: the stage only measures.
+    pub const DEFAULT: Self = Self {
+        normalize: false,
+        target_lufs: -23.0,
+        max_gain_db: 12.0,
+        night_mode: false,
+    };
+
+    /// Clamp the target into -40..=-5 LUFS and the boost into 0..=24 dB.
+    /// Called by `Player::set_loudness`; NaN falls back to the default.
+    pub fn sanitised(self) -> Self {
+        let clamp = |v: f32, lo: f32, hi: f32, default: f32| {
+            if v.is_finite() {
+                v.clamp(lo, hi)
+            } else {
+                default
+            }
+        };
+        Self {
+            target_lufs: clamp(self.target_lufs, -40.0, -5.0, Self::DEFAULT.target_lufs),
+            max_gain_db: clamp(self.max_gain_db, 0.0, 24.0, Self::DEFAULT.max_gain_db),
+            ..self
+        }
+    }
+}
+
+impl Default for LoudnessParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Absolute gate (BS.1770-4 §5.1).
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Relative gate, below the ungated mean of the blocks above the absolute
+/// gate.
+const RELATIVE_GATE_LU: f64 = 10.0;
+/// Integrated-loudness histogram: 0.1 LU bins from the absolute gate up to
+/// +10 LUFS, so the session integral needs no per-block history.
+const HIST_BINS: usize = 800;
+/// Gated blocks (100 ms apart) in the normalisation window.
+const WINDOW_BLOCKS: usize = 100;
+/// Blocks the window needs before normalisation starts steering (~3 s).
+const MIN_WINDOW_BLOCKS: usize = 30;
+/// Normalisation never cuts by more than this, dB.
+const MAX_CUT_DB: f32 = 24.0;
+/// Fastest normalisation gain change, dB per second.
+const GAIN_SLEW_DB_PER_S: f32 = 3.0;
+
+/// Night-mode compressor: threshold (dBFS, on the post-normalisation
+/// peak), ratio, time constants and makeup gain.
+const COMP_THRESHOLD_DB: f32 = -24.0;
+const COMP_RATIO: f32 = 4.0;
+const COMP_ATTACK_MS: f32 = 5.0;
+const COMP_RELEASE_MS: f32 = 200.0;
+const COMP_MAKEUP_DB: f32 = 6.0;
+/// Toggling night mode crossfades over this long instead of stepping by
+/// the makeup gain.
+const NIGHT_FADE_MS: f32 = 250.0;
+/// Limiter ceiling, -1 dBFS.
+const LIMIT_CEILING: f32 = 0.891_250_9;
+const LIMIT_RELEASE_MS: f32 = 50.0;
+
+/// K-weighting: the BS.1770 high-shelf ("pre-filter") then high-pass
+/// ("RLB") biquads, with coefficients derived for any sample rate the way
+/// libebur128 does (the spec only tabulates 48 kHz).
+fn k_weighting(sample_rate: u32) -> [[f64; 5]; 2] {
+    let fs = f64::from(sample_rate.max(1));
+    let pi = std::f64::consts::PI;
+
+    let f0 = 1681.974_450_955_533;
+    let g = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (pi * f0 / fs).tan();
+    let vh = 10f64.powf(g / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = [
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    ];
+
+    let f0 = 38.135_470_876_024_44;
+    let q = 0.500_327_037_323_877_3;
+    let k = (pi * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = [
+        1.0,
+        -2.0,
+        1.0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    ];
+    [shelf, high_pass]
+}
+
+/// BS.1770 channel weight: surrounds count +1.5 dB, LFE not at all.
+fn channel_weight(speaker: Option<&Speaker>) -> f64 {
+    match speaker {
+        Some(Speaker::Lfe) => 0.0,
+        Some(
+            Speaker::BackLeft
+            | Speaker::BackRight
+            | Speaker::BackCenter
+            | Speaker::SideLeft
+            | Speaker::SideRight,
+        ) => 1.41,
+        _ => 1.0,
+    }
+}
+
+fn energy_to_lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Gated BS.1770 meter over interleaved frames in device channel order.
+struct LoudnessMeter {
+    channels: usize,
+    coeffs: [[f64; 5]; 2],
+    weights: Vec<f64>,
+    /// Per channel: transposed direct-form II state of both biquads.
+    state: Vec<[f64; 4]>,
+    /// Frames per 100 ms sub-block.
+    step: usize,
+    acc: f64,
+    acc_frames: usize,
+    /// Mean weighted energy of the last four sub-blocks (one 400 ms block).
+    subs: VecDeque<f64>,
+    hist_count: Vec<u32>,
+    hist_energy: Vec<f64>,
+    /// Blocks above the absolute gate, for the integral's relative gate.
+    gated_count: u64,
+    gated_energy: f64,
+    /// Recent blocks above the absolute gate, for normalisation.
+    window: VecDeque<f64>,
+}
+
+impl LoudnessMeter {
+    fn new(sample_rate: u32, channels: usize) -> Self {
+        let layout = device_layout(channels);
+        Self {
+            channels,
+            coeffs: k_weighting(sample_rate),
+            weights: (0..channels)
+                .map(|c| channel_weight(layout.get(c)))
+                .collect(),
+            state: vec![[0.0; 4]; channels],
+            step: (sample_rate / 10).max(1) as usize,
+            acc: 0.0,
+            acc_frames: 0,
+            subs: VecDeque::with_capacity(4),
+            hist_count: vec![0; HIST_BINS],
+            hist_energy: vec![0.0; HIST_BINS],
+            gated_count: 0,
+            gated_energy: 0.0,
+            window: VecDeque::with_capacity(WINDOW_BLOCKS),
+        }
+    }
+
+    fn measure(&mut self, samples: &[f32]) {
+        let [s, h] = self.coeffs;
+        for frame in samples.chunks_exact(self.channels) {
+            for (c, &x) in frame.iter().enumerate() {
+                if self.weights[c] == 0.0 {
+                    continue;
+                }
+                let z = &mut self.state[c];
+                let x = f64::from(x);
+                let y = s[0] * x + z[0];
+                z[0] = s[1] * x - s[3] * y + z[1];
+                z[1] = s[2] * x - s[4] * y;
+                let k = h[0] * y + z[2];
+                z[2] = h[1] * y - h[3] * k + z[3];
+                z[3] = h[2] * y - h[4] * k;
+                self.acc += self.weights[c] * k * k;
+            }
+            self.acc_frames += 1;
+            if self.acc_frames == self.step {
+                if self.subs.len() == 4 {
+                    self.subs.pop_front();
+                }
+                self.subs.push_back(self.acc / self.step as f64);
+                self.acc = 0.0;
+                self.acc_frames = 0;
+                if self.subs.len() == 4 {
+                    self.add_block(self.subs.iter().sum::<f64>() / 4.0);
+                }
+            }
+        }
+    }
+
+    fn add_block(&mut self, energy: f64) {
+        if energy <= 0.0 {
+            return;
+        }
+        let lufs = energy_to_lufs(energy);
+        if lufs < ABSOLUTE_GATE_LUFS {
+            return;
+        }
+        let bin = (((lufs - ABSOLUTE_GATE_LUFS) * 10.0) as usize).min(HIST_BINS - 1);
+        self.hist_count[bin] += 1;
+        self.hist_energy[bin] += energy;
+        self.gated_count += 1;
+        self.gated_energy += energy;
+        if self.window.len() == WINDOW_BLOCKS {
+            self.window.pop_front();
+        }
+        self.window.push_back(energy);
+    }
+
+    /// Integrated loudness of everything measured so far. `None` until a
+    /// block clears the absolute gate.
+    fn integrated_lufs(&self) -> Option<f32> {
+        if self.gated_count == 0 {
+            return None;
+        }
+        let relative =
+            energy_to_lufs(self.gated_energy / self.gated_count as f64) - RELATIVE_GATE_LU;
+        let first = ((relative - ABSOLUTE_GATE_LUFS) * 10.0).ceil().max(0.0) as usize;
+        let (mut count, mut energy) = (0u64, 0.0);
+        for bin in first.min(HIST_BINS)..HIST_BINS {
+            count += u64::from(self.hist_count[bin]);
+            energy += self.hist_energy[bin];
+        }
+        (count > 0).then(|| energy_to_lufs(energy / count as f64) as f32)
+    }
+
+    /// Gated loudness of the recent window, once it holds enough blocks.
+    fn window_lufs(&self) -> Option<f32> {
+        if self.window.len() < MIN_WINDOW_BLOCKS {
+            return None;
+        }
+        let mean = self.window.iter().sum::<f64>() / self.window.len() as f64;
+        let relative = energy_to_lufs(mean) - RELATIVE_GATE_LU;
+        let (count, energy) = self
+            .window
+            .iter()
+            .filter(|&&e| energy_to_lufs(e) >= relative)
+            .fold((0usize, 0.0), |(n, sum), &e| (n + 1, sum + e));
+        (count > 0).then(|| energy_to_lufs(energy / count as f64) as f32)
+    }
+}
+
+/// Meter plus gain stages for one output format. Owned by the `Player`
+/// rather than a pipeline, so the measurement and the applied gain carry
+/// across seeks and track switches instead of re-converging each time.
+pub(crate) struct LoudnessProcessor {
+    sample_rate: u32,
+    channels: usize,
+    meter: LoudnessMeter,
+    /// Normalisation gain currently applied, dB.
+    gain_db: f32,
+    /// Night-mode blend, 0..=1.
+    night: f32,
+    /// Compressor gain reduction envelope, dB (≤ 0).
+    reduction_db: f32,
+    /// Limiter gain (≤ 1).
+    limiter: f32,
+}
+
+impl LoudnessProcessor {
+    pub(crate) fn new(sample_rate: u32, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        Self {
+            sample_rate: sample_rate.max(1),
+            channels,
+            meter: LoudnessMeter::new(sample_rate, channels),
+            gain_db: 0.0,
+            night: 0.0,
+            reduction_db: 0.0,
+            limiter: 1.0,
+        }
+    }
+
+    /// True when built for this output format; the player starts a new
+    /// measurement when the device format changes.
+    pub(crate) fn matches(&self, sample_rate: u32, channels: u16) -> bool {
+        self.sample_rate == sample_rate && self.channels == channels.max(1) as usize
+    }
+
+    pub(crate) fn integrated_lufs(&self) -> Option<f32> {
+        self.meter.integrated_lufs()
+    }
+
+    /// Measure `samples` and, when a gain stage is on (or still ramping
+    /// back to unity), return the processed copy. `None` means the input
+    /// plays unchanged.
+    pub(crate) fn process(&mut self, samples: &[f32], params: &LoudnessParams) -> Option<Vec<f32>> {
+        self.meter.measure(samples);
+        let idle = !params.normalize && !params.night_mode;
+        if idle && self.gain_db == 0.0 && self.night == 0.0 {
+            return None;
+        }
+
+        let sr = self.sample_rate as f32;
+        let target_db = if params.normalize {
+            self.meter
+                .window_lufs()
+                .map(|lufs| (params.target_lufs - lufs).clamp(-MAX_CUT_DB, params.max_gain_db))
+                .unwrap_or(self.gain_db)
+        } else {
+            0.0
+        };
+        let slew = GAIN_SLEW_DB_PER_S / sr;
+        let night_target = if params.night_mode { 1.0 } else { 0.0 };
+        let night_step = 1000.0 / (NIGHT_FADE_MS * sr);
+        let attack = 1.0 - (-1000.0 / (COMP_ATTACK_MS * sr)).exp();
+        let release = 1.0 - (-1000.0 / (COMP_RELEASE_MS * sr)).exp();
+        let limit_release = 1.0 - (-1000.0 / (LIMIT_RELEASE_MS * sr)).exp();
+
+        let mut out = samples.to_vec();
+        for frame in out.chunks_exact_mut(self.channels) {
+            self.gain_db += (target_db - self.gain_db).clamp(-slew, slew);
+            self.night += (night_target - self.night).clamp(-night_step, night_step);
+
+            let peak = frame.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+            let mut gain_db = self.gain_db;
+            if self.night > 0.0 {
+                let level_db = 20.0 * (peak * db_to_gain(self.gain_db)).max(1e-6).log10();
+                let over = level_db - COMP_THRESHOLD_DB;
+                let wanted = if over > 0.0 {
+                    -over * (1.0 - 1.0 / COMP_RATIO)
+                } else {
+                    0.0
+                };
+                let coef = if wanted < self.reduction_db {
+                    attack
+                } else {
+                    release
+                };
+                self.reduction_db += (wanted - self.reduction_db) * coef;
+                gain_db += self.night * (self.reduction_db + COMP_MAKEUP_DB);
+            } else {
+                self.reduction_db = 0.0;
+            }
+            let gain = db_to_gain(gain_db);
+
+            let loud = peak * gain;
+            self.limiter = (self.limiter + (1.0 - self.limiter) * limit_release).min(1.0);
+            if loud * self.limiter > LIMIT_CEILING {
+                self.limiter = LIMIT_CEILING / loud;
+            }
+            let gain = gain * self.limiter;
+            for s in frame.iter_mut() {
+                *s *= gain;
+            }
+        }
+        if idle && self.gain_db.abs() < 0.01 && self.night == 0.0 {
+            // Back at unity: snap so the next buffer takes the bypass.
+            self.gain_db = 0.0;
+            self.limiter = 1.0;
+        }
+        Some(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// Stereo 1 kHz sine at `dbfs` peak amplitude, `secs` long.
+    fn sine(dbfs: f32, secs: f32) -> Vec<f32> {
+        let amp = db_to_gain(dbfs);
+        (0..(RATE as f32 * secs) as usize)
+            .flat_map(|i| {
+                let s = (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / RATE as f32).sin() * amp;
+                [s, s]
+            })
+            .collect()
+    }
+
+    fn measure(parts: &[Vec<f32>]) -> Option<f32> {
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        for p in parts {
+            meter.measure(p);
+        }
+        meter.integrated_lufs()
+    }
+
+    #[test]
+    fn stereo_sine_reads_its_level() {
+        // EBU Tech 3341 case 1: 1 kHz stereo sine at -23 dBFS reads -23 LUFS.
+        let lufs = measure(&[sine(-23.0, 20.0)]).unwrap();
+        assert!((lufs + 23.0).abs() < 0.1, "{lufs}");
+    }
+
+    #[test]
+    fn gates_silence_and_quiet_passages() {
+        let silence = vec![0.0; RATE as usize * 2 * 10];
+        let lufs = measure(&[sine(-20.0, 10.0), silence]).unwrap();
+        assert!((lufs + 20.0).abs() < 0.1, "{lufs}");
+        // EBU Tech 3341 case 3: the -36 dB passages fall under the
+        // relative gate.
+        let lufs = measure(&[sine(-36.0, 10.0), sine(-23.0, 60.0), sine(-36.0, 10.0)]).unwrap();
+        assert!((lufs + 23.0).abs() < 0.1, "{lufs}");
+        assert_eq!(measure(&[vec![0.0; 96_000]]), None);
+    }
+
+    #[test]
+    fn lfe_is_not_measured() {
+        let layout = device_layout(6);
+        let lfe = layout.iter().position(|&s| s == Speaker::Lfe).unwrap();
+        let front = layout
+            .iter()
+            .position(|&s| s == Speaker::FrontLeft)
+            .unwrap();
+        let tone = sine(-20.0, 5.0);
+        let only = |ch: usize| -> Vec<f32> {
+            tone.iter()
+                .step_by(2)
+                .flat_map(|&s| (0..6).map(move |c| if c == ch { s } else { 0.0 }))
+                .collect()
+        };
+        let mut meter = LoudnessMeter::new(RATE, 6);
+        meter.measure(&only(lfe));
+        assert_eq!(meter.integrated_lufs(), None);
+        meter.measure(&only(front));
+        assert!(meter.integrated_lufs().is_some());
+    }
+
+    #[test]
+    fn off_is_bit_exact() {
+        let mut p = LoudnessProcessor::new(RATE, 2);
+        assert_eq!(p.process(&sine(-10.0, 1.0), &LoudnessParams::DEFAULT), None);
+    }
+
+    #[test]
+    fn normalizes_towards_target() {
+        let params = LoudnessParams {
+            normalize: true,
+            ..LoudnessParams::DEFAULT
+        };
+        let mut p = LoudnessProcessor::new(RATE, 2);
+        let input = sine(-33.0, 1.0);
+        let mut out = Vec::new();
+        for _ in 0..15 {
+            out = p.process(&input, &params).unwrap();
+        }
+        assert!((p.gain_db - 10.0).abs() < 0.2, "{}", p.gain_db);
+        let peak = out.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        assert!((20.0 * peak.log10() + 23.0).abs() < 0.3, "{peak}");
+        assert!((p.integrated_lufs().unwrap() + 33.0).abs() < 0.1);
+
+        // Switching off ramps back to unity, then bypasses.
+        let off = LoudnessParams::DEFAULT;
+        for _ in 0..5 {
+            p.process(&input, &off);
+        }
+        assert_eq!(p.process(&input, &off), None);
+    }
+
+    #[test]
+    fn night_mode_narrows_dynamics_under_the_ceiling() {
+        let params = LoudnessParams {
+            night_mode: true,
+            ..LoudnessParams::DEFAULT
+        };
+        let mut p = LoudnessProcessor::new(RATE, 2);
+        let peak = |v: &[f32]| v[v.len() / 2..].iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+        p.process(&sine(-40.0, 1.0), &params);
+        let quiet = peak(&p.process(&sine(-40.0, 1.0), &params).unwrap());
+        let loud = p.process(&sine(0.0, 1.0), &params).unwrap();
+        assert!(loud.iter().all(|s| s.abs() <= LIMIT_CEILING + 1e-6));
+        let spread_db = 20.0 * (peak(&loud) / quiet).log10();
+        assert!(spread_db < 40.0 - 10.0, "{spread_db}");
+    }
+
+    #[test]
+    fn sanitised_clamps_and_rejects_nan() {
+        let p = LoudnessParams {
+            target_lufs: 3.0,
+            max_gain_db: f32::NAN,
+            ..LoudnessParams::DEFAULT
+        }
+        .sanitised();
+        assert_eq!(p.target_lufs, -5.0);
+        assert_eq!(p.max_gain_db, LoudnessParams::DEFAULT.max_gain_db);
+        let p = LoudnessParams {
+            target_lufs: f32::NAN,
+            max_gain_db: 40.0,
+            ..LoudnessParams::DEFAULT
+        }
+        .sanitised();
+        assert_eq!(p.target_lufs, -23.0);
+        assert_eq!(p.max_gain_db, 24.0);
+    }
+}
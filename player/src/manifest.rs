@@ -189,6 +189,24 @@ pub fn slice_representation(block: &str, rep_id: u32) -> Option<&str> {
 /// `block` whose `schemeIdUri` contains `scheme_substring`. Returns the
 /// `value` attribute of each match.
 pub fn find_descriptor_values(block: &str, scheme_substring: &str) -> Vec<String> {
+    // We only care about *Property elements (Supplemental / Essential).
+    find_tag_values(
+        block,
+        &["SupplementalProperty", "EssentialProperty"],
+        scheme_substring,
+    )
+}
+
+/// Same as `find_descriptor_values`, for `<Accessibility>` descriptors —
+/// e.g. the SCTE 214-1 closed-caption declarations
+/// `schemeIdUri="urn:scte:dash:cc:cea-608:2015" value="CC1=eng;CC3=spa"`.
+pub fn find_accessibility_values(block: &str, scheme_substring: &str) -> Vec<String> {
+    find_tag_values(block, &["Accessibility"], scheme_substring)
+}
+
+/// `value` attributes of the elements named one of `tags` whose text
+/// contains `scheme_substring`.
+fn find_tag_values(block: &str, tags: &[&str], scheme_substring: &str) -> Vec<String> {
     let mut out = Vec::new();
     let mut cursor = block;
     while let Some(open) = cursor.find('<') {
@@ -198,10 +216,8 @@ pub fn find_descriptor_values(block: &str, scheme_substring: &str) -> Vec<String
             None => break,
         };
         let tag = &after[..tag_end];
-        // We only care about *Property elements (Supplemental / Essential).
-        let is_prop = tag.starts_with("SupplementalProperty")
-            || tag.starts_with("EssentialProperty");
-        if is_prop && tag.contains(scheme_substring) {
+        let wanted = tags.iter().any(|t| tag.starts_with(t));
+        if wanted && tag.contains(scheme_substring) {
             // Pull out the value="..." attribute if present.
             if let Some(val_start) = tag.find("value=\"") {
                 let rest = &tag[val_start + 7..];
@@ -239,6 +255,19 @@ pub fn find_switchable_ids(block: &str) -> Vec<u32> {
     out
 }
 
+/// For a DASH trick-mode AdaptationSet — `<EssentialProperty
+/// schemeIdUri="http://dashif.org/guidelines/trickmode" value="N"/>` in
+/// `block` — the id of the main AdaptationSet it serves. `None` for a
+/// regular set (or a malformed value).
+pub fn find_trickmode_target(block: &str) -> Option<u32> {
+    // Only the AdaptationSet's own descriptors: stop at its first
+    // Representation so a stray nested property can't misclassify it.
+    let head = block.find("<Representation").map_or(block, |i| &block[..i]);
+    find_descriptor_values(head, "dashif.org/guidelines/trickmode")
+        .first()
+        .and_then(|v| v.trim().parse().ok())
+}
+
 /// Return the integer value of the FIRST `<AudioChannelConfiguration value="N"/>`
 /// in `block`, e.g. for parsing audio channel counts from a Representation.
 pub fn find_audio_channel_count(block: &str) -> Option<u32> {
@@ -466,6 +495,57 @@ mod tests {
         assert_eq!(find_audio_channel_count(frag), Some(2));
     }
 
+    // -------------------------------------------------------------------
+    // find_trickmode_target
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn find_trickmode_target_reads_main_set_id() {
+        let frag = r#"<AdaptationSet id="9" contentType="video">
+            <EssentialProperty schemeIdUri="http://dashif.org/guidelines/trickmode" value="223705"/>
+            <Representation id="1" bandwidth="300000"/>
+        </AdaptationSet>"#;
+        assert_eq!(find_trickmode_target(frag), Some(223705));
+        // Regular sets carry no trickmode descriptor.
+        let adapt = slice_adaptation_set(REAL_MPD, 223705).unwrap();
+        assert_eq!(find_trickmode_target(adapt), None);
+    }
+
+    #[test]
+    fn find_trickmode_target_ignores_representation_level_descriptor() {
+        let frag = r#"<AdaptationSet id="9" contentType="video">
+            <Representation id="1" bandwidth="300000">
+            <EssentialProperty schemeIdUri="http://dashif.org/guidelines/trickmode" value="7"/>
+            </Representation>
+        </AdaptationSet>"#;
+        assert_eq!(find_trickmode_target(frag), None);
+    }
+
+    // -------------------------------------------------------------------
+    // find_accessibility_values
+    // -------------------------------------------------------------------
+
+    #[test]
+    fn find_accessibility_values_reads_caption_declarations() {
+        let frag = r#"<AdaptationSet id="9" contentType="video">
+            <Accessibility schemeIdUri="urn:scte:dash:cc:cea-608:2015" value="CC1=eng;CC3=spa"/>
+            <Accessibility schemeIdUri="urn:scte:dash:cc:cea-708:2015" value="1=lang:eng"/>
+            <SupplementalProperty schemeIdUri="urn:scte:dash:cc:cea-608:2015" value="CC2=deu"/>
+        </AdaptationSet>"#;
+        assert_eq!(
+            find_accessibility_values(frag, "cc:cea-608"),
+            vec!["CC1=eng;CC3=spa"]
+        );
+        assert_eq!(
+            find_accessibility_values(frag, "cc:cea-708"),
+            vec!["1=lang:eng"]
+        );
+        // Property descriptors are not Accessibility descriptors.
+        assert!(find_descriptor_values(frag, "cc:cea-708").is_empty());
+        let adapt = slice_adaptation_set(REAL_MPD, 223705).unwrap();
+        assert!(find_accessibility_values(adapt, "cc:cea-608").is_empty());
+    }
+
     // -------------------------------------------------------------------
     // Manifest::parse — round-trip a small MPD through serde
     // -------------------------------------------------------------------
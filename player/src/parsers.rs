@@ -1,3 +1,59 @@
+pub mod av1;
+pub mod avc;
+pub mod cea608;
+pub mod cea708;
+pub mod flac;
 pub mod hevc;
 pub mod mp4;
+pub mod opus;
+pub mod srt;
+pub mod ttml;
+pub mod vp9;
 pub mod vtt;
+
+/// Expand the character references of XML-ish markup text: the predefined
+/// `&amp;` `&lt;` `&gt;` `&quot;` `&apos;`, numeric `&#…;` / `&#x…;` and
+/// the format's own named ones in `extra` (WebVTT's `&nbsp;` …). Anything
+/// unrecognised is kept literally.
+pub(crate) fn decode_entities(s: &str, extra: &[(&str, char)]) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let c = match &rest[1..semi] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                name if !name.starts_with('#') => extra.iter().find(|(n, _)| *n == name)?.1,
+                num => {
+                    let num = &num[1..];
+                    let code = match num.strip_prefix(['x', 'X']) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => num.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, semi + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,370 @@
+//! AV1 bitstream parsing: the `av1C` codec configuration record and the
+//! sequence header OBU's `color_config` (bit depth, colour description,
+//! range).
+//!
+//! Mirrors [`super::hevc`]: the sequence header is authoritative over the
+//! MPD, and the result is expressed as the same [`SpsColorInfo`] so
+//! `VideoColorInfo::from_sps` picks PQ/HLG exactly as it does for HEVC.
+//!
+//! Everything here is defensive: any malformed input yields `None` and the
+//! caller falls back to the `av1C` header bits / manifest-level sniffing.
+
+use super::hevc::{BitReader, SpsColorInfo};
+
+pub const OBU_SEQUENCE_HEADER: u8 = 1;
+
+/// Parsed `av1C` box (AV1-ISOBMFF §2.3 AV1CodecConfigurationRecord).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Av1Config {
+    pub seq_profile: u8,
+    pub seq_level_idx_0: u8,
+    pub seq_tier_0: bool,
+    /// 8, 10 or 12 — derived from high_bitdepth / twelve_bit.
+    pub bit_depth: u8,
+    pub monochrome: bool,
+    /// configOBUs: zero or more OBUs (in practice the sequence header,
+    /// sometimes followed by metadata OBUs), low-overhead format.
+    pub config_obus: Vec<u8>,
+}
+
+/// Parse the body of an `av1C` box (everything after the 8-byte box header).
+pub fn parse_av1c_record(av1c: &[u8]) -> Option<Av1Config> {
+    // marker(1)=1 version(7)=1 | seq_profile(3) seq_level_idx_0(5) |
+    // seq_tier_0(1) high_bitdepth(1) twelve_bit(1) monochrome(1)
+    // chroma_subsampling_x(1) chroma_subsampling_y(1)
+    // chroma_sample_position(2) | reserved(3)
+    // initial_presentation_delay(4 + flag) | configOBUs
+    if av1c.len() < 4 || av1c[0] != 0x81 {
+        return None;
+    }
+    let high_bitdepth = av1c[2] & 0x40 != 0;
+    let twelve_bit = av1c[2] & 0x20 != 0;
+    Some(Av1Config {
+        seq_profile: av1c[1] >> 5,
+        seq_level_idx_0: av1c[1] & 0x1F,
+        seq_tier_0: av1c[2] & 0x80 != 0,
+        bit_depth: match (high_bitdepth, twelve_bit) {
+            (false, _) => 8,
+            (true, false) => 10,
+            (true, true) => 12,
+        },
+        monochrome: av1c[2] & 0x10 != 0,
+        config_obus: av1c[4..].to_vec(),
+    })
+}
+
+/// Find the sequence header among low-overhead-format OBUs and return its
+/// payload (header and size field stripped).
+fn find_sequence_header(mut d: &[u8]) -> Option<&[u8]> {
+    while !d.is_empty() {
+        let header = d[0];
+        if header & 0x80 != 0 {
+            return None; // obu_forbidden_bit
+        }
+        let obu_type = (header >> 3) & 0x0F;
+        let has_extension = header & 0x04 != 0;
+        let has_size = header & 0x02 != 0;
+        let mut pos = 1 + has_extension as usize;
+        let size = if has_size {
+            let (size, n) = read_leb128(d.get(pos..)?)?;
+            pos += n;
+            size as usize
+        } else {
+            // No size field: the OBU runs to the end of the buffer.
+            d.len().checked_sub(pos)?
+        };
+        let payload = d.get(pos..pos.checked_add(size)?)?;
+        if obu_type == OBU_SEQUENCE_HEADER {
+            return Some(payload);
+        }
+        d = &d[pos + size..];
+    }
+    None
+}
+
+/// leb128() — returns (value, bytes consumed). At most 8 bytes per spec.
+fn read_leb128(d: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &b) in d.iter().take(8).enumerate() {
+        value |= ((b & 0x7F) as u64) << (i * 7);
+        if b & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Colour info from the sequence header carried in `av1C`'s configOBUs.
+pub fn parse_av1c_color_info(config_obus: &[u8]) -> Option<SpsColorInfo> {
+    parse_sequence_header(find_sequence_header(config_obus)?)
+}
+
+/// Parse a sequence_header_obu() payload (AV1 spec §5.5) up to and
+/// including color_config().
+fn parse_sequence_header(payload: &[u8]) -> Option<SpsColorInfo> {
+    let mut r = BitReader::new(payload);
+
+    let seq_profile = r.u(3)?;
+    if seq_profile > 2 {
+        return None;
+    }
+    r.u(1)?; // still_picture
+    let reduced_still_picture_header = r.flag()?;
+    if reduced_still_picture_header {
+        r.u(5)?; // seq_level_idx[0]
+    } else {
+        let mut buffer_delay_length = 0u32;
+        let mut decoder_model_info_present = false;
+        if r.flag()? {
+            // timing_info_present_flag → timing_info()
+            r.u(32)?; // num_units_in_display_tick
+            r.u(32)?; // time_scale
+            if r.flag()? {
+                // equal_picture_interval
+                skip_uvlc(&mut r)?; // num_ticks_per_picture_minus_1
+            }
+            decoder_model_info_present = r.flag()?;
+            if decoder_model_info_present {
+                buffer_delay_length = r.u(5)? + 1;
+                r.u(32)?; // num_units_in_decoding_tick
+                r.u(5)?; // buffer_removal_time_length_minus_1
+                r.u(5)?; // frame_presentation_time_length_minus_1
+            }
+        }
+        let initial_display_delay_present = r.flag()?;
+        let operating_points = r.u(5)? + 1;
+        for _ in 0..operating_points {
+            r.u(12)?; // operating_point_idc
+            let seq_level_idx = r.u(5)?;
+            if seq_level_idx > 7 {
+                r.u(1)?; // seq_tier
+            }
+            if decoder_model_info_present && r.flag()? {
+                // operating_parameters_info()
+                r.u(buffer_delay_length)?; // decoder_buffer_delay
+                r.u(buffer_delay_length)?; // encoder_buffer_delay
+                r.u(1)?; // low_delay_mode_flag
+            }
+            if initial_display_delay_present && r.flag()? {
+                r.u(4)?; // initial_display_delay_minus_1
+            }
+        }
+    }
+
+    let frame_width_bits = r.u(4)? + 1;
+    let frame_height_bits = r.u(4)? + 1;
+    r.u(frame_width_bits)?; // max_frame_width_minus_1
+    r.u(frame_height_bits)?; // max_frame_height_minus_1
+    if !reduced_still_picture_header && r.flag()? {
+        // frame_id_numbers_present_flag
+        r.u(4)?; // delta_frame_id_length_minus_2
+        r.u(3)?; // additional_frame_id_length_minus_1
+    }
+    r.u(1)?; // use_128x128_superblock
+    r.u(1)?; // enable_filter_intra
+    r.u(1)?; // enable_intra_edge_filter
+    if !reduced_still_picture_header {
+        r.u(1)?; // enable_interintra_compound
+        r.u(1)?; // enable_masked_compound
+        r.u(1)?; // enable_warped_motion
+        r.u(1)?; // enable_dual_filter
+        let enable_order_hint = r.flag()?;
+        if enable_order_hint {
+            r.u(1)?; // enable_jnt_comp
+            r.u(1)?; // enable_ref_frame_mvs
+        }
+        let force_screen_content_tools = if r.flag()? {
+            // seq_choose_screen_content_tools → SELECT_SCREEN_CONTENT_TOOLS
+            2
+        } else {
+            r.u(1)?
+        };
+        if force_screen_content_tools > 0 && !r.flag()? {
+            // seq_choose_integer_mv == 0
+            r.u(1)?; // seq_force_integer_mv
+        }
+        if enable_order_hint {
+            r.u(3)?; // order_hint_bits_minus_1
+        }
+    }
+    r.u(1)?; // enable_superres
+    r.u(1)?; // enable_cdef
+    r.u(1)?; // enable_restoration
+
+    // color_config()
+    let high_bitdepth = r.flag()?;
+    let bit_depth_luma = if seq_profile == 2 && high_bitdepth {
+        if r.flag()? {
+            12
+        } else {
+            10
+        }
+    } else if high_bitdepth {
+        10
+    } else {
+        8
+    };
+    let mono_chrome = if seq_profile == 1 { false } else { r.flag()? };
+
+    let mut info = SpsColorInfo {
+        bit_depth_luma,
+        ..Default::default()
+    };
+    if r.flag()? {
+        // color_description_present_flag
+        info.colour_primaries = r.u(8)? as u8;
+        info.transfer_characteristics = r.u(8)? as u8;
+        info.matrix_coeffs = r.u(8)? as u8;
+    }
+    info.full_range = if !mono_chrome
+        && info.colour_primaries == 1
+        && info.transfer_characteristics == 13
+        && info.matrix_coeffs == 0
+    {
+        // sRGB 4:4:4 — color_range is implied full.
+        true
+    } else {
+        r.flag()?
+    };
+    Some(info)
+}
+
+/// uvlc() — skip.
+fn skip_uvlc(r: &mut BitReader) -> Option<()> {
+    let mut leading_zeros = 0u32;
+    while !r.flag()? {
+        leading_zeros += 1;
+        if leading_zeros >= 32 {
+            return None;
+        }
+    }
+    r.u(leading_zeros)?;
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits_to_bytes(bits: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for chunk in bits.as_bytes().chunks(8) {
+            let mut b = 0u8;
+            for (i, &c) in chunk.iter().enumerate() {
+                if c == b'1' {
+                    b |= 1 << (7 - i);
+                }
+            }
+            bytes.push(b);
+        }
+        bytes
+    }
+
+    /// Main-profile sequence header with timing info and one operating
+    /// point, built in parse_sequence_header's read order.
+    fn seq_header(high_bitdepth: bool, colour: Option<(u8, u8, u8)>, full_range: bool) -> Vec<u8> {
+        let mut bits = String::new();
+        bits += "000";                   // seq_profile = 0 (Main)
+        bits += "0";                     // still_picture
+        bits += "0";                     // reduced_still_picture_header
+        bits += "1";                     // timing_info_present_flag
+        bits += &format!("{:032b}", 1001); // num_units_in_display_tick
+        bits += &format!("{:032b}", 24000); // time_scale
+        bits += "1";                     // equal_picture_interval
+        bits += "1";                     // num_ticks_per_picture_minus_1 uvlc = 0
+        bits += "0";                     // decoder_model_info_present_flag
+        bits += "0";                     // initial_display_delay_present_flag
+        bits += "00000";                 // operating_points_cnt_minus_1 = 0
+        bits += "000000000000";          // operating_point_idc[0]
+        bits += "01001";                 // seq_level_idx[0] = 9 (> 7 → tier)
+        bits += "0";                     // seq_tier[0]
+        bits += "1011";                  // frame_width_bits_minus_1 = 11
+        bits += "1011";                  // frame_height_bits_minus_1 = 11
+        bits += &format!("{:012b}", 3839); // max_frame_width_minus_1
+        bits += &format!("{:012b}", 2159); // max_frame_height_minus_1
+        bits += "0";                     // frame_id_numbers_present_flag
+        bits += "0";                     // use_128x128_superblock
+        bits += "1";                     // enable_filter_intra
+        bits += "1";                     // enable_intra_edge_filter
+        bits += "0000";                  // interintra/masked/warped/dual_filter
+        bits += "1";                     // enable_order_hint
+        bits += "11";                    // enable_jnt_comp, enable_ref_frame_mvs
+        bits += "1";                     // seq_choose_screen_content_tools
+        bits += "1";                     // seq_choose_integer_mv
+        bits += "110";                   // order_hint_bits_minus_1 = 6
+        bits += "011";                   // superres, cdef, restoration
+        bits += if high_bitdepth { "1" } else { "0" };
+        bits += "0";                     // mono_chrome
+        match colour {
+            Some((p, t, m)) => {
+                bits += "1";
+                bits += &format!("{:08b}{:08b}{:08b}", p, t, m);
+            }
+            None => bits += "0",
+        }
+        bits += if full_range { "1" } else { "0" };
+        bits += "00";                    // chroma_sample_position
+        bits += "0";                     // separate_uv_delta_q
+        bits += "0";                     // film_grain_params_present
+        bits += "1";                     // trailing bit
+        bits_to_bytes(&bits)
+    }
+
+    fn obu(obu_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![(obu_type << 3) | 0x02, payload.len() as u8];
+        out.extend_from_slice(payload);
+        out
+    }
+
+    #[test]
+    fn sequence_header_pq_bt2020_10bit() {
+        let obus = obu(OBU_SEQUENCE_HEADER, &seq_header(true, Some((9, 16, 9)), false));
+        let info = parse_av1c_color_info(&obus).expect("parse");
+        assert_eq!(info.bit_depth_luma, 10);
+        assert_eq!(info.colour_primaries, 9);
+        assert_eq!(info.transfer_characteristics, 16);
+        assert_eq!(info.matrix_coeffs, 9);
+        assert!(!info.full_range);
+    }
+
+    #[test]
+    fn sequence_header_without_colour_description() {
+        let obus = obu(OBU_SEQUENCE_HEADER, &seq_header(false, None, true));
+        let info = parse_av1c_color_info(&obus).expect("parse");
+        assert_eq!(info.bit_depth_luma, 8);
+        assert_eq!(info.transfer_characteristics, 2);
+        assert!(info.full_range);
+    }
+
+    #[test]
+    fn sequence_header_found_after_other_obus() {
+        // A metadata OBU (type 5) ahead of the sequence header is skipped.
+        let mut obus = obu(5, &[0x01, 0x02, 0x03]);
+        obus.extend(obu(OBU_SEQUENCE_HEADER, &seq_header(true, Some((9, 18, 9)), false)));
+        let info = parse_av1c_color_info(&obus).expect("parse");
+        assert_eq!(info.transfer_characteristics, 18);
+    }
+
+    #[test]
+    fn av1c_record_header_bits() {
+        // marker+version, profile 0 level 13, tier 0 high_bitdepth=1
+        // twelve_bit=0 mono=0 subsampling 1/1 position 0, no delay.
+        let mut rec = vec![0x81, 0x0D, 0x4C, 0x00];
+        let obus = obu(OBU_SEQUENCE_HEADER, &seq_header(true, Some((9, 16, 9)), false));
+        rec.extend_from_slice(&obus);
+        let cfg = parse_av1c_record(&rec).expect("parse");
+        assert_eq!(cfg.seq_profile, 0);
+        assert_eq!(cfg.seq_level_idx_0, 13);
+        assert_eq!(cfg.bit_depth, 10);
+        assert!(!cfg.monochrome);
+        assert_eq!(cfg.config_obus, obus);
+    }
+
+    #[test]
+    fn malformed_input_is_none() {
+        assert!(parse_av1c_record(&[0x80, 0, 0, 0]).is_none());
+        assert!(parse_av1c_color_info(&[]).is_none());
+        // Size field claims more bytes than present.
+        assert!(parse_av1c_color_info(&[(OBU_SEQUENCE_HEADER << 3) | 0x02, 0x10, 0x00]).is_none());
+    }
+}
@@ -0,0 +1,383 @@
+//! H.264/AVC bitstream parsing: the `avcC` decoder configuration record
+//! (SPS/PPS extraction) and SPS colour information (bit depth, VUI colour
+//! description).
+//!
+//! Mirrors [`super::hevc`]: the SPS VUI is authoritative over the MPD, and
+//! the result is expressed as the same [`SpsColorInfo`] so
+//! `VideoColorInfo::from_sps` serves both codecs.
+//!
+//! Everything here is defensive: any malformed input yields `None` and the
+//! caller falls back to the `avcC` bit depth / manifest-level sniffing.
+
+use super::hevc::{sei_cc_data, unescape_rbsp, BitReader, SpsColorInfo};
+
+/// H.264 nal_unit_type from the first byte of a NAL unit (low 5 bits).
+pub fn nal_unit_type(nalu: &[u8]) -> Option<u8> {
+    nalu.first().map(|b| b & 0x1F)
+}
+
+pub const NAL_SEI: u8 = 6;
+pub const NAL_SPS: u8 = 7;
+pub const NAL_PPS: u8 = 8;
+
+/// ATSC A/53 closed-caption triplets from one SEI NAL unit (raw NALU
+/// bytes including the 1-byte header); see `hevc::parse_sei_cc_data`.
+pub fn parse_sei_cc_data(nalu: &[u8]) -> Vec<[u8; 3]> {
+    nalu.get(1..).map(sei_cc_data).unwrap_or_default()
+}
+
+/// Parsed `avcC` box (ISO/IEC 14496-15 AVCDecoderConfigurationRecord).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AvcDecoderConfig {
+    pub profile_idc: u8,
+    pub profile_compatibility: u8,
+    pub level_idc: u8,
+    /// Sample NALU length prefix size in bytes (1, 2 or 4).
+    pub nal_length_size: u8,
+    /// Raw SPS NAL units (with the 1-byte NAL header, no prefix).
+    pub sps: Vec<Vec<u8>>,
+    /// Raw PPS NAL units (with the 1-byte NAL header, no prefix).
+    pub pps: Vec<Vec<u8>>,
+    /// `bit_depth_luma_minus8 + 8` from the High-profile extension of the
+    /// record. None for Baseline/Main/Extended, which are always 8-bit.
+    pub bit_depth_luma: Option<u8>,
+}
+
+impl AvcDecoderConfig {
+    /// SPS followed by PPS — the parameter-set order every decoder wants.
+    pub fn parameter_sets(&self) -> Vec<Vec<u8>> {
+        self.sps.iter().chain(self.pps.iter()).cloned().collect()
+    }
+}
+
+/// Profiles whose SPS carries chroma_format_idc / bit depths / scaling
+/// matrices (H.264 7.3.2.1.1), and whose avcC carries the extension bytes.
+fn is_high_profile(profile_idc: u8) -> bool {
+    matches!(
+        profile_idc,
+        100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+    )
+}
+
+/// Parse the body of an `avcC` box (everything after the 8-byte box header).
+pub fn parse_avcc_record(avcc: &[u8]) -> Option<AvcDecoderConfig> {
+    // configurationVersion(8) profile(8) compat(8) level(8)
+    // reserved(6) lengthSizeMinusOne(2) reserved(3) numOfSPS(5)
+    if avcc.len() < 6 || avcc[0] != 1 {
+        return None;
+    }
+    let mut cfg = AvcDecoderConfig {
+        profile_idc: avcc[1],
+        profile_compatibility: avcc[2],
+        level_idc: avcc[3],
+        nal_length_size: (avcc[4] & 0x03) + 1,
+        ..Default::default()
+    };
+    let num_sps = (avcc[5] & 0x1F) as usize;
+    let mut d = &avcc[6..];
+    cfg.sps = read_nalu_array(&mut d, num_sps)?;
+    let num_pps = *d.first()? as usize;
+    d = &d[1..];
+    cfg.pps = read_nalu_array(&mut d, num_pps)?;
+
+    // High-profile extension: chroma_format(2) / bit_depth_luma_minus8(3) /
+    // bit_depth_chroma_minus8(3), each in a byte with reserved high bits.
+    // Older muxers omit it even for High — treat absence as "unknown".
+    if is_high_profile(cfg.profile_idc) && d.len() >= 3 {
+        cfg.bit_depth_luma = Some(8 + (d[1] & 0x07));
+    }
+    Some(cfg)
+}
+
+fn read_nalu_array(d: &mut &[u8], count: usize) -> Option<Vec<Vec<u8>>> {
+    let mut out = Vec::with_capacity(count);
+    for _ in 0..count {
+        if d.len() < 2 {
+            return None;
+        }
+        let len = u16::from_be_bytes([d[0], d[1]]) as usize;
+        let body = d.get(2..2 + len)?;
+        out.push(body.to_vec());
+        *d = &d[2 + len..];
+    }
+    Some(out)
+}
+
+/// Parse colour info from the first SPS found in `nalus` (raw NALU bytes,
+/// no start code / length prefix — the shape `parse_avcc_record` returns).
+pub fn parse_sps_color_info(nalus: &[Vec<u8>]) -> Option<SpsColorInfo> {
+    nalus
+        .iter()
+        .find(|n| nal_unit_type(n) == Some(NAL_SPS))
+        .and_then(|n| parse_sps(n))
+}
+
+/// Parse one SPS NAL unit (including the 1-byte NAL header).
+fn parse_sps(nalu: &[u8]) -> Option<SpsColorInfo> {
+    let rbsp = unescape_rbsp(nalu.get(1..)?);
+    let mut r = BitReader::new(&rbsp);
+
+    let profile_idc = r.u(8)? as u8;
+    r.u(8)?; // constraint_set flags + reserved_zero_2bits
+    r.u(8)?; // level_idc
+    r.ue()?; // seq_parameter_set_id
+
+    let mut bit_depth_luma = 8u8;
+    if is_high_profile(profile_idc) {
+        let chroma_format_idc = r.ue()?;
+        if chroma_format_idc == 3 {
+            r.u(1)?; // separate_colour_plane_flag
+        }
+        let bit_depth_luma_minus8 = r.ue()?;
+        if bit_depth_luma_minus8 > 6 {
+            // Spec range is 0..6 — anything else means a corrupt SPS.
+            return None;
+        }
+        bit_depth_luma = 8 + bit_depth_luma_minus8 as u8;
+        r.ue()?; // bit_depth_chroma_minus8
+        r.u(1)?; // qpprime_y_zero_transform_bypass_flag
+        if r.flag()? {
+            // seq_scaling_matrix_present_flag
+            let lists = if chroma_format_idc == 3 { 12 } else { 8 };
+            for i in 0..lists {
+                if r.flag()? {
+                    // seq_scaling_list_present_flag[i]
+                    skip_scaling_list(&mut r, if i < 6 { 16 } else { 64 })?;
+                }
+            }
+        }
+    }
+
+    r.ue()?; // log2_max_frame_num_minus4
+    let pic_order_cnt_type = r.ue()?;
+    match pic_order_cnt_type {
+        0 => {
+            r.ue()?; // log2_max_pic_order_cnt_lsb_minus4
+        }
+        1 => {
+            r.u(1)?; // delta_pic_order_always_zero_flag
+            r.se()?; // offset_for_non_ref_pic
+            r.se()?; // offset_for_top_to_bottom_field
+            let cycle = r.ue()?;
+            if cycle > 255 {
+                return None; // spec max
+            }
+            for _ in 0..cycle {
+                r.se()?; // offset_for_ref_frame
+            }
+        }
+        2 => {}
+        _ => return None,
+    }
+
+    r.ue()?; // max_num_ref_frames
+    r.u(1)?; // gaps_in_frame_num_value_allowed_flag
+    r.ue()?; // pic_width_in_mbs_minus1
+    r.ue()?; // pic_height_in_map_units_minus1
+    if !r.flag()? {
+        // frame_mbs_only_flag == 0
+        r.u(1)?; // mb_adaptive_frame_field_flag
+    }
+    r.u(1)?; // direct_8x8_inference_flag
+    if r.flag()? {
+        // frame_cropping: left/right/top/bottom offsets
+        r.ue()?;
+        r.ue()?;
+        r.ue()?;
+        r.ue()?;
+    }
+
+    let mut info = SpsColorInfo {
+        bit_depth_luma,
+        ..Default::default()
+    };
+
+    if r.flag()? {
+        // vui_parameters_present_flag
+        if r.flag()? {
+            // aspect_ratio_info_present_flag
+            let idc = r.u(8)?;
+            if idc == 255 {
+                r.u(16)?; // sar_width
+                r.u(16)?; // sar_height
+            }
+        }
+        if r.flag()? {
+            // overscan_info_present_flag
+            r.u(1)?;
+        }
+        if r.flag()? {
+            // video_signal_type_present_flag
+            r.u(3)?; // video_format
+            info.full_range = r.flag()?;
+            if r.flag()? {
+                // colour_description_present_flag
+                info.colour_primaries = r.u(8)? as u8;
+                info.transfer_characteristics = r.u(8)? as u8;
+                info.matrix_coeffs = r.u(8)? as u8;
+            }
+        }
+        // Remaining VUI fields are irrelevant for colour.
+    }
+
+    Some(info)
+}
+
+/// scaling_list(size) — skip (H.264 7.3.2.1.1.1).
+fn skip_scaling_list(r: &mut BitReader, size: u32) -> Option<()> {
+    let mut last_scale = 8i32;
+    let mut next_scale = 8i32;
+    for _ in 0..size {
+        if next_scale != 0 {
+            // delta_scale is -128..=127; anything else is a corrupt SPS.
+            let delta = r.se()?;
+            if !(-128..=127).contains(&delta) {
+                return None;
+            }
+            next_scale = (last_scale + delta + 256) % 256;
+        }
+        if next_scale != 0 {
+            last_scale = next_scale;
+        }
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits_to_bytes(bits: &str) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for chunk in bits.as_bytes().chunks(8) {
+            let mut b = 0u8;
+            for (i, &c) in chunk.iter().enumerate() {
+                if c == b'1' {
+                    b |= 1 << (7 - i);
+                }
+            }
+            bytes.push(b);
+        }
+        bytes
+    }
+
+    /// Minimal High-profile SPS (profile 100 or 110) with a VUI colour
+    /// description, built in parse_sps's read order.
+    fn high_sps(profile_idc: u8, bit_depth_minus8: &str, colour: Option<(u8, u8, u8)>) -> Vec<u8> {
+        let mut bits = String::new();
+        bits += &format!("{:08b}", profile_idc);
+        bits += "00000000";              // constraint flags
+        bits += "00101000";              // level_idc = 40
+        bits += "1";                     // seq_parameter_set_id ue = 0
+        bits += "010";                   // chroma_format_idc ue = 1
+        bits += bit_depth_minus8;        // bit_depth_luma_minus8
+        bits += bit_depth_minus8;        // bit_depth_chroma_minus8
+        bits += "0";                     // qpprime_y_zero_transform_bypass_flag
+        bits += "0";                     // seq_scaling_matrix_present_flag
+        bits += "1";                     // log2_max_frame_num_minus4 ue = 0
+        bits += "1";                     // pic_order_cnt_type ue = 0
+        bits += "1";                     // log2_max_pic_order_cnt_lsb_minus4 ue = 0
+        bits += "011";                   // max_num_ref_frames ue = 2
+        bits += "0";                     // gaps_in_frame_num_value_allowed_flag
+        bits += "1";                     // pic_width_in_mbs_minus1 ue = 0
+        bits += "1";                     // pic_height_in_map_units_minus1 ue = 0
+        bits += "1";                     // frame_mbs_only_flag
+        bits += "1";                     // direct_8x8_inference_flag
+        bits += "0";                     // frame_cropping_flag
+        match colour {
+            Some((p, t, m)) => {
+                bits += "1";             // vui_parameters_present_flag
+                bits += "0";             // aspect_ratio_info_present_flag
+                bits += "0";             // overscan_info_present_flag
+                bits += "1";             // video_signal_type_present_flag
+                bits += "101";           // video_format = 5
+                bits += "1";             // video_full_range_flag = 1
+                bits += "1";             // colour_description_present_flag
+                bits += &format!("{:08b}{:08b}{:08b}", p, t, m);
+            }
+            None => bits += "0",         // vui_parameters_present_flag
+        }
+        bits += "1";                     // rbsp_stop_one_bit
+        let mut nalu = vec![0x67]; // nal_ref_idc = 3, type 7
+        nalu.extend_from_slice(&bits_to_bytes(&bits));
+        nalu
+    }
+
+    #[test]
+    fn sps_high_bt709() {
+        let sps = high_sps(100, "1", Some((1, 1, 1)));
+        let info = parse_sps_color_info(&[sps]).expect("parse");
+        assert_eq!(info.bit_depth_luma, 8);
+        assert!(info.full_range);
+        assert_eq!(info.colour_primaries, 1);
+        assert_eq!(info.transfer_characteristics, 1);
+        assert_eq!(info.matrix_coeffs, 1);
+    }
+
+    #[test]
+    fn sps_high10_hlg_bt2020() {
+        let sps = high_sps(110, "011", Some((9, 18, 9)));
+        let info = parse_sps_color_info(&[sps]).expect("parse");
+        assert_eq!(info.bit_depth_luma, 10);
+        assert_eq!(info.transfer_characteristics, 18);
+        assert_eq!(info.colour_primaries, 9);
+    }
+
+    #[test]
+    fn sps_without_vui_is_unspecified() {
+        let sps = high_sps(100, "1", None);
+        let info = parse_sps_color_info(&[sps]).expect("parse");
+        assert_eq!(info, SpsColorInfo::default());
+    }
+
+    #[test]
+    fn sps_lookup_skips_pps() {
+        // A PPS ahead of the SPS must not be mistaken for it.
+        let pps = vec![0x68, 0xEB, 0xE3, 0xCB];
+        let sps = high_sps(100, "1", Some((1, 1, 1)));
+        assert!(parse_sps_color_info(&[pps.clone(), sps]).is_some());
+        assert!(parse_sps_color_info(&[pps]).is_none());
+    }
+
+    #[test]
+    fn avcc_record_high_profile() {
+        let sps = high_sps(100, "1", None);
+        let pps = vec![0x68, 0xEB, 0xE3, 0xCB];
+        let mut rec = vec![1, 100, 0x00, 40, 0xFF, 0xE1];
+        rec.extend_from_slice(&(sps.len() as u16).to_be_bytes());
+        rec.extend_from_slice(&sps);
+        rec.push(1);
+        rec.extend_from_slice(&(pps.len() as u16).to_be_bytes());
+        rec.extend_from_slice(&pps);
+        // High extension: chroma_format = 1, luma/chroma depth minus8 = 0,
+        // numOfSequenceParameterSetExt = 0.
+        rec.extend_from_slice(&[0xFD, 0xF8, 0xF8, 0x00]);
+
+        let cfg = parse_avcc_record(&rec).expect("parse");
+        assert_eq!(cfg.profile_idc, 100);
+        assert_eq!(cfg.level_idc, 40);
+        assert_eq!(cfg.nal_length_size, 4);
+        assert_eq!(cfg.sps, vec![sps.clone()]);
+        assert_eq!(cfg.pps, vec![pps.clone()]);
+        assert_eq!(cfg.bit_depth_luma, Some(8));
+        assert_eq!(cfg.parameter_sets(), vec![sps, pps]);
+    }
+
+    #[test]
+    fn scaling_list_rejects_out_of_range_delta() {
+        // se(v) = +1: a legal delta.
+        let legal = bits_to_bytes("010");
+        assert_eq!(skip_scaling_list(&mut BitReader::new(&legal), 1), Some(()));
+        // se(v) = i32::MAX (ue 2^32 - 3) must not overflow the scale sum.
+        let corrupt = bits_to_bytes(&format!("{}1{}0", "0".repeat(31), "1".repeat(30)));
+        assert_eq!(skip_scaling_list(&mut BitReader::new(&corrupt), 16), None);
+    }
+
+    #[test]
+    fn avcc_record_truncated() {
+        // Claims one SPS of 0x20 bytes but carries only two.
+        let rec = [1, 66, 0xC0, 30, 0xFF, 0xE1, 0x00, 0x20, 0x67, 0x42];
+        assert!(parse_avcc_record(&rec).is_none());
+        assert!(parse_avcc_record(&[]).is_none());
+    }
+}
@@ -0,0 +1,604 @@
+//! CEA-608 ("Line 21") closed-caption decoding, for captions carried as
+//! A/53 `cc_data` in the video SEI (`hevc::parse_sei_cc_data`,
+//! `avc::parse_sei_cc_data`).
+//!
+//! One [`Cea608Decoder`] runs per field: field 1 carries CC1/CC2, field 2
+//! CC3/CC4, and the control codes pick the data channel. Pop-on, roll-up
+//! and paint-on captions are rebuilt on the 15×32 caption grid; every
+//! change of the displayed memory closes the cues on screen and opens the
+//! next ones, so the output is a stream of `VttCue`s the overlay draws
+//! like any other subtitle. Byte pairs must arrive in presentation order.
+//! Text mode (T1–T4), XDS and the background attribute codes are ignored.
+
+use super::vtt::{CueLayout, CueSpan, DisplayAlign, SpanStyle, TextAlign, VttCue};
+
+/// End time given to a cue while it is still on screen. The decoder
+/// re-sends the cue with its real end once the captions change; this
+/// bound only matters when the stream never does (the usual decoder
+/// practice of blanking stale captions after ~16 s).
+pub const OPEN_CUE_MS: i64 = 16_000;
+
+const ROWS: usize = 15;
+const COLS: usize = 32;
+
+/// Styling of one grid cell. Shared with `cea708`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(super) struct CellStyle {
+    /// `None` = white, the caption default, which the user's
+    /// `SubtitleStyle` colour replaces.
+    pub color: Option<[u8; 4]>,
+    pub background: Option<[u8; 4]>,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Cell {
+    pub ch: char,
+    pub style: CellStyle,
+}
+
+/// A caption screen: `ROWS` × `COLS` cells, `None` = transparent.
+type Screen = [[Option<Cell>; COLS]; ROWS];
+
+const EMPTY: Screen = [[None; COLS]; ROWS];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    PopOn,
+    /// Roll-up with this many rows.
+    RollUp(usize),
+    PaintOn,
+    /// T1–T4 text service: not captions, dropped.
+    Text,
+}
+
+/// One data channel (CC1/CC2 on field 1, CC3/CC4 on field 2).
+struct Channel {
+    mode: Mode,
+    displayed: Screen,
+    /// Pop-on's off-screen buffer, swapped in by EOC.
+    non_displayed: Screen,
+    row: usize,
+    col: usize,
+    style: CellStyle,
+    /// `displayed` as last published.
+    shown: Screen,
+    /// Cues on screen since the last publish, open-ended.
+    open: Vec<VttCue>,
+}
+
+impl Channel {
+    fn new() -> Self {
+        Channel {
+            mode: Mode::PopOn,
+            displayed: EMPTY,
+            non_displayed: EMPTY,
+            row: ROWS - 1,
+            col: 0,
+            style: CellStyle::default(),
+            shown: EMPTY,
+            open: Vec::new(),
+        }
+    }
+
+    /// The memory text goes to in the current mode.
+    fn target(&mut self) -> &mut Screen {
+        match self.mode {
+            Mode::PopOn => &mut self.non_displayed,
+            _ => &mut self.displayed,
+        }
+    }
+
+    fn put(&mut self, ch: char) {
+        if self.mode == Mode::Text {
+            return;
+        }
+        let (row, col, style) = (self.row, self.col, self.style);
+        self.target()[row][col] = Some(Cell { ch, style });
+        self.col = (col + 1).min(COLS - 1);
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let (row, col) = (self.row, self.col);
+            self.target()[row][col] = None;
+        }
+    }
+
+    /// Roll the roll-up window (the `n` rows ending at the cursor row) up
+    /// one row.
+    fn carriage_return(&mut self) {
+        let Mode::RollUp(n) = self.mode else {
+            return;
+        };
+        let top = (self.row + 1).saturating_sub(n);
+        for r in 0..ROWS {
+            if r < top || r > self.row {
+                self.displayed[r] = [None; COLS];
+            }
+        }
+        for r in top..self.row {
+            self.displayed[r] = self.displayed[r + 1];
+        }
+        self.displayed[self.row] = [None; COLS];
+        self.col = 0;
+    }
+
+    /// Preamble address code: move the cursor to `row` with an indent and
+    /// style. A roll-up window moves along with its base row.
+    fn preamble(&mut self, row: usize, col: usize, style: CellStyle) {
+        if let Mode::RollUp(n) = self.mode {
+            if row != self.row {
+                let rows = n.min(row + 1).min(self.row + 1);
+                let mut moved = EMPTY;
+                for i in 0..rows {
+                    moved[row - i] = self.displayed[self.row - i];
+                }
+                self.displayed = moved;
+            }
+        }
+        self.row = row;
+        self.col = col;
+        self.style = style;
+    }
+
+    /// Close the cues on screen and open the new ones if the displayed
+    /// memory changed since the last call.
+    fn publish(&mut self, pts_ms: i64, out: &mut Vec<VttCue>) {
+        if self.displayed == self.shown {
+            return;
+        }
+        self.shown = self.displayed;
+        for mut cue in self.open.drain(..) {
+            cue.end_ms = pts_ms.max(cue.start_ms);
+            out.push(cue);
+        }
+        let rows: Vec<Vec<Option<Cell>>> = self.displayed.iter().map(|r| r.to_vec()).collect();
+        self.open = grid_cues(&rows, COLS, pts_ms, &screen_layout);
+        out.extend(self.open.iter().cloned());
+    }
+
+    fn flush(&mut self, pts_ms: i64, out: &mut Vec<VttCue>) {
+        for mut cue in self.open.drain(..) {
+            cue.end_ms = pts_ms.max(cue.start_ms);
+            out.push(cue);
+        }
+        *self = Channel::new();
+    }
+}
+
+/// Decoder for one field of CEA-608 byte pairs.
+pub struct Cea608Decoder {
+    channels: [Channel; 2],
+    /// Data channel (0 or 1) the last control code addressed.
+    current: usize,
+    /// Control codes are sent twice in a row; the repeat is dropped.
+    last_control: Option<(u8, u8)>,
+}
+
+impl Default for Cea608Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cea608Decoder {
+    pub fn new() -> Self {
+        Cea608Decoder {
+            channels: [Channel::new(), Channel::new()],
+            current: 0,
+            last_control: None,
+        }
+    }
+
+    /// Feed one byte pair (parity bits included) shown at `pts_ms`.
+    /// Cue updates go to `out` tagged with their data channel (0 = CC1 /
+    /// CC3, 1 = CC2 / CC4): a cue already sent earlier comes again with
+    /// the same `start_ms` and text and its final `end_ms`, so consumers
+    /// replace rather than add it.
+    pub fn feed(&mut self, pts_ms: i64, b1: u8, b2: u8, out: &mut Vec<(usize, VttCue)>) {
+        let (b1, b2) = (b1 & 0x7F, b2 & 0x7F);
+        if (0x10..=0x1F).contains(&b1) {
+            if self.last_control == Some((b1, b2)) {
+                self.last_control = None;
+                return;
+            }
+            self.last_control = Some((b1, b2));
+            self.current = ((b1 >> 3) & 1) as usize;
+            self.control(b1 & 0x17, b2);
+        } else {
+            self.last_control = None;
+            if b1 < 0x20 {
+                // Padding, or XDS on field 2.
+                return;
+            }
+            let ch = &mut self.channels[self.current];
+            ch.put(standard_char(b1));
+            if b2 >= 0x20 {
+                ch.put(standard_char(b2));
+            }
+        }
+        let mut cues = Vec::new();
+        self.channels[self.current].publish(pts_ms, &mut cues);
+        out.extend(cues.into_iter().map(|c| (self.current, c)));
+    }
+
+    /// Close every cue on screen at `pts_ms` and reset — a seek or
+    /// pipeline restart, after which the byte stream starts over.
+    pub fn flush(&mut self, pts_ms: i64, out: &mut Vec<(usize, VttCue)>) {
+        for (i, ch) in self.channels.iter_mut().enumerate() {
+            let mut cues = Vec::new();
+            ch.flush(pts_ms, &mut cues);
+            out.extend(cues.into_iter().map(|c| (i, c)));
+        }
+        self.current = 0;
+        self.last_control = None;
+    }
+
+    /// A control code with the channel bit cleared (`b1` in 0x10..=0x17).
+    fn control(&mut self, b1: u8, b2: u8) {
+        let ch = &mut self.channels[self.current];
+        match (b1, b2) {
+            // Miscellaneous control codes (0x15 on field 2).
+            (0x14 | 0x15, 0x20..=0x2F) => match b2 {
+                0x20 => ch.mode = Mode::PopOn,
+                0x21 => ch.backspace(),
+                0x24 => {
+                    // DER: delete to end of row.
+                    let (row, col) = (ch.row, ch.col);
+                    ch.target()[row][col..].fill(None);
+                }
+                0x25..=0x27 => {
+                    let n = (b2 - 0x23) as usize;
+                    if !matches!(ch.mode, Mode::RollUp(_)) {
+                        ch.displayed = EMPTY;
+                        ch.non_displayed = EMPTY;
+                        ch.row = ROWS - 1;
+                    }
+                    ch.mode = Mode::RollUp(n);
+                    ch.col = 0;
+                }
+                0x29 => ch.mode = Mode::PaintOn,
+                0x2A | 0x2B => ch.mode = Mode::Text,
+                0x2C => ch.displayed = EMPTY,
+                0x2D => ch.carriage_return(),
+                0x2E => ch.non_displayed = EMPTY,
+                0x2F => {
+                    std::mem::swap(&mut ch.displayed, &mut ch.non_displayed);
+                    ch.mode = Mode::PopOn;
+                }
+                // AOF / AON / FON: nothing to do.
+                _ => {}
+            },
+            // Tab offsets 1–3.
+            (0x17, 0x21..=0x23) => ch.col = (ch.col + (b2 - 0x20) as usize).min(COLS - 1),
+            // Mid-row style change; takes a space on screen.
+            (0x11, 0x20..=0x2F) => {
+                ch.style = attribute_style(b2 & 0x0F);
+                ch.put(' ');
+            }
+            (0x11, 0x30..=0x3F) => ch.put(table_char(SPECIAL, b2 - 0x30)),
+            // Extended characters replace the standard fallback sent
+            // just before them.
+            (0x12, 0x20..=0x3F) => {
+                ch.backspace();
+                ch.put(table_char(EXTENDED_SPANISH_FRENCH, b2 - 0x20));
+            }
+            (0x13, 0x20..=0x3F) => {
+                ch.backspace();
+                ch.put(table_char(EXTENDED_PORTUGUESE_GERMAN, b2 - 0x20));
+            }
+            (_, 0x40..=0x7F) => {
+                // Preamble address code: row from b1 and b2 bit 5, then
+                // either a style or an indent.
+                const ROW_OF: [usize; 8] = [10, 0, 2, 11, 13, 4, 6, 8];
+                let row = (ROW_OF[(b1 & 7) as usize] + ((b2 >> 5) & 1) as usize).min(ROWS - 1);
+                let attr = b2 & 0x1F;
+                let (col, style) = if attr < 0x10 {
+                    (0, attribute_style(attr))
+                } else {
+                    let underline = attr & 1 == 1;
+                    (
+                        ((attr - 0x10) >> 1) as usize * 4,
+                        CellStyle {
+                            underline,
+                            ..CellStyle::default()
+                        },
+                    )
+                };
+                ch.preamble(row, col, style);
+            }
+            // Background / foreground attribute codes and the unassigned
+            // rest.
+            _ => {}
+        }
+    }
+}
+
+/// Style of a PAC / mid-row attribute nibble: colour (or italics) in bits
+/// 3..1, underline in bit 0.
+fn attribute_style(attr: u8) -> CellStyle {
+    const COLORS: [Option<[u8; 4]>; 7] = [
+        None,
+        Some([0, 255, 0, 255]),
+        Some([0, 0, 255, 255]),
+        Some([0, 255, 255, 255]),
+        Some([255, 0, 0, 255]),
+        Some([255, 255, 0, 255]),
+        Some([255, 0, 255, 255]),
+    ];
+    let idx = (attr >> 1) as usize;
+    CellStyle {
+        color: COLORS.get(idx).copied().flatten(),
+        italic: idx == 7,
+        underline: attr & 1 == 1,
+        ..CellStyle::default()
+    }
+}
+
+/// The basic character set is ASCII except for a few code points.
+fn standard_char(b: u8) -> char {
+    match b {
+        0x2A => 'á',
+        0x5C => 'é',
+        0x5E => 'í',
+        0x5F => 'ó',
+        0x60 => 'ú',
+        0x7B => 'ç',
+        0x7C => '÷',
+        0x7D => 'Ñ',
+        0x7E => 'ñ',
+        0x7F => '█',
+        _ => b as char,
+    }
+}
+
+/// 0x11 / 0x19 0x30..=0x3F; 0x39 is the transparent space.
+const SPECIAL: &str = "®°½¿™¢£♪à èâêîôû";
+/// 0x12 / 0x1A 0x20..=0x3F.
+const EXTENDED_SPANISH_FRENCH: &str = "ÁÉÓÚÜü‘¡*'—©℠•“”ÀÂÇÈÊËëÎÏïÔÙùÛ«»";
+/// 0x13 / 0x1B 0x20..=0x3F.
+const EXTENDED_PORTUGUESE_GERMAN: &str = "ÃãÍÌìÒòÕõ{}\\^_|~ÄäÖöß¥¤│ÅåØø┌┐└┘";
+
+fn table_char(table: &str, idx: u8) -> char {
+    table.chars().nth(idx as usize).unwrap_or(' ')
+}
+
+/// Placement of CEA-608 rows `first..=last` (0-based) whose text spans
+/// columns `cols`: rows map onto the 80% title-safe area, captions in the
+/// upper half hang from their top row and the rest sit on their bottom
+/// row, so a font taller than a caption row grows away from the edge.
+/// Lines that share a centre near the middle are centred; anything else
+/// keeps its left indent.
+fn screen_layout(first: usize, last: usize, cols: (usize, usize, bool)) -> CueLayout {
+    let (min_col, max_col, centred) = cols;
+    let row_h = 0.8 / ROWS as f32;
+    let (y, height, display_align) = if first < ROWS / 2 {
+        let y = 0.1 + first as f32 * row_h;
+        (y, 0.95 - y, DisplayAlign::Before)
+    } else {
+        let bottom = 0.1 + (last + 1) as f32 * row_h;
+        (0.05, bottom - 0.05, DisplayAlign::After)
+    };
+    let mid = (min_col + max_col + 1) as f32 / 2.0;
+    let (x, width, text_align) = if centred && (mid - COLS as f32 / 2.0).abs() <= 3.0 {
+        (0.1, 0.8, TextAlign::Center)
+    } else {
+        let x = 0.1 + min_col as f32 / COLS as f32 * 0.8;
+        (x, 0.95 - x, TextAlign::Left)
+    };
+    CueLayout {
+        x,
+        y,
+        width,
+        height,
+        text_align,
+        display_align,
+        background: None,
+        line: None,
+        max_lines: None,
+    }
+}
+
+/// Turn a caption grid into cues opening at `pts_ms`: one per run of
+/// consecutive non-empty rows, placed by `layout(first_row, last_row,
+/// (min_col, max_col, centred))`. Shared with `cea708`, whose windows
+/// are grids too.
+pub(super) fn grid_cues(
+    rows: &[Vec<Option<Cell>>],
+    width: usize,
+    pts_ms: i64,
+    layout: &dyn Fn(usize, usize, (usize, usize, bool)) -> CueLayout,
+) -> Vec<VttCue> {
+    let extent = |row: &[Option<Cell>]| {
+        let first = row.iter().position(|c| c.is_some_and(|c| c.ch != ' '))?;
+        let last = row.iter().rposition(|c| c.is_some_and(|c| c.ch != ' '))?;
+        Some((first, last))
+    };
+    let mut out = Vec::new();
+    let mut r = 0;
+    while r < rows.len() {
+        if extent(&rows[r]).is_none() {
+            r += 1;
+            continue;
+        }
+        let first = r;
+        while r < rows.len() && extent(&rows[r]).is_some() {
+            r += 1;
+        }
+        let block = &rows[first..r];
+        let extents: Vec<(usize, usize)> = block.iter().filter_map(|row| extent(row)).collect();
+        let min_col = extents.iter().map(|e| e.0).min().unwrap_or(0);
+        let max_col = extents.iter().map(|e| e.1).max().unwrap_or(0);
+        let centres: Vec<usize> = extents.iter().map(|e| e.0 + e.1).collect();
+        let centred = centres.iter().max().unwrap_or(&0) - centres.iter().min().unwrap_or(&0) <= 2;
+
+        let mut text = String::new();
+        let mut spans: Vec<CueSpan> = Vec::new();
+        for (i, (row, &(start, end))) in block.iter().zip(&extents).enumerate() {
+            if i > 0 {
+                push_run(&mut text, &mut spans, '\n', None);
+            }
+            let from = if centred { start } else { min_col };
+            for cell in &row[from..=end.min(width - 1)] {
+                match cell {
+                    Some(c) => push_run(&mut text, &mut spans, c.ch, Some(c.style)),
+                    None => push_run(&mut text, &mut spans, ' ', None),
+                }
+            }
+        }
+        if spans.iter().all(|s| s.style == SpanStyle::default()) {
+            spans.clear();
+        }
+        out.push(VttCue {
+            start_ms: pts_ms,
+            end_ms: pts_ms + OPEN_CUE_MS,
+            text,
+            settings: String::new(),
+            spans,
+            layout: Some(layout(first, r - 1, (min_col, max_col, centred))),
+        });
+    }
+    out
+}
+
+/// Append `ch` to the cue text and its span list, extending the last span
+/// when the style matches. `None` (line breaks, gaps) joins whatever run
+/// is open.
+fn push_run(text: &mut String, spans: &mut Vec<CueSpan>, ch: char, style: Option<CellStyle>) {
+    text.push(ch);
+    let style = style.map(|s| SpanStyle {
+        color: s.color,
+        background: s.background,
+        bold: false,
+        italic: s.italic,
+        underline: s.underline,
+    });
+    match (spans.last_mut(), style) {
+        (Some(last), None) => last.text.push(ch),
+        (Some(last), Some(style)) if last.style == style => last.text.push(ch),
+        (_, style) => spans.push(CueSpan {
+            text: ch.to_string(),
+            style: style.unwrap_or_default(),
+            ruby: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `text` as character pairs at `pts_ms`.
+    fn chars(dec: &mut Cea608Decoder, pts_ms: i64, text: &str, out: &mut Vec<(usize, VttCue)>) {
+        for pair in text.as_bytes().chunks(2) {
+            dec.feed(pts_ms, pair[0], *pair.get(1).unwrap_or(&0), out);
+        }
+    }
+
+    /// Feed a control code the way encoders send it: twice.
+    fn control(
+        dec: &mut Cea608Decoder,
+        pts_ms: i64,
+        b1: u8,
+        b2: u8,
+        out: &mut Vec<(usize, VttCue)>,
+    ) {
+        dec.feed(pts_ms, b1, b2, out);
+        dec.feed(pts_ms, b1, b2, out);
+    }
+
+    #[test]
+    fn pop_on_caption_shows_on_eoc_and_clears_on_edm() {
+        let mut dec = Cea608Decoder::new();
+        let mut out = Vec::new();
+        control(&mut dec, 0, 0x14, 0x20, &mut out); // RCL
+        control(&mut dec, 0, 0x14, 0x70, &mut out); // PAC row 15, indent 0
+        chars(&mut dec, 0, "HELLO", &mut out);
+        assert!(out.is_empty(), "nothing displayed before EOC");
+
+        control(&mut dec, 1000, 0x14, 0x2F, &mut out); // EOC
+        assert_eq!(out.len(), 1);
+        let (channel, cue) = &out[0];
+        assert_eq!(*channel, 0);
+        assert_eq!(cue.text, "HELLO");
+        assert_eq!((cue.start_ms, cue.end_ms), (1000, 1000 + OPEN_CUE_MS));
+        let layout = cue.layout.unwrap();
+        assert_eq!(layout.display_align, DisplayAlign::After);
+
+        out.clear();
+        control(&mut dec, 3000, 0x14, 0x2C, &mut out); // EDM
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].1.text, "HELLO");
+        assert_eq!((out[0].1.start_ms, out[0].1.end_ms), (1000, 3000));
+    }
+
+    #[test]
+    fn roll_up_scrolls_on_carriage_return() {
+        let mut dec = Cea608Decoder::new();
+        let mut out = Vec::new();
+        control(&mut dec, 0, 0x14, 0x25, &mut out); // RU2
+        control(&mut dec, 0, 0x14, 0x2D, &mut out); // CR
+        chars(&mut dec, 100, "AB", &mut out);
+        control(&mut dec, 200, 0x14, 0x2D, &mut out); // CR
+        chars(&mut dec, 300, "CD", &mut out);
+        let last = &out.last().unwrap().1;
+        assert_eq!(last.text, "AB\nCD");
+        assert_eq!(last.start_ms, 300);
+
+        // A third line pushes the first out of the two-row window.
+        control(&mut dec, 400, 0x14, 0x2D, &mut out);
+        chars(&mut dec, 500, "EF", &mut out);
+        assert_eq!(out.last().unwrap().1.text, "CD\nEF");
+    }
+
+    #[test]
+    fn second_data_channel_and_special_characters() {
+        let mut dec = Cea608Decoder::new();
+        let mut out = Vec::new();
+        control(&mut dec, 0, 0x1C, 0x29, &mut out); // RDC on CC2
+        control(&mut dec, 0, 0x1C, 0x70, &mut out); // PAC row 15
+        chars(&mut dec, 0, "e", &mut out);
+        control(&mut dec, 0, 0x1A, 0x21, &mut out); // É replaces the e
+        control(&mut dec, 0, 0x19, 0x37, &mut out); // ♪
+        let (channel, cue) = out.last().unwrap();
+        assert_eq!(*channel, 1);
+        assert_eq!(cue.text, "É♪");
+    }
+
+    #[test]
+    fn styles_and_rows_become_spans_and_cues() {
+        let mut dec = Cea608Decoder::new();
+        let mut out = Vec::new();
+        control(&mut dec, 0, 0x14, 0x29, &mut out); // RDC
+        control(&mut dec, 0, 0x11, 0x40, &mut out); // PAC row 1
+        chars(&mut dec, 0, "TOP", &mut out);
+        control(&mut dec, 0, 0x14, 0x60, &mut out); // PAC row 15
+        chars(&mut dec, 0, "A", &mut out);
+        control(&mut dec, 0, 0x11, 0x2E, &mut out); // mid-row italics
+        chars(&mut dec, 0, "B", &mut out);
+
+        let open: Vec<&VttCue> = out.iter().rev().take(2).map(|(_, c)| c).collect();
+        let bottom = open[0];
+        let top = open[1];
+        assert_eq!(top.text, "TOP");
+        assert_eq!(top.layout.unwrap().display_align, DisplayAlign::Before);
+        assert_eq!(bottom.text, "A B");
+        assert!(bottom.spans.last().unwrap().style.italic);
+        assert!(!bottom.spans[0].style.italic);
+    }
+
+    #[test]
+    fn flush_closes_open_cues() {
+        let mut dec = Cea608Decoder::new();
+        let mut out = Vec::new();
+        control(&mut dec, 0, 0x14, 0x29, &mut out);
+        chars(&mut dec, 500, "HI", &mut out);
+        out.clear();
+        dec.flush(800, &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!((out[0].1.start_ms, out[0].1.end_ms), (500, 800));
+    }
+}
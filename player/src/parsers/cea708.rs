@@ -0,0 +1,622 @@
+//! CEA-708 (DTVCC) closed-caption decoding, from the `cc_data` triplets
+//! with `cc_type` 2/3 in the video SEI.
+//!
+//! Triplets are assembled into caption channel packets, packets split into
+//! service blocks, and each service (1 = primary, usually English) runs its
+//! own set of eight windows. Every visible window becomes a cue placed at
+//! the window's anchor, with the window fill as background; as with
+//! `cea608`, each change of what is on screen closes the service's cues
+//! and opens the next ones. Pen sizes, fonts, edge styles, window borders,
+//! scroll / wipe effects and the DLY / DLC timing commands are ignored.
+
+use super::cea608::{grid_cues, Cell, CellStyle};
+use super::vtt::{CueLayout, DisplayAlign, TextAlign, VttCue};
+
+/// Widest window the 16:9 caption grid allows.
+const MAX_COLS: usize = 42;
+const MAX_ROWS: usize = 15;
+
+#[derive(Clone)]
+struct Window {
+    visible: bool,
+    priority: u8,
+    relative: bool,
+    anchor_v: u8,
+    anchor_h: u8,
+    /// 0..=8, row-major from top-left.
+    anchor_point: u8,
+    /// 0 left, 1 right, 2 centre, 3 full (treated as left).
+    justify: u8,
+    fill: Option<[u8; 4]>,
+    rows: Vec<Vec<Option<Cell>>>,
+    pen_row: usize,
+    pen_col: usize,
+    pen: CellStyle,
+}
+
+impl Window {
+    fn put(&mut self, ch: char) {
+        let (row, col) = (self.pen_row, self.pen_col);
+        if let Some(cell) = self.rows.get_mut(row).and_then(|r| r.get_mut(col)) {
+            *cell = Some(Cell {
+                ch,
+                style: self.pen,
+            });
+        }
+        self.pen_col = (col + 1).min(MAX_COLS - 1);
+    }
+
+    fn clear(&mut self) {
+        for row in &mut self.rows {
+            row.fill(None);
+        }
+        self.pen_row = 0;
+        self.pen_col = 0;
+    }
+
+    fn carriage_return(&mut self) {
+        if self.pen_row + 1 < self.rows.len() {
+            self.pen_row += 1;
+        } else {
+            self.rows.remove(0);
+            self.rows.push(vec![None; MAX_COLS]);
+        }
+        self.pen_col = 0;
+    }
+
+    /// The window's box: it extends from the anchor away from the anchor
+    /// point's side, inside the 80% title-safe area.
+    fn layout(&self) -> CueLayout {
+        let (v, h) = if self.relative {
+            (self.anchor_v as f32 / 100.0, self.anchor_h as f32 / 100.0)
+        } else {
+            (self.anchor_v as f32 / 74.0, self.anchor_h as f32 / 209.0)
+        };
+        let y = 0.1 + v.clamp(0.0, 1.0) * 0.8;
+        let x = 0.1 + h.clamp(0.0, 1.0) * 0.8;
+        let (y, height, display_align) = match self.anchor_point / 3 {
+            0 => (y, 0.95 - y, DisplayAlign::Before),
+            1 => {
+                let half = (y - 0.05).min(0.95 - y);
+                (y - half, 2.0 * half, DisplayAlign::Center)
+            }
+            _ => (0.05, y - 0.05, DisplayAlign::After),
+        };
+        let (x, width, anchored) = match self.anchor_point % 3 {
+            0 => (x, 0.95 - x, TextAlign::Left),
+            1 => {
+                let half = (x - 0.05).min(0.95 - x);
+                (x - half, 2.0 * half, TextAlign::Center)
+            }
+            _ => (0.05, x - 0.05, TextAlign::Right),
+        };
+        let text_align = match self.justify {
+            1 => TextAlign::Right,
+            2 => TextAlign::Center,
+            _ => anchored,
+        };
+        CueLayout {
+            x,
+            y,
+            width,
+            height,
+            text_align,
+            display_align,
+            background: self.fill,
+            line: None,
+            max_lines: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Service {
+    windows: [Option<Window>; 8],
+    current: usize,
+    /// Cues on screen since the last change, open-ended.
+    open: Vec<VttCue>,
+}
+
+impl Service {
+    fn window(&mut self) -> Option<&mut Window> {
+        self.windows[self.current].as_mut()
+    }
+
+    /// Windows selected by a CLW / DSW / HDW / TGW / DLW bitmap.
+    fn each_window(&mut self, bitmap: u8, mut f: impl FnMut(&mut Option<Window>)) {
+        for (i, w) in self.windows.iter_mut().enumerate() {
+            if bitmap & (1 << i) != 0 {
+                f(w);
+            }
+        }
+    }
+
+    /// Interpret one service block.
+    fn run(&mut self, data: &[u8]) {
+        let mut i = 0;
+        while i < data.len() {
+            let code = data[i];
+            let arg = |n: usize| data.get(i + 1 + n).copied().unwrap_or(0);
+            let params = match code {
+                0x08 => {
+                    if let Some(w) = self.window() {
+                        if w.pen_col > 0 {
+                            w.pen_col -= 1;
+                            let (row, col) = (w.pen_row, w.pen_col);
+                            if let Some(cell) = w.rows.get_mut(row).and_then(|r| r.get_mut(col)) {
+                                *cell = None;
+                            }
+                        }
+                    }
+                    0
+                }
+                0x0C => {
+                    if let Some(w) = self.window() {
+                        w.clear();
+                    }
+                    0
+                }
+                0x0D => {
+                    if let Some(w) = self.window() {
+                        w.carriage_return();
+                    }
+                    0
+                }
+                0x0E => {
+                    if let Some(w) = self.window() {
+                        let row = w.pen_row;
+                        if let Some(r) = w.rows.get_mut(row) {
+                            r.fill(None);
+                        }
+                        w.pen_col = 0;
+                    }
+                    0
+                }
+                0x00..=0x0F => 0,
+                0x10 => self.extended(arg(0), &data[(i + 2).min(data.len())..]),
+                0x11..=0x17 => 1,
+                0x18 => {
+                    let ch = char::from_u32((u32::from(arg(0)) << 8) | u32::from(arg(1)));
+                    if let (Some(ch), Some(w)) = (ch, self.window()) {
+                        w.put(ch);
+                    }
+                    2
+                }
+                0x19..=0x1F => 2,
+                0x20..=0x7F => {
+                    let ch = if code == 0x7F { '♪' } else { code as char };
+                    if let Some(w) = self.window() {
+                        w.put(ch);
+                    }
+                    0
+                }
+                0x80..=0x9F => self.command(code, &data[i + 1..]),
+                0xA0..=0xFF => {
+                    if let Some(w) = self.window() {
+                        w.put(code as char);
+                    }
+                    0
+                }
+            };
+            i += 1 + params;
+        }
+    }
+
+    /// EXT1 followed by `code` and then `rest`; returns the bytes consumed
+    /// after EXT1.
+    fn extended(&mut self, code: u8, rest: &[u8]) -> usize {
+        1 + match code {
+            // C2: reserved codes of fixed length.
+            0x00..=0x07 => 0,
+            0x08..=0x0F => 1,
+            0x10..=0x17 => 2,
+            0x18..=0x1F => 3,
+            0x20..=0x7F => {
+                if let Some(ch) = g2_char(code) {
+                    if let Some(w) = self.window() {
+                        w.put(ch);
+                    }
+                }
+                0
+            }
+            // C3: reserved; 0x90.. carry their own length.
+            0x80..=0x87 => 4,
+            0x88..=0x8F => 5,
+            0x90..=0x9F => rest.first().map_or(0, |&n| 1 + (n & 0x3F) as usize),
+            // G3: only the [CC] icon is defined.
+            0xA0..=0xFF => 0,
+        }
+    }
+
+    /// A C1 command; returns its parameter length.
+    fn command(&mut self, code: u8, p: &[u8]) -> usize {
+        let arg = |n: usize| p.get(n).copied().unwrap_or(0);
+        match code {
+            0x80..=0x87 => {
+                let n = (code - 0x80) as usize;
+                if self.windows[n].is_some() {
+                    self.current = n;
+                }
+                0
+            }
+            0x88 => {
+                self.each_window(arg(0), |w| {
+                    if let Some(w) = w {
+                        for row in &mut w.rows {
+                            row.fill(None);
+                        }
+                    }
+                });
+                1
+            }
+            0x89..=0x8B => {
+                self.each_window(arg(0), |w| {
+                    if let Some(w) = w {
+                        w.visible = match code {
+                            0x89 => true,
+                            0x8A => false,
+                            _ => !w.visible,
+                        };
+                    }
+                });
+                1
+            }
+            0x8C => {
+                self.each_window(arg(0), |w| *w = None);
+                1
+            }
+            0x8D => 1,
+            0x8E => 0,
+            0x8F => {
+                let open = std::mem::take(&mut self.open);
+                *self = Service {
+                    open,
+                    ..Service::default()
+                };
+                0
+            }
+            0x90 => {
+                if let Some(w) = self.window() {
+                    w.pen.italic = arg(1) & 0x80 != 0;
+                    w.pen.underline = arg(1) & 0x40 != 0;
+                }
+                2
+            }
+            0x91 => {
+                if let Some(w) = self.window() {
+                    w.pen.color = color(arg(0)).filter(|c| *c != [255, 255, 255, 255]);
+                    w.pen.background = color(arg(1)).filter(|c| *c != [0, 0, 0, 255]);
+                }
+                3
+            }
+            0x92 => {
+                if let Some(w) = self.window() {
+                    w.pen_row = (arg(0) & 0x0F) as usize;
+                    w.pen_col = ((arg(1) & 0x3F) as usize).min(MAX_COLS - 1);
+                }
+                2
+            }
+            0x97 => {
+                if let Some(w) = self.window() {
+                    w.fill = color(arg(0));
+                    w.justify = arg(2) & 0x03;
+                }
+                4
+            }
+            0x98..=0x9F => {
+                let n = (code - 0x98) as usize;
+                let rows = ((arg(3) & 0x0F) as usize + 1).min(MAX_ROWS);
+                let w = self.windows[n].get_or_insert_with(|| Window {
+                    visible: false,
+                    priority: 0,
+                    relative: false,
+                    anchor_v: 0,
+                    anchor_h: 0,
+                    anchor_point: 0,
+                    justify: 0,
+                    fill: None,
+                    rows: Vec::new(),
+                    pen_row: 0,
+                    pen_col: 0,
+                    pen: CellStyle::default(),
+                });
+                w.visible = arg(0) & 0x20 != 0;
+                w.priority = arg(0) & 0x07;
+                w.relative = arg(1) & 0x80 != 0;
+                w.anchor_v = arg(1) & 0x7F;
+                w.anchor_h = arg(2);
+                w.anchor_point = (arg(3) >> 4).min(8);
+                // Resizing keeps the bottom rows, where roll-up text lives.
+                let drop = w.rows.len().saturating_sub(rows);
+                w.rows.drain(..drop);
+                w.rows.resize(rows, vec![None; MAX_COLS]);
+                w.pen_row = w.pen_row.min(rows - 1);
+                self.current = n;
+                6
+            }
+            // 0x93..=0x96 are undefined and carry no parameters.
+            _ => 0,
+        }
+    }
+
+    /// The service's cues as of now: one per visible window, lowest
+    /// priority first so higher-priority windows draw on top.
+    fn cues(&self, pts_ms: i64) -> Vec<VttCue> {
+        let mut windows: Vec<&Window> = self
+            .windows
+            .iter()
+            .flatten()
+            .filter(|w| w.visible)
+            .collect();
+        windows.sort_by_key(|w| std::cmp::Reverse(w.priority));
+        windows
+            .into_iter()
+            .flat_map(|w| {
+                let layout = w.layout();
+                let mut cues = grid_cues(&w.rows, MAX_COLS, pts_ms, &|_, _, _| layout);
+                // One window is one box: keep its rows together.
+                if cues.len() > 1 {
+                    let mut first = cues.remove(0);
+                    for cue in cues.drain(..) {
+                        first.text.push('\n');
+                        first.text.push_str(&cue.text);
+                    }
+                    first.spans.clear();
+                    cues.push(first);
+                }
+                cues
+            })
+            .collect()
+    }
+
+    /// Close the open cues and open the current ones if what is on screen
+    /// changed.
+    fn publish(&mut self, pts_ms: i64, out: &mut Vec<VttCue>) {
+        let cues = self.cues(pts_ms);
+        let same = cues.len() == self.open.len()
+            && cues
+                .iter()
+                .zip(&self.open)
+                .all(|(a, b)| a.text == b.text && a.spans == b.spans && a.layout == b.layout);
+        if same {
+            return;
+        }
+        for mut cue in self.open.drain(..) {
+            cue.end_ms = pts_ms.max(cue.start_ms);
+            out.push(cue);
+        }
+        out.extend(cues.iter().cloned());
+        self.open = cues;
+    }
+}
+
+/// 2-bit-per-channel colour with opacity in bits 7..6 (0 solid, 1 flash,
+/// 2 translucent, 3 transparent = `None`).
+fn color(b: u8) -> Option<[u8; 4]> {
+    let alpha = match b >> 6 {
+        0 | 1 => 255,
+        2 => 128,
+        _ => return None,
+    };
+    let c = |shift: u8| ((b >> shift) & 3) * 85;
+    Some([c(4), c(2), c(0), alpha])
+}
+
+/// The G2 characters; unassigned codes yield `None`.
+fn g2_char(code: u8) -> Option<char> {
+    Some(match code {
+        0x20 | 0x21 => ' ',
+        0x25 => '…',
+        0x2A => 'Š',
+        0x2C => 'Œ',
+        0x30 => '█',
+        0x31 => '‘',
+        0x32 => '’',
+        0x33 => '“',
+        0x34 => '”',
+        0x35 => '•',
+        0x39 => '™',
+        0x3A => 'š',
+        0x3C => 'œ',
+        0x3D => '℠',
+        0x3F => 'Ÿ',
+        0x76 => '⅛',
+        0x77 => '⅜',
+        0x78 => '⅝',
+        0x79 => '⅞',
+        0x7A => '│',
+        0x7B => '┐',
+        0x7C => '└',
+        0x7D => '─',
+        0x7E => '┘',
+        0x7F => '┌',
+        _ => return None,
+    })
+}
+
+/// Decoder for the DTVCC stream of one video track.
+#[derive(Default)]
+pub struct Cea708Decoder {
+    packet: Vec<u8>,
+    /// Services seen so far, indexed by service number (1..=63).
+    services: Vec<(u8, Service)>,
+}
+
+impl Cea708Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one `cc_data` triplet with `cc_type` 2 (packet data) or 3
+    /// (packet start) shown at `pts_ms`. Cue updates go to `out` tagged with
+    /// their service number, with the same replace-by-start semantics as
+    /// `Cea608Decoder::feed`.
+    pub fn feed(&mut self, pts_ms: i64, cc_type: u8, b1: u8, b2: u8, out: &mut Vec<(u8, VttCue)>) {
+        match cc_type {
+            3 => {
+                self.finish(pts_ms, out);
+                self.packet.extend([b1, b2]);
+            }
+            2 if !self.packet.is_empty() => self.packet.extend([b1, b2]),
+            _ => return,
+        }
+        let size = match self.packet[0] & 0x3F {
+            0 => 128,
+            n => n as usize * 2,
+        };
+        if self.packet.len() >= size {
+            self.finish(pts_ms, out);
+        }
+    }
+
+    /// Close every cue on screen at `pts_ms` and reset, as
+    /// `Cea608Decoder::flush`.
+    pub fn flush(&mut self, pts_ms: i64, out: &mut Vec<(u8, VttCue)>) {
+        self.packet.clear();
+        for (number, mut service) in self.services.drain(..) {
+            for mut cue in service.open.drain(..) {
+                cue.end_ms = pts_ms.max(cue.start_ms);
+                out.push((number, cue));
+            }
+        }
+    }
+
+    /// Run the assembled packet's service blocks.
+    fn finish(&mut self, pts_ms: i64, out: &mut Vec<(u8, VttCue)>) {
+        let packet = std::mem::take(&mut self.packet);
+        let size = match packet.first().map(|b| b & 0x3F) {
+            None => return,
+            Some(0) => 128,
+            Some(n) => n as usize * 2,
+        };
+        let data = &packet[1..size.min(packet.len())];
+        let mut i = 0;
+        while i < data.len() {
+            let mut number = data[i] >> 5;
+            let len = (data[i] & 0x1F) as usize;
+            i += 1;
+            if number == 0 || len == 0 {
+                break;
+            }
+            if number == 7 {
+                number = data.get(i).map_or(0, |b| b & 0x3F);
+                i += 1;
+            }
+            let block = &data[i.min(data.len())..(i + len).min(data.len())];
+            i += len;
+            let idx = match self.services.iter().position(|(n, _)| *n == number) {
+                Some(idx) => idx,
+                None => {
+                    self.services.push((number, Service::default()));
+                    self.services.len() - 1
+                }
+            };
+            let service = &mut self.services[idx].1;
+            service.run(block);
+            let mut cues = Vec::new();
+            service.publish(pts_ms, &mut cues);
+            out.extend(cues.into_iter().map(|c| (number, c)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wrap `data` for `service` in one caption channel packet and feed it
+    /// as triplets.
+    fn send(dec: &mut Cea708Decoder, pts_ms: i64, service: u8, data: &[u8]) -> Vec<(u8, VttCue)> {
+        let mut packet = vec![0, (service << 5) | data.len() as u8];
+        packet.extend_from_slice(data);
+        if packet.len() % 2 == 1 {
+            packet.push(0);
+        }
+        packet[0] = (packet.len() / 2) as u8;
+        let mut out = Vec::new();
+        for (i, pair) in packet.chunks(2).enumerate() {
+            let cc_type = if i == 0 { 3 } else { 2 };
+            dec.feed(pts_ms, cc_type, pair[0], pair[1], &mut out);
+        }
+        out
+    }
+
+    /// DF0: visible, anchored bottom-centre at the middle of the bottom
+    /// edge, two rows of 32 columns.
+    const DEFINE_BOTTOM: [u8; 7] = [0x98, 0x20, 74, 105, 0x71, 31, 0];
+
+    #[test]
+    fn visible_window_text_becomes_a_cue() {
+        let mut dec = Cea708Decoder::new();
+        let mut data = DEFINE_BOTTOM.to_vec();
+        data.extend_from_slice(b"Hi");
+        data.extend([0x0D, 0x7F]);
+        let out = send(&mut dec, 1000, 1, &data);
+        let (service, cue) = out.last().unwrap();
+        assert_eq!(*service, 1);
+        assert_eq!(cue.text, "Hi\n♪");
+        assert_eq!(cue.start_ms, 1000);
+        let layout = cue.layout.unwrap();
+        assert_eq!(layout.display_align, DisplayAlign::After);
+        assert_eq!(layout.text_align, TextAlign::Center);
+        assert!((layout.y + layout.height - 0.9).abs() < 1e-3);
+
+        // Clearing the window closes the cue.
+        let out = send(&mut dec, 2500, 1, &[0x88, 0x01]);
+        assert_eq!(out.len(), 1);
+        assert_eq!((out[0].1.start_ms, out[0].1.end_ms), (1000, 2500));
+    }
+
+    #[test]
+    fn hidden_window_shows_on_toggle_with_pen_colour() {
+        let mut dec = Cea708Decoder::new();
+        let mut data = DEFINE_BOTTOM.to_vec();
+        data[1] = 0x00; // hidden
+                        // SPC: yellow foreground, default background.
+        data.extend([0x91, 0x3C, 0x00, 0x00]);
+        data.extend_from_slice(b"Yo");
+        assert!(send(&mut dec, 0, 2, &data).is_empty());
+
+        let out = send(&mut dec, 400, 2, &[0x8B, 0x01]);
+        let (service, cue) = &out[0];
+        assert_eq!(*service, 2);
+        assert_eq!(cue.text, "Yo");
+        assert_eq!(cue.spans.len(), 1);
+        assert_eq!(cue.spans[0].style.color, Some([255, 255, 0, 255]));
+    }
+
+    #[test]
+    fn carriage_return_scrolls_a_full_window_and_flush_closes() {
+        let mut dec = Cea708Decoder::new();
+        let mut data = DEFINE_BOTTOM.to_vec();
+        data.extend_from_slice(b"A\rB\rC");
+        let out = send(&mut dec, 0, 1, &data);
+        assert_eq!(out.last().unwrap().1.text, "B\nC");
+
+        let mut out = Vec::new();
+        dec.flush(700, &mut out);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].1.end_ms, 700);
+    }
+
+    #[test]
+    fn extended_service_numbers_and_g2_characters() {
+        let mut dec = Cea708Decoder::new();
+        let mut data = vec![9];
+        data.extend(DEFINE_BOTTOM);
+        data.extend([0x10, 0x39]);
+        let mut packet = vec![0, (7 << 5) | (data.len() as u8 - 1)];
+        packet.extend(data);
+        if packet.len() % 2 == 1 {
+            packet.push(0);
+        }
+        packet[0] = (packet.len() / 2) as u8;
+        let mut out = Vec::new();
+        for (i, pair) in packet.chunks(2).enumerate() {
+            dec.feed(0, if i == 0 { 3 } else { 2 }, pair[0], pair[1], &mut out);
+        }
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].0, 9);
+        assert_eq!(out[0].1.text, "™");
+    }
+}
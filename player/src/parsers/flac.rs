@@ -0,0 +1,130 @@
+//! FLAC codec configuration: the `dfLa` box from the FLAC in ISO Base Media
+//! File Format encapsulation (FLACSpecificBox).
+//!
+//! `dfLa` holds the stream's native metadata blocks (STREAMINFO first)
+//! without the `fLaC` marker. Prefixing the marker gives the stream header
+//! both FFmpeg (extradata) and MediaCodec (`csd-0`) accept.
+
+/// Parsed `dfLa` box.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FlacConfig {
+    pub sample_rate: u32,
+    pub channels: u8,
+    pub bits_per_sample: u8,
+    /// `fLaC` followed by every metadata block from the box.
+    pub stream_header: Vec<u8>,
+}
+
+const STREAMINFO_LEN: usize = 34;
+
+/// Parse the body of a `dfLa` box (everything after the 8-byte box header,
+/// i.e. starting at the FullBox version/flags).
+pub fn parse_dfla_record(dfla: &[u8]) -> Option<FlacConfig> {
+    if dfla.len() < 4 || dfla[0] != 0 {
+        return None;
+    }
+    let blocks = &dfla[4..];
+    // Each block: last(1) type(7) length(24) | data. STREAMINFO (type 0)
+    // must come first.
+    if blocks.len() < 4 + STREAMINFO_LEN || blocks[0] & 0x7f != 0 {
+        return None;
+    }
+    let len = u32::from_be_bytes([0, blocks[1], blocks[2], blocks[3]]) as usize;
+    if len != STREAMINFO_LEN {
+        return None;
+    }
+    // Validate the rest of the chain so a truncated box isn't handed to a
+    // decoder that would read past it.
+    let mut i = 0;
+    loop {
+        let header = blocks.get(i..i + 4)?;
+        let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+        i += 4 + len;
+        if i > blocks.len() {
+            return None;
+        }
+        if header[0] & 0x80 != 0 || i == blocks.len() {
+            break;
+        }
+    }
+
+    // STREAMINFO: min/max block size (16+16), min/max frame size (24+24),
+    // sample rate (20), channels - 1 (3), bits per sample - 1 (5), ...
+    let si = &blocks[4..4 + STREAMINFO_LEN];
+    let sample_rate = (u32::from(si[10]) << 12) | (u32::from(si[11]) << 4) | (u32::from(si[12]) >> 4);
+    if sample_rate == 0 {
+        return None;
+    }
+    let channels = ((si[12] >> 1) & 0x07) + 1;
+    let bits_per_sample = (((si[12] & 0x01) << 4) | (si[13] >> 4)) + 1;
+
+    let mut stream_header = Vec::with_capacity(4 + i);
+    stream_header.extend_from_slice(b"fLaC");
+    stream_header.extend_from_slice(&blocks[..i]);
+    Some(FlacConfig {
+        sample_rate,
+        channels,
+        bits_per_sample,
+        stream_header,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn streaminfo(rate: u32, channels: u8, bps: u8) -> [u8; STREAMINFO_LEN] {
+        let mut si = [0u8; STREAMINFO_LEN];
+        si[0..2].copy_from_slice(&4096u16.to_be_bytes());
+        si[2..4].copy_from_slice(&4096u16.to_be_bytes());
+        si[10] = (rate >> 12) as u8;
+        si[11] = (rate >> 4) as u8;
+        si[12] = ((rate & 0x0f) as u8) << 4 | (channels - 1) << 1 | (bps - 1) >> 4;
+        si[13] = ((bps - 1) & 0x0f) << 4;
+        si
+    }
+
+    fn dfla(blocks: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut v = vec![0, 0, 0, 0];
+        for (n, (ty, data)) in blocks.iter().enumerate() {
+            let last = if n + 1 == blocks.len() { 0x80 } else { 0 };
+            v.push(last | ty);
+            v.extend_from_slice(&(data.len() as u32).to_be_bytes()[1..]);
+            v.extend_from_slice(data);
+        }
+        v
+    }
+
+    #[test]
+    fn streaminfo_only() {
+        let si = streaminfo(44_100, 2, 16);
+        let cfg = parse_dfla_record(&dfla(&[(0, &si)])).expect("parse");
+        assert_eq!(cfg.sample_rate, 44_100);
+        assert_eq!(cfg.channels, 2);
+        assert_eq!(cfg.bits_per_sample, 16);
+        assert_eq!(&cfg.stream_header[..4], b"fLaC");
+        assert_eq!(cfg.stream_header[4], 0x80);
+        assert_eq!(cfg.stream_header.len(), 4 + 4 + STREAMINFO_LEN);
+    }
+
+    #[test]
+    fn keeps_trailing_blocks() {
+        let si = streaminfo(96_000, 6, 24);
+        let comment = [0u8; 8];
+        let cfg = parse_dfla_record(&dfla(&[(0, &si), (4, &comment)])).expect("parse");
+        assert_eq!(cfg.sample_rate, 96_000);
+        assert_eq!(cfg.channels, 6);
+        assert_eq!(cfg.bits_per_sample, 24);
+        assert_eq!(cfg.stream_header.len(), 4 + 2 * 4 + STREAMINFO_LEN + comment.len());
+    }
+
+    #[test]
+    fn rejects_missing_streaminfo_and_truncation() {
+        let si = streaminfo(48_000, 2, 16);
+        assert!(parse_dfla_record(&dfla(&[(4, &si)])).is_none());
+        let mut truncated = dfla(&[(0, &si), (4, &[0u8; 8])]);
+        truncated.truncate(truncated.len() - 2);
+        assert!(parse_dfla_record(&truncated).is_none());
+        assert!(parse_dfla_record(&[0, 0, 0, 0, 0x80, 0, 0]).is_none());
+    }
+}
@@ -1,5 +1,6 @@
 //! HEVC bitstream parsing: SPS colour information (bit depth, VUI colour
-//! description) and SEI metadata needed for HDR playback.
+//! description), SEI metadata needed for HDR playback and the closed
+//! captions carried in SEI.
 //!
 //! The DASH manifest is not a reliable source of colorimetry — real-world
 //! streams signal BT.709 in the MPD while the bitstream carries PQ/BT.2020
@@ -393,8 +394,60 @@ pub fn parse_sei_hdr_metadata(nalu: &[u8]) -> SeiHdrMetadata {
         return out;
     };
     let rbsp = unescape_rbsp(payload);
-    let mut d = &rbsp[..];
+    for_each_sei_message(&rbsp, |payload_type, body| match payload_type {
+        // SMPTE 2086: 3×(primary x,y u16)=12B + white point (x,y u16)=4B
+        // = 16 bytes, THEN max_display_mastering_luminance u32 (0.0001
+        // nits) at 16..20, then min u32 at 20..24. (The old code read
+        // 20..24 = the MIN — that surfaced as a bogus 0.005-nit "peak"
+        // flip-flopping against MaxCLL on real streams.)
+        SEI_MASTERING_DISPLAY if body.len() >= 20 => {
+            let max = u32::from_be_bytes([body[16], body[17], body[18], body[19]]);
+            out.static_info.mastering_peak_nits = Some(max as f32 * 0.0001);
+        }
+        SEI_CONTENT_LIGHT_LEVEL if body.len() >= 2 => {
+            let max_cll = u16::from_be_bytes([body[0], body[1]]);
+            if max_cll > 0 {
+                out.static_info.max_cll_nits = Some(max_cll as f32);
+            }
+        }
+        SEI_USER_DATA_REGISTERED_T35 => {
+            if let Some(info) = parse_t35_hdr10plus(body) {
+                out.hdr10plus = Some(info);
+            }
+        }
+        _ => {}
+    });
+    out
+}
+
+/// Closed-caption data carried in one SEI prefix NAL unit (raw NALU bytes
+/// including the 2-byte header): the ATSC A/53 `cc_data` triplets of every
+/// ITU-T T.35 `GA94` payload, in bitstream order. Each triplet is
+/// `[marker_bits | cc_valid << 2 | cc_type, cc_data_1, cc_data_2]` exactly
+/// as coded; `cc_type` 0/1 are CEA-608 field 1/2 byte pairs, 2/3 CEA-708
+/// DTVCC packet data / start. Empty when the NAL carries no captions.
+pub fn parse_sei_cc_data(nalu: &[u8]) -> Vec<[u8; 3]> {
+    nalu.get(2..).map(sei_cc_data).unwrap_or_default()
+}
 
+/// [`parse_sei_cc_data`] on an SEI payload with the NAL header already
+/// stripped — the SEI message syntax is shared with H.264
+/// (`avc::parse_sei_cc_data`).
+pub(crate) fn sei_cc_data(payload: &[u8]) -> Vec<[u8; 3]> {
+    let mut out = Vec::new();
+    for_each_sei_message(&unescape_rbsp(payload), |payload_type, body| {
+        if payload_type == SEI_USER_DATA_REGISTERED_T35 {
+            parse_t35_cc_data(body, &mut out);
+        }
+    });
+    out
+}
+
+/// Walk the `sei_message`s of an SEI RBSP (header stripped, emulation
+/// prevention removed), calling `f(payload_type, payload)` for each. A
+/// truncated message ends the walk.
+fn for_each_sei_message(rbsp: &[u8], mut f: impl FnMut(u32, &[u8])) {
+    let mut d = rbsp;
     // sei_message loop: payload_type and payload_size are both coded as
     // sequences of 0xFF (add 255) terminated by the final byte.
     loop {
@@ -408,7 +461,7 @@ pub fn parse_sei_hdr_metadata(nalu: &[u8]) -> SeiHdrMetadata {
                         break;
                     }
                 }
-                None => return out,
+                None => return,
             }
         }
         let mut payload_size: usize = 0;
@@ -421,50 +474,50 @@ pub fn parse_sei_hdr_metadata(nalu: &[u8]) -> SeiHdrMetadata {
                         break;
                     }
                 }
-                None => return out,
+                None => return,
             }
         }
         let Some(body) = d.get(..payload_size) else {
-            return out;
+            return;
         };
-        match payload_type {
-            SEI_MASTERING_DISPLAY => {
-                // SMPTE 2086: 3×(primary x,y u16)=12B + white point (x,y u16)=4B
-                // = 16 bytes, THEN max_display_mastering_luminance u32 (0.0001
-                // nits) at 16..20, then min u32 at 20..24. (The old code read
-                // 20..24 = the MIN — that surfaced as a bogus 0.005-nit "peak"
-                // flip-flopping against MaxCLL on real streams.)
-                if body.len() >= 20 {
-                    let max = u32::from_be_bytes([body[16], body[17], body[18], body[19]]);
-                    out.static_info.mastering_peak_nits = Some(max as f32 * 0.0001);
-                }
-            }
-            SEI_CONTENT_LIGHT_LEVEL => {
-                if body.len() >= 2 {
-                    let max_cll = u16::from_be_bytes([body[0], body[1]]);
-                    if max_cll > 0 {
-                        out.static_info.max_cll_nits = Some(max_cll as f32);
-                    }
-                }
-            }
-            SEI_USER_DATA_REGISTERED_T35 => {
-                if let Some(info) = parse_t35_hdr10plus(body) {
-                    out.hdr10plus = Some(info);
-                }
-            }
-            _ => {}
-        }
+        f(payload_type, body);
         d = &d[payload_size..];
         // rbsp_trailing_bits: 0x80 (stop bit + alignment), possibly
         // followed by zero padding, terminates the message list. A real
         // payload type byte can also be ≥0x80 (137/144 are), so only
         // treat it as trailing when nothing but zeros follows.
         if d.is_empty() || (d[0] == 0x80 && d[1..].iter().all(|&b| b == 0)) {
-            return out;
+            return;
         }
     }
 }
 
+/// ATSC A/53 Part 4 caption data inside the ITU-T T.35 SEI: country 0xB5
+/// (USA), provider 0x0031 (ATSC), user_identifier `GA94`,
+/// user_data_type_code 3 (cc_data). Appends the triplets to `out`.
+fn parse_t35_cc_data(body: &[u8], out: &mut Vec<[u8; 3]>) {
+    if body.len() < 10
+        || body[0] != 0xB5
+        || u16::from_be_bytes([body[1], body[2]]) != 0x0031
+        || &body[3..7] != b"GA94"
+        || body[7] != 0x03
+    {
+        return;
+    }
+    // reserved(1) process_cc_data_flag(1) additional_data_flag(1)
+    // cc_count(5), then em_data(8).
+    if body[8] & 0x40 == 0 {
+        return;
+    }
+    let cc_count = (body[8] & 0x1F) as usize;
+    out.extend(
+        body[10..]
+            .chunks_exact(3)
+            .take(cc_count)
+            .map(|t| [t[0], t[1], t[2]]),
+    );
+}
+
 /// ST 2094-40 (HDR10+) dynamic metadata inside the ITU-T T.35 SEI.
 /// Returns window-0 maxscl/average in nits.
 fn parse_t35_hdr10plus(body: &[u8]) -> Option<Hdr10PlusInfo> {
@@ -607,6 +660,33 @@ mod tests {
         assert!(meta.hdr10plus.is_none());
     }
 
+    #[test]
+    fn sei_cc_data_extracts_ga94_triplets() {
+        // GA94 cc_data with two triplets: a CEA-608 field-1 pair and a
+        // DTVCC packet start.
+        let mut t35 = vec![0xB5, 0x00, 0x31];
+        t35.extend_from_slice(b"GA94");
+        t35.extend_from_slice(&[0x03, 0x40 | 2, 0xFF]); // cc_data, process + count, em_data
+        t35.extend_from_slice(&[0xFC, 0x94, 0x20, 0xFF, 0x02, 0x21]);
+        t35.push(0xFF); // marker_bits
+        let mut nalu = vec![NAL_SEI_PREFIX << 1, 0x01];
+        nalu.push(4);
+        nalu.push(t35.len() as u8);
+        nalu.extend_from_slice(&t35);
+        nalu.push(0x80);
+        assert_eq!(
+            parse_sei_cc_data(&nalu),
+            vec![[0xFC, 0x94, 0x20], [0xFF, 0x02, 0x21]]
+        );
+        // The HDR walk still ignores it.
+        assert!(parse_sei_hdr_metadata(&nalu).hdr10plus.is_none());
+
+        // process_cc_data_flag clear: nothing.
+        let mut off = nalu.clone();
+        off[12] = 2;
+        assert!(parse_sei_cc_data(&off).is_empty());
+    }
+
     #[test]
     fn rbsp_unescape() {
         assert_eq!(unescape_rbsp(&[0, 0, 3, 1]), vec![0, 0, 1]);
@@ -142,6 +142,113 @@ pub fn parse_sidx(data: &mut &[u8]) -> Result<SidxBox, Box<dyn Error>> {
     })
 }
 
+/// Byte ranges (relative to the fragment start) of a media fragment's `moof`
+/// and of its first sample's data — all a keyframe-only fetch needs from a
+/// SAP-aligned subsegment (trick play).
+#[derive(Debug, PartialEq, Eq)]
+pub struct FirstSample {
+    pub moof: std::ops::Range<usize>,
+    pub data: std::ops::Range<usize>,
+}
+
+/// Header of the box at `at`: (body start, box end, type). `None` when the
+/// header or the box itself runs past the end of `data`.
+fn box_at(data: &[u8], at: usize) -> Option<(usize, usize, [u8; 4])> {
+    let header = data.get(at..at + 8)?;
+    let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as u64;
+    let kind = [header[4], header[5], header[6], header[7]];
+    let (body, size) = match size {
+        1 => {
+            let large = data.get(at + 8..at + 16)?;
+            (at + 16, u64::from_be_bytes(large.try_into().ok()?))
+        }
+        0 => (at + 8, (data.len() - at) as u64),
+        s => (at + 8, s),
+    };
+    let end = at.checked_add(usize::try_from(size).ok()?)?;
+    if end > data.len() || body > end {
+        return None;
+    }
+    Some((body, end, kind))
+}
+
+/// First child box of type `kind` in `data[start..end]`: (body start, end).
+fn child_box(data: &[u8], start: usize, end: usize, kind: &[u8; 4]) -> Option<(usize, usize)> {
+    let mut at = start;
+    while at < end {
+        let (body, box_end, k) = box_at(&data[..end], at)?;
+        if &k == kind {
+            return Some((body, box_end));
+        }
+        at = box_end;
+    }
+    None
+}
+
+/// Locate the first sample of the first `traf` in a media fragment (any
+/// `styp` / `sidx` / `prft` / `emsg` before the `moof` is skipped). Works on
+/// a prefix of the fragment — only the `moof` has to be complete. `None`
+/// when it is cut off, or doesn't pin the sample down relative to itself
+/// (an explicit base_data_offset, no trun data_offset, no sample size).
+pub fn locate_first_sample(fragment: &[u8]) -> Option<FirstSample> {
+    let mut at = 0;
+    let (moof_start, moof_body, moof_end) = loop {
+        let (body, end, kind) = box_at(fragment, at)?;
+        if &kind == b"moof" {
+            break (at, body, end);
+        }
+        at = end;
+    };
+    let (traf, traf_end) = child_box(fragment, moof_body, moof_end, b"traf")?;
+    let (tfhd, tfhd_end) = child_box(fragment, traf, traf_end, b"tfhd")?;
+    let (trun, trun_end) = child_box(fragment, traf, traf_end, b"trun")?;
+
+    let mut d = fragment.get(tfhd..tfhd_end)?;
+    if d.len() < 8 {
+        return None;
+    }
+    let tf_flags = read_u32(&mut d) & 0x00FF_FFFF;
+    let _track_id = read_u32(&mut d);
+    if tf_flags & 0x01 != 0 {
+        return None;
+    }
+    // sample_description_index, default_sample_duration precede the size.
+    let skip = 4 * ((tf_flags & 0x02 != 0) as usize + (tf_flags & 0x08 != 0) as usize);
+    let default_size = if tf_flags & 0x10 != 0 {
+        let mut v = d.get(skip..skip + 4)?;
+        Some(read_u32(&mut v))
+    } else {
+        None
+    };
+
+    let mut d = fragment.get(trun..trun_end)?;
+    if d.len() < 8 {
+        return None;
+    }
+    let tr_flags = read_u32(&mut d) & 0x00FF_FFFF;
+    if read_u32(&mut d) == 0 || tr_flags & 0x01 == 0 || d.len() < 4 {
+        return None;
+    }
+    let data_offset = read_u32(&mut d) as i32;
+    // first_sample_flags, then the first sample's duration precede its size.
+    let skip = 4 * ((tr_flags & 0x04 != 0) as usize + (tr_flags & 0x100 != 0) as usize);
+    let size = if tr_flags & 0x200 != 0 {
+        let mut v = d.get(skip..skip + 4)?;
+        read_u32(&mut v)
+    } else {
+        default_size?
+    };
+
+    let start = usize::try_from(moof_start as i64 + i64::from(data_offset)).ok()?;
+    if start < moof_end {
+        return None;
+    }
+    Some(FirstSample {
+        moof: moof_start..moof_end,
+        data: start..start + size as usize,
+    })
+}
+
 /// Prefix a raw NALU body with the 4-byte Annex-B start code (`00 00 00 01`).
 pub fn append_hevc_header(mut nalu_data: Vec<u8>) -> Vec<u8> {
     let mut nalu = vec![0x00, 0x00, 0x00, 0x01];
@@ -201,3 +308,74 @@ pub fn aac_sampling_frequency_index_to_u32(index: u8) -> u32 {
         _ => 44100,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        b.extend_from_slice(kind);
+        b.extend_from_slice(body);
+        b
+    }
+
+    /// styp + moof (one traf: `tfhd`, `trun` with `sizes`) + mdat.
+    fn fragment(tfhd_flags: u32, tfhd_extra: &[u8], trun_flags: u32, sizes: &[u32]) -> Vec<u8> {
+        let mut tfhd = tfhd_flags.to_be_bytes().to_vec();
+        tfhd.extend_from_slice(&1u32.to_be_bytes());
+        tfhd.extend_from_slice(tfhd_extra);
+        let trun_for = |data_offset: u32| {
+            let mut trun = trun_flags.to_be_bytes().to_vec();
+            trun.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+            trun.extend_from_slice(&data_offset.to_be_bytes());
+            for s in sizes {
+                if trun_flags & 0x200 != 0 {
+                    trun.extend_from_slice(&s.to_be_bytes());
+                }
+                trun.extend_from_slice(&0u32.to_be_bytes()); // cto
+            }
+            trun
+        };
+        let moof_for = |data_offset: u32| {
+            let mut traf = mp4_box(b"tfhd", &tfhd);
+            traf.extend(mp4_box(b"trun", &trun_for(data_offset)));
+            let mut moof = mp4_box(b"mfhd", &[0, 0, 0, 0, 0, 0, 0, 1]);
+            moof.extend(mp4_box(b"traf", &traf));
+            mp4_box(b"moof", &moof)
+        };
+        let moof_len = moof_for(0).len() as u32;
+        let mut out = mp4_box(b"styp", b"msdhcmfs");
+        out.extend(moof_for(moof_len + 8));
+        let payload: Vec<u8> = (0..sizes.iter().sum::<u32>()).map(|i| i as u8).collect();
+        out.extend(mp4_box(b"mdat", &payload));
+        out
+    }
+
+    #[test]
+    fn locates_first_sample_from_trun_sizes() {
+        let frag = fragment(0x02_0000, &[], 0x01 | 0x200 | 0x800, &[300, 40]);
+        let loc = locate_first_sample(&frag).expect("located");
+        assert_eq!(loc.moof.start, 16);
+        assert_eq!(loc.data.start, loc.moof.end + 8);
+        assert_eq!(loc.data.len(), 300);
+        assert_eq!(frag[loc.data.start], 0);
+        // A prefix that cuts into the mdat still locates it.
+        assert_eq!(locate_first_sample(&frag[..loc.data.start + 10]), Some(loc));
+    }
+
+    #[test]
+    fn falls_back_to_the_tfhd_default_size() {
+        let frag = fragment(0x02_0000 | 0x10, &500u32.to_be_bytes(), 0x01, &[500, 500]);
+        let loc = locate_first_sample(&frag).expect("located");
+        assert_eq!(loc.data.len(), 500);
+    }
+
+    #[test]
+    fn rejects_cut_moof_and_absolute_base_offset() {
+        let frag = fragment(0x02_0000, &[], 0x01 | 0x200, &[300]);
+        assert_eq!(locate_first_sample(&frag[..40]), None);
+        let frag = fragment(0x01, &0u64.to_be_bytes(), 0x01 | 0x200, &[300]);
+        assert_eq!(locate_first_sample(&frag), None);
+    }
+}
@@ -0,0 +1,143 @@
+//! Opus codec configuration: the `dOps` box from the Opus in ISO Base Media
+//! File Format encapsulation (OpusSpecificBox).
+//!
+//! `dOps` carries the same fields as the Ogg `OpusHead` identification
+//! header, but big-endian and without the magic / version prefix. Both
+//! FFmpeg (extradata) and MediaCodec (`csd-0`) want the `OpusHead` form, so
+//! the record is re-serialised rather than passed through.
+
+/// Opus always decodes at 48 kHz; `input_sample_rate` in the header is
+/// informational only (the rate of the source before encoding).
+pub const OPUS_DECODE_RATE: u32 = 48_000;
+
+/// Pre-roll a decoder needs to converge after starting mid-stream, in
+/// 48 kHz samples (80 ms, per the Ogg Opus encapsulation spec).
+pub const OPUS_SEEK_PREROLL: u32 = 3840;
+
+/// Parsed `dOps` box.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpusConfig {
+    pub output_channel_count: u8,
+    /// Samples (at 48 kHz) to discard from the start of the decoded stream.
+    pub pre_skip: u16,
+    pub input_sample_rate: u32,
+    /// Q7.8 dB gain to apply on output.
+    pub output_gain: i16,
+    /// 0 = mono/stereo, 1 = Vorbis channel order (up to 8 channels),
+    /// 255 = unordered.
+    pub channel_mapping_family: u8,
+    pub stream_count: u8,
+    pub coupled_count: u8,
+    /// One entry per output channel; empty for mapping family 0.
+    pub channel_mapping: Vec<u8>,
+}
+
+/// Parse the body of a `dOps` box (everything after the 8-byte box header).
+pub fn parse_dops_record(dops: &[u8]) -> Option<OpusConfig> {
+    // Version(8) | OutputChannelCount(8) | PreSkip(16) |
+    // InputSampleRate(32) | OutputGain(16) | ChannelMappingFamily(8) |
+    // [StreamCount(8) CoupledCount(8) ChannelMapping(8 * OutputChannelCount)]
+    if dops.len() < 11 || dops[0] != 0 {
+        return None;
+    }
+    let channels = dops[1];
+    if channels == 0 {
+        return None;
+    }
+    let family = dops[10];
+    let (stream_count, coupled_count, channel_mapping) = if family == 0 {
+        if channels > 2 {
+            return None;
+        }
+        (1, channels - 1, Vec::new())
+    } else {
+        let table = dops.get(11..13 + channels as usize)?;
+        (table[0], table[1], table[2..].to_vec())
+    };
+    Some(OpusConfig {
+        output_channel_count: channels,
+        pre_skip: u16::from_be_bytes([dops[2], dops[3]]),
+        input_sample_rate: u32::from_be_bytes([dops[4], dops[5], dops[6], dops[7]]),
+        output_gain: i16::from_be_bytes([dops[8], dops[9]]),
+        channel_mapping_family: family,
+        stream_count,
+        coupled_count,
+        channel_mapping,
+    })
+}
+
+impl OpusConfig {
+    /// Serialise as an Ogg `OpusHead` identification header (RFC 7845 §5.1)
+    /// with the given pre-skip. The player trims pre-skip itself (see
+    /// `audio_play`), so it passes 0 here to keep the decoders from trimming
+    /// a second time.
+    pub fn opus_head(&self, pre_skip: u16) -> Vec<u8> {
+        let mut head = Vec::with_capacity(21 + self.channel_mapping.len());
+        head.extend_from_slice(b"OpusHead");
+        head.push(1);
+        head.push(self.output_channel_count);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&self.input_sample_rate.to_le_bytes());
+        head.extend_from_slice(&self.output_gain.to_le_bytes());
+        head.push(self.channel_mapping_family);
+        if self.channel_mapping_family != 0 {
+            head.push(self.stream_count);
+            head.push(self.coupled_count);
+            head.extend_from_slice(&self.channel_mapping);
+        }
+        head
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stereo_family0() {
+        let dops = [0, 2, 0x01, 0x38, 0, 0, 0xbb, 0x80, 0, 0, 0];
+        let cfg = parse_dops_record(&dops).expect("parse");
+        assert_eq!(cfg.output_channel_count, 2);
+        assert_eq!(cfg.pre_skip, 312);
+        assert_eq!(cfg.input_sample_rate, 48_000);
+        assert_eq!(cfg.channel_mapping_family, 0);
+        assert_eq!(cfg.stream_count, 1);
+        assert_eq!(cfg.coupled_count, 1);
+
+        let head = cfg.opus_head(0);
+        assert_eq!(&head[..8], b"OpusHead");
+        assert_eq!(head.len(), 19);
+        assert_eq!(head[9], 2);
+        assert_eq!(&head[10..12], &[0, 0]);
+        assert_eq!(&head[12..16], &48_000u32.to_le_bytes());
+        assert_eq!(&cfg.opus_head(312)[10..12], &312u16.to_le_bytes());
+    }
+
+    #[test]
+    fn surround_family1_keeps_mapping_table() {
+        let mut dops = vec![0, 6, 0x0f, 0x00, 0, 0, 0xbb, 0x80, 0xff, 0x00, 1];
+        dops.extend_from_slice(&[4, 2, 0, 4, 1, 2, 3, 5]);
+        let cfg = parse_dops_record(&dops).expect("parse");
+        assert_eq!(cfg.pre_skip, 3840);
+        assert_eq!(cfg.output_gain, -256);
+        assert_eq!(cfg.stream_count, 4);
+        assert_eq!(cfg.coupled_count, 2);
+        assert_eq!(cfg.channel_mapping, vec![0, 4, 1, 2, 3, 5]);
+
+        let head = cfg.opus_head(0);
+        assert_eq!(head.len(), 21 + 6);
+        assert_eq!(&head[16..18], &(-256i16).to_le_bytes());
+        assert_eq!(&head[19..], &[4, 2, 0, 4, 1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn rejects_truncated_and_bad_family0() {
+        assert!(parse_dops_record(&[0, 2, 0, 0]).is_none());
+        // Family 0 only allows mono / stereo.
+        assert!(parse_dops_record(&[0, 6, 0, 0, 0, 0, 0xbb, 0x80, 0, 0, 0]).is_none());
+        // Family 1 with a short mapping table.
+        assert!(parse_dops_record(&[0, 6, 0, 0, 0, 0, 0xbb, 0x80, 0, 0, 1, 4, 2, 0]).is_none());
+        // Unknown version.
+        assert!(parse_dops_record(&[1, 2, 0, 0, 0, 0, 0xbb, 0x80, 0, 0, 0]).is_none());
+    }
+}
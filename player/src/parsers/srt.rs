@@ -0,0 +1,250 @@
+//! SubRip (`.srt`) cue extraction, for sidecar subtitles added with
+//! `Player::add_external_subtitle`.
+//!
+//! SubRip has no spec; this follows what players accept in practice:
+//! blocks separated by blank lines, an optional numeric counter, a
+//! `HH:MM:SS,mmm --> HH:MM:SS,mmm` timing line (`.` tolerated for `,`,
+//! trailing `X1:` coordinates ignored) and the text. Markup is rewritten
+//! into WebVTT cue text and styled by `parsers::vtt`: `<b>`, `<i>`, `<u>`
+//! carry over, `<font color>` becomes a class the document's generated
+//! `::cue` sheet colours, and an ASS `{\anN}` override places the cue
+//! (top / middle / bottom, left / centre / right). Other `{\…}`
+//! overrides and `<font face|size>` are dropped.
+
+use super::vtt::{parse_cue_text, CueSettings, CueStyleSheet, VttCue};
+
+/// Parse a whole SubRip document into cues, in file order.
+pub fn parse_document(data: &[u8]) -> Vec<VttCue> {
+    let body = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    // Same leniency as raw WebVTT: stray invalid bytes and any line
+    // ending convention.
+    let text = String::from_utf8_lossy(body)
+        .replace("\r\n", "\n")
+        .replace('\r', "\n");
+
+    let mut sheet = CueStyleSheet::default();
+    let mut colors: Vec<String> = Vec::new();
+    let mut out = Vec::new();
+    for block in text.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| l.trim().is_empty());
+        let Some(mut timing) = lines.next() else {
+            continue;
+        };
+        if !timing.contains("-->") {
+            // The counter; SubRip numbers cues but nothing relies on it.
+            match lines.next() {
+                Some(next) if next.contains("-->") => timing = next,
+                _ => continue,
+            }
+        }
+        let Some((start, end)) = timing.split_once("-->") else {
+            continue;
+        };
+        let end = end.split_whitespace().next().unwrap_or("");
+        let (Some(start_ms), Some(end_ms)) = (parse_timestamp(start.trim()), parse_timestamp(end))
+        else {
+            continue;
+        };
+
+        let payload = lines.collect::<Vec<_>>().join("\n");
+        let (markup, an) = to_webvtt(&payload, &mut sheet, &mut colors);
+        let (text, spans) = parse_cue_text(&markup, &sheet, "");
+        if text.trim().is_empty() {
+            continue;
+        }
+        let settings = an.map(an_settings).unwrap_or_default();
+        let layout = CueSettings::parse(&settings).layout(&[]);
+        out.push(VttCue {
+            start_ms,
+            end_ms,
+            text,
+            settings,
+            spans,
+            layout,
+        });
+    }
+    out
+}
+
+/// Parse `HH:MM:SS,mmm` (or `.mmm`; hours may be omitted) to
+/// milliseconds. A short fraction is a decimal one: `,5` is 500 ms.
+fn parse_timestamp(s: &str) -> Option<i64> {
+    let (clock, frac) = s.split_once([',', '.']).unwrap_or((s, "0"));
+    let parts: Vec<i64> = clock
+        .split(':')
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let (h, m, sec) = match parts.as_slice() {
+        [h, m, s] => (*h, *m, *s),
+        [m, s] => (0, *m, *s),
+        _ => return None,
+    };
+    let digits = frac.get(..frac.len().min(3))?;
+    let ms = digits.parse::<i64>().ok()? * 10i64.pow(3 - digits.len() as u32);
+    Some((h * 3600 + m * 60 + sec) * 1000 + ms)
+}
+
+/// Rewrite a SubRip payload into WebVTT cue text. `<font color>` colours
+/// get a class each (`srt0`, `srt1` …, shared across the document) with a
+/// matching rule added to `sheet`. Returns the markup and the last
+/// `{\anN}` position override, if any.
+fn to_webvtt(
+    payload: &str,
+    sheet: &mut CueStyleSheet,
+    colors: &mut Vec<String>,
+) -> (String, Option<u8>) {
+    let mut out = String::with_capacity(payload.len());
+    let mut an = None;
+    let mut rest = payload;
+    while let Some(i) = rest.find(['<', '{']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if let Some(tail) = rest.strip_prefix("{\\") {
+            let Some(close) = tail.find('}') else {
+                break;
+            };
+            let overrides = &tail[..close];
+            if let Some(n) = overrides
+                .split('\\')
+                .find_map(|o| o.strip_prefix("an")?.trim().parse::<u8>().ok())
+                .filter(|n| (1..=9).contains(n))
+            {
+                an = Some(n);
+            }
+            rest = &tail[close + 1..];
+            continue;
+        }
+        let tag = rest
+            .strip_prefix('<')
+            .and_then(|t| t.find('>').map(|end| &t[..end]))
+            .map(|t| t.trim().to_ascii_lowercase());
+        let replacement = match tag.as_deref() {
+            Some(t @ ("b" | "i" | "u" | "/b" | "/i" | "/u")) => Some(format!("<{t}>")),
+            Some("/font") => Some("</c>".to_string()),
+            Some(t) if t.starts_with("font") => {
+                let class = font_color(t).map(|color| {
+                    let n = colors.iter().position(|c| *c == color).unwrap_or_else(|| {
+                        sheet.parse_block(&format!(
+                            "::cue(.srt{}) {{ color: {} }}",
+                            colors.len(),
+                            color
+                        ));
+                        colors.push(color);
+                        colors.len() - 1
+                    });
+                    format!(".srt{n}")
+                });
+                Some(format!("<c{}>", class.unwrap_or_default()))
+            }
+            _ => None,
+        };
+        match replacement {
+            Some(markup) => {
+                out.push_str(&markup);
+                rest = &rest[rest.find('>').map_or(rest.len(), |e| e + 1)..];
+            }
+            None => {
+                // Not markup ("<3", a stray "{"): keep it as text.
+                out.push_str(if rest.starts_with('<') { "&lt;" } else { "{" });
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    (out, an)
+}
+
+/// The `color` attribute of a lowercased `font …` tag, as CSS. Bare hex
+/// (`color="ff0000"`, common in the wild) gets its `#`.
+fn font_color(tag: &str) -> Option<String> {
+    let value = tag
+        .split_once("color")?
+        .1
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    let value = match value.strip_prefix(['"', '\'']) {
+        Some(quoted) => quoted.split(['"', '\'']).next()?,
+        None => value.split_whitespace().next()?,
+    };
+    let bare_hex = matches!(value.len(), 6 | 8) && value.chars().all(|c| c.is_ascii_hexdigit());
+    Some(if bare_hex {
+        format!("#{value}")
+    } else {
+        value.to_string()
+    })
+}
+
+/// WebVTT settings for an ASS numpad position: 7–9 top, 4–6 middle, 1–3
+/// bottom; left / centre / right column.
+fn an_settings(an: u8) -> String {
+    let line = match an {
+        7..=9 => "line:0",
+        4..=6 => "line:50%,center",
+        _ => "",
+    };
+    let align = match an % 3 {
+        1 => "align:left",
+        0 => "align:right",
+        _ => "",
+    };
+    [line, align]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::vtt::TextAlign;
+
+    #[test]
+    fn parses_counter_timing_and_text() {
+        let doc = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,500\r\nHello\r\nworld\r\n\r\n\
+                   2\r\n00:01:00.5 --> 00:01:02,000 X1:10 X2:20 Y1:5 Y2:9\r\nSecond\r\n";
+        let cues = parse_document(doc.as_bytes());
+        assert_eq!(cues.len(), 2);
+        assert_eq!((cues[0].start_ms, cues[0].end_ms), (1000, 2500));
+        assert_eq!(cues[0].text, "Hello\nworld");
+        assert_eq!((cues[1].start_ms, cues[1].end_ms), (60_500, 62_000));
+        assert!(cues[1].layout.is_none());
+    }
+
+    #[test]
+    fn styles_tags_and_font_colours() {
+        let doc =
+            "1\n00:00:00,000 --> 00:00:01,000\n<B>bold</B> <font color=\"#ff0000\">red</font> \
+                   <font color=ff0000>again</font> <3\n";
+        let cues = parse_document(doc.as_bytes());
+        assert_eq!(cues[0].text, "bold red again <3");
+        let spans = &cues[0].spans;
+        assert!(spans[0].style.bold);
+        let red = spans.iter().find(|s| s.text == "red").unwrap();
+        assert_eq!(red.style.color, Some([255, 0, 0, 255]));
+        let again = spans.iter().find(|s| s.text == "again").unwrap();
+        assert_eq!(again.style.color, Some([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn ass_position_override_places_the_cue() {
+        let doc = "1\n00:00:00,000 --> 00:00:01,000\n{\\an7}Top left\n\n\
+                   2\n00:00:01,000 --> 00:00:02,000\n{\\i1}plain{\\i0}\n";
+        let cues = parse_document(doc.as_bytes());
+        assert_eq!(cues[0].text, "Top left");
+        let layout = cues[0].layout.expect("positioned");
+        assert_eq!(layout.text_align, TextAlign::Left);
+        assert_eq!(layout.line, Some(0));
+        assert_eq!(cues[1].text, "plain");
+        assert!(cues[1].layout.is_none());
+    }
+
+    #[test]
+    fn skips_malformed_blocks() {
+        let doc = "garbage\n\n1\nnot a timing\ntext\n\n2\n00:00:03,000 --> 00:00:04,000\nok\n";
+        let cues = parse_document(doc.as_bytes());
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].text, "ok");
+    }
+}
@@ -0,0 +1,1081 @@
+//! TTML / IMSC1 cue extraction from DASH text segments.
+//!
+//! DASH ships TTML either as `stpp` ISO BMFF fragments — every sample is
+//! one complete TTML document (ISO/IEC 14496-30) — or as a sidecar
+//! `.ttml` / `.dfxp` file. The entry point [`parse_segment`] sniffs both
+//! forms and turns each `<p>` into [`VttCue`]s carrying styled spans and,
+//! when the paragraph sits in a region, a [`CueLayout`].
+//!
+//! Scope is the IMSC1 Text Profile subset that real packagers emit:
+//!   - timing on `body` / `div` / `p` / `span` (`begin`, `end`, `dur`) in
+//!     clock (`00:00:01.500`, `00:00:01:12`) and offset (`1.5s`, `1500ms`,
+//!     `36f`, `15000000t`) form, honouring `ttp:tickRate`,
+//!     `ttp:frameRate`, `ttp:frameRateMultiplier` and `ttp:subFrameRate`;
+//!   - referential (`style="a b"`), chained, region and inline `tts:`
+//!     styles with inheritance: `color`, `backgroundColor`, `fontStyle`,
+//!     `fontWeight`, `textDecoration`, `textAlign`, `displayAlign`;
+//!   - regions (`tts:origin` / `tts:extent` in `%`, `px` against the root
+//!     `tts:extent`, or `c` cells against `ttp:cellResolution`);
+//!   - `<br/>`, nested spans, `xml:space`.
+//!
+//! Dropped silently: the IMSC Image Profile (`smpte:backgroundImage`),
+//! `<set>` animation, ruby, vertical writing modes and `tts:fontSize` /
+//! `fontFamily` — the overlay's `SubtitleStyle` sizes text.
+//!
+//! The document is read with `quick_xml` into a small element tree.
+//! Prefixes are resolved against the in-scope `xmlns` declarations, so
+//! `tt:p` and `p` are the same element while `tts:`, `ttp:` and `xml:`
+//! attributes stay apart; elements from other namespaces (`ttm:`,
+//! `smpte:` metadata) are dropped with their content.
+
+use super::decode_entities;
+use super::vtt::{named_color, CueLayout, CueSpan, DisplayAlign, SpanStyle, TextAlign, VttCue};
+use crate::SubtitleStyle;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+
+/// Best-effort parse of one DASH text segment (an `stpp` fragment) or of
+/// a whole sidecar document. Document times are on the track's media
+/// timeline, so cues are reported as-is; in a fragment each sample's
+/// window (`composition_timestamp` + `duration`) additionally clips the
+/// sample's cues — packagers repeat a cue that straddles a sample
+/// boundary in both documents. `segment_pts_ms` only stands in when the
+/// fragment carries no usable timescale.
+pub fn parse_segment(data: &[u8], segment_pts_ms: i64) -> Vec<VttCue> {
+    let body = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    if body.trim_ascii_start().starts_with(b"<") {
+        return parse_document(&String::from_utf8_lossy(body));
+    }
+    parse_iso_bmff_ttml(data, segment_pts_ms)
+}
+
+/// Parse one TTML document into cues, sorted by start time. Open-ended
+/// content (no `end` / `dur` anywhere up the tree) gets `end_ms =
+/// i64::MAX`.
+pub fn parse_document(xml: &str) -> Vec<VttCue> {
+    let Some(tt) = parse_xml(xml) else {
+        log::debug!("[ttml] no <tt> root — document dropped");
+        return Vec::new();
+    };
+    let timing = Timing::from_root(&tt);
+    let lengths = Lengths::from_root(&tt);
+    let head = tt.child("head");
+    let sheet = head.map(|h| style_sheet(h, &lengths)).unwrap_or_default();
+    let regions = head
+        .map(|h| regions(h, &sheet, &lengths))
+        .unwrap_or_default();
+
+    let mut out = Vec::new();
+    if let Some(body) = tt.child("body") {
+        let ctx = Context {
+            timing: &timing,
+            lengths: &lengths,
+            sheet: &sheet,
+            regions: &regions,
+        };
+        let scope = Scope {
+            begin: 0,
+            end: i64::MAX,
+            chain: Vec::new(),
+            region: None,
+            preserve: tt.attr(Ns::Xml, "space") == Some("preserve"),
+        };
+        ctx.walk_block(body, &scope, &mut out);
+    }
+    out.sort_by_key(|c| c.start_ms);
+    out
+}
+
+// ---------------------------------------------------------------------------
+// ISO BMFF (`stpp`) framing
+// ---------------------------------------------------------------------------
+
+fn parse_iso_bmff_ttml(data: &[u8], segment_pts_ms: i64) -> Vec<VttCue> {
+    // Same sample walk as the ISO BMFF WebVTT path in `parsers::vtt`.
+    let mp4 = match re_mp4::Mp4::read_bytes(data) {
+        Ok(m) => m,
+        Err(e) => {
+            log::debug!("[ttml] mp4 parse failed: {} — segment dropped", e);
+            return Vec::new();
+        }
+    };
+    let track = match mp4.tracks().values().next() {
+        Some(t) => t,
+        None => return Vec::new(),
+    };
+    let timescale = track.samples.first().map(|s| s.timescale).unwrap_or(1000);
+
+    let mut out = Vec::new();
+    for sample in &track.samples {
+        let off = sample.offset as usize;
+        let size = sample.size as usize;
+        if off + size > data.len() {
+            continue;
+        }
+        let (start_ms, end_ms) = if timescale > 0 {
+            let start = sample.composition_timestamp * 1000 / timescale as i64;
+            (start, start + (sample.duration as i64) * 1000 / timescale as i64)
+        } else {
+            (segment_pts_ms, i64::MAX)
+        };
+        // Image-profile samples append PNG subsamples after the XML; the
+        // reader ignores anything past `</tt>`.
+        let doc = String::from_utf8_lossy(&data[off..off + size]);
+        for mut cue in parse_document(&doc) {
+            cue.start_ms = cue.start_ms.max(start_ms);
+            cue.end_ms = cue.end_ms.min(end_ms);
+            if cue.end_ms > cue.start_ms {
+                out.push(cue);
+            }
+        }
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Content walk
+// ---------------------------------------------------------------------------
+
+struct Context<'a> {
+    timing: &'a Timing,
+    lengths: &'a Lengths,
+    sheet: &'a HashMap<String, Style>,
+    regions: &'a HashMap<String, Style>,
+}
+
+/// What an element inherits from its ancestors.
+#[derive(Clone)]
+struct Scope<'a> {
+    begin: i64,
+    end: i64,
+    /// Specified styles of the ancestors, outermost first.
+    chain: Vec<Style>,
+    region: Option<&'a str>,
+    preserve: bool,
+}
+
+/// A piece of paragraph content with its own active interval: a text
+/// node, or `"\n"` for `<br/>`.
+struct Fragment {
+    begin: i64,
+    end: i64,
+    text: String,
+    style: SpanStyle,
+    preserve: bool,
+}
+
+impl<'a> Context<'a> {
+    /// `body` / `div`: narrow the scope and recurse until a `<p>`.
+    fn walk_block(&self, el: &'a Element, parent: &Scope<'a>, out: &mut Vec<VttCue>) {
+        let scope = self.enter(el, parent);
+        if scope.begin >= scope.end {
+            return;
+        }
+        for child in el.elements() {
+            match child.name.as_str() {
+                "div" => self.walk_block(child, &scope, out),
+                "p" => self.paragraph(child, &scope, out),
+                _ => {}
+            }
+        }
+    }
+
+    fn enter(&self, el: &'a Element, parent: &Scope<'a>) -> Scope<'a> {
+        let (begin, end) = self.interval(el, parent.begin, parent.end);
+        let mut chain = parent.chain.clone();
+        chain.push(specified_style(el, self.sheet, self.lengths));
+        Scope {
+            begin,
+            end,
+            chain,
+            region: el.attr(Ns::None, "region").or(parent.region),
+            preserve: match el.attr(Ns::Xml, "space") {
+                Some("preserve") => true,
+                Some("default") => false,
+                _ => parent.preserve,
+            },
+        }
+    }
+
+    /// Active interval of `el` inside its parent's (`par` semantics —
+    /// `seq` containers are rare in subtitles and treated the same).
+    fn interval(&self, el: &Element, parent_begin: i64, parent_end: i64) -> (i64, i64) {
+        let offset = |name| el.attr(Ns::None, name).and_then(|v| self.timing.parse(v));
+        let begin = parent_begin.saturating_add(offset("begin").unwrap_or(0));
+        let mut end = parent_end;
+        if let Some(e) = offset("end") {
+            end = end.min(parent_begin.saturating_add(e));
+        }
+        if let Some(d) = offset("dur") {
+            end = end.min(begin.saturating_add(d));
+        }
+        (begin, end)
+    }
+
+    /// One `<p>` → one cue per stretch of time over which its visible
+    /// content doesn't change (timed spans split the paragraph).
+    fn paragraph(&self, p: &'a Element, parent: &Scope<'a>, out: &mut Vec<VttCue>) {
+        let scope = self.enter(p, parent);
+        if scope.begin >= scope.end {
+            return;
+        }
+        let region = scope.region.and_then(|id| self.regions.get(id));
+        // Content inherits the region's styles, except the fill: a region
+        // background paints the block, not every run.
+        let mut base = region.cloned().unwrap_or_default();
+        base.background = None;
+        let mut computed = base;
+        for s in &scope.chain {
+            computed.merge(s);
+        }
+
+        let mut fragments = Vec::new();
+        self.inline(p, &scope, &computed, &mut fragments);
+
+        let mut cuts: Vec<i64> = vec![scope.begin, scope.end];
+        for f in &fragments {
+            cuts.push(f.begin);
+            cuts.push(f.end);
+        }
+        cuts.retain(|&t| t >= scope.begin && t <= scope.end);
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        let layout = region.map(|r| r.layout(&computed));
+        for w in cuts.windows(2) {
+            let (start_ms, end_ms) = (w[0], w[1]);
+            let live = fragments
+                .iter()
+                .filter(|f| f.begin <= start_ms && f.end >= end_ms);
+            let spans = collapse_whitespace(live);
+            if spans.iter().all(|s| s.text.trim().is_empty()) {
+                continue;
+            }
+            // Extend the previous cue instead of cutting an identical one.
+            if let Some(prev) = out.last_mut() {
+                if prev.end_ms == start_ms && prev.spans == spans && prev.layout == layout {
+                    prev.end_ms = end_ms;
+                    continue;
+                }
+            }
+            out.push(VttCue {
+                start_ms,
+                end_ms,
+                text: spans.iter().map(|s| s.text.as_str()).collect(),
+                settings: String::new(),
+                spans,
+                layout,
+            });
+        }
+    }
+
+    /// Flatten the inline content of `el` (a `p` or `span`) into timed,
+    /// styled fragments.
+    fn inline(&self, el: &'a Element, scope: &Scope<'a>, style: &Style, out: &mut Vec<Fragment>) {
+        for node in &el.children {
+            match node {
+                Node::Text(t) => out.push(Fragment {
+                    begin: scope.begin,
+                    end: scope.end,
+                    text: t.clone(),
+                    style: style.span_style(),
+                    preserve: scope.preserve,
+                }),
+                Node::Element(child) if child.name == "br" => out.push(Fragment {
+                    begin: scope.begin,
+                    end: scope.end,
+                    text: "\n".into(),
+                    style: style.span_style(),
+                    preserve: true,
+                }),
+                Node::Element(child) if child.name == "span" => {
+                    let inner = self.enter(child, scope);
+                    if inner.begin >= inner.end {
+                        continue;
+                    }
+                    let mut computed = style.clone();
+                    if let Some(s) = inner.chain.last() {
+                        computed.merge(s);
+                    }
+                    self.inline(child, &inner, &computed, out);
+                }
+                // <set>, <metadata>, image-profile <div> … not rendered.
+                Node::Element(_) => {}
+            }
+        }
+    }
+}
+
+/// XML whitespace handling (`xml:space="default"`): runs of whitespace
+/// collapse to one space, and spaces at the edges of a line vanish.
+/// Preserved text keeps its spaces and turns newlines into line breaks.
+/// Adjacent runs with the same style merge.
+fn collapse_whitespace<'f>(fragments: impl Iterator<Item = &'f Fragment>) -> Vec<CueSpan> {
+    let mut chars: Vec<(char, SpanStyle, bool)> = Vec::new();
+    for f in fragments {
+        for c in f.text.chars() {
+            if c == '\r' {
+                continue;
+            }
+            if f.preserve {
+                if c == '\n' {
+                    trim_soft_spaces(&mut chars);
+                }
+                chars.push((c, f.style, true));
+            } else if c.is_whitespace() {
+                if !chars.last().is_none_or(|&(p, _, _)| p == '\n' || p == ' ') {
+                    chars.push((' ', f.style, false));
+                }
+            } else {
+                chars.push((c, f.style, false));
+            }
+        }
+    }
+    trim_soft_spaces(&mut chars);
+
+    let mut spans: Vec<CueSpan> = Vec::new();
+    for (c, style, _) in chars {
+        match spans.last_mut() {
+            Some(last) if last.style == style => last.text.push(c),
+            _ => spans.push(CueSpan {
+                text: c.to_string(),
+                style,
+                ruby: None,
+            }),
+        }
+    }
+    spans
+}
+
+/// Drop collapsible (non-preserved) spaces at the end of the current line.
+fn trim_soft_spaces(chars: &mut Vec<(char, SpanStyle, bool)>) {
+    while chars.last().is_some_and(|&(c, _, preserved)| c == ' ' && !preserved) {
+        chars.pop();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Styling
+// ---------------------------------------------------------------------------
+
+/// Specified `tts:` properties of a style, region or content element;
+/// `None` = not specified here (inherit).
+#[derive(Clone, Debug, Default)]
+struct Style {
+    color: Option<[u8; 4]>,
+    background: Option<[u8; 4]>,
+    bold: Option<bool>,
+    italic: Option<bool>,
+    underline: Option<bool>,
+    text_align: Option<TextAlign>,
+    display_align: Option<DisplayAlign>,
+    origin: Option<[f32; 2]>,
+    extent: Option<[f32; 2]>,
+}
+
+impl Style {
+    /// Overlay `other`'s specified properties on top of ours.
+    fn merge(&mut self, other: &Style) {
+        macro_rules! take {
+            ($($f:ident),*) => { $( if other.$f.is_some() { self.$f = other.$f; } )* };
+        }
+        take!(color, background, bold, italic, underline, text_align, display_align, origin, extent);
+    }
+
+    fn apply_attrs(&mut self, el: &Element, lengths: &Lengths) {
+        for (ns, name, value) in &el.attrs {
+            if *ns != Ns::Styling {
+                continue;
+            }
+            let v = value.trim();
+            match name.as_str() {
+                "color" => self.color = color(v).or(self.color),
+                "backgroundColor" => self.background = color(v).or(self.background),
+                "fontWeight" => self.bold = Some(v == "bold"),
+                "fontStyle" => self.italic = Some(v == "italic" || v == "oblique"),
+                "textDecoration" => {
+                    if v.split_whitespace().any(|t| t == "underline") {
+                        self.underline = Some(true);
+                    } else if v.split_whitespace().any(|t| t == "noUnderline" || t == "none") {
+                        self.underline = Some(false);
+                    }
+                }
+                "textAlign" => {
+                    self.text_align = match v {
+                        "left" | "start" | "justify" => Some(TextAlign::Left),
+                        "center" => Some(TextAlign::Center),
+                        "right" | "end" => Some(TextAlign::Right),
+                        _ => self.text_align,
+                    }
+                }
+                "displayAlign" => {
+                    self.display_align = match v {
+                        "before" => Some(DisplayAlign::Before),
+                        "center" => Some(DisplayAlign::Center),
+                        "after" => Some(DisplayAlign::After),
+                        _ => self.display_align,
+                    }
+                }
+                "origin" => self.origin = lengths.pair(v).or(self.origin),
+                "extent" => self.extent = lengths.pair(v).or(self.extent),
+                _ => {}
+            }
+        }
+    }
+
+    fn span_style(&self) -> SpanStyle {
+        SpanStyle {
+            color: self.color,
+            background: self.background,
+            bold: self.bold.unwrap_or(false),
+            italic: self.italic.unwrap_or(false),
+            underline: self.underline.unwrap_or(false),
+        }
+    }
+
+    /// Layout of a region style; `content` supplies the paragraph's
+    /// (inherited) `textAlign`. TTML's initial values: origin 0 0, extent
+    /// of the whole root container, `displayAlign="before"`,
+    /// `textAlign="start"`.
+    fn layout(&self, content: &Style) -> CueLayout {
+        let [x, y] = self.origin.unwrap_or([0.0, 0.0]);
+        let [width, height] = self.extent.unwrap_or([1.0 - x, 1.0 - y]);
+        CueLayout {
+            x,
+            y,
+            width,
+            height,
+            text_align: content.text_align.unwrap_or(TextAlign::Left),
+            display_align: self.display_align.unwrap_or(DisplayAlign::Before),
+            background: self.background.filter(|c| c[3] > 0),
+            line: None,
+            max_lines: None,
+        }
+    }
+}
+
+/// Referential styles plus inline `tts:` attributes of `el`, in that
+/// precedence order (inline wins).
+fn specified_style(el: &Element, sheet: &HashMap<String, Style>, lengths: &Lengths) -> Style {
+    let mut style = Style::default();
+    for id in el.attr(Ns::None, "style").unwrap_or("").split_whitespace() {
+        if let Some(s) = sheet.get(id) {
+            style.merge(s);
+        }
+    }
+    style.apply_attrs(el, lengths);
+    style
+}
+
+/// `<head><styling><style xml:id=…>` → resolved styles, following `style`
+/// references between them.
+fn style_sheet(head: &Element, lengths: &Lengths) -> HashMap<String, Style> {
+    let defs: HashMap<&str, &Element> = head
+        .elements()
+        .filter(|e| e.name == "styling")
+        .flat_map(|s| s.elements())
+        .filter(|e| e.name == "style")
+        .filter_map(|e| Some((e.attr(Ns::Xml, "id")?, e)))
+        .collect();
+
+    fn resolve(id: &str, defs: &HashMap<&str, &Element>, lengths: &Lengths, depth: u8) -> Style {
+        let mut style = Style::default();
+        let Some(el) = defs.get(id) else {
+            return style;
+        };
+        // Reference cycles are a document error; a depth cap keeps them
+        // from recursing forever.
+        if depth < 8 {
+            for r in el.attr(Ns::None, "style").unwrap_or("").split_whitespace() {
+                style.merge(&resolve(r, defs, lengths, depth + 1));
+            }
+        }
+        style.apply_attrs(el, lengths);
+        style
+    }
+
+    defs.keys()
+        .map(|id| (id.to_string(), resolve(id, &defs, lengths, 0)))
+        .collect()
+}
+
+/// `<head><layout><region xml:id=…>` → the region's computed style
+/// (referential, nested `<style>` children, then inline attributes).
+fn regions(
+    head: &Element,
+    sheet: &HashMap<String, Style>,
+    lengths: &Lengths,
+) -> HashMap<String, Style> {
+    head.elements()
+        .filter(|e| e.name == "layout")
+        .flat_map(|l| l.elements())
+        .filter(|e| e.name == "region")
+        .filter_map(|r| {
+            let mut style = Style::default();
+            for id in r.attr(Ns::None, "style").unwrap_or("").split_whitespace() {
+                if let Some(s) = sheet.get(id) {
+                    style.merge(s);
+                }
+            }
+            for nested in r.elements().filter(|e| e.name == "style") {
+                style.merge(&specified_style(nested, sheet, lengths));
+            }
+            style.apply_attrs(r, lengths);
+            Some((r.attr(Ns::Xml, "id")?.to_string(), style))
+        })
+        .collect()
+}
+
+/// TTML `<color>`: `#rrggbb`, `#rrggbbaa`, `rgb()` and `rgba()` go
+/// through [`SubtitleStyle::parse_color`]; names take their exact CSS
+/// values, not the settings-UI shades that function maps them to.
+fn color(s: &str) -> Option<[u8; 4]> {
+    let s = s.trim();
+    if s.starts_with('#') || s.starts_with("rgb") {
+        SubtitleStyle::parse_color(s)
+    } else {
+        named_color(s)
+    }
+}
+
+/// Root-container geometry for turning `tts:origin` / `tts:extent` into
+/// fractions of the frame.
+struct Lengths {
+    /// Root `tts:extent` in pixels, when given in `px`.
+    root_px: Option<[f32; 2]>,
+    /// `ttp:cellResolution` (default 32 × 15).
+    cells: [f32; 2],
+}
+
+impl Lengths {
+    fn from_root(tt: &Element) -> Self {
+        let px = |v: &str| v.strip_suffix("px")?.trim().parse::<f32>().ok();
+        let root_px = tt.attr(Ns::Styling, "extent").and_then(|e| {
+            let mut it = e.split_whitespace();
+            Some([px(it.next()?)?, px(it.next()?)?])
+        });
+        let cells = tt
+            .attr(Ns::Parameter, "cellResolution")
+            .and_then(|c| {
+                let mut it = c.split_whitespace().map(|n| n.parse::<f32>().ok());
+                Some([it.next()??, it.next()??])
+            })
+            .filter(|c| c[0] > 0.0 && c[1] > 0.0)
+            .unwrap_or([32.0, 15.0]);
+        Self { root_px, cells }
+    }
+
+    /// `"10% 80%"` / `"192px 864px"` / `"2c 13c"` → fractions. `px`
+    /// without a root extent is resolved against 1920 × 1080, which is
+    /// what such documents are authored for in practice.
+    fn pair(&self, v: &str) -> Option<[f32; 2]> {
+        let mut it = v.split_whitespace();
+        let (a, b) = (it.next()?, it.next()?);
+        let axis = |s: &str, i: usize| -> Option<f32> {
+            let f = if let Some(n) = s.strip_suffix('%') {
+                n.parse::<f32>().ok()? / 100.0
+            } else if let Some(n) = s.strip_suffix("px") {
+                let root = self.root_px.unwrap_or([1920.0, 1080.0])[i];
+                n.parse::<f32>().ok()? / root
+            } else if let Some(n) = s.strip_suffix('c') {
+                n.parse::<f32>().ok()? / self.cells[i]
+            } else {
+                return None;
+            };
+            f.is_finite().then(|| f.clamp(0.0, 1.0))
+        };
+        Some([axis(a, 0)?, axis(b, 1)?])
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Timing
+// ---------------------------------------------------------------------------
+
+/// The document's `ttp:` timing parameters.
+struct Timing {
+    /// Effective frame rate (`frameRate` × `frameRateMultiplier`).
+    frame_rate: f64,
+    sub_frame_rate: f64,
+    tick_rate: f64,
+}
+
+impl Timing {
+    fn from_root(tt: &Element) -> Self {
+        let num = |name| {
+            tt.attr(Ns::Parameter, name)
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+        };
+        let declared_frame_rate = num("frameRate");
+        let multiplier = tt
+            .attr(Ns::Parameter, "frameRateMultiplier")
+            .and_then(|m| {
+                let mut it = m.split_whitespace().map(|n| n.parse::<f64>().ok());
+                Some(it.next()?? / it.next()??)
+            })
+            .filter(|m| m.is_finite() && *m > 0.0)
+            .unwrap_or(1.0);
+        let frame_rate = declared_frame_rate.unwrap_or(30.0) * multiplier;
+        let sub_frame_rate = num("subFrameRate").unwrap_or(1.0);
+        // TTML §7.2.10: without an explicit tickRate, ticks are sub-frames
+        // when a frame rate is declared, seconds otherwise.
+        let tick_rate = num("tickRate").unwrap_or(match declared_frame_rate {
+            Some(_) => frame_rate * sub_frame_rate,
+            None => 1.0,
+        });
+        Self {
+            frame_rate,
+            sub_frame_rate,
+            tick_rate,
+        }
+    }
+
+    /// TTML `<timeExpression>` → milliseconds.
+    fn parse(&self, s: &str) -> Option<i64> {
+        let s = s.trim();
+        let secs = if s.contains(':') {
+            let parts: Vec<&str> = s.split(':').collect();
+            let hms = |h: &str, m: &str, sec: &str| -> Option<f64> {
+                Some(h.parse::<f64>().ok()? * 3600.0 + m.parse::<f64>().ok()? * 60.0 + sec.parse::<f64>().ok()?)
+            };
+            match parts[..] {
+                [h, m, sec] => hms(h, m, sec)?,
+                [h, m, sec, frames] => {
+                    let (f, sub) = frames.split_once('.').unwrap_or((frames, "0"));
+                    let frames = f.parse::<f64>().ok()? + sub.parse::<f64>().ok()? / self.sub_frame_rate;
+                    hms(h, m, sec)? + frames / self.frame_rate
+                }
+                _ => return None,
+            }
+        } else {
+            let split = s.find(|c: char| c.is_ascii_alphabetic())?;
+            let value = s[..split].parse::<f64>().ok()?;
+            match &s[split..] {
+                "h" => value * 3600.0,
+                "m" => value * 60.0,
+                "s" => value,
+                "ms" => value / 1000.0,
+                "f" => value / self.frame_rate,
+                "t" => value / self.tick_rate,
+                _ => return None,
+            }
+        };
+        secs.is_finite().then(|| (secs * 1000.0).round() as i64)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// XML tree
+// ---------------------------------------------------------------------------
+
+/// Attribute namespaces told apart. TTML keeps its styling (`tts:`),
+/// parameter (`ttp:`) and core (`xml:`) attributes in separate
+/// namespaces; `begin`, `style`, `region` … are unqualified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Ns {
+    None,
+    Styling,
+    Parameter,
+    Xml,
+    /// Any other namespace (`ttm:`, `smpte:`, `ebutts:` …) — never read.
+    Other,
+}
+
+#[derive(Debug, Default)]
+struct Element {
+    /// Local name; only TTML elements make it into the tree.
+    name: String,
+    /// Namespace, local name and entity-decoded value of each attribute.
+    attrs: Vec<(Ns, String, String)>,
+    children: Vec<Node>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn attr(&self, ns: Ns, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(n, local, _)| *n == ns && local == name)
+            .map(|(_, _, v)| v.as_str())
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|n| match n {
+            Node::Element(e) => Some(e),
+            Node::Text(_) => None,
+        })
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.name == name)
+    }
+
+    fn push_text(&mut self, text: &str) {
+        match self.children.last_mut() {
+            Some(Node::Text(t)) => t.push_str(text),
+            _ => self.children.push(Node::Text(text.to_string())),
+        }
+    }
+}
+
+/// `prefix:local` → (`Some(prefix)`, `local`).
+fn split_name(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, local)) => (Some(prefix), local),
+        None => (None, name),
+    }
+}
+
+/// The in-scope `xmlns` declarations while reading.
+#[derive(Default)]
+struct Namespaces {
+    /// (prefix, URI), innermost last; `""` is the default namespace.
+    bindings: Vec<(String, String)>,
+    /// `bindings.len()` when each open element was entered.
+    marks: Vec<usize>,
+}
+
+impl Namespaces {
+    /// Enter `tag`: bring its declarations into scope and build the
+    /// element, or `None` when it isn't a TTML element. Every call is
+    /// paired with a [`leave`](Self::leave).
+    fn enter(&mut self, tag: &BytesStart) -> Option<Element> {
+        self.marks.push(self.bindings.len());
+        let mut attrs = Vec::new();
+        for attr in tag.attributes().with_checks(false).flatten() {
+            let name = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = decode_entities(&String::from_utf8_lossy(&attr.value), &[]);
+            if name == "xmlns" {
+                self.bindings.push((String::new(), value));
+            } else if let Some(prefix) = name.strip_prefix("xmlns:") {
+                self.bindings.push((prefix.to_string(), value));
+            } else {
+                attrs.push((name, value));
+            }
+        }
+        let name = String::from_utf8_lossy(tag.name().as_ref()).into_owned();
+        let (prefix, local) = split_name(&name);
+        if !self.is_ttml(prefix) {
+            return None;
+        }
+        let attrs = attrs
+            .into_iter()
+            .map(|(name, value)| {
+                let (prefix, local) = split_name(&name);
+                (self.attr_ns(prefix), local.to_string(), value)
+            })
+            .collect();
+        Some(Element {
+            name: local.to_string(),
+            attrs,
+            children: Vec::new(),
+        })
+    }
+
+    fn leave(&mut self) {
+        if let Some(mark) = self.marks.pop() {
+            self.bindings.truncate(mark);
+        }
+    }
+
+    fn uri(&self, prefix: &str) -> Option<&str> {
+        self.bindings
+            .iter()
+            .rev()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, uri)| uri.as_str())
+    }
+
+    /// Elements in the TTML namespace (or the DFXP drafts' `ttaf1`) and,
+    /// in documents that never declare it, unprefixed and `tt:` ones.
+    fn is_ttml(&self, prefix: Option<&str>) -> bool {
+        match self.uri(prefix.unwrap_or("")) {
+            Some(uri) => {
+                !uri.contains('#')
+                    && (uri.starts_with("http://www.w3.org/ns/ttml") || uri.contains("/ttaf1"))
+            }
+            None => matches!(prefix, None | Some("tt")),
+        }
+    }
+
+    /// Undeclared `tts:` / `ttp:` prefixes keep their conventional
+    /// meaning — sidecar files often omit the declarations.
+    fn attr_ns(&self, prefix: Option<&str>) -> Ns {
+        let Some(prefix) = prefix else {
+            return Ns::None;
+        };
+        if prefix == "xml" {
+            return Ns::Xml;
+        }
+        match self.uri(prefix) {
+            Some(uri) if uri.ends_with("#styling") => Ns::Styling,
+            Some(uri) if uri.ends_with("#parameter") => Ns::Parameter,
+            Some(_) => Ns::Other,
+            None => match prefix {
+                "tts" => Ns::Styling,
+                "ttp" => Ns::Parameter,
+                _ => Ns::Other,
+            },
+        }
+    }
+}
+
+/// Deepest element nesting kept in the tree. Elements below it are
+/// flattened into their ancestor at this depth — text kept, their own
+/// timing and styling dropped — which bounds the recursion of the content
+/// walk. Real documents nest well under ten.
+const MAX_DEPTH: usize = 32;
+
+/// How an open element was taken into the tree.
+enum Open {
+    Kept,
+    Flattened,
+    /// Not TTML, or inside such an element: skipped with its content.
+    Foreign,
+}
+
+/// Parse `src` and return its `<tt>` root. Tolerant rather than
+/// validating: mismatched close tags just close the innermost element, a
+/// malformed or truncated tail closes whatever is open, and anything after
+/// the root is ignored.
+fn parse_xml(src: &str) -> Option<Element> {
+    let mut reader = Reader::from_str(src);
+    let config = reader.config_mut();
+    config.check_end_names = false;
+    config.allow_dangling_amp = true;
+
+    let mut namespaces = Namespaces::default();
+    // stack[0] is the document node.
+    let mut stack: Vec<Element> = vec![Element::default()];
+    let mut open: Vec<Open> = Vec::new();
+    let mut foreign = 0usize;
+    loop {
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => event,
+            Err(e) => {
+                log::debug!("[ttml] XML error: {} — keeping what parsed", e);
+                break;
+            }
+        };
+        let top = stack.len() - 1;
+        match event {
+            Event::Start(tag) => {
+                let kind = match namespaces.enter(&tag) {
+                    _ if foreign > 0 => Open::Foreign,
+                    None => Open::Foreign,
+                    Some(_) if stack.len() > MAX_DEPTH => Open::Flattened,
+                    Some(el) => {
+                        stack.push(el);
+                        Open::Kept
+                    }
+                };
+                if matches!(kind, Open::Foreign) {
+                    foreign += 1;
+                }
+                open.push(kind);
+            }
+            Event::Empty(tag) => {
+                let el = namespaces.enter(&tag);
+                namespaces.leave();
+                if let (0, Some(el)) = (foreign, el) {
+                    stack[top].children.push(Node::Element(el));
+                }
+            }
+            Event::End(_) => {
+                let Some(kind) = open.pop() else {
+                    continue;
+                };
+                namespaces.leave();
+                match kind {
+                    Open::Kept => {
+                        let el = stack.pop()?;
+                        let done = stack.len() == 1 && el.name == "tt";
+                        stack[top - 1].children.push(Node::Element(el));
+                        if done {
+                            break;
+                        }
+                    }
+                    Open::Flattened => {}
+                    Open::Foreign => foreign -= 1,
+                }
+            }
+            Event::Text(text) if foreign == 0 => {
+                stack[top].push_text(&decode_entities(&String::from_utf8_lossy(&text), &[]));
+            }
+            Event::GeneralRef(name) if foreign == 0 => {
+                let reference = format!("&{};", String::from_utf8_lossy(&name));
+                stack[top].push_text(&decode_entities(&reference, &[]));
+            }
+            Event::CData(text) if foreign == 0 => {
+                stack[top].push_text(&String::from_utf8_lossy(&text));
+            }
+            _ => {}
+        }
+    }
+    // Truncated document: close whatever is still open.
+    while stack.len() > 1 {
+        let el = stack.pop()?;
+        stack.last_mut()?.children.push(Node::Element(el));
+    }
+    stack.pop()?.children.into_iter().find_map(|n| match n {
+        Node::Element(e) if e.name == "tt" => Some(e),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling"
+    xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:tickRate="10000000" xml:lang="en">
+  <head>
+    <styling>
+      <style xml:id="base" tts:color="white" tts:textAlign="center"/>
+      <style xml:id="yellow" style="base" tts:color="#ffff00"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 15%"
+              tts:displayAlign="after" tts:backgroundColor="#00000080"/>
+    </layout>
+  </head>
+  <body style="base" region="bottom">
+    <div>
+      <p begin="10000000t" end="25000000t">Hello
+         <span tts:fontStyle="italic">there</span><br/>
+         <span style="yellow" tts:fontWeight="bold">Tom &amp; Jerry</span></p>
+      <p begin="00:00:03.000" dur="1s" tts:textAlign="left">Second</p>
+    </div>
+  </body>
+</tt>"##;
+
+    #[test]
+    fn parses_styles_regions_and_line_breaks() {
+        let cues = parse_document(DOC);
+        assert_eq!(cues.len(), 2);
+        let c = &cues[0];
+        assert_eq!((c.start_ms, c.end_ms), (1000, 2500));
+        assert_eq!(c.text, "Hello there\nTom & Jerry");
+        let texts: Vec<&str> = c.spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["Hello ", "there", "\n", "Tom & Jerry"]);
+        assert!(c.spans[1].style.italic);
+        assert_eq!(c.spans[0].style.color, Some([255, 255, 255, 255]));
+        assert_eq!(c.spans[3].style.color, Some([255, 255, 0, 255]));
+        assert!(c.spans[3].style.bold && !c.spans[3].style.italic);
+        // The region fill belongs to the layout, not to the runs.
+        assert_eq!(c.spans[0].style.background, None);
+
+        let layout = c.layout.expect("region layout");
+        assert!((layout.x - 0.1).abs() < 1e-6 && (layout.y - 0.8).abs() < 1e-6);
+        assert!((layout.width - 0.8).abs() < 1e-6 && (layout.height - 0.15).abs() < 1e-6);
+        assert_eq!(layout.text_align, TextAlign::Center);
+        assert_eq!(layout.display_align, DisplayAlign::After);
+        assert_eq!(layout.background, Some([0, 0, 0, 128]));
+
+        assert_eq!((cues[1].start_ms, cues[1].end_ms), (3000, 4000));
+        assert_eq!(cues[1].layout.unwrap().text_align, TextAlign::Left);
+    }
+
+    #[test]
+    fn parses_time_expressions() {
+        let tt = Element {
+            name: "tt".into(),
+            attrs: vec![
+                (Ns::Parameter, "frameRate".into(), "30".into()),
+                (
+                    Ns::Parameter,
+                    "frameRateMultiplier".into(),
+                    "1000 1001".into(),
+                ),
+            ],
+            children: Vec::new(),
+        };
+        let t = Timing::from_root(&tt);
+        assert_eq!(t.parse("00:01:02.5"), Some(62_500));
+        assert_eq!(t.parse("00:00:01:00"), Some(1_000));
+        assert_eq!(t.parse("00:00:00:30"), Some(1_001));
+        assert_eq!(t.parse("1.5s"), Some(1_500));
+        assert_eq!(t.parse("250ms"), Some(250));
+        assert_eq!(t.parse("2m"), Some(120_000));
+        assert_eq!(t.parse("30f"), Some(1_001));
+        // No tickRate + a declared frameRate → ticks are frames.
+        assert_eq!(t.parse("30t"), Some(1_001));
+        assert_eq!(t.parse("soon"), None);
+
+        let plain = Timing::from_root(&Element::default());
+        assert_eq!(plain.parse("3t"), Some(3_000));
+        assert_eq!(plain.parse("30f"), Some(1_000));
+    }
+
+    #[test]
+    fn timed_spans_split_the_paragraph() {
+        let doc = r#"<tt:tt xmlns:tt="http://www.w3.org/ns/ttml"><tt:body><tt:div begin="10s">
+            <tt:p begin="0s" end="4s">one <tt:span begin="2s">two</tt:span></tt:p>
+        </tt:div></tt:body></tt:tt>"#;
+        let cues = parse_document(doc);
+        let got: Vec<(i64, i64, &str)> = cues
+            .iter()
+            .map(|c| (c.start_ms, c.end_ms, c.text.as_str()))
+            .collect();
+        assert_eq!(got, [(10_000, 12_000, "one"), (12_000, 14_000, "one two")]);
+        assert!(cues[0].layout.is_none());
+    }
+
+    #[test]
+    fn preserves_space_and_decodes_references() {
+        let doc = "<tt xml:space=\"preserve\"><body><p begin=\"0s\" end=\"1s\">a  b\nc&#233;&#x4E2D;&nbsp;</p></body></tt>";
+        let cues = parse_document(doc);
+        assert_eq!(cues[0].text, "a  b\nc\u{e9}\u{4e2d}&nbsp;");
+    }
+
+    #[test]
+    fn keeps_namespaces_apart() {
+        // Only `tts:` attributes style, only `xml:id` names a style, and a
+        // foreign element goes with its content — whatever the prefixes.
+        let doc = r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:s="http://www.w3.org/ns/ttml#styling"
+            xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:ebutts="urn:ebu:tt:style">
+          <head><styling><style id="plain" s:color="red"/><style xml:id="x" s:color="lime"/></styling></head>
+          <body><p begin="0s" end="1s" style="plain" ebutts:color="blue">a<span style="x" ttp:color="red">b</span>
+            <smpte:image xmlns:smpte="http://www.smpte-ra.org/schemas/2052-1/2010/smpte-tt">junk</smpte:image></p></body>
+        </tt>"#;
+        let cues = parse_document(doc);
+        assert_eq!(cues[0].text, "ab");
+        assert_eq!(cues[0].spans[0].style.color, None);
+        assert_eq!(cues[0].spans[1].style.color, Some([0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn deep_nesting_is_flattened() {
+        let depth = 100_000;
+        let doc = format!(
+            "<tt><body><p begin=\"0s\" end=\"1s\">{}deep{}</p></body></tt>",
+            "<span>".repeat(depth),
+            "</span>".repeat(depth)
+        );
+        assert_eq!(parse_document(&doc)[0].text, "deep");
+    }
+
+    #[test]
+    fn sniffs_sidecar_documents_and_resolves_pixel_regions() {
+        let doc = br#"<tt tts:extent="1280px 720px"><head><layout>
+            <region xml:id="r" tts:origin="128px 576px" tts:extent="1024px 72px"/>
+        </layout></head><body><p region="r" begin="1s" end="2s">x</p></body></tt>"#;
+        let cues = parse_segment(doc, 0);
+        let layout = cues[0].layout.unwrap();
+        assert!((layout.x - 0.1).abs() < 1e-6 && (layout.y - 0.8).abs() < 1e-6);
+        assert!((layout.width - 0.8).abs() < 1e-6 && (layout.height - 0.1).abs() < 1e-6);
+        assert_eq!(layout.display_align, DisplayAlign::Before);
+        assert_eq!(color("rgba(1,2,3,4)"), Some([1, 2, 3, 4]));
+        assert_eq!(color("#11223344"), Some([0x11, 0x22, 0x33, 0x44]));
+        assert_eq!(color("navy"), Some([0, 0, 128, 255]));
+    }
+}
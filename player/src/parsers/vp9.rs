@@ -0,0 +1,99 @@
+//! VP9 codec configuration: the `vpcC` box from the VP Codec ISO Media
+//! File Format Binding (VPCodecConfigurationRecord).
+//!
+//! VP9 has no parameter sets — every frame header is self-contained — so
+//! `vpcC` is the only out-of-band source for bit depth and colour. Its
+//! primaries / transfer / matrix fields are H.273 code points, expressed
+//! as the same [`SpsColorInfo`] HEVC and AV1 produce so Profile 2 PQ/HLG
+//! takes the existing HDR tonemap path.
+
+use super::hevc::SpsColorInfo;
+
+/// Parsed `vpcC` box (version 1 record).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vp9Config {
+    /// 0 = 8-bit 4:2:0, 1 = 8-bit 4:2:2/4:4:4, 2 = 10/12-bit 4:2:0,
+    /// 3 = 10/12-bit 4:2:2/4:4:4.
+    pub profile: u8,
+    pub level: u8,
+    pub bit_depth: u8,
+    /// 0/1 = 4:2:0 (vertical / colocated), 2 = 4:2:2, 3 = 4:4:4.
+    pub chroma_subsampling: u8,
+    pub color: SpsColorInfo,
+}
+
+/// Parse the body of a `vpcC` box (everything after the 8-byte box header,
+/// i.e. starting at the FullBox version/flags).
+///
+/// Only version 1 is accepted: version 0 (the pre-standard WebM draft)
+/// packs a VP9-specific colour space instead of H.273 code points and is
+/// not produced by current packagers.
+pub fn parse_vpcc_record(vpcc: &[u8]) -> Option<Vp9Config> {
+    // version(8) flags(24) | profile(8) | level(8) |
+    // bitDepth(4) chromaSubsampling(3) videoFullRangeFlag(1) |
+    // colourPrimaries(8) | transferCharacteristics(8) |
+    // matrixCoefficients(8) | codecInitializationDataSize(16) | data
+    if vpcc.len() < 12 || vpcc[0] != 1 {
+        return None;
+    }
+    let bit_depth = vpcc[6] >> 4;
+    if !matches!(bit_depth, 8 | 10 | 12) {
+        return None;
+    }
+    Some(Vp9Config {
+        profile: vpcc[4],
+        level: vpcc[5],
+        bit_depth,
+        chroma_subsampling: (vpcc[6] >> 1) & 0x07,
+        color: SpsColorInfo {
+            bit_depth_luma: bit_depth,
+            full_range: vpcc[6] & 0x01 != 0,
+            colour_primaries: vpcc[7],
+            transfer_characteristics: vpcc[8],
+            matrix_coeffs: vpcc[9],
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vpcc(profile: u8, depth: u8, full_range: bool, prim: u8, trc: u8, mtx: u8) -> Vec<u8> {
+        let mut v = vec![1, 0, 0, 0, profile, 41];
+        v.push((depth << 4) | (1 << 1) | full_range as u8);
+        v.extend_from_slice(&[prim, trc, mtx, 0, 0]);
+        v
+    }
+
+    #[test]
+    fn profile2_pq_bt2020() {
+        let cfg = parse_vpcc_record(&vpcc(2, 10, false, 9, 16, 9)).expect("parse");
+        assert_eq!(cfg.profile, 2);
+        assert_eq!(cfg.level, 41);
+        assert_eq!(cfg.bit_depth, 10);
+        assert_eq!(cfg.chroma_subsampling, 1);
+        assert_eq!(cfg.color.bit_depth_luma, 10);
+        assert_eq!(cfg.color.colour_primaries, 9);
+        assert_eq!(cfg.color.transfer_characteristics, 16);
+        assert_eq!(cfg.color.matrix_coeffs, 9);
+        assert!(!cfg.color.full_range);
+    }
+
+    #[test]
+    fn profile0_sdr_full_range() {
+        let cfg = parse_vpcc_record(&vpcc(0, 8, true, 1, 1, 1)).expect("parse");
+        assert_eq!(cfg.bit_depth, 8);
+        assert_eq!(cfg.color.transfer_characteristics, 1);
+        assert!(cfg.color.full_range);
+    }
+
+    #[test]
+    fn rejects_version0_and_bad_depth() {
+        let mut v0 = vpcc(0, 8, false, 1, 1, 1);
+        v0[0] = 0;
+        assert!(parse_vpcc_record(&v0).is_none());
+        assert!(parse_vpcc_record(&vpcc(2, 9, false, 9, 16, 9)).is_none());
+        assert!(parse_vpcc_record(&[1, 0, 0, 0, 2]).is_none());
+    }
+}
@@ -5,14 +5,21 @@
 //! raw WebVTT text inside `mdat`. The entry point [`parse_segment`] sniffs
 //! both forms.
 //!
-//! Phase 1 scope: plain-text cues only. We strip any inline tags
-//! (`<b>`, `<i>`, `<c.classname>` …) so the renderer just gets readable
-//! UTF-8. Cue settings (`line:`, `position:`, `align:`) are parsed for
-//! future use but currently ignored by the overlay.
+//! Cue text markup (`<b>`, `<i>`, `<u>`, `<c.classname>`, `<v Speaker>`,
+//! `<lang>`, `<ruby>`/`<rt>`) is parsed into styled [`CueSpan`]s: the
+//! default colour classes (`.yellow`, `.bg_blue` …) and the `::cue` rules
+//! of raw-file `STYLE` blocks ([`CueStyleSheet`]) resolve to each run's
+//! [`SpanStyle`]. Cue settings (`line:`, `position:`, `size:`, `align:`,
+//! `region:`) and raw-file `REGION` blocks are parsed into
+//! [`CueSettings`] / [`VttRegion`] and resolved to the cue's
+//! [`CueLayout`]; `vertical:` is parsed but the cue is still laid out
+//! horizontally.
 
+use super::decode_entities;
 use std::time::Duration;
-use unicode_normalization::UnicodeNormalization;
 
+/// A timed subtitle cue as handed to the overlay — WebVTT's, and the
+/// shape other text formats (TTML, see `parsers::ttml`) convert into.
 #[derive(Clone, Debug)]
 pub struct VttCue {
     /// Cue start time in milliseconds, relative to the same timeline as
@@ -20,11 +27,99 @@ pub struct VttCue {
     /// relative).
     pub start_ms: i64,
     pub end_ms: i64,
-    /// UTF-8 payload, inline tags stripped, line breaks preserved as `\n`.
+    /// UTF-8 payload, inline tags stripped (ruby annotations dropped),
+    /// line breaks preserved as `\n`.
     pub text: String,
     /// Raw cue settings string ("line:90% position:50% align:center").
     /// Empty when the cue had none.
     pub settings: String,
+    /// `text` split into styled runs (same characters, `\n` included).
+    /// Empty = all of `text` in the overlay's default style.
+    pub spans: Vec<CueSpan>,
+    /// Where the cue sits on screen. `None` = the default bottom-centre
+    /// placement above the safe area.
+    pub layout: Option<CueLayout>,
+}
+
+/// A run of cue text sharing one style.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CueSpan {
+    pub text: String,
+    pub style: SpanStyle,
+    /// Ruby annotation (`<rt>` text) drawn small and centred above this
+    /// run, which is then the whole ruby base.
+    pub ruby: Option<String>,
+}
+
+/// Per-run styling. `None` colours fall back to the overlay's
+/// `SubtitleStyle` (the user's preference wins only where the source is
+/// silent).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SpanStyle {
+    /// Glyph fill, RGBA.
+    pub color: Option<[u8; 4]>,
+    /// Box painted behind the run's glyphs, RGBA.
+    pub background: Option<[u8; 4]>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+/// A cue's box on screen, as fractions (0..=1) of the video frame, and how
+/// its text sits inside the box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CueLayout {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub text_align: TextAlign,
+    pub display_align: DisplayAlign,
+    /// Fill behind the whole text block, RGBA (a TTML region background).
+    pub background: Option<[u8; 4]>,
+    /// Snap-to-lines placement (WebVTT `line:` numbers): when set, `y` /
+    /// `height` are ignored and the block sits on text line `n` of the
+    /// title-safe area — from the top for n >= 0, from the bottom for
+    /// n < 0 (-1 = the last line). A block that collides with another
+    /// cue steps line by line away from its edge.
+    pub line: Option<i32>,
+    /// Show at most this many lines, dropping the oldest (top) ones — a
+    /// WebVTT region's `lines:`.
+    pub max_lines: Option<u32>,
+}
+
+/// Horizontal alignment of lines inside a [`CueLayout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical placement of the text block inside a [`CueLayout`]: top,
+/// middle or bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayAlign {
+    Before,
+    Center,
+    After,
+}
+
+impl CueLayout {
+    /// The box an unpositioned cue gets when it shares the screen with
+    /// positioned ones: the full width, text bottom-aligned at 90% height
+    /// (the overlay's 10% title-safe fallback).
+    pub const BOTTOM: Self = Self {
+        x: 0.05,
+        y: 0.0,
+        width: 0.9,
+        height: 0.9,
+        text_align: TextAlign::Center,
+        display_align: DisplayAlign::After,
+        background: None,
+        line: None,
+        max_lines: None,
+    };
 }
 
 impl VttCue {
@@ -80,19 +175,26 @@ fn parse_raw_webvtt(data: &[u8]) -> Vec<VttCue> {
     let text = text.replace("\r\n", "\n").replace('\r', "\n");
 
     let mut out = Vec::new();
+    let mut regions = Vec::new();
+    let mut sheet = CueStyleSheet::default();
     for block in text.split("\n\n") {
         let block = block.trim_matches(|c: char| c == '\n' || c == ' ' || c == '\t');
         if block.is_empty() {
             continue;
         }
-        if block.starts_with("WEBVTT")
-            || block.starts_with("STYLE")
-            || block.starts_with("REGION")
-            || block.starts_with("NOTE")
-        {
+        if let Some(definition) = block.strip_prefix("REGION") {
+            regions.extend(VttRegion::parse(definition));
+            continue;
+        }
+        if let Some(css) = block.strip_prefix("STYLE") {
+            sheet.parse_block(css);
             continue;
         }
-        if let Some(cue) = parse_cue_block(block) {
+        if block.starts_with("WEBVTT") || block.starts_with("NOTE") {
+            continue;
+        }
+        if let Some(mut cue) = parse_cue_block(block, &sheet) {
+            cue.layout = CueSettings::parse(&cue.settings).layout(&regions);
             out.push(cue);
         }
     }
@@ -106,14 +208,16 @@ fn parse_raw_webvtt(data: &[u8]) -> Vec<VttCue> {
 /// First line of text
 /// Second line
 /// ```
-fn parse_cue_block(block: &str) -> Option<VttCue> {
+fn parse_cue_block(block: &str, sheet: &CueStyleSheet) -> Option<VttCue> {
     let mut lines = block.lines();
     let mut first = lines.next()?.trim();
+    let mut id = "";
     // Optional identifier line — if it doesn't contain "-->" the next
     // line is the timing line.
     let timing = if first.contains("-->") {
         first
     } else {
+        id = first;
         first = lines.next()?.trim();
         if !first.contains("-->") {
             return None;
@@ -137,26 +241,18 @@ fn parse_cue_block(block: &str) -> Option<VttCue> {
     let start_ms = parse_timestamp(timings.trim())?;
     let end_ms = parse_timestamp(end_part)?;
 
-    let mut text = String::new();
-    for line in lines {
-        if !text.is_empty() {
-            text.push('\n');
-        }
-        text.push_str(&strip_inline_tags(line));
-    }
-
+    let payload = lines.collect::<Vec<_>>().join("\n");
+    let (text, spans) = parse_cue_text(&payload, sheet, id);
     if text.is_empty() {
         return None;
     }
-    // fontdue has no shaping engine — collapse decomposed sequences
-    // (NFD) to precomposed code points (NFC) so diacritics render as a
-    // single glyph the font actually carries.
-    let text: String = text.nfc().collect();
     Some(VttCue {
         start_ms,
         end_ms,
         text,
         settings,
+        spans,
+        layout: None,
     })
 }
 
@@ -173,22 +269,393 @@ fn parse_timestamp(s: &str) -> Option<i64> {
     Some(((h * 3600 + m * 60 + sec) * 1000) + ms)
 }
 
-/// Strip simple WebVTT inline tags. Phase 1 doesn't render styling, so
-/// `<b>bold</b>` becomes `bold`, `<c.red>foo</c>` becomes `foo`, etc.
-fn strip_inline_tags(line: &str) -> String {
-    let mut out = String::with_capacity(line.len());
-    let mut in_tag = false;
-    for c in line.chars() {
-        match c {
-            '<' => in_tag = true,
-            '>' if in_tag => in_tag = false,
-            _ if !in_tag => out.push(c),
-            _ => {}
+// ---------------------------------------------------------------------------
+// Cue text markup and `::cue` styles (WebVTT §4.2.2, §6.4, §8.2)
+// ---------------------------------------------------------------------------
+
+/// The `::cue` rules of a file's `STYLE` blocks, in source order (a later
+/// rule overrides an earlier one, as in CSS for equal specificity).
+#[derive(Clone, Debug, Default)]
+pub struct CueStyleSheet {
+    rules: Vec<(CueSelector, CueCss)>,
+}
+
+/// What a `::cue` rule applies to.
+#[derive(Clone, Debug, PartialEq)]
+enum CueSelector {
+    /// `::cue` — all text of every cue.
+    All,
+    /// `::cue(#id)` — all text of the cue with that identifier.
+    Id(String),
+    /// `::cue(tag.class…)` — elements with that tag (empty = any) carrying
+    /// every listed class.
+    Element { tag: String, classes: Vec<String> },
+    /// `::cue(v[voice="Name"])`.
+    Voice(String),
+}
+
+/// The `::cue` properties that map onto a [`SpanStyle`]. `None` = the
+/// rule doesn't set it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct CueCss {
+    color: Option<[u8; 4]>,
+    background: Option<[u8; 4]>,
+    bold: Option<bool>,
+    italic: Option<bool>,
+    underline: Option<bool>,
+}
+
+impl CueCss {
+    fn apply(&self, style: &mut SpanStyle) {
+        if let Some(c) = self.color {
+            style.color = Some(c);
+        }
+        if let Some(c) = self.background {
+            style.background = Some(c);
         }
+        style.bold = self.bold.unwrap_or(style.bold);
+        style.italic = self.italic.unwrap_or(style.italic);
+        style.underline = self.underline.unwrap_or(style.underline);
+    }
+}
+
+impl CueStyleSheet {
+    /// Append the `::cue` rules of one `STYLE` block's body. Rules with
+    /// other selectors (or `::cue` pseudo-classes like `:past`) are
+    /// skipped, as are properties a subtitle overlay can't honour.
+    pub fn parse_block(&mut self, css: &str) {
+        let mut rest = strip_css_comments(css);
+        while let Some(open) = rest.find('{') {
+            let Some(close) = rest[open..].find('}').map(|c| open + c) else {
+                break;
+            };
+            let css = parse_declarations(&rest[open + 1..close]);
+            for selector in rest[..open].split(',') {
+                if let Some(selector) = parse_selector(selector.trim()) {
+                    self.rules.push((selector, css));
+                }
+            }
+            rest = rest.split_off(close + 1);
+        }
+    }
+}
+
+fn strip_css_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .split_once("*/")
+            .map_or("", |(_, after)| after);
     }
+    out.push_str(rest);
     out
 }
 
+fn parse_selector(s: &str) -> Option<CueSelector> {
+    let inner = s.strip_prefix("::cue")?.trim();
+    if inner.is_empty() {
+        return Some(CueSelector::All);
+    }
+    let inner = inner.strip_prefix('(')?.strip_suffix(')')?.trim();
+    if let Some(id) = inner.strip_prefix('#') {
+        // CSS escapes in ids (`#\31 23`) are rare enough to ignore.
+        return Some(CueSelector::Id(id.to_string()));
+    }
+    if let Some(voice) = inner.strip_prefix("v[voice=") {
+        let voice = voice
+            .strip_suffix(']')?
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'');
+        return Some(CueSelector::Voice(voice.to_string()));
+    }
+    let mut parts = inner.split('.');
+    let tag = parts.next()?;
+    let classes: Vec<String> = parts.map(String::from).collect();
+    if !tag.chars().all(|c| c.is_ascii_alphanumeric()) || classes.iter().any(String::is_empty) {
+        return None;
+    }
+    Some(CueSelector::Element {
+        tag: tag.to_string(),
+        classes,
+    })
+}
+
+fn parse_declarations(body: &str) -> CueCss {
+    let mut css = CueCss::default();
+    for declaration in body.split(';') {
+        let Some((name, value)) = declaration.split_once(':') else {
+            continue;
+        };
+        let value = value
+            .trim()
+            .trim_end_matches("!important")
+            .trim()
+            .to_ascii_lowercase();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "color" => css.color = css_color(&value).or(css.color),
+            // The `background` shorthand: take whichever token is a colour.
+            "background" | "background-color" => {
+                css.background = css_color(&value)
+                    .or_else(|| value.split_whitespace().find_map(css_color))
+                    .or(css.background);
+            }
+            "font-weight" => {
+                css.bold = match value.as_str() {
+                    "bold" | "bolder" => Some(true),
+                    "normal" | "lighter" => Some(false),
+                    v => v.parse::<u16>().ok().map(|w| w >= 600).or(css.bold),
+                }
+            }
+            "font-style" => {
+                css.italic = match value.as_str() {
+                    "normal" => Some(false),
+                    v if v == "italic" || v.starts_with("oblique") => Some(true),
+                    _ => css.italic,
+                }
+            }
+            "text-decoration" | "text-decoration-line" => {
+                if value.contains("underline") {
+                    css.underline = Some(true);
+                } else if value == "none" {
+                    css.underline = Some(false);
+                }
+            }
+            _ => {}
+        }
+    }
+    css
+}
+
+/// A CSS colour value: `#rgb` / `#rrggbb` / `#rrggbbaa`, `rgb()` /
+/// `rgba()` (alpha 0..=1) or a colour name.
+fn css_color(value: &str) -> Option<[u8; 4]> {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return crate::SubtitleStyle::parse_color(value);
+    }
+    let args = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+        .and_then(|a| a.strip_suffix(')'));
+    if let Some(args) = args {
+        let channel = |s: &str| {
+            s.trim()
+                .parse::<f32>()
+                .ok()
+                .map(|v| v.clamp(0.0, 255.0).round() as u8)
+        };
+        let parts: Vec<&str> = args.split(',').collect();
+        return match parts[..] {
+            [r, g, b] => Some([channel(r)?, channel(g)?, channel(b)?, 255]),
+            [r, g, b, a] => {
+                let a: f32 = a.trim().parse().ok()?;
+                Some([
+                    channel(r)?,
+                    channel(g)?,
+                    channel(b)?,
+                    (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+                ])
+            }
+            _ => None,
+        };
+    }
+    named_color(value)
+}
+
+/// CSS colour keywords — the eight WebVTT default classes plus the few
+/// others subtitle stylesheets use, and the rest of TTML's named colours.
+pub(super) fn named_color(name: &str) -> Option<[u8; 4]> {
+    Some(match name {
+        "white" => [255, 255, 255, 255],
+        "lime" => [0, 255, 0, 255],
+        "cyan" | "aqua" => [0, 255, 255, 255],
+        "red" => [255, 0, 0, 255],
+        "yellow" => [255, 255, 0, 255],
+        "magenta" | "fuchsia" => [255, 0, 255, 255],
+        "blue" => [0, 0, 255, 255],
+        "black" => [0, 0, 0, 255],
+        "green" => [0, 128, 0, 255],
+        "gray" | "grey" => [128, 128, 128, 255],
+        "silver" => [192, 192, 192, 255],
+        "orange" => [255, 165, 0, 255],
+        "maroon" => [128, 0, 0, 255],
+        "purple" => [128, 0, 128, 255],
+        "olive" => [128, 128, 0, 255],
+        "navy" => [0, 0, 128, 255],
+        "teal" => [0, 128, 128, 255],
+        "transparent" => [0, 0, 0, 0],
+        _ => return None,
+    })
+}
+
+/// The WebVTT default classes: `.white` … `.black` set the text colour,
+/// `.bg_white` … `.bg_black` the background.
+fn default_class(class: &str) -> Option<CueCss> {
+    const CLASSES: [&str; 8] = [
+        "white", "lime", "cyan", "red", "yellow", "magenta", "blue", "black",
+    ];
+    match class.strip_prefix("bg_") {
+        Some(bg) if CLASSES.contains(&bg) => Some(CueCss {
+            background: named_color(bg),
+            ..CueCss::default()
+        }),
+        None if CLASSES.contains(&class) => Some(CueCss {
+            color: named_color(class),
+            ..CueCss::default()
+        }),
+        _ => None,
+    }
+}
+
+/// Parse a cue payload's markup into styled runs. Returns the plain text
+/// (the runs' concatenation, ruby annotations excluded) and the runs.
+/// Unknown tags and timestamp tags (`<00:00:01.000>`) are dropped with
+/// their text kept, so a malformed cue still reads.
+pub(super) fn parse_cue_text(
+    payload: &str,
+    sheet: &CueStyleSheet,
+    id: &str,
+) -> (String, Vec<CueSpan>) {
+    let mut root = SpanStyle::default();
+    for (selector, css) in &sheet.rules {
+        match selector {
+            CueSelector::All => css.apply(&mut root),
+            CueSelector::Id(want) if want == id => css.apply(&mut root),
+            _ => {}
+        }
+    }
+
+    // Open elements, innermost last, with the style their text gets.
+    let mut open: Vec<(&str, SpanStyle)> = Vec::new();
+    let mut spans: Vec<CueSpan> = Vec::new();
+    // Index of the first span of the current ruby base, and the `<rt>`
+    // text being collected.
+    let mut ruby_base: Option<usize> = None;
+    let mut annotation: Option<String> = None;
+    let mut rest = payload;
+    while !rest.is_empty() {
+        let Some(tag) = rest.strip_prefix('<') else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            let text = decode_entities(&rest[..end], &VTT_ENTITIES);
+            rest = &rest[end..];
+            if let Some(annotation) = annotation.as_mut() {
+                annotation.push_str(&text);
+                continue;
+            }
+            let style = open.last().map_or(root, |&(_, style)| style);
+            // Never merge into the span before a ruby base starts, nor
+            // into a finished ruby run.
+            let merge = ruby_base != Some(spans.len())
+                && spans
+                    .last()
+                    .is_some_and(|last| last.style == style && last.ruby.is_none());
+            match spans.last_mut() {
+                Some(last) if merge => last.text.push_str(&text),
+                _ => spans.push(CueSpan {
+                    text,
+                    style,
+                    ruby: None,
+                }),
+            }
+            continue;
+        };
+        let end = tag.find('>').unwrap_or(tag.len());
+        rest = tag.get(end + 1..).unwrap_or("");
+        let tag = &tag[..end];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            // Closing an element also closes anything left open inside it.
+            if let Some(pos) = open.iter().rposition(|&(n, _)| n == name) {
+                open.truncate(pos);
+            }
+            match name {
+                "rt" => {
+                    if let Some(text) = annotation.take() {
+                        attach_ruby(&mut spans, ruby_base, &text);
+                    }
+                    // Further base text in the same `<ruby>` starts a new pair.
+                    ruby_base = Some(spans.len());
+                }
+                "ruby" => {
+                    if let Some(text) = annotation.take() {
+                        attach_ruby(&mut spans, ruby_base, &text);
+                    }
+                    ruby_base = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        let (head, voice) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let mut parts = head.split('.');
+        let name = parts.next().unwrap_or("");
+        if !matches!(name, "b" | "i" | "u" | "c" | "v" | "lang" | "ruby" | "rt") {
+            continue;
+        }
+        let classes: Vec<&str> = parts.filter(|c| !c.is_empty()).collect();
+        let mut style = open.last().map_or(root, |&(_, style)| style);
+        match name {
+            "b" => style.bold = true,
+            "i" => style.italic = true,
+            "u" => style.underline = true,
+            _ => {}
+        }
+        for css in classes.iter().filter_map(|c| default_class(c)) {
+            css.apply(&mut style);
+        }
+        for (selector, css) in &sheet.rules {
+            let hit = match selector {
+                CueSelector::Element { tag, classes: want } => {
+                    (tag.is_empty() || tag == name)
+                        && want.iter().all(|w| classes.contains(&w.as_str()))
+                }
+                CueSelector::Voice(want) => name == "v" && want == voice.trim(),
+                _ => false,
+            };
+            if hit {
+                css.apply(&mut style);
+            }
+        }
+        match name {
+            "ruby" => ruby_base = Some(spans.len()),
+            "rt" => annotation = Some(String::new()),
+            _ => {}
+        }
+        open.push((name, style));
+    }
+    if let Some(text) = annotation.take() {
+        attach_ruby(&mut spans, ruby_base, &text);
+    }
+
+    // Text stays as authored (NFD included): the overlay's shaper composes
+    // or positions combining marks per font.
+    spans.retain(|s| !s.text.is_empty());
+    let text = spans.iter().map(|s| s.text.as_str()).collect();
+    (text, spans)
+}
+
+/// Fold the ruby base (spans from `base` on) into one run carrying the
+/// annotation. The base keeps its first run's style.
+fn attach_ruby(spans: &mut Vec<CueSpan>, base: Option<usize>, annotation: &str) {
+    let Some(base) = base.filter(|&b| b < spans.len()) else {
+        return;
+    };
+    let text: String = spans[base..].iter().map(|s| s.text.as_str()).collect();
+    spans.truncate(base + 1);
+    spans[base].text = text;
+    spans[base].ruby = Some(annotation.trim().to_string());
+}
+
+/// The named character references WebVTT cue text adds to XML's.
+const VTT_ENTITIES: [(&str, char); 3] = [
+    ("lrm", '\u{200E}'),
+    ("rlm", '\u{200F}'),
+    ("nbsp", '\u{00A0}'),
+];
+
 // ---------------------------------------------------------------------------
 // ISO BMFF WebVTT parser (ISO/IEC 14496-30)
 // ---------------------------------------------------------------------------
@@ -274,6 +741,7 @@ fn parse_vtt_sample(sample: &[u8], start_ms: i64, end_ms: i64, out: &mut Vec<Vtt
 
 fn parse_vttc(body: &[u8], start_ms: i64, end_ms: i64) -> Option<VttCue> {
     let mut payload = String::new();
+    let mut id = String::new();
     let mut settings = String::new();
     let mut i = 0;
     while i + 8 <= body.len() {
@@ -288,12 +756,12 @@ fn parse_vttc(body: &[u8], start_ms: i64, end_ms: i64) -> Option<VttCue> {
         match kind {
             b"payl" => {
                 if let Ok(s) = std::str::from_utf8(child) {
-                    for line in s.lines() {
-                        if !payload.is_empty() {
-                            payload.push('\n');
-                        }
-                        payload.push_str(&strip_inline_tags(line));
-                    }
+                    payload = s.lines().collect::<Vec<_>>().join("\n");
+                }
+            }
+            b"iden" => {
+                if let Ok(s) = std::str::from_utf8(child) {
+                    id = s.trim().to_string();
                 }
             }
             b"sttg" => {
@@ -305,19 +773,334 @@ fn parse_vttc(body: &[u8], start_ms: i64, end_ms: i64) -> Option<VttCue> {
         }
         i += size;
     }
-    if payload.is_empty() {
+    // `STYLE` blocks live in the sample entry's `vttC` config too, so
+    // only the tags and the default colour classes apply here.
+    let (text, spans) = parse_cue_text(&payload, &CueStyleSheet::default(), &id);
+    if text.is_empty() {
         return None;
     }
-    // Same NFC fold-down as the raw-WebVTT path — see parse_cue_block.
-    let payload: String = payload.nfc().collect();
+    // Regions live in the sample entry's `vttC` config, which segment
+    // parsing doesn't see; `region:` falls back to the plain settings.
+    let layout = CueSettings::parse(&settings).layout(&[]);
     Some(VttCue {
         start_ms,
         end_ms,
-        text: payload,
+        text,
         settings,
+        spans,
+        layout,
     })
 }
 
+// ---------------------------------------------------------------------------
+// Cue settings and regions (WebVTT §4.4, §6.2, §7.2)
+// ---------------------------------------------------------------------------
+
+/// Height of one overlay text line as a fraction of the frame at the
+/// default size (5% font × 1.25 line spacing, see the rasterizer). Region
+/// heights are given in lines but `CueLayout` is fractional.
+const NOMINAL_LINE: f32 = 0.0625;
+
+/// `vertical:` — direction of a vertical cue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vertical {
+    Rl,
+    Lr,
+}
+
+/// `line:` — where the cue sits vertically.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CueLine {
+    /// The bottom line, moved up past other cues on screen.
+    Auto,
+    /// Snap-to-lines: text line `n` from the top (n >= 0) or the bottom
+    /// (n < 0).
+    Number(i32),
+    /// Percentage of the frame height.
+    Percent(f32),
+}
+
+/// The `,start|center|end` suffix of a percentage `line:` — which edge
+/// of the cue box sits on the line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineAlign {
+    Start,
+    Center,
+    End,
+}
+
+/// The `,line-left|center|line-right` suffix of `position:` — which
+/// edge of the cue box sits on the position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionAlign {
+    Auto,
+    LineLeft,
+    Center,
+    LineRight,
+}
+
+/// `align:` — alignment of the text inside the cue box. `Start` / `End`
+/// are left / right (left-to-right text assumed).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CueAlign {
+    Start,
+    Center,
+    End,
+    Left,
+    Right,
+}
+
+/// Typed WebVTT cue settings, parsed from [`VttCue::settings`]. Unknown
+/// or malformed settings are skipped, as the spec's parser does.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CueSettings {
+    pub vertical: Option<Vertical>,
+    pub line: CueLine,
+    pub line_align: LineAlign,
+    /// `position:` percentage; `None` = auto (follows `align`).
+    pub position: Option<f32>,
+    pub position_align: PositionAlign,
+    /// `size:` — box width, percent of the frame.
+    pub size: f32,
+    pub align: CueAlign,
+    /// `region:` id, resolved against the file's `REGION` blocks.
+    pub region: Option<String>,
+}
+
+impl Default for CueSettings {
+    fn default() -> Self {
+        Self {
+            vertical: None,
+            line: CueLine::Auto,
+            line_align: LineAlign::Start,
+            position: None,
+            position_align: PositionAlign::Auto,
+            size: 100.0,
+            align: CueAlign::Center,
+            region: None,
+        }
+    }
+}
+
+impl CueSettings {
+    pub fn parse(settings: &str) -> Self {
+        let mut out = Self::default();
+        for token in settings.split_whitespace() {
+            let Some((name, value)) = token.split_once(':') else {
+                continue;
+            };
+            let (value, suffix) = value.split_once(',').unwrap_or((value, ""));
+            match name {
+                "vertical" => match value {
+                    "rl" => out.vertical = Some(Vertical::Rl),
+                    "lr" => out.vertical = Some(Vertical::Lr),
+                    _ => {}
+                },
+                "line" => {
+                    if let Some(p) = parse_percent(value) {
+                        out.line = CueLine::Percent(p);
+                    } else if let Ok(n) = value.parse::<i32>() {
+                        out.line = CueLine::Number(n);
+                    } else {
+                        continue;
+                    }
+                    out.line_align = match suffix {
+                        "center" => LineAlign::Center,
+                        "end" => LineAlign::End,
+                        _ => LineAlign::Start,
+                    };
+                }
+                "position" => {
+                    let Some(p) = parse_percent(value) else {
+                        continue;
+                    };
+                    out.position = Some(p);
+                    out.position_align = match suffix {
+                        "line-left" => PositionAlign::LineLeft,
+                        "center" => PositionAlign::Center,
+                        "line-right" => PositionAlign::LineRight,
+                        _ => PositionAlign::Auto,
+                    };
+                }
+                "size" => {
+                    if let Some(p) = parse_percent(value) {
+                        out.size = p;
+                    }
+                }
+                "align" => {
+                    out.align = match value {
+                        "start" => CueAlign::Start,
+                        "center" | "middle" => CueAlign::Center,
+                        "end" => CueAlign::End,
+                        "left" => CueAlign::Left,
+                        "right" => CueAlign::Right,
+                        _ => out.align,
+                    }
+                }
+                "region" => out.region = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        out
+    }
+
+    /// Resolve to the cue's box (WebVTT §7.2, horizontal text). `None`
+    /// when nothing moves the cue off the default — the overlay's own
+    /// bottom-centre placement, which also honours the host's safe area.
+    ///
+    /// A cue in a known region fills the region's width and stacks from
+    /// its bottom, showing the region's last `lines` lines; `line:`,
+    /// `position:` and `size:` don't apply there.
+    pub fn layout(&self, regions: &[VttRegion]) -> Option<CueLayout> {
+        let region = self
+            .region
+            .as_deref()
+            .and_then(|id| regions.iter().find(|r| r.id == id));
+        let untouched = Self {
+            vertical: self.vertical,
+            region: self.region.clone(),
+            ..Self::default()
+        };
+        if region.is_none() && *self == untouched {
+            return None;
+        }
+        let text_align = match self.align {
+            CueAlign::Start | CueAlign::Left => TextAlign::Left,
+            CueAlign::Center => TextAlign::Center,
+            CueAlign::End | CueAlign::Right => TextAlign::Right,
+        };
+
+        if let Some(r) = region {
+            let width = r.width / 100.0;
+            let height = r.lines as f32 * NOMINAL_LINE;
+            return Some(CueLayout {
+                x: (r.viewport_anchor[0] / 100.0 - r.region_anchor[0] / 100.0 * width)
+                    .clamp(0.0, 1.0 - width),
+                y: (r.viewport_anchor[1] / 100.0 - r.region_anchor[1] / 100.0 * height)
+                    .clamp(0.0, (1.0 - height).max(0.0)),
+                width,
+                height,
+                text_align,
+                display_align: DisplayAlign::After,
+                background: None,
+                line: None,
+                max_lines: Some(r.lines),
+            });
+        }
+
+        let position = self.position.unwrap_or(match self.align {
+            CueAlign::Start | CueAlign::Left => 0.0,
+            CueAlign::Center => 50.0,
+            CueAlign::End | CueAlign::Right => 100.0,
+        });
+        let position_align = match (self.position_align, self.align) {
+            (PositionAlign::Auto, CueAlign::Start | CueAlign::Left) => PositionAlign::LineLeft,
+            (PositionAlign::Auto, CueAlign::End | CueAlign::Right) => PositionAlign::LineRight,
+            (PositionAlign::Auto, CueAlign::Center) => PositionAlign::Center,
+            (a, _) => a,
+        };
+        // The box may not hang off the frame on its anchored side.
+        let max_size = match position_align {
+            PositionAlign::LineLeft => 100.0 - position,
+            PositionAlign::LineRight => position,
+            _ if position <= 50.0 => position * 2.0,
+            _ => (100.0 - position) * 2.0,
+        };
+        let size = self.size.min(max_size);
+        let x = match position_align {
+            PositionAlign::LineLeft => position,
+            PositionAlign::LineRight => position - size,
+            _ => position - size / 2.0,
+        };
+
+        let (y, height, display_align, line) = match self.line {
+            CueLine::Auto => (0.0, 1.0, DisplayAlign::After, Some(-1)),
+            CueLine::Number(n) => (0.0, 1.0, DisplayAlign::After, Some(n)),
+            CueLine::Percent(p) => {
+                let p = p / 100.0;
+                match self.line_align {
+                    LineAlign::Start => (p, 1.0 - p, DisplayAlign::Before, None),
+                    LineAlign::End => (0.0, p, DisplayAlign::After, None),
+                    LineAlign::Center => {
+                        let half = p.min(1.0 - p);
+                        (p - half, 2.0 * half, DisplayAlign::Center, None)
+                    }
+                }
+            }
+        };
+        Some(CueLayout {
+            x: x / 100.0,
+            y,
+            width: size / 100.0,
+            height,
+            text_align,
+            display_align,
+            background: None,
+            line,
+            max_lines: None,
+        })
+    }
+}
+
+/// A WebVTT `REGION` definition block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VttRegion {
+    pub id: String,
+    /// Percent of the frame width.
+    pub width: f32,
+    /// Height in text lines.
+    pub lines: u32,
+    /// Point of the region, percent of its own size, pinned to
+    /// `viewport_anchor`.
+    pub region_anchor: [f32; 2],
+    /// Percent of the frame.
+    pub viewport_anchor: [f32; 2],
+    /// `scroll:up` — new lines push older ones up and out.
+    pub scroll_up: bool,
+}
+
+impl VttRegion {
+    /// Parse the settings of a `REGION` block (everything after the
+    /// keyword). `None` without an `id`, which cues couldn't refer to.
+    pub fn parse(definition: &str) -> Option<Self> {
+        let mut region = Self {
+            id: String::new(),
+            width: 100.0,
+            lines: 3,
+            region_anchor: [0.0, 100.0],
+            viewport_anchor: [0.0, 100.0],
+            scroll_up: false,
+        };
+        let pair = |v: &str| -> Option<[f32; 2]> {
+            let (a, b) = v.split_once(',')?;
+            Some([parse_percent(a)?, parse_percent(b)?])
+        };
+        for token in definition.split_whitespace() {
+            let Some((name, value)) = token.split_once(':') else {
+                continue;
+            };
+            match name {
+                "id" => region.id = value.to_string(),
+                "width" => region.width = parse_percent(value).unwrap_or(region.width),
+                "lines" => region.lines = value.parse().unwrap_or(region.lines),
+                "regionanchor" => region.region_anchor = pair(value).unwrap_or(region.region_anchor),
+                "viewportanchor" => {
+                    region.viewport_anchor = pair(value).unwrap_or(region.viewport_anchor)
+                }
+                "scroll" => region.scroll_up = value == "up",
+                _ => {}
+            }
+        }
+        (!region.id.is_empty()).then_some(region)
+    }
+}
+
+/// `"42.5%"` → 42.5, limited to the spec's 0..=100.
+fn parse_percent(s: &str) -> Option<f32> {
+    let v: f32 = s.strip_suffix('%')?.parse().ok()?;
+    (0.0..=100.0).contains(&v).then_some(v)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,8 +1132,125 @@ mod tests {
         assert_eq!(parse_timestamp("00:01:02.345"), Some(62345));
     }
 
+    #[test]
+    fn parses_cue_settings() {
+        let s = CueSettings::parse("line:10%,end position:30%,line-left size:40% align:left bogus:1 vertical:rl");
+        assert_eq!(s.line, CueLine::Percent(10.0));
+        assert_eq!(s.line_align, LineAlign::End);
+        assert_eq!(s.position, Some(30.0));
+        assert_eq!(s.position_align, PositionAlign::LineLeft);
+        assert_eq!(s.size, 40.0);
+        assert_eq!(s.align, CueAlign::Left);
+        assert_eq!(s.vertical, Some(Vertical::Rl));
+        assert_eq!(CueSettings::parse("line:-2 size:250%").line, CueLine::Number(-2));
+        assert_eq!(CueSettings::parse("size:250%").size, 100.0);
+        // Nothing but defaults → the overlay's own placement.
+        assert_eq!(CueSettings::parse("align:center").layout(&[]), None);
+    }
+
+    #[test]
+    fn resolves_settings_to_layout() {
+        // Top line, left-aligned box from 10% covering 40% of the width.
+        let top = CueSettings::parse("line:0 position:10% size:40% align:start")
+            .layout(&[])
+            .unwrap();
+        assert_eq!(top.line, Some(0));
+        assert!((top.x - 0.1).abs() < 1e-6 && (top.width - 0.4).abs() < 1e-6);
+        assert_eq!(top.text_align, TextAlign::Left);
+
+        // `position` centred at 80% can't be wider than 2 × 20%.
+        let right = CueSettings::parse("position:80% line:50%,center").layout(&[]).unwrap();
+        assert!((right.width - 0.4).abs() < 1e-6 && (right.x - 0.6).abs() < 1e-6);
+        assert_eq!(right.line, None);
+        assert!((right.y - 0.0).abs() < 1e-6 && (right.height - 1.0).abs() < 1e-6);
+        assert_eq!(right.display_align, DisplayAlign::Center);
+    }
+
+    #[test]
+    fn region_blocks_place_their_cues() {
+        let data = b"WEBVTT\n\nREGION\nid:speaker width:40% lines:2 regionanchor:0%,100% viewportanchor:10%,90% scroll:up\n\n00:00:01.000 --> 00:00:02.000 region:speaker align:left\nHi\n\n00:00:03.000 --> 00:00:04.000 region:nope\nPlain";
+        let cues = parse_raw_webvtt(data);
+        assert_eq!(cues.len(), 2);
+        let layout = cues[0].layout.unwrap();
+        assert!((layout.x - 0.1).abs() < 1e-6 && (layout.width - 0.4).abs() < 1e-6);
+        assert!((layout.y + layout.height - 0.9).abs() < 1e-6);
+        assert_eq!(layout.max_lines, Some(2));
+        assert_eq!(layout.display_align, DisplayAlign::After);
+        // An unknown region is no region at all.
+        assert_eq!(cues[1].layout, None);
+    }
+
+    #[test]
+    fn decodes_webvtt_character_references() {
+        assert_eq!(
+            decode_entities("a&nbsp;b&lrm;&#233;&#x4E2D;&bogus; & c", &VTT_ENTITIES),
+            "a\u{a0}b\u{200e}\u{e9}\u{4e2d}&bogus; & c"
+        );
+        // The WebVTT names are not XML's.
+        assert_eq!(decode_entities("&nbsp;&amp;", &[]), "&nbsp;&");
+    }
+
     #[test]
     fn strips_inline_tags() {
-        assert_eq!(strip_inline_tags("<b>bold</b> <c.red>red</c>"), "bold red");
+        let (text, _) = parse_cue_text("<b>bold</b> <c.red>red</c>", &CueStyleSheet::default(), "");
+        assert_eq!(text, "bold red");
+    }
+
+    #[test]
+    fn parses_inline_styles_and_default_classes() {
+        let (text, spans) = parse_cue_text(
+            "<v Bob>plain <b>bold <i>both</i></b>\n<u>under</u> <c.yellow.bg_blue>amber</c> &amp;&lt;",
+            &CueStyleSheet::default(),
+            "",
+        );
+        assert_eq!(text, "plain bold both\nunder amber &<");
+        let texts: Vec<&str> = spans.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["plain ", "bold ", "both", "\n", "under", " ", "amber", " &<"]
+        );
+        assert!(spans[1].style.bold && !spans[1].style.italic);
+        assert!(spans[2].style.bold && spans[2].style.italic);
+        assert!(spans[4].style.underline);
+        assert_eq!(spans[6].style.color, Some([255, 255, 0, 255]));
+        assert_eq!(spans[6].style.background, Some([0, 0, 255, 255]));
+        assert_eq!(spans[7].style, SpanStyle::default());
+    }
+
+    #[test]
+    fn applies_cue_style_blocks() {
+        let data = b"WEBVTT\n\nSTYLE\n::cue { color: rgba(255, 255, 255, 0.5) }\n/* voices */\n::cue(v[voice=\"Ann\"]) { color: #0f0; font-style: italic }\n::cue(.loud), ::cue(#intro) { font-weight: 700; background: black }\n::cue(:past) { color: gray }\n\nintro\n00:00:01.000 --> 00:00:02.000\n<v Ann>hi</v> <c.loud>HEY</c>\n\n00:00:03.000 --> 00:00:04.000\nquiet";
+        let cues = parse_raw_webvtt(data);
+        assert_eq!(cues.len(), 2);
+        let spans = &cues[0].spans;
+        assert_eq!(spans[0].text, "hi");
+        assert_eq!(spans[0].style.color, Some([0, 255, 0, 255]));
+        assert!(spans[0].style.italic && spans[0].style.bold);
+        // `#intro` makes the whole first cue bold on black.
+        assert_eq!(spans[1].style.color, Some([255, 255, 255, 128]));
+        assert_eq!(spans[1].style.background, Some([0, 0, 0, 255]));
+        // `.loud` adds nothing `#intro` hasn't, so "HEY" joins the space.
+        assert_eq!(spans[1].text, " HEY");
+        assert!(spans[1].style.bold);
+        // The second cue only gets `::cue`.
+        assert_eq!(cues[1].spans[0].style.color, Some([255, 255, 255, 128]));
+        assert!(!cues[1].spans[0].style.bold);
+    }
+
+    #[test]
+    fn attaches_ruby_annotations() {
+        let (text, spans) = parse_cue_text(
+            "<ruby>漢<b>字</b><rt>かんじ</rt>仮<rt>か</rt></ruby>です",
+            &CueStyleSheet::default(),
+            "",
+        );
+        assert_eq!(text, "漢字仮です");
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].text, "漢字");
+        assert_eq!(spans[0].ruby.as_deref(), Some("かんじ"));
+        assert_eq!(spans[1].text, "仮");
+        assert_eq!(spans[1].ruby.as_deref(), Some("か"));
+        assert_eq!(spans[2].text, "です");
+        assert_eq!(spans[2].ruby, None);
     }
 }
@@ -1,15 +1,21 @@
 mod abr;
+mod audio_layout;
+mod audio_processor;
 mod capabilities;
 mod crypto;
 mod decoders;
 mod events;
 mod ffmpeg_log;
+mod closed_captions;
 mod hdr_tonemap;
+mod host_subtitles;
+mod loudness;
 mod manifest;
 mod net;
 mod parsers;
 mod renderers;
 mod subtitle_style;
+mod time_stretch;
 mod tracks;
 mod utils;
 
@@ -22,12 +28,21 @@ pub use capabilities::{capabilities, probe_capabilities, PlayerCapabilities};
 /// types stay reachable through its public `video`/`audio`/`text` fields — a
 /// consumer reads them via inference (no need to name the inner types).
 pub use tracks::Tracks;
+pub use tracks::text::{CaptionService, SubtitleFormat};
 pub use events::{
-    BufferingReason, Fps, PlayerErrorKind, PlayerEvent, TrackInfo, TrackKind,
+    BufferingReason, Fps, PlayerErrorKind, PlayerEvent, SubtitleSpan, TrackInfo, TrackKind,
 };
 pub use ffmpeg_log::{set_log_level, LogLevel};
+pub use audio_layout::DownmixCoefficients;
+pub use audio_processor::{AudioProcessor, AudioProcessorId};
 pub use hdr_tonemap::HdrTonemapParams;
-pub use subtitle_style::SubtitleStyle;
+pub use loudness::LoudnessParams;
+pub use renderers::audio::AudioOutputDevice;
+pub use subtitle_style::{
+    SecondarySubtitlePosition, SubtitleEdgeStyle, SubtitleFontFace, SubtitleFontFamily,
+    SubtitleStyle,
+};
+pub use time_stretch::{MAX_PLAYBACK_RATE, MIN_PLAYBACK_RATE};
 pub use net::{
     tls_client, BoxError, HttpClient, LicenseResolver, NoopInterceptor, PreparedRequest,
     RequestInterceptor, RequestKind, RetryPolicy,
@@ -52,20 +67,27 @@ impl<P> PhysicalSize<P> {
 pub use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 
 use crypto::{
-    kid_short, parse_aac_config, parse_hvcc_bit_depth, parse_hvcc_nalus, parse_senc, parse_tenc,
+    kid_short, parse_aac_config, parse_av1c, parse_avcc, parse_vpcc, parse_hvcc_bit_depth, parse_hvcc_nalus, parse_senc,
+    parse_dfla, parse_dops, parse_tenc,
     ClearKeyDecryptor,
-    Decryptor, TrackCrypto,
+    Decryptor, SencEntry, TrackCrypto,
 };
 use decoders::{
     AudioCodec, AudioDecoder, AudioDecoderParams, DecodedAudioFrame, DecodedVideoFrame,
     HwVideoDecoder, VideoCodec, VideoColorInfo, VideoDecoderParams,
 };
-use parsers::mp4::aac_sampling_frequency_index_to_u32;
+use parsers::mp4::{aac_sampling_frequency_index_to_u32, locate_first_sample};
+use parsers::opus::{OPUS_DECODE_RATE, OPUS_SEEK_PREROLL};
 use pollster::FutureExt;
 use re_mp4::Mp4;
 use renderers::audio::AudioRenderer;
 use renderers::video::VideoRenderer;
 use renderers::{AudioSink, VideoSink};
+use audio_processor::AudioProcessorChain;
+use closed_captions::{CaptionFeed, ClosedCaptions};
+use host_subtitles::HostSubtitles;
+use loudness::LoudnessProcessor;
+use time_stretch::TimeStretch;
 
 // Additive: re-export the offscreen ring handle + a convenience alias. Offscreen
 // (in-app) video reuses `VideoRenderer` with an offscreen target, so the in-app
@@ -76,7 +98,7 @@ pub type OffscreenPlayer = Player<VideoRenderer, AudioRenderer>;
 use arc_swap::ArcSwap;
 use std::collections::HashMap;
 use std::error::Error;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicI64, AtomicU32, AtomicU64, AtomicUsize, Ordering};
 #[cfg(target_os = "android")]
 use libc;
 use std::sync::Mutex as StdMutex;
@@ -108,6 +130,28 @@ const DEFAULT_BUFFER_TARGET_SECS: u32 = 8;
 /// 2 is a conservative floor that biases the cap upward.
 const ASSUMED_SEGMENT_SECS: u32 = 2;
 
+/// Largest lip-sync offset `Player::set_av_offset_ms` accepts, either way.
+pub const MAX_AV_OFFSET_MS: i32 = 2_000;
+
+/// Largest subtitle timing offset `Player::set_subtitle_offset_ms` accepts,
+/// either way.
+pub const MAX_SUBTITLE_OFFSET_MS: i64 = 600_000;
+
+/// Slowest and fastest `Player::set_trick_play` speed, either direction.
+pub const MIN_TRICK_SPEED: i32 = 2;
+pub const MAX_TRICK_SPEED: i32 = 32;
+
+/// Convert a buffer target into a channel capacity (segments-in-flight)
+/// using the conservative segment-duration estimate. The target is wall
+/// time: above 1× the renderer drains media faster, so the same target
+/// needs proportionally more segments (below 1× it keeps the 1× depth).
+/// Floored at 2 so even with a tiny buffer target the decoder has room for
+/// the next segment behind the one currently being processed.
+fn segments_in_flight(buffer_target_secs: u32, playback_rate: f32) -> usize {
+    let secs = buffer_target_secs.max(2) as f32 * playback_rate.max(1.0);
+    ((secs / ASSUMED_SEGMENT_SECS as f32) as usize).max(2)
+}
+
 /// Result of a starvation-state update — exposed by the helper so the
 /// caller can react to combined-state transitions (the moment EITHER
 /// side starts stalling, or the moment BOTH have recovered).
@@ -229,6 +273,10 @@ struct StatsState {
     /// this is the measurement that tells us when an active servo is
     /// warranted on a given device class.
     av_drift_ms: std::sync::atomic::AtomicI64,
+    /// Integrated loudness (LUFS) of the session's PCM audio so far, written
+    /// by `audio_sync_loop` from the loudness meter and surfaced via
+    /// PlayerEvent::Stats. `None` until a block clears the BS.1770 gate.
+    audio_loudness_lufs: StdMutex<Option<f32>>,
 
     // ---- pipeline stage counters (diagnostics) ------------------------------
     // Cumulative per-stage progress counters, sampled by the watchdog thread
@@ -365,6 +413,24 @@ pub struct Player<V: VideoSink = VideoRenderer, A: AudioSink = AudioRenderer> {
     /// `set_subtitle_track` / `clear_subtitle_track`.
     subtitle_representation: Arc<StdMutex<Option<tracks::text::TextRepresenation>>>,
 
+    /// Secondary subtitle track shown alongside the primary one (dual
+    /// subtitles). Same lifecycle as `subtitle_representation`; toggled via
+    /// `set_secondary_subtitle_track` / `clear_secondary_subtitle_track`.
+    secondary_subtitle_representation: Arc<StdMutex<Option<tracks::text::TextRepresenation>>>,
+
+    /// Subtitle timing offset in ms (+ = cues later), clamped to
+    /// ±`MAX_SUBTITLE_OFFSET_MS`. Set via `set_subtitle_offset_ms`.
+    subtitle_offset_ms: Arc<AtomicI64>,
+
+    /// Host-rendered subtitle mode (`set_host_subtitles`): when enabled,
+    /// text_play feeds cues here instead of the overlay and they reach the
+    /// host as `SubtitleCue` / `SubtitleCueCleared` events.
+    host_subtitles: Arc<HostSubtitles>,
+
+    /// CEA-608/708 captions decoded from the video SEI, relayed by
+    /// text_play when a caption track (`embedded_captions`) is selected.
+    closed_captions: Arc<ClosedCaptions>,
+
     /// Android direct mode: the dedicated video-plane `ANativeWindow` the
     /// decoder renders into (0 = classic renderer path). Set by the host before
     /// play(); consumed at pipeline build. Wrapped in [`DirectWindow`] so we
@@ -393,6 +459,52 @@ pub struct Player<V: VideoSink = VideoRenderer, A: AudioSink = AudioRenderer> {
     /// swap), so a change applies from the next (re)configure on.
     hdr_decode_8bit: Arc<std::sync::atomic::AtomicBool>,
 
+    /// Force the FFmpeg software video decoder (desktop only). Seeded from
+    /// the `RUST_PLAYER_SOFTWARE_DECODE` env var; sampled at `play()`. The
+    /// software path is also picked automatically when the hw-device can't
+    /// be created.
+    software_video_decode: Arc<std::sync::atomic::AtomicBool>,
+
+    /// Fold gains for surround channels the output device lacks (5.1 / 7.1
+    /// on a stereo device). Set via `set_downmix`; read at every audio
+    /// pipeline (re)build.
+    downmix: Arc<ArcSwap<DownmixCoefficients>>,
+
+    /// Playback rate as `f32` bits, clamped to
+    /// `MIN_PLAYBACK_RATE..=MAX_PLAYBACK_RATE`. Read live by the audio sync
+    /// loop's time stretch (the clock follows the audio it produces) and at
+    /// every pipeline (re)build for download-ahead sizing.
+    playback_rate: Arc<AtomicU32>,
+
+    /// Loudness normalisation / night-mode settings. Set via `set_loudness`;
+    /// read live by the audio sync loop.
+    loudness: Arc<ArcSwap<LoudnessParams>>,
+    /// The loudness meter and gain state, shared by successive pipelines so
+    /// a seek or track switch doesn't restart the measurement or re-ramp the
+    /// gain. Cleared by `open_url` (a new programme).
+    loudness_dsp: Arc<StdMutex<Option<LoudnessProcessor>>>,
+    /// Host effects (`add_audio_processor`), run by the audio sync loop
+    /// after the loudness stage. Shared by successive pipelines.
+    audio_processors: Arc<AudioProcessorChain>,
+
+    /// Lip-sync offset in ms (+ = video later than audio), clamped to
+    /// ±`MAX_AV_OFFSET_MS`. Set via `set_av_offset_ms`; read live by
+    /// `MediaClock`.
+    av_offset_ms: Arc<AtomicI32>,
+
+    /// False while the host has video detached (`set_video_enabled`): each
+    /// pipeline (re)build then skips video download/decode and the audio
+    /// clock alone drives position and stats.
+    video_enabled: Arc<AtomicBool>,
+
+    /// `step_frame` state shared with the video sync loop: pending forward
+    /// steps and the exact PTS / cadence of the frame on screen.
+    frame_step: Arc<FrameStep>,
+
+    /// Trick-play speed (`set_trick_play`): 0 = off, else ±2..=32 × real
+    /// time. Checked at every pipeline (re)build, read live by trick play.
+    trick_speed: Arc<AtomicI32>,
+
     /// True once the current pipeline has produced its first frame (set in
     /// av_sync_handler after video_ready, reset to false on every pipeline
     /// (re)build). The ABR tick consults it so the FIRST auto-switch can't
@@ -446,10 +558,24 @@ impl<V: VideoSink, A: AudioSink> Clone for Player<V, A> {
             video_switch_tx: Arc::clone(&self.video_switch_tx),
             buffer_target_secs: Arc::clone(&self.buffer_target_secs),
             subtitle_representation: Arc::clone(&self.subtitle_representation),
+            secondary_subtitle_representation: Arc::clone(&self.secondary_subtitle_representation),
+            subtitle_offset_ms: Arc::clone(&self.subtitle_offset_ms),
+            host_subtitles: Arc::clone(&self.host_subtitles),
+            closed_captions: Arc::clone(&self.closed_captions),
             video_output_window: Arc::clone(&self.video_output_window),
             adaptive_frame_rate: Arc::clone(&self.adaptive_frame_rate),
             audio_passthrough: Arc::clone(&self.audio_passthrough),
             hdr_decode_8bit: Arc::clone(&self.hdr_decode_8bit),
+            software_video_decode: Arc::clone(&self.software_video_decode),
+            downmix: Arc::clone(&self.downmix),
+            playback_rate: Arc::clone(&self.playback_rate),
+            loudness: Arc::clone(&self.loudness),
+            loudness_dsp: Arc::clone(&self.loudness_dsp),
+            audio_processors: Arc::clone(&self.audio_processors),
+            av_offset_ms: Arc::clone(&self.av_offset_ms),
+            video_enabled: Arc::clone(&self.video_enabled),
+            frame_step: Arc::clone(&self.frame_step),
+            trick_speed: Arc::clone(&self.trick_speed),
             pipeline_live: Arc::clone(&self.pipeline_live),
             pending_resume: Arc::clone(&self.pending_resume),
             video_renderer: Arc::clone(&self.video_renderer),
@@ -506,6 +632,51 @@ fn clock_monotonic_ns() -> i64 {
 #[cfg(not(target_os = "android"))]
 fn clock_monotonic_ns() -> i64 { 0 }
 
+/// Output-time → media-time map for one pipeline.
+///
+/// With a playback rate other than 1.0 the audio output advances slower or
+/// faster than the media it carries, so "played" no longer equals "media
+/// elapsed". Each rate change starts a new piece anchored where the previous
+/// one had reached; `audio_sync_loop` appends a piece when its time stretch
+/// switches rate (keyed by how much output it has pushed), and `MediaClock`
+/// maps the sink's played position through it. At a constant 1.0 it is the
+/// plain `seek_offset + elapsed` rebase.
+struct RateTimeline {
+    /// (output µs, media µs, rate), ascending by output time. Never empty.
+    pieces: std::sync::Mutex<Vec<(i64, i64, f64)>>,
+}
+
+impl RateTimeline {
+    fn new(media_start_us: i64, rate: f32) -> Self {
+        Self {
+            pieces: std::sync::Mutex::new(vec![(0, media_start_us, f64::from(rate))]),
+        }
+    }
+
+    /// Media position (µs) and rate at `out_us` of pipeline output. Times
+    /// before the first piece extrapolate from it.
+    fn at(&self, out_us: i64) -> (i64, f64) {
+        let pieces = self.pieces.lock().unwrap();
+        let &(o, m, r) = pieces
+            .iter()
+            .rev()
+            .find(|p| p.0 <= out_us)
+            .unwrap_or(&pieces[0]);
+        (m + ((out_us - o) as f64 * r) as i64, r)
+    }
+
+    /// Rate of the most recent piece — what the audio path is producing now.
+    fn latest_rate(&self) -> f32 {
+        self.pieces.lock().unwrap().last().map_or(1.0, |p| p.2 as f32)
+    }
+
+    /// Switch to `rate` from `out_us` of pipeline output on.
+    fn push(&self, out_us: i64, rate: f32) {
+        let (media_us, _) = self.at(out_us);
+        self.pieces.lock().unwrap().push((out_us, media_us, f64::from(rate)));
+    }
+}
+
 /// Playback master clock — 0-based media time, audio-disciplined.
 ///
 /// Mastered by the audio sink's real playback position so video (and any other
@@ -513,22 +684,30 @@ fn clock_monotonic_ns() -> i64 { 0 }
 /// the sink's coarse position updates it interpolates with the wall clock for
 /// smooth pacing; when the sink reports no position (mocks / not-yet-started)
 /// it falls back to the wall clock. Rebased onto THIS pipeline's 0-based
-/// timeline (media = seek_offset + (played − audio_base)) so the reported
-/// position is absolute, not the audio device's free-running counter.
+/// timeline (media = seek_offset + (played − audio_base), scaled piecewise by
+/// the playback rate — see `RateTimeline`) so the reported position is
+/// absolute, not the audio device's free-running counter.
 ///
 /// The audio sink is the only clock source today and the seam for tomorrow: an
 /// AudioTrack passthrough sink reports `played_ms` via getTimestamp, so the
 /// clock serves bitstream (Dolby/DTS passthrough) and multichannel without
 /// video / subtitles knowing the difference. Shareable (interior-mutable
 /// anchor) so future consumers can pace to the same clock.
+///
+/// The host's lip-sync offset (`Player::set_av_offset_ms`) is applied here,
+/// live: a positive offset holds the clock back so video — and the
+/// subtitles keyed off its pts — land later than the audio by that much.
 struct MediaClock<A: AudioSink> {
     audio_sink: Arc<A>,
     // Wall anchor (= now − seek_offset): the fallback when the sink has no clock.
     start_time: Arc<Instant>,
-    // seek_offset_us − audio_base_us: rebases the cumulative audio counter onto
-    // this pipeline's 0-based timeline. Constant per pipeline; cancels out of
-    // the frame-pacing delta, so it only affects the *reported* position.
-    rebase_us: i64,
+    seek_offset_us: i64,
+    // Cumulative played_ms at this pipeline's anchor: played − audio_base is
+    // the pipeline's output time, which `timeline` maps to media time.
+    audio_base_us: i64,
+    timeline: Arc<RateTimeline>,
+    // Player-level lip-sync offset, ms of output time (+ = video later).
+    av_offset_ms: Arc<AtomicI32>,
     // (last observed played_ms, wall instant then) for sub-update interpolation.
     anchor: std::sync::Mutex<Option<(u64, Instant)>>,
 }
@@ -539,20 +718,26 @@ impl<A: AudioSink> MediaClock<A> {
         start_time: Arc<Instant>,
         seek_offset: Duration,
         audio_base_ms: u64,
+        timeline: Arc<RateTimeline>,
+        av_offset_ms: Arc<AtomicI32>,
     ) -> Self {
         Self {
             audio_sink,
             start_time,
-            rebase_us: seek_offset.as_micros() as i64 - audio_base_ms as i64 * 1_000,
+            seek_offset_us: seek_offset.as_micros() as i64,
+            audio_base_us: audio_base_ms as i64 * 1_000,
+            timeline,
+            av_offset_ms,
             anchor: std::sync::Mutex::new(None),
         }
     }
 
-    /// Audio-disciplined position (µs), or None when the sink reports no clock.
-    /// `played_ms` advances at the device rate and freezes on pause/starvation,
-    /// so it already subsumes pause skew; `output_latency_ms` folds in so the
-    /// picture lands when its audio is audible, not merely consumed.
-    fn audio_now_us(&self) -> Option<i64> {
+    /// Audio-disciplined position (µs) and rate, or None when the sink
+    /// reports no clock. `played_ms` advances at the device rate and freezes
+    /// on pause/starvation, so it already subsumes pause skew;
+    /// `output_latency_ms` folds in so the picture lands when its audio is
+    /// audible, not merely consumed.
+    fn audio_now(&self) -> Option<(i64, f64)> {
         let played = self.audio_sink.played_ms()?;
         let lat_us = self.audio_sink.output_latency_ms() as i64 * 1_000;
         let now = Instant::now();
@@ -572,21 +757,113 @@ impl<A: AudioSink> MediaClock<A> {
         } else {
             p0 as i64 * 1_000
         };
-        Some((pos_us + self.rebase_us - lat_us).max(0))
+        let (media_us, rate) = self.timeline.at(pos_us - self.audio_base_us - lat_us);
+        Some((media_us.max(0), rate))
+    }
+
+    /// Current 0-based media time (µs) and the playback rate it advances at:
+    /// audio when available, else the wall clock, minus the lip-sync offset.
+    /// Only the wall fallback applies `pause_skew` — the audio clock freezes
+    /// during pause on its own.
+    fn now(&self, pause_skew: Duration) -> (i64, f64) {
+        let (media_us, rate) = self.audio_now().unwrap_or_else(|| {
+            let wall_us = self
+                .start_time
+                .elapsed()
+                .saturating_sub(pause_skew)
+                .saturating_sub(Duration::from_millis(self.audio_sink.output_latency_ms()))
+                .as_micros() as i64;
+            self.timeline.at(wall_us - self.seek_offset_us)
+        });
+        // The offset is output time (what the listener perceives); at a
+        // playback rate other than 1.0 that spans `rate` × as much media.
+        let offset_us = f64::from(self.av_offset_ms.load(Ordering::Relaxed)) * 1_000.0 * rate;
+        ((media_us - offset_us as i64).max(0), rate)
     }
 
-    /// Current 0-based media time (µs): audio when available, else the wall
-    /// clock. Only the wall fallback applies `pause_skew` — the audio clock
-    /// freezes during pause on its own.
     fn now_us(&self, pause_skew: Duration) -> i64 {
-        if let Some(us) = self.audio_now_us() {
-            return us;
+        self.now(pause_skew).0
+    }
+}
+
+/// Frame stepping while paused (`Player::step_frame`).
+///
+/// Forward steps are queued here and served by the video sync loop's pause
+/// gate, which presents the next decoded frame immediately instead of pacing
+/// it. Backward steps can't come out of the decoder (it only runs forward),
+/// so they seek: the frame-accurate seek re-decodes from the segment-start
+/// keyframe and discards up to the target frame. The sync loop records the
+/// exact PTS of every presented frame plus the observed frame interval, which
+/// is what a backward step aims from.
+struct FrameStep {
+    /// Forward steps not yet presented.
+    forward: AtomicU32,
+    /// Wakes the pause gate when `forward` grows.
+    notify: Notify,
+    /// Exact 0-based PTS (µs) of the frame on screen; -1 = none yet.
+    presented_us: AtomicI64,
+    /// Last observed frame interval (µs).
+    interval_us: AtomicI64,
+    /// The next presented frame is a backward step's target: report it.
+    report_next: AtomicBool,
+    /// Forward steps moved video ahead of the (paused) audio: resume must
+    /// realign with a seek to the stepped-to frame.
+    dirty: AtomicBool,
+}
+
+impl Default for FrameStep {
+    fn default() -> Self {
+        Self {
+            forward: AtomicU32::new(0),
+            notify: Notify::new(),
+            presented_us: AtomicI64::new(-1),
+            interval_us: AtomicI64::new(Self::DEFAULT_INTERVAL_US),
+            report_next: AtomicBool::new(false),
+            dirty: AtomicBool::new(false),
         }
-        self.start_time
-            .elapsed()
-            .saturating_sub(pause_skew)
-            .saturating_sub(Duration::from_millis(self.audio_sink.output_latency_ms()))
-            .as_micros() as i64
+    }
+}
+
+impl FrameStep {
+    /// Assumed cadence (25 fps) until two consecutive frames were seen.
+    const DEFAULT_INTERVAL_US: i64 = 40_000;
+    /// Gaps above this are discontinuities, not a frame interval.
+    const MAX_INTERVAL_US: i64 = 200_000;
+
+    /// Consume one pending forward step, if any.
+    fn take_forward(&self) -> bool {
+        self.forward
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok()
+    }
+
+    /// Record the frame at 0-based `pts_us` as on screen. `prev_us` is the
+    /// previous frame of the same sync loop run — `None` right after a
+    /// (re)start, whose jump from the old frame isn't a frame interval.
+    fn presented(&self, pts_us: i64, prev_us: Option<i64>) -> Duration {
+        let pts_us = pts_us.max(0);
+        if let Some(prev) = prev_us {
+            let d = pts_us - prev;
+            if d > 0 && d <= Self::MAX_INTERVAL_US {
+                self.interval_us.store(d, Ordering::Relaxed);
+            }
+        }
+        self.presented_us.store(pts_us, Ordering::Relaxed);
+        Duration::from_micros(pts_us as u64)
+    }
+
+    /// Seek target for stepping `n` frames back from the one on screen: a
+    /// quarter interval short of the wanted frame's PTS, so the seek's
+    /// discard lands on it despite timestamp rounding. `None` before the
+    /// first frame.
+    fn backward_target(&self, n: u32) -> Option<Duration> {
+        let presented = self.presented_us.load(Ordering::Relaxed);
+        if presented < 0 {
+            return None;
+        }
+        let interval = self.interval_us.load(Ordering::Relaxed);
+        let target = presented - i64::from(n) * interval - interval / 4;
+        Some(Duration::from_micros(target.max(0) as u64))
     }
 }
 
@@ -625,6 +902,15 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
     paused: Arc<AtomicBool>,
     pause_notify: Arc<Notify>,
     stats: Arc<StatsState>,
+    // This pipeline's output→media time map (playback rate); shared with
+    // audio_sync_loop, which extends it as the rate changes.
+    timeline: Arc<RateTimeline>,
+    // Host lip-sync offset, read live by the clock.
+    av_offset_ms: Arc<AtomicI32>,
+    // Content origin (absolute pts of the first segment): frame.pts_us minus
+    // this is the exact 0-based PTS `step_frame` reports.
+    origin: Duration,
+    frame_step: Arc<FrameStep>,
 ) {
     // While paused, real time keeps advancing but media time must NOT.
     // We accumulate the wall-clock duration spent paused and subtract
@@ -663,6 +949,10 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
     // sees where they landed instead of a stale frozen frame. Pauses during
     // playback (presented_frame already true) park immediately as before.
     let mut presented_frame = false;
+    // Exact 0-based PTS (µs) of the last frame this run presented — feeds
+    // the frame interval `step_frame` steps backward by.
+    let mut last_presented_us: Option<i64> = None;
+    let origin_us = origin.as_micros() as i64;
     // Per-second HEALTH heartbeat baselines: previous cumulative counters,
     // so the stats tick can log frame DROPS and DECODES as a per-second
     // delta (a climbing cumulative number is hard to read live). See the
@@ -710,9 +1000,11 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
         start_time.clone(),
         seek_offset,
         audio_base_ms,
+        timeline,
+        av_offset_ms,
     );
     log::info!("[vsync gen {}] loop start (seek_offset={}ms)", gen, seek_offset.as_millis());
-    loop {
+    'sync: loop {
         if stop_flag.load(Ordering::Relaxed) {
             break;
         }
@@ -722,9 +1014,40 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
         // before we honour the pause.
         if paused.load(Ordering::Relaxed) && presented_frame {
             let pause_started = Instant::now();
-            tokio::select! {
-                _ = pause_notify.notified() => {}
-                _ = stop.notified() => break,
+            // Parked, but serving `step_frame`: each forward step presents
+            // the next decoded frame at once — no pacing, the clock is
+            // frozen. Audio stays put, so resume() realigns with a seek.
+            loop {
+                if frame_step.take_forward() {
+                    let mut frame = tokio::select! {
+                        maybe = input_rx.recv() => match maybe {
+                            Some(f) => f,
+                            None => return,
+                        },
+                        _ = stop.notified() => break 'sync,
+                    };
+                    let pts_ms = ((frame.pts_us / 1000) as u64).saturating_sub(pts_base.unwrap_or(0));
+                    last_pts_ms = pts_ms;
+                    position_ms.store(pts_ms, Ordering::Relaxed);
+                    renderer.set_subtitle_pts(pts_ms as i64);
+                    let position = frame_step.presented(frame.pts_us - origin_us, last_presented_us);
+                    last_presented_us = Some(position.as_micros() as i64);
+                    frame.desired_present_ns = clock_monotonic_ns();
+                    renderer.render_frame(frame).await;
+                    stats.diag_video_ren.fetch_add(1, Ordering::Relaxed);
+                    stats.video_frames_decoded.fetch_add(1, Ordering::Relaxed);
+                    frame_step.dirty.store(true, Ordering::Relaxed);
+                    let _ = events.send(PlayerEvent::FrameStepped { position });
+                    continue;
+                }
+                if !paused.load(Ordering::Relaxed) {
+                    break;
+                }
+                tokio::select! {
+                    _ = pause_notify.notified() => break,
+                    _ = frame_step.notify.notified() => {}
+                    _ = stop.notified() => break 'sync,
+                }
             }
             pause_skew += pause_started.elapsed();
             if stop_flag.load(Ordering::Relaxed) {
@@ -857,7 +1180,10 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
         // media time, so subtitles (keyed off pts_ms) and the picture move
         // together against this same audio-anchored clock.
         // Master clock — audio-disciplined, wall fallback. See MediaClock.
-        let elapsed = (clock.now_us(pause_skew) / 1_000) as u64;
+        // `rate` converts media-time distances into the wall time the pacing
+        // below sleeps / stamps in (media advances `rate` × wall).
+        let (now_us, rate) = clock.now(pause_skew);
+        let elapsed = (now_us / 1_000) as u64;
 
         let base = *pts_base.get_or_insert(raw_pts_ms.saturating_sub(elapsed));
         let mut pts_ms = raw_pts_ms.saturating_sub(base);
@@ -929,10 +1255,11 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
             };
             #[cfg(not(target_os = "android"))]
             let render_budget_ms = RENDER_BUDGET_MS;
-            let target_wake_ms = pts_ms.saturating_sub(render_budget_ms);
-            if target_wake_ms > elapsed {
+            // Media ms until the frame is due, in wall ms at the current rate.
+            let to_go_ms = ((pts_ms - elapsed) as f64 / rate) as u64;
+            if to_go_ms > render_budget_ms {
                 tokio::select! {
-                    _ = tokio::time::sleep(Duration::from_millis(target_wake_ms - elapsed)) => {}
+                    _ = tokio::time::sleep(Duration::from_millis(to_go_ms - render_budget_ms)) => {}
                     _ = stop.notified() => break,
                 }
                 if stop_flag.load(Ordering::Relaxed) {
@@ -952,21 +1279,22 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
         let base_us = base as i64 * 1_000;
         let pts_us_rel = (raw_pts_us - base_us).max(0);
         let elapsed_us = clock.now_us(pause_skew);
-        let pts_to_go_ns = (pts_us_rel - elapsed_us).max(0) * 1_000;
+        let pts_to_go_ns = ((pts_us_rel - elapsed_us).max(0) as f64 * 1_000.0 / rate) as i64;
         let raw_present_ns = clock_monotonic_ns() + pts_to_go_ns;
         // De-judder: `raw_present_ns` carries the audio master clock's
         // frame-to-frame wobble (it's a quantized per-callback staircase, wall-
         // interpolated), enough to land a frame on the wrong VSync = visible
         // judder, worst right after resume when the clock anchor + output
         // latency lurch. The ideal present time advances by exactly the media
-        // delta from the previous frame; snap to it, but only within
-        // ±PRESENT_SMOOTH_NS of raw so a seek / LATE-drain / resume jump (raw
-        // moves further than the window) is followed at once and the cadence
-        // re-bases on the next frame. The raw formula above is untouched, so the
+        // delta from the previous frame (divided by the playback rate); snap
+        // to it, but only within ±PRESENT_SMOOTH_NS of raw so a seek /
+        // LATE-drain / resume jump (raw moves further than the window) is
+        // followed at once and the cadence re-bases on the next frame. The raw formula above is untouched, so the
         // sleep gate / LATE drain / clock all behave exactly as before.
         let present_ns = match last_present {
             Some((last_ns, last_pts_us)) => {
-                let ideal_ns = last_ns + (pts_us_rel - last_pts_us) * 1_000;
+                let ideal_ns =
+                    last_ns + ((pts_us_rel - last_pts_us) as f64 * 1_000.0 / rate) as i64;
                 ideal_ns.clamp(
                     raw_present_ns - PRESENT_SMOOTH_NS,
                     raw_present_ns + PRESENT_SMOOTH_NS,
@@ -1029,7 +1357,7 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
             // steady-state (sane consecutive deltas) so seeks, splices and
             // segment boundaries don't count as stutter.
             if delta_pts > 0 && delta_pts < 100 {
-                let jitter = interval_ms as i64 - delta_pts as i64;
+                let jitter = interval_ms as i64 - (delta_pts as f64 / rate) as i64;
                 if jitter.abs() > 10 {
                     stats.judder_frames.fetch_add(1, Ordering::Relaxed);
                 }
@@ -1067,6 +1395,8 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
         // VTT timestamps. Use `pts_ms` here, NOT frame.pts_us — the
         // latter still carries the DASH BMDT offset.
         renderer.set_subtitle_pts(pts_ms as i64);
+        let stepped_to = frame_step.presented(frame.pts_us - origin_us, last_presented_us);
+        last_presented_us = Some(stepped_to.as_micros() as i64);
 
         // Emit `Playing` once on the first rendered frame after a sync
         // loop (re)starts (Buffering→Playing transition) — but NOT for a
@@ -1084,6 +1414,10 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
         stats.diag_video_ren.fetch_add(1, Ordering::Relaxed);
         // One frame is now on screen — from here the pause gate parks.
         presented_frame = true;
+        // A backward step is a seek: its first frame is the one stepped to.
+        if frame_step.report_next.swap(false, Ordering::Relaxed) {
+            let _ = events.send(PlayerEvent::FrameStepped { position: stepped_to });
+        }
 
         let render_done = start_time
             .elapsed()
@@ -1144,12 +1478,6 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
                 }
             }
             drift_min_window = i64::MAX;
-            let decoder_name = stats.decoder_name.lock().unwrap().clone();
-            // Hoist the counters out of the event literal: net_stall_ms is
-            // swap-reset on read, and the HEALTH line below reuses all of them.
-            let decoded_total = stats.video_frames_decoded.load(Ordering::Relaxed);
-            let dropped_total = stats.video_frames_dropped.load(Ordering::Relaxed);
-            let net_stall = stats.net_stall_ms.swap(0, Ordering::Relaxed);
             // Per-side buffer depth relative to the frame being rendered
             // (absolute media pts on both sides). Negative = decoder behind
             // the picture (imminent starvation).
@@ -1157,30 +1485,15 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
                 stats.last_decoded_pts_ms.load(Ordering::Relaxed) - raw_pts_ms as i64;
             let a_ahead =
                 stats.audio_last_decoded_pts_ms.load(Ordering::Relaxed) - raw_pts_ms as i64;
-            let _ = events.send(PlayerEvent::Stats {
-                video_frames_decoded: decoded_total,
-                video_frames_dropped: dropped_total,
-                audio_underruns: stats.audio_underruns.load(Ordering::Relaxed),
-                net_stall_ms: net_stall,
-                decoder_name,
-                current_resolution: Some((frame_w, frame_h)),
-                audio_peak_db: audio_sink.last_peak_db(),
-                av_drift_ms: drift_out,
-                video_buffer_ahead_ms: v_ahead,
-                audio_buffer_ahead_ms: a_ahead,
-                video_segment: stats.video_segment_id.load(Ordering::Relaxed),
-                stall_events: stats.stall_events.load(Ordering::Relaxed),
-                pipeline_retries: stats.pipeline_retries.load(Ordering::Relaxed),
-                render_gap_max_ms: stats.render_gap_max_ms.load(Ordering::Relaxed),
-                judder_frames: stats.judder_frames.load(Ordering::Relaxed),
-                interval_hist: [
-                    stats.int_lt25.load(Ordering::Relaxed),
-                    stats.int_25_41.load(Ordering::Relaxed),
-                    stats.int_42_58.load(Ordering::Relaxed),
-                    stats.int_gt58.load(Ordering::Relaxed),
-                ],
-                bandwidth_bps: stats.bandwidth_bps_ewma.load(Ordering::Relaxed),
-            });
+            let (event, decoded_total, dropped_total, net_stall) = build_stats(
+                &stats,
+                audio_sink.last_peak_db(),
+                Some((frame_w, frame_h)),
+                drift_out,
+                v_ahead,
+                a_ahead,
+            );
+            let _ = events.send(event);
 
             // HEALTH heartbeat: a single warn line per second WHEN something
             // is off — frames dropped this second, A/V drift past ~2 frames,
@@ -1207,6 +1520,152 @@ async fn video_sync_loop<V: VideoSink, A: AudioSink>(
     }
 }
 
+/// One `PlayerEvent::Stats` off the shared counters, for `video_sync_loop`
+/// and `audio_clock_loop`; the loop supplies what only it knows. Also
+/// returns the decoded / dropped totals and the net stall it read, for
+/// the HEALTH line: `net_stall_ms` is swap-reset on read.
+fn build_stats(
+    stats: &StatsState,
+    peaks: Option<Vec<f32>>,
+    current_resolution: Option<(u32, u32)>,
+    av_drift_ms: Option<i64>,
+    video_buffer_ahead_ms: i64,
+    audio_buffer_ahead_ms: i64,
+) -> (PlayerEvent, u64, u64, u64) {
+    let decoded_total = stats.video_frames_decoded.load(Ordering::Relaxed);
+    let dropped_total = stats.video_frames_dropped.load(Ordering::Relaxed);
+    let net_stall = stats.net_stall_ms.swap(0, Ordering::Relaxed);
+    let event = PlayerEvent::Stats {
+        video_frames_decoded: decoded_total,
+        video_frames_dropped: dropped_total,
+        audio_underruns: stats.audio_underruns.load(Ordering::Relaxed),
+        net_stall_ms: net_stall,
+        decoder_name: stats.decoder_name.lock().unwrap().clone(),
+        current_resolution,
+        audio_peak_db: peaks.as_deref().map(stereo_peak_db),
+        audio_peak_db_channels: peaks,
+        audio_loudness_lufs: *stats.audio_loudness_lufs.lock().unwrap(),
+        av_drift_ms,
+        video_buffer_ahead_ms,
+        audio_buffer_ahead_ms,
+        video_segment: stats.video_segment_id.load(Ordering::Relaxed),
+        stall_events: stats.stall_events.load(Ordering::Relaxed),
+        pipeline_retries: stats.pipeline_retries.load(Ordering::Relaxed),
+        render_gap_max_ms: stats.render_gap_max_ms.load(Ordering::Relaxed),
+        judder_frames: stats.judder_frames.load(Ordering::Relaxed),
+        interval_hist: [
+            stats.int_lt25.load(Ordering::Relaxed),
+            stats.int_25_41.load(Ordering::Relaxed),
+            stats.int_42_58.load(Ordering::Relaxed),
+            stats.int_gt58.load(Ordering::Relaxed),
+        ],
+        bandwidth_bps: stats.bandwidth_bps_ewma.load(Ordering::Relaxed),
+    };
+    (event, decoded_total, dropped_total, net_stall)
+}
+
+/// Audio-only stand-in for `video_sync_loop`: with no frames to pace, it
+/// ticks off the same `MediaClock` to keep `position_ms`, the `Playing`
+/// transition and the rate-limited `Position` / `Stats` events going.
+/// Buffering is the audio sync loop's business here. Runs until
+/// av_sync_handler aborts it (the audio loop ended) or a stop.
+async fn audio_clock_loop<A: AudioSink>(
+    start_time: Arc<Instant>,
+    seek_offset: Duration,
+    audio_base_ms: u64,
+    audio_sink: Arc<A>,
+    position_ms: Arc<AtomicU64>,
+    stop: Arc<Notify>,
+    stop_flag: Arc<AtomicBool>,
+    events: Arc<broadcast::Sender<PlayerEvent>>,
+    media_duration: Duration,
+    paused: Arc<AtomicBool>,
+    pause_notify: Arc<Notify>,
+    stats: Arc<StatsState>,
+    timeline: Arc<RateTimeline>,
+) {
+    // Well under the 250 ms Position cadence.
+    const TICK: Duration = Duration::from_millis(50);
+    // Wall-fallback pause compensation, as in video_sync_loop.
+    let mut pause_skew = Duration::ZERO;
+    let mut emitted_playing = false;
+    let mut last_position_emit = Instant::now() - Duration::from_secs(1);
+    let mut last_stats_emit = Instant::now() - Duration::from_secs(1);
+    // No picture to line up with, so the lip-sync offset doesn't apply.
+    let clock = MediaClock::new(
+        audio_sink.clone(),
+        start_time,
+        seek_offset,
+        audio_base_ms,
+        timeline,
+        Arc::new(AtomicI32::new(0)),
+    );
+    log::info!("[aclock] loop start (seek_offset={}ms)", seek_offset.as_millis());
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            break;
+        }
+        if paused.load(Ordering::Relaxed) {
+            let pause_started = Instant::now();
+            tokio::select! {
+                _ = pause_notify.notified() => {}
+                _ = stop.notified() => break,
+            }
+            pause_skew += pause_started.elapsed();
+            // resume() leaves the Playing event to us (see video_sync_loop).
+            emitted_playing = false;
+            continue;
+        }
+        // Starving audio is paused at the sink, so the audio clock holds
+        // still on its own; the wall fallback needs the skew.
+        if stats.audio_starving.load(Ordering::Relaxed) {
+            let park_started = Instant::now();
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_millis(100)) => {}
+                _ = stop.notified() => break,
+            }
+            pause_skew += park_started.elapsed();
+            continue;
+        }
+
+        let pos_ms = (clock.now_us(pause_skew) / 1_000) as u64;
+        position_ms.store(pos_ms, Ordering::Relaxed);
+        if !emitted_playing {
+            let _ = events.send(PlayerEvent::Playing);
+            emitted_playing = true;
+        }
+
+        let audio_ahead =
+            stats.audio_last_decoded_pts_ms.load(Ordering::Relaxed) - pos_ms as i64;
+        if last_position_emit.elapsed() >= Duration::from_millis(250) {
+            let _ = events.send(PlayerEvent::Position {
+                position: Duration::from_millis(pos_ms),
+                duration: media_duration,
+                buffered_ahead_secs: audio_ahead.max(0) as f32 / 1000.0,
+                bandwidth_bps: stats.bandwidth_bps_ewma.load(Ordering::Relaxed),
+            });
+            last_position_emit = Instant::now();
+        }
+        if last_stats_emit.elapsed() >= Duration::from_secs(1) {
+            let (event, ..) = build_stats(
+                &stats,
+                audio_sink.last_peak_db(),
+                None,
+                None,
+                0,
+                audio_ahead,
+            );
+            let _ = events.send(event);
+            last_stats_emit = Instant::now();
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(TICK) => {}
+            _ = stop.notified() => break,
+        }
+    }
+}
+
 async fn audio_sync_loop<A: AudioSink>(
     mut input_rx: mpsc::Receiver<DecodedAudioFrame>,
     sink: Arc<A>,
@@ -1216,6 +1675,11 @@ async fn audio_sync_loop<A: AudioSink>(
     stats: Arc<StatsState>,
     events: Arc<broadcast::Sender<PlayerEvent>>,
     paused: Arc<AtomicBool>,
+    timeline: Arc<RateTimeline>,
+    playback_rate: Arc<AtomicU32>,
+    loudness: Arc<ArcSwap<LoudnessParams>>,
+    loudness_dsp: Arc<StdMutex<Option<LoudnessProcessor>>>,
+    audio_processors: Arc<AudioProcessorChain>,
 ) {
     // Align the FIRST audible sample with `target_pts_ms` (= video's
     // snapped seek offset). DASH audio and video segments rarely share
@@ -1231,8 +1695,24 @@ async fn audio_sync_loop<A: AudioSink>(
     // emitted sample corresponds to media-time `target_pts_ms`, the
     // same anchor video_sync_loop uses for its first rendered frame.
     let sample_rate = sink.sample_rate() as i64;
+    let channels = sink.channels().max(1) as usize;
+    let format_generation = sink.format_generation();
     let mut aligned = false;
+    // End PTS of the last frame handed on. An `audio_supervisor` retry
+    // restarts the pipeline at a segment boundary, re-delivering audio that
+    // was already pushed; once aligned, frames are spliced against this so
+    // playback continues forward instead of repeating.
+    let mut splice_ms = target_pts_ms;
     let mut starving = false;
+    // Playback rate: the stretch sits between alignment (which works in
+    // media time) and the sink. `out_frames` counts what has been pushed,
+    // so a rate change is recorded in the timeline at the exact output
+    // position the video clock will see it take effect.
+    let mut stretch = TimeStretch::new(sample_rate as u32, channels as u16);
+    let mut rate = timeline.latest_rate();
+    let mut out_frames = 0u64;
+    // A new pipeline is a discontinuity for the host effects.
+    audio_processors.reset();
     loop {
         if stop_flag.load(Ordering::Relaxed) {
             break;
@@ -1291,51 +1771,113 @@ async fn audio_sync_loop<A: AudioSink>(
             continue;
         }
         // Build the slice/owned buffer to actually hand off to cpal.
-        // Stereo interleaved: samples.len() / 2 = per-channel frames.
-        let trimmed: std::borrow::Cow<'_, [f32]> = if aligned {
-            std::borrow::Cow::Borrowed(&frame.samples)
+        // Interleaved: samples.len() / channels = per-channel frames.
+        let frames_per_chan = (frame.samples.len() / channels) as i64;
+        let dur_ms = if sample_rate > 0 {
+            frames_per_chan * 1000 / sample_rate
         } else {
-            let frames_per_chan = (frame.samples.len() / 2) as i64;
-            let dur_ms = if sample_rate > 0 {
-                frames_per_chan * 1000 / sample_rate
-            } else {
-                0
-            };
-            let frame_end_ms = frame.pts_ms + dur_ms;
-            if frame_end_ms <= target_pts_ms {
-                // Whole frame lies before the target — drop it.
+            0
+        };
+        let frame_end_ms = frame.pts_ms + dur_ms;
+        let trimmed: std::borrow::Cow<'_, [f32]> = if aligned {
+            // PTS rounding jitters contiguous frames by a millisecond or two;
+            // only a real overlap (a retry's re-delivery) is cut.
+            const SPLICE_TOLERANCE_MS: i64 = 10;
+            let overlap_ms = splice_ms - frame.pts_ms;
+            if overlap_ms <= SPLICE_TOLERANCE_MS {
+                std::borrow::Cow::Borrowed(&frame.samples)
+            } else if frame_end_ms <= splice_ms {
                 continue;
-            } else if frame.pts_ms < target_pts_ms {
-                // Frame straddles the target — trim leading samples.
-                let drop_ms = target_pts_ms - frame.pts_ms;
-                let drop_chan = (drop_ms * sample_rate / 1000) as usize;
-                let drop_idx = (drop_chan * 2).min(frame.samples.len());
-                aligned = true;
-                if drop_idx >= frame.samples.len() {
-                    continue;
-                }
+            } else {
+                let drop_idx =
+                    ((overlap_ms * sample_rate / 1000) as usize * channels).min(frame.samples.len());
                 std::borrow::Cow::Borrowed(&frame.samples[drop_idx..])
+            }
+        } else if frame_end_ms <= target_pts_ms {
+            // Whole frame lies before the target — drop it.
+            continue;
+        } else if frame.pts_ms < target_pts_ms {
+            // Frame straddles the target — trim leading samples.
+            let drop_ms = target_pts_ms - frame.pts_ms;
+            let drop_chan = (drop_ms * sample_rate / 1000) as usize;
+            let drop_idx = (drop_chan * channels).min(frame.samples.len());
+            aligned = true;
+            if drop_idx >= frame.samples.len() {
+                continue;
+            }
+            std::borrow::Cow::Borrowed(&frame.samples[drop_idx..])
+        } else {
+            // Frame starts at/after target — pad silence so the
+            // first audible sample lands on target.
+            let pad_ms = frame.pts_ms - target_pts_ms;
+            let pad_chan = (pad_ms * sample_rate / 1000) as usize;
+            aligned = true;
+            if pad_chan == 0 {
+                std::borrow::Cow::Borrowed(&frame.samples)
             } else {
-                // Frame starts at/after target — pad silence so the
-                // first audible sample lands on target.
-                let pad_ms = frame.pts_ms - target_pts_ms;
-                let pad_chan = (pad_ms * sample_rate / 1000) as usize;
-                aligned = true;
-                if pad_chan == 0 {
-                    std::borrow::Cow::Borrowed(&frame.samples)
-                } else {
-                    let mut buf = Vec::with_capacity(pad_chan * 2 + frame.samples.len());
-                    buf.resize(pad_chan * 2, 0.0_f32);
-                    buf.extend_from_slice(&frame.samples);
-                    std::borrow::Cow::Owned(buf)
-                }
+                let mut buf = Vec::with_capacity(pad_chan * channels + frame.samples.len());
+                buf.resize(pad_chan * channels, 0.0_f32);
+                buf.extend_from_slice(&frame.samples);
+                std::borrow::Cow::Owned(buf)
             }
         };
+        splice_ms = splice_ms.max(frame_end_ms);
         if trimmed.is_empty() {
             continue;
         }
+        let want = f32::from_bits(playback_rate.load(Ordering::Relaxed));
+        if want != rate && sample_rate > 0 {
+            timeline.push(out_frames as i64 * 1_000_000 / sample_rate, want);
+            log::info!("[async] playback rate {} -> {}", rate, want);
+            rate = want;
+        }
+        let stretched;
+        let out: &[f32] = if stretch.is_bypass(rate) {
+            &trimmed
+        } else {
+            stretched = stretch.process(&trimmed, rate);
+            if stretched.is_empty() {
+                continue;
+            }
+            &stretched
+        };
+        out_frames += (out.len() / channels) as u64;
+        // Loudness: the meter always runs (for Stats); gain is applied only
+        // when normalisation / night mode is on or still ramping back.
+        let leveled = {
+            let mut slot = loudness_dsp.lock().unwrap();
+            let (sr, ch) = (sample_rate as u32, channels as u16);
+            if !slot.as_ref().is_some_and(|d| d.matches(sr, ch)) {
+                *slot = Some(LoudnessProcessor::new(sr, ch));
+            }
+            let dsp = slot.as_mut().unwrap();
+            let leveled = dsp.process(out, &loudness.load());
+            *stats.audio_loudness_lufs.lock().unwrap() = dsp.integrated_lufs();
+            leveled
+        };
+        let out: &[f32] = leveled.as_deref().unwrap_or(out);
+        // Host effects. Their delay pushes the audible audio later, exactly
+        // like a deeper device buffer, so it is reported to the sink as
+        // output latency and the video clock follows. Passthrough carries
+        // no PCM: the chain sits idle and its latency is dropped.
+        let processed = if sink.is_passthrough() {
+            None
+        } else {
+            audio_processors.process(out, sample_rate as u32, channels as u16)
+        };
+        sink.set_processing_latency_us(if processed.is_some() {
+            audio_processors.latency_us()
+        } else {
+            0
+        });
+        let out: &[f32] = processed.as_deref().unwrap_or(out);
+        // The output reopened at another format (device switch): these
+        // samples no longer fit it. Drop them until the player rebuilds.
+        if sink.format_generation() != format_generation {
+            continue;
+        }
         tokio::select! {
-            _ = sink.put_samples(&trimmed) => {
+            _ = sink.put_samples(out) => {
                 stats.diag_audio_sunk.fetch_add(1, Ordering::Relaxed);
             }
             _ = stop.notified() => return,
@@ -1365,6 +1907,16 @@ async fn av_sync_handler<V: VideoSink, A: AudioSink>(
     pause_notify: Arc<Notify>,
     stats: Arc<StatsState>,
     pipeline_live: Arc<AtomicBool>,
+    playback_rate: Arc<AtomicU32>,
+    loudness: Arc<ArcSwap<LoudnessParams>>,
+    loudness_dsp: Arc<StdMutex<Option<LoudnessProcessor>>>,
+    audio_processors: Arc<AudioProcessorChain>,
+    av_offset_ms: Arc<AtomicI32>,
+    origin: Duration,
+    frame_step: Arc<FrameStep>,
+    // No video pipeline (`video_rx` is closed): gate on audio alone and let
+    // `audio_clock_loop` stand in for the video sync loop.
+    audio_only: bool,
 ) {
     // Emit Buffering{Initial} immediately so the consumer can show "buffering"
     // while the first segments download.
@@ -1409,26 +1961,30 @@ async fn av_sync_handler<V: VideoSink, A: AudioSink>(
     // Video MUST produce its first frame: the sync loop needs it to anchor the
     // clock base, and in direct mode the sync loop is also what releases the
     // codec's output buffers back to it.
-    tokio::select! {
-        _ = video_ready.notified() => {}
-        _ = stop.notified() => {
-            stop.notify_waiters();
-            return;
+    if !audio_only {
+        tokio::select! {
+            _ = video_ready.notified() => {}
+            _ = stop.notified() => {
+                stop.notify_waiters();
+                return;
+            }
         }
+        log::debug!("[av_sync] video_ready passed (seek_offset={}ms)", seek_offset.as_millis());
+        // First frame is out: the pipeline is live. Lets the ABR tick resume —
+        // any switch from here rebuilds a pipeline that's actually producing,
+        // not a half-started one.
+        pipeline_live.store(true, Ordering::Relaxed);
     }
-    log::debug!("[av_sync] video_ready passed (seek_offset={}ms)", seek_offset.as_millis());
-    // First frame is out: the pipeline is live. Lets the ABR tick resume —
-    // any switch from here rebuilds a pipeline that's actually producing,
-    // not a half-started one.
-    pipeline_live.store(true, Ordering::Relaxed);
     // Audio readiness is BOUNDED, not a hard gate: if it gated the sync loop's
     // start, a slow audio decoder after a seek/start-at-offset would keep the
     // loop from running — and in direct mode that means the video codec's
     // output buffers never get released, so it backpressure-stalls on
     // dequeue_input forever. A/V sync self-aligns once audio starts flowing.
+    // Audio-only there is no codec to protect and nothing else to anchor
+    // to, so it is a hard gate — and the point the pipeline goes live.
     tokio::select! {
         _ = audio_ready.notified() => {}
-        _ = tokio::time::sleep(Duration::from_secs(3)) => {
+        _ = tokio::time::sleep(Duration::from_secs(3)), if !audio_only => {
             log::warn!("[vsync] audio not ready after 3s — starting playback without waiting (guards the direct-mode video codec against a backpressure stall)");
         }
         _ = stop.notified() => {
@@ -1436,6 +1992,9 @@ async fn av_sync_handler<V: VideoSink, A: AudioSink>(
             return;
         }
     }
+    if audio_only {
+        pipeline_live.store(true, Ordering::Relaxed);
+    }
     // Unpause the audio device. AudioRenderer starts paused at construction
     // (cpal would otherwise pull from an empty mpsc and play silence while
     // the audio decoder warmed up, then "catch up" once real samples
@@ -1499,21 +2058,43 @@ async fn av_sync_handler<V: VideoSink, A: AudioSink>(
     } else {
         audio_sink.played_ms().unwrap_or(0)
     };
-    log::debug!("[av_sync] spawning sync loops (audio_base={}ms)", audio_base_ms);
+    // Bitstream passthrough plays at 1.0 regardless of the knob (it can't be
+    // stretched; set_playback_rate rebuilds onto PCM), so its clock must too.
+    let initial_rate = if audio_sink.is_passthrough() {
+        1.0
+    } else {
+        f32::from_bits(playback_rate.load(Ordering::Relaxed))
+    };
+    let timeline = Arc::new(RateTimeline::new(seek_offset.as_micros() as i64, initial_rate));
+    log::debug!(
+        "[av_sync] spawning sync loops (audio_base={}ms, rate={})",
+        audio_base_ms, initial_rate
+    );
     let stats_audio = Arc::clone(&stats);
     let events_audio = Arc::clone(&events);
     let paused_audio = Arc::clone(&paused);
     let end_position = Arc::clone(&position_ms);
-    let (_, _) = tokio::join!(
-        tokio::spawn(video_sync_loop(
-            gen,
-            start_time.clone(),
+    let audio_task = tokio::spawn(audio_sync_loop(
+        audio_rx,
+        audio_sink.clone(),
+        seek_offset.as_millis() as i64,
+        stop.clone(),
+        stop_flag.clone(),
+        stats_audio,
+        events_audio,
+        paused_audio,
+        Arc::clone(&timeline),
+        playback_rate,
+        loudness,
+        loudness_dsp,
+        audio_processors,
+    ));
+    if audio_only {
+        let clock_task = tokio::spawn(audio_clock_loop(
+            start_time,
             seek_offset,
             audio_base_ms,
-            video_discard_below_us,
-            video_rx,
-            video_sink,
-            audio_sink.clone(),
+            audio_sink,
             position_ms,
             stop.clone(),
             stop_flag.clone(),
@@ -1522,18 +2103,39 @@ async fn av_sync_handler<V: VideoSink, A: AudioSink>(
             paused,
             pause_notify,
             stats,
-        )),
-        tokio::spawn(audio_sync_loop(
-            audio_rx,
-            audio_sink,
-            seek_offset.as_millis() as i64,
-            stop.clone(),
-            stop_flag.clone(),
-            stats_audio,
-            events_audio,
-            paused_audio,
-        )),
-    );
+            timeline,
+        ));
+        // The clock has nothing of its own to run out of: it ends with the
+        // audio (EOF or stop).
+        let _ = audio_task.await;
+        clock_task.abort();
+    } else {
+        let (_, _) = tokio::join!(
+            tokio::spawn(video_sync_loop(
+                gen,
+                start_time.clone(),
+                seek_offset,
+                audio_base_ms,
+                video_discard_below_us,
+                video_rx,
+                video_sink,
+                audio_sink,
+                position_ms,
+                stop.clone(),
+                stop_flag.clone(),
+                events.clone(),
+                media_duration,
+                paused,
+                pause_notify,
+                stats,
+                timeline,
+                av_offset_ms,
+                origin,
+                frame_step,
+            )),
+            audio_task,
+        );
+    }
     // Both loops returning naturally (channels closed by decoder EOF) means
     // we hit end-of-stream. If we were stopped explicitly, the consumer is
     // tearing down and doesn't care about EndOfStream.
@@ -1592,6 +2194,9 @@ async fn video_decoder_task(
     // (frames at/below `skip_below_pts_us`) so the splice is forward-contiguous,
     // and stamps the first-frame-after-teardown timing log. `None` initially.
     splice: Option<SwapSplice>,
+    // Closed-caption input for this pipeline; `None` for codecs without
+    // A/53 SEI.
+    captions: Option<CaptionFeed>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let mut first_frame_signaled = false;
 
@@ -1627,33 +2232,16 @@ async fn video_decoder_task(
         move |segment: DataSegment| {
             let init_data = Arc::clone(&init_data);
             let crypto = crypto.clone();
+            let captions = captions.clone();
             tokio::task::spawn_blocking(
                 move || -> Result<PreparedSegment, Box<dyn Error + Send + Sync>> {
-                    let mut data_vec =
-                        Vec::with_capacity(init_data.len() + segment.data.len());
-                    data_vec.extend_from_slice(&init_data);
-                    data_vec.extend_from_slice(&segment.data[..]);
-                    decrypt_segment_in_place(&mut data_vec, crypto.as_ref())?;
-                    let sample_info: Vec<(usize, usize, i64, u64)> = {
-                        let mp4 = Mp4::read_bytes(&data_vec).map_err(
-                            |e| -> Box<dyn Error + Send + Sync> { format!("mp4: {}", e).into() },
-                        )?;
-                        let (_id, track) = mp4.tracks().first_key_value().ok_or_else(
-                            || -> Box<dyn Error + Send + Sync> { "no track".into() },
-                        )?;
-                        track
-                            .samples
-                            .iter()
-                            .map(|s| {
-                                (
-                                    s.offset as usize,
-                                    s.size as usize,
-                                    s.composition_timestamp,
-                                    s.timescale,
-                                )
-                            })
-                            .collect()
-                    };
+                    let (data_vec, sample_info) =
+                        fragment_samples(&init_data, &segment.data, crypto.as_ref())?;
+                    // Captions are decoded a segment ahead of display;
+                    // their cues carry the PTS they show at.
+                    if let Some(captions) = &captions {
+                        captions.feed_segment(&data_vec, &sample_info);
+                    }
                     Ok(PreparedSegment {
                         id: segment.id,
                         data_vec,
@@ -1895,6 +2483,7 @@ async fn drain_video_decoder(
     Ok(true)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn audio_decoder_task(
     mut receiver: Receiver<DataSegment>,
     sender: Sender<DecodedAudioFrame>,
@@ -1903,6 +2492,13 @@ async fn audio_decoder_task(
     audio_ready: Arc<Notify>,
     track_crypto: Option<TrackCrypto>,
     stats: Arc<StatsState>,
+    mut trim_frames: usize,
+    // The first segment only primes the decoder (Opus seek pre-roll): just
+    // its last `OPUS_SEEK_PREROLL` is decoded, and nothing before the next
+    // segment's first sample is played.
+    mut preroll: bool,
+    output_sample_rate: u32,
+    output_channels: u16,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     // Mirror the video pattern: fire audio_ready only once we've
     // actually produced + queued a real PCM frame, not on the first
@@ -1911,6 +2507,8 @@ async fn audio_decoder_task(
     // started hearing real samples 20-200 ms after video began
     // rendering — perceived as constant audio lag.
     let mut first_audio_signaled = false;
+    // Decoded frames before this PTS are pre-roll output and dropped.
+    let mut play_from_ms = i64::MIN;
     while let Some(segment) = receiver.recv().await {
         log::debug!("[dec] consuming audio segment: {}", segment.id);
         stats.diag_audio_seg.fetch_add(1, Ordering::Relaxed);
@@ -1953,18 +2551,57 @@ async fn audio_decoder_task(
             },
         )?;
 
+        let sample_pts_us = |ts: i64, ts_scale: u64| {
+            if ts_scale > 0 {
+                ts * 1_000_000 / ts_scale as i64
+            } else {
+                0
+            }
+        };
+        let mut submit_from_us = i64::MIN;
+        if std::mem::take(&mut preroll) {
+            let last_us = sample_info
+                .iter()
+                .map(|s| sample_pts_us(s.2, s.3))
+                .max()
+                .unwrap_or(0);
+            submit_from_us =
+                last_us - i64::from(OPUS_SEEK_PREROLL) * 1_000_000 / i64::from(OPUS_DECODE_RATE);
+            play_from_ms = i64::MAX;
+        } else if play_from_ms == i64::MAX {
+            play_from_ms = sample_info
+                .iter()
+                .map(|s| sample_pts_us(s.2, s.3) / 1000)
+                .min()
+                .unwrap_or(i64::MIN);
+        }
+
         for (offset, size, ts, ts_scale) in sample_info {
             if offset + size > data_vec.len() {
                 continue;
             }
             let sample_data = &data_vec[offset..offset + size];
-            let pts_us = if ts_scale > 0 { ts * 1_000_000 / ts_scale as i64 } else { 0 };
+            let pts_us = sample_pts_us(ts, ts_scale);
+            if pts_us < submit_from_us {
+                continue;
+            }
 
             decoder.submit(sample_data, pts_us)?;
 
             loop {
                 match decoder.try_recv()? {
-                    Some(frame) => {
+                    Some(mut frame) => {
+                        if frame.pts_ms < play_from_ms {
+                            continue;
+                        }
+                        if !trim_leading_frames(
+                            &mut frame,
+                            &mut trim_frames,
+                            output_sample_rate,
+                            output_channels,
+                        ) {
+                            continue;
+                        }
                         let pts_ms = frame.pts_ms;
                         let prev = stats
                             .audio_last_decoded_pts_ms
@@ -1991,6 +2628,40 @@ async fn audio_decoder_task(
     Ok(())
 }
 
+/// `Stats.audio_peak_db` from the per-channel peaks: front L/R (the first
+/// two channels in every device layout), a mono device's channel twice.
+fn stereo_peak_db(peaks: &[f32]) -> [f32; 2] {
+    match peaks {
+        [] => [-120.0; 2],
+        [mono] => [*mono; 2],
+        [l, r, ..] => [*l, *r],
+    }
+}
+
+/// Drop up to `remaining` leading frames (of `channels` interleaved samples)
+/// from `frame` (decoder priming / pre-roll), advancing its pts past them.
+/// Returns false when the whole frame was consumed and nothing is left to
+/// play.
+fn trim_leading_frames(
+    frame: &mut DecodedAudioFrame,
+    remaining: &mut usize,
+    sample_rate: u32,
+    channels: u16,
+) -> bool {
+    if *remaining == 0 {
+        return true;
+    }
+    let channels = channels.max(1) as usize;
+    let frames = frame.samples.len() / channels;
+    let dropped = frames.min(*remaining);
+    *remaining -= dropped;
+    frame.samples.drain(..dropped * channels);
+    if sample_rate > 0 {
+        frame.pts_ms += (dropped as u64 * 1000 / u64::from(sample_rate)) as i64;
+    }
+    !frame.samples.is_empty()
+}
+
 // ---------------------------------------------------------------------------
 // Download + decode pipeline builders (renderer-agnostic)
 // ---------------------------------------------------------------------------
@@ -2010,7 +2681,9 @@ struct VideoPrefetch {
     width: u32,
     height: u32,
     init_data: Vec<u8>,
-    hvcc_nalus: Vec<Vec<u8>>,
+    codec: VideoCodec,
+    parameter_sets: Vec<Vec<u8>>,
+    config_record: Vec<u8>,
     color: VideoColorInfo,
     dovi_profile: Option<u8>,
     track_crypto: Option<TrackCrypto>,
@@ -2021,37 +2694,69 @@ struct VideoPrefetch {
     primed: Arc<Notify>,
 }
 
-/// Download half of a video pipeline: fetch + parse the init segment, resolve
-/// the CENC key, and spawn [`download_task`] streaming media segments into a
-/// bounded channel. Touches the network only — never the HW decoder — so it is
-/// safe to run concurrently with another representation's live decoder.
-#[allow(clippy::too_many_arguments)]
-async fn video_prefetch(
+/// A video representation's init segment, parsed into everything the decoder
+/// needs: codec + parameter sets, colour, Dolby Vision profile and the CENC
+/// key for its samples.
+struct VideoInit {
+    init_data: Vec<u8>,
+    codec: VideoCodec,
+    parameter_sets: Vec<Vec<u8>>,
+    config_record: Vec<u8>,
+    color: VideoColorInfo,
+    dovi_profile: Option<u8>,
+    track_crypto: Option<TrackCrypto>,
+}
+
+/// Fetch + parse `repr`'s init segment and resolve its CENC key. Shared by
+/// [`video_prefetch`] and trick play, which decodes the same representations
+/// without a download pipeline.
+async fn video_init(
     repr: &VideoRepresenation,
-    start_index: usize,
-    stop: Arc<Notify>,
-    stop_flag: Arc<AtomicBool>,
     decryptor: Option<Arc<dyn Decryptor>>,
-    http: Arc<HttpClient>,
-    stats: Arc<StatsState>,
-    segments_in_flight: usize,
-    soft_end_exclusive: Arc<AtomicUsize>,
-    // Notify `primed` once this many segments are buffered. `usize::MAX` means
-    // "never signal" — used for the initial pipeline, which has no OLD to
-    // overlap and so decodes immediately.
-    prime_target: usize,
-) -> Result<VideoPrefetch, Box<dyn Error + Send + Sync>> {
-    let (download_tx, download_rx) = mpsc::channel::<DataSegment>(segments_in_flight);
-
+    http: &HttpClient,
+) -> Result<VideoInit, Box<dyn Error + Send + Sync>> {
     let init_dl = repr
         .segment_init
-        .download(&http, RequestKind::InitSegment)
+        .download(http, RequestKind::InitSegment)
         .await
         .map_err(|e| -> Box<dyn Error + Send + Sync> { format!("init download: {}", e).into() })?;
     let init_data = init_dl.data;
 
-    let hvcc_nalus = parse_hvcc_nalus(&init_data)
-        .ok_or_else(|| -> Box<dyn Error + Send + Sync> { "no hvcC in init segment".into() })?;
+    // The decoder configuration box decides the codec: hvcC → HEVC (incl.
+    // Dolby Vision dvh1/dvhe), avcC → H.264, av1C → AV1, vpcC → VP9. Each
+    // yields its parameter sets (or, for AV1, the raw record) plus the colour
+    // info from the SPS / sequence header / vpcC and the record's own bit
+    // depth as fallback.
+    let (codec, parameter_sets, config_record, sps_color, config_bit_depth) =
+        if let Some(nalus) = parse_hvcc_nalus(&init_data) {
+            let sps_color = crate::parsers::hevc::parse_sps_color_info(&nalus);
+            (VideoCodec::Hevc, nalus, Vec::new(), sps_color, parse_hvcc_bit_depth(&init_data))
+        } else if let Some(avcc) = parse_avcc(&init_data) {
+            // Samples are split with 4-byte NALU lengths (parse_hevc_nalu);
+            // 1/2-byte avcC framing is legal but unseen in DASH packaging.
+            if avcc.nal_length_size != 4 {
+                return Err(format!(
+                    "avcC lengthSizeMinusOne={} unsupported (need 4-byte NALU lengths)",
+                    avcc.nal_length_size - 1
+                )
+                .into());
+            }
+            let nalus = avcc.parameter_sets();
+            let sps_color = crate::parsers::avc::parse_sps_color_info(&nalus);
+            (VideoCodec::H264, nalus, Vec::new(), sps_color, avcc.bit_depth_luma)
+        } else if let Some((av1c, record)) = parse_av1c(&init_data) {
+            // configOBUs is optional in av1C; without it the colour info
+            // comes from the record's bit depth alone until the decoder sees
+            // the in-band sequence header.
+            let sps_color = crate::parsers::av1::parse_av1c_color_info(&av1c.config_obus);
+            (VideoCodec::Av1, Vec::new(), record, sps_color, Some(av1c.bit_depth))
+        } else if let Some(vpcc) = parse_vpcc(&init_data) {
+            // No SPS equivalent: vpcC's H.273 fields are the only place a
+            // Profile 2 stream says PQ vs HLG, so they ARE the colour info.
+            (VideoCodec::Vp9, Vec::new(), Vec::new(), Some(vpcc.color), Some(vpcc.bit_depth))
+        } else {
+            return Err("no hvcC/avcC/av1C/vpcC in init segment".into());
+        };
 
     // Dolby Vision policy: profiles 7/8 carry a decodable HEVC base layer
     // (HDR10/SDR/HLG-compatible, correctly signalled in the SPS VUI), so
@@ -2092,30 +2797,73 @@ async fn video_prefetch(
 
     // Colour info comes from the SPS VUI — the MPD is not trustworthy here
     // (our test stream signals BT.709 on PQ representations). Fall back to
-    // the hvcC bit depth when the SPS doesn't parse.
-    let sps_color = crate::parsers::hevc::parse_sps_color_info(&hvcc_nalus);
-    let color = VideoColorInfo::from_sps(sps_color, parse_hvcc_bit_depth(&init_data));
+    // the hvcC/avcC/av1C/vpcC bit depth when the SPS doesn't parse.
+    let color = VideoColorInfo::from_sps(sps_color, config_bit_depth);
     if color.bit_depth != 8 || color.is_hdr() {
         log::info!(
-            "video: {}-bit, transfer={:?}, bt2020={}, full_range={} (SPS VUI {})",
+            "video: {:?} {}-bit, transfer={:?}, bt2020={}, full_range={} (SPS VUI {})",
+            codec,
             color.bit_depth,
             color.transfer,
             color.bt2020,
             color.full_range,
-            if sps_color.is_some() { "parsed" } else { "missing — hvcC fallback" },
+            if sps_color.is_some() { "parsed" } else { "missing — config record fallback" },
         );
     }
 
     let track_crypto = setup_track_crypto(&init_data, decryptor, "video").await?;
 
-    let segments = repr.segments.clone();
-    let primed = Arc::new(Notify::new());
-    let dl_stats = Arc::clone(&stats);
-    let cb_primed = Arc::clone(&primed);
-    let downloaded = Arc::new(AtomicUsize::new(0));
-    // Only the initial / OLD pipeline (prime_target == MAX) advances the
-    // download-time decoded-PTS high-water. A swap-prefetch must NOT touch it:
-    // the supervisor reads last_decoded_pts_ms at OLD teardown as the splice
+    Ok(VideoInit {
+        init_data,
+        codec,
+        parameter_sets,
+        config_record,
+        color,
+        dovi_profile,
+        track_crypto,
+    })
+}
+
+/// Download half of a video pipeline: fetch + parse the init segment, resolve
+/// the CENC key, and spawn [`download_task`] streaming media segments into a
+/// bounded channel. Touches the network only — never the HW decoder — so it is
+/// safe to run concurrently with another representation's live decoder.
+#[allow(clippy::too_many_arguments)]
+async fn video_prefetch(
+    repr: &VideoRepresenation,
+    start_index: usize,
+    stop: Arc<Notify>,
+    stop_flag: Arc<AtomicBool>,
+    decryptor: Option<Arc<dyn Decryptor>>,
+    http: Arc<HttpClient>,
+    stats: Arc<StatsState>,
+    segments_in_flight: usize,
+    soft_end_exclusive: Arc<AtomicUsize>,
+    // Notify `primed` once this many segments are buffered. `usize::MAX` means
+    // "never signal" — used for the initial pipeline, which has no OLD to
+    // overlap and so decodes immediately.
+    prime_target: usize,
+) -> Result<VideoPrefetch, Box<dyn Error + Send + Sync>> {
+    let (download_tx, download_rx) = mpsc::channel::<DataSegment>(segments_in_flight);
+
+    let VideoInit {
+        init_data,
+        codec,
+        parameter_sets,
+        config_record,
+        color,
+        dovi_profile,
+        track_crypto,
+    } = video_init(repr, decryptor, &http).await?;
+
+    let segments = repr.segments.clone();
+    let primed = Arc::new(Notify::new());
+    let dl_stats = Arc::clone(&stats);
+    let cb_primed = Arc::clone(&primed);
+    let downloaded = Arc::new(AtomicUsize::new(0));
+    // Only the initial / OLD pipeline (prime_target == MAX) advances the
+    // download-time decoded-PTS high-water. A swap-prefetch must NOT touch it:
+    // the supervisor reads last_decoded_pts_ms at OLD teardown as the splice
     // point, and NEW's downloaded-ahead segments would otherwise inflate it.
     let track_dl_pts = prime_target == usize::MAX;
     let on_video_dl: SegmentDoneCallback = Arc::new(move |pts_ms| {
@@ -2149,7 +2897,9 @@ async fn video_prefetch(
         width: repr.width,
         height: repr.height,
         init_data,
-        hvcc_nalus,
+        codec,
+        parameter_sets,
+        config_record,
         color,
         dovi_profile,
         track_crypto,
@@ -2309,6 +3059,35 @@ struct SwapSplice {
     skip_below_pts_us: i64,
 }
 
+/// Join `init` + one media `fragment`, CENC-decrypt it in place and list its
+/// samples as `(offset, size, composition_timestamp, timescale)` into the
+/// joined buffer.
+#[allow(clippy::type_complexity)]
+fn fragment_samples(
+    init: &[u8],
+    fragment: &[u8],
+    crypto: Option<&TrackCrypto>,
+) -> Result<(Vec<u8>, Vec<(usize, usize, i64, u64)>), Box<dyn Error + Send + Sync>> {
+    let mut data_vec = Vec::with_capacity(init.len() + fragment.len());
+    data_vec.extend_from_slice(init);
+    data_vec.extend_from_slice(fragment);
+    decrypt_segment_in_place(&mut data_vec, crypto)?;
+    let sample_info = {
+        let mp4 = Mp4::read_bytes(&data_vec)
+            .map_err(|e| -> Box<dyn Error + Send + Sync> { format!("mp4: {}", e).into() })?;
+        let (_id, track) = mp4
+            .tracks()
+            .first_key_value()
+            .ok_or_else(|| -> Box<dyn Error + Send + Sync> { "no track".into() })?;
+        track
+            .samples
+            .iter()
+            .map(|s| (s.offset as usize, s.size as usize, s.composition_timestamp, s.timescale))
+            .collect()
+    };
+    Ok((data_vec, sample_info))
+}
+
 /// Decode half: configure the HW decoder and run [`video_decoder_task`] against
 /// the segments [`video_prefetch`] is already streaming, joining both halves to
 /// completion. `decoder` (the scarce HW slot) must be created by the caller
@@ -2327,17 +3106,28 @@ async fn run_decode(
     // Player-level HDR-to-8-bit decode switch, sampled here at configure
     // time so ABR swaps / retries pick up a changed value.
     hdr_decode_8bit: Arc<AtomicBool>,
+    closed_captions: Arc<ClosedCaptions>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    // A/53 captions ride in H.264 / HEVC SEI only.
+    let captions = match pf.codec {
+        VideoCodec::Hevc => Some(closed_captions.restart(true)),
+        VideoCodec::H264 => Some(closed_captions.restart(false)),
+        VideoCodec::Av1 | VideoCodec::Vp9 => None,
+    };
     decoder.configure(VideoDecoderParams {
-        codec: VideoCodec::Hevc,
+        codec: pf.codec,
         width: pf.width,
         height: pf.height,
-        hvcc_nalus: pf.hvcc_nalus,
+        parameter_sets: pf.parameter_sets,
+        config_record: pf.config_record,
         color: pf.color,
         direct_window,
         dovi_profile: pf.dovi_profile,
         force_8bit_hdr: hdr_decode_8bit.load(Ordering::Relaxed),
     })?;
+    // configure() may settle on a different backend than the factory probe
+    // reported (AV1 via dav1d on a GPU without AV1 decode).
+    *stats.decoder_name.lock().unwrap() = decoder.name().to_string();
     // Direct mode: let the input-buffer spin observe teardown so a seek /
     // track-switch can't strand the decode task in the spin (see [B] in
     // mediacodec submit_direct).
@@ -2353,6 +3143,7 @@ async fn run_decode(
         stats,
         decoder_stop_flag,
         splice,
+        captions,
     ));
 
     let (dl_res, dec_res) = join!(pf.download_handle, decoder_task);
@@ -2368,6 +3159,277 @@ async fn run_decode(
     }
 }
 
+/// Bytes fetched up front for a main-representation keyframe: the `moof`
+/// plus, for most HD subsegments, the whole first sample. A larger keyframe
+/// costs one more range request for the rest.
+const TRICK_PREFIX_BYTES: u64 = 64 * 1024;
+
+/// Idle period of the trick-play loop between keyframe checks.
+const TRICK_TICK: Duration = Duration::from_millis(40);
+
+/// How long trick play waits for a submitted keyframe to come out of the
+/// decoder before moving on (the next keyframe pushes it out).
+const TRICK_DECODE_WAIT: Duration = Duration::from_millis(150);
+
+/// Trick play's virtual media clock: `speed` × wall time from an anchor
+/// (absolute µs), re-anchored whenever the effective speed changes — 0
+/// while paused, so the picture holds.
+struct TrickClock {
+    anchor_us: i64,
+    since: Instant,
+    speed: i32,
+}
+
+impl TrickClock {
+    fn new(at_us: i64, now: Instant) -> Self {
+        TrickClock {
+            anchor_us: at_us,
+            since: now,
+            speed: 0,
+        }
+    }
+
+    fn at(&self, now: Instant) -> i64 {
+        let elapsed_us = now.saturating_duration_since(self.since).as_micros() as i64;
+        self.anchor_us + self.speed as i64 * elapsed_us
+    }
+
+    fn set_speed(&mut self, speed: i32, now: Instant) {
+        if speed != self.speed {
+            self.anchor_us = self.at(now);
+            self.since = now;
+            self.speed = speed;
+        }
+    }
+}
+
+/// Segment trick play shows at absolute media time `at`: the last one
+/// starting at/before it on a SAP (sidx `starts_with_SAP`), else the first
+/// after it. Without any SAP flags (SegmentTemplate, SegmentList) every
+/// segment counts — DASH segments open on a SAP so representations can
+/// switch at their boundaries. `None` without segments.
+fn trick_keyframe_index(segments: &[Segment], at: Duration) -> Option<usize> {
+    if segments.is_empty() {
+        return None;
+    }
+    let idx = find_segment_index(segments, at);
+    if !segments.iter().any(Segment::starts_with_sap) {
+        return Some(idx);
+    }
+    segments[..=idx]
+        .iter()
+        .rposition(Segment::starts_with_sap)
+        .or_else(|| segments[idx..].iter().position(Segment::starts_with_sap).map(|i| idx + i))
+        .or(Some(idx))
+}
+
+/// Trick-mode representation to scan instead of a main one `height` tall:
+/// the richest one no taller, else the smallest. Ones without segments
+/// are skipped.
+fn pick_trick_representation(
+    trick: &[VideoRepresenation],
+    height: u32,
+) -> Option<&VideoRepresenation> {
+    let usable = || trick.iter().filter(|r| !r.segments.is_empty());
+    usable()
+        .filter(|r| r.height <= height)
+        .max_by_key(|r| r.bandwidth)
+        .or_else(|| usable().min_by_key(|r| r.bandwidth))
+}
+
+/// First sample of `segment` — its keyframe when the segment opens on a SAP
+/// — decrypted. Range-requests just the `moof` and that sample; falls back
+/// to the whole segment when the `moof` doesn't pin the sample down.
+async fn fetch_keyframe(
+    segment: &Segment,
+    init: &[u8],
+    http: &HttpClient,
+    crypto: Option<&TrackCrypto>,
+) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+    let mut buf = segment
+        .download_span(http, RequestKind::Segment, 0, TRICK_PREFIX_BYTES)
+        .await?
+        .data;
+    let Some(first) = locate_first_sample(&buf) else {
+        let full = segment.download(http, RequestKind::Segment).await?;
+        let (data, samples) = fragment_samples(init, &full.data, crypto)?;
+        let &(offset, size, ..) = samples.first().ok_or("trick play: segment has no samples")?;
+        let sample = data
+            .get(offset..offset + size)
+            .ok_or("trick play: sample past segment end")?;
+        return Ok(sample.to_vec());
+    };
+    if buf.len() < first.data.end {
+        let missing = (first.data.end - buf.len()) as u64;
+        let tail = segment
+            .download_span(http, RequestKind::Segment, buf.len() as u64, missing)
+            .await?;
+        buf.extend_from_slice(&tail.data);
+    }
+    let mut sample = buf
+        .get(first.data.clone())
+        .ok_or("trick play: keyframe past segment end")?
+        .to_vec();
+    if let Some(tc) = crypto {
+        // No senc = a clear segment (see decrypt_segment_in_place).
+        if let Some(entry) = parse_senc(&buf[..first.moof.end], tc.iv_size)
+            .and_then(|entries| entries.into_iter().next())
+        {
+            decrypt_sample_in_place(tc, &entry, &mut sample)?;
+        }
+    }
+    Ok(sample)
+}
+
+/// Keyframe-only trick play (`Player::set_trick_play`). A virtual clock runs
+/// at the requested speed from `start` (0-based) and the keyframe at/before
+/// it is fetched, decoded and shown at once — nothing in between is
+/// downloaded. From a trick-mode representation (`from_trick`) whole
+/// segments are fetched and each of their samples shown as the clock passes
+/// it; from a main one only the first sample of each SAP segment. Speed
+/// changes apply live, a pause holds the clock; no audio runs. Returns the
+/// 0-based position on screen when the clock runs off either end of the
+/// content, `None` when stopped (seek, exit, track switch); Err when `repr`
+/// has no segments to scan.
+#[allow(clippy::too_many_arguments)]
+async fn trick_play<V: VideoSink>(
+    repr: VideoRepresenation,
+    from_trick: bool,
+    start: Duration,
+    origin: Duration,
+    media_duration: Duration,
+    trick_speed: Arc<AtomicI32>,
+    paused: Arc<AtomicBool>,
+    stop: Arc<Notify>,
+    stop_flag: Arc<AtomicBool>,
+    decryptor: Option<Arc<dyn Decryptor>>,
+    mut decoder: Box<dyn HwVideoDecoder>,
+    http: Arc<HttpClient>,
+    video_sink: Arc<V>,
+    position_ms: Arc<AtomicU64>,
+    events: Arc<broadcast::Sender<PlayerEvent>>,
+    stats: Arc<StatsState>,
+    direct_window: usize,
+    force_8bit_hdr: bool,
+) -> Result<Option<Duration>, Box<dyn Error + Send + Sync>> {
+    let init = video_init(&repr, decryptor, &http).await?;
+    decoder.configure(VideoDecoderParams {
+        codec: init.codec,
+        width: repr.width,
+        height: repr.height,
+        parameter_sets: init.parameter_sets,
+        config_record: init.config_record,
+        color: init.color,
+        direct_window,
+        dovi_profile: init.dovi_profile,
+        force_8bit_hdr,
+    })?;
+    *stats.decoder_name.lock().unwrap() = decoder.name().to_string();
+    decoder.set_stop_signal(Arc::clone(&stop_flag));
+
+    let origin_us = origin.as_micros() as i64;
+    let end_us = origin_us + media_duration.as_micros() as i64;
+    let mut clock = TrickClock::new(origin_us + start.as_micros() as i64, Instant::now());
+    // Trick-mode segment fetched last: (index, init + segment, samples).
+    #[allow(clippy::type_complexity)]
+    let mut fetched: Option<(usize, Vec<u8>, Vec<(usize, usize, i64, u64)>)> = None;
+    // PTS (absolute µs) of the sample submitted last.
+    let mut shown_us: Option<i64> = None;
+    let mut last_position_emit: Option<Instant> = None;
+    loop {
+        if stop_flag.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        // Speed 0 = exit requested; the seek that follows stops us.
+        let speed = trick_speed.load(Ordering::Relaxed);
+        let now = Instant::now();
+        clock.set_speed(if paused.load(Ordering::Relaxed) { 0 } else { speed }, now);
+        let at_us = clock.at(now).clamp(origin_us, end_us);
+        let at_boundary = (speed < 0 && at_us <= origin_us) || (speed > 0 && at_us >= end_us);
+
+        let idx = trick_keyframe_index(&repr.segments, Duration::from_micros(at_us as u64))
+            .ok_or("trick play: representation has no segments")?;
+        let next: Option<(i64, Vec<u8>)> = if from_trick {
+            if fetched.as_ref().map(|f| f.0) != Some(idx) {
+                let dl = repr.segments[idx].download(&http, RequestKind::Segment).await?;
+                let (data, samples) =
+                    fragment_samples(&init.init_data, &dl.data, init.track_crypto.as_ref())?;
+                fetched = Some((idx, data, samples));
+            }
+            let (_, data, samples) = fetched.as_ref().unwrap();
+            let mut timed: Vec<(i64, usize, usize)> = samples
+                .iter()
+                .filter(|&&(offset, size, ..)| offset + size <= data.len())
+                .map(|&(offset, size, ts, scale)| {
+                    let pts_us = if scale > 0 { ts * 1_000_000 / scale as i64 } else { 0 };
+                    (pts_us, offset, size)
+                })
+                .collect();
+            timed.sort_unstable();
+            let pick = timed
+                .iter()
+                .rev()
+                .find(|s| s.0 <= at_us)
+                .or_else(|| timed.first());
+            pick.filter(|s| Some(s.0) != shown_us)
+                .map(|&(pts_us, offset, size)| (pts_us, data[offset..offset + size].to_vec()))
+        } else {
+            let segment = &repr.segments[idx];
+            let pts_us = segment.start_time().as_micros() as i64;
+            if Some(pts_us) == shown_us {
+                None
+            } else {
+                let sample =
+                    fetch_keyframe(segment, &init.init_data, &http, init.track_crypto.as_ref())
+                        .await?;
+                Some((pts_us, sample))
+            }
+        };
+
+        if let Some((pts_us, sample)) = next {
+            if stop_flag.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
+            shown_us = Some(pts_us);
+            decoder.submit(&sample, pts_us)?;
+            let deadline = Instant::now() + TRICK_DECODE_WAIT;
+            let mut presented = false;
+            loop {
+                while let Some(mut frame) = decoder.try_recv()? {
+                    presented = true;
+                    let pos_ms = ((frame.pts_us - origin_us).max(0) / 1000) as u64;
+                    position_ms.store(pos_ms, Ordering::Relaxed);
+                    video_sink.set_subtitle_pts(pos_ms as i64);
+                    frame.desired_present_ns = clock_monotonic_ns();
+                    video_sink.render_frame(frame).await;
+                    stats.video_frames_decoded.fetch_add(1, Ordering::Relaxed);
+                }
+                if presented || Instant::now() >= deadline || stop_flag.load(Ordering::Relaxed) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+
+        if last_position_emit.is_none_or(|t| t.elapsed() >= Duration::from_millis(250)) {
+            let _ = events.send(PlayerEvent::Position {
+                position: Duration::from_millis(position_ms.load(Ordering::Relaxed)),
+                duration: media_duration,
+                buffered_ahead_secs: 0.0,
+                bandwidth_bps: stats.bandwidth_bps_ewma.load(Ordering::Relaxed),
+            });
+            last_position_emit = Some(Instant::now());
+        }
+        if at_boundary {
+            return Ok(Some(Duration::from_millis(position_ms.load(Ordering::Relaxed))));
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(TRICK_TICK) => {}
+            _ = stop.notified() => return Ok(None),
+        }
+    }
+}
+
 /// Log and collapse a joined task result into `Some(error)` when either the
 /// task itself failed (panic/abort) or it returned `Err`.
 fn flatten_task_result<T>(
@@ -2406,6 +3468,7 @@ async fn video_play(
     // Android direct mode video window (0 = renderer path).
     direct_window: usize,
     hdr_decode_8bit: Arc<AtomicBool>,
+    closed_captions: Arc<ClosedCaptions>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     // Initial pipeline: nothing to overlap with, so download and decode run
     // back to back. `prime_target = MAX` → the readiness signal never fires
@@ -2433,6 +3496,7 @@ async fn video_play(
         None,
         direct_window,
         hdr_decode_8bit,
+        closed_captions,
     )
     .await
 }
@@ -2443,6 +3507,8 @@ async fn audio_play(
     audio_ready: Arc<Notify>,
     sender: Sender<DecodedAudioFrame>,
     output_sample_rate: u32,
+    output_channels: u16,
+    downmix: DownmixCoefficients,
     stop: Arc<Notify>,
     stop_flag: Arc<AtomicBool>,
     decryptor: Option<Arc<dyn Decryptor>>,
@@ -2469,6 +3535,10 @@ async fn audio_play(
         AudioCodec::Eac3
     } else if codecs_str == "ac-3" {
         AudioCodec::Ac3
+    } else if codecs_str.eq_ignore_ascii_case("opus") {
+        AudioCodec::Opus
+    } else if codecs_str.eq_ignore_ascii_case("flac") {
+        AudioCodec::Flac
     } else {
         return Err(format!("Unsupported audio codec: {}", codecs_str).into());
     };
@@ -2477,7 +3547,10 @@ async fn audio_play(
     // FFmpeg and MediaCodec want those 2 bytes as extradata / csd-0 to open
     // the decoder. AC-3 and EAC-3 are self-describing (each frame begins with
     // a syncinfo header), so the decoder just needs the MIME plus the
-    // sample-rate/channel hints from the DASH manifest.
+    // sample-rate/channel hints from the DASH manifest. Opus (`dOps`) and
+    // FLAC (`dfLa`) are re-serialised into the stream headers the decoders
+    // expect; their rate/channels come from the box, not the manifest.
+    let mut opus_pre_skip = 0u32;
     let (input_sample_rate, input_channels, codec_specific_data) = match codec {
         AudioCodec::Aac => {
             let aac_config = parse_aac_config(&init_data)
@@ -2500,13 +3573,53 @@ async fn audio_play(
             log::info!("audio: {:?} {}Hz {}ch", codec, rate, ch);
             (rate, ch, Vec::new())
         }
+        AudioCodec::Opus => {
+            let opus = parse_dops(&init_data)
+                .ok_or("Audio codec not supported (no dOps in init segment)")?;
+            log::info!(
+                "audio: Opus {}ch pre_skip={} mapping_family={} (source {}Hz)",
+                opus.output_channel_count, opus.pre_skip, opus.channel_mapping_family,
+                opus.input_sample_rate
+            );
+            opus_pre_skip = u32::from(opus.pre_skip);
+            // Opus always decodes at 48 kHz whatever the source rate was.
+            (OPUS_DECODE_RATE, opus.output_channel_count as u16, opus.opus_head(0))
+        }
+        AudioCodec::Flac => {
+            let flac = parse_dfla(&init_data)
+                .ok_or("Audio codec not supported (no dfLa in init segment)")?;
+            log::info!(
+                "audio: FLAC {}Hz {}ch {}-bit",
+                flac.sample_rate, flac.channels, flac.bits_per_sample
+            );
+            (flac.sample_rate, flac.channels as u16, flac.stream_header)
+        }
+    };
+
+    // Opus output opens with `pre_skip` priming samples that aren't part of
+    // the programme; they are trimmed here rather than inside the decoders
+    // so FFmpeg and MediaCodec behave identically — the OpusHead handed to
+    // them carries a zero pre-skip. A decoder started mid-stream (every seek
+    // restarts this pipeline at a segment boundary) instead needs 80 ms of
+    // pre-roll to converge: the download starts one segment early and
+    // `audio_decoder_task` decodes that segment's tail, dropping only its
+    // output.
+    let opus_preroll = matches!(codec, AudioCodec::Opus) && start_index > 0;
+    let download_start = start_index - usize::from(opus_preroll);
+    let trim_48k = match codec {
+        AudioCodec::Opus if start_index == 0 => opus_pre_skip,
+        _ => 0,
     };
+    let trim_frames =
+        (u64::from(trim_48k) * u64::from(output_sample_rate) / u64::from(OPUS_DECODE_RATE)) as usize;
 
     decoder.configure(AudioDecoderParams {
         codec,
         input_sample_rate,
         input_channels,
         output_sample_rate,
+        output_channels,
+        downmix,
         codec_specific_data,
     })?;
 
@@ -2522,7 +3635,7 @@ async fn audio_play(
     });
     let download_task = task::spawn(download_task(
         segments,
-        start_index,
+        download_start,
         download_tx,
         stop,
         stop_flag,
@@ -2542,6 +3655,10 @@ async fn audio_play(
         audio_ready,
         track_crypto,
         stats,
+        trim_frames,
+        opus_preroll,
+        output_sample_rate,
+        output_channels,
     ));
 
     let (dl_res, dec_res) = join!(download_task, decoder_task);
@@ -2553,6 +3670,109 @@ async fn audio_play(
     }
 }
 
+/// Retrying owner of the audio pipeline (`audio_play` or
+/// `audio_passthrough_play`, built by `spawn_pipeline`) for one pipeline
+/// generation — the audio counterpart of `video_supervisor`'s failure arm.
+///
+/// A failed pipeline is respawned, after a 1/2/3 s backoff, from the segment
+/// containing the playback position, feeding the same sample channel / sink.
+/// The respawn re-delivers audio that is already queued; `audio_sync_loop`
+/// splices it off by PTS (the passthrough feed skips AUs through its
+/// `PassthroughCursor`), so playback continues forward. av_sync's starvation
+/// detection shows `Buffering { Stall }` meanwhile. Retries share
+/// `RetryBudget` semantics with video; exhausting it parks the position,
+/// emits `Error { Decoder }` and stops the pipeline.
+///
+/// Respawns get a throwaway readiness `Notify`: `audio_ready` is the
+/// player-wide gate of av_sync, and a stale permit from a retry would open
+/// the NEXT pipeline's gate early.
+#[allow(clippy::too_many_arguments)]
+async fn audio_supervisor(
+    mut spawn_pipeline: AudioPipelineSpawner,
+    segments: Vec<Segment>,
+    initial_start_index: usize,
+    audio_ready: Arc<Notify>,
+    stop: Arc<Notify>,
+    stop_flag: Arc<AtomicBool>,
+    stats: Arc<StatsState>,
+    position_ms: Arc<AtomicU64>,
+    events: Arc<broadcast::Sender<PlayerEvent>>,
+    // Content origin: position_ms is 0-based, segment times absolute.
+    origin: Duration,
+    pending_resume: Arc<StdMutex<Option<Duration>>>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut retries = RetryBudget::default();
+    let mut handle = spawn_pipeline(initial_start_index, audio_ready);
+    loop {
+        let detail = match handle.await {
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(e)) => e.to_string(),
+            Err(e) => format!("audio pipeline task panicked: {}", e),
+        };
+        if stop_flag.load(Ordering::Relaxed) {
+            // Teardown raced the failure — not an error.
+            return Ok(());
+        }
+        log::error!("[audio] supervisor: pipeline failed: {}", detail);
+
+        let pos_now = position_ms.load(Ordering::Relaxed);
+        stats.pipeline_retries.fetch_add(1, Ordering::Relaxed);
+        let Some(attempt) = retries.fail(pos_now) else {
+            log::error!(
+                "[audio] supervisor: {} consecutive pipeline failures — giving up at {}ms",
+                RetryBudget::MAX_RETRIES,
+                pos_now
+            );
+            // Same exit as video: park the position for the consumer's next
+            // play(), surface the error, stop without a fake EndOfStream.
+            *pending_resume.lock().unwrap() = Some(Duration::from_millis(pos_now));
+            let _ = events.send(PlayerEvent::Error {
+                kind: PlayerErrorKind::Decoder,
+                detail,
+            });
+            stop_flag.store(true, Ordering::Relaxed);
+            stop.notify_waiters();
+            return Err("audio pipeline retries exhausted".into());
+        };
+        log::warn!(
+            "[audio] supervisor: retrying pipeline ({}/{}) from {}ms",
+            attempt,
+            RetryBudget::MAX_RETRIES,
+            pos_now
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(attempt as u64)) => {}
+            _ = stop.notified() => return Ok(()),
+        }
+        if stop_flag.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let resume_idx = find_segment_index(&segments, Duration::from_millis(pos_now) + origin);
+        handle = spawn_pipeline(resume_idx, Arc::new(Notify::new()));
+    }
+}
+
+/// Where a passthrough feed got to, shared across `audio_supervisor`
+/// retries of one pipeline: a respawned feed skips the AUs already written
+/// and paces against the same playback-head baseline.
+#[cfg(target_os = "android")]
+struct PassthroughCursor {
+    /// PTS (ms) of the first AU written; `i64::MIN` before it.
+    base_pts_ms: std::sync::atomic::AtomicI64,
+    /// PTS (µs) of the last AU written; `i64::MIN` before it.
+    last_au_us: std::sync::atomic::AtomicI64,
+}
+
+#[cfg(target_os = "android")]
+impl PassthroughCursor {
+    fn new() -> Self {
+        PassthroughCursor {
+            base_pts_ms: std::sync::atomic::AtomicI64::new(i64::MIN),
+            last_au_us: std::sync::atomic::AtomicI64::new(i64::MIN),
+        }
+    }
+}
+
 /// Audio passthrough feed: download + decrypt the audio segments, slice the
 /// compressed access units out of the mp4 samples and write them straight to
 /// the bitstream sink (no decode, no PCM channel). Mirrors `audio_play`'s
@@ -2573,6 +3793,7 @@ async fn audio_passthrough_play(
     segments_in_flight: usize,
     discard_below_us: i64,
     pipeline_live: Arc<AtomicBool>,
+    cursor: Arc<PassthroughCursor>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
     let (download_tx, download_rx) = mpsc::channel::<DataSegment>(segments_in_flight);
 
@@ -2607,11 +3828,15 @@ async fn audio_passthrough_play(
         stop_flag,
         discard_below_us,
         pipeline_live,
+        cursor,
     ));
     let (dl_res, feed_res) = join!(download, feed);
-    log_task_result("audio download_task (passthrough)", dl_res);
-    log_task_result("audio passthrough_task", feed_res);
-    Ok(())
+    let dl_err = flatten_task_result("audio download_task (passthrough)", dl_res);
+    let feed_err = flatten_task_result("audio passthrough_task", feed_res);
+    match dl_err.or(feed_err) {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }
 
 #[cfg(target_os = "android")]
@@ -2624,10 +3849,13 @@ async fn audio_passthrough_task(
     stop_flag: Arc<AtomicBool>,
     discard_below_us: i64,
     pipeline_live: Arc<AtomicBool>,
+    cursor: Arc<PassthroughCursor>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    let mut first_au_written = false;
+    // A retry (see `audio_supervisor`) resumes the previous feed's baseline
+    // and must not re-arm `audio_ready`.
+    let mut base_pts_ms = cursor.base_pts_ms.load(Ordering::Relaxed);
+    let mut first_au_written = base_pts_ms != i64::MIN;
     let mut au_count = 0u64;
-    let mut base_pts_ms: i64 = 0;
     while let Some(segment) = receiver.recv().await {
         if stop_flag.load(Ordering::Relaxed) {
             break;
@@ -2658,8 +3886,9 @@ async fn audio_passthrough_task(
                 continue;
             }
             let pts_us = if ts_scale > 0 { ts * 1_000_000 / ts_scale as i64 } else { 0 };
-            // Frame-accurate seek: drop AUs before the target.
-            if pts_us < discard_below_us {
+            // Frame-accurate seek: drop AUs before the target — and, on a
+            // retry, the AUs the previous feed already wrote.
+            if pts_us < discard_below_us || pts_us <= cursor.last_au_us.load(Ordering::Relaxed) {
                 continue;
             }
             let au_ms = pts_us / 1000;
@@ -2675,6 +3904,7 @@ async fn audio_passthrough_task(
                     tokio::time::sleep(Duration::from_millis(5)).await;
                 }
                 base_pts_ms = au_ms;
+                cursor.base_pts_ms.store(au_ms, Ordering::Relaxed);
             }
             // Two-phase feed. PRIME: an E-AC-3 *direct* AudioTrack does not
             // begin output — `getTimestamp` stays false / `played_ms` reads 0 —
@@ -2782,6 +4012,7 @@ async fn audio_passthrough_task(
             // pipeline!) to a sibling worker while the JNI write runs.
             let au = &data_vec[offset..offset + size];
             tokio::task::block_in_place(|| sink.write(au));
+            cursor.last_au_us.store(pts_us, Ordering::Relaxed);
             au_count += 1;
             if !first_au_written {
                 log::debug!("[audio-pt] first AU written (au_ms={}), play() armed", au_ms);
@@ -2808,28 +4039,48 @@ async fn audio_passthrough_task(
 /// the video sink so the wgpu overlay can render them.
 ///
 /// Two delivery patterns are supported:
-///   1. Single-file VTT (the common "external .vtt per language"
+///   1. Single-file VTT / TTML (the common "external .vtt per language"
 ///      pattern) — `single_file_url` set, segments empty. Download
-///      whole file once, parse as raw WebVTT, hand off, done.
-///   2. ISO BMFF VTT in CMAF — `segment_init` + `segments` populated.
-///      Stream segments through the normal download path, parse each
-///      as `vttc` boxes, push cues incrementally.
+///      whole file once, parse as a raw document, hand off, done.
+///   2. ISO BMFF `wvtt` / `stpp` in CMAF — `segment_init` + `segments`
+///      populated. Stream segments through the normal download path,
+///      parse each fragment's samples, push cues incrementally.
 ///
-/// Both paths skip silently if the representation isn't decodable
-/// (TTML for now).
+/// External subtitles (`Player::add_external_subtitle`) were fetched and
+/// parsed when added; their `external_cues` go straight to the sink.
+///
+/// Closed captions (`embedded_captions`) have nothing to download: the
+/// video pipeline decodes them into `ClosedCaptions`, and this task relays
+/// each new or updated cue of its service until the track is deselected.
+///
+/// `target` says where cues go: the overlay's primary or secondary track,
+/// or — in host-rendered mode (`Player::set_host_subtitles`) — the
+/// `HostSubtitles` store, leaving the overlay empty.
+///
+/// WebVTT goes through `parsers::vtt`, TTML / IMSC1 through
+/// `parsers::ttml`; both paths skip silently if the representation is
+/// neither.
 /// `active` is the live cell holding the currently-selected subtitle
 /// representation. If the consumer flips it (via clear_subtitle_track
 /// or set_subtitle_track to a different track) the task notices between
 /// operations and exits so stale downloads stop wasting bandwidth.
+#[allow(clippy::too_many_arguments)]
 async fn text_play<V: VideoSink>(
     text_representation: tracks::text::TextRepresenation,
     stop: Arc<Notify>,
     stop_flag: Arc<AtomicBool>,
     http: Arc<HttpClient>,
     video_sink: Arc<V>,
+    captions: Arc<ClosedCaptions>,
+    target: CueTarget,
     active: Arc<StdMutex<Option<tracks::text::TextRepresenation>>>,
     target_id: u32,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let queue_cues = |cues: Vec<crate::parsers::vtt::VttCue>| match &target {
+        CueTarget::Overlay => video_sink.queue_subtitle_cues(cues),
+        CueTarget::SecondaryOverlay => video_sink.queue_secondary_subtitle_cues(cues),
+        CueTarget::Host(host) => host.queue(cues),
+    };
     // Helper: did the consumer change subtitle selection out from under us?
     let still_selected = |active: &Arc<StdMutex<Option<tracks::text::TextRepresenation>>>| -> bool {
         active
@@ -2840,19 +4091,58 @@ async fn text_play<V: VideoSink>(
             .unwrap_or(false)
     };
 
-    if !text_representation.is_webvtt() {
+    if let Some(cues) = &text_representation.external_cues {
         log::info!(
-            "[subs] representation {} is not WebVTT ({}/{}) — skipping",
-            text_representation.id,
-            text_representation.codecs,
-            text_representation.mime_type,
+            "[subs] queueing {} external cues ({})",
+            cues.len(),
+            text_representation.codec_short()
         );
+        queue_cues(cues.as_ref().clone());
+        return Ok(());
+    }
+
+    if let Some(service) = text_representation.embedded_captions {
+        log::info!("[subs] relaying {} closed captions", service.name());
+        let mut seq = 0;
+        while still_selected(&active) {
+            // Register before reading so an update in between still wakes
+            // us. Not `stop`: a seek fires it, yet captions carry on.
+            let updated = captions.notified();
+            let (cues, next) = captions.updates(service, seq);
+            seq = next;
+            if !cues.is_empty() {
+                queue_cues(cues);
+            }
+            tokio::select! {
+                _ = updated => {}
+                _ = tokio::time::sleep(Duration::from_millis(500)) => {}
+            }
+        }
         return Ok(());
     }
 
+    let parse_segment: fn(&[u8], i64) -> Vec<crate::parsers::vtt::VttCue> =
+        if text_representation.is_webvtt() {
+            crate::parsers::vtt::parse_segment
+        } else if text_representation.is_ttml() {
+            crate::parsers::ttml::parse_segment
+        } else {
+            log::info!(
+                "[subs] representation {} is neither WebVTT nor TTML ({}/{}) — skipping",
+                text_representation.id,
+                text_representation.codecs,
+                text_representation.mime_type,
+            );
+            return Ok(());
+        };
+
     // ---- single-file delivery ----
     if let Some(url) = &text_representation.single_file_url {
-        log::info!("[subs] downloading single-file VTT: {}", url);
+        log::info!(
+            "[subs] downloading single-file {}: {}",
+            text_representation.codec_short(),
+            url
+        );
         let dl_fut = http.get(url.clone(), RequestKind::InitSegment);
         let bytes = tokio::select! {
             r = dl_fut => match r {
@@ -2867,9 +4157,9 @@ async fn text_play<V: VideoSink>(
         if !still_selected(&active) {
             return Ok(());
         }
-        let cues = crate::parsers::vtt::parse_segment(&bytes, 0);
+        let cues = parse_segment(&bytes, 0);
         log::info!(
-            "[subs] parsed {} cues from {} bytes (single VTT file)",
+            "[subs] parsed {} cues from {} bytes (single file)",
             cues.len(),
             bytes.len()
         );
@@ -2919,7 +4209,7 @@ async fn text_play<V: VideoSink>(
                 preview_len, hex_dump, ascii_dump
             );
         }
-        video_sink.queue_subtitle_cues(cues);
+        queue_cues(cues);
         return Ok(());
     }
 
@@ -2948,10 +4238,10 @@ async fn text_play<V: VideoSink>(
         match dl {
             Ok(d) => {
                 let pts_ms = seg.start_time().as_millis() as i64;
-                let cues = crate::parsers::vtt::parse_segment(&d.data, pts_ms);
+                let cues = parse_segment(&d.data, pts_ms);
                 if !cues.is_empty() {
                     log::debug!("[subs] segment {} produced {} cues", i, cues.len());
-                    video_sink.queue_subtitle_cues(cues);
+                    queue_cues(cues);
                 }
             }
             Err(e) => {
@@ -2962,6 +4252,44 @@ async fn text_play<V: VideoSink>(
     Ok(())
 }
 
+/// Where `text_play` delivers parsed cues.
+enum CueTarget {
+    /// The overlay's primary track.
+    Overlay,
+    /// The overlay's secondary track (dual subtitles).
+    SecondaryOverlay,
+    /// Host-rendered mode: cues become `SubtitleCue` events.
+    Host(Arc<HostSubtitles>),
+}
+
+/// Longest the host-subtitle clock sleeps between checks, so seeks and
+/// resumes show up promptly even far from the next cue boundary.
+const HOST_SUBTITLE_POLL_MS: u64 = 100;
+
+/// Drives `HostSubtitles` in host-rendered mode: re-evaluates the active
+/// cue set at the rendered position (`position_ms`, which every sync loop
+/// stores from `MediaClock`) and sleeps until the next cue boundary,
+/// scaled by the playback rate. Runs until the subtitle selection changes
+/// (`session` moves on).
+async fn host_subtitle_clock(
+    host: Arc<HostSubtitles>,
+    session: u64,
+    position_ms: Arc<AtomicU64>,
+    playback_rate: Arc<AtomicU32>,
+) {
+    while host.session() == session {
+        let pts_ms = position_ms.load(Ordering::Relaxed) as i64;
+        let next = host.tick(pts_ms);
+        let rate = f32::from_bits(playback_rate.load(Ordering::Relaxed)).max(MIN_PLAYBACK_RATE);
+        let wait_ms = next.map_or(HOST_SUBTITLE_POLL_MS, |t| {
+            // position_ms moves a frame at a time, so never spin faster
+            // than a frame even right at the boundary.
+            (((t - pts_ms) as f32 / rate) as u64).clamp(10, HOST_SUBTITLE_POLL_MS)
+        });
+        tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Video supervisor — owns the video pipeline lifecycle within one play()
 // ---------------------------------------------------------------------------
@@ -2970,6 +4298,43 @@ async fn text_play<V: VideoSink>(
 /// invokes it once per spawned `video_play` (i.e. once per representation),
 /// so the platform-specific decoder type stays out of this module.
 type VideoDecoderFactory = Arc<dyn Fn() -> Box<dyn HwVideoDecoder> + Send + Sync>;
+type AudioDecoderFactory = Arc<dyn Fn() -> Box<dyn AudioDecoder> + Send + Sync>;
+/// Spawns one attempt of the audio pipeline from a segment index, signalling
+/// the given readiness `Notify` (see `audio_supervisor`).
+type AudioPipelineSpawner = Box<
+    dyn FnMut(usize, Arc<Notify>) -> tokio::task::JoinHandle<Result<(), Box<dyn Error + Send + Sync>>>
+        + Send,
+>;
+
+/// Retry budget of a pipeline supervisor (`video_supervisor`,
+/// `audio_supervisor`). Pipeline failures (network death mid-stream,
+/// decoder errors) are retried from the current playback position with
+/// backoff. The counter resets once playback makes real progress, so a
+/// long movie surviving three separate hiccups hours apart keeps
+/// recovering, while a hard failure (dead URL, broken stream) exhausts
+/// quickly and surfaces as PlayerEvent::Error instead of a fake EndOfStream.
+#[derive(Default)]
+struct RetryBudget {
+    attempt: u32,
+    last_fail_pos_ms: u64,
+}
+
+impl RetryBudget {
+    const MAX_RETRIES: u32 = 3;
+    const PROGRESS_RESET_MS: u64 = 10_000;
+
+    /// Record a failure at playback position `pos_ms`. Returns the attempt
+    /// to make (1-based, also the backoff in seconds), or `None` once the
+    /// budget is exhausted.
+    fn fail(&mut self, pos_ms: u64) -> Option<u32> {
+        if pos_ms.saturating_sub(self.last_fail_pos_ms) > Self::PROGRESS_RESET_MS {
+            self.attempt = 0;
+        }
+        self.last_fail_pos_ms = pos_ms;
+        self.attempt += 1;
+        (self.attempt <= Self::MAX_RETRIES).then_some(self.attempt)
+    }
+}
 
 /// Long-lived task that owns the video pipeline for a single `play()` call.
 /// It runs one representation's decode at a time and switches on ABR request.
@@ -3018,21 +4383,13 @@ async fn video_supervisor(
     // Android direct mode video window (0 = renderer path).
     direct_window: usize,
     hdr_decode_8bit: Arc<AtomicBool>,
+    closed_captions: Arc<ClosedCaptions>,
     // Resume slot written when retries are exhausted: the NEXT play() call
     // starts from this position instead of zero ("continue where we
     // stopped" semantics for the consumer's manual retry).
     pending_resume: Arc<StdMutex<Option<Duration>>>,
 ) -> Result<(), Box<dyn Error + Send + Sync>> {
-    // Pipeline failures (network death mid-stream, decoder errors) are
-    // retried from the current playback position with backoff. The
-    // counter resets once playback makes real progress, so a long movie
-    // surviving three separate hiccups hours apart keeps recovering, while
-    // a hard failure (dead URL, broken stream) exhausts quickly and
-    // surfaces as PlayerEvent::Error instead of a fake EndOfStream.
-    const MAX_PIPELINE_RETRIES: u32 = 3;
-    const PROGRESS_RESET_MS: u64 = 10_000;
-    let mut retry_attempt: u32 = 0;
-    let mut last_fail_pos_ms: u64 = 0;
+    let mut retries = RetryBudget::default();
     let spawn_pipeline = |repr: VideoRepresenation,
                           start_index: usize,
                           local_stop: Arc<Notify>,
@@ -3057,6 +4414,7 @@ async fn video_supervisor(
             soft_end.clone(),
             direct_window,
             Arc::clone(&hdr_decode_8bit),
+            Arc::clone(&closed_captions),
         ));
         (handle, soft_end)
     };
@@ -3127,16 +4485,11 @@ async fn video_supervisor(
                     // Bounded retry-with-resume. Progress since the last
                     // failure resets the budget.
                     let pos_now = position_ms.load(Ordering::Relaxed);
-                    if pos_now.saturating_sub(last_fail_pos_ms) > PROGRESS_RESET_MS {
-                        retry_attempt = 0;
-                    }
-                    last_fail_pos_ms = pos_now;
-                    retry_attempt += 1;
                     stats.pipeline_retries.fetch_add(1, Ordering::Relaxed);
-                    if retry_attempt > MAX_PIPELINE_RETRIES {
+                    let Some(retry_attempt) = retries.fail(pos_now) else {
                         log::error!(
                             "[video] supervisor: {} consecutive pipeline failures — giving up at {}ms",
-                            retry_attempt - 1,
+                            RetryBudget::MAX_RETRIES,
                             pos_now
                         );
                         // Park the position for the consumer's next play()
@@ -3152,11 +4505,11 @@ async fn video_supervisor(
                         stop_flag.store(true, Ordering::Relaxed);
                         stop.notify_waiters();
                         return Err("video pipeline retries exhausted".into());
-                    }
+                    };
                     log::warn!(
                         "[video] supervisor: retrying pipeline ({}/{}) from {}ms",
                         retry_attempt,
-                        MAX_PIPELINE_RETRIES,
+                        RetryBudget::MAX_RETRIES,
                         pos_now
                     );
                     // Backoff, abortable by stop. av_sync's starvation
@@ -3192,6 +4545,7 @@ async fn video_supervisor(
                         let video_ready = video_ready.clone();
                         let decoder_factory = decoder_factory.clone();
                         let hdr_decode_8bit = Arc::clone(&hdr_decode_8bit);
+                        let closed_captions = Arc::clone(&closed_captions);
                         let splice_pts_us = pos_abs.as_micros() as i64;
                         async move {
                             let pf = video_prefetch(
@@ -3220,6 +4574,7 @@ async fn video_supervisor(
                                 }),
                                 direct_window,
                                 hdr_decode_8bit,
+                                closed_captions,
                             )
                             .await
                         }
@@ -3434,6 +4789,7 @@ async fn video_supervisor(
                 }),
                 direct_window,
                 Arc::clone(&hdr_decode_8bit),
+                Arc::clone(&closed_captions),
             ));
             Some((handle, release))
         } else {
@@ -3568,6 +4924,7 @@ async fn video_supervisor(
                     }),
                     direct_window,
                     Arc::clone(&hdr_decode_8bit),
+                    Arc::clone(&closed_captions),
                 ))
             }
         };
@@ -3609,6 +4966,30 @@ impl Player<VideoRenderer, AudioRenderer> {
         self.video_renderer.set_pre_present_hook(hook);
     }
 
+    /// Audio output devices the system offers, for a device picker. Desktop
+    /// only — empty on Android / iOS, where the OS owns audio routing.
+    pub fn audio_output_devices(&self) -> Vec<AudioOutputDevice> {
+        self.audio_renderer.output_devices()
+    }
+
+    /// Route audio to device `id` (an [`AudioOutputDevice::id`] from
+    /// [`audio_output_devices`](Self::audio_output_devices)), or follow the
+    /// system default with `None` — the initial behaviour, which also
+    /// migrates when the default changes. The stream is reopened in place and
+    /// the audio clock carries over, so sync holds; a device with another
+    /// rate / channel count rebuilds the pipeline at the current position.
+    /// An id that isn't present plays on the default until it reappears.
+    /// Each actual change emits `PlayerEvent::AudioDeviceChanged`.
+    pub fn set_audio_output_device(&self, id: Option<&str>) {
+        self.audio_renderer.set_output_device(id.map(str::to_owned));
+    }
+
+    /// The device audio is playing on, `None` before the output opened or
+    /// when it has no device identity (null sink, Android, iOS).
+    pub fn audio_output_device(&self) -> Option<AudioOutputDevice> {
+        self.audio_renderer.output_device()
+    }
+
     /// Embedded Apple path: render into a host-provided `CAMetalLayer*`.
     /// Mirror of `new` for an app that owns `UIApplicationMain` itself (no
     /// winit). The host keeps the layer alive for the player's lifetime and
@@ -3676,10 +5057,37 @@ impl Player<VideoRenderer, AudioRenderer> {
         // before any state transition.
         let _ = events.send(PlayerEvent::Idle);
 
+        // Output device / format changes (see `set_audio_output_device`) →
+        // `AudioDeviceChanged`. Ends with the renderer, which owns the sender.
+        let mut output_changes = audio_renderer.subscribe_output_changes();
+        let device_events = Arc::clone(&events);
+        tokio::spawn(async move {
+            loop {
+                match output_changes.recv().await {
+                    Ok((device, sample_rate, channels)) => {
+                        let _ = device_events.send(PlayerEvent::AudioDeviceChanged {
+                            device,
+                            sample_rate,
+                            channels,
+                        });
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        let host_subtitles = Arc::new(HostSubtitles::new(Arc::clone(&events)));
+        let tracks = Arc::new(StdMutex::new(None));
+        let closed_captions = Arc::new(ClosedCaptions::new(
+            Arc::clone(&tracks),
+            Arc::clone(&events),
+        ));
+
         Player {
             base_url: None,
             manifest: None,
-            tracks: Arc::new(StdMutex::new(None)),
+            tracks,
             http: Arc::new(HttpClient::new()),
             events,
             paused: Arc::new(AtomicBool::new(false)),
@@ -3708,12 +5116,28 @@ impl Player<VideoRenderer, AudioRenderer> {
             video_switch_tx: Arc::new(StdMutex::new(None)),
             buffer_target_secs: Arc::new(AtomicU32::new(DEFAULT_BUFFER_TARGET_SECS)),
             subtitle_representation: Arc::new(StdMutex::new(None)),
+            secondary_subtitle_representation: Arc::new(StdMutex::new(None)),
+            subtitle_offset_ms: Arc::new(AtomicI64::new(0)),
+            host_subtitles,
+            closed_captions,
             video_output_window: Arc::new(DirectWindow::new()),
             adaptive_frame_rate: Arc::new(std::sync::atomic::AtomicBool::new(true)),
             audio_passthrough: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             hdr_decode_8bit: Arc::new(std::sync::atomic::AtomicBool::new(
                 std::env::var_os("RUST_PLAYER_VT_FORCE_8BIT").is_some(),
             )),
+            software_video_decode: Arc::new(std::sync::atomic::AtomicBool::new(
+                std::env::var_os("RUST_PLAYER_SOFTWARE_DECODE").is_some(),
+            )),
+            downmix: Arc::new(ArcSwap::from_pointee(DownmixCoefficients::DEFAULT)),
+            playback_rate: Arc::new(AtomicU32::new(1.0_f32.to_bits())),
+            loudness: Arc::new(ArcSwap::from_pointee(LoudnessParams::DEFAULT)),
+            loudness_dsp: Arc::new(StdMutex::new(None)),
+            audio_processors: Arc::new(AudioProcessorChain::default()),
+            av_offset_ms: Arc::new(AtomicI32::new(0)),
+            video_enabled: Arc::new(AtomicBool::new(true)),
+            frame_step: Arc::new(FrameStep::default()),
+            trick_speed: Arc::new(AtomicI32::new(0)),
             pipeline_live: Arc::new(std::sync::atomic::AtomicBool::new(false)),
             pending_resume: Arc::new(StdMutex::new(None)),
 
@@ -3871,6 +5295,9 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
             subtitle_tracks: text,
         });
         self.manifest = Some(manifest);
+        // New programme: start the loudness measurement afresh.
+        *self.loudness_dsp.lock().unwrap() = None;
+        *self.stats.audio_loudness_lufs.lock().unwrap() = None;
 
         // A new manifest is a fresh playback session — never inherit the
         // `paused` flag from a prior session on this Player instance.
@@ -3906,6 +5333,8 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 return Err(e);
             }
         };
+        // Captions found in the previous presentation's video are gone.
+        self.closed_captions.clear();
         *self.tracks.lock().unwrap() = Some(tracks);
         let _ = self.events.send(PlayerEvent::Prepared);
         Ok(())
@@ -3944,6 +5373,12 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
     /// after resume. No-op if not paused.
     pub fn resume(&self) {
         if self.paused.swap(false, Ordering::Relaxed) {
+            // Forward steps left video ahead of the paused audio: restart
+            // both at the frame on screen.
+            if self.frame_step.dirty.swap(false, Ordering::Relaxed) {
+                let presented = self.frame_step.presented_us.load(Ordering::Relaxed);
+                self.seek(Duration::from_micros(presented.max(0) as u64));
+            }
             self.audio_renderer.set_paused(false);
             self.pause_notify.notify_waiters();
         }
@@ -3953,7 +5388,33 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
         self.paused.load(Ordering::Relaxed)
     }
 
-    /// Install a `RequestInterceptor` (auth headers, URL rewrites). Replaces
+    /// Step `n` frames while paused: forward (`n > 0`) presents the next
+    /// decoded frames straight from the running pipeline; backward
+    /// (`n < 0`) seeks back, re-decoding from the segment-start keyframe to
+    /// the target frame. Each landing emits `PlayerEvent::FrameStepped`
+    /// with the exact PTS on screen, and `position()` follows. Backward
+    /// steps count in the stream's observed frame interval, so they are
+    /// exact on constant-frame-rate content. No-op unless paused with video
+    /// attached; audio stays parked and `resume()` picks up from the
+    /// stepped-to frame.
+    pub fn step_frame(&self, n: i32) {
+        if n == 0 || !self.is_paused() || !self.video_enabled() || self.trick_play() != 0 {
+            return;
+        }
+        log::debug!("step_frame: {}", n);
+        if n > 0 {
+            self.frame_step.forward.fetch_add(n as u32, Ordering::Relaxed);
+            self.frame_step.notify.notify_one();
+            return;
+        }
+        let Some(target) = self.frame_step.backward_target(n.unsigned_abs()) else {
+            return;
+        };
+        self.seek(target);
+        self.frame_step.report_next.store(true, Ordering::Relaxed);
+    }
+
+    /// Install a `RequestInterceptor` (auth headers, URL rewrites). Replaces
     /// the default `NoopInterceptor`. Subsequent requests use the new
     /// interceptor; in-flight requests keep the previous one.
     pub fn set_request_interceptor(&self, interceptor: Arc<dyn RequestInterceptor>) {
@@ -4245,6 +5706,176 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
             .store(enabled, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Set the gains used to fold surround channels the output device
+    /// doesn't have (e.g. 5.1 E-AC-3 on a stereo device). Default is the
+    /// ITU-R BS.775 downmix ([`DownmixCoefficients::DEFAULT`]). Values are
+    /// sanitised into 0..=1. Multichannel devices play matching channels
+    /// 1:1 and only fold what they lack. Takes effect at the next audio
+    /// pipeline (re)build — `play()`, a seek or an audio track switch.
+    pub fn set_downmix(&self, coefficients: DownmixCoefficients) {
+        self.downmix.store(Arc::new(coefficients.sanitised()));
+    }
+
+    /// Returns the active downmix gains.
+    pub fn downmix(&self) -> DownmixCoefficients {
+        **self.downmix.load()
+    }
+
+    /// Configure the loudness stage on the PCM path: EBU R128 normalisation
+    /// to `target_lufs` and/or night-mode compression
+    /// ([`LoudnessParams::DEFAULT`] = both off). Values are sanitised.
+    /// Takes effect live, with the gain ramping rather than stepping. The
+    /// integrated loudness is measured regardless and reported in
+    /// `PlayerEvent::Stats`. Bitstream passthrough bypasses the stage — the
+    /// AVR owns the level there.
+    pub fn set_loudness(&self, params: LoudnessParams) {
+        self.loudness.store(Arc::new(params.sanitised()));
+    }
+
+    /// Returns the active loudness settings.
+    pub fn loudness(&self) -> LoudnessParams {
+        **self.loudness.load()
+    }
+
+    /// Append a host effect to the PCM chain (run after the built-in
+    /// loudness stage, in insertion order, right before the audio sink).
+    /// Takes effect live; the processor's `latency_frames` is added to the
+    /// sink's output latency so video stays in sync. Skipped while
+    /// bitstream passthrough is engaged.
+    pub fn add_audio_processor(&self, processor: Box<dyn AudioProcessor>) -> AudioProcessorId {
+        self.audio_processors.add(processor)
+    }
+
+    /// Remove a processor added with `add_audio_processor`. Returns false
+    /// if it was already gone.
+    pub fn remove_audio_processor(&self, id: AudioProcessorId) -> bool {
+        self.audio_processors.remove(id)
+    }
+
+    /// Remove every host processor.
+    pub fn clear_audio_processors(&self) {
+        self.audio_processors.clear();
+    }
+
+    /// Shift video relative to audio, in ms: positive delays the picture
+    /// (for outputs that play audio late and don't report it through
+    /// `output_latency_ms` — Bluetooth headsets, some AVRs), negative
+    /// advances it. Clamped to ±[`MAX_AV_OFFSET_MS`]. Applied live by the
+    /// playback clock, no pipeline rebuild; subtitles follow the video.
+    /// Reported positions follow the picture too. The player does not
+    /// persist the value — hosts keep it per output device (the platform
+    /// shells do this) and re-apply it on a route change.
+    pub fn set_av_offset_ms(&self, offset_ms: i32) {
+        let offset_ms = offset_ms.clamp(-MAX_AV_OFFSET_MS, MAX_AV_OFFSET_MS);
+        let prev = self.av_offset_ms.swap(offset_ms, Ordering::Relaxed);
+        if prev != offset_ms {
+            log::info!("set_av_offset_ms: {} -> {}", prev, offset_ms);
+        }
+    }
+
+    /// Current lip-sync offset in ms (+ = video later than audio).
+    pub fn av_offset_ms(&self) -> i32 {
+        self.av_offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// Detach (`false`) or re-attach (`true`) video, for audio-only
+    /// playback — background listening, a podcast-style view. While
+    /// detached, pipelines download and decode audio only, the audio device
+    /// clock drives the timeline and `Position` / `Stats` keep flowing (the
+    /// video figures stay at zero). The video sink itself is left alone, so
+    /// the host may release its surface meanwhile (on Android also pass
+    /// null to `set_video_output_window`). Re-attaching rebuilds the
+    /// pipeline at the current position with video. Applies to a running
+    /// pipeline at once, otherwise from the next `play()`. A manifest with
+    /// no video adaptation plays audio-only regardless of this flag.
+    pub fn set_video_enabled(&self, enabled: bool) {
+        if self.video_enabled.swap(enabled, Ordering::Relaxed) == enabled {
+            return;
+        }
+        log::info!("set_video_enabled: {}", enabled);
+        // Same gate as change_video_track: a pipeline still starting up
+        // re-reads the flag on its next (re)build. Leaving trick play
+        // rebuilds it anyway.
+        if !enabled && self.trick_play() != 0 {
+            self.set_trick_play(0);
+        } else if self.pipeline_live.load(Ordering::Relaxed) {
+            self.seek(self.position());
+        }
+    }
+
+    /// Whether video is attached (see `set_video_enabled`).
+    pub fn video_enabled(&self) -> bool {
+        self.video_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Fast-forward (`speed > 0`) or rewind (`speed < 0`) through keyframes
+    /// only, at `speed` × real time — TV-remote scanning. The magnitude is
+    /// clamped to [`MIN_TRICK_SPEED`]..=[`MAX_TRICK_SPEED`]; 0 exits.
+    /// Keyframes come from a DASH trick-mode AdaptationSet when the manifest
+    /// has one, else from the SAP-aligned segments of the current
+    /// representation, of which only the `moof` and first sample are
+    /// downloaded. Audio is muted throughout, `Position` follows the
+    /// keyframe on screen, and a pause holds the picture. Changing speed
+    /// applies live; entering and exiting rebuild the pipeline, and exit
+    /// resumes normal playback exactly at the keyframe last shown. Running
+    /// off either end of the content exits on its own. Each change emits
+    /// `PlayerEvent::TrickPlayChanged`. No-op while video is detached.
+    pub fn set_trick_play(&self, speed: i32) {
+        let speed = speed.signum()
+            * speed
+                .unsigned_abs()
+                .clamp(MIN_TRICK_SPEED as u32, MAX_TRICK_SPEED as u32) as i32;
+        if speed != 0 && !self.video_enabled() {
+            return;
+        }
+        let prev = self.trick_speed.swap(speed, Ordering::Relaxed);
+        if prev == speed {
+            return;
+        }
+        log::info!("set_trick_play: {} -> {}", prev, speed);
+        let _ = self.events.send(PlayerEvent::TrickPlayChanged { speed });
+        // A speed change is picked up live; entering / leaving swaps the
+        // pipeline. position() is the keyframe on screen, so the exit seek
+        // lands on it frame-accurately.
+        if prev == 0 || speed == 0 {
+            self.seek(self.position());
+        }
+    }
+
+    /// Current trick-play speed, 0 when off (see `set_trick_play`).
+    pub fn trick_play(&self) -> i32 {
+        self.trick_speed.load(Ordering::Relaxed)
+    }
+
+    /// Play faster or slower than real time, 0.5× to 2× (clamped; NaN means
+    /// 1.0). Audio is time-stretched so pitch is preserved, video follows
+    /// the stretched audio clock and drops frames if decode can't keep up.
+    /// Takes effect live on the running pipeline and emits
+    /// `PlayerEvent::PlaybackRateChanged` when the rate actually changes.
+    ///
+    /// Bitstream passthrough can't be stretched: while it is engaged a rate
+    /// change restarts the pipeline at the current position so the track is
+    /// decoded to PCM instead. The download-ahead depth scales with the rate
+    /// from the next pipeline (re)build. Like the other knobs, the player
+    /// does not persist the value.
+    pub fn set_playback_rate(&self, rate: f32) {
+        let rate = time_stretch::clamp_rate(rate);
+        let prev = f32::from_bits(self.playback_rate.swap(rate.to_bits(), Ordering::Relaxed));
+        if prev == rate {
+            return;
+        }
+        log::info!("set_playback_rate: {} -> {}", prev, rate);
+        let _ = self.events.send(PlayerEvent::PlaybackRateChanged { rate });
+        if self.audio_renderer.is_passthrough() {
+            self.seek(self.position());
+        }
+    }
+
+    /// Current playback rate (1.0 = normal speed).
+    pub fn playback_rate(&self) -> f32 {
+        f32::from_bits(self.playback_rate.load(Ordering::Relaxed))
+    }
+
     /// Debug/compat switch: force HDR (PQ/HLG) video to decode to an
     /// 8-bit destination. The in-player HDR→SDR tonemap still runs — the
     /// picture stays colour-correct, just with 8-bit quantization of the
@@ -4261,6 +5892,20 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
             .store(enabled, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Force software video decode (libavcodec threads, frames in system
+    /// memory, CPU upload into the renderer) instead of D3D11VA / VAAPI.
+    /// Windows/Linux only; other platforms ignore it. Without it the
+    /// software path is still chosen automatically when the hw-device
+    /// can't be created (GPU-less host, VM, CI runner). Default OFF unless
+    /// the `RUST_PLAYER_SOFTWARE_DECODE` env var is set at construction.
+    ///
+    /// Takes effect at the next `play()`; `Stats::decoder_name` reports
+    /// `"Software (FFmpeg)"` while it is active.
+    pub fn set_software_video_decode(&self, enabled: bool) {
+        self.software_video_decode
+            .store(enabled, std::sync::atomic::Ordering::Relaxed);
+    }
+
     /// Initial playback position for the next `play()` (resume). Unlike
     /// `seek()` — which is fire-and-forget and races `play()`'s read of the
     /// seek target — this stores the position **synchronously**, so a
@@ -4284,7 +5929,8 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
     /// keeps its current capacity until it restarts.
     ///
     /// Clamped to at least 2s so the channel always has room for one
-    /// segment ahead of the decoder.
+    /// segment ahead of the decoder. The target is wall time: above 1×
+    /// (`set_playback_rate`) the player buffers proportionally more media.
     pub fn set_buffer_target_secs(&self, secs: u32) {
         self.buffer_target_secs.store(secs.max(2), Ordering::Relaxed);
     }
@@ -4305,8 +5951,59 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
         Ok(())
     }
 
-    /// Set the subtitle overlay's visual style — text/outline colour and a
-    /// size multiplier (see [`SubtitleStyle`]). Values are sanitised into
+    /// Provide an ordered subtitle font fallback list. The first font is
+    /// the regular face; each character a run's own face can't draw comes
+    /// from the first later font that has it — e.g. a Latin UI font, then
+    /// Noto Sans CJK, then Noto Sans Arabic — with the embedded DejaVu
+    /// last. An empty list reverts to DejaVu alone. All or nothing: if any
+    /// entry is invalid the installed fonts are kept and Err names it.
+    pub fn set_subtitle_fonts(&self, fonts: Vec<Vec<u8>>) -> Result<(), Box<dyn Error>> {
+        self.video_renderer
+            .set_subtitle_fonts(fonts)
+            .map_err(|e| -> Box<dyn Error> { format!("subtitle fonts: {}", e).into() })?;
+        Ok(())
+    }
+
+    /// Provide one face of the subtitle font family — e.g. the bold and
+    /// italic files next to the regular one — so styled cue runs render in
+    /// a real bold / italic design instead of a synthetic one.
+    /// [`SubtitleFontFace::Regular`] is the same as `set_subtitle_font`.
+    /// Invalid bytes leave the installed face untouched and return Err.
+    pub fn set_subtitle_font_face(
+        &self,
+        face: SubtitleFontFace,
+        bytes: Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.video_renderer
+            .set_subtitle_font_face(face, bytes)
+            .map_err(|e| -> Box<dyn Error> { format!("subtitle font ({:?}): {}", face, e).into() })?;
+        Ok(())
+    }
+
+    /// Provide the font for one generic caption family (monospaced serif,
+    /// casual, small capitals, …), drawn when a subtitle style picks it
+    /// through [`SubtitleStyle::font_family`]. A family with no font
+    /// installed draws in the regular font. On Android the system caption
+    /// fonts work, e.g. `/system/fonts/CutiveMono.ttf` for monospaced
+    /// serif or `ComingSoon.ttf` for casual. Invalid bytes, or
+    /// [`SubtitleFontFamily::Default`] (that is `set_subtitle_font`),
+    /// return Err and change nothing.
+    pub fn set_subtitle_family_font(
+        &self,
+        family: SubtitleFontFamily,
+        bytes: Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        self.video_renderer
+            .set_subtitle_family_font(family, bytes)
+            .map_err(|e| -> Box<dyn Error> {
+                format!("subtitle font ({:?}): {}", family, e).into()
+            })?;
+        Ok(())
+    }
+
+    /// Set the subtitle overlay's visual style — text and edge colours,
+    /// edge style, line and window boxes, font family and a size
+    /// multiplier (see [`SubtitleStyle`]). Values are sanitised into
     /// the safe rendering range before being applied, and the change takes
     /// effect on the next cue draw (any cached rasterization is dropped).
     ///
@@ -4319,6 +6016,58 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
         self.video_renderer.set_subtitle_style(style.sanitised());
     }
 
+    /// Add a sidecar subtitle file delivered outside the manifest. The file
+    /// is fetched through the player's `HttpClient` as a
+    /// `RequestKind::Segment` — so the request interceptor's auth applies —
+    /// parsed as `format`, and registered as an extra text adaptation at
+    /// the end of `get_tracks().text`, where it is listed and selected
+    /// (`set_subtitle_track`) like any manifest track. `label` names it in
+    /// track pickers; empty = none.
+    ///
+    /// Call after `prepare()`; a later `prepare()` rebuilds the track list
+    /// from the manifest and drops external tracks. Emits
+    /// `PlayerEvent::TextTracksChanged` once the track is listed. Returns
+    /// Err when the download fails or the file yields no cues.
+    pub async fn add_external_subtitle(
+        &self,
+        url: &str,
+        lang: &str,
+        label: &str,
+        format: SubtitleFormat,
+    ) -> Result<tracks::text::TextAdaptation, Box<dyn Error>> {
+        if self.tracks.lock().unwrap().is_none() {
+            return Err("No parsed tracks - player not prepared".into());
+        }
+        let bytes = self
+            .http
+            // Media payload, like a text track's segments — not an init.
+            .get(url.to_string(), RequestKind::Segment)
+            .await
+            .map_err(|e| -> Box<dyn Error> {
+                format!("external subtitle {}: {}", url, e).into()
+            })?;
+        let cues = format.parse(&bytes);
+        if cues.is_empty() {
+            return Err(format!("external subtitle {}: no {:?} cues parsed", url, format).into());
+        }
+        log::info!(
+            "[subs] external {:?} subtitle {} ({} cues, lang={:?})",
+            format,
+            url,
+            cues.len(),
+            lang
+        );
+        let adaptation = {
+            let mut tracks = self.tracks.lock().unwrap();
+            let tracks = tracks
+                .as_mut()
+                .ok_or("No parsed tracks - player not prepared")?;
+            tracks.add_external_text(url, lang, label, format, cues)
+        };
+        let _ = self.events.send(PlayerEvent::TextTracksChanged);
+        Ok(adaptation)
+    }
+
     /// Select a subtitle track. Spawns the text_play pipeline
     /// immediately — works regardless of whether `play()` is currently
     /// running, has finished, or hasn't been called yet. Single-file
@@ -4329,23 +6078,48 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
         // Wipe any cues from the previous track so they don't bleed
         // across the switch.
         self.video_renderer.clear_subtitles();
+        let session = self.host_subtitles.reset();
         log::info!(
             "[subs] selected representation id={} codecs={} mime={}",
             representation.id, representation.codecs, representation.mime_type
         );
 
-        // Spawn text_play right now. We don't track the handle — when
-        // clear_subtitle_track flips subtitle_representation to None
-        // the running task checks the flag between segments and exits.
+        let target = if self.host_subtitles.enabled() {
+            self.rt.spawn(host_subtitle_clock(
+                Arc::clone(&self.host_subtitles),
+                session,
+                Arc::clone(&self.position_ms),
+                Arc::clone(&self.playback_rate),
+            ));
+            CueTarget::Host(Arc::clone(&self.host_subtitles))
+        } else {
+            CueTarget::Overlay
+        };
+        self.spawn_text_play(representation, target, &self.subtitle_representation);
+    }
+
+    /// Spawn text_play right now. We don't track the handle — when the
+    /// `active` cell is cleared or re-pointed the running task checks it
+    /// between segments and exits.
+    fn spawn_text_play(
+        &self,
+        representation: &tracks::text::TextRepresenation,
+        target: CueTarget,
+        active: &Arc<StdMutex<Option<tracks::text::TextRepresenation>>>,
+    ) {
         let repr = representation.clone();
         let stop = self.stop.clone();
         let stop_flag = self.stop_flag.clone();
         let http = Arc::clone(&self.http);
         let sink = self.video_renderer.clone();
-        let active = Arc::clone(&self.subtitle_representation);
+        let captions = Arc::clone(&self.closed_captions);
+        let active = Arc::clone(active);
         let target_id = representation.id;
         self.rt.spawn(async move {
-            let res = text_play(repr, stop, stop_flag, http, sink, active, target_id).await;
+            let res = text_play(
+                repr, stop, stop_flag, http, sink, captions, target, active, target_id,
+            )
+            .await;
             if let Err(e) = res {
                 log::warn!("[subs] text_play exited: {}", e);
             }
@@ -4358,19 +6132,124 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
     pub fn clear_subtitle_track(&self) {
         *self.subtitle_representation.lock().unwrap() = None;
         self.video_renderer.clear_subtitles();
+        self.host_subtitles.reset();
+    }
+
+    /// Host-rendered subtitle mode. When enabled, the selected subtitle
+    /// track is still downloaded and parsed, but nothing is drawn by the
+    /// built-in overlay: cues arrive as `PlayerEvent::SubtitleCue` when
+    /// they come on screen and `SubtitleCueCleared` when the visible set
+    /// changes, timed against the rendered position. For apps that draw
+    /// subtitles in their own UI (accessibility services, custom fonts).
+    /// Default off. Switching mode re-selects the current track, so its
+    /// cues move to the new destination at once. Only the primary track is
+    /// delivered; a secondary track (`set_secondary_subtitle_track`) stays
+    /// selected but is not shown while host mode is on.
+    pub fn set_host_subtitles(&self, enabled: bool) {
+        if self.host_subtitles.set_enabled(enabled) == enabled {
+            return;
+        }
+        log::info!(
+            "[subs] host-rendered subtitles {}",
+            if enabled { "on" } else { "off" }
+        );
+        let current = self.subtitle_representation.lock().unwrap().clone();
+        match current {
+            Some(repr) => self.set_subtitle_track(&repr),
+            None => {
+                self.host_subtitles.reset();
+            }
+        }
+        let secondary = self
+            .secondary_subtitle_representation
+            .lock()
+            .unwrap()
+            .clone();
+        if let Some(repr) = secondary {
+            self.set_secondary_subtitle_track(&repr);
+        }
+    }
+
+    pub fn host_subtitles(&self) -> bool {
+        self.host_subtitles.enabled()
     }
 
     pub fn current_subtitle_representation(&self) -> Option<tracks::text::TextRepresenation> {
         self.subtitle_representation.lock().unwrap().clone()
     }
 
-    /// Convert the configured `buffer_target_secs` into a channel capacity
-    /// (segments-in-flight) using the conservative segment-duration estimate.
-    /// Floored at 2 so even with a tiny buffer target the decoder has room
-    /// for the next segment behind the one currently being processed.
-    fn segments_in_flight(&self) -> usize {
-        let secs = self.buffer_target_secs.load(Ordering::Relaxed).max(2);
-        ((secs / ASSUMED_SEGMENT_SECS) as usize).max(2)
+    /// Select a secondary subtitle track, shown together with the primary
+    /// one (dual subtitles — e.g. a second language for learners). It is
+    /// drawn at the top of the picture, or stacked above the primary cues,
+    /// per `set_secondary_subtitle_style`; its cues' own positioning is
+    /// ignored. Any text track from `get_tracks()` works, external ones
+    /// included. Not shown in host-rendered mode.
+    pub fn set_secondary_subtitle_track(&self, representation: &tracks::text::TextRepresenation) {
+        *self.secondary_subtitle_representation.lock().unwrap() = Some(representation.clone());
+        self.video_renderer.clear_secondary_subtitles();
+        if self.host_subtitles.enabled() {
+            log::info!(
+                "[subs] secondary representation id={} kept but hidden in host-rendered mode",
+                representation.id
+            );
+            return;
+        }
+        log::info!(
+            "[subs] selected secondary representation id={} codecs={} mime={}",
+            representation.id, representation.codecs, representation.mime_type
+        );
+        self.spawn_text_play(
+            representation,
+            CueTarget::SecondaryOverlay,
+            &self.secondary_subtitle_representation,
+        );
+    }
+
+    /// Remove the secondary subtitle track; the primary one is untouched.
+    pub fn clear_secondary_subtitle_track(&self) {
+        *self.secondary_subtitle_representation.lock().unwrap() = None;
+        self.video_renderer.clear_secondary_subtitles();
+    }
+
+    pub fn current_secondary_subtitle_representation(
+        &self,
+    ) -> Option<tracks::text::TextRepresenation> {
+        self.secondary_subtitle_representation
+            .lock()
+            .unwrap()
+            .clone()
+    }
+
+    /// Style of the secondary subtitle track and where it sits:
+    /// `SecondarySubtitlePosition::Top` (default) or `Stacked` directly
+    /// above the primary cues. Sanitised like `set_subtitle_style`. Until
+    /// this is called the secondary track uses the default style.
+    pub fn set_secondary_subtitle_style(
+        &self,
+        style: SubtitleStyle,
+        position: SecondarySubtitlePosition,
+    ) {
+        self.video_renderer
+            .set_secondary_subtitle_style(style.sanitised(), position);
+    }
+
+    /// Shift subtitle timing, in ms: positive shows cues later, negative
+    /// earlier — for sidecar files authored against a different cut.
+    /// Clamped to ±[`MAX_SUBTITLE_OFFSET_MS`]. Applies to both subtitle
+    /// tracks and to host-rendered cues, live. Not persisted.
+    pub fn set_subtitle_offset_ms(&self, offset_ms: i64) {
+        let offset_ms = offset_ms.clamp(-MAX_SUBTITLE_OFFSET_MS, MAX_SUBTITLE_OFFSET_MS);
+        let prev = self.subtitle_offset_ms.swap(offset_ms, Ordering::Relaxed);
+        if prev != offset_ms {
+            log::info!("set_subtitle_offset_ms: {} -> {}", prev, offset_ms);
+        }
+        self.video_renderer.set_subtitle_offset_ms(offset_ms);
+        self.host_subtitles.set_offset_ms(offset_ms);
+    }
+
+    /// Current subtitle timing offset in ms (+ = cues later).
+    pub fn subtitle_offset_ms(&self) -> i64 {
+        self.subtitle_offset_ms.load(Ordering::Relaxed)
     }
 
     /// One ABR reconsideration. Called from the per-second tick spawned in
@@ -4505,10 +6384,18 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
     /// the generic A/V sync loop. Only the concrete decoder types differ per
     /// platform; the rest of the pipeline is identical.
     pub fn play(&self) -> Result<JoinHandle<()>, Box<dyn Error>> {
-        let video_representation = match self.video_representation.lock().unwrap().as_ref() {
-            Some(r) => r.clone(),
-            None => return Err("Video Track not set".into()),
-        };
+        // No video track is only legal for an audio-only manifest, which
+        // plays off the audio clock alone (as does `set_video_enabled(false)`).
+        let video_representation = self.video_representation.lock().unwrap().clone();
+        let has_video_tracks = self
+            .tracks
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or(true, |t| !t.video.is_empty());
+        if video_representation.is_none() && has_video_tracks {
+            return Err("Video Track not set".into());
+        }
         let audio_representation = match self.audio_representation.lock().unwrap().as_ref() {
             Some(r) => r.clone(),
             None => return Err("Audio Track not set".into()),
@@ -4519,7 +6406,9 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
         // (kind=2 = 0 requests, eternal buffering, no error). This happens when
         // a SegmentBase/sidx yields no subsegments (e.g. a mis-parsed sidx).
         // Fail loud instead of wedging so the cause is visible.
-        if video_representation.segments.is_empty() {
+        if let Some(video_representation) =
+            video_representation.as_ref().filter(|r| r.segments.is_empty())
+        {
             let msg = format!(
                 "video representation {} has no media segments (SegmentBase/sidx yielded none)",
                 video_representation.id
@@ -4572,8 +6461,21 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 // codec instead of recreating the D3D11/VAAPI device. Recreating
                 // it is slow and on Windows stalls the wgpu present + the DWM
                 // compositor, hitching the whole UI for a moment on each switch.
-                // Fall back to a per-decoder device if creation fails.
-                let factory: VideoDecoderFactory =
+                // Fall back to a per-decoder device if creation fails; a
+                // decoder that can't create one either (GPU-less host, VM, CI
+                // runner) and a forced switch mean software decode with CPU
+                // upload in the renderer.
+                let software: VideoDecoderFactory = Arc::new(|| {
+                    Box::new(decoders::ffmpeg_hw::FfmpegHwDecoder::new_software())
+                        as Box<dyn HwVideoDecoder>
+                });
+                let factory: VideoDecoderFactory = if self
+                    .software_video_decode
+                    .load(Ordering::Relaxed)
+                {
+                    log::info!("[video] software decode forced");
+                    software
+                } else {
                     match decoders::ffmpeg_hw::SharedHwDevice::new() {
                         Ok(dev) => {
                             log::info!("[video] shared hw-device created; ABR swaps reuse it");
@@ -4587,12 +6489,16 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                             log::warn!(
                                 "[video] shared hw-device init failed ({e}); per-decoder device fallback"
                             );
-                            Arc::new(|| {
-                                Box::new(decoders::ffmpeg_hw::FfmpegHwDecoder::new())
-                                    as Box<dyn HwVideoDecoder>
+                            // One latch per play() cycle, as on a shared device.
+                            let av1_hw_unsupported = Arc::new(AtomicBool::new(false));
+                            Arc::new(move || {
+                                Box::new(decoders::ffmpeg_hw::FfmpegHwDecoder::new(Arc::clone(
+                                    &av1_hw_unsupported,
+                                ))) as Box<dyn HwVideoDecoder>
                             })
                         }
-                    };
+                    }
+                };
                 factory
             }
             #[cfg(target_os = "android")]
@@ -4625,12 +6531,10 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
         let abr_player = self.clone();
         // Capture the configured buffer target at play() time so the
         // spawned pipeline stays consistent across its lifetime even if
-        // the consumer flips set_buffer_target_secs mid-play.
-        let seg_in_flight = self.segments_in_flight();
-        log::info!(
-            "play(): buffer target {}s -> {} segments in flight",
-            self.buffer_target_secs.load(Ordering::Relaxed), seg_in_flight
-        );
+        // the consumer flips set_buffer_target_secs mid-play. The playback
+        // rate, by contrast, is re-read at every (re)build below.
+        let buffer_target_secs = self.buffer_target_secs.load(Ordering::Relaxed);
+        let playback_rate = Arc::clone(&self.playback_rate);
         // Oneshot used to kill the abr_tick task when this play() invocation
         // ends FOR REAL (outer loop break — either no track selected or a
         // genuine stop()). Critically NOT tied to `stop_flag`: that gets
@@ -4656,6 +6560,16 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
         let pipeline_live = Arc::clone(&self.pipeline_live);
         let audio_passthrough = Arc::clone(&self.audio_passthrough);
         let hdr_decode_8bit = Arc::clone(&self.hdr_decode_8bit);
+        let closed_captions = Arc::clone(&self.closed_captions);
+        let downmix = Arc::clone(&self.downmix);
+        let loudness = Arc::clone(&self.loudness);
+        let loudness_dsp = Arc::clone(&self.loudness_dsp);
+        let audio_processors = Arc::clone(&self.audio_processors);
+        let av_offset_ms = Arc::clone(&self.av_offset_ms);
+        let video_enabled = Arc::clone(&self.video_enabled);
+        let frame_step = Arc::clone(&self.frame_step);
+        let trick_speed = Arc::clone(&self.trick_speed);
+        let video_adaptation_cell = Arc::clone(&self.video_adaptation);
         let play = tokio::spawn(async move {
             // ABR tick runs once for the whole play() lifetime (survives
             // every seek/track-switch restart below). On Manual it's a
@@ -4665,13 +6579,29 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 ticker.set_missed_tick_behavior(
                     tokio::time::MissedTickBehavior::Skip,
                 );
+                // The audio output reopening at another rate / channel count
+                // (device switch) leaves the decoders resampling for the old
+                // one; rebuild at the current position so they re-target.
+                // `format_changed` wakes the loop the moment that happens,
+                // rather than up to a tick later with the sync loop dropping
+                // every sample meanwhile.
+                let mut audio_format = abr_player.audio_renderer.format_generation();
                 loop {
                     tokio::select! {
-                        _ = ticker.tick() => {
-                            abr_player.abr_tick();
-                        }
+                        _ = ticker.tick() => abr_player.abr_tick(),
+                        _ = abr_player.audio_renderer.format_changed() => {}
                         _ = &mut abr_kill_rx => break,
                     }
+                    // Checked on every tick too: a change that landed while
+                    // the pipeline wasn't live yet is picked up once it is.
+                    let format_now = abr_player.audio_renderer.format_generation();
+                    if format_now != audio_format
+                        && abr_player.pipeline_live.load(Ordering::Relaxed)
+                    {
+                        audio_format = format_now;
+                        log::info!("[play] audio output format changed, rebuilding pipeline");
+                        abr_player.seek(abr_player.position());
+                    }
                 }
             });
 
@@ -4711,17 +6641,21 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
 
                 // Re-read the current selection so a track switch that arrived
                 // alongside the seek takes effect on restart.
-                let video_representation = match video_repr_cell.lock().unwrap().clone() {
-                    Some(v) => v,
-                    None => break,
-                };
+                let video_representation = video_repr_cell.lock().unwrap().clone();
                 let audio_representation = match audio_repr_cell.lock().unwrap().clone() {
                     Some(a) => a,
                     None => break,
                 };
+                // Audio-only pipeline: an audio-only manifest (no video track
+                // to select) or video detached by the host. Re-read on every
+                // (re)build, so set_video_enabled's seek flips it live.
+                let audio_only =
+                    video_representation.is_none() || !video_enabled.load(Ordering::Relaxed);
                 // A zero-segment representation would buffer forever — fail loud
                 // instead of wedging (also guards a restart onto a broken rep).
-                if video_representation.segments.is_empty()
+                if video_representation
+                    .as_ref()
+                    .is_some_and(|v| v.segments.is_empty())
                     || audio_representation.segments.is_empty()
                 {
                     let _ = events.send(PlayerEvent::Error {
@@ -4735,8 +6669,12 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 // media PTS / sidx EPT are absolute (non-zero
                 // baseMediaDecodeTime), so subtracting this exposes a 0-based
                 // position/seek to consumers (matches the 0-based duration).
-                let origin = video_representation
-                    .segments
+                // Taken from the video track even while it is detached, so
+                // positions don't shift when video is re-attached.
+                let entry_segments = video_representation
+                    .as_ref()
+                    .map_or(&audio_representation.segments, |v| &v.segments);
+                let origin = entry_segments
                     .first()
                     .map(|s| s.start_time())
                     .unwrap_or(Duration::ZERO);
@@ -4744,8 +6682,7 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 // seek_offset is 0-based; map to the absolute media timeline to
                 // locate the segment.
                 let abs_offset = seek_offset + origin;
-                let video_start_index =
-                    find_segment_index(&video_representation.segments, abs_offset);
+                let video_start_index = find_segment_index(entry_segments, abs_offset);
                 let audio_start_index =
                     find_segment_index(&audio_representation.segments, abs_offset);
 
@@ -4755,7 +6692,7 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 // the target (`discard_below_us`, absolute pts) and audio trims
                 // to it, so playback lands exactly where the user aimed instead
                 // of snapping back to the segment boundary.
-                let entry_seg = video_representation.segments.get(video_start_index);
+                let entry_seg = entry_segments.get(video_start_index);
                 let seg_start = entry_seg
                     .map(|s| s.start_time())
                     .unwrap_or(abs_offset)
@@ -4781,7 +6718,7 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                     "[play] start: target={}ms seg_start={}ms discard_below={}ms vidx={} aidx={} segs={}",
                     seek_offset.as_millis(), seg_start.as_millis(), discard_below_us / 1000,
                     video_start_index, audio_start_index,
-                    video_representation.segments.len()
+                    entry_segments.len()
                 );
                 // Anchor position/clock to the TARGET (video discards to it,
                 // audio trims to it) — not the segment start.
@@ -4792,6 +6729,12 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 stats
                     .audio_last_decoded_pts_ms
                     .store(seek_offset.as_millis() as i64, Ordering::Relaxed);
+                let rate_now = f32::from_bits(playback_rate.load(Ordering::Relaxed));
+                let seg_in_flight = segments_in_flight(buffer_target_secs, rate_now);
+                log::info!(
+                    "[play] buffer target {}s at {}x -> {} segments in flight",
+                    buffer_target_secs, rate_now, seg_in_flight
+                );
                 // Clear stale starvation state so the fresh pipeline can emit
                 // its initial Playing event.
                 stats.video_starving.store(false, Ordering::Relaxed);
@@ -4804,23 +6747,105 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                     .clone()
                     .map(|d| d as Arc<dyn Decryptor>);
 
-                // Per-iteration audio decoder (consumed by audio_play).
+                // Trick play replaces the pipeline: keyframes are fetched and
+                // shown straight off its virtual clock while the audio sink
+                // stays flushed and paused (muted). Leaving it comes back
+                // through here via a seek to the last keyframe shown.
+                let trick = trick_speed.load(Ordering::Relaxed);
+                if trick != 0 && audio_only {
+                    // Video detached since — nothing to scan.
+                    trick_speed.store(0, Ordering::Relaxed);
+                    let _ = events.send(PlayerEvent::TrickPlayChanged { speed: 0 });
+                } else if let Some(main) = video_representation.as_ref().filter(|_| trick != 0) {
+                    let trick_repr = video_adaptation_cell
+                        .lock()
+                        .unwrap()
+                        .as_ref()
+                        .and_then(|a| {
+                            pick_trick_representation(&a.trick_representations, main.height).cloned()
+                        });
+                    // Not a live pipeline: keeps the ABR tick and format
+                    // rebuilds off.
+                    pipeline_live.store(false, Ordering::Relaxed);
+                    let from_trick = trick_repr.is_some();
+                    let repr = trick_repr.unwrap_or_else(|| main.clone());
+                    log::info!(
+                        "[trick] {}x from {} representation {} at {}ms",
+                        trick,
+                        if from_trick { "trick-mode" } else { "main" },
+                        repr.id,
+                        seek_offset.as_millis()
+                    );
+                    let result = trick_play(
+                        repr,
+                        from_trick,
+                        seek_offset,
+                        origin,
+                        media_duration,
+                        Arc::clone(&trick_speed),
+                        paused.clone(),
+                        stop.clone(),
+                        stop_flag.clone(),
+                        decryptor_snapshot.clone(),
+                        video_decoder_factory(),
+                        Arc::clone(&http),
+                        video_sink.clone(),
+                        position_ms.clone(),
+                        Arc::clone(&events),
+                        Arc::clone(&stats),
+                        video_output_window.get(),
+                        hdr_decode_8bit.load(Ordering::Relaxed),
+                    )
+                    .await;
+                    let resume_at = match result {
+                        Ok(at) => at,
+                        Err(e) => {
+                            log::warn!("[trick] {} — back to normal playback", e);
+                            Some(Duration::from_millis(position_ms.load(Ordering::Relaxed)))
+                        }
+                    };
+                    // Ran off the content (or failed): leave trick play
+                    // where it stopped, unless a seek already superseded it.
+                    if let Some(at) = resume_at {
+                        if trick_speed.swap(0, Ordering::Relaxed) != 0 {
+                            let _ = events.send(PlayerEvent::TrickPlayChanged { speed: 0 });
+                        }
+                        let mut target = seek_target.write().await;
+                        if target.is_none() {
+                            *target = Some(at);
+                        }
+                    }
+                    if seek_target.read().await.is_none() {
+                        break;
+                    }
+                    continue;
+                }
+
+                // Audio decoder factory — a fresh decoder per audio_play (the
+                // initial one and every audio_supervisor retry).
                 #[cfg(any(
                     target_os = "windows",
                     target_os = "linux",
                     target_os = "macos",
                     target_os = "ios"
                 ))]
-                let audio_decoder: Box<dyn AudioDecoder> =
-                    Box::new(decoders::ffmpeg_audio::FfmpegAudioDecoder::new());
+                let audio_decoder_factory: AudioDecoderFactory = Arc::new(|| {
+                    Box::new(decoders::ffmpeg_audio::FfmpegAudioDecoder::new()) as Box<dyn AudioDecoder>
+                });
                 #[cfg(target_os = "android")]
-                let audio_decoder: Box<dyn AudioDecoder> =
-                    Box::new(decoders::mediacodec_audio::MediaCodecAudioDecoder::new());
+                let audio_decoder_factory: AudioDecoderFactory = Arc::new(|| {
+                    Box::new(decoders::mediacodec_audio::MediaCodecAudioDecoder::new())
+                        as Box<dyn AudioDecoder>
+                });
 
-                // Fresh per-iteration switch channel for ABR soft-swaps.
+                // Fresh per-iteration switch channel for ABR soft-swaps. Not
+                // installed for an audio-only pipeline: a swap then just
+                // stores the representation for the next video (re)build.
                 let (switch_tx, switch_rx) =
                     tokio::sync::watch::channel::<Option<VideoRepresenation>>(None);
-                *video_switch_slot.lock().unwrap() = Some(switch_tx);
+                if !audio_only {
+                    *video_switch_slot.lock().unwrap() = Some(switch_tx);
+                }
 
                 // Capacity 8: keeps concurrent D3D11VA surfaces (DPB ~7 + pipeline)
                 // well under Intel Arc A750's driver limit of ~21 individual
@@ -4854,37 +6879,80 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                 // if a superseded generation keeps running (orphaned pipeline).
                 static PIPELINE_GEN: AtomicU64 = AtomicU64::new(0);
                 let gen = PIPELINE_GEN.fetch_add(1, Ordering::Relaxed);
-                let video = tokio::spawn(video_supervisor(
-                    gen,
-                    video_representation,
-                    video_start_index,
-                    frame_sender,
-                    video_ready.clone(),
-                    stop.clone(),
-                    stop_flag.clone(),
-                    decryptor_snapshot.clone(),
-                    video_decoder_factory.clone(),
-                    Arc::clone(&http),
-                    Arc::clone(&stats),
-                    switch_rx,
-                    position_ms.clone(),
-                    Arc::clone(&events),
-                    seg_in_flight,
-                    origin,
-                    direct_window,
-                    Arc::clone(&hdr_decode_8bit),
-                    Arc::clone(&pending_resume),
-                ));
+                let video = match video_representation.filter(|_| !audio_only) {
+                    Some(repr) => Some(tokio::spawn(video_supervisor(
+                        gen,
+                        repr,
+                        video_start_index,
+                        frame_sender,
+                        video_ready.clone(),
+                        stop.clone(),
+                        stop_flag.clone(),
+                        decryptor_snapshot.clone(),
+                        video_decoder_factory.clone(),
+                        Arc::clone(&http),
+                        Arc::clone(&stats),
+                        switch_rx,
+                        position_ms.clone(),
+                        Arc::clone(&events),
+                        seg_in_flight,
+                        origin,
+                        direct_window,
+                        Arc::clone(&hdr_decode_8bit),
+                        Arc::clone(&closed_captions),
+                        Arc::clone(&pending_resume),
+                    ))),
+                    None => {
+                        log::info!("[play] audio-only pipeline (no video track or video detached)");
+                        // Closes the frame channel: nothing decodes video.
+                        drop(frame_sender);
+                        None
+                    }
+                };
 
                 let sample_rate = audio_sink.sample_rate();
+                let channels = audio_sink.channels();
+                let downmix_now = **downmix.load();
                 // Audio passthrough decision: host opted in AND the selected
-                // track is a passthrough codec. The sink create self-gates
-                // (None on unsupported → PCM). When engaged, feed raw AUs to
-                // the bitstream sink and let av_sync's audio_sync_loop no-op
-                // (its sample channel is dropped → recv None → returns).
+                // track is a passthrough codec AND we play at normal speed (a
+                // bitstream can't be time-stretched). The sink create
+                // self-gates (None on unsupported → PCM). When engaged, feed
+                // raw AUs to the bitstream sink and let av_sync's
+                // audio_sync_loop no-op (its sample channel is dropped → recv
+                // None → returns).
                 let want_passthrough = audio_passthrough.load(Ordering::Relaxed)
+                    && rate_now == 1.0
                     && matches!(audio_representation.codecs.as_str(), "ec-3" | "ac-3");
-                let audio;
+                let audio_segments = audio_representation.segments.clone();
+                // PCM pipeline spawner; holds the sample channel's sender for
+                // every retry, so replacing it (passthrough) closes the channel.
+                #[allow(unused_mut)]
+                let mut audio_pipeline: AudioPipelineSpawner = {
+                    let repr = audio_representation.clone();
+                    let stop = stop.clone();
+                    let stop_flag = stop_flag.clone();
+                    let decryptor = decryptor_snapshot.clone();
+                    let http = Arc::clone(&http);
+                    let stats = Arc::clone(&stats);
+                    Box::new(move |start_index: usize, audio_ready: Arc<Notify>| {
+                        task::spawn(audio_play(
+                            repr.clone(),
+                            start_index,
+                            audio_ready,
+                            sample_sender.clone(),
+                            sample_rate,
+                            channels,
+                            downmix_now,
+                            stop.clone(),
+                            stop_flag.clone(),
+                            decryptor.clone(),
+                            audio_decoder_factory(),
+                            Arc::clone(&http),
+                            Arc::clone(&stats),
+                            seg_in_flight,
+                        ))
+                    })
+                };
                 #[cfg(target_os = "android")]
                 {
                     let pt_sink: Option<Arc<dyn crate::renderers::AudioPassthrough>> =
@@ -4916,58 +6984,50 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                     if pt_sink.is_some() {
                         audio_sink.set_paused(paused.load(Ordering::Relaxed));
                     }
-                    audio = if let Some(sink) = pt_sink {
-                        drop(sample_sender);
+                    if let Some(sink) = pt_sink {
                         log::info!("[audio] passthrough engaged ({})", audio_representation.codecs);
-                        tokio::spawn(audio_passthrough_play(
-                            audio_representation,
-                            audio_start_index,
-                            sink,
-                            audio_ready.clone(),
-                            stop.clone(),
-                            stop_flag.clone(),
-                            decryptor_snapshot,
-                            Arc::clone(&http),
-                            Arc::clone(&stats),
-                            seg_in_flight,
-                            discard_below_us,
-                            Arc::clone(&pipeline_live),
-                        ))
-                    } else {
-                        tokio::spawn(audio_play(
-                            audio_representation,
-                            audio_start_index,
-                            audio_ready.clone(),
-                            sample_sender,
-                            sample_rate,
-                            stop.clone(),
-                            stop_flag.clone(),
-                            decryptor_snapshot,
-                            audio_decoder,
-                            Arc::clone(&http),
-                            Arc::clone(&stats),
-                            seg_in_flight,
-                        ))
-                    };
+                        let repr = audio_representation.clone();
+                        let stop = stop.clone();
+                        let stop_flag = stop_flag.clone();
+                        let decryptor = decryptor_snapshot.clone();
+                        let http = Arc::clone(&http);
+                        let stats = Arc::clone(&stats);
+                        let pipeline_live = Arc::clone(&pipeline_live);
+                        let cursor = Arc::new(PassthroughCursor::new());
+                        audio_pipeline = Box::new(move |start_index: usize, audio_ready: Arc<Notify>| {
+                            task::spawn(audio_passthrough_play(
+                                repr.clone(),
+                                start_index,
+                                Arc::clone(&sink),
+                                audio_ready,
+                                stop.clone(),
+                                stop_flag.clone(),
+                                decryptor.clone(),
+                                Arc::clone(&http),
+                                Arc::clone(&stats),
+                                seg_in_flight,
+                                discard_below_us,
+                                Arc::clone(&pipeline_live),
+                                Arc::clone(&cursor),
+                            ))
+                        });
+                    }
                 }
                 #[cfg(not(target_os = "android"))]
-                {
-                    let _ = want_passthrough;
-                    audio = tokio::spawn(audio_play(
-                        audio_representation,
-                        audio_start_index,
-                        audio_ready.clone(),
-                        sample_sender,
-                        sample_rate,
-                        stop.clone(),
-                        stop_flag.clone(),
-                        decryptor_snapshot,
-                        audio_decoder,
-                        Arc::clone(&http),
-                        Arc::clone(&stats),
-                        seg_in_flight,
-                    ));
-                }
+                let _ = want_passthrough;
+                let audio = tokio::spawn(audio_supervisor(
+                    audio_pipeline,
+                    audio_segments,
+                    audio_start_index,
+                    audio_ready.clone(),
+                    stop.clone(),
+                    stop_flag.clone(),
+                    Arc::clone(&stats),
+                    position_ms.clone(),
+                    Arc::clone(&events),
+                    origin,
+                    Arc::clone(&pending_resume),
+                ));
 
                 // New pipeline: not "live" until it produces its first frame.
                 // Gates the ABR tick off this fragile startup window.
@@ -4991,12 +7051,28 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
                     pause_notify.clone(),
                     Arc::clone(&stats),
                     Arc::clone(&pipeline_live),
+                    Arc::clone(&playback_rate),
+                    Arc::clone(&loudness),
+                    Arc::clone(&loudness_dsp),
+                    Arc::clone(&audio_processors),
+                    Arc::clone(&av_offset_ms),
+                    origin,
+                    Arc::clone(&frame_step),
+                    audio_only,
                 )
                 .await;
 
+                let video = async move {
+                    match video {
+                        Some(v) => Some(v.await),
+                        None => None,
+                    }
+                };
                 let (play_res, audio_res) = join!(video, audio);
-                log_task_result("video_supervisor", play_res);
-                log_task_result("audio_play", audio_res);
+                if let Some(play_res) = play_res {
+                    log_task_result("video_supervisor", play_res);
+                }
+                log_task_result("audio_supervisor", audio_res);
 
                 // Drop the watch sender so a stale apply_video_representation
                 // between pipelines becomes a no-op.
@@ -5016,6 +7092,10 @@ impl<V: VideoSink, A: AudioSink> Player<V, A> {
     }
 
     pub fn seek(&self, target: Duration) {
+        // The new pipeline starts in step with audio at the target; steps
+        // still queued against the old one are moot.
+        self.frame_step.forward.store(0, Ordering::Relaxed);
+        self.frame_step.dirty.store(false, Ordering::Relaxed);
         let seek_target = self.seek_target.clone();
         let stop = self.stop.clone();
         let stop_flag = self.stop_flag.clone();
@@ -5402,21 +7482,31 @@ fn decrypt_segment_in_place(
         if end > data_vec.len() {
             continue;
         }
-        // Per-sample "clear" entries also exist within an encrypted senc:
-        //   - IV all-zeros AND no subsamples → sample is clear
-        //   - subsamples list present but every entry has encrypted=0
-        // In both cases applying the keystream is a no-op anyway (CTR with
-        // IV=0 still XORs against a real keystream, breaking the data), so
-        // we must detect and skip.
-        let iv_is_zero = entry.iv.iter().all(|&b| b == 0);
-        let no_encrypted_bytes = !entry.subsamples.is_empty()
-            && entry.subsamples.iter().all(|&(_, enc)| enc == 0);
-        if iv_is_zero || no_encrypted_bytes {
-            continue;
-        }
-        tc.decryptor
-            .decrypt_sample(&tc.kid, &entry.iv, &mut data_vec[*offset..end], &entry.subsamples)?;
+        decrypt_sample_in_place(tc, entry, &mut data_vec[*offset..end])?;
+    }
+    Ok(())
+}
+
+/// Decrypt one sample in place with its `senc` entry.
+fn decrypt_sample_in_place(
+    tc: &TrackCrypto,
+    entry: &SencEntry,
+    sample: &mut [u8],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    // Per-sample "clear" entries also exist within an encrypted senc:
+    //   - IV all-zeros AND no subsamples → sample is clear
+    //   - subsamples list present but every entry has encrypted=0
+    // In both cases applying the keystream is a no-op anyway (CTR with
+    // IV=0 still XORs against a real keystream, breaking the data), so
+    // we must detect and skip.
+    let iv_is_zero = entry.iv.iter().all(|&b| b == 0);
+    let no_encrypted_bytes =
+        !entry.subsamples.is_empty() && entry.subsamples.iter().all(|&(_, enc)| enc == 0);
+    if iv_is_zero || no_encrypted_bytes {
+        return Ok(());
     }
+    tc.decryptor
+        .decrypt_sample(&tc.kid, &entry.iv, sample, &entry.subsamples)?;
     Ok(())
 }
 
@@ -5534,6 +7624,253 @@ mod tests {
         .expect("stub segment")
     }
 
+    // ---------------- playback rate ----------------
+
+    #[test]
+    fn rate_timeline_is_continuous_across_changes() {
+        let t = RateTimeline::new(10_000_000, 1.0);
+        assert_eq!(t.at(2_000_000), (12_000_000, 1.0));
+        // 2 s of output at 1x, then 2x from there on.
+        t.push(2_000_000, 2.0);
+        assert_eq!(t.latest_rate(), 2.0);
+        assert_eq!(t.at(2_000_000).0, 12_000_000);
+        assert_eq!(t.at(3_000_000), (14_000_000, 2.0));
+        // Looking behind the change still uses the 1x piece.
+        assert_eq!(t.at(1_000_000), (11_000_000, 1.0));
+        t.push(4_000_000, 0.5);
+        assert_eq!(t.at(6_000_000), (17_000_000, 0.5));
+    }
+
+    #[test]
+    fn segments_in_flight_scale_with_fast_playback_only() {
+        assert_eq!(segments_in_flight(8, 1.0), 4);
+        assert_eq!(segments_in_flight(8, 2.0), 8);
+        assert_eq!(segments_in_flight(8, 0.5), 4);
+        assert_eq!(segments_in_flight(0, 1.0), 2);
+    }
+
+    // ---------------- A/V offset ----------------
+
+    /// Sink whose device clock reads a fixed `played_ms`.
+    struct FixedClockSink(u64);
+
+    impl AudioSink for FixedClockSink {
+        fn put_samples<'a>(&'a self, _samples: &'a [f32]) -> impl std::future::Future<Output = ()> + Send + 'a {
+            async {}
+        }
+        fn sample_rate(&self) -> u32 {
+            48_000
+        }
+        fn played_ms(&self) -> Option<u64> {
+            Some(self.0)
+        }
+        fn flush(&self) {}
+        fn stop(&self) -> impl std::future::Future<Output = ()> + Send + '_ {
+            async {}
+        }
+        fn set_volume(&self, _volume: f32) {}
+        fn get_volume(&self) -> f32 {
+            1.0
+        }
+        fn set_paused(&self, _paused: bool) {}
+    }
+
+    #[test]
+    fn av_offset_holds_the_clock_back_in_output_time() {
+        let offset = Arc::new(AtomicI32::new(0));
+        let timeline = Arc::new(RateTimeline::new(10_000_000, 1.0));
+        let clock = MediaClock::new(
+            Arc::new(FixedClockSink(2_000)),
+            Arc::new(Instant::now()),
+            Duration::from_secs(10),
+            0,
+            Arc::clone(&timeline),
+            Arc::clone(&offset),
+        );
+        let near = |got: i64, want: i64| (got - want).abs() < 5_000;
+        assert!(near(clock.now_us(Duration::ZERO), 12_000_000));
+        offset.store(300, Ordering::Relaxed);
+        assert!(near(clock.now_us(Duration::ZERO), 11_700_000));
+        offset.store(-300, Ordering::Relaxed);
+        assert!(near(clock.now_us(Duration::ZERO), 12_300_000));
+        // At 2x the same perceived delay spans twice the media.
+        timeline.push(2_000_000, 2.0);
+        offset.store(300, Ordering::Relaxed);
+        assert!(near(clock.now_us(Duration::ZERO), 11_400_000));
+    }
+
+    // ---------------- audio-only clock ----------------
+
+    #[tokio::test]
+    async fn audio_clock_loop_reports_position_off_the_audio_clock() {
+        let (tx, mut rx) = broadcast::channel(16);
+        let position = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(Notify::new());
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let task = tokio::spawn(audio_clock_loop(
+            Arc::new(Instant::now()),
+            Duration::from_secs(10),
+            0,
+            Arc::new(FixedClockSink(2_000)),
+            Arc::clone(&position),
+            Arc::clone(&stop),
+            Arc::clone(&stop_flag),
+            Arc::new(tx),
+            Duration::from_secs(60),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Notify::new()),
+            Arc::new(StatsState::default()),
+            Arc::new(RateTimeline::new(10_000_000, 1.0)),
+        ));
+        // Seek offset 10 s + 2 s played.
+        let near = |ms: u64| ms.abs_diff(12_000) < 100;
+        assert!(matches!(rx.recv().await, Ok(PlayerEvent::Playing)));
+        match rx.recv().await {
+            Ok(PlayerEvent::Position { position, duration, .. }) => {
+                assert!(near(position.as_millis() as u64), "{position:?}");
+                assert_eq!(duration, Duration::from_secs(60));
+            }
+            other => panic!("expected Position, got {other:?}"),
+        }
+        assert!(matches!(
+            rx.recv().await,
+            Ok(PlayerEvent::Stats { current_resolution: None, av_drift_ms: None, .. })
+        ));
+        stop_flag.store(true, Ordering::Relaxed);
+        stop.notify_waiters();
+        task.await.unwrap();
+        assert!(near(position.load(Ordering::Relaxed)));
+    }
+
+    // ---------------- audio retry ----------------
+
+    #[test]
+    fn retry_budget_exhausts_then_refills_after_progress() {
+        let mut budget = RetryBudget::default();
+        assert_eq!(budget.fail(1_000), Some(1));
+        assert_eq!(budget.fail(2_000), Some(2));
+        assert_eq!(budget.fail(3_000), Some(3));
+        assert_eq!(budget.fail(4_000), None);
+        // Real progress since the last failure starts a fresh budget.
+        let mut budget = RetryBudget::default();
+        assert_eq!(budget.fail(1_000), Some(1));
+        assert_eq!(budget.fail(2_000), Some(2));
+        assert_eq!(budget.fail(20_000), Some(1));
+    }
+
+    #[test]
+    fn frame_step_backward_target_lands_on_the_previous_frame() {
+        let step = FrameStep::default();
+        assert_eq!(step.backward_target(1), None);
+        // 24000/1001 fps: frames 41_708 µs apart.
+        step.presented(1_000_000, None);
+        step.presented(1_041_708, Some(1_000_000));
+        // A restart's jump isn't an interval.
+        step.presented(5_000_000, Some(1_041_708));
+        assert_eq!(step.interval_us.load(Ordering::Relaxed), 41_708);
+        // One back: the previous frame (4_958_292), sought a quarter
+        // interval early at 4_947_865 so the seek lands on it.
+        assert_eq!(step.backward_target(1), Some(Duration::from_micros(4_947_865)));
+        assert_eq!(step.backward_target(1_000), Some(Duration::ZERO));
+        assert!(!step.take_forward());
+        step.forward.store(2, Ordering::Relaxed);
+        assert!(step.take_forward() && step.take_forward() && !step.take_forward());
+    }
+
+    /// Sink counting the samples pushed to it.
+    #[derive(Default)]
+    struct CountingSink(std::sync::atomic::AtomicUsize);
+
+    impl AudioSink for CountingSink {
+        fn put_samples<'a>(&'a self, samples: &'a [f32]) -> impl std::future::Future<Output = ()> + Send + 'a {
+            self.0.fetch_add(samples.len(), Ordering::Relaxed);
+            async {}
+        }
+        fn sample_rate(&self) -> u32 {
+            48_000
+        }
+        fn flush(&self) {}
+        fn stop(&self) -> impl std::future::Future<Output = ()> + Send + '_ {
+            async {}
+        }
+        fn set_volume(&self, _volume: f32) {}
+        fn get_volume(&self) -> f32 {
+            1.0
+        }
+        fn set_paused(&self, _paused: bool) {}
+    }
+
+    #[tokio::test]
+    async fn audio_sync_loop_splices_off_a_retried_pipelines_overlap() {
+        // 48 kHz stereo: 960 samples = 10 ms.
+        let mk = |pts_ms, ms: usize| DecodedAudioFrame { pts_ms, samples: vec![0.1; ms * 96] };
+        let (tx, rx) = mpsc::channel(16);
+        for frame in [
+            mk(0, 10),
+            mk(10, 10),
+            mk(20, 10),
+            // Retry restarts at the segment start: already pushed.
+            mk(0, 10),
+            mk(10, 10),
+            mk(20, 10),
+            // Straddles the splice point: the first 15 ms go.
+            mk(15, 20),
+            mk(35, 10),
+        ] {
+            tx.send(frame).await.unwrap();
+        }
+        drop(tx);
+        let sink = Arc::new(CountingSink::default());
+        audio_sync_loop(
+            rx,
+            Arc::clone(&sink),
+            0,
+            Arc::new(Notify::new()),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(StatsState::default()),
+            Arc::new(broadcast::channel(16).0),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(RateTimeline::new(0, 1.0)),
+            Arc::new(AtomicU32::new(1.0_f32.to_bits())),
+            Arc::new(ArcSwap::from_pointee(LoudnessParams::DEFAULT)),
+            Arc::new(StdMutex::new(None)),
+            Arc::new(AudioProcessorChain::default()),
+        )
+        .await;
+        assert_eq!(sink.0.load(Ordering::Relaxed), (30 + 5 + 10) * 96);
+    }
+
+    // ---------------- trim_leading_frames ----------------
+
+    #[test]
+    fn trim_leading_frames_spans_frames_and_advances_pts() {
+        // 48 kHz: 480 stereo frames = 10 ms per decoded frame.
+        let mk = |pts_ms| DecodedAudioFrame { pts_ms, samples: vec![0.5; 960] };
+        let mut remaining = 600;
+
+        let mut first = mk(0);
+        assert!(!trim_leading_frames(&mut first, &mut remaining, 48_000, 2));
+        assert_eq!(remaining, 120);
+
+        let mut second = mk(10);
+        assert!(trim_leading_frames(&mut second, &mut remaining, 48_000, 2));
+        assert_eq!(remaining, 0);
+        assert_eq!(second.samples.len(), (480 - 120) * 2);
+        assert_eq!(second.pts_ms, 12);
+
+        let mut third = mk(20);
+        assert!(trim_leading_frames(&mut third, &mut remaining, 48_000, 2));
+        assert_eq!(third.samples.len(), 960);
+        assert_eq!(third.pts_ms, 20);
+    }
+
+    #[test]
+    fn stereo_peak_db_takes_the_front_pair() {
+        assert_eq!(stereo_peak_db(&[-3.0]), [-3.0, -3.0]);
+        let five_one = [-1.0, -2.0, -9.0, -20.0, -6.0, -7.0];
+        assert_eq!(stereo_peak_db(&five_one), [-1.0, -2.0]);
+    }
+
     // ---------------- find_segment_index ----------------
 
     #[test]
@@ -5597,6 +7934,71 @@ mod tests {
         assert_eq!(find_segment_index(&segs, Duration::from_millis(24_001)), 4);
     }
 
+    #[test]
+    fn trick_keyframe_index_snaps_back_to_a_sap_segment() {
+        let flags = [true, false, false, true, false];
+        let segs: Vec<Segment> = flags
+            .iter()
+            .enumerate()
+            .map(|(i, &sap)| seg_ms(i as u64 * 2000, (i as u64 + 1) * 2000).with_sap(sap))
+            .collect();
+        let at = Duration::from_millis;
+        assert_eq!(trick_keyframe_index(&segs, at(5_000)), Some(0));
+        assert_eq!(trick_keyframe_index(&segs, at(9_500)), Some(3));
+        // No SAP flags at all: every segment opens on one.
+        let plain: Vec<Segment> = (0..5).map(|i| seg_ms(i * 2000, (i + 1) * 2000)).collect();
+        assert_eq!(trick_keyframe_index(&plain, at(5_000)), Some(2));
+        assert_eq!(trick_keyframe_index(&[], at(5_000)), None);
+    }
+
+    #[test]
+    fn trick_representation_skips_ones_without_segments() {
+        let rep = |id, height, bandwidth, segments: u64| VideoRepresenation {
+            id,
+            base_url: String::new(),
+            file_url: String::new(),
+            segment_init: seg_ms(0, 0),
+            segment_range: seg_ms(0, 0),
+            segments: (0..segments)
+                .map(|i| seg_ms(i * 2000, (i + 1) * 2000))
+                .collect(),
+            bandwidth,
+            codecs: "hvc1".to_string(),
+            mime_type: "video/mp4".to_string(),
+            width: height * 16 / 9,
+            height,
+            sar: "1:1".to_string(),
+            hdr10: false,
+            dolby_vision: false,
+        };
+        let trick = [
+            rep(1, 360, 300_000, 0),
+            rep(2, 270, 200_000, 3),
+            rep(3, 720, 900_000, 3),
+        ];
+        let pick = |height| pick_trick_representation(&trick, height).map(|r| r.id);
+        assert_eq!(pick(1080), Some(3));
+        assert_eq!(pick(360), Some(2));
+        assert_eq!(pick(180), Some(2));
+        assert!(pick_trick_representation(&trick[..1], 1080).is_none());
+    }
+
+    #[test]
+    fn trick_clock_scales_wall_time_and_holds_at_zero_speed() {
+        let t0 = Instant::now();
+        let mut clock = TrickClock::new(10_000_000, t0);
+        clock.set_speed(8, t0);
+        let t1 = t0 + Duration::from_millis(500);
+        assert_eq!(clock.at(t1), 14_000_000);
+        // Paused: the picture holds.
+        clock.set_speed(0, t1);
+        assert_eq!(clock.at(t1 + Duration::from_secs(3)), 14_000_000);
+        // Rewinding from there.
+        let t2 = t1 + Duration::from_secs(3);
+        clock.set_speed(-16, t2);
+        assert_eq!(clock.at(t2 + Duration::from_millis(250)), 10_000_000);
+    }
+
     // ---------------- update_bandwidth_ewma ----------------
 
     #[test]
@@ -24,7 +24,36 @@ pub trait VideoSink: Send + Sync + 'static {
         Ok(())
     }
 
-    /// Apply subtitle styling (text/outline colour, size multiplier).
+    /// Install the subtitle font fallback chain: the first font is the
+    /// regular one, the rest are tried per character for glyphs it lacks.
+    /// No-op on sinks that don't render subtitles themselves. Returns Err
+    /// (installing nothing) if any entry is invalid.
+    fn set_subtitle_fonts(&self, _fonts: Vec<Vec<u8>>) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Install one face (bold, italic, …) of the subtitle font family;
+    /// `Regular` replaces the base font like `set_subtitle_font`. No-op on
+    /// sinks that don't render subtitles themselves.
+    fn set_subtitle_font_face(
+        &self,
+        _face: crate::SubtitleFontFace,
+        _bytes: Vec<u8>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Install the font a subtitle style's generic `font_family` draws
+    /// with. No-op on sinks that don't render subtitles themselves.
+    fn set_subtitle_family_font(
+        &self,
+        _family: crate::SubtitleFontFamily,
+        _bytes: Vec<u8>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Apply subtitle styling (colours, boxes, edges, family, size).
     /// Default no-op so sinks that don't render subtitles keep compiling.
     /// Sinks that own the overlay store it and invalidate any cached
     /// rasterization. Mirrors `set_hdr_tonemap_params`.
@@ -39,6 +68,26 @@ pub trait VideoSink: Send + Sync + 'static {
     /// track switch or when the consumer disables subtitles.
     fn clear_subtitles(&self) {}
 
+    /// Secondary subtitle track (`Player::set_secondary_subtitle_track`):
+    /// the same cue queue, drawn alongside the primary cues in its own
+    /// style. Default no-op.
+    fn queue_secondary_subtitle_cues(&self, _cues: Vec<VttCue>) {}
+
+    /// Wipe the secondary track's cues; the primary ones stay.
+    fn clear_secondary_subtitles(&self) {}
+
+    /// Style and placement of the secondary track. Default no-op.
+    fn set_secondary_subtitle_style(
+        &self,
+        _style: crate::SubtitleStyle,
+        _position: crate::SecondarySubtitlePosition,
+    ) {
+    }
+
+    /// Subtitle timing offset in ms (+ = cues later), applied by
+    /// `set_subtitle_pts` to both tracks. Default no-op.
+    fn set_subtitle_offset_ms(&self, _offset_ms: i64) {}
+
     /// Feed the current media-timeline PTS (ms, 0-based since start of
     /// content) into the subtitle overlay so its active-cue picker
     /// matches the cues' own timestamps. Called by the video sync loop
@@ -115,6 +164,12 @@ pub trait AudioPassthrough: Send + Sync {
 pub trait AudioSink: Send + Sync + 'static {
     fn put_samples<'a>(&'a self, samples: &'a [f32]) -> impl Future<Output = ()> + Send + 'a;
     fn sample_rate(&self) -> u32;
+    /// Interleaved channels per frame `put_samples` expects, in the
+    /// device's channel order (see `audio_layout::device_layout`). Default
+    /// stereo for sinks that don't open a multichannel device.
+    fn channels(&self) -> u16 {
+        2
+    }
     /// Media milliseconds the output device has actually PLAYED (samples
     /// consumed by the device callback; pause/underrun silence does not
     /// count). The device crystal is the clock the listener hears, so the
@@ -133,6 +188,10 @@ pub trait AudioSink: Send + Sync + 'static {
     fn output_latency_ms(&self) -> u64 {
         0
     }
+    /// Delay the player's PCM processing (the host `AudioProcessor` chain)
+    /// adds in front of this sink, in µs. Sinks fold it into
+    /// `output_latency_ms` while playing PCM. Default: ignored.
+    fn set_processing_latency_us(&self, _us: u64) {}
     fn flush(&self);
     fn stop(&self) -> impl Future<Output = ()> + Send + '_;
     /// Absolute volume in 0.0..=1.0. Implementations must clamp.
@@ -166,10 +225,26 @@ pub trait AudioSink: Send + Sync + 'static {
         false
     }
 
-    /// Latest L/R peak in dB (range roughly -120..=0). `None` before the
-    /// first audio frame has been pushed. Surfaced via `PlayerEvent::Stats`
-    /// so the TUI can draw a tiny VU meter.
-    fn last_peak_db(&self) -> Option<[f32; 2]> {
+    /// Bumped whenever the output reopened at a different sample rate or
+    /// channel count (an audio device switch). Samples decoded for the old
+    /// format are useless to it, so the player rebuilds its pipeline when
+    /// this moves. Default: the format never changes.
+    fn format_generation(&self) -> u64 {
+        0
+    }
+
+    /// Resolves when `format_generation` moves, so the player rebuilds as
+    /// soon as the output reopens rather than on its next poll. Default:
+    /// never.
+    fn format_changed(&self) -> impl Future<Output = ()> + Send + '_ {
+        std::future::pending()
+    }
+
+    /// Latest per-channel peak in dB (range roughly -120..=0), one entry
+    /// per output channel in device order. `None` before the first audio
+    /// frame has been pushed. Surfaced via `PlayerEvent::Stats` so the TUI
+    /// can draw a tiny VU meter.
+    fn last_peak_db(&self) -> Option<Vec<f32>> {
         None
     }
 }
@@ -1,11 +1,12 @@
 use std::sync::{
     atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 
 #[cfg(any(target_os = "windows", target_os = "linux"))]
 use ffmpeg_next::frame::Audio;
 use tokio::sync::{
+    broadcast,
     mpsc::{self, Sender},
     Notify,
 };
@@ -21,10 +22,25 @@ pub mod audio_passthrough;
 #[cfg(target_os = "android")]
 mod audio_track_pcm;
 
+/// An audio output the system offers, as listed by
+/// `Player::audio_output_devices`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AudioOutputDevice {
+    /// Host-specific stable id; pass it to `Player::set_audio_output_device`.
+    pub id: String,
+    /// Human-readable name for a device picker.
+    pub name: String,
+    /// True for the device the system currently routes audio to by default.
+    pub is_default: bool,
+}
+
 pub struct AudioRenderer {
     command_sender: Sender<AudioRendererCommand>,
     sample_sender: Sender<f32>,
-    sample_rate: u32,
+    /// Device, format and clock base of the open output. Written by the
+    /// output backend on every (re)open; the rate / channel count are the
+    /// device's native ones on cpal, 48 kHz stereo on the Android AudioTrack.
+    output: Arc<OutputState>,
     flush_flag: Arc<AtomicBool>,
     paused_flag: Arc<AtomicBool>,
     /// Volume gain in 0.0..=1.0, stored as `f32::to_bits` so the cpal
@@ -32,16 +48,16 @@ pub struct AudioRenderer {
     /// it without blocking. Writes go through `set_volume`; the integration
     /// layer is expected to restore any persisted user value on startup.
     volume: Arc<AtomicU32>,
-    /// Last-frame peak dB per channel (f32 bits stored in AtomicU32).
-    /// `peak_seen` flips on first push so we can return `None` until data
-    /// is actually flowing — avoids reporting `-inf` at startup.
-    peak_l_db: Arc<AtomicU32>,
-    peak_r_db: Arc<AtomicU32>,
+    /// Last-frame peak dB per output channel (f32 bits stored in
+    /// AtomicU32, `MAX_CHANNELS` slots so a device switch can't outgrow it). `peak_seen` flips on first push so
+    /// we can return `None` until data is actually flowing — avoids
+    /// reporting `-inf` at startup.
+    peak_db: Box<[AtomicU32]>,
     peak_seen: Arc<AtomicBool>,
     /// Samples (interleaved f32s, post-resample at the OUTPUT rate) the
     /// cpal callback has actually consumed from the channel. The DEVICE
     /// clock — silence emitted during pause/underrun does not advance it,
-    /// so `consumed / 2 / out_rate` is exactly how much media time the
+    /// so `consumed / channels / out_rate` is exactly how much media time the
     /// listener has heard. Drives the A/V drift measurement in the video
     /// sync loop (the device crystal and CLOCK_MONOTONIC disagree by
     /// 10-100 ppm — minutes-long playback drifts audibly without it).
@@ -53,6 +69,9 @@ pub struct AudioRenderer {
     /// the speaker — otherwise video leads by this much at every
     /// (re)start, the "audio delayed after a seek/switch" symptom.
     output_latency_ms: Arc<AtomicU64>,
+    /// Delay of the player's host `AudioProcessor` chain in µs, set by the
+    /// audio sync loop and added to `output_latency_ms` on the PCM path.
+    processing_latency_us: AtomicU64,
     /// When set (audio passthrough engaged), the cpal PCM path is dormant and
     /// this bitstream output is the real output + clock source: `played_ms` /
     /// `output_latency_ms` / `flush` / `set_paused` delegate to it, so
@@ -78,6 +97,126 @@ pub struct AudioRenderer {
 
 enum AudioRendererCommand {
     Stop,
+    /// The requested device changed (`OutputState::requested`): re-resolve
+    /// and reopen if the output isn't already on it.
+    Reroute,
+}
+
+/// What a reopen reports to subscribers: the device now playing (`None` for
+/// the null sink / outputs without a device identity) and its format.
+pub(crate) type OutputChange = (Option<AudioOutputDevice>, u32, u16);
+
+/// The open output as seen from outside the backend thread.
+///
+/// The device-consumed counter (`samples_consumed`) runs per stream in that
+/// stream's format, so each reopen folds what was played so far into
+/// `base_ms` and re-marks the counter: `played_ms` then continues seamlessly
+/// across a device switch instead of jumping with the new rate/channels,
+/// and `MediaClock` keeps its anchor. Reopens happen between streams (no
+/// callback running), so the fold is exact.
+pub(crate) struct OutputState {
+    clock: Mutex<OutputClock>,
+    device: Mutex<Option<AudioOutputDevice>>,
+    /// Device id the host asked for; `None` follows the system default.
+    requested: Mutex<Option<String>>,
+    /// Bumped whenever a reopen changed the rate or channel count.
+    format_generation: AtomicU64,
+    /// Signalled after each `format_generation` bump.
+    format_changed: Notify,
+    changes: broadcast::Sender<OutputChange>,
+}
+
+#[derive(Default)]
+struct OutputClock {
+    sample_rate: u32,
+    channels: u16,
+    /// Media ms played on earlier streams.
+    base_ms: u64,
+    /// `samples_consumed` when the current stream opened.
+    mark: u64,
+}
+
+impl OutputState {
+    fn new() -> Self {
+        OutputState {
+            clock: Mutex::new(OutputClock::default()),
+            device: Mutex::new(None),
+            requested: Mutex::new(None),
+            format_generation: AtomicU64::new(0),
+            format_changed: Notify::new(),
+            changes: broadcast::channel(8).0,
+        }
+    }
+
+    /// Record a (re)opened output. A changed format flushes the queued
+    /// samples (decoded for the old one) and bumps `format_generation`; a
+    /// changed device or format is broadcast to `changes`.
+    pub(crate) fn opened(
+        &self,
+        device: Option<AudioOutputDevice>,
+        sample_rate: u32,
+        channels: u16,
+        samples_consumed: &AtomicU64,
+        flush_flag: &AtomicBool,
+    ) {
+        let format_changed = {
+            let mut clock = self.clock.lock().unwrap();
+            let consumed = samples_consumed.load(Ordering::Relaxed);
+            if clock.sample_rate > 0 {
+                let frames = consumed.saturating_sub(clock.mark) / clock.channels.max(1) as u64;
+                clock.base_ms += frames * 1000 / clock.sample_rate as u64;
+            }
+            clock.mark = consumed;
+            let first = clock.sample_rate == 0;
+            let changed = (clock.sample_rate, clock.channels) != (sample_rate, channels);
+            clock.sample_rate = sample_rate;
+            clock.channels = channels;
+            changed && !first
+        };
+        if format_changed {
+            flush_flag.store(true, Ordering::Relaxed);
+            self.format_generation.fetch_add(1, Ordering::Relaxed);
+            self.format_changed.notify_one();
+        }
+        let device_changed = {
+            let mut current = self.device.lock().unwrap();
+            let changed = current.as_ref().map(|d| &d.id) != device.as_ref().map(|d| &d.id);
+            *current = device.clone();
+            changed
+        };
+        if format_changed || device_changed {
+            log::info!(
+                "[audio] output now {} ({} Hz / {} ch)",
+                device.as_ref().map_or("<none>", |d| d.name.as_str()),
+                sample_rate,
+                channels
+            );
+            let _ = self.changes.send((device, sample_rate, channels));
+        }
+    }
+
+    #[cfg(not(target_os = "android"))]
+    pub(crate) fn requested(&self) -> Option<String> {
+        self.requested.lock().unwrap().clone()
+    }
+
+    fn format(&self) -> (u32, u16) {
+        let clock = self.clock.lock().unwrap();
+        (clock.sample_rate, clock.channels)
+    }
+
+    #[cfg(not(target_os = "android"))]
+    fn played_ms(&self, samples_consumed: &AtomicU64) -> Option<u64> {
+        let clock = self.clock.lock().unwrap();
+        if clock.sample_rate == 0 {
+            return None;
+        }
+        // Interleaved: `channels` f32s per frame at the OUTPUT rate (the
+        // resampler preserves duration, so output time = media time).
+        let frames = samples_consumed.load(Ordering::Relaxed).saturating_sub(clock.mark)
+            / clock.channels.max(1) as u64;
+        Some(clock.base_ms + frames * 1000 / clock.sample_rate as u64)
+    }
 }
 
 impl AudioRenderer {
@@ -108,11 +247,12 @@ impl AudioRenderer {
         // sync loop instead anchors its clock to when audio actually starts
         // playing — both universal, no per-device constants.
         let output_latency_ms = Arc::new(AtomicU64::new(0));
+        let output = Arc::new(OutputState::new());
         let (command_sender, command_receiver) = mpsc::channel(4);
 
         #[cfg(not(target_os = "android"))]
-        let (sample_sender, sample_rate) = {
-            let t = audio_cpal::start_thread(
+        let sample_sender = {
+            audio_cpal::start_thread(
                 command_receiver,
                 stop,
                 flush_flag.clone(),
@@ -120,8 +260,8 @@ impl AudioRenderer {
                 volume.clone(),
                 samples_consumed.clone(),
                 output_latency_ms.clone(),
-            );
-            (t.0, t.1)
+                output.clone(),
+            )
         };
         // Android outputs PCM through an AudioTrack (cpal/AAudio is stolen on some
         // TV HALs); the cpal stop/command machinery is unused on this path. The
@@ -139,19 +279,24 @@ impl AudioRenderer {
                 volume.clone(),
             )
         };
+        // The AudioTrack is opened CHANNEL_OUT_STEREO.
+        #[cfg(target_os = "android")]
+        output.opened(None, sample_rate, 2, &samples_consumed, &flush_flag);
 
         AudioRenderer {
             command_sender,
             sample_sender,
-            sample_rate,
+            output,
             flush_flag,
             paused_flag,
             volume,
-            peak_l_db: Arc::new(AtomicU32::new(0)),
-            peak_r_db: Arc::new(AtomicU32::new(0)),
+            peak_db: (0..crate::audio_layout::MAX_CHANNELS)
+                .map(|_| AtomicU32::new(0))
+                .collect(),
             peak_seen: Arc::new(AtomicBool::new(false)),
             samples_consumed,
             output_latency_ms,
+            processing_latency_us: AtomicU64::new(0),
             passthrough: std::sync::Mutex::new(None),
             #[cfg(target_os = "android")]
             pcm_sink,
@@ -160,26 +305,27 @@ impl AudioRenderer {
         }
     }
 
-    /// Compute interleaved-stereo peak in dB and stash it for the next
-    /// `last_peak_db()` poll. Cheap — one abs+max per sample.
+    /// Compute the per-channel peak of interleaved samples in dB and stash
+    /// it for the next `last_peak_db()` poll. Cheap — one abs+max per
+    /// sample.
     fn update_peaks(&self, samples: &[f32]) {
         if samples.is_empty() {
             return;
         }
-        let mut max_l = 0.0_f32;
-        let mut max_r = 0.0_f32;
-        for chunk in samples.chunks_exact(2) {
-            max_l = max_l.max(chunk[0].abs());
-            max_r = max_r.max(chunk[1].abs());
+        let ch = (self.channels() as usize).clamp(1, self.peak_db.len());
+        let mut max = [0.0_f32; crate::audio_layout::MAX_CHANNELS];
+        for frame in samples.chunks_exact(ch) {
+            for (m, s) in max.iter_mut().zip(frame) {
+                *m = m.max(s.abs());
+            }
         }
         // 20 * log10(|s|). Floor at -120 dB to avoid log(0) = -inf.
         let to_db = |v: f32| -> f32 {
             if v <= 1.0e-6 { -120.0 } else { 20.0 * v.log10() }
         };
-        self.peak_l_db
-            .store(to_db(max_l).to_bits(), Ordering::Relaxed);
-        self.peak_r_db
-            .store(to_db(max_r).to_bits(), Ordering::Relaxed);
+        for (slot, m) in self.peak_db.iter().zip(max) {
+            slot.store(to_db(m).to_bits(), Ordering::Relaxed);
+        }
         self.peak_seen.store(true, Ordering::Relaxed);
     }
 
@@ -202,19 +348,60 @@ impl AudioRenderer {
         }
     }
 
-    /// Returns the last computed L/R peak in dB, or `None` before the
-    /// first audio frame has been pushed.
-    pub fn last_peak_db(&self) -> Option<[f32; 2]> {
+    /// Returns the last computed per-channel peak in dB (device channel
+    /// order), or `None` before the first audio frame has been pushed.
+    pub fn last_peak_db(&self) -> Option<Vec<f32>> {
         if !self.peak_seen.load(Ordering::Relaxed) {
             return None;
         }
-        let l = f32::from_bits(self.peak_l_db.load(Ordering::Relaxed));
-        let r = f32::from_bits(self.peak_r_db.load(Ordering::Relaxed));
-        Some([l, r])
+        Some(
+            self.peak_db[..(self.channels() as usize).min(self.peak_db.len())]
+                .iter()
+                .map(|p| f32::from_bits(p.load(Ordering::Relaxed)))
+                .collect(),
+        )
     }
 
     pub fn sample_rate(&self) -> u32 {
-        self.sample_rate
+        self.output.format().0
+    }
+
+    /// Interleaved channels per frame the output expects.
+    pub fn channels(&self) -> u16 {
+        self.output.format().1
+    }
+
+    /// Output devices the system offers. Empty on Android and iOS, where
+    /// the OS owns routing (AudioTrack / AVAudioSession follow the route).
+    pub fn output_devices(&self) -> Vec<AudioOutputDevice> {
+        #[cfg(not(target_os = "android"))]
+        {
+            audio_cpal::output_devices()
+        }
+        #[cfg(target_os = "android")]
+        {
+            Vec::new()
+        }
+    }
+
+    /// The device the output is currently open on, `None` before it opened
+    /// or on outputs without a device identity (null sink, Android).
+    pub fn output_device(&self) -> Option<AudioOutputDevice> {
+        self.output.device.lock().unwrap().clone()
+    }
+
+    /// Route to device `id`, or follow the system default with `None`. An id
+    /// that isn't present falls back to the default until it shows up.
+    pub fn set_output_device(&self, id: Option<String>) {
+        *self.output.requested.lock().unwrap() = id;
+        // Capacity-bounded command channel: a full queue already holds a
+        // pending Reroute/Stop, and the backend re-reads `requested` anyway.
+        let _ = self.command_sender.try_send(AudioRendererCommand::Reroute);
+    }
+
+    /// Every device / format change of the output, as it happens.
+    pub(crate) fn subscribe_output_changes(&self) -> broadcast::Receiver<OutputChange> {
+        self.output.changes.subscribe()
     }
 
     /// Output-path latency in ms (device buffer + DAC) reported by the cpal
@@ -275,6 +462,10 @@ impl super::AudioSink for AudioRenderer {
         AudioRenderer::sample_rate(self)
     }
 
+    fn channels(&self) -> u16 {
+        AudioRenderer::channels(self)
+    }
+
     fn played_ms(&self) -> Option<u64> {
         // Passthrough: the bitstream output's playback head is the clock source.
         if let Some(pt) = self.passthrough.lock().unwrap().as_ref() {
@@ -288,32 +479,39 @@ impl super::AudioSink for AudioRenderer {
         }
         #[cfg(not(target_os = "android"))]
         {
-            if self.sample_rate == 0 {
-                return None;
-            }
-            // Interleaved stereo: 2 f32s per frame at the OUTPUT rate (the
-            // resampler preserves duration, so output time = media time).
-            let frames = self.samples_consumed.load(Ordering::Relaxed) / 2;
-            Some(frames * 1000 / self.sample_rate as u64)
+            self.output.played_ms(&self.samples_consumed)
         }
     }
 
+    fn format_generation(&self) -> u64 {
+        self.output.format_generation.load(Ordering::Relaxed)
+    }
+
+    fn format_changed(&self) -> impl std::future::Future<Output = ()> + Send + '_ {
+        self.output.format_changed.notified()
+    }
+
     fn output_latency_ms(&self) -> u64 {
         if let Some(pt) = self.passthrough.lock().unwrap().as_ref() {
             return pt.output_latency_ms();
         }
+        let processing_ms = self.processing_latency_us.load(Ordering::Relaxed) / 1_000;
         // Android AudioTrack: played_ms is the presented head, so no extra
         // output-path latency to fold in.
         #[cfg(target_os = "android")]
         {
-            0
+            processing_ms
         }
         #[cfg(not(target_os = "android"))]
         {
-            AudioRenderer::output_latency_ms(self)
+            AudioRenderer::output_latency_ms(self) + processing_ms
         }
     }
 
+    fn set_processing_latency_us(&self, us: u64) {
+        self.processing_latency_us.store(us, Ordering::Relaxed);
+    }
+
     fn flush(&self) {
         if let Some(pt) = self.passthrough.lock().unwrap().as_ref() {
             pt.flush();
@@ -369,7 +567,7 @@ impl super::AudioSink for AudioRenderer {
         AudioRenderer::set_paused(self, paused)
     }
 
-    fn last_peak_db(&self) -> Option<[f32; 2]> {
+    fn last_peak_db(&self) -> Option<Vec<f32>> {
         AudioRenderer::last_peak_db(self)
     }
 }
@@ -1,17 +1,23 @@
 //! Desktop (Windows/Linux/macOS) + iOS PCM output via cpal.
 //!
-//! The cpal output stream pulls resampled packed-stereo f32 from the channel in
-//! its realtime callback; `samples_consumed` (frames the device actually took)
+//! The cpal output stream pulls resampled packed f32 — already remixed by the
+//! decoder to the device's native channel count (at most 7.1) and order —
+//! from the channel in its realtime callback; `samples_consumed` (frames the device actually took)
 //! is the clock the video sync loop paces against. Android does NOT use this —
 //! its AAudio stream gets stolen on some TV HALs, so it outputs via an
 //! `AudioTrack` instead (see `audio_track_pcm`).
 //!
-//! Extracted verbatim from the old inline `AudioRenderer::{start_thread,
+//! The stream is supervised rather than fire-and-forget: the output thread
+//! reopens it on another device when the host picks one, when the system
+//! default changes, or when the stream dies (device unplugged), feeding the
+//! new stream from the same sample queue (see `run_output`).
+//!
+//! Extracted from the old inline `AudioRenderer::{start_thread,
 //! start_audio}` so each output backend lives in its own file (mirrors `video`).
 
 use std::sync::{
     atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 use std::time::Duration;
 
@@ -21,13 +27,13 @@ use cpal::{
 };
 #[cfg(not(target_os = "ios"))]
 use cpal::SupportedStreamConfig;
-use pollster::FutureExt;
 use tokio::sync::{
     mpsc::{self, Receiver, Sender},
     Notify,
 };
 
-use super::AudioRendererCommand;
+use super::{AudioOutputDevice, AudioRendererCommand, OutputState};
+use crate::audio_layout::{fill_device_frames, stream_channels};
 
 /// iOS only: the OS-authoritative output sample rate, read from
 /// `AVAudioSession.sharedInstance().sampleRate`.
@@ -87,33 +93,165 @@ fn ios_output_channels() -> Option<u16> {
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-async fn start_audio(
-    mut sample_receiver: Receiver<f32>,
-    device: Device,
-    // Rate + channel count to open the device at, resolved in `start_thread`
-    // (rate from the iOS AVAudioSession; channels prefer stereo so the OS
-    // owns routing/downmix). Kept in lock-step with the resampler.
-    out_rate: u32,
-    out_channels: u16,
+/// Everything the realtime callback shares with the renderer. Outlives any
+/// one stream: a reopened device's stream drains the same sample queue, so
+/// audio already decoded carries on on the new output without a pipeline
+/// restart.
+#[derive(Clone)]
+struct StreamShared {
+    // Locked (try_lock) only by the callback of the single live stream;
+    // the old stream is dropped before a new one is built.
+    sample_receiver: Arc<Mutex<Receiver<f32>>>,
     volume: Arc<AtomicU32>,
-    stop: Arc<Notify>,
     flush_flag: Arc<AtomicBool>,
     paused_flag: Arc<AtomicBool>,
     samples_consumed: Arc<AtomicU64>,
     output_latency_ms: Arc<AtomicU64>,
-) {
-    // The resampler always emits packed STEREO. With a stereo stream
-    // (the normal case) the callback copies 1:1; on a mono-only output it
-    // downmixes (L+R)/2 — otherwise stereo fed 1:1 into a mono stream plays
-    // at half speed (an octave low, "deep voice").
-    let out_ch = out_channels as usize;
+}
+
+/// Stable host-specific id of `device` (survives re-enumeration).
+#[cfg(not(target_os = "ios"))]
+fn device_id(device: &Device) -> Option<String> {
+    device.id().ok().map(|id| id.to_string())
+}
+
+#[cfg(not(target_os = "ios"))]
+fn describe(device: &Device, default_id: Option<&str>) -> AudioOutputDevice {
+    let name = device.description().ok().map(|d| d.name().to_string());
+    let id = device_id(device)
+        .or_else(|| name.clone())
+        .unwrap_or_default();
+    AudioOutputDevice {
+        name: name.unwrap_or_else(|| id.clone()),
+        is_default: default_id == Some(id.as_str()),
+        id,
+    }
+}
+
+/// What `AudioRenderer::output_devices` lists. iOS reports none: the OS
+/// routes (AVAudioSession) and cpal's enumeration hangs there (see
+/// `start_thread`).
+pub(super) fn output_devices() -> Vec<AudioOutputDevice> {
+    #[cfg(target_os = "ios")]
+    {
+        Vec::new()
+    }
+    #[cfg(not(target_os = "ios"))]
+    {
+        let host = cpal::default_host();
+        let default_id = host.default_output_device().as_ref().and_then(device_id);
+        match host.output_devices() {
+            Ok(devices) => devices
+                .map(|d| describe(&d, default_id.as_deref()))
+                .collect(),
+            Err(e) => {
+                log::warn!("[audio] output device enumeration failed: {}", e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// The device the output should be on: `requested` while it is present,
+/// the system default otherwise.
+fn pick_device(requested: Option<&str>) -> Option<Device> {
+    let host = cpal::default_host();
+    #[cfg(not(target_os = "ios"))]
+    if let Some(id) = requested {
+        let found = host
+            .output_devices()
+            .ok()
+            .and_then(|mut ds| ds.find(|d| device_id(d).as_deref() == Some(id)));
+        if found.is_some() {
+            return found;
+        }
+        log::warn!("[audio] output device {} not present, using the system default", id);
+    }
+    #[cfg(target_os = "ios")]
+    let _ = requested;
+    host.default_output_device()
+}
+
+/// Resolve the output device and the rate + channel count to open it at.
+///
+/// On iOS, read BOTH straight from the live AVAudioSession and do NOT
+/// call cpal's `default_output_config()` / `supported_output_configs()`:
+/// querying the audio device's formats right after the previous stream
+/// stopped HANGS the setup on a second playback ("Loading…" forever — the
+/// player rotates to landscape but never starts). AVAudioSession is the
+/// OS truth anyway: the RemoteIO rate (44100 on the SE, which disagrees
+/// with cpal's canonical 48000 → "deep voice" if mismatched) and the
+/// live route's channel count (mono on the SE speaker, stereo on
+/// headphones / AirPods, more on a multichannel AirPlay / HDMI route).
+/// The decoder remixes to that count, so mono folds (L+R)/2 upstream.
+fn resolve_output(requested: Option<&str>) -> Option<(Device, u32, u16)> {
+    let device = pick_device(requested)?;
+    #[cfg(target_os = "ios")]
+    {
+        Some((
+            device,
+            ios_output_sample_rate().unwrap_or(48_000),
+            ios_output_channels().unwrap_or(2),
+        ))
+    }
+    #[cfg(not(target_os = "ios"))]
+    {
+        let config: SupportedStreamConfig = device.default_output_config().ok()?;
+        let rc = (config.sample_rate(), config.channels().max(1));
+        Some((device, rc.0, rc.1))
+    }
+}
+
+/// `AudioOutputDevice` for an opened device. iOS has no enumeration, so
+/// the route has no identity beyond the OS default.
+fn identify(device: &Device) -> Option<AudioOutputDevice> {
+    #[cfg(target_os = "ios")]
+    {
+        let _ = device;
+        None
+    }
+    #[cfg(not(target_os = "ios"))]
+    {
+        let default_id = cpal::default_host()
+            .default_output_device()
+            .as_ref()
+            .and_then(device_id);
+        Some(describe(device, default_id.as_deref()))
+    }
+}
+
+fn build_stream(
+    device: &Device,
+    // Rate + channel count to open the device at, from `resolve_output`
+    // (rate from the iOS AVAudioSession; channels are the device's native
+    // count). Kept in lock-step with the decoder's resampler / remixer.
+    out_rate: u32,
+    out_channels: u16,
+    shared: &StreamShared,
+    // Set by the error callback; the supervisor reopens on it (the device
+    // was unplugged / invalidated and this stream is dead).
+    failed: Arc<AtomicBool>,
+) -> Option<cpal::Stream> {
+    // Decoders emit `stream_channels(out_channels)` interleaved channels in
+    // device order (see `audio_layout`), so the callback copies them 1:1 —
+    // the mono-speaker fold and any surround downmix already happened
+    // upstream — and pads the channels of a device wider than 7.1 with
+    // silence.
+    let source_channels = stream_channels(out_channels) as usize;
     let stream_config = StreamConfig {
         channels: out_channels,
         sample_rate: out_rate,
         buffer_size: cpal::BufferSize::Default,
     };
 
+    let StreamShared {
+        sample_receiver,
+        volume,
+        flush_flag,
+        paused_flag,
+        samples_consumed,
+        output_latency_ms,
+    } = shared.clone();
     let callback = move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
         // Output latency = (when this buffer's first sample is AUDIBLE)
         // − (now). The device buffer + DAC delay everything the callback
@@ -133,6 +271,10 @@ async fn start_audio(
         if ms > 0 && ms <= 1000 {
             output_latency_ms.store(ms, Ordering::Relaxed);
         }
+        let Ok(mut sample_receiver) = sample_receiver.try_lock() else {
+            data.fill(Sample::EQUILIBRIUM);
+            return;
+        };
         if flush_flag.swap(false, Ordering::Relaxed) {
             while sample_receiver.try_recv().is_ok() {}
         }
@@ -145,39 +287,10 @@ async fn start_audio(
             return;
         }
         let vol = f32::from_bits(volume.load(Ordering::Relaxed));
-        let mut consumed = 0u64;
-        if out_ch >= 2 {
-            for sample in data.iter_mut() {
-                let asample = match sample_receiver.try_recv() {
-                    Ok(s) => {
-                        consumed += 1;
-                        s
-                    }
-                    Err(_) => Sample::EQUILIBRIUM,
-                };
-                *sample = asample * vol;
-            }
-        } else {
-            // Mono output device: downmix the packed-stereo source (L+R)/2
-            // per output sample so playback runs at the correct speed/pitch.
-            for sample in data.iter_mut() {
-                let l = match sample_receiver.try_recv() {
-                    Ok(s) => {
-                        consumed += 1;
-                        s
-                    }
-                    Err(_) => Sample::EQUILIBRIUM,
-                };
-                let r = match sample_receiver.try_recv() {
-                    Ok(s) => {
-                        consumed += 1;
-                        s
-                    }
-                    Err(_) => l,
-                };
-                *sample = (l + r) * 0.5 * vol;
-            }
-        }
+        let consumed =
+            fill_device_frames(data, out_channels as usize, source_channels, vol, || {
+                sample_receiver.try_recv().ok()
+            });
         if consumed > 0 {
             samples_consumed.fetch_add(consumed, Ordering::Relaxed);
         }
@@ -186,26 +299,153 @@ async fn start_audio(
     // RealtimeDenied (AAudio couldn't grant the low-latency/realtime
     // path) is informational, not fatal — the stream falls back to the
     // normal mode and keeps playing. Log it quieter than real errors.
-    let err_fn = |err: cpal::Error| {
+    let err_fn = move |err: cpal::Error| {
         if err.to_string().contains("Realtime") {
             log::info!("audio: realtime/low-latency not granted, using normal mode");
         } else {
             log::error!("audio stream error: {}", err);
+            failed.store(true, Ordering::Relaxed);
         }
     };
 
-    let stream = device
-        .build_output_stream(stream_config, callback, err_fn, Some(Duration::from_secs(20)))
-        .expect("Failed to build audio stream");
+    let stream = match device.build_output_stream(
+        stream_config,
+        callback,
+        err_fn,
+        Some(Duration::from_secs(20)),
+    ) {
+        Ok(stream) => stream,
+        Err(e) => {
+            log::error!("[audio] failed to build output stream: {}", e);
+            return None;
+        }
+    };
+    if let Err(e) = stream.play() {
+        log::error!("[audio] failed to start output stream: {}", e);
+        return None;
+    }
+    Some(stream)
+}
+
+/// True while the open output is where it should be: the requested device
+/// if present, else the current system default. Whether a requested device
+/// that isn't open has appeared takes a full enumeration, done only when
+/// `probe` is set. iOS follows the route on its own (RemoteIO), so only "is
+/// anything open" matters there.
+fn on_wanted_device(open: Option<&Device>, state: &OutputState, probe: bool) -> bool {
+    let Some(open) = open else {
+        return false;
+    };
+    #[cfg(target_os = "ios")]
+    {
+        let _ = (open, state, probe);
+        true
+    }
+    #[cfg(not(target_os = "ios"))]
+    {
+        let open_id = device_id(open);
+        if let Some(id) = state.requested() {
+            if open_id.as_deref() == Some(id.as_str()) {
+                return true;
+            }
+            let present = probe
+                && cpal::default_host()
+                    .output_devices()
+                    .is_ok_and(|mut ds| ds.any(|d| device_id(&d).as_deref() == Some(id.as_str())));
+            if present {
+                return false;
+            }
+        }
+        let default_id = cpal::default_host()
+            .default_output_device()
+            .as_ref()
+            .and_then(device_id);
+        open_id == default_id
+    }
+}
 
-    stream.play().expect("Failed to start audio stream");
+/// Output thread body: open the resolved device, then supervise it until
+/// `Stop` (or the renderer is dropped) — reopening when the host picks
+/// another device, when the followed system default changes (headphones
+/// plugged, HDMI selected) and when the stream dies (device unplugged).
+/// With no device available it keeps retrying once a second; consumption
+/// (and with it the audio clock) holds meanwhile. A requested device that is
+/// missing is looked for with a backoff, up to every 30 s, until the next
+/// `Reroute`.
+fn run_output(
+    first: (Device, u32, u16),
+    shared: StreamShared,
+    mut command_receiver: Receiver<AudioRendererCommand>,
+    state: Arc<OutputState>,
+) {
+    const TICK: Duration = Duration::from_millis(100);
+    // Default-device polling cadence, in ticks.
+    const CHECK_EVERY: u32 = 10;
+    // Ceiling of the missing-device presence probe backoff, in ticks.
+    const PROBE_MAX: u32 = 300;
+    let mut next = Some(first);
+    loop {
+        let failed = Arc::new(AtomicBool::new(false));
+        let mut open: Option<(cpal::Stream, Device)> = None;
+        if let Some((device, out_rate, out_channels)) = next.take() {
+            log::info!("[audio] opening output {} Hz / {} ch", out_rate, out_channels);
+            if let Some(stream) =
+                build_stream(&device, out_rate, out_channels, &shared, failed.clone())
+            {
+                state.opened(
+                    identify(&device),
+                    out_rate,
+                    stream_channels(out_channels),
+                    &shared.samples_consumed,
+                    &shared.flush_flag,
+                );
+                open = Some((stream, device));
+            }
+        }
 
-    stop.notified().block_on();
+        let mut ticks = 0u32;
+        let mut probe_every = CHECK_EVERY;
+        let mut next_probe = CHECK_EVERY;
+        loop {
+            match command_receiver.try_recv() {
+                Ok(AudioRendererCommand::Stop)
+                | Err(mpsc::error::TryRecvError::Disconnected) => return,
+                Ok(AudioRendererCommand::Reroute) => {
+                    probe_every = CHECK_EVERY;
+                    next_probe = ticks + CHECK_EVERY;
+                    if !on_wanted_device(open.as_ref().map(|(_, d)| d), &state, true) {
+                        break;
+                    }
+                }
+                Err(mpsc::error::TryRecvError::Empty) => {}
+            }
+            if failed.load(Ordering::Relaxed) {
+                break;
+            }
+            ticks += 1;
+            if ticks.is_multiple_of(CHECK_EVERY) {
+                let probe = ticks >= next_probe;
+                if probe {
+                    probe_every = (probe_every * 2).min(PROBE_MAX);
+                    next_probe = ticks + probe_every;
+                }
+                if !on_wanted_device(open.as_ref().map(|(_, d)| d), &state, probe) {
+                    break;
+                }
+            }
+            std::thread::sleep(TICK);
+        }
+        // Tear the old stream down BEFORE building the next one: the shared
+        // receiver is single-consumer and `OutputState::opened` folds the
+        // consumed counter while no callback runs.
+        drop(open);
+        next = resolve_output(state.requested().as_deref());
+    }
 }
 
 /// Device-less audio path: a plain thread drains the sample channel at
-/// real-time pace (48 kHz packed stereo — the resampler's output format for
-/// the rate we report back), honoring pause/flush and counting consumption
+/// real-time pace (48 kHz packed stereo — the rate and channel count we
+/// report back, so the decoder produces exactly that), honoring pause/flush and counting consumption
 /// exactly like the cpal callback would. Everything downstream behaves as if
 /// a perfect silent device were attached; video plays, nothing is audible.
 fn start_null_sink(
@@ -215,7 +455,9 @@ fn start_null_sink(
     flush_flag: Arc<AtomicBool>,
     paused_flag: Arc<AtomicBool>,
     samples_consumed: Arc<AtomicU64>,
+    state: Arc<OutputState>,
 ) {
+    state.opened(None, 48_000, 2, &samples_consumed, &flush_flag);
     std::thread::Builder::new()
         .name("bz-audio-null".into())
         .spawn(move || {
@@ -257,13 +499,14 @@ fn start_null_sink(
         .expect("spawn null audio thread");
 
     tokio::spawn(async move {
-        #[allow(clippy::never_loop)]
+        // No device to route to: `Reroute` has nothing to do here.
         while let Some(command) = command_receiver.recv().await {
             match command {
                 AudioRendererCommand::Stop => {
                     stop.notify_waiters();
                     break;
                 }
+                AudioRendererCommand::Reroute => {}
             }
         }
     });
@@ -271,48 +514,21 @@ fn start_null_sink(
 
 #[allow(clippy::too_many_arguments)]
 pub(super) fn start_thread(
-    mut command_receiver: Receiver<AudioRendererCommand>,
+    command_receiver: Receiver<AudioRendererCommand>,
     stop: Arc<Notify>,
     flush_flag: Arc<AtomicBool>,
     paused_flag: Arc<AtomicBool>,
     volume: Arc<AtomicU32>,
     samples_consumed: Arc<AtomicU64>,
     output_latency_ms: Arc<AtomicU64>,
-) -> (Sender<f32>, u32) {
+    state: Arc<OutputState>,
+) -> Sender<f32> {
     let (sample_sender, sample_receiver) = mpsc::channel::<f32>(192_000);
 
     // No usable audio output (headless CI runner, server, unplugged dock):
     // don't panic the whole player — run a NULL sink that consumes samples at
     // real-time pace so the pipeline flows and video plays silently.
-    let maybe_device = cpal::default_host().default_output_device();
-
-    // Resolve the output rate + channel count.
-    //
-    // On iOS, read BOTH straight from the live AVAudioSession and do NOT
-    // call cpal's `default_output_config()` / `supported_output_configs()`:
-    // querying the audio device's formats right after the previous stream
-    // stopped HANGS the setup on a second playback ("Loading…" forever — the
-    // player rotates to landscape but never starts). AVAudioSession is the
-    // OS truth anyway: the RemoteIO rate (44100 on the SE, which disagrees
-    // with cpal's canonical 48000 → "deep voice" if mismatched) and the
-    // live route's channel count (mono on the SE speaker, stereo on
-    // headphones / AirPods). The resampler emits packed STEREO, so the
-    // callback downmixes (L+R)/2 when the output is mono.
-    #[cfg(target_os = "ios")]
-    let resolved: Option<(Device, u32, u16)> = maybe_device.map(|d| {
-        (
-            d,
-            ios_output_sample_rate().unwrap_or(48_000),
-            ios_output_channels().unwrap_or(2),
-        )
-    });
-    #[cfg(not(target_os = "ios"))]
-    let resolved: Option<(Device, u32, u16)> = maybe_device.and_then(|d| {
-        let config: SupportedStreamConfig = d.default_output_config().ok()?;
-        let rc = (config.sample_rate(), config.channels().max(1));
-        Some((d, rc.0, rc.1))
-    });
-    let Some((device, out_rate, out_channels)) = resolved else {
+    let Some((device, out_rate, out_channels)) = resolve_output(None) else {
         log::warn!(
             "[audio] no usable output device — NULL audio sink (silent playback, real-time drain)"
         );
@@ -323,54 +539,49 @@ pub(super) fn start_thread(
             flush_flag,
             paused_flag,
             samples_consumed,
+            state,
         );
-        return (sample_sender, 48_000);
+        return sample_sender;
     };
-
-    log::info!("[audio] opening output {} Hz / {} ch", out_rate, out_channels);
-
-    let stop_cpal = stop.clone();
-    // Run the cpal output stream on a DEDICATED OS thread, NOT a tokio
-    // worker. `start_audio` parks (pollster `block_on`) on `stop` for the
-    // whole playback; doing that on a tokio worker permanently consumes it.
-    // On a low-core device (2-core iPhone SE) the pool is then exhausted
-    // after the first play: the command loop that fires the stop
-    // notification can't get a worker, so the previous stream never tears
-    // down — and the SECOND play's spawned tasks (audio build, open_url)
-    // never get scheduled → stuck on "Loading…" forever. A plain thread
-    // keeps the blocking wait off the async runtime entirely. (Multi-core
-    // simulators have spare workers, which is why it only bit on device.)
-    let audio_fut = start_audio(
-        sample_receiver,
-        device,
+    // Publish the format now: the renderer's getters must be valid as soon
+    // as `new` returns. The thread's first open records the same values.
+    state.opened(
+        identify(&device),
         out_rate,
-        out_channels,
+        stream_channels(out_channels),
+        &samples_consumed,
+        &flush_flag,
+    );
+    drop(stop);
+
+    let shared = StreamShared {
+        sample_receiver: Arc::new(Mutex::new(sample_receiver)),
         volume,
-        stop_cpal,
         flush_flag,
         paused_flag,
         samples_consumed,
         output_latency_ms,
-    );
+    };
+    // Run the cpal output stream on a DEDICATED OS thread, NOT a tokio
+    // worker. The supervisor blocks (sleep-polls its commands) for the
+    // whole playback; doing that on a tokio worker permanently consumes it.
+    // On a low-core device (2-core iPhone SE) the pool is then exhausted
+    // after the first play: the previous stream never tears down — and the
+    // SECOND play's spawned tasks (audio build, open_url) never get
+    // scheduled → stuck on "Loading…" forever. A plain thread keeps the
+    // blocking wait off the async runtime entirely. (Multi-core simulators
+    // have spare workers, which is why it only bit on device.)
     std::thread::Builder::new()
         .name("bz-audio-out".into())
-        .spawn(move || audio_fut.block_on())
+        .spawn(move || {
+            run_output(
+                (device, out_rate, out_channels),
+                shared,
+                command_receiver,
+                state,
+            )
+        })
         .expect("spawn audio output thread");
 
-    tokio::spawn(async move {
-        // `Stop` is the only command today, so this drains exactly once —
-        // but the loop is kept so adding non-terminal commands later is a
-        // pure addition (new match arm) rather than a control-flow rewrite.
-        #[allow(clippy::never_loop)]
-        while let Some(command) = command_receiver.recv().await {
-            match command {
-                AudioRendererCommand::Stop => {
-                    stop.notify_waiters();
-                    break;
-                }
-            }
-        }
-    });
-
-    (sample_sender, out_rate)
+    sample_sender
 }
@@ -0,0 +1,64 @@
+// Subtitle overlay: one instanced quad per glyph or flat box.
+//
+// Every instance samples the overlay's R8 glyph atlas as coverage and
+// tints it with its own straight-alpha colour; flat boxes (line, window
+// and run backgrounds, underlines) point at the atlas's solid white
+// block. Glyph edges (shadow, outline, raised / depressed) are further
+// instances of the same glyph in the edge colour, offset and drawn first.
+// Output is premultiplied, blended with PREMULTIPLIED_ALPHA_BLENDING over
+// the already-drawn video, in instance order — the same "over" sequence
+// the CPU painter (`Frame::paint`, GLES path) runs.
+
+struct Overlay {
+    // xy = NDC of the frame's top-left pixel, zw = NDC per pixel (2/w, 2/h).
+    transform: vec4<f32>,
+};
+
+@group(0) @binding(0) var t_atlas: texture_2d<f32>;
+@group(0) @binding(1) var s_atlas: sampler;
+@group(0) @binding(2) var<uniform> overlay: Overlay;
+
+struct InstanceIn {
+    // x, y, w, h in frame pixels (y down).
+    @location(0) rect: vec4<f32>,
+    // x, y, w, h in atlas texels.
+    @location(1) uv: vec4<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct VertexOut {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vi: u32, inst: InstanceIn) -> VertexOut {
+    // Unit quad corners, two triangles.
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(0.0, 1.0),
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(1.0, 1.0),
+    );
+    let c = corners[vi];
+    let px = inst.rect.xy + c * inst.rect.zw;
+    let atlas = vec2<f32>(textureDimensions(t_atlas));
+    var out: VertexOut;
+    out.position = vec4<f32>(
+        overlay.transform.x + px.x * overlay.transform.z,
+        overlay.transform.y - px.y * overlay.transform.w,
+        0.0, 1.0,
+    );
+    out.tex_coords = (inst.uv.xy + c * inst.uv.zw) / atlas;
+    out.color = inst.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    let a = in.color.a * textureSample(t_atlas, s_atlas, in.tex_coords).r;
+    return vec4<f32>(in.color.rgb * a, a);
+}
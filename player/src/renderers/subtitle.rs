@@ -1,105 +1,200 @@
-//! WebVTT subtitle overlay rendered via wgpu.
+//! Subtitle overlay (WebVTT, TTML) rendered via wgpu.
 //!
-//! Phase 1 scope: plain white text with a dark drop shadow, bottom-center,
-//! fixed proportional size. No styling, no positioning, no language
-//! mixing — just makes cues readable.
+//! Text is white with a dark drop shadow at a fixed proportional size
+//! unless `SubtitleStyle` says otherwise (colours, edge style, line and
+//! window boxes, font family), bottom-center by default. Cues that carry
+//! styled runs (colour, background, bold, italic, underline, ruby) or a
+//! layout box — TTML regions, WebVTT cue settings and regions — are drawn
+//! as authored, with line-snapped WebVTT cues stepping around each other.
+//! Bold / italic runs use the installed font faces (`set_font_face`),
+//! synthesised when the family lacks one. A secondary track
+//! (`queue_secondary_cues`) draws in its own style at the top, or stacked
+//! above the primary cues.
 //!
 //! Pipeline:
 //!   1. `queue_cues` — text_play task pushes parsed cues here as they
 //!      arrive; we keep them sorted by start time.
 //!   2. `set_pts_ms` — av_sync's video loop sets the current playback
-//!      PTS just before drawing. The overlay picks the cue that's
-//!      active right now and rasterizes it (cached: same text + same
-//!      target width = same texture).
+//!      PTS just before drawing. The overlay collects every cue that's
+//!      active right now and lays them out into one frame of glyph and
+//!      box instances (cached: same cue set + same target width = same
+//!      instances).
 //!   3. `draw_into` — called from VideoRenderer's render path after the
-//!      main video draw. Issues one textured-quad draw against the
+//!      main video draw. Issues one instanced draw against the
 //!      already-bound surface target.
 //!
-//! Font rasterization is on the CPU via `fontdue`. No glyph atlas: a
-//! whole-cue bitmap is generated once per active cue and reused until
-//! the cue expires. Cues are short (~2-5s) so this is cheaper than
-//! atlas bookkeeping for our use case.
-
+//! Text is shaped (`rustybuzz`, with bidi reordering and UAX #14 line
+//! breaking) and rasterized (`fontdue`) on the CPU, one glyph at a time:
+//! each character is drawn in the first font of the chain — run face,
+//! then the `set_fonts` fallbacks, then the embedded DejaVu — that has
+//! it. Rasterized glyphs are shelf-packed into a single R8 atlas texture
+//! and stay there, so a cue change only uploads glyphs not seen before.
+
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use wgpu::util::DeviceExt;
 
 use crate::parsers::vtt::VttCue;
-use crate::SubtitleStyle;
+use crate::{SecondarySubtitlePosition, SubtitleFontFace, SubtitleFontFamily, SubtitleStyle};
 
-// CPU cue rasterization (fontdue) lives in its own file (mirrors `video`).
+// CPU cue shaping + rasterization lives in its own file (mirrors `video`).
 mod rasterizer;
 
-const SHADER_WGSL: &str = r#"
-struct VertexOut {
-    @builtin(position) position: vec4<f32>,
-    @location(0) tex_coords: vec2<f32>,
-};
-
-struct Quad {
-    /// xy = NDC center, zw = NDC half-extent.
-    transform: vec4<f32>,
-};
-
-@group(0) @binding(0) var t_tex: texture_2d<f32>;
-@group(0) @binding(1) var s_tex: sampler;
-@group(0) @binding(2) var<uniform> quad: Quad;
-
-@vertex
-fn vs_main(@builtin(vertex_index) vi: u32) -> VertexOut {
-    // Unit quad in [-1, 1] × [-1, 1], two triangles.
-    var pos = array<vec2<f32>, 6>(
-        vec2<f32>(-1.0, -1.0),
-        vec2<f32>( 1.0, -1.0),
-        vec2<f32>(-1.0,  1.0),
-        vec2<f32>(-1.0,  1.0),
-        vec2<f32>( 1.0, -1.0),
-        vec2<f32>( 1.0,  1.0),
-    );
-    var uv = array<vec2<f32>, 6>(
-        vec2<f32>(0.0, 1.0),
-        vec2<f32>(1.0, 1.0),
-        vec2<f32>(0.0, 0.0),
-        vec2<f32>(0.0, 0.0),
-        vec2<f32>(1.0, 1.0),
-        vec2<f32>(1.0, 0.0),
-    );
-    let p = pos[vi];
-    var out: VertexOut;
-    out.position = vec4<f32>(
-        quad.transform.x + p.x * quad.transform.z,
-        quad.transform.y + p.y * quad.transform.w,
-        0.0, 1.0,
-    );
-    out.tex_coords = uv[vi];
-    return out;
-}
+use rasterizer::{DrawOp, GlyphBitmap, GlyphCache, GlyphKey, LoadedFont};
 
-@fragment
-fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
-    return textureSample(t_tex, s_tex, in.tex_coords);
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct OverlayUniform {
+    /// xy = NDC of the frame's top-left pixel, zw = NDC per pixel.
+    transform: [f32; 4],
 }
-"#;
 
+/// One quad of the instanced draw: a glyph or a flat box.
 #[repr(C)]
 #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-struct QuadUniform {
-    /// xy = NDC center, zw = NDC half-extent
-    transform: [f32; 4],
+struct GlyphInstance {
+    /// x, y, w, h in frame pixels.
+    rect: [f32; 4],
+    /// x, y, w, h in atlas texels.
+    uv: [f32; 4],
+    /// Straight-alpha RGBA, 0..1.
+    color: [f32; 4],
+}
+
+impl GlyphInstance {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4, 2 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GlyphInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Side of the square glyph atlas, texels. Holds a few thousand glyphs
+/// at typical cue sizes.
+const ATLAS_SIZE: u32 = 1024;
+/// The atlas's solid white block, sampled by flat boxes.
+const SOLID: [u32; 4] = [0, 0, 2, 2];
+
+/// Rasterized glyph coverage packed into one R8 texture shared by every
+/// cue. Shelf-packed with a 1px gutter; a glyph is uploaded the first
+/// time it is drawn and stays until the atlas fills or the fonts change,
+/// when it starts over.
+struct GlyphAtlas {
+    texture: wgpu::Texture,
+    /// Where each uploaded glyph sits: x, y, w, h in texels.
+    slots: HashMap<GlyphKey, [u32; 4]>,
+    /// Shelf packer: the current shelf's top and height, and the next
+    /// free column on it.
+    shelf_y: u32,
+    shelf_h: u32,
+    cursor_x: u32,
+}
+
+impl GlyphAtlas {
+    fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("subtitle_glyph_atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE,
+                height: ATLAS_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let mut atlas = GlyphAtlas {
+            texture,
+            slots: HashMap::new(),
+            shelf_y: 0,
+            shelf_h: 0,
+            cursor_x: 0,
+        };
+        atlas.upload(queue, SOLID, &[255; 4]);
+        atlas.reset();
+        atlas
+    }
+
+    /// Forget every glyph. The solid block at the origin stays; packing
+    /// resumes beside it.
+    fn reset(&mut self) {
+        self.slots.clear();
+        self.shelf_y = 0;
+        self.shelf_h = SOLID[3] + 1;
+        self.cursor_x = SOLID[2] + 1;
+    }
+
+    /// The glyph's texel rect, uploading it first if it isn't in the
+    /// atlas yet. `None` when it no longer fits.
+    fn ensure(
+        &mut self,
+        queue: &wgpu::Queue,
+        key: GlyphKey,
+        glyph: &GlyphBitmap,
+    ) -> Option<[u32; 4]> {
+        if let Some(rect) = self.slots.get(&key) {
+            return Some(*rect);
+        }
+        let (w, h) = (glyph.width, glyph.height);
+        if self.cursor_x + w > ATLAS_SIZE {
+            self.shelf_y += self.shelf_h;
+            self.shelf_h = 0;
+            self.cursor_x = 0;
+        }
+        if self.cursor_x + w > ATLAS_SIZE || self.shelf_y + h > ATLAS_SIZE {
+            return None;
+        }
+        let rect = [self.cursor_x, self.shelf_y, w, h];
+        self.upload(queue, rect, &glyph.coverage);
+        self.cursor_x += w + 1;
+        self.shelf_h = self.shelf_h.max(h + 1);
+        self.slots.insert(key, rect);
+        Some(rect)
+    }
+
+    fn upload(&self, queue: &wgpu::Queue, [x, y, w, h]: [u32; 4], coverage: &[u8]) {
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            coverage,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(w),
+                rows_per_image: Some(h),
+            },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
 }
 
 struct CachedCue {
-    /// Identity = (text, target_pixel_width). When either changes we
-    /// re-rasterize.
-    text: String,
+    /// Identity = (active cue set, target_pixel_width). When either
+    /// changes we lay the cues out again.
+    key: String,
     target_w: u32,
-    bitmap_w: u32,
-    bitmap_h: u32,
-    /// Held to keep the underlying GPU resource alive for as long as `view`
-    /// is referenced. Never read directly — the `view` does all the work.
-    #[allow(dead_code)]
-    texture: wgpu::Texture,
-    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+    /// Top-left as a fraction of the target; `None` = bottom-center.
+    anchor: Option<[f32; 2]>,
+    instances: wgpu::Buffer,
+    count: u32,
 }
 
 pub struct SubtitleOverlay {
@@ -107,8 +202,9 @@ pub struct SubtitleOverlay {
     queue: Arc<wgpu::Queue>,
     surface_format: wgpu::TextureFormat,
     pipeline: wgpu::RenderPipeline,
-    bind_group_layout: wgpu::BindGroupLayout,
-    sampler: wgpu::Sampler,
+    /// Atlas + sampler + uniform; the atlas texture never changes, only
+    /// its contents, so this is built once.
+    bind_group: wgpu::BindGroup,
     uniform_buffer: wgpu::Buffer,
 
     inner: Mutex<Inner>,
@@ -119,20 +215,38 @@ struct Inner {
     /// current PTS passes their end. Bounded to a few thousand to
     /// avoid pathological memory growth on hours-long streams.
     cues: Vec<VttCue>,
+    /// The secondary track's cues, same rules; drawn with
+    /// `secondary_style` at `secondary_position`.
+    secondary_cues: Vec<VttCue>,
+    secondary_style: SubtitleStyle,
+    secondary_position: SecondarySubtitlePosition,
     /// Current playback PTS in ms, updated by the render path before
     /// each draw call.
     current_pts_ms: i64,
-    /// fontdue::Font held behind RwLock-ish so set_font can swap it
+    /// The regular font (shaper + rasterizer) so set_font can swap it
     /// at any time. Initialised to the embedded DejaVu default so cues
     /// render without a host-supplied font; `None` only if that default
     /// somehow fails to parse, in which case render is a no-op.
-    font: Option<fontdue::Font>,
+    font: Option<LoadedFont>,
+    /// Bold / italic / bold-italic faces next to `font`, and the generic
+    /// family fonts styles pick; empty until the host installs them
+    /// (`set_font_face`, `set_family_font`), so styled runs start out
+    /// synthesised and every family draws in `font`.
+    faces: rasterizer::FontFaces,
+    /// Fonts tried in order for characters the run's face lacks: the
+    /// host's `set_fonts` fallbacks, then the embedded default.
+    fallbacks: Vec<LoadedFont>,
+    /// The embedded DejaVu, kept to end every fallback chain.
+    embedded: Option<LoadedFont>,
+    /// Glyphs rasterized with the current fonts, and the GPU copies of
+    /// them. Both are keyed by font slot, so any font change clears them.
+    glyphs: GlyphCache,
+    atlas: GlyphAtlas,
     /// Visual style (colours + size multiplier). Swapped by `set_style`;
-    /// changing it drops the cached rasterization so the next draw rebuilds.
+    /// changing it drops the cached layout so the next draw rebuilds.
     style: SubtitleStyle,
-    /// Cached rasterized cue. Invalidated when the active cue's text
-    /// changes or the target width drifts more than 5% from the
-    /// cached size.
+    /// Cached cue layout. Invalidated when the active cue set changes or
+    /// the target width drifts more than 5% from the cached size.
     cached: Option<CachedCue>,
     /// CPU-side variant of `cached` for the GLES hook path (Android),
     /// which uploads the bitmap into a GL texture itself. Same identity
@@ -143,8 +257,9 @@ struct Inner {
 }
 
 /// A rasterized cue as plain pixels, for sinks that own their texture
-/// upload (the Android GLES hook). The same `rasterize_cue` output the
-/// wgpu path uploads — a libass backend would feed this exact shape.
+/// upload (the Android GLES hook). The same layout the wgpu path draws
+/// from the atlas, painted into one bitmap — a libass backend would feed
+/// this exact shape.
 pub struct SubtitleBitmap {
     pub rgba: Vec<u8>,
     pub width: u32,
@@ -152,11 +267,142 @@ pub struct SubtitleBitmap {
     /// Monotonic content identity — changes whenever the visible bitmap
     /// changes. Lets callers cache uploads and detect updates cheaply.
     pub generation: u64,
+    /// Top-left corner as a fraction of the target surface, for
+    /// positioned cues (TTML regions, WebVTT settings). `None` = draw bottom-center above
+    /// the safe area.
+    pub anchor: Option<[f32; 2]>,
     /// Identity for cache validation (mirrors CachedCue).
-    text: String,
+    key: String,
     target_w: u32,
 }
 
+impl Inner {
+    /// Every primary and secondary cue active at the current PTS (start
+    /// order), plus an identity string for both sets — the cache key.
+    /// With secondary cues showing, the layout also bakes in the primary
+    /// block's safe-area position, so the key covers `bottom_inset_px`.
+    fn active_cues(&self, bottom_inset_px: u32) -> (Vec<VttCue>, Vec<VttCue>, String) {
+        let pts = self.current_pts_ms;
+        let active = |cues: &[VttCue]| -> Vec<VttCue> {
+            cues.iter().filter(|c| c.is_active(pts)).cloned().collect()
+        };
+        let key_of = |cues: &[VttCue]| {
+            cues.iter()
+                .map(|c| format!("{}:{}:{}", c.start_ms, c.end_ms, c.text))
+                .collect::<Vec<_>>()
+                .join("\u{1}")
+        };
+        let (primary, secondary) = (active(&self.cues), active(&self.secondary_cues));
+        let mut key = key_of(&primary);
+        if !secondary.is_empty() {
+            key = format!("{}\u{2}{}\u{2}{}", key, key_of(&secondary), bottom_inset_px);
+        }
+        (primary, secondary, key)
+    }
+
+    /// Lay out whatever is active (see `rasterizer::layout_dual`).
+    fn layout(
+        &self,
+        fonts: &rasterizer::FontFamily,
+        primary: &[VttCue],
+        secondary: &[VttCue],
+        target_w: u32,
+        target_h: u32,
+        bottom_inset_px: u32,
+    ) -> Option<rasterizer::Frame> {
+        rasterizer::layout_dual(
+            fonts,
+            primary,
+            &self.style,
+            secondary,
+            &self.secondary_style,
+            self.secondary_position,
+            target_w,
+            target_h,
+            bottom_inset_px,
+        )
+    }
+
+    /// After any font swap: glyph caches are keyed by font slot, so both
+    /// the CPU glyphs and the atlas start over, along with the layouts
+    /// built from them.
+    fn fonts_changed(&mut self) {
+        self.glyphs.clear();
+        self.atlas.reset();
+        self.cached = None;
+        self.cpu_cached = None;
+    }
+}
+
+/// Add cues to a track's list, keeping it sorted by start time. A cue with
+/// the start and text of one already listed replaces it (closed captions
+/// re-send a cue once its end is known). Capped at ~5000 cues: a 2h movie
+/// at 1 cue/2s is 3600, so plenty of headroom for normal content.
+fn push_cues(list: &mut Vec<VttCue>, cues: Vec<VttCue>) {
+    list.retain(|c| !cues.iter().any(|n| n.start_ms == c.start_ms && n.text == c.text));
+    list.extend(cues);
+    list.sort_by_key(|c| c.start_ms);
+    const MAX_CUES: usize = 5000;
+    if list.len() > MAX_CUES {
+        let excess = list.len() - MAX_CUES;
+        list.drain(0..excess);
+    }
+}
+
+/// True when a cached rasterization for (`key`, `cached_w`) can't be
+/// reused at `target_w`: the cue set changed or the width drifted >5%.
+fn needs_rebuild(cached: Option<(&str, u32)>, key: &str, target_w: u32) -> bool {
+    match cached {
+        Some((k, w)) => {
+            k != key || (w as i64 - target_w as i64).abs() > (target_w as i64 / 20).max(8)
+        }
+        None => true,
+    }
+}
+
+/// Turn a frame's draw ops into atlas instances, uploading glyphs the
+/// atlas doesn't hold yet. `None` when the atlas filled up on the way,
+/// unless `lossy`, which skips glyphs that don't fit instead.
+fn build_instances(
+    ops: &[DrawOp],
+    fonts: &rasterizer::FontFamily,
+    glyphs: &mut GlyphCache,
+    atlas: &mut GlyphAtlas,
+    queue: &wgpu::Queue,
+    lossy: bool,
+) -> Option<Vec<GlyphInstance>> {
+    let tint = |c: [u8; 4]| c.map(|v| v as f32 / 255.0);
+    let mut out = Vec::with_capacity(ops.len());
+    for op in ops {
+        match *op {
+            DrawOp::Rect { x, y, w, h, color } => out.push(GlyphInstance {
+                rect: [x as f32, y as f32, w as f32, h as f32],
+                uv: SOLID.map(|v| v as f32),
+                color: tint(color),
+            }),
+            DrawOp::Glyph { key, x, y, color } => {
+                let glyph = glyphs.get(fonts, key);
+                if glyph.width == 0 || glyph.height == 0 {
+                    continue;
+                }
+                let Some(uv) = atlas.ensure(queue, key, glyph) else {
+                    if lossy {
+                        continue;
+                    }
+                    return None;
+                };
+                let (gx, gy) = (x + glyph.left, y + glyph.top);
+                out.push(GlyphInstance {
+                    rect: [gx as f32, gy as f32, uv[2] as f32, uv[3] as f32],
+                    uv: uv.map(|v| v as f32),
+                    color: tint(color),
+                });
+            }
+        }
+    }
+    Some(out)
+}
+
 impl SubtitleOverlay {
     pub fn new(
         device: Arc<wgpu::Device>,
@@ -168,7 +414,7 @@ impl SubtitleOverlay {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Texture {
                         sample_type: wgpu::TextureSampleType::Float { filterable: true },
                         view_dimension: wgpu::TextureViewDimension::D2,
@@ -195,10 +441,7 @@ impl SubtitleOverlay {
             ],
         });
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("subtitle_shader"),
-            source: wgpu::ShaderSource::Wgsl(SHADER_WGSL.into()),
-        });
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shader_subtitle.wgsl"));
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("subtitle_pipeline_layout"),
             bind_group_layouts: &[Some(&bind_group_layout)],
@@ -210,7 +453,7 @@ impl SubtitleOverlay {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_main"),
-                buffers: &[],
+                buffers: &[GlyphInstance::desc()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
@@ -235,33 +478,66 @@ impl SubtitleOverlay {
             cache: None,
         });
 
+        // Nearest: glyphs are drawn texel-for-pixel at whole-pixel
+        // positions, and filtering would bleed neighbours into them.
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("subtitle_sampler"),
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
             ..Default::default()
         });
 
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("subtitle_uniform"),
-            contents: bytemuck::cast_slice(&[QuadUniform {
+            contents: bytemuck::cast_slice(&[OverlayUniform {
                 transform: [0.0, 0.0, 0.0, 0.0],
             }]),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
+        let atlas = GlyphAtlas::new(&device, &queue);
+        let atlas_view = atlas
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("subtitle_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let embedded = rasterizer::default_font();
         SubtitleOverlay {
             device,
             queue,
             surface_format,
             pipeline,
-            bind_group_layout,
-            sampler,
+            bind_group,
             uniform_buffer,
             inner: Mutex::new(Inner {
                 cues: Vec::new(),
+                secondary_cues: Vec::new(),
+                secondary_style: SubtitleStyle::DEFAULT,
+                secondary_position: SecondarySubtitlePosition::default(),
                 current_pts_ms: 0,
-                font: rasterizer::default_font(),
+                font: embedded.clone(),
+                faces: rasterizer::FontFaces::default(),
+                fallbacks: embedded.iter().cloned().collect(),
+                embedded,
+                glyphs: GlyphCache::default(),
+                atlas,
                 style: SubtitleStyle::DEFAULT,
                 cached: None,
                 cpu_cached: None,
@@ -271,20 +547,80 @@ impl SubtitleOverlay {
     }
 
     /// Install a TTF/OTF font for cue rasterization, replacing the
-    /// embedded DejaVu default. Invalidates any cached rasterization. On
-    /// invalid bytes the previous font is kept and an Err is returned.
+    /// regular font (the embedded DejaVu default unless `set_fonts` chose
+    /// another); the fallback chain is kept. Invalidates any cached
+    /// rasterization. On invalid bytes the previous font is kept and an
+    /// Err is returned.
     pub fn set_font(&self, bytes: Vec<u8>) -> Result<(), String> {
-        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default())
-            .map_err(|e| e.to_string())?;
+        let font = LoadedFont::from_bytes(bytes)?;
         let mut inner = self.inner.lock().unwrap();
         inner.font = Some(font);
-        inner.cached = None;
-        inner.cpu_cached = None;
+        inner.fonts_changed();
+        Ok(())
+    }
+
+    /// Install the font fallback chain. The first font becomes the regular
+    /// font; the rest are tried in order, per character, for anything the
+    /// run's own face can't draw, and the embedded DejaVu always ends the
+    /// chain. An empty list reverts to DejaVu alone. All or nothing: if
+    /// any entry is invalid nothing changes and the Err names it.
+    pub fn set_fonts(&self, fonts: Vec<Vec<u8>>) -> Result<(), String> {
+        let mut chain = fonts
+            .into_iter()
+            .enumerate()
+            .map(|(i, bytes)| LoadedFont::from_bytes(bytes).map_err(|e| format!("font {i}: {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut inner = self.inner.lock().unwrap();
+        let regular = if chain.is_empty() {
+            inner.embedded.clone()
+        } else {
+            Some(chain.remove(0))
+        };
+        chain.extend(inner.embedded.clone());
+        inner.font = regular;
+        inner.fallbacks = chain;
+        inner.fonts_changed();
+        Ok(())
+    }
+
+    /// Install one face of the font family. `Regular` is `set_font`; the
+    /// styled faces replace synthetic bold / oblique for matching runs.
+    /// On invalid bytes the previous face is kept and an Err is returned.
+    pub fn set_font_face(&self, face: SubtitleFontFace, bytes: Vec<u8>) -> Result<(), String> {
+        if face == SubtitleFontFace::Regular {
+            return self.set_font(bytes);
+        }
+        let font = LoadedFont::from_bytes(bytes)?;
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(slot) = inner.faces.slot(face) {
+            *slot = Some(font);
+        }
+        inner.fonts_changed();
         Ok(())
     }
 
-    /// Replace the visual style. Drops the cached cue bitmap so the next
-    /// draw re-rasterizes with the new colours/size. Cheap; safe to call
+    /// Install the font a style's `font_family` draws with. On invalid
+    /// bytes the previous font is kept and an Err is returned; the default
+    /// family is the regular font (`set_font`) and is refused.
+    pub fn set_family_font(
+        &self,
+        family: SubtitleFontFamily,
+        bytes: Vec<u8>,
+    ) -> Result<(), String> {
+        if family == SubtitleFontFamily::Default {
+            return Err("the default family is the regular font".into());
+        }
+        let font = LoadedFont::from_bytes(bytes)?;
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(slot) = inner.faces.family_slot(family) {
+            *slot = Some(font);
+        }
+        inner.fonts_changed();
+        Ok(())
+    }
+
+    /// Replace the visual style. Drops the cached cue layout so the next
+    /// draw rebuilds it with the new colours/size. Cheap; safe to call
     /// from any thread at any time.
     pub fn set_style(&self, style: SubtitleStyle) {
         let mut inner = self.inner.lock().unwrap();
@@ -293,21 +629,25 @@ impl SubtitleOverlay {
         inner.cpu_cached = None;
     }
 
+    /// Style and placement of the secondary track. Drops the cached
+    /// layout like `set_style`.
+    pub fn set_secondary_style(&self, style: SubtitleStyle, position: SecondarySubtitlePosition) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.secondary_style = style;
+        inner.secondary_position = position;
+        inner.cached = None;
+        inner.cpu_cached = None;
+    }
+
     /// Push new cues into the active list. text_play sends a batch per
     /// segment; raw single-file delivery sends the whole list once.
     pub fn queue_cues(&self, cues: Vec<VttCue>) {
-        let mut inner = self.inner.lock().unwrap();
-        for c in cues {
-            inner.cues.push(c);
-        }
-        inner.cues.sort_by_key(|c| c.start_ms);
-        // Cap memory: keep at most ~5000 cues. 2h movie at 1 cue/2s =
-        // 3600 cues, so plenty of headroom for normal content.
-        const MAX_CUES: usize = 5000;
-        if inner.cues.len() > MAX_CUES {
-            let excess = inner.cues.len() - MAX_CUES;
-            inner.cues.drain(0..excess);
-        }
+        push_cues(&mut self.inner.lock().unwrap().cues, cues);
+    }
+
+    /// `queue_cues` for the secondary track.
+    pub fn queue_secondary_cues(&self, cues: Vec<VttCue>) {
+        push_cues(&mut self.inner.lock().unwrap().secondary_cues, cues);
     }
 
     /// Drop everything — called when the consumer switches subtitle
@@ -319,43 +659,62 @@ impl SubtitleOverlay {
         inner.cpu_cached = None;
     }
 
-    /// GLES-hook variant of `draw_into`: resolve the cue active at the
-    /// current PTS and return its rasterized bitmap (cached across calls;
-    /// `generation` identifies the content). `None` = nothing to show.
+    /// `clear` for the secondary track; the primary cues stay.
+    pub fn clear_secondary(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.secondary_cues.clear();
+        inner.cached = None;
+        inner.cpu_cached = None;
+    }
+
+    /// GLES-hook variant of `draw_into`: resolve the cues active at the
+    /// current PTS and return their rasterized bitmap (cached across
+    /// calls; `generation` identifies the content). `None` = nothing to
+    /// show.
     pub fn active_bitmap(
         &self,
         target_w: u32,
         target_h: u32,
+        bottom_inset_px: u32,
     ) -> Option<std::sync::Arc<SubtitleBitmap>> {
-        let mut inner = self.inner.lock().unwrap();
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
         let pts = inner.current_pts_ms;
-        let active = inner.cues.iter().find(|c| c.is_active(pts)).cloned()?;
-        let font = inner.font.as_ref()?.clone();
-        let style = inner.style;
-
-        let rebuild = match &inner.cpu_cached {
-            Some(c) => {
-                c.text != active.text
-                    || (c.target_w as i64 - target_w as i64).abs()
-                        > (target_w as i64 / 20).max(8)
-            }
-            None => true,
-        };
-        if rebuild {
-            let (width, height, rgba) =
-                rasterizer::rasterize_cue(&font, &active.text, target_w, target_h, &style)?;
+        let (active, secondary, key) = inner.active_cues(bottom_inset_px);
+        if active.is_empty() && secondary.is_empty() {
+            return None;
+        }
+
+        let cached = inner.cpu_cached.as_ref().map(|c| (c.key.as_str(), c.target_w));
+        if needs_rebuild(cached, &key, target_w) {
+            let fonts = rasterizer::FontFamily {
+                regular: inner.font.as_ref()?,
+                faces: &inner.faces,
+                fallbacks: &inner.fallbacks,
+                family: None,
+            };
+            let frame = inner.layout(
+                &fonts,
+                &active,
+                &secondary,
+                target_w,
+                target_h,
+                bottom_inset_px,
+            )?;
+            let rgba = frame.paint(&fonts, &mut inner.glyphs);
             inner.generation += 1;
             let generation = inner.generation;
             log::debug!(
-                "[subs] rasterized cue gen={} {}x{} (pts={}ms)",
-                generation, width, height, pts
+                "[subs] rasterized {} cue(s) gen={} {}x{} (pts={}ms)",
+                active.len() + secondary.len(), generation, frame.width, frame.height, pts
             );
             inner.cpu_cached = Some(std::sync::Arc::new(SubtitleBitmap {
                 rgba,
-                width,
-                height,
+                width: frame.width,
+                height: frame.height,
                 generation,
-                text: active.text.clone(),
+                anchor: frame.anchor,
+                key,
                 target_w,
             }));
         }
@@ -379,10 +738,11 @@ impl SubtitleOverlay {
 
     /// Issue the draw into a caller-owned render pass. The caller has
     /// already attached the surface color target; we just emit one
-    /// triangle-list draw at the bottom-center of the viewport.
+    /// instanced draw — at the bottom-center of the viewport, or at the
+    /// cues' own position when they carry a layout.
     ///
     /// `target_w`/`target_h` are pixel dimensions of the surface so we
-    /// can size the rasterization to match.
+    /// can size the layout to match.
     pub fn draw_into(
         &self,
         render_pass: &mut wgpu::RenderPass<'_>,
@@ -390,83 +750,67 @@ impl SubtitleOverlay {
         target_h: u32,
         bottom_inset_px: u32,
     ) {
-        let mut inner = self.inner.lock().unwrap();
-        let pts = inner.current_pts_ms;
-        let active = inner
-            .cues
-            .iter()
-            .find(|c| c.is_active(pts))
-            .cloned();
-        let active = match active {
-            Some(c) => c,
-            None => return,
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let (active, secondary, key) = inner.active_cues(bottom_inset_px);
+        let Some(font) = inner.font.as_ref() else {
+            return;
         };
-        if inner.font.is_none() {
+        if active.is_empty() && secondary.is_empty() {
             return;
         }
 
-        // Rasterize or reuse cached bitmap. Cache hit when text and
-        // target width match within 5%.
-        let rebuild = match &inner.cached {
-            Some(c) => {
-                c.text != active.text
-                    || (c.target_w as i64 - target_w as i64).abs()
-                        > (target_w as i64 / 20).max(8)
-            }
-            None => true,
-        };
-        if rebuild {
-            let font = inner.font.as_ref().unwrap().clone();
-            let style = inner.style;
-            let rasterized = rasterizer::rasterize_cue(&font, &active.text, target_w, target_h, &style);
-            if let Some((bitmap_w, bitmap_h, rgba)) = rasterized {
-                let texture = self.device.create_texture(&wgpu::TextureDescriptor {
-                    label: Some("subtitle_cue_texture"),
-                    size: wgpu::Extent3d {
-                        width: bitmap_w,
-                        height: bitmap_h,
-                        depth_or_array_layers: 1,
-                    },
-                    mip_level_count: 1,
-                    sample_count: 1,
-                    dimension: wgpu::TextureDimension::D2,
-                    format: wgpu::TextureFormat::Rgba8Unorm,
-                    usage: wgpu::TextureUsages::TEXTURE_BINDING
-                        | wgpu::TextureUsages::COPY_DST,
-                    view_formats: &[],
-                });
-                self.queue.write_texture(
-                    wgpu::TexelCopyTextureInfo {
-                        texture: &texture,
-                        mip_level: 0,
-                        origin: wgpu::Origin3d::ZERO,
-                        aspect: wgpu::TextureAspect::All,
-                    },
-                    &rgba,
-                    wgpu::TexelCopyBufferLayout {
-                        offset: 0,
-                        bytes_per_row: Some(bitmap_w * 4),
-                        rows_per_image: Some(bitmap_h),
-                    },
-                    wgpu::Extent3d {
-                        width: bitmap_w,
-                        height: bitmap_h,
-                        depth_or_array_layers: 1,
-                    },
-                );
-                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-                inner.cached = Some(CachedCue {
-                    text: active.text.clone(),
-                    target_w,
-                    bitmap_w,
-                    bitmap_h,
-                    texture,
-                    view,
-                });
-            } else {
-                inner.cached = None;
+        // Lay out or reuse the cached instances. Cache hit when the cue
+        // set matches and the target width is within 5%.
+        let cached = inner.cached.as_ref().map(|c| (c.key.as_str(), c.target_w));
+        if needs_rebuild(cached, &key, target_w) {
+            inner.cached = None;
+            let fonts = rasterizer::FontFamily {
+                regular: font,
+                faces: &inner.faces,
+                fallbacks: &inner.fallbacks,
+                family: None,
+            };
+            let Some(frame) = inner.layout(
+                &fonts,
+                &active,
+                &secondary,
+                target_w,
+                target_h,
+                bottom_inset_px,
+            ) else {
+                return;
+            };
+            let (glyphs, atlas) = (&mut inner.glyphs, &mut inner.atlas);
+            let instances = build_instances(&frame.ops, &fonts, glyphs, atlas, &self.queue, false)
+                .or_else(|| {
+                    // Full: start the atlas over with just this frame's
+                    // glyphs. A frame that alone overflows it loses the
+                    // glyphs that don't fit.
+                    log::debug!("[subs] glyph atlas full, repacking");
+                    atlas.reset();
+                    build_instances(&frame.ops, &fonts, glyphs, atlas, &self.queue, true)
+                })
+                .unwrap_or_default();
+            if instances.is_empty() {
                 return;
             }
+            let buffer = self
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("subtitle_instances"),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsages::VERTEX,
+                });
+            inner.cached = Some(CachedCue {
+                key,
+                target_w,
+                width: frame.width,
+                height: frame.height,
+                anchor: frame.anchor,
+                instances: buffer,
+                count: instances.len() as u32,
+            });
         }
 
         let cached = match inner.cached.as_ref() {
@@ -474,28 +818,22 @@ impl SubtitleOverlay {
             None => return,
         };
 
-        // Position: bottom-center, anchored to the host-reported bottom safe
-        // area (real screen geometry via WindowInsets; on a TV the host maxes
-        // it with the title-safe margin so invisible HDMI overscan is still
-        // cleared). bottom_inset_px == 0 → 10% TV title-safe fallback. Kept in
-        // parity with the GLES path.
-        let bmp_w = cached.bitmap_w as f32;
-        let bmp_h = cached.bitmap_h as f32;
+        // Bottom-centre above the safe area, or where the layout anchored
+        // the cues.
         let tw = target_w as f32;
         let th = target_h as f32;
-        // Half-extent in NDC = (px/2) / (target/2) = px / target.
-        let half_w = bmp_w / tw;
-        let half_h = bmp_h / th;
-        let center_x = 0.0; // horizontal center
-        let safe_frac = if bottom_inset_px > 0 {
-            (bottom_inset_px as f32 / th).clamp(0.0, 0.45)
-        } else {
-            0.10
-        };
-        let center_y = -1.0 + half_h + 2.0 * safe_frac;
+        let (x, y) = rasterizer::origin(
+            cached.anchor,
+            cached.width,
+            cached.height,
+            target_w,
+            target_h,
+            bottom_inset_px,
+        );
+        let (x, y) = (x as f32, y as f32);
 
-        let uniform = QuadUniform {
-            transform: [center_x, center_y, half_w, half_h],
+        let uniform = OverlayUniform {
+            transform: [-1.0 + 2.0 * x / tw, 1.0 - 2.0 * y / th, 2.0 / tw, 2.0 / th],
         };
         self.queue.write_buffer(
             &self.uniform_buffer,
@@ -503,30 +841,11 @@ impl SubtitleOverlay {
             bytemuck::cast_slice(&[uniform]),
         );
 
-        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("subtitle_bind_group"),
-            layout: &self.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&cached.view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.sampler),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: self.uniform_buffer.as_entire_binding(),
-                },
-            ],
-        });
-
         render_pass.set_pipeline(&self.pipeline);
-        render_pass.set_bind_group(0, &bind_group, &[]);
-        render_pass.draw(0..6, 0..1);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, cached.instances.slice(..));
+        render_pass.draw(0..6, 0..cached.count);
         // suppress unused-warning on surface_format
         let _ = self.surface_format;
     }
 }
-
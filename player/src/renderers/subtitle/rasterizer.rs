@@ -1,12 +1,30 @@
-//! Cue rasterization — CPU side, via `fontdue`.
+//! Cue rasterization — CPU side: `rustybuzz` shapes, `fontdue` draws.
 //!
-//! Lays a WebVTT cue's text out into an RGBA8 coverage bitmap (wrap, measure,
-//! per-glyph blit with a drop shadow). Platform-agnostic: the wgpu overlay
-//! uploads the result to a texture, and the Android GLES hook uploads the same
-//! bytes itself. Extracted from `subtitle.rs` so the renderer (`overlay`) and
-//! the rasterizer live in separate files (mirrors the `video` module split).
+//! Lays the active cues out into one [`Frame`]: a list of flat boxes and
+//! glyph placements (styled runs shaped per face with per-glyph font
+//! fallback, Unicode bidi reordering, UAX #14 line breaking, align, ruby
+//! annotations, the style's glyph edges and line / window boxes;
+//! positioned cues placed at their boxes). Glyphs are rasterized once per (font, glyph, size)
+//! into a [`GlyphCache`]. All pure Rust — no HarfBuzz/ICU system libs.
+//! Platform-agnostic: the wgpu overlay packs cached glyphs into its atlas
+//! texture, and the Android GLES hook uploads [`Frame::paint`]'s RGBA8
+//! bitmap itself. Extracted from `subtitle.rs` so the renderer
+//! (`overlay`) and the rasterizer live in separate files (mirrors the
+//! `video` module split).
 
-use crate::SubtitleStyle;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use rustybuzz::{Direction, UnicodeBuffer};
+use unicode_bidi::{bidi_class, BidiClass, BidiInfo};
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+
+use crate::parsers::vtt::{CueLayout, DisplayAlign, SpanStyle, TextAlign, VttCue};
+use crate::{
+    SecondarySubtitlePosition, SubtitleEdgeStyle, SubtitleFontFace, SubtitleFontFamily,
+    SubtitleStyle,
+};
 
 /// Default font baked into the binary: DejaVu Sans (Bitstream Vera +
 /// public-domain changes — redistributable, see assets/fonts/LICENSE).
@@ -19,12 +37,49 @@ use crate::SubtitleStyle;
 /// override it.
 const DEFAULT_FONT: &[u8] = include_bytes!("../../../assets/fonts/DejaVuSans.ttf");
 
+/// A font loaded for both halves of the pipeline: `rustybuzz` shapes from
+/// the raw bytes, `fontdue` rasterizes the glyph ids the shaper picks.
+/// Cheap to clone (both halves are shared).
+#[derive(Clone)]
+pub(super) struct LoadedFont {
+    raster: Arc<fontdue::Font>,
+    data: Arc<[u8]>,
+}
+
+impl LoadedFont {
+    /// Parse TTF/OTF bytes. Err unless both the shaper and the rasterizer
+    /// accept them, so a font that loads is one every cue can use.
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Result<Self, String> {
+        let data: Arc<[u8]> = bytes.into();
+        if rustybuzz::Face::from_slice(&data, 0).is_none() {
+            return Err("unsupported font (no shaping tables)".into());
+        }
+        let raster = fontdue::Font::from_bytes(&*data, fontdue::FontSettings::default())
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            raster: Arc::new(raster),
+            data,
+        })
+    }
+
+    /// The shaping view of the font. Cheap (table lookups over the shared
+    /// bytes) and `Some` for every font `from_bytes` accepted.
+    fn shaper(&self) -> Option<rustybuzz::Face<'_>> {
+        rustybuzz::Face::from_slice(&self.data, 0)
+    }
+
+    /// Whether the font has a glyph for `ch` (anything but `.notdef`).
+    fn covers(&self, ch: char) -> bool {
+        self.raster.lookup_glyph_index(ch) != 0
+    }
+}
+
 /// Parse the embedded default font. Infallible in practice (the bytes are
 /// compiled in and known-good); returns `None` only if a future font swap
 /// breaks it, in which case the overlay degrades to drawing nothing rather
 /// than panicking the render thread.
-pub(super) fn default_font() -> Option<fontdue::Font> {
-    match fontdue::Font::from_bytes(DEFAULT_FONT, fontdue::FontSettings::default()) {
+pub(super) fn default_font() -> Option<LoadedFont> {
+    match LoadedFont::from_bytes(DEFAULT_FONT) {
         Ok(f) => Some(f),
         Err(e) => {
             log::error!("[subs] embedded default font failed to parse: {}", e);
@@ -33,160 +88,1134 @@ pub(super) fn default_font() -> Option<fontdue::Font> {
     }
 }
 
-/// Lay out a cue's text into an RGBA8 bitmap. Returns (width, height,
-/// pixels) or None when the text is empty or the layout doesn't fit.
+/// The styled faces installed next to the overlay's regular font. A run
+/// whose face is missing is synthesised from the closest one present (see
+/// [`FontFamily::face`]). Alongside them, the fonts installed for the
+/// generic families a style can pick (`SubtitleStyle::font_family`), in
+/// [`SubtitleFontFamily::GENERIC`] order.
+#[derive(Clone, Default)]
+pub(super) struct FontFaces {
+    pub bold: Option<LoadedFont>,
+    pub italic: Option<LoadedFont>,
+    pub bold_italic: Option<LoadedFont>,
+    pub generic: [Option<LoadedFont>; 7],
+}
+
+impl FontFaces {
+    /// Where `face` is kept; `None` for the regular face, which is the
+    /// overlay's base font rather than part of this set.
+    pub fn slot(&mut self, face: SubtitleFontFace) -> Option<&mut Option<LoadedFont>> {
+        match face {
+            SubtitleFontFace::Regular => None,
+            SubtitleFontFace::Bold => Some(&mut self.bold),
+            SubtitleFontFace::Italic => Some(&mut self.italic),
+            SubtitleFontFace::BoldItalic => Some(&mut self.bold_italic),
+        }
+    }
+
+    /// Where the font for `family` is kept; `None` for the default
+    /// family, which is the regular font.
+    pub fn family_slot(&mut self, family: SubtitleFontFamily) -> Option<&mut Option<LoadedFont>> {
+        family.generic_index().map(|i| &mut self.generic[i])
+    }
+}
+
+/// The font family cues are drawn with: the regular font, whichever
+/// styled faces the host installed, and the fallback chain tried in order
+/// for characters the run's own face has no glyph for. `family` is the
+/// slot of the generic family font a style picked (see
+/// [`with_family`](Self::with_family)), `None` for the regular font.
+#[derive(Clone, Copy)]
+pub(super) struct FontFamily<'a> {
+    pub regular: &'a LoadedFont,
+    pub faces: &'a FontFaces,
+    pub fallbacks: &'a [LoadedFont],
+    pub family: Option<u16>,
+}
+
+/// A font's index in its [`FontFamily`]: 0 regular, 1 bold, 2 italic,
+/// 3 bold-italic, the seven generic families from `GENERIC_SLOT`, then the
+/// fallbacks from `FALLBACK_SLOT` on.
+const GENERIC_SLOT: u16 = 4;
+const FALLBACK_SLOT: u16 = GENERIC_SLOT + 7;
+
+impl FontFamily<'_> {
+    /// The font in `slot`. A styled face the family lacks resolves to the
+    /// regular font.
+    fn font(&self, slot: u16) -> &LoadedFont {
+        let faces = self.faces;
+        let font = match slot {
+            1 => faces.bold.as_ref(),
+            2 => faces.italic.as_ref(),
+            3 => faces.bold_italic.as_ref(),
+            s if s >= FALLBACK_SLOT => self.fallbacks.get((s - FALLBACK_SLOT) as usize),
+            s if s >= GENERIC_SLOT => faces.generic[(s - GENERIC_SLOT) as usize].as_ref(),
+            _ => None,
+        };
+        font.unwrap_or(self.regular)
+    }
+
+    /// This family with text set in `family`'s font, when one is
+    /// installed; otherwise unchanged (the regular font).
+    pub fn with_family(&self, family: SubtitleFontFamily) -> Self {
+        let family = family
+            .generic_index()
+            .filter(|&i| self.faces.generic[i].is_some())
+            .map(|i| GENERIC_SLOT + i as u16);
+        Self { family, ..*self }
+    }
+
+    /// The face a run is drawn in (its slot), plus whether bold and italic
+    /// still have to be synthesised on top of it (double-strike, oblique
+    /// shear). A generic family font is a single face, so bold and italic
+    /// are always synthesised on it.
+    fn face(&self, style: &SpanStyle) -> (u16, bool, bool) {
+        if let Some(slot) = self.family {
+            return (slot, style.bold, style.italic);
+        }
+        let faces = self.faces;
+        match (style.bold, style.italic) {
+            (false, false) => (0, false, false),
+            (true, false) if faces.bold.is_some() => (1, false, false),
+            (true, false) => (0, true, false),
+            (false, true) if faces.italic.is_some() => (2, false, false),
+            (false, true) => (0, false, true),
+            (true, true) => {
+                if faces.bold_italic.is_some() {
+                    (3, false, false)
+                } else if faces.bold.is_some() {
+                    (1, false, true)
+                } else if faces.italic.is_some() {
+                    (2, true, false)
+                } else {
+                    (0, true, true)
+                }
+            }
+        }
+    }
+
+    /// The font `ch` is drawn in: the run's face when it has the glyph,
+    /// else the first fallback that does (fallbacks are single faces, so
+    /// bold and italic are synthesised there), else the face anyway —
+    /// nothing covers it and `.notdef` is the honest answer.
+    fn pick(&self, style: &SpanStyle, ch: char) -> (u16, bool, bool) {
+        let face = self.face(style);
+        if self.font(face.0).covers(ch) {
+            return face;
+        }
+        match self.fallbacks.iter().position(|f| f.covers(ch)) {
+            Some(i) => (FALLBACK_SLOT + i as u16, style.bold, style.italic),
+            None => face,
+        }
+    }
+}
+
+/// One character of cue text: its run style and, inside a ruby base, the
+/// index of its annotation in the block's ruby table.
+#[derive(Clone, Copy)]
+struct StyledChar {
+    ch: char,
+    style: SpanStyle,
+    ruby: Option<usize>,
+}
+
+/// One shaped glyph of a laid-out line. Lines hold them in visual
+/// (left-to-right drawing) order.
+#[derive(Clone, Copy)]
+struct Glyph {
+    /// Glyph id in the font at `font` (a [`FontFamily`] slot).
+    id: u16,
+    font: u16,
+    /// Bold / italic the font lacks, synthesised when drawing.
+    synth_bold: bool,
+    synth_italic: bool,
+    /// Index of the character whose cluster produced the glyph.
+    cluster: usize,
+    style: SpanStyle,
+    ruby: Option<usize>,
+    /// Pen advance and the shaper's offset from the pen, px (y up).
+    advance: f32,
+    dx: f32,
+    dy: f32,
+}
+
+/// A rasterized glyph's identity: which font of the family, which glyph,
+/// at what size, upright or sheared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(super) struct GlyphKey {
+    font: u16,
+    id: u16,
+    /// Pixel size in 1/64 px.
+    size: u32,
+    oblique: bool,
+}
+
+impl GlyphKey {
+    fn new(glyph: &Glyph, px_size: f32) -> Self {
+        Self {
+            font: glyph.font,
+            id: glyph.id,
+            size: (px_size * 64.0).round() as u32,
+            oblique: glyph.synth_italic,
+        }
+    }
+}
+
+/// A glyph's coverage bitmap, and where its top-left corner sits relative
+/// to the pen position on the baseline (px, y down).
+pub(super) struct GlyphBitmap {
+    pub width: u32,
+    pub height: u32,
+    pub left: i32,
+    pub top: i32,
+    pub coverage: Vec<u8>,
+}
+
+/// Rasterized glyphs by key, shared by every frame drawn with the same
+/// fonts — a cue change only rasterizes glyphs not seen before. Slots are
+/// family indices, so the owner clears it whenever the fonts change.
+#[derive(Default)]
+pub(super) struct GlyphCache {
+    glyphs: HashMap<GlyphKey, GlyphBitmap>,
+}
+
+impl GlyphCache {
+    /// Entries kept before the cache starts over. A long CJK film at
+    /// several sizes stays well below it.
+    const CAPACITY: usize = 4096;
+
+    pub fn get(&mut self, fonts: &FontFamily, key: GlyphKey) -> &GlyphBitmap {
+        if self.glyphs.len() >= Self::CAPACITY && !self.glyphs.contains_key(&key) {
+            self.glyphs.clear();
+        }
+        self.glyphs
+            .entry(key)
+            .or_insert_with(|| rasterize_glyph(fonts, key))
+    }
+
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+    }
+}
+
+fn rasterize_glyph(fonts: &FontFamily, key: GlyphKey) -> GlyphBitmap {
+    let px_size = key.size as f32 / 64.0;
+    let font = fonts.font(key.font);
+    let (metrics, upright) = font.raster.rasterize_indexed(key.id, px_size);
+    let (coverage, width, slant_dx) = if key.oblique {
+        shear(&upright, metrics.width, metrics.height, metrics.ymin)
+    } else {
+        (upright, metrics.width, 0)
+    };
+    GlyphBitmap {
+        width: width as u32,
+        height: metrics.height as u32,
+        left: metrics.xmin + slant_dx,
+        top: -(metrics.height as i32) - metrics.ymin,
+        coverage,
+    }
+}
+
+/// Ruby annotations are drawn at half the base size.
+const RUBY_SCALE: f32 = 0.5;
+
+/// One drawing step of a [`Frame`], in frame pixels (y down). Steps
+/// composite in order with premultiplied "over".
+#[derive(Clone, Copy, Debug)]
+pub(super) enum DrawOp {
+    /// A flat box: cue and run backgrounds, underlines.
+    Rect {
+        x: i32,
+        y: i32,
+        w: i32,
+        h: i32,
+        color: [u8; 4],
+    },
+    /// A cached glyph with its pen at (`x`, `y`) on the baseline; the
+    /// bitmap's own `left` / `top` place it from there.
+    Glyph {
+        key: GlyphKey,
+        x: i32,
+        y: i32,
+        color: [u8; 4],
+    },
+}
+
+impl DrawOp {
+    fn offset(self, dx: i32, dy: i32) -> Self {
+        match self {
+            DrawOp::Rect { x, y, w, h, color } => DrawOp::Rect {
+                x: x + dx,
+                y: y + dy,
+                w,
+                h,
+                color,
+            },
+            DrawOp::Glyph { key, x, y, color } => DrawOp::Glyph {
+                key,
+                x: x + dx,
+                y: y + dy,
+                color,
+            },
+        }
+    }
+}
+
+/// The active cues laid out as one `width` × `height` block of draw ops.
+pub(super) struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub ops: Vec<DrawOp>,
+    /// Top-left corner as a fraction of the target surface. `None` = the
+    /// classic bottom-centre placement, left to the caller's safe-area
+    /// logic.
+    pub anchor: Option<[f32; 2]>,
+}
+
+impl Frame {
+    /// Execute the ops into a premultiplied RGBA8 bitmap of the frame's
+    /// size, for sinks that upload whole bitmaps (the GLES hook).
+    pub fn paint(&self, fonts: &FontFamily, cache: &mut GlyphCache) -> Vec<u8> {
+        let (w, h) = (self.width, self.height);
+        let mut rgba = vec![0u8; (w * h * 4) as usize];
+        for op in &self.ops {
+            match *op {
+                DrawOp::Rect {
+                    x,
+                    y,
+                    w: rw,
+                    h: rh,
+                    color,
+                } => fill_rect(x, y, rw, rh, color, w, h, &mut rgba),
+                DrawOp::Glyph { key, x, y, color } => {
+                    let g = cache.get(fonts, key);
+                    blit_coverage(
+                        &g.coverage,
+                        g.width as i32,
+                        g.height as i32,
+                        x + g.left,
+                        y + g.top,
+                        color,
+                        w,
+                        h,
+                        &mut rgba,
+                    );
+                }
+            }
+        }
+        rgba
+    }
+
+    /// Top-left corner on a `target_w` × `target_h` surface; see
+    /// [`origin`].
+    pub fn origin(&self, target_w: u32, target_h: u32, bottom_inset_px: u32) -> (i32, i32) {
+        origin(
+            self.anchor,
+            self.width,
+            self.height,
+            target_w,
+            target_h,
+            bottom_inset_px,
+        )
+    }
+}
+
+/// Top-left corner of a `width` × `height` frame on a `target_w` ×
+/// `target_h` surface, in whole pixels so atlas texels land 1:1. Anchored
+/// frames go where their layout put them. Unanchored ones sit
+/// bottom-centre, above the host-reported bottom safe area (real screen
+/// geometry via WindowInsets; on a TV the host maxes it with the
+/// title-safe margin so invisible HDMI overscan is still cleared);
+/// `bottom_inset_px` == 0 → 10% TV title-safe fallback. Kept in parity
+/// with the GLES quad.
+pub(super) fn origin(
+    anchor: Option<[f32; 2]>,
+    width: u32,
+    height: u32,
+    target_w: u32,
+    target_h: u32,
+    bottom_inset_px: u32,
+) -> (i32, i32) {
+    let (tw, th) = (target_w as f32, target_h as f32);
+    let (x, y) = match anchor {
+        Some([ax, ay]) => (ax * tw, ay * th),
+        None => {
+            let safe_frac = if bottom_inset_px > 0 {
+                (bottom_inset_px as f32 / th).clamp(0.0, 0.45)
+            } else {
+                0.10
+            };
+            (
+                (tw - width as f32) / 2.0,
+                th * (1.0 - safe_frac) - height as f32,
+            )
+        }
+    };
+    (x.round() as i32, y.round() as i32)
+}
+
+/// Title-safe band that snap-to-lines cues (`CueLayout::line`) count their
+/// lines in, as fractions of the target height. The bottom edge matches
+/// [`CueLayout::BOTTOM`].
+const LINES_TOP: f32 = 0.05;
+const LINES_BOTTOM: f32 = 0.9;
+
+/// Lay out every cue active right now into one [`Frame`]. Returns None
+/// when there is nothing visible.
 ///
-/// Glyph fill, outline colour and size come from `style`; the layout is
-/// still Phase 1 (a 1px drop-shadow offset each direction, line break at
-/// `\n` and at word boundaries when a single line would exceed 90% of the
-/// target width).
-pub(super) fn rasterize_cue(
-    font: &fontdue::Font,
-    text: &str,
+/// Cues without a layout stack bottom-centre in start order (the Phase 1
+/// look). As soon as one cue is positioned, every cue is placed in its
+/// box instead — unpositioned ones in [`CueLayout::BOTTOM`] — and cues
+/// sharing a box stack inside it. Line-snapped blocks that would overlap
+/// an earlier cue step a line at a time away from their edge (WebVTT
+/// §7.2 collision avoidance); percentage-positioned ones stay put.
+pub(super) fn layout_frame(
+    fonts: &FontFamily,
+    cues: &[VttCue],
     target_w: u32,
     target_h: u32,
     style: &SubtitleStyle,
-) -> Option<(u32, u32, Vec<u8>)> {
-    if text.is_empty() {
-        return None;
+) -> Option<Frame> {
+    if cues.iter().all(|c| c.layout.is_none()) {
+        let all: Vec<&VttCue> = cues.iter().collect();
+        let max_w = (target_w as f32 * 0.9) as i32;
+        let (width, height, ops) =
+            layout_block(fonts, &all, max_w, target_h, &CueLayout::BOTTOM, style)?;
+        return Some(Frame {
+            width,
+            height,
+            ops,
+            anchor: None,
+        });
+    }
+
+    let mut groups: Vec<(CueLayout, Vec<&VttCue>)> = Vec::new();
+    for cue in cues {
+        let layout = cue.layout.unwrap_or(CueLayout::BOTTOM);
+        match groups.iter_mut().find(|(l, _)| *l == layout) {
+            Some((_, group)) => group.push(cue),
+            None => groups.push((layout, vec![cue])),
+        }
     }
+
+    let (tw, th) = (target_w as i32, target_h as i32);
+    let (_, line_height) = text_metrics(target_h, style);
+    let mut placed: Vec<(i32, i32, u32, u32, Vec<DrawOp>)> = Vec::new();
+    for (layout, group) in groups {
+        let box_w = ((layout.width * tw as f32) as i32).max(1);
+        let box_h = (layout.height * th as f32) as i32;
+        let Some((w, h, ops)) = layout_block(fonts, &group, box_w, target_h, &layout, style) else {
+            continue;
+        };
+        let (box_x, box_y) = ((layout.x * tw as f32) as i32, (layout.y * th as f32) as i32);
+        let x = match layout.text_align {
+            TextAlign::Left => box_x,
+            TextAlign::Center => box_x + (box_w - w as i32) / 2,
+            TextAlign::Right => box_x + box_w - w as i32,
+        };
+        let y = match (layout.line, layout.display_align) {
+            (Some(n), _) if n >= 0 => (LINES_TOP * th as f32) as i32 + n * line_height,
+            (Some(n), _) => {
+                (LINES_BOTTOM * th as f32) as i32 - h as i32 - (-n - 1) * line_height
+            }
+            (None, DisplayAlign::Before) => box_y,
+            (None, DisplayAlign::Center) => box_y + (box_h - h as i32) / 2,
+            (None, DisplayAlign::After) => box_y + box_h - h as i32,
+        };
+        // A block bigger than its box still stays on screen.
+        let x = x.min(tw - w as i32).max(0);
+        let mut y = y.min(th - h as i32).max(0);
+        if let Some(n) = layout.line {
+            let step = if n >= 0 { line_height } else { -line_height };
+            let hits = |y: i32| {
+                placed.iter().any(|&(px, py, pw, ph, _)| {
+                    x < px + pw as i32 && px < x + w as i32 && y < py + ph as i32 && py < y + h as i32
+                })
+            };
+            let mut moved = y;
+            while hits(moved) {
+                moved += step;
+                if moved < 0 || moved + h as i32 > th {
+                    // No free line left: keep the spot the cue asked for.
+                    moved = y;
+                    break;
+                }
+            }
+            y = moved;
+        }
+        placed.push((x, y, w, h, ops));
+    }
+
+    let x0 = placed.iter().map(|p| p.0).min()?;
+    let y0 = placed.iter().map(|p| p.1).min()?;
+    let x1 = placed.iter().map(|p| p.0 + p.2 as i32).max()?;
+    let y1 = placed.iter().map(|p| p.1 + p.3 as i32).max()?;
+    let ops = placed
+        .into_iter()
+        .flat_map(|(x, y, _, _, ops)| ops.into_iter().map(move |op| op.offset(x - x0, y - y0)))
+        .collect();
+    Some(Frame {
+        width: (x1 - x0) as u32,
+        height: (y1 - y0) as u32,
+        ops,
+        anchor: Some([x0 as f32 / tw as f32, y0 as f32 / th as f32]),
+    })
+}
+
+/// [`layout_frame`] plus a secondary subtitle track in its own style.
+/// Secondary cues ignore their positioning and stack bottom-centre as one
+/// block, placed at the top of the title-safe band or right above the
+/// primary cues (`position`). With both present the result is a single
+/// anchored frame spanning the two, so the primary block's bottom-centre
+/// spot is resolved here against `bottom_inset_px`.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn layout_dual(
+    fonts: &FontFamily,
+    primary: &[VttCue],
+    style: &SubtitleStyle,
+    secondary: &[VttCue],
+    secondary_style: &SubtitleStyle,
+    position: SecondarySubtitlePosition,
+    target_w: u32,
+    target_h: u32,
+    bottom_inset_px: u32,
+) -> Option<Frame> {
+    let frame = layout_frame(fonts, primary, target_w, target_h, style);
+    let unpositioned: Vec<VttCue> = secondary
+        .iter()
+        .map(|c| VttCue {
+            layout: None,
+            ..c.clone()
+        })
+        .collect();
+    let Some(second) = layout_frame(fonts, &unpositioned, target_w, target_h, secondary_style)
+    else {
+        return frame;
+    };
+
+    let (tw, th) = (target_w as i32, target_h as i32);
+    let x = (tw - second.width as i32) / 2;
+    let y = match (position, &frame) {
+        (SecondarySubtitlePosition::Top, _) => (LINES_TOP * th as f32) as i32,
+        (SecondarySubtitlePosition::Stacked, Some(f)) => {
+            f.origin(target_w, target_h, bottom_inset_px).1 - second.height as i32
+        }
+        (SecondarySubtitlePosition::Stacked, None) => {
+            second.origin(target_w, target_h, bottom_inset_px).1
+        }
+    };
+    let mut parts = vec![(x, y.max(0), second)];
+    if let Some(f) = frame {
+        let (fx, fy) = f.origin(target_w, target_h, bottom_inset_px);
+        parts.push((fx, fy, f));
+    }
+
+    let x0 = parts.iter().map(|p| p.0).min()?;
+    let y0 = parts.iter().map(|p| p.1).min()?;
+    let x1 = parts.iter().map(|p| p.0 + p.2.width as i32).max()?;
+    let y1 = parts.iter().map(|p| p.1 + p.2.height as i32).max()?;
+    let ops = parts
+        .into_iter()
+        .flat_map(|(x, y, f)| f.ops.into_iter().map(move |op| op.offset(x - x0, y - y0)))
+        .collect();
+    Some(Frame {
+        width: (x1 - x0) as u32,
+        height: (y1 - y0) as u32,
+        ops,
+        anchor: Some([x0 as f32 / tw as f32, y0 as f32 / th as f32]),
+    })
+}
+
+/// (font px size, line height px) for a target of `target_h` pixels.
+fn text_metrics(target_h: u32, style: &SubtitleStyle) -> (f32, i32) {
     // Font size: ~5% of video height scaled by the user's size_scale,
     // clamped to a readable range. The lower 12px floor keeps the 0.5×
     // setting legible on tiny preview windows; the upper bound caps the
     // cue bitmap so a 3× setting on a 4K surface can't exceed texture
     // limits.
     let px_size = (target_h as f32 * 0.05 * style.size_scale).clamp(12.0, 160.0);
-    let max_line_w = (target_w as f32 * 0.9) as i32;
-    let line_height = (px_size * 1.25).ceil() as i32;
-    let shadow = 2i32;
+    (px_size, (px_size * 1.25).ceil() as i32)
+}
+
+/// Lay a group of cues out as one text block, wrapped to `max_w` and
+/// aligned per `layout.text_align`, keeping only the last
+/// `layout.max_lines` lines. Returns (width, height, draw ops) or None
+/// when there is no text.
+///
+/// Glyph fill, edges, font family and size come from `style`; a run's
+/// own colour (TTML `tts:color`, WebVTT classes and `::cue`) overrides the
+/// fill. Bold and italic runs use the family's bold / italic face, or are
+/// synthesised by double-striking and shearing the glyph when the font
+/// drawing it has none; underline and run backgrounds are flat boxes. Lines carrying
+/// ruby get a band above them for the annotations. `style.window_color`
+/// fills the whole block, then `layout.background` over it;
+/// `style.background_color` boxes each line.
+fn layout_block(
+    fonts: &FontFamily,
+    cues: &[&VttCue],
+    max_w: i32,
+    target_h: u32,
+    layout: &CueLayout,
+    style: &SubtitleStyle,
+) -> Option<(u32, u32, Vec<DrawOp>)> {
+    let fonts = &fonts.with_family(style.font_family);
+    let (px_size, line_height) = text_metrics(target_h, style);
+    // Margin around the text: room for the glyph edges, and the padding
+    // of the line boxes.
+    let shadow = 2i32.max(outline_width(px_size));
 
-    // Wrap each input line, then concatenate into a flat list of layout lines.
-    let mut layout_lines: Vec<String> = Vec::new();
-    for raw_line in text.lines() {
-        wrap_line(font, raw_line, px_size, max_line_w, &mut layout_lines);
+    // Split each cue into hard lines, wrap those, and stack the results.
+    let mut rubies: Vec<(String, SpanStyle)> = Vec::new();
+    let mut layout_lines: Vec<Vec<Glyph>> = Vec::new();
+    for cue in cues {
+        let chars = styled_chars(cue, &mut rubies);
+        let mut hard: Vec<&[StyledChar]> = chars.split(|c| c.ch == '\n').collect();
+        if hard.last().is_some_and(|l| l.is_empty()) {
+            hard.pop();
+        }
+        for line in hard {
+            let para = Paragraph::new(line);
+            let bidi = BidiInfo::new(&para.text, None);
+            for range in para.break_lines(fonts, &bidi, px_size, max_w as f32) {
+                layout_lines.push(para.shape_line(fonts, &bidi, range, px_size));
+            }
+        }
+    }
+    if let Some(max) = layout.max_lines {
+        let excess = layout_lines.len().saturating_sub(max as usize);
+        layout_lines.drain(..excess);
     }
     if layout_lines.is_empty() {
         return None;
     }
 
     // First pass: measure each line.
-    let mut line_widths: Vec<i32> = Vec::with_capacity(layout_lines.len());
-    let mut max_width = 0i32;
-    for line in &layout_lines {
-        let w = measure_text(font, line, px_size);
-        line_widths.push(w);
-        if w > max_width {
-            max_width = w;
-        }
-    }
+    let line_widths: Vec<i32> = layout_lines.iter().map(|l| line_width(l)).collect();
+    let max_width = line_widths.iter().copied().max().unwrap_or(0);
+    let ruby_band = (px_size * RUBY_SCALE * 1.25).ceil() as i32;
+    let bands: Vec<i32> = layout_lines
+        .iter()
+        .map(|l| {
+            if l.iter().any(|c| c.ruby.is_some()) {
+                ruby_band
+            } else {
+                0
+            }
+        })
+        .collect();
 
     let bitmap_w = (max_width + shadow * 2).max(8) as u32;
-    let bitmap_h = (line_height * layout_lines.len() as i32 + shadow * 2).max(8) as u32;
+    let text_h = line_height * layout_lines.len() as i32 + bands.iter().sum::<i32>();
+    let bitmap_h = (text_h + shadow * 2).max(8) as u32;
 
-    let mut rgba = vec![0u8; (bitmap_w * bitmap_h * 4) as usize];
+    let mut ops = Vec::new();
+    let window = Some(style.window_color).filter(|c| c[3] > 0);
+    for color in window.into_iter().chain(layout.background) {
+        let (w, h) = (bitmap_w as i32, bitmap_h as i32);
+        ops.push(DrawOp::Rect {
+            x: 0,
+            y: 0,
+            w,
+            h,
+            color,
+        });
+    }
 
-    // Second pass: rasterize each line centered horizontally in the bitmap.
+    let line_x = |line_w: i32| match layout.text_align {
+        TextAlign::Left => shadow,
+        TextAlign::Center => (bitmap_w as i32 - line_w) / 2,
+        TextAlign::Right => bitmap_w as i32 - line_w - shadow,
+    };
+    // Line boxes go down before any glyph, so no box covers the edge or
+    // descender of the line above.
+    if style.background_color[3] > 0 {
+        let mut y = shadow;
+        for (idx, &line_w) in line_widths.iter().enumerate() {
+            let h = bands[idx] + line_height;
+            if line_w > 0 {
+                let x = (line_x(line_w) - shadow).max(0);
+                let right = (line_x(line_w) + line_w + shadow).min(bitmap_w as i32);
+                ops.push(DrawOp::Rect {
+                    x,
+                    y,
+                    w: right - x,
+                    h,
+                    color: style.background_color,
+                });
+            }
+            y += h;
+        }
+    }
+
+    // Second pass: lay out each line, aligned inside the block.
+    let mut y_start = shadow;
     for (idx, line) in layout_lines.iter().enumerate() {
-        let line_w = line_widths[idx];
-        let x_start = (bitmap_w as i32 - line_w) / 2;
-        let y_start = idx as i32 * line_height + shadow;
-        rasterize_line(
-            font, line, px_size, x_start, y_start, bitmap_w, bitmap_h,
-            style.text_color, style.outline_color, &mut rgba,
+        let x_start = line_x(line_widths[idx]);
+        if bands[idx] > 0 {
+            layout_ruby(
+                fonts, line, &rubies, px_size, x_start, y_start, bands[idx], style, &mut ops,
+            );
+            y_start += bands[idx];
+        }
+        layout_line(
+            line,
+            px_size,
+            x_start,
+            y_start,
+            line_height,
+            style,
+            &mut ops,
         );
+        y_start += line_height;
     }
-    Some((bitmap_w, bitmap_h, rgba))
+    Some((bitmap_w, bitmap_h, ops))
 }
 
-fn wrap_line(
-    font: &fontdue::Font,
-    line: &str,
-    px_size: f32,
-    max_w: i32,
-    out: &mut Vec<String>,
-) {
-    if measure_text(font, line, px_size) <= max_w {
-        out.push(line.to_string());
-        return;
+/// A cue's characters paired with their run style; plain cues get the
+/// default style throughout. Ruby annotations go into `rubies`, and the
+/// base characters point at theirs.
+fn styled_chars(cue: &VttCue, rubies: &mut Vec<(String, SpanStyle)>) -> Vec<StyledChar> {
+    if cue.spans.is_empty() {
+        return cue
+            .text
+            .chars()
+            .map(|ch| StyledChar {
+                ch,
+                style: SpanStyle::default(),
+                ruby: None,
+            })
+            .collect();
     }
-    let mut current = String::new();
-    for word in line.split_whitespace() {
-        let candidate = if current.is_empty() {
-            word.to_string()
-        } else {
-            format!("{} {}", current, word)
-        };
-        if measure_text(font, &candidate, px_size) <= max_w {
-            current = candidate;
-        } else {
-            if !current.is_empty() {
-                out.push(current.clone());
+    let mut out = Vec::new();
+    for span in &cue.spans {
+        let ruby = span.ruby.as_ref().map(|text| {
+            rubies.push((text.clone(), span.style));
+            rubies.len() - 1
+        });
+        out.extend(span.text.chars().map(|ch| StyledChar {
+            ch,
+            style: span.style,
+            ruby,
+        }));
+    }
+    out
+}
+
+/// A hard line of cue text (no `\n`) as one string, for the bidi, line
+/// breaking and shaping passes, which all work in byte offsets.
+struct Paragraph<'c> {
+    chars: &'c [StyledChar],
+    text: String,
+    /// Character index of every byte of `text`.
+    char_at: Vec<usize>,
+    /// Byte offset of every character, plus `text.len()` at the end.
+    byte_of: Vec<usize>,
+}
+
+impl<'c> Paragraph<'c> {
+    fn new(chars: &'c [StyledChar]) -> Self {
+        let mut text = String::with_capacity(chars.len());
+        let mut char_at = Vec::with_capacity(chars.len());
+        let mut byte_of = Vec::with_capacity(chars.len() + 1);
+        for (i, c) in chars.iter().enumerate() {
+            byte_of.push(text.len());
+            text.push(c.ch);
+            char_at.resize(text.len(), i);
+        }
+        byte_of.push(text.len());
+        Self {
+            chars,
+            text,
+            char_at,
+            byte_of,
+        }
+    }
+
+    /// Split into lines no wider than `max_w` at UAX #14 break
+    /// opportunities (greedy, like a browser). A run with no opportunity
+    /// that is wider on its own still gets a line to itself. Returns
+    /// character ranges; whitespace at a wrap point is dropped.
+    fn break_lines(
+        &self,
+        fonts: &FontFamily,
+        bidi: &BidiInfo,
+        px_size: f32,
+        max_w: f32,
+    ) -> Vec<Range<usize>> {
+        let n = self.chars.len();
+        // Logical width of every character: shape the whole paragraph
+        // once and credit each glyph to its cluster.
+        let mut widths = vec![0.0f32; n];
+        for g in self.shape_line(fonts, bidi, 0..n, px_size) {
+            widths[g.cluster] += g.advance;
+        }
+        let mut lines = Vec::new();
+        let (mut line_start, mut seg_start, mut width) = (0, 0, 0.0f32);
+        for (byte, opportunity) in linebreaks(&self.text) {
+            let end = self.char_at.get(byte).copied().unwrap_or(n);
+            let visible = self.trim_end(seg_start..end);
+            let seg_w: f32 = widths[seg_start..end].iter().sum();
+            let visible_w: f32 = widths[visible].iter().sum();
+            if seg_start > line_start && width + visible_w > max_w {
+                lines.push(self.trim_end(line_start..seg_start));
+                line_start = seg_start;
+                width = 0.0;
+            }
+            width += seg_w;
+            seg_start = end;
+            // Paragraph separators inside the line (U+2029, NEL …).
+            if opportunity == BreakOpportunity::Mandatory && end < n {
+                lines.push(self.trim_end(line_start..end));
+                line_start = end;
+                width = 0.0;
+            }
+        }
+        lines.push(line_start..n);
+        lines
+    }
+
+    /// `range` without its trailing whitespace.
+    fn trim_end(&self, range: Range<usize>) -> Range<usize> {
+        let trailing = self.chars[range.clone()]
+            .iter()
+            .rev()
+            .take_while(|c| c.ch.is_whitespace())
+            .count();
+        range.start..range.end - trailing
+    }
+
+    /// Shape the characters in `range` into glyphs in visual order:
+    /// reorder into bidi level runs (UAX #9, line-level rules included),
+    /// then shape each run per face in its direction.
+    fn shape_line(
+        &self,
+        fonts: &FontFamily,
+        bidi: &BidiInfo,
+        range: Range<usize>,
+        px_size: f32,
+    ) -> Vec<Glyph> {
+        let bytes = self.byte_of[range.start]..self.byte_of[range.end];
+        let mut glyphs = Vec::new();
+        for para in bidi
+            .paragraphs
+            .iter()
+            .filter(|p| p.range.start < bytes.end && bytes.start < p.range.end)
+        {
+            let line = bytes.start.max(para.range.start)..bytes.end.min(para.range.end);
+            let (levels, runs) = bidi.visual_runs(para, line);
+            for run in runs {
+                let rtl = levels[run.start].is_rtl();
+                self.shape_run(fonts, run, rtl, px_size, &mut glyphs);
+            }
+        }
+        glyphs
+    }
+
+    /// Shape one bidi level run (bytes). A run mixing fonts — a bold word
+    /// in plain text, a character only a fallback covers — is shaped font
+    /// by font, since glyph ids belong to one font; right-to-left runs
+    /// emit the pieces last-first.
+    fn shape_run(
+        &self,
+        fonts: &FontFamily,
+        bytes: Range<usize>,
+        rtl: bool,
+        px_size: f32,
+        out: &mut Vec<Glyph>,
+    ) {
+        if bytes.is_empty() {
+            return;
+        }
+        let (first, last) = (self.char_at[bytes.start], self.char_at[bytes.end - 1]);
+        let mut fonts_of: Vec<(u16, bool, bool)> = Vec::with_capacity(last + 1 - first);
+        for i in first..=last {
+            let c = &self.chars[i];
+            // Spaces and combining marks stay in the font of what precedes
+            // them, so a fallback word isn't split at every space and a
+            // mark shapes onto its base.
+            let font = match fonts_of.last() {
+                Some(&prev)
+                    if c.style == self.chars[i - 1].style
+                        && joins_previous(c.ch)
+                        && fonts.font(prev.0).covers(c.ch) =>
+                {
+                    prev
+                }
+                _ => fonts.pick(&c.style, c.ch),
+            };
+            fonts_of.push(font);
+        }
+        let mut pieces = Vec::new();
+        let mut start = first;
+        for i in first + 1..=last {
+            if fonts_of[i - first] != fonts_of[start - first] {
+                pieces.push(start..i);
+                start = i;
+            }
+        }
+        pieces.push(start..last + 1);
+        if rtl {
+            pieces.reverse();
+        }
+
+        for piece in pieces {
+            let (font, synth_bold, synth_italic) = fonts_of[piece.start - first];
+            let Some(face) = fonts.font(font).shaper() else {
+                continue;
+            };
+            let offset = self.byte_of[piece.start];
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(&self.text[offset..self.byte_of[piece.end]]);
+            buffer.set_direction(if rtl {
+                Direction::RightToLeft
+            } else {
+                Direction::LeftToRight
+            });
+            buffer.guess_segment_properties();
+            let shaped = rustybuzz::shape(&face, &[], buffer);
+            let scale = px_size / face.units_per_em() as f32;
+            let bold = if synth_bold {
+                bold_offset(px_size) as f32
+            } else {
+                0.0
+            };
+            for (info, pos) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                let cluster = self.char_at[offset + info.cluster as usize];
+                let advance = pos.x_advance as f32 * scale;
+                out.push(Glyph {
+                    id: info.glyph_id as u16,
+                    font,
+                    synth_bold,
+                    synth_italic,
+                    cluster,
+                    style: self.chars[cluster].style,
+                    ruby: self.chars[cluster].ruby,
+                    // Synthetic bold widens spacing glyphs by the double-
+                    // strike offset so they don't touch; marks stay put.
+                    advance: if advance > 0.0 {
+                        advance + bold
+                    } else {
+                        advance
+                    },
+                    dx: pos.x_offset as f32 * scale,
+                    dy: pos.y_offset as f32 * scale,
+                });
             }
-            current = word.to_string();
         }
     }
-    if !current.is_empty() {
-        out.push(current);
+}
+
+/// Whitespace, combining marks and format controls: characters that take
+/// no font of their own when the one before them can draw them.
+fn joins_previous(ch: char) -> bool {
+    matches!(
+        bidi_class(ch),
+        BidiClass::WS | BidiClass::NSM | BidiClass::BN
+    )
+}
+
+fn line_width(line: &[Glyph]) -> i32 {
+    line.iter().map(|g| g.advance).sum::<f32>().ceil() as i32
+}
+
+fn bold_offset(px_size: f32) -> i32 {
+    (px_size / 24.0).round().max(1.0) as i32
+}
+
+/// Thickness of a `SubtitleEdgeStyle::Uniform` outline.
+fn outline_width(px_size: f32) -> i32 {
+    (px_size / 20.0).round().clamp(1.0, 4.0) as i32
+}
+
+/// The copies of a glyph (or underline) drawn under its fill for
+/// `style.edge_style`, as (colour, dx, dy): a 1px shadow down-right, a
+/// light and a dark 1px edge for raised / depressed, or eight copies
+/// around it for an outline.
+fn edge_strokes(style: &SubtitleStyle, px_size: f32) -> Vec<([u8; 4], i32, i32)> {
+    let dark = style.outline_color;
+    let light = [255, 255, 255, dark[3]];
+    match style.edge_style {
+        SubtitleEdgeStyle::None => Vec::new(),
+        SubtitleEdgeStyle::DropShadow => vec![(dark, 1, 1)],
+        SubtitleEdgeStyle::Raised => vec![(light, -1, -1), (dark, 1, 1)],
+        SubtitleEdgeStyle::Depressed => vec![(dark, -1, -1), (light, 1, 1)],
+        SubtitleEdgeStyle::Uniform => {
+            let w = outline_width(px_size);
+            let mut strokes = Vec::with_capacity(8);
+            for dy in [-w, 0, w] {
+                for dx in [-w, 0, w] {
+                    if (dx, dy) != (0, 0) {
+                        strokes.push((dark, dx, dy));
+                    }
+                }
+            }
+            strokes
+        }
     }
 }
 
-fn measure_text(font: &fontdue::Font, line: &str, px_size: f32) -> i32 {
-    let mut total = 0.0f32;
-    for ch in line.chars() {
-        let metrics = font.metrics(ch, px_size);
-        total += metrics.advance_width;
+/// Lay a line's glyphs out left-to-right from `x_start`, in the line box
+/// starting at `y_start`. The fill is the run's colour or
+/// `style.text_color`; the `style.edge_style` copies in
+/// `style.outline_color` are drawn first (see [`edge_strokes`]). Run
+/// backgrounds go down before any glyph so neighbouring edges draw over
+/// them.
+fn layout_line(
+    line: &[Glyph],
+    px_size: f32,
+    x_start: i32,
+    y_start: i32,
+    line_height: i32,
+    style: &SubtitleStyle,
+    ops: &mut Vec<DrawOp>,
+) {
+    let mut pen_x = x_start as f32;
+    for g in line {
+        let next = pen_x + g.advance;
+        if let Some(color) = g.style.background {
+            let x = pen_x.round() as i32;
+            ops.push(DrawOp::Rect {
+                x,
+                y: y_start,
+                w: next.round() as i32 - x,
+                h: line_height,
+                color,
+            });
+        }
+        pen_x = next;
+    }
+
+    let baseline = y_start + (px_size * 0.9) as i32;
+    let bold_dx = bold_offset(px_size);
+    let edges = edge_strokes(style, px_size);
+    let mut pen_x = x_start as f32;
+    for g in line {
+        let run = &g.style;
+        let key = GlyphKey::new(g, px_size);
+        let gx = (pen_x + g.dx).round() as i32;
+        let gy = baseline - g.dy.round() as i32;
+        let fill = run.color.unwrap_or(style.text_color);
+        let strikes: &[i32] = if g.synth_bold { &[0, bold_dx] } else { &[0] };
+        // Edges first, then the foreground fill.
+        for &(color, ex, ey) in edges.iter().chain([&(fill, 0, 0)]) {
+            for dx in strikes {
+                ops.push(DrawOp::Glyph {
+                    key,
+                    x: gx + dx + ex,
+                    y: gy + ey,
+                    color,
+                });
+            }
+        }
+        let next = pen_x + g.advance;
+        if run.underline {
+            let x = pen_x.round() as i32;
+            let w = next.round() as i32 - x;
+            let y = baseline + (px_size * 0.1).ceil() as i32;
+            let h = (px_size / 16.0).round().max(1.0) as i32;
+            for &(color, ex, ey) in edges.iter().chain([&(fill, 0, 0)]) {
+                ops.push(DrawOp::Rect {
+                    x: x + ex,
+                    y: y + ey,
+                    w,
+                    h,
+                    color,
+                });
+            }
+        }
+        pen_x = next;
     }
-    total.ceil() as i32
 }
 
-/// Draw glyphs left-to-right starting at (x, y_baseline-ish). `text_color`
-/// is the fill, `outline_color` the drop-shadow drawn first at a (+1, +1)
-/// offset; both are RGBA with the alpha multiplying glyph coverage.
+/// Lay out the ruby annotations of `line` (laid out from `x_start`)
+/// centred over their base runs, in the `band` px above the line that
+/// starts at `y_top`. Annotations take their base's colour and face but
+/// no background or underline.
 #[allow(clippy::too_many_arguments)]
-fn rasterize_line(
-    font: &fontdue::Font,
-    line: &str,
+fn layout_ruby(
+    fonts: &FontFamily,
+    line: &[Glyph],
+    rubies: &[(String, SpanStyle)],
     px_size: f32,
     x_start: i32,
-    y_start: i32,
+    y_top: i32,
+    band: i32,
+    style: &SubtitleStyle,
+    ops: &mut Vec<DrawOp>,
+) {
+    let ruby_px = px_size * RUBY_SCALE;
+    let mut pen_x = x_start as f32;
+    let mut i = 0;
+    while i < line.len() {
+        // Walk one run of characters sharing an annotation (or none).
+        let (base_x, ruby) = (pen_x, line[i].ruby);
+        while i < line.len() && line[i].ruby == ruby {
+            pen_x += line[i].advance;
+            i += 1;
+        }
+        let Some((text, base)) = ruby.and_then(|r| rubies.get(r)) else {
+            continue;
+        };
+        let style_of = SpanStyle {
+            background: None,
+            underline: false,
+            ..*base
+        };
+        let chars: Vec<StyledChar> = text
+            .chars()
+            .map(|ch| StyledChar {
+                ch,
+                style: style_of,
+                ruby: None,
+            })
+            .collect();
+        let para = Paragraph::new(&chars);
+        let bidi = BidiInfo::new(&para.text, None);
+        let annotation = para.shape_line(fonts, &bidi, 0..chars.len(), ruby_px);
+        let w = line_width(&annotation);
+        let x = ((base_x + pen_x) / 2.0).round() as i32 - w / 2;
+        layout_line(&annotation, ruby_px, x, y_top, band, style, ops);
+    }
+}
+
+/// Synthetic oblique: shift each coverage row right in proportion to its
+/// height above the baseline (~12°). Returns the widened coverage, its
+/// width, and where its left edge sits relative to the upright glyph's.
+fn shear(coverage: &[u8], w: usize, h: usize, ymin: i32) -> (Vec<u8>, usize, i32) {
+    const SLANT: f32 = 0.21;
+    if w == 0 || h == 0 {
+        return (coverage.to_vec(), w, 0);
+    }
+    // Row r sits (h - 1 - r + ymin) px above the baseline; row 0 (the top)
+    // shifts furthest right, the last row (possibly a descender) least.
+    let shift = |r: usize| ((h as i32 - 1 - r as i32 + ymin) as f32 * SLANT).round() as i32;
+    let (lo, hi) = (shift(h - 1), shift(0));
+    let out_w = w + (hi - lo) as usize;
+    let mut out = vec![0u8; out_w * h];
+    for r in 0..h {
+        let dx = (shift(r) - lo) as usize;
+        out[r * out_w + dx..r * out_w + dx + w].copy_from_slice(&coverage[r * w..(r + 1) * w]);
+    }
+    (out, out_w, lo)
+}
+
+/// A flat box, composited like a fully covered glyph.
+#[allow(clippy::too_many_arguments)]
+fn fill_rect(
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    color: [u8; 4],
     bitmap_w: u32,
     bitmap_h: u32,
-    text_color: [u8; 4],
-    outline_color: [u8; 4],
     rgba: &mut [u8],
 ) {
-    let baseline = y_start + (px_size * 0.9) as i32;
-    let mut pen_x = x_start as f32;
-    for ch in line.chars() {
-        let (metrics, glyph_bitmap) = font.rasterize(ch, px_size);
-        let gx = pen_x.round() as i32 + metrics.xmin;
-        let gy = baseline - metrics.height as i32 - metrics.ymin;
-        // Drop shadow first (offset +1, +1)
-        blit_coverage(
-            &glyph_bitmap,
-            metrics.width as i32,
-            metrics.height as i32,
-            gx + 1,
-            gy + 1,
-            outline_color,
-            bitmap_w,
-            bitmap_h,
-            rgba,
-        );
-        // Foreground fill
-        blit_coverage(
-            &glyph_bitmap,
-            metrics.width as i32,
-            metrics.height as i32,
-            gx,
-            gy,
-            text_color,
-            bitmap_w,
-            bitmap_h,
-            rgba,
-        );
-        pen_x += metrics.advance_width;
+    if w <= 0 || h <= 0 {
+        return;
     }
+    let coverage = vec![255u8; (w * h) as usize];
+    blit_coverage(&coverage, w, h, x, y, color, bitmap_w, bitmap_h, rgba);
 }
 
 /// Blit an alpha-coverage glyph bitmap with a flat color over an RGBA8
@@ -6,6 +6,8 @@ use tokio::sync::{
 };
 #[cfg(any(target_os = "windows", target_os = "linux"))]
 use video_frame::VideoFrame;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use video_software::SoftwareFrameUploader;
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 use video_metal::{MetalNV12Frame, MetalTextureCache};
 use wgpu::{Backends, Buffer};
@@ -17,6 +19,8 @@ use super::video_offscreen::{OffscreenTarget, OFFSCREEN_FORMAT};
 mod video_directx;
 #[cfg(any(target_os = "windows", target_os = "linux"))]
 mod video_frame;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod video_software;
 #[cfg(target_os = "linux")]
 mod video_vaapi;
 // Vulkan import helper is shared by every backend that imports external GPU
@@ -297,6 +301,10 @@ pub struct VideoRenderer {
     /// Defaults to `HdrTonemapParams::DEFAULT`. Storage is platform-agnostic
     /// — sinks that don't have the HDR shader path simply ignore it.
     hdr_tonemap_params: Arc<arc_swap::ArcSwap<crate::HdrTonemapParams>>,
+    /// Plane textures for software-decoded frames (no hw_frames_ctx) —
+    /// uploaded from system memory instead of imported.
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    sw_upload: SoftwareFrameUploader,
     command_sender: Sender<VideoRendererCommand>,
     // GLES zero-copy OES renderer for devices without working Vulkan (e.g. Google TV MT8696).
     // Arc so the renderer can be shared with the present hook closure.
@@ -333,6 +341,9 @@ pub struct VideoRenderer {
     /// `Player::set_subtitle_safe_insets`, threaded into the subtitle quad's
     /// anchor. 0 = unset → renderers fall back to a 10% TV title-safe margin.
     subtitle_safe_bottom_px: std::sync::atomic::AtomicU32,
+    /// `Player::set_subtitle_offset_ms`: subtracted from the PTS handed to
+    /// the overlay's cue picker, so + shows cues later.
+    subtitle_offset_ms: std::sync::atomic::AtomicI64,
     /// `ANativeWindow*` of the host surface (embed model) for
     /// `ANativeWindow_setBuffersDataSpace`. 0 when unavailable (winit
     /// path) — passthrough then stays off.
@@ -592,14 +603,18 @@ impl VideoRenderer {
         // TEXTURE_FORMAT_NV12 (multi-planar texture). On macOS Metal the
         // pipeline binds two separate Y+UV textures (R8/RG8) imported from
         // CVPixelBuffer planes, so no NV12 feature is needed — but the
-        // pipeline + vertex buffer still need to be created.
+        // pipeline + vertex buffer still need to be created. Windows/Linux
+        // also build it without NV12: software-decoded frames bind as plain
+        // R8/Rg8 (or R16/Rg16) plane textures, and a GPU-less host's CPU
+        // Vulkan driver (lavapipe / WARP) rarely exposes NV12.
         let needs_pipeline = required_features.contains(wgpu::Features::TEXTURE_FORMAT_NV12)
-            || backend == wgpu::Backend::Metal;
+            || backend == wgpu::Backend::Metal
+            || (cfg!(any(target_os = "windows", target_os = "linux")) && is_hw_backend);
         let has_nv12_feature = needs_pipeline;
         log::info!(
             "[renderer] backend={:?} nv12={} adapter={}",
             backend,
-            has_nv12_feature,
+            required_features.contains(wgpu::Features::TEXTURE_FORMAT_NV12),
             adapter.get_info().name,
         );
 
@@ -1029,6 +1044,8 @@ impl VideoRenderer {
             hdr_tonemap_params: Arc::new(arc_swap::ArcSwap::from_pointee(
                 crate::HdrTonemapParams::DEFAULT,
             )),
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
+            sw_upload: SoftwareFrameUploader::default(),
             command_sender,
             subtitle_overlay: Arc::new(std::sync::Mutex::new(None)),
             #[cfg(target_os = "android")]
@@ -1042,6 +1059,7 @@ impl VideoRenderer {
             #[cfg(target_os = "android")]
             display_hdr_types: std::sync::atomic::AtomicU32::new(0),
             subtitle_safe_bottom_px: std::sync::atomic::AtomicU32::new(0),
+            subtitle_offset_ms: std::sync::atomic::AtomicI64::new(0),
             #[cfg(target_os = "android")]
             android_window: 0,
             #[cfg(target_os = "android")]
@@ -1382,6 +1400,8 @@ impl VideoRenderer {
             hdr_tonemap_params: Arc::new(arc_swap::ArcSwap::from_pointee(
                 crate::HdrTonemapParams::DEFAULT,
             )),
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
+            sw_upload: SoftwareFrameUploader::default(),
             command_sender,
             subtitle_overlay: Arc::new(std::sync::Mutex::new(None)),
             #[cfg(target_os = "android")]
@@ -1397,6 +1417,7 @@ impl VideoRenderer {
             #[cfg(target_os = "android")]
             display_hdr_types: std::sync::atomic::AtomicU32::new(0),
             subtitle_safe_bottom_px: std::sync::atomic::AtomicU32::new(0),
+            subtitle_offset_ms: std::sync::atomic::AtomicI64::new(0),
             #[cfg(target_os = "android")]
             android_window: 0,
             #[cfg(target_os = "android")]
@@ -1505,7 +1526,10 @@ impl VideoRenderer {
         let size = self.inner_size();
         let subtitle = {
             let overlay = self.subtitle_overlay.lock().unwrap().clone();
-            overlay.and_then(|o| o.active_bitmap(size.width, size.height))
+            let inset = self
+                .subtitle_safe_bottom_px
+                .load(std::sync::atomic::Ordering::Relaxed);
+            overlay.and_then(|o| o.active_bitmap(size.width, size.height, inset))
         };
         let gen = subtitle.as_ref().map(|b| b.generation).unwrap_or(0);
         if self
@@ -1764,22 +1788,29 @@ impl VideoRenderer {
     /// CVPixelBuffer → MTLTexture).
     #[cfg(any(target_os = "windows", target_os = "linux"))]
     pub async fn render(&self, frame: Arc<Video>) {
-        let video_frame = VideoFrame::new(self.device.clone(), self.backend, frame.clone());
+        // Software decode mode hands over system-memory NV12/P010 frames (no
+        // hw_frames_ctx): upload their planes instead of importing a surface.
+        let software = unsafe { (*frame.as_ptr()).hw_frames_ctx.is_null() };
+        let video_frame = (!software)
+            .then(|| VideoFrame::new(self.device.clone(), self.backend, frame.clone()));
+        let is_hdr = match &video_frame {
+            Some(vf) => vf.get_texture().format() == TextureFormat::P010,
+            None => frame.format() == ffmpeg_next::format::Pixel::P010LE,
+        };
 
         // Granular checkpoint logging — Intel UHD reports INVALID_CALL → device-removed
         // somewhere after a successful D3D11→DX12 P010 import. The cheap calls below
         // each take a turn at the DX12 device (CreateShaderResourceView, descriptor
         // allocation), so the last "step ok" log tells us which one the driver killed.
         #[cfg(target_os = "windows")]
-        let is_p010_dbg = video_frame.get_texture().format() == TextureFormat::P010
-            && self.backend == wgpu::Backend::Dx12;
+        let is_p010_dbg = !software && is_hdr && self.backend == wgpu::Backend::Dx12;
         #[cfg(not(target_os = "windows"))]
         let is_p010_dbg = false;
         if is_p010_dbg {
             log::trace!("[p010_render] step 1: creating Y plane view (R16Unorm, Plane0)");
         }
 
-        let (y_plane_view, uv_plane_view) = {
+        let (y_plane_view, uv_plane_view) = if let Some(video_frame) = &video_frame {
             let texture = video_frame.get_texture();
             let y = match texture.format() {
                 TextureFormat::P010 => texture.create_view(&wgpu::TextureViewDescriptor {
@@ -1819,6 +1850,11 @@ impl VideoRenderer {
                 video_directx::log_dx12_device_removed_reason(&self.device);
             }
             (y, uv)
+        } else {
+            let Some((y, uv, _)) = self.sw_upload.upload(&self.device, &self.queue, &frame) else {
+                return;
+            };
+            (y, uv)
         };
 
         if is_p010_dbg {
@@ -1895,7 +1931,6 @@ impl VideoRenderer {
         // don't). SDR frames skip detection entirely. All inputs/outputs
         // are GPU-resident — the imported decoder texture is read in place,
         // nothing is staged back to the CPU.
-        let is_hdr = video_frame.get_texture().format() == TextureFormat::P010;
         let detect_bind_group = if is_hdr && frame_w > 0 && frame_h > 0 {
             self.hdr_detect.as_ref().map(|det| {
                 self.device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -1935,14 +1970,14 @@ impl VideoRenderer {
         {
             let vb_arc = self.vertex_buffer.as_ref().expect("no vertex buffer");
             let vertex_buffer = vb_arc.read().await;
-            // P010 imported frames go through the HDR (Rec.2020 + PQ → SDR) pipeline;
+            // P010 frames go through the HDR (Rec.2020 + PQ → SDR) pipeline;
             // NV12 frames stay on the existing SDR pipeline.
-            let render_pipeline = match video_frame.get_texture().format() {
-                TextureFormat::P010 => self
-                    .render_pipeline_hdr
+            let render_pipeline = if is_hdr {
+                self.render_pipeline_hdr
                     .as_ref()
-                    .expect("no HDR render pipeline"),
-                _ => self.render_pipeline.as_ref().expect("no render pipeline"),
+                    .expect("no HDR render pipeline")
+            } else {
+                self.render_pipeline.as_ref().expect("no render pipeline")
             };
 
             // Resolve overlay snapshot BEFORE begin_render_pass so no
@@ -2535,7 +2570,10 @@ impl VideoRenderer {
         // Active subtitle cue for this frame (None = no cue / no track).
         let subtitle = {
             let overlay = self.subtitle_overlay.lock().unwrap().clone();
-            overlay.and_then(|o| o.active_bitmap(window_size.width, window_size.height))
+            let inset = self
+                .subtitle_safe_bottom_px
+                .load(std::sync::atomic::Ordering::Relaxed);
+            overlay.and_then(|o| o.active_bitmap(window_size.width, window_size.height, inset))
         };
 
         // Publish frame data for the present hook to consume.
@@ -2886,6 +2924,29 @@ impl super::VideoSink for VideoRenderer {
         overlay.set_font(bytes)
     }
 
+    fn set_subtitle_fonts(&self, fonts: Vec<Vec<u8>>) -> Result<(), String> {
+        let overlay = self.ensure_subtitle_overlay();
+        overlay.set_fonts(fonts)
+    }
+
+    fn set_subtitle_font_face(
+        &self,
+        face: crate::SubtitleFontFace,
+        bytes: Vec<u8>,
+    ) -> Result<(), String> {
+        let overlay = self.ensure_subtitle_overlay();
+        overlay.set_font_face(face, bytes)
+    }
+
+    fn set_subtitle_family_font(
+        &self,
+        family: crate::SubtitleFontFamily,
+        bytes: Vec<u8>,
+    ) -> Result<(), String> {
+        let overlay = self.ensure_subtitle_overlay();
+        overlay.set_family_font(family, bytes)
+    }
+
     fn set_subtitle_style(&self, style: crate::SubtitleStyle) {
         let overlay = self.ensure_subtitle_overlay();
         overlay.set_style(style);
@@ -2905,9 +2966,40 @@ impl super::VideoSink for VideoRenderer {
         }
     }
 
+    fn queue_secondary_subtitle_cues(&self, cues: Vec<crate::parsers::vtt::VttCue>) {
+        if cues.is_empty() {
+            return;
+        }
+        let overlay = self.ensure_subtitle_overlay();
+        overlay.queue_secondary_cues(cues);
+    }
+
+    fn clear_secondary_subtitles(&self) {
+        if let Some(ov) = self.subtitle_overlay.lock().unwrap().as_ref() {
+            ov.clear_secondary();
+        }
+    }
+
+    fn set_secondary_subtitle_style(
+        &self,
+        style: crate::SubtitleStyle,
+        position: crate::SecondarySubtitlePosition,
+    ) {
+        let overlay = self.ensure_subtitle_overlay();
+        overlay.set_secondary_style(style, position);
+    }
+
+    fn set_subtitle_offset_ms(&self, offset_ms: i64) {
+        self.subtitle_offset_ms
+            .store(offset_ms, std::sync::atomic::Ordering::Relaxed);
+    }
+
     fn set_subtitle_pts(&self, pts_ms: i64) {
         if let Some(ov) = self.subtitle_overlay.lock().unwrap().clone() {
-            ov.set_pts_ms(pts_ms);
+            let offset = self
+                .subtitle_offset_ms
+                .load(std::sync::atomic::Ordering::Relaxed);
+            ov.set_pts_ms(pts_ms - offset);
         }
     }
 
@@ -1287,8 +1287,8 @@ impl GlesOesRenderer {
         Ok(())
     }
 
-    /// Draw the rasterized cue bottom-center with alpha blending. The
-    /// bitmap is uploaded into the persistent GL texture only when its
+    /// Draw the rasterized cues with alpha blending — bottom-center, or at
+    /// the bitmap's `anchor` for positioned cues. The bitmap is uploaded into the persistent GL texture only when its
     /// generation changes (texts persist across many frames).
     unsafe fn draw_subtitle(
         &self,
@@ -1347,9 +1347,14 @@ impl GlesOesRenderer {
         } else {
             0.10
         };
-        let center_y = (-1.0 + 2.0 * safe_frac + half_h).min(1.0 - half_h);
+        let (center_x, center_y) = match bmp.anchor {
+            // Positioned cues (TTML regions, WebVTT settings) were laid out against the
+            // surface already; the anchor is their top-left corner.
+            Some([ax, ay]) => (-1.0 + 2.0 * ax + half_w, 1.0 - 2.0 * ay - half_h),
+            None => (0.0, (-1.0 + 2.0 * safe_frac + half_h).min(1.0 - half_h)),
+        };
         if let Some(ref loc) = sub.rect_loc {
-            gl.uniform_4_f32(Some(loc), 0.0, center_y, half_w, half_h);
+            gl.uniform_4_f32(Some(loc), center_x, center_y, half_w, half_h);
         }
         gl.bind_vertex_array(Some(self.vao));
         gl.draw_arrays(glow::TRIANGLES, 0, 6);
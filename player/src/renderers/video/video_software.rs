@@ -0,0 +1,149 @@
+#![cfg(any(target_os = "windows", target_os = "linux"))]
+
+//! CPU upload for software-decoded FFmpeg frames (decoder software mode —
+//! see `FfmpegHwDecoder::new_software`). The frame arrives in system memory
+//! as NV12 or P010LE; its two planes are copied into a Y texture and a UV
+//! texture that bind exactly like the Plane0 / Plane1 views of an imported
+//! hardware frame, so the SDR and HDR pipelines run unchanged.
+//!
+//! Deliberately avoids the multi-planar NV12/P010 wgpu formats: a GPU-less
+//! host renders through a CPU Vulkan driver (lavapipe / WARP) that usually
+//! lacks them.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use ffmpeg_next::format::Pixel;
+use ffmpeg_next::frame::Video;
+use wgpu::{Device, Extent3d, Queue, Texture, TextureFormat, TextureView};
+
+/// Plane textures reused across frames; rebuilt on size / depth change.
+struct Planes {
+    key: (u32, u32, bool),
+    y: Texture,
+    uv: Texture,
+}
+
+#[derive(Default)]
+pub struct SoftwareFrameUploader {
+    planes: Mutex<Option<Planes>>,
+    /// Set once a frame in another format was dropped, so the log line
+    /// isn't repeated every frame.
+    warned: AtomicBool,
+}
+
+impl SoftwareFrameUploader {
+    /// Upload `frame` and return its (Y, UV) plane views plus whether it is
+    /// 10-bit (selects the HDR pipeline, like a P010 import does). `None`
+    /// for a frame that is neither NV12 nor P010LE — the decoder converts
+    /// to those, so this is a bug upstream; the frame is dropped rather
+    /// than taking the host app down.
+    ///
+    /// P010 planes go up as R16/Rg16 when the device has
+    /// `TEXTURE_FORMAT_16BIT_NORM`; otherwise the high byte of each sample
+    /// is uploaded as R8/Rg8. The normalized values the HDR shader samples
+    /// are the same either way, just quantized to 8 bits.
+    pub fn upload(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        frame: &Video,
+    ) -> Option<(TextureView, TextureView, bool)> {
+        let ten_bit = match frame.format() {
+            Pixel::P010LE => true,
+            Pixel::NV12 => false,
+            other => {
+                if !self.warned.swap(true, Ordering::Relaxed) {
+                    log::warn!(
+                        "[video] software frame in unexpected format {:?}; dropped",
+                        other
+                    );
+                }
+                return None;
+            }
+        };
+        let wide = ten_bit && device.features().contains(wgpu::Features::TEXTURE_FORMAT_16BIT_NORM);
+        let (w, h) = (frame.width(), frame.height());
+        let (uv_w, uv_h) = (w.div_ceil(2), h.div_ceil(2));
+
+        let mut slot = self.planes.lock().unwrap();
+        if slot.as_ref().map(|p| p.key) != Some((w, h, wide)) {
+            let (y_fmt, uv_fmt) = if wide {
+                (TextureFormat::R16Unorm, TextureFormat::Rg16Unorm)
+            } else {
+                (TextureFormat::R8Unorm, TextureFormat::Rg8Unorm)
+            };
+            *slot = Some(Planes {
+                key: (w, h, wide),
+                y: plane_texture(device, "sw_video_y", w, h, y_fmt),
+                uv: plane_texture(device, "sw_video_uv", uv_w, uv_h, uv_fmt),
+            });
+        }
+        let planes = slot.as_ref().unwrap();
+
+        if ten_bit && !wide {
+            let y = high_bytes(frame.data(0), frame.stride(0), w as usize, h as usize);
+            write_plane(queue, &planes.y, &y, w, w, h);
+            let uv = high_bytes(frame.data(1), frame.stride(1), uv_w as usize * 2, uv_h as usize);
+            write_plane(queue, &planes.uv, &uv, uv_w * 2, uv_w, uv_h);
+        } else {
+            write_plane(queue, &planes.y, frame.data(0), frame.stride(0) as u32, w, h);
+            write_plane(queue, &planes.uv, frame.data(1), frame.stride(1) as u32, uv_w, uv_h);
+        }
+
+        Some((
+            planes.y.create_view(&wgpu::TextureViewDescriptor::default()),
+            planes.uv.create_view(&wgpu::TextureViewDescriptor::default()),
+            ten_bit,
+        ))
+    }
+}
+
+fn plane_texture(device: &Device, label: &str, width: u32, height: u32, format: TextureFormat) -> Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
+
+fn write_plane(queue: &Queue, texture: &Texture, data: &[u8], bytes_per_row: u32, width: u32, height: u32) {
+    queue.write_texture(
+        wgpu::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+            aspect: wgpu::TextureAspect::All,
+        },
+        data,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_row),
+            rows_per_image: Some(height),
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// P010LE → 8-bit: keep the high byte of every little-endian u16 sample.
+/// `samples` is per row (UV rows hold two samples per chroma texel).
+fn high_bytes(data: &[u8], stride: usize, samples: usize, rows: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(samples * rows);
+    for row in data.chunks(stride).take(rows) {
+        out.extend(row[..samples * 2].chunks_exact(2).map(|s| s[1]));
+    }
+    out
+}
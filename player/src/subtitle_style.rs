@@ -5,15 +5,23 @@
 //! semantics deliberately mirror ASS so a future libass backend can map
 //! them 1:1 instead of inventing a parallel vocabulary:
 //!
-//!   * `text_color`    → ASS `PrimaryColour`
-//!   * `outline_color` → ASS `OutlineColour` / `BackColour` (today we use
-//!                       one dark colour for the 1px drop-shadow)
-//!   * `size_scale`    → a multiplier on the auto-computed `Fontsize`
+//!   * `text_color`       → ASS `PrimaryColour`
+//!   * `outline_color`    → ASS `OutlineColour` / `BackColour` (one colour
+//!                          for whichever edge `edge_style` draws)
+//!   * `size_scale`       → a multiplier on the auto-computed `Fontsize`
+//!   * `background_color` → ASS `BorderStyle=3` opaque box, per line
+//!   * `window_color`     → the CEA-708 window fill behind the whole cue
+//!   * `edge_style`       → ASS `Outline` / `Shadow`, plus the raised and
+//!                          depressed edges of the FCC caption settings
+//!   * `font_family`      → ASS `Fontname`, as one of the seven generic
+//!                          caption families
 //!
-//! Anything libass adds later — bold, italic, alignment, margins, border
-//! style — slots in here as new fields without breaking call sites: the
-//! struct is `#[non_exhaustive]`-friendly via `DEFAULT` + struct-update
-//! syntax, and every consumer reads fields by name.
+//! The box, edge and family fields are the caption display settings FCC
+//! 47 CFR 79.103 and EN 301 549 require users to be able to change.
+//! Anything libass adds later — bold, italic, alignment, margins — slots
+//! in here as new fields without breaking call sites: the struct is
+//! `#[non_exhaustive]`-friendly via `DEFAULT` + struct-update syntax, and
+//! every consumer reads fields by name.
 
 /// Visual styling for the subtitle overlay. `Copy` so it can be read out
 /// from behind the overlay's mutex without cloning. Construct from
@@ -28,8 +36,9 @@ pub struct SubtitleStyle {
     /// coverage (255 = fully opaque, 0 = invisible). Default: opaque
     /// white.
     pub text_color: [u8; 4],
-    /// Drop-shadow / outline colour, RGBA. Default: opaque black — the
-    /// dark halo that keeps white text readable over bright video.
+    /// Edge colour, RGBA: the drop shadow, outline or the dark side of a
+    /// raised / depressed edge (see `edge_style`). Default: opaque black —
+    /// the dark halo that keeps white text readable over bright video.
     pub outline_color: [u8; 4],
     /// Multiplier applied to the auto-computed font size (which tracks
     /// ~5% of the video height). `1.0` leaves the responsive default
@@ -37,35 +46,69 @@ pub struct SubtitleStyle {
     /// [`sanitised`](Self::sanitised) so a wild value can't blow the cue
     /// bitmap up past texture limits.
     pub size_scale: f32,
+    /// Box behind each line of text, RGBA; the alpha channel is its
+    /// opacity. Default: fully transparent (no box). A run's own
+    /// background (WebVTT `bg_*` classes, TTML `tts:backgroundColor`)
+    /// draws over it.
+    pub background_color: [u8; 4],
+    /// Box behind the whole cue block, RGBA, under the line boxes.
+    /// Default: fully transparent. An authored region / window background
+    /// draws over it.
+    pub window_color: [u8; 4],
+    /// How glyph edges are drawn, in `outline_color`. Default: drop
+    /// shadow.
+    pub edge_style: SubtitleEdgeStyle,
+    /// Generic family the text is set in. Draws with the font installed
+    /// for it (`Player::set_subtitle_family_font`); a family without one
+    /// uses the regular font. Default: the regular font.
+    pub font_family: SubtitleFontFamily,
 }
 
 impl SubtitleStyle {
-    /// The Phase-1 look: opaque white text, black shadow, auto size.
+    /// The Phase-1 look: opaque white text, black shadow, auto size, no
+    /// boxes.
     pub const DEFAULT: Self = Self {
         text_color: [255, 255, 255, 255],
         outline_color: [0, 0, 0, 255],
         size_scale: 1.0,
+        background_color: [0, 0, 0, 0],
+        window_color: [0, 0, 0, 0],
+        edge_style: SubtitleEdgeStyle::DropShadow,
+        font_family: SubtitleFontFamily::Default,
     };
 
     /// Clamp every field into a safe rendering range. Mirrors
     /// `HdrTonemapParams::sanitised` — the public setter runs this so a
     /// host can't push a value that breaks rasterization. Colours need no
-    /// clamping (every `u8` is valid); only `size_scale` is bounded.
+    /// clamping (every `u8` is valid); `size_scale` is bounded, a box
+    /// with zero opacity becomes plain transparent black and an edge in a
+    /// fully transparent colour becomes no edge, so an invisible setting
+    /// costs no draw work and compares equal to the default.
     pub fn sanitised(self) -> Self {
+        let clear = |c: [u8; 4]| if c[3] == 0 { [0; 4] } else { c };
         Self {
             size_scale: if self.size_scale.is_finite() {
                 self.size_scale.clamp(0.5, 3.0)
             } else {
                 1.0
             },
+            background_color: clear(self.background_color),
+            window_color: clear(self.window_color),
+            edge_style: if self.outline_color[3] == 0 {
+                SubtitleEdgeStyle::None
+            } else {
+                self.edge_style
+            },
             ..self
         }
     }
 
     /// Parse a CSS-ish colour string into RGBA, defaulting alpha to 255.
-    /// Accepts `#RGB`, `#RRGGBB`, `#RRGGBBAA` (the `#` is optional) and a
-    /// handful of names common in subtitle settings UIs. Returns `None`
-    /// on anything unrecognised so the caller keeps its existing colour.
+    /// Accepts `#RGB`, `#RRGGBB`, `#RRGGBBAA` (the `#` is optional),
+    /// `rgb(r, g, b)` / `rgba(r, g, b, a)` with 0–255 components (the TTML
+    /// form) and a handful of names common in subtitle settings UIs.
+    /// Returns `None` on anything unrecognised so the caller keeps its
+    /// existing colour.
     ///
     /// Useful at the host/config layer (env var, settings file) to turn a
     /// user's `"yellow"` or `"#FFCC00"` into a `text_color`.
@@ -85,6 +128,21 @@ impl SubtitleStyle {
         if named.is_some() {
             return named;
         }
+        if let Some(args) = s
+            .strip_prefix("rgba(")
+            .or_else(|| s.strip_prefix("rgb("))
+            .and_then(|a| a.strip_suffix(')'))
+        {
+            let v: Vec<u8> = args
+                .split(',')
+                .map(|c| c.trim().parse::<u8>().ok())
+                .collect::<Option<_>>()?;
+            return match v[..] {
+                [r, g, b] => Some([r, g, b, 255]),
+                [r, g, b, a] => Some([r, g, b, a]),
+                _ => None,
+            };
+        }
 
         let hex = s.strip_prefix('#').unwrap_or(s);
         if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
@@ -114,6 +172,125 @@ impl Default for SubtitleStyle {
     }
 }
 
+/// How subtitle glyph edges are drawn (`SubtitleStyle::edge_style`), in
+/// the style's `outline_color`. The choices of the FCC / CEA-708 caption
+/// settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SubtitleEdgeStyle {
+    /// Plain glyphs.
+    None,
+    /// Lit from the top left: a light edge up-left, a dark one down-right.
+    Raised,
+    /// Pressed in: a dark edge up-left, a light one down-right.
+    Depressed,
+    /// An outline all around.
+    Uniform,
+    /// A shadow down-right.
+    #[default]
+    DropShadow,
+}
+
+impl SubtitleEdgeStyle {
+    /// From an Android `CaptionStyleCompat` / `CaptioningManager.CaptionStyle`
+    /// edge type: 0 none, 1 outline, 2 drop shadow, 3 raised, 4 depressed.
+    /// The Android style setters take this numbering; anything else is
+    /// the default drop shadow.
+    pub fn from_edge_type(edge_type: i32) -> Self {
+        match edge_type {
+            0 => Self::None,
+            1 => Self::Uniform,
+            3 => Self::Raised,
+            4 => Self::Depressed,
+            _ => Self::DropShadow,
+        }
+    }
+
+    /// From an iOS `MACaptionAppearanceTextEdgeStyle`: 1 none, 2 raised,
+    /// 3 depressed, 4 uniform, 5 drop shadow. The iOS style setters take
+    /// this numbering; 0 (undefined) and anything else is the default
+    /// drop shadow.
+    pub fn from_text_edge_style(edge_style: i32) -> Self {
+        match edge_style {
+            1 => Self::None,
+            2 => Self::Raised,
+            3 => Self::Depressed,
+            4 => Self::Uniform,
+            _ => Self::DropShadow,
+        }
+    }
+}
+
+/// Generic font family for `SubtitleStyle::font_family`: the seven caption
+/// families of FCC 47 CFR 79.103 / CEA-708, each drawn with the font the
+/// host installs for it (`Player::set_subtitle_family_font`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SubtitleFontFamily {
+    /// The regular subtitle font (`set_subtitle_font` / `set_subtitle_fonts`).
+    #[default]
+    Default,
+    MonospacedSerif,
+    ProportionalSerif,
+    MonospacedSansSerif,
+    ProportionalSansSerif,
+    Casual,
+    Cursive,
+    SmallCapitals,
+}
+
+impl SubtitleFontFamily {
+    /// The installable families, in FCC order.
+    pub const GENERIC: [Self; 7] = [
+        Self::MonospacedSerif,
+        Self::ProportionalSerif,
+        Self::MonospacedSansSerif,
+        Self::ProportionalSansSerif,
+        Self::Casual,
+        Self::Cursive,
+        Self::SmallCapitals,
+    ];
+
+    /// From the platform setters' number: 0 default, then 1–7 in FCC order
+    /// (the order of iOS `MACaptionAppearanceFontStyle` too). Anything
+    /// else is the default.
+    pub fn from_index(index: i32) -> Self {
+        match index {
+            1..=7 => Self::GENERIC[index as usize - 1],
+            _ => Self::Default,
+        }
+    }
+
+    /// Position in [`GENERIC`](Self::GENERIC); `None` for `Default`.
+    pub fn generic_index(self) -> Option<usize> {
+        Self::GENERIC.iter().position(|f| *f == self)
+    }
+}
+
+/// A face of the subtitle font family, for
+/// `Player::set_subtitle_font_face`. Bold / italic cue runs (WebVTT `<b>`,
+/// `<i>`, `::cue` rules; TTML `tts:fontWeight` / `tts:fontStyle`) use the
+/// matching face when one is installed and fall back to a synthetic
+/// emboldening / oblique of the nearest face otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubtitleFontFace {
+    /// The base face — the same slot `set_subtitle_font` fills.
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+}
+
+/// Where the secondary subtitle track (`Player::set_secondary_subtitle_track`)
+/// sits relative to the primary one. Either way its cues ignore their own
+/// positioning, so the two tracks never land on top of each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SecondarySubtitlePosition {
+    /// Top of the title-safe area.
+    #[default]
+    Top,
+    /// Directly above the primary cues (bottom-centre when none is shown).
+    Stacked,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,14 +305,93 @@ mod tests {
             SubtitleStyle::parse_color("#80808080"),
             Some([128, 128, 128, 128])
         );
+        assert_eq!(
+            SubtitleStyle::parse_color("rgb(1, 2, 3)"),
+            Some([1, 2, 3, 255])
+        );
+        assert_eq!(
+            SubtitleStyle::parse_color("rgba(1,2,3,4)"),
+            Some([1, 2, 3, 4])
+        );
+        assert_eq!(SubtitleStyle::parse_color("rgba(1,2,3,0.5)"), None);
         assert_eq!(SubtitleStyle::parse_color("not-a-colour"), None);
         assert_eq!(SubtitleStyle::parse_color("#12"), None);
     }
 
+    #[test]
+    fn sanitise_drops_invisible_boxes_and_edges() {
+        let style = SubtitleStyle {
+            background_color: [10, 20, 30, 0],
+            window_color: [40, 50, 60, 128],
+            outline_color: [0, 0, 0, 0],
+            edge_style: SubtitleEdgeStyle::Uniform,
+            ..Default::default()
+        }
+        .sanitised();
+        assert_eq!(style.background_color, [0, 0, 0, 0]);
+        assert_eq!(style.window_color, [40, 50, 60, 128]);
+        assert_eq!(style.edge_style, SubtitleEdgeStyle::None);
+        let raised = SubtitleStyle {
+            edge_style: SubtitleEdgeStyle::Raised,
+            ..Default::default()
+        };
+        assert_eq!(raised.sanitised(), raised);
+    }
+
+    #[test]
+    fn platform_numbers_map_to_edges_and_families() {
+        assert_eq!(
+            SubtitleEdgeStyle::from_edge_type(1),
+            SubtitleEdgeStyle::Uniform
+        );
+        assert_eq!(
+            SubtitleEdgeStyle::from_edge_type(4),
+            SubtitleEdgeStyle::Depressed
+        );
+        assert_eq!(
+            SubtitleEdgeStyle::from_edge_type(-1),
+            SubtitleEdgeStyle::DropShadow
+        );
+        assert_eq!(
+            SubtitleFontFamily::from_index(0),
+            SubtitleFontFamily::Default
+        );
+        assert_eq!(
+            SubtitleFontFamily::from_index(5),
+            SubtitleFontFamily::Casual
+        );
+        assert_eq!(
+            SubtitleFontFamily::from_index(8),
+            SubtitleFontFamily::Default
+        );
+        assert_eq!(SubtitleFontFamily::Cursive.generic_index(), Some(5));
+        assert_eq!(SubtitleFontFamily::Default.generic_index(), None);
+    }
+
     #[test]
     fn sanitise_clamps_size_only() {
         assert_eq!(SubtitleStyle { size_scale: 9.0, ..Default::default() }.sanitised().size_scale, 3.0);
         assert_eq!(SubtitleStyle { size_scale: 0.1, ..Default::default() }.sanitised().size_scale, 0.5);
         assert_eq!(SubtitleStyle { size_scale: f32::NAN, ..Default::default() }.sanitised().size_scale, 1.0);
     }
+
+    #[test]
+    fn apple_edge_styles_map_by_their_own_numbers() {
+        use SubtitleEdgeStyle as E;
+        // MACaptionAppearanceTextEdgeStyle 0 (undefined) ..= 5.
+        let apple = [
+            E::DropShadow,
+            E::None,
+            E::Raised,
+            E::Depressed,
+            E::Uniform,
+            E::DropShadow,
+        ];
+        for (n, edge) in apple.into_iter().enumerate() {
+            assert_eq!(E::from_text_edge_style(n as i32), edge, "edge style {n}");
+        }
+        assert_eq!(E::from_text_edge_style(6), E::DropShadow);
+        // Apple's 1 is none where Android's 1 is an outline.
+        assert_ne!(E::from_text_edge_style(1), E::from_edge_type(1));
+    }
 }
@@ -0,0 +1,322 @@
+//! Pitch-preserving time stretch for `Player::set_playback_rate`.
+//!
+//! WSOLA (waveform-similarity overlap-add): the output is built from
+//! Hann-windowed input segments overlapped at a fixed synthesis hop, while
+//! the read position advances by `hop × rate`. Each segment's start is
+//! nudged within a small search window to the offset whose waveform best
+//! continues the previous segment, so the overlaps add in phase and the
+//! pitch is unchanged. At 1.0 the stage is a pass-through — bit-exact with
+//! the unstretched pipeline — and entering / leaving a stretch splices
+//! onto the surrounding audio without a fade.
+
+/// Slowest supported playback rate.
+pub const MIN_PLAYBACK_RATE: f32 = 0.5;
+/// Fastest supported playback rate.
+pub const MAX_PLAYBACK_RATE: f32 = 2.0;
+
+/// Clamp a host-supplied rate into the supported range. NaN / infinity
+/// mean normal speed.
+pub(crate) fn clamp_rate(rate: f32) -> f32 {
+    if rate.is_finite() {
+        rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE)
+    } else {
+        1.0
+    }
+}
+
+/// Segment length. 30 ms spans a few pitch periods of speech and music
+/// without smearing transients audibly.
+const SEGMENT_MS: u32 = 30;
+/// How far (each way) a segment may slide from its nominal position to
+/// find the best waveform match.
+const SEEK_MS: u32 = 8;
+/// Stride of the coarse similarity search, in frames; the best coarse hit
+/// is refined at stride 1.
+const COARSE_STEP: usize = 4;
+
+/// Streaming WSOLA over interleaved `f32` frames of a fixed channel count.
+pub(crate) struct TimeStretch {
+    channels: usize,
+    /// Segment length in frames (even).
+    segment: usize,
+    /// Synthesis hop = half a segment, so consecutive Hann windows sum to 1.
+    hop: usize,
+    seek: usize,
+    window: Vec<f32>,
+    /// Interleaved input not yet consumed.
+    input: Vec<f32>,
+    /// Nominal read position in `input`, in frames.
+    pos: f64,
+    /// Start (in `input`) of the natural continuation of the last segment —
+    /// what the next one is matched against. `None` until the first segment.
+    template: Option<usize>,
+    /// Falling half of the last windowed segment, awaiting its overlap.
+    tail: Vec<f32>,
+    /// True while stretching (or draining back to pass-through).
+    active: bool,
+}
+
+impl TimeStretch {
+    pub(crate) fn new(sample_rate: u32, channels: u16) -> Self {
+        let channels = channels.max(1) as usize;
+        let hop = (sample_rate * SEGMENT_MS / 2000).max(8) as usize;
+        let segment = hop * 2;
+        // Periodic Hann: w[i] + w[i + hop] == 1.
+        let window = (0..segment)
+            .map(|i| {
+                0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / segment as f32).cos()
+            })
+            .collect();
+        Self {
+            channels,
+            segment,
+            hop,
+            seek: (sample_rate * SEEK_MS / 1000).max(1) as usize,
+            window,
+            input: Vec::new(),
+            pos: 0.0,
+            template: None,
+            tail: Vec::new(),
+            active: false,
+        }
+    }
+
+    /// True when `process` at `rate` would return its input unchanged, so
+    /// the caller can skip the copy.
+    pub(crate) fn is_bypass(&self, rate: f32) -> bool {
+        !self.active && rate == 1.0
+    }
+
+    /// Feed interleaved samples and collect whatever output is ready at
+    /// `rate`. While stretching, up to a segment plus the search window of
+    /// input is held back; switching to 1.0 flushes it once the last
+    /// overlap can be completed.
+    pub(crate) fn process(&mut self, input: &[f32], rate: f32) -> Vec<f32> {
+        if self.is_bypass(rate) {
+            return input.to_vec();
+        }
+        self.active = true;
+        self.input.extend_from_slice(input);
+        let ch = self.channels;
+        let mut out = Vec::with_capacity((input.len() as f32 / rate) as usize + self.hop * ch);
+        loop {
+            let frames = self.input.len() / ch;
+            let nominal = self.pos.round() as usize;
+            let Some(template) = self.template else {
+                if rate == 1.0 {
+                    // Back to normal speed before the first segment: nothing
+                    // overlaps yet, so the held input goes out as-is.
+                    out.extend_from_slice(&self.input[nominal.min(frames) * ch..]);
+                    self.reset();
+                    break;
+                }
+                if frames < nominal + self.segment {
+                    break;
+                }
+                // The first segment continues the unstretched audio before
+                // it, so its leading half goes out unwindowed instead of
+                // fading in.
+                out.extend_from_slice(&self.input[nominal * ch..(nominal + self.hop) * ch]);
+                self.keep_tail(nominal);
+                self.template = Some(nominal + self.hop);
+                self.advance(rate);
+                continue;
+            };
+            if frames < nominal + self.seek + self.segment {
+                break;
+            }
+            let start = self.best_offset(template, nominal);
+            for i in 0..self.hop {
+                let w = self.window[i];
+                for c in 0..ch {
+                    out.push(self.tail[i * ch + c] + self.input[(start + i) * ch + c] * w);
+                }
+            }
+            if rate == 1.0 {
+                // The rising half above completed the last overlap; from
+                // here the input runs through untouched.
+                out.extend_from_slice(&self.input[(start + self.hop) * ch..]);
+                self.reset();
+                break;
+            }
+            self.keep_tail(start);
+            self.template = Some(start + self.hop);
+            self.advance(rate);
+        }
+        out
+    }
+
+    fn keep_tail(&mut self, start: usize) {
+        let ch = self.channels;
+        self.tail.clear();
+        for i in self.hop..self.segment {
+            let w = self.window[i];
+            let frame = &self.input[(start + i) * ch..(start + i + 1) * ch];
+            self.tail.extend(frame.iter().map(|s| s * w));
+        }
+    }
+
+    /// Step the read position one analysis hop and drop input no future
+    /// segment or template can reach.
+    fn advance(&mut self, rate: f32) {
+        self.pos += self.hop as f64 * f64::from(rate);
+        let reachable = (self.pos.floor() as usize).saturating_sub(self.seek);
+        let drop = reachable.min(self.template.unwrap_or(0));
+        if drop > 0 {
+            self.input.drain(..drop * self.channels);
+            self.pos -= drop as f64;
+            self.template = self.template.map(|t| t - drop);
+        }
+    }
+
+    /// Segment start within `nominal ± seek` whose leading half best
+    /// matches the template (normalised cross-correlation of the channel
+    /// sum). Ties — silence above all — keep the nominal position.
+    fn best_offset(&self, template: usize, nominal: usize) -> usize {
+        let ch = self.channels;
+        let lo = nominal.saturating_sub(self.seek);
+        let hi = nominal + self.seek;
+        let mono = |from: usize, len: usize| -> Vec<f32> {
+            self.input[from * ch..(from + len) * ch]
+                .chunks_exact(ch)
+                .map(|f| f.iter().sum())
+                .collect()
+        };
+        let target = mono(template, self.hop);
+        let candidates = mono(lo, hi - lo + self.hop);
+        let score = |k: usize, step: usize| -> f32 {
+            let (mut corr, mut energy) = (0.0_f32, 0.0_f32);
+            for i in (0..self.hop).step_by(step) {
+                let x = candidates[k - lo + i];
+                corr += x * target[i];
+                energy += x * x;
+            }
+            if energy > 0.0 {
+                corr / energy.sqrt()
+            } else {
+                0.0
+            }
+        };
+        let search = |from: usize, to: usize, step: usize| -> usize {
+            let mut best = (nominal.clamp(from, to), score(nominal.clamp(from, to), step));
+            for k in (from..=to).step_by(step) {
+                let s = score(k, step);
+                if s > best.1 {
+                    best = (k, s);
+                }
+            }
+            best.0
+        };
+        let coarse = search(lo, hi, COARSE_STEP);
+        search(
+            coarse.saturating_sub(COARSE_STEP - 1).max(lo),
+            (coarse + COARSE_STEP - 1).min(hi),
+            1,
+        )
+    }
+
+    fn reset(&mut self) {
+        self.input.clear();
+        self.tail.clear();
+        self.pos = 0.0;
+        self.template = None;
+        self.active = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    fn sine(freq: f32, frames: usize, channels: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let s = (2.0 * std::f32::consts::PI * freq * i as f32 / RATE as f32).sin() * 0.5;
+                std::iter::repeat(s).take(channels)
+            })
+            .collect()
+    }
+
+    /// Feed in 1024-frame blocks, as decoders deliver them.
+    fn run(ts: &mut TimeStretch, input: &[f32], channels: usize, rate: f32) -> Vec<f32> {
+        input
+            .chunks(1024 * channels)
+            .flat_map(|block| ts.process(block, rate))
+            .collect()
+    }
+
+    fn zero_crossing_hz(mono: &[f32]) -> f32 {
+        let crossings = mono
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        crossings as f32 * RATE as f32 / mono.len() as f32
+    }
+
+    #[test]
+    fn unity_rate_is_bit_exact() {
+        let mut ts = TimeStretch::new(RATE, 2);
+        let input = sine(440.0, 4096, 2);
+        assert!(ts.is_bypass(1.0));
+        assert_eq!(run(&mut ts, &input, 2, 1.0), input);
+    }
+
+    #[test]
+    fn output_length_follows_rate() {
+        for rate in [0.5_f32, 0.75, 1.5, 2.0] {
+            let mut ts = TimeStretch::new(RATE, 2);
+            let frames = RATE as usize * 2;
+            let out = run(&mut ts, &sine(440.0, frames, 2), 2, rate);
+            let expected = frames as f32 / rate;
+            let got = (out.len() / 2) as f32;
+            // Up to a segment plus the search window of input is held back.
+            let held = (SEGMENT_MS + SEEK_MS) as f32 / 1000.0 * RATE as f32 / rate;
+            assert!((got - expected).abs() <= held, "rate {rate}: {got} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn pitch_is_preserved() {
+        for rate in [0.5_f32, 1.5, 2.0] {
+            let mut ts = TimeStretch::new(RATE, 1);
+            let out = run(&mut ts, &sine(440.0, RATE as usize * 2, 1), 1, rate);
+            let hz = zero_crossing_hz(&out[out.len() / 4..out.len() * 3 / 4]);
+            assert!((hz - 440.0).abs() < 440.0 * 0.02, "rate {rate}: {hz} Hz");
+        }
+    }
+
+    #[test]
+    fn channels_stay_separate() {
+        let mut ts = TimeStretch::new(RATE, 2);
+        let input: Vec<f32> = sine(440.0, RATE as usize, 1)
+            .into_iter()
+            .flat_map(|s| [s, 0.0])
+            .collect();
+        let out = run(&mut ts, &input, 2, 1.5);
+        assert!(!out.is_empty());
+        assert!(out.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+        assert!(out.iter().step_by(2).any(|&s| s.abs() > 0.1));
+    }
+
+    #[test]
+    fn returns_to_pass_through_at_unity() {
+        let mut ts = TimeStretch::new(RATE, 2);
+        let input = sine(440.0, 8192, 2);
+        run(&mut ts, &input, 2, 1.5);
+        assert!(!ts.is_bypass(1.0));
+        let flushed = run(&mut ts, &input, 2, 1.0);
+        assert!(!flushed.is_empty());
+        assert!(ts.is_bypass(1.0));
+        assert_eq!(ts.process(&input, 1.0), input);
+    }
+
+    #[test]
+    fn clamp_rate_bounds_and_rejects_nan() {
+        assert_eq!(clamp_rate(4.0), MAX_PLAYBACK_RATE);
+        assert_eq!(clamp_rate(0.1), MIN_PLAYBACK_RATE);
+        assert_eq!(clamp_rate(1.25), 1.25);
+        assert_eq!(clamp_rate(f32::NAN), 1.0);
+    }
+}
@@ -4,13 +4,15 @@ pub mod text;
 pub mod video;
 
 use crate::manifest::{
-    find_audio_channel_count, find_descriptor_values, find_switchable_ids,
-    slice_adaptation_set, slice_representation, AdaptationSet, Representation, MPD,
+    find_accessibility_values, find_audio_channel_count, find_descriptor_values,
+    find_switchable_ids, find_trickmode_target, slice_adaptation_set, slice_representation,
+    AdaptationSet, Representation, MPD,
 };
 use crate::net::{HttpClient, RequestKind};
 use crate::parsers::mp4::{parse_sidx, SidxBox};
 use crate::tracks::audio::{AudioAdaptation, AudioRepresentation};
-use crate::tracks::text::{TextAdaptation, TextRepresenation};
+use crate::parsers::vtt::VttCue;
+use crate::tracks::text::{CaptionService, SubtitleFormat, TextAdaptation, TextRepresenation};
 use crate::tracks::video::{VideoAdaptation, VideoRepresenation};
 use crate::utils::time::iso_to_std_duration;
 
@@ -18,6 +20,7 @@ use futures_util::stream::{self, StreamExt, TryStreamExt};
 use iso8601_duration::Duration as IsoDuration;
 use segment::Segment;
 use std::error::Error;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// prepare() network fan-out bounds. Every representation needs one sidx
@@ -55,12 +58,114 @@ impl Tracks {
         let duration = Self::parse_duration(mpd)?;
         let tracks = Self::parse_tracks(base_url, mpd, raw_mpd, http).await?;
 
-        Ok(Tracks {
+        let mut tracks = Tracks {
             duration,
             video: tracks.video,
             audio: tracks.audio,
             text: tracks.text,
-        })
+        };
+        let video_ids = mpd
+            .periods
+            .first()
+            .into_iter()
+            .flat_map(|p| &p.adaptation_sets)
+            .filter(|a| a.content_type == "video")
+            .map(|a| a.id);
+        for (service, lang) in declared_captions(video_ids, raw_mpd) {
+            tracks.add_caption_track(service, &lang);
+        }
+        Ok(tracks)
+    }
+
+    /// First id past every text adaptation / representation id in use.
+    fn next_text_id(&self) -> u32 {
+        self.text
+            .iter()
+            .flat_map(|a| std::iter::once(a.id).chain(a.representations.iter().map(|r| r.id)))
+            .max()
+            .map_or(1, |id| id + 1)
+    }
+
+    /// Register a sidecar subtitle (`Player::add_external_subtitle`) as a
+    /// one-representation text adaptation at the end of `text`, and return
+    /// it. Adaptation and representation ids continue past the highest
+    /// text id in use, so they never collide with a manifest track.
+    pub fn add_external_text(
+        &mut self,
+        url: &str,
+        lang: &str,
+        label: &str,
+        format: SubtitleFormat,
+        cues: Vec<VttCue>,
+    ) -> TextAdaptation {
+        let next_id = self.next_text_id();
+        let adaptation = TextAdaptation {
+            id: next_id,
+            lang: lang.to_string(),
+            roles: vec!["subtitle".to_string()],
+            label: (!label.is_empty()).then(|| label.to_string()),
+            representations: vec![TextRepresenation {
+                id: next_id + 1,
+                codecs: String::new(),
+                mime_type: format.mime_type().to_string(),
+                bandwidth: 0,
+                base_url: String::new(),
+                file_url: url.to_string(),
+                segment_init: None,
+                segment_range: None,
+                segments: Vec::new(),
+                single_file_url: Some(url.to_string()),
+                external_cues: Some(Arc::new(cues)),
+                embedded_captions: None,
+            }],
+        };
+        self.text.push(adaptation.clone());
+        adaptation
+    }
+
+    /// Register closed-caption `service` from the video stream as a
+    /// caption adaptation at the end of `text` (ids as for
+    /// `add_external_text`), and return it. `None` when the service is
+    /// listed already.
+    pub fn add_caption_track(
+        &mut self,
+        service: CaptionService,
+        lang: &str,
+    ) -> Option<TextAdaptation> {
+        if self
+            .text
+            .iter()
+            .any(|a| a.embedded_captions() == Some(service))
+        {
+            return None;
+        }
+        let next_id = self.next_text_id();
+        let mime_type = match service {
+            CaptionService::Cea608(_) => "text/cea-608",
+            CaptionService::Cea708(_) => "text/cea-708",
+        };
+        let adaptation = TextAdaptation {
+            id: next_id,
+            lang: lang.to_string(),
+            roles: vec!["caption".to_string()],
+            label: Some(service.name()),
+            representations: vec![TextRepresenation {
+                id: next_id + 1,
+                codecs: String::new(),
+                mime_type: mime_type.to_string(),
+                bandwidth: 0,
+                base_url: String::new(),
+                file_url: String::new(),
+                segment_init: None,
+                segment_range: None,
+                segments: Vec::new(),
+                single_file_url: None,
+                external_cues: None,
+                embedded_captions: Some(service),
+            }],
+        };
+        self.text.push(adaptation.clone());
+        Some(adaptation)
     }
 
     fn parse_range(range: &str) -> Result<(u64, u64), Box<dyn Error>> {
@@ -111,7 +216,8 @@ impl Tracks {
                 Some(start),
                 Some(end_time),
                 Some(sidx.timescale),
-            )?;
+            )?
+            .with_sap(entry.starts_with_sap != 0);
             segments.push(segment);
 
             start_byte += entry.reference_size;
@@ -485,6 +591,7 @@ impl Tracks {
             //par,
             roles,
             representations: video_representations,
+            trick_representations: Vec::new(),
         };
 
         // SwitchingProperty IDs come from the same adaptation_block we just
@@ -629,6 +736,8 @@ impl Tracks {
                 segment_range,
                 segments,
                 single_file_url,
+                external_cues: None,
+                embedded_captions: None,
             })
         };
             async move { fut.await.map_err(|e| e.to_string()) }
@@ -650,6 +759,7 @@ impl Tracks {
             id: adaptation.id,
             lang,
             roles,
+            label: None,
             representations: text_representations,
         })
     }
@@ -719,7 +829,10 @@ impl Tracks {
         )
         .map_err(|e| -> Box<dyn Error> { e.into() })?;
 
-        let video_adaptations = merge_switchable_adaptations(video_pairs);
+        let video_adaptations = merge_switchable_adaptations(attach_trick_adaptations(
+            video_pairs,
+            raw_mpd,
+        ));
 
         Ok(TracksResult {
             video: video_adaptations,
@@ -729,6 +842,57 @@ impl Tracks {
     }
 }
 
+/// Closed captions the video AdaptationSets `video_ids` declare with SCTE
+/// 214-1 `Accessibility` descriptors (`urn:scte:dash:cc:cea-608:2015` /
+/// `cea-708:2015`), in document order.
+fn declared_captions(
+    video_ids: impl Iterator<Item = u32>,
+    raw_mpd: &str,
+) -> Vec<(CaptionService, String)> {
+    let mut out = Vec::new();
+    for id in video_ids {
+        let Some(block) = slice_adaptation_set(raw_mpd, id) else {
+            continue;
+        };
+        // Only the AdaptationSet's own descriptors, as for trick mode.
+        let head = block.find("<Representation").map_or(block, |i| &block[..i]);
+        for (scheme, cea708) in [("cc:cea-608:2015", false), ("cc:cea-708:2015", true)] {
+            for value in find_accessibility_values(head, scheme) {
+                out.extend(CaptionService::parse_accessibility(&value, cea708));
+            }
+        }
+    }
+    out
+}
+
+/// Fold DASH trick-mode AdaptationSets (ISO/IEC 23009-1 §5.8.5.7 — an
+/// `EssentialProperty` `http://dashif.org/guidelines/trickmode` whose value
+/// is the id of the main set) into the set they serve, as its
+/// `trick_representations`. They must not surface as selectable tracks: a
+/// keyframe-only ladder is unwatchable at 1×. One pointing at no known set
+/// is dropped.
+fn attach_trick_adaptations(
+    pairs: Vec<(VideoAdaptation, Vec<u32>)>,
+    raw_mpd: &str,
+) -> Vec<(VideoAdaptation, Vec<u32>)> {
+    let (trick, mut main): (Vec<_>, Vec<_>) = pairs.into_iter().partition(|(a, _)| {
+        slice_adaptation_set(raw_mpd, a.id)
+            .and_then(find_trickmode_target)
+            .is_some()
+    });
+    for (adaptation, _) in trick {
+        let target = slice_adaptation_set(raw_mpd, adaptation.id).and_then(find_trickmode_target);
+        match main.iter_mut().find(|(a, _)| Some(a.id) == target) {
+            Some((a, _)) => a.trick_representations.extend(adaptation.representations),
+            None => log::warn!(
+                "[tracks] trick-mode adaptation {} targets unknown adaptation {:?} — ignoring",
+                adaptation.id, target
+            ),
+        }
+    }
+    main
+}
+
 /// Merge AdaptationSets that declare themselves switching-equivalent via
 /// `urn:mpeg:dash:adaptation-set-switching:2016` into one logical
 /// `VideoAdaptation` per connected component. Sets with no switching links
@@ -875,11 +1039,13 @@ fn merge_video_adaptation_group(members: Vec<VideoAdaptation>) -> VideoAdaptatio
 
         // ---- representations: append; final sort happens once below ----
         base.representations.extend(sibling.representations);
+        base.trick_representations.extend(sibling.trick_representations);
     }
 
     base.frame_rate = best_fps;
     base.representations
         .sort_by_key(|r| r.bandwidth);
+    base.trick_representations.sort_by_key(|r| r.bandwidth);
 
     base
 }
@@ -928,6 +1094,7 @@ mod tests {
             subsegment_alignment: true,
             roles: vec!["main".to_string()],
             representations: reps,
+            trick_representations: Vec::new(),
         }
     }
 
@@ -1057,4 +1224,118 @@ mod tests {
         // Conservative: one non-aligned member disqualifies the merged pool.
         assert!(!out[0].subsegment_alignment);
     }
+
+    #[test]
+    fn trick_mode_set_attaches_to_its_target_and_is_not_a_track() {
+        let raw = r#"<MPD><Period>
+<AdaptationSet id="1" contentType="video"><Representation id="100"/></AdaptationSet>
+<AdaptationSet id="2" contentType="video">
+<EssentialProperty schemeIdUri="http://dashif.org/guidelines/trickmode" value="1"/>
+<Representation id="900"/></AdaptationSet>
+<AdaptationSet id="3" contentType="video">
+<EssentialProperty schemeIdUri="http://dashif.org/guidelines/trickmode" value="42"/>
+<Representation id="901"/></AdaptationSet>
+</Period></MPD>"#;
+        let main = adaptation(1, 1920, 1080, "24/1", vec![rep(100, 6_000_000, "hvc1", 1920, 1080)]);
+        let trick = adaptation(2, 960, 540, "1/1", vec![rep(900, 400_000, "hvc1", 960, 540)]);
+        let orphan = adaptation(3, 960, 540, "1/1", vec![rep(901, 400_000, "hvc1", 960, 540)]);
+        let pairs = vec![(main, vec![]), (trick, vec![]), (orphan, vec![])];
+
+        let out = merge_switchable_adaptations(attach_trick_adaptations(pairs, raw));
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].id, 1);
+        assert_eq!(out[0].representations.len(), 1);
+        let trick_ids: Vec<u32> = out[0].trick_representations.iter().map(|r| r.id).collect();
+        assert_eq!(trick_ids, vec![900]);
+    }
+
+    #[test]
+    fn external_text_gets_fresh_ids_and_its_cues() {
+        let mut tracks = Tracks {
+            duration: Duration::ZERO,
+            video: Vec::new(),
+            audio: Vec::new(),
+            text: Vec::new(),
+        };
+        let cue = VttCue {
+            start_ms: 0,
+            end_ms: 1000,
+            text: "hi".to_string(),
+            settings: String::new(),
+            spans: Vec::new(),
+            layout: None,
+        };
+        let first = tracks.add_external_text(
+            "https://cdn/a.srt",
+            "cs",
+            "Czech",
+            SubtitleFormat::Srt,
+            vec![cue],
+        );
+        let second =
+            tracks.add_external_text("https://cdn/b.vtt", "", "", SubtitleFormat::WebVtt, vec![]);
+
+        assert_eq!(tracks.text.len(), 2);
+        assert!(first.is_external());
+        assert_eq!(first.label.as_deref(), Some("Czech"));
+        assert_eq!(second.label, None);
+        let (a, b) = (&first.representations[0], &second.representations[0]);
+        assert!(second.id > a.id && b.id > second.id);
+        assert_eq!(a.codec_short(), "SRT");
+        assert!(b.is_webvtt());
+        assert_eq!(a.external_cues.as_ref().map(|c| c.len()), Some(1));
+        assert_eq!(a.single_file_url.as_deref(), Some("https://cdn/a.srt"));
+    }
+
+    #[test]
+    fn declared_captions_become_caption_tracks_once() {
+        let raw = r#"<MPD><Period>
+<AdaptationSet id="1" contentType="video">
+<Accessibility schemeIdUri="urn:scte:dash:cc:cea-608:2015" value="CC1=eng;CC3=spa"/>
+<Accessibility schemeIdUri="urn:scte:dash:cc:cea-708:2015" value="1=lang:eng,war:1;2=lang:deu"/>
+<Representation id="100"/></AdaptationSet>
+</Period></MPD>"#;
+        let declared = declared_captions([1, 7].into_iter(), raw);
+        assert_eq!(
+            declared,
+            vec![
+                (CaptionService::Cea608(1), "eng".to_string()),
+                (CaptionService::Cea608(3), "spa".to_string()),
+                (CaptionService::Cea708(1), "eng".to_string()),
+                (CaptionService::Cea708(2), "deu".to_string()),
+            ]
+        );
+        assert_eq!(
+            CaptionService::parse_accessibility("eng;fra", false),
+            vec![
+                (CaptionService::Cea608(1), "eng".to_string()),
+                (CaptionService::Cea608(2), "fra".to_string()),
+            ]
+        );
+        assert!(CaptionService::parse_accessibility("CC9=eng", false).is_empty());
+
+        let mut tracks = Tracks {
+            duration: Duration::ZERO,
+            video: Vec::new(),
+            audio: Vec::new(),
+            text: Vec::new(),
+        };
+        for (service, lang) in declared {
+            assert!(tracks.add_caption_track(service, &lang).is_some());
+        }
+        // Found again in the stream: already listed.
+        assert!(tracks
+            .add_caption_track(CaptionService::Cea608(3), "")
+            .is_none());
+        let found = tracks
+            .add_caption_track(CaptionService::Cea608(2), "")
+            .expect("new service");
+        assert_eq!(tracks.text.len(), 5);
+        assert!(found.is_caption());
+        assert_eq!(found.label.as_deref(), Some("CC2"));
+        assert_eq!(found.embedded_captions(), Some(CaptionService::Cea608(2)));
+        assert_eq!(tracks.text[2].representations[0].codec_short(), "CEA-708");
+        assert_eq!(tracks.text[3].language(), Some("deu"));
+    }
+
 }
@@ -65,8 +65,10 @@ impl AudioRepresentation {
             "DDP"
         } else if c == "ac-3" {
             "DD"
-        } else if c.starts_with("opus") {
+        } else if c.eq_ignore_ascii_case("opus") {
             "Opus"
+        } else if c.eq_ignore_ascii_case("flac") {
+            "FLAC"
         } else {
             self.codecs.as_str()
         }
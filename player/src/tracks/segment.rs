@@ -10,6 +10,9 @@ pub struct Segment {
     end: u64,
     start_time: Duration,
     end_time: Duration,
+    // sidx `starts_with_SAP`: the subsegment opens on a stream access point,
+    // so its first sample decodes on its own (trick play's keyframes).
+    starts_with_sap: bool,
 }
 
 /// Bandwidth-tracking result from a segment download: payload bytes plus
@@ -50,9 +53,19 @@ impl Segment {
             end,
             start_time,
             end_time,
+            starts_with_sap: false,
         })
     }
 
+    pub fn with_sap(mut self, starts_with_sap: bool) -> Self {
+        self.starts_with_sap = starts_with_sap;
+        self
+    }
+
+    pub fn starts_with_sap(&self) -> bool {
+        self.starts_with_sap
+    }
+
     pub fn start_time(&self) -> Duration {
         self.start_time
     }
@@ -81,4 +94,24 @@ impl Segment {
             elapsed: started.elapsed(),
         })
     }
+
+    /// Fetch `len` bytes from `offset` into the segment (clamped to its end)
+    /// — trick play reads just the `moof` and the keyframe of a subsegment.
+    pub async fn download_span(
+        &self,
+        http: &HttpClient,
+        kind: RequestKind,
+        offset: u64,
+        len: u64,
+    ) -> Result<DownloadResult, Box<dyn Error + Send + Sync>> {
+        let url = format!("{}{}", &self.base_url, &self.file_url);
+        let start = self.start + offset;
+        let end = (start + len.max(1) - 1).min(self.end);
+        let started = std::time::Instant::now();
+        let bytes = http.get_range(url, kind, start, end).await?;
+        Ok(DownloadResult {
+            data: bytes.to_vec(),
+            elapsed: started.elapsed(),
+        })
+    }
 }
@@ -4,10 +4,120 @@
 //! Phase 1 of PLAYER_INTEGRATION.md §6.3: enumerate text adaptations so
 //! the consumer can list them, and (when activated via
 //! `Player::set_subtitle_track`) drive a download / parse / render
-//! pipeline that mirrors the audio one. Only WebVTT in ISO BMFF (and
-//! raw WebVTT) is decoded today; TTML is enumerated but won't render.
+//! pipeline that mirrors the audio one. WebVTT and TTML / IMSC1 text are
+//! decoded, each in ISO BMFF (`wvtt` / `stpp`) or as a single raw file;
+//! anything else is enumerated but won't render. Sidecar files added with
+//! `Player::add_external_subtitle` (SubRip, WebVTT, TTML) join the list as
+//! one-representation adaptations carrying their parsed cues. CEA-608 /
+//! CEA-708 closed captions inside the video stream surface as caption
+//! adaptations too (`TextRepresenation::embedded_captions`), added when the
+//! MPD declares them or the video decoder first finds them.
+
+use std::sync::Arc;
 
 use super::segment::Segment;
+use crate::parsers::vtt::VttCue;
+
+/// Format of a sidecar subtitle file (`Player::add_external_subtitle`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    /// SubRip `.srt`.
+    Srt,
+    /// Raw WebVTT `.vtt`.
+    WebVtt,
+    /// TTML / IMSC1 / DFXP document.
+    Ttml,
+}
+
+impl SubtitleFormat {
+    /// From the platform bridges' number: 0 SubRip, 1 WebVTT, 2 TTML.
+    pub fn from_index(index: i32) -> Option<Self> {
+        match index {
+            0 => Some(Self::Srt),
+            1 => Some(Self::WebVtt),
+            2 => Some(Self::Ttml),
+            _ => None,
+        }
+    }
+
+    /// The MIME type an external representation of this format reports,
+    /// which `TextRepresenation::codec_short` and friends key off.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "application/x-subrip",
+            SubtitleFormat::WebVtt => "text/vtt",
+            SubtitleFormat::Ttml => "application/ttml+xml",
+        }
+    }
+
+    /// Parse a whole file of this format.
+    pub fn parse(&self, data: &[u8]) -> Vec<VttCue> {
+        match self {
+            SubtitleFormat::Srt => crate::parsers::srt::parse_document(data),
+            SubtitleFormat::WebVtt => crate::parsers::vtt::parse_segment(data, 0),
+            SubtitleFormat::Ttml => crate::parsers::ttml::parse_segment(data, 0),
+        }
+    }
+}
+
+/// A closed-caption service carried in the video stream's SEI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CaptionService {
+    /// CEA-608 channel 1..=4 (CC1–CC4).
+    Cea608(u8),
+    /// CEA-708 service 1..=63.
+    Cea708(u8),
+}
+
+impl CaptionService {
+    /// Conventional name for a track picker: `"CC1"`, `"SERVICE2"`.
+    pub fn name(&self) -> String {
+        match self {
+            CaptionService::Cea608(n) => format!("CC{}", n),
+            CaptionService::Cea708(n) => format!("SERVICE{}", n),
+        }
+    }
+
+    /// The services and languages of a DASH `Accessibility` value under
+    /// `urn:scte:dash:cc:cea-608:2015` (`"CC1=eng;CC3=spa"`) or, when
+    /// `cea708`, `urn:scte:dash:cc:cea-708:2015`
+    /// (`"1=lang:eng;2=lang:spa"`). A bare `"eng;spa"` numbers the
+    /// services in order; malformed entries are skipped.
+    pub fn parse_accessibility(value: &str, cea708: bool) -> Vec<(CaptionService, String)> {
+        let mut out = Vec::new();
+        for (i, entry) in value.split(';').map(str::trim).enumerate() {
+            if entry.is_empty() {
+                continue;
+            }
+            let (id, lang) = match entry.split_once('=') {
+                Some((id, rest)) => {
+                    // 708 values may carry more `,`-separated keys
+                    // ("lang:eng,war:1").
+                    let lang = rest
+                        .split(',')
+                        .find_map(|kv| kv.strip_prefix("lang:"))
+                        .unwrap_or(if cea708 { "" } else { rest });
+                    (id.trim(), lang.trim())
+                }
+                None => ("", entry),
+            };
+            let number = if id.is_empty() {
+                Some(i as u8 + 1)
+            } else if cea708 {
+                id.parse::<u8>().ok()
+            } else {
+                id.strip_prefix("CC").and_then(|n| n.parse::<u8>().ok())
+            };
+            let service = match number {
+                Some(n @ 1..=4) if !cea708 => CaptionService::Cea608(n),
+                Some(n @ 1..=63) if cea708 => CaptionService::Cea708(n),
+                _ => continue,
+            };
+            out.push((service, lang.to_string()));
+        }
+        out
+    }
+}
 
 #[derive(Clone)]
 pub struct TextAdaptation {
@@ -18,6 +128,10 @@ pub struct TextAdaptation {
     /// DASH `<Role value="..."/>`s (e.g. `"subtitle"`, `"caption"`,
     /// `"forced-subtitle"`). Most adaptation sets carry zero or one.
     pub roles: Vec<String>,
+    /// Display name for a track picker. Set for external subtitles (the
+    /// host's label) and closed captions (`"CC1"`); `None` for manifest
+    /// tracks.
+    pub label: Option<String>,
     pub representations: Vec<TextRepresenation>,
 }
 
@@ -50,6 +164,22 @@ impl TextAdaptation {
     pub fn is_caption(&self) -> bool {
         self.roles.iter().any(|r| r == "caption" || r == "captions")
     }
+
+    /// True for a sidecar subtitle added with
+    /// `Player::add_external_subtitle` rather than declared in the MPD.
+    pub fn is_external(&self) -> bool {
+        self.representations
+            .iter()
+            .any(|r| r.external_cues.is_some())
+    }
+
+    /// The closed-caption service this track carries in the video stream;
+    /// `None` for downloaded subtitles.
+    pub fn embedded_captions(&self) -> Option<CaptionService> {
+        self.representations
+            .iter()
+            .find_map(|r| r.embedded_captions)
+    }
 }
 
 #[derive(Clone)]
@@ -78,15 +208,33 @@ pub struct TextRepresenation {
     /// indexRange). Most "rip with external subs" workflows ship this
     /// way: one .vtt per language, unencrypted, downloaded once at
     /// activation time. text_play fetches the full URL and parses the
-    /// payload as raw WebVTT.
+    /// payload as a raw WebVTT or TTML document.
     pub single_file_url: Option<String>,
+
+    /// External (sidecar) delivery — the cues of a file added with
+    /// `Player::add_external_subtitle`, fetched and parsed once when it
+    /// was added. text_play queues them as-is; `single_file_url` keeps
+    /// the source URL for display.
+    pub external_cues: Option<Arc<Vec<VttCue>>>,
+
+    /// Closed captions decoded from the video stream's SEI rather than
+    /// downloaded: text_play relays what the video decoder extracts for
+    /// this service. No URLs or segments.
+    pub embedded_captions: Option<CaptionService>,
 }
 
 impl TextRepresenation {
     pub fn codec_short(&self) -> &str {
         let c = self.codecs.as_str();
-        if c.starts_with("wvtt") || self.mime_type == "text/vtt" {
+        if let Some(service) = self.embedded_captions {
+            match service {
+                CaptionService::Cea608(_) => "CEA-608",
+                CaptionService::Cea708(_) => "CEA-708",
+            }
+        } else if c.starts_with("wvtt") || self.mime_type == "text/vtt" {
             "WebVTT"
+        } else if self.mime_type == SubtitleFormat::Srt.mime_type() {
+            "SRT"
         } else if c.starts_with("stpp") || c.starts_with("ttml") || self.mime_type.contains("ttml") {
             "TTML"
         } else if c.is_empty() {
@@ -96,14 +244,20 @@ impl TextRepresenation {
         }
     }
 
-    /// True when the representation looks decodable today (i.e. WebVTT
-    /// in ISO BMFF or raw). TTML and sidecar formats return false; the
-    /// subtitle pipeline silently no-ops on those.
+    /// True for WebVTT, in ISO BMFF (`wvtt`) or raw.
     pub fn is_webvtt(&self) -> bool {
         let c = self.codecs.as_str();
         c.starts_with("wvtt") || self.mime_type == "text/vtt" || self.mime_type == "application/x-mpegurl"
     }
 
+    /// True for TTML / IMSC1, in ISO BMFF (`stpp`) or as a sidecar
+    /// `.ttml` / `.dfxp` document. Only the Text Profile renders; Image
+    /// Profile samples parse to no cues.
+    pub fn is_ttml(&self) -> bool {
+        let c = self.codecs.as_str();
+        c.starts_with("stpp") || c.starts_with("ttml") || self.mime_type.contains("ttml")
+    }
+
     /// Single-line summary for a track picker, e.g. `"WebVTT · 12 kbps"`.
     pub fn label(&self) -> String {
         let kbps = (self.bandwidth as f64 / 1000.0).round() as u64;
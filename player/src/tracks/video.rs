@@ -17,6 +17,12 @@ pub struct VideoAdaptation {
     pub roles: Vec<String>,
 
     pub representations: Vec<VideoRepresenation>,
+
+    /// Keyframe-only representations from DASH trick-mode AdaptationSets
+    /// (`EssentialProperty` `http://dashif.org/guidelines/trickmode`) that
+    /// point at this one. Not selectable tracks — trick play fetches from
+    /// them when present.
+    pub trick_representations: Vec<VideoRepresenation>,
 }
 
 impl VideoAdaptation {
@@ -84,10 +90,20 @@ impl VideoRepresenation {
     }
 
     /// Returns `true` if the codec string indicates a 10-bit profile
-    /// (HEVC Main10 etc.). Useful for the label suffix.
+    /// (HEVC Main10, H.264 High 10 etc.). Useful for the label suffix.
     pub fn is_10bit(&self) -> bool {
         let c = self.codecs.as_str();
-        c.starts_with("hvc1.2") || c.starts_with("hev1.2") || self.dolby_vision
+        // avc1.PPCCLL — profile_idc 0x6E (110) is High 10.
+        let avc_high10 = (c.starts_with("avc1.") || c.starts_with("avc3."))
+            && c.get(5..7).is_some_and(|p| p.eq_ignore_ascii_case("6e"));
+        // av01.P.LLT.DD — DD is the bit depth.
+        let av1_high = c.starts_with("av01.")
+            && c.split('.').nth(3).is_some_and(|d| d == "10" || d == "12");
+        c.starts_with("hvc1.2")
+            || c.starts_with("hev1.2")
+            || avc_high10
+            || av1_high
+            || self.dolby_vision
     }
 
     /// Dolby Vision profile from the codec string (`dvh1.08.06` → 8).
@@ -211,6 +227,12 @@ mod tests {
 
     // ---------------- is_10bit ----------------
 
+    #[test]
+    fn is_10bit_reads_av1_bit_depth_field() {
+        assert!(rep("av01.0.13M.10.0.110.09.16.09.0", 0, 0, false, false).is_10bit());
+        assert!(!rep("av01.0.05M.08", 0, 0, false, false).is_10bit());
+    }
+
     #[test]
     fn is_10bit_true_for_hevc_main10_profile() {
         // hvc1.2 / hev1.2 == Main10 profile = 10-bit. The 4K HDR rep from
@@ -236,6 +258,13 @@ mod tests {
     #[test]
     fn is_10bit_false_for_h264() {
         assert!(!rep("avc1.64001f", 0, 0, false, false).is_10bit());
+        assert!(!rep("avc1.4d401e", 0, 0, false, false).is_10bit());
+    }
+
+    #[test]
+    fn is_10bit_true_for_h264_high10() {
+        assert!(rep("avc1.6e0028", 0, 0, false, false).is_10bit());
+        assert!(rep("avc3.6E0028", 0, 0, false, false).is_10bit());
     }
 
     // ---------------- is_hdr10 / is_dolby_vision ----------------
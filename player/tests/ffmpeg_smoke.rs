@@ -30,11 +30,12 @@ fn ffmpeg_version_is_in_7_x_series() {
 
 #[test]
 fn required_decoders_present() {
-    // player/src/decoders/ffmpeg_audio.rs maps AudioCodec → AAC/AC3/EAC3
+    // player/src/decoders/ffmpeg_audio.rs maps AudioCodec → AAC/AC3/EAC3/
+    //                                     Opus/FLAC
     // player/src/decoders/ffmpeg_hw.rs   maps VideoCodec → H264/HEVC
     // The software fallback path also needs the video decoders, so we
-    // assert all five reachable by name.
-    for name in ["h264", "hevc", "aac", "ac3", "eac3"] {
+    // assert all of them reachable by name.
+    for name in ["h264", "hevc", "aac", "ac3", "eac3", "opus", "flac"] {
         let cname = CString::new(name).unwrap();
         let codec = unsafe { sys::avcodec_find_decoder_by_name(cname.as_ptr()) };
         assert!(
@@ -45,6 +46,23 @@ fn required_decoders_present() {
     }
 }
 
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+#[test]
+fn av1_vp9_decoders_present() {
+    // ffmpeg_hw.rs opens native `av1` when a D3D11VA/VAAPI AV1 hwaccel is
+    // built in, libdav1d otherwise (GPUs without an AV1 decode block).
+    // VP9 is the same native-decoder-plus-hwaccel shape without a fallback.
+    for name in ["av1", "libdav1d", "vp9"] {
+        let cname = CString::new(name).unwrap();
+        let codec = unsafe { sys::avcodec_find_decoder_by_name(cname.as_ptr()) };
+        assert!(
+            !codec.is_null(),
+            "{name} decoder missing — check DECODERS / --enable-libdav1d \
+             in scripts/build-ffmpeg.sh"
+        );
+    }
+}
+
 #[test]
 fn required_parsers_present() {
     // build-ffmpeg.sh enables h264, hevc, aac, ac3 parsers. av_parser_init
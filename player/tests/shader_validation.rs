@@ -31,3 +31,11 @@ fn hdr_detect_shader_validates() {
         include_str!("../src/renderers/shader_hdr_detect.wgsl"),
     );
 }
+
+#[test]
+fn subtitle_shader_validates() {
+    validate(
+        "shader_subtitle.wgsl",
+        include_str!("../src/renderers/shader_subtitle.wgsl"),
+    );
+}